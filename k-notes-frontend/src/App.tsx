@@ -6,6 +6,7 @@ import RegisterPage from "@/pages/register";
 import DashboardPage from "@/pages/dashboard";
 import PrivacyPolicyPage from "@/pages/privacy-policy";
 import OidcCallbackPage from "@/pages/oidc-callback";
+import ConfirmEmailPage from "@/pages/confirm-email";
 import Layout from "@/components/layout";
 import { useSync } from "@/lib/sync";
 import { useMobileStatusBar } from "@/hooks/use-mobile-status-bar";
@@ -32,6 +33,7 @@ function App() {
           <Route path="/" element={<DashboardPage />} />
           <Route path="/archive" element={<DashboardPage />} />
           <Route path="/settings" element={<SettingsPage />} />
+          <Route path="/confirm-email" element={<ConfirmEmailPage />} />
         </Route>
       </Route>
 
@@ -0,0 +1,65 @@
+import { useState } from "react";
+import { Globe } from "lucide-react";
+import { type LinkPreview } from "@/hooks/use-notes";
+
+interface BookmarkCardsProps {
+    previews: LinkPreview[];
+}
+
+function hostOf(url: string) {
+    try {
+        return new URL(url).host;
+    } catch {
+        return url;
+    }
+}
+
+function Favicon({ src }: { src?: string | null }) {
+    const [failed, setFailed] = useState(false);
+
+    if (!src || failed) {
+        return <Globe className="h-4 w-4 shrink-0 text-muted-foreground" />;
+    }
+    return (
+        <img
+            src={src}
+            alt=""
+            className="h-4 w-4 shrink-0 rounded-sm"
+            referrerPolicy="no-referrer"
+            onError={() => setFailed(true)}
+        />
+    );
+}
+
+export function BookmarkCards({ previews }: BookmarkCardsProps) {
+    if (previews.length === 0) {
+        return null;
+    }
+
+    return (
+        <div className="space-y-2 pb-4">
+            {previews.map((preview) => (
+                <a
+                    key={preview.url}
+                    href={preview.url}
+                    target="_blank"
+                    rel="noopener noreferrer"
+                    className="block rounded-lg border border-black/10 dark:border-white/10 bg-background/60 p-3 hover:bg-background/90 transition-colors"
+                >
+                    <div className="flex items-center gap-2 text-xs text-muted-foreground">
+                        <Favicon src={preview.favicon_url} />
+                        <span className="truncate">{hostOf(preview.url)}</span>
+                    </div>
+                    <div className="mt-1 font-medium leading-snug line-clamp-2">
+                        {preview.title || preview.url}
+                    </div>
+                    {preview.description && (
+                        <p className="mt-1 text-sm text-muted-foreground line-clamp-2">
+                            {preview.description}
+                        </p>
+                    )}
+                </a>
+            ))}
+        </div>
+    );
+}
@@ -0,0 +1,109 @@
+import { useEffect, useRef, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { api } from "@/lib/api";
+import { solveProofOfWork } from "@/lib/challenge";
+import type { ChallengeConfig } from "@/hooks/useConfig";
+
+interface CaptchaApi {
+    render: (container: HTMLElement, options: { sitekey: string; callback: (token: string) => void }) => unknown;
+}
+
+declare global {
+    interface Window {
+        hcaptcha?: CaptchaApi;
+        turnstile?: CaptchaApi;
+    }
+}
+
+const CAPTCHA_SCRIPTS: Record<string, string> = {
+    hcaptcha: "https://js.hcaptcha.com/1/api.js?render=explicit",
+    turnstile: "https://challenges.cloudflare.com/turnstile/v0/api.js?render=explicit",
+};
+
+function loadScript(src: string): Promise<void> {
+    const existing = document.querySelector<HTMLScriptElement>(`script[src="${src}"]`);
+    if (existing?.dataset.loaded) {
+        return Promise.resolve();
+    }
+    return new Promise((resolve, reject) => {
+        const script = existing ?? document.createElement("script");
+        script.addEventListener("load", () => {
+            script.dataset.loaded = "true";
+            resolve();
+        });
+        script.addEventListener("error", () => reject(new Error(`Failed to load ${src}`)));
+        if (!existing) {
+            script.src = src;
+            script.async = true;
+            document.head.appendChild(script);
+        }
+    });
+}
+
+interface ChallengeWidgetProps {
+    config: ChallengeConfig;
+    /** Called with the response to submit once the challenge is solved */
+    onSolved: (response: string) => void;
+}
+
+/**
+ * Bot check shown on the register form.
+ *
+ * Renders the hCaptcha or Turnstile widget, or solves a proof-of-work
+ * challenge in the background. Remount it (e.g. with a new `key`) to get a
+ * fresh response after one has been used.
+ */
+export function ChallengeWidget({ config, onSolved }: ChallengeWidgetProps) {
+    const { t } = useTranslation();
+    const container = useRef<HTMLDivElement>(null);
+    const onSolvedRef = useRef(onSolved);
+    onSolvedRef.current = onSolved;
+    const [failed, setFailed] = useState(false);
+    const [solving, setSolving] = useState(config.provider === "pow");
+
+    useEffect(() => {
+        let cancelled = false;
+
+        const run = async () => {
+            if (config.provider === "pow") {
+                const { challenge, difficulty } = await api.get("/auth/challenge");
+                const response = await solveProofOfWork(challenge, difficulty);
+                if (!cancelled) {
+                    setSolving(false);
+                    onSolvedRef.current(response);
+                }
+                return;
+            }
+
+            await loadScript(CAPTCHA_SCRIPTS[config.provider]);
+            const captcha = config.provider === "hcaptcha" ? window.hcaptcha : window.turnstile;
+            if (!cancelled && captcha && container.current && config.site_key) {
+                captcha.render(container.current, {
+                    sitekey: config.site_key,
+                    callback: (token) => onSolvedRef.current(token),
+                });
+            }
+        };
+
+        run().catch(() => {
+            if (!cancelled) {
+                setSolving(false);
+                setFailed(true);
+            }
+        });
+
+        return () => {
+            cancelled = true;
+        };
+    }, [config.provider, config.site_key]);
+
+    if (failed) {
+        return <p className="text-sm text-destructive">{t("Could not verify you are human")}</p>;
+    }
+    if (config.provider === "pow") {
+        return solving ? (
+            <p className="text-sm text-gray-500 dark:text-gray-400">{t("Verifying you are human...")}</p>
+        ) : null;
+    }
+    return <div ref={container} />;
+}
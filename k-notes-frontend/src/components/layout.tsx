@@ -7,10 +7,14 @@ import { useLogout, useUser } from "@/hooks/use-auth";
 import { ModeToggle } from "@/components/mode-toggle";
 import { BulkSelectionProvider } from "@/components/bulk-selection-context";
 import { BulkActionsBar } from "@/components/bulk-actions-bar";
+import { useConfig } from "@/hooks/useConfig";
+import { useTranslation } from "react-i18next";
 
 export default function Layout() {
   const { mutate: logout } = useLogout();
   const { data: user } = useUser();
+  const { data: config } = useConfig();
+  const { t } = useTranslation();
 
   return (
     <BulkSelectionProvider>
@@ -34,6 +38,11 @@ export default function Layout() {
               </Button>
             </div>
           </header>
+          {config?.read_only && (
+            <div className="border-b bg-amber-100 text-amber-900 dark:bg-amber-900/30 dark:text-amber-200 px-4 py-2 text-sm text-center">
+              {t("The instance is in read-only maintenance mode. You can browse your notes, but changes are temporarily disabled.")}
+            </div>
+          )}
           <div className="flex-1 p-4 lg:p-6 bg-muted/10">
             <Outlet />
           </div>
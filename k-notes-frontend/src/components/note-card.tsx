@@ -1,8 +1,8 @@
-import { type Note, useDeleteNote, useUpdateNote } from "@/hooks/use-notes";
+import { type Note, useDeleteNote, useDuplicateNote, useUpdateNote } from "@/hooks/use-notes";
 import { Card, CardContent, CardDescription, CardFooter, CardHeader, CardTitle } from "@/components/ui/card";
 import { Badge } from "@/components/ui/badge";
 import { Button } from "@/components/ui/button";
-import { Pin, Archive, Trash2, Edit, History, Copy } from "lucide-react";
+import { Pin, Archive, Trash2, Edit, History, Copy, CopyPlus } from "lucide-react";
 import { format } from "date-fns";
 import { toast } from "sonner";
 import { useState } from "react";
@@ -25,6 +25,7 @@ interface NoteCardProps {
 export function NoteCard({ note }: NoteCardProps) {
   const { mutate: deleteNote } = useDeleteNote();
   const { mutate: updateNote } = useUpdateNote();
+  const { mutate: duplicateNote } = useDuplicateNote();
   const [editing, setEditing] = useState(false);
   const [historyOpen, setHistoryOpen] = useState(false);
   const [viewOpen, setViewOpen] = useState(false);
@@ -75,6 +76,14 @@ export function NoteCard({ note }: NoteCardProps) {
     }
   }
 
+  const handleDuplicate = (e: React.MouseEvent) => {
+    e.stopPropagation();
+    duplicateNote(note.id, {
+      onSuccess: () => toast.success(t("Note duplicated")),
+      onError: () => toast.error(t("Failed to duplicate note")),
+    });
+  }
+
   const handleEdit = (data: any) => {
     const tags = data.tags
       ? data.tags.split(",").map((t: string) => t.trim()).filter(Boolean)
@@ -148,6 +157,9 @@ export function NoteCard({ note }: NoteCardProps) {
             <Button variant="ghost" size="icon" className="h-8 w-8 hover:bg-black/5 dark:hover:bg-white/10" onClick={handleCopy} title={t("Copy note")}>
               <Copy className="h-4 w-4" />
             </Button>
+            <Button variant="ghost" size="icon" className="h-8 w-8 hover:bg-black/5 dark:hover:bg-white/10" onClick={handleDuplicate} title={t("Duplicate note")}>
+              <CopyPlus className="h-4 w-4" />
+            </Button>
             <Button variant="ghost" size="icon" className="h-8 w-8 hover:bg-black/5 dark:hover:bg-white/10" onClick={(e) => { e.stopPropagation(); setEditing(true); }}>
               <Edit className="h-4 w-4" />
             </Button>
@@ -9,6 +9,7 @@ import { getNoteColor } from "@/lib/constants";
 import clsx from "clsx";
 import remarkGfm from "remark-gfm";
 import { RelatedNotes } from "./related-notes";
+import { BookmarkCards } from "./bookmark-cards";
 
 
 interface NoteViewDialogProps {
@@ -42,6 +43,8 @@ export function NoteViewDialog({ note, open, onOpenChange, onEdit, onSelectNote
                         <ReactMarkdown remarkPlugins={[remarkGfm]}>{note.content}</ReactMarkdown>
                     </div>
 
+                    <BookmarkCards previews={note.link_previews ?? []} />
+
                     {/* Smart Features: Related Notes */}
                     <div className="pb-4">
                         <RelatedNotes
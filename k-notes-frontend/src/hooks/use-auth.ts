@@ -67,7 +67,7 @@ export function useRegister() {
     const navigate = useNavigate();
 
     return useMutation({
-        mutationFn: (credentials: { email: string; password: string }): Promise<LoginResult> =>
+        mutationFn: (credentials: { email: string; password: string; invite_code?: string; challenge_response?: string }): Promise<LoginResult> =>
             api.post("/auth/register", credentials),
         onSuccess: (result: LoginResult) => {
             // If we got a token response, store the token
@@ -1,5 +1,5 @@
 import { useRef } from "react";
-import { api } from "@/lib/api";
+import { api, waitForJob } from "@/lib/api";
 import { toast } from "sonner";
 import { useTranslation } from "react-i18next";
 
@@ -31,7 +31,12 @@ export function useDataManagement() {
         try {
             const text = await file.text();
             const data = JSON.parse(text);
-            await api.importData(data);
+            const job = await waitForJob((await api.importData(data)).id);
+            if (job.status === "failed") {
+                console.error(job.error);
+                toast.error(t("Import failed"));
+                return;
+            }
             toast.success(t("Import successful. Reloading..."));
             setTimeout(() => window.location.reload(), 1000);
         } catch (e) {
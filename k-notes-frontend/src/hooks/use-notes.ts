@@ -8,25 +8,56 @@ export interface Note {
     title: string;
     content: string;
     is_pinned: boolean;
+    pin_order?: number | null;
     is_archived: boolean;
+    is_locked?: boolean;
     color: string;
     tags: Tag[];
     created_at: string;
     updated_at: string;
+    deleted_at?: string | null;
+    link_previews?: LinkPreview[];
+    latitude?: number | null;
+    longitude?: number | null;
+    place_name?: string | null;
+    remind_at?: string | null;
+}
+
+export interface LinkPreview {
+    url: string;
+    title?: string | null;
+    description?: string | null;
+    favicon_url?: string | null;
+    fetched_at: string;
 }
 
 export interface Tag {
     id: string;
     name: string;
+    is_pinned: boolean;
+    sort_order: number | null;
     created_at?: string;
 }
 
+export interface NoteList {
+    items: Note[];
+    // Counts of notes outside the trash, whatever the filter
+    total: number;
+    pinned_count: number;
+    archived_count: number;
+    filter: { pinned: boolean | null; archived: "true" | "false" | "all"; tag: string | null; trashed: boolean };
+}
+
 export interface CreateNoteInput {
     title: string;
     content: string;
     tags?: string[];
     color?: string;
     is_pinned?: boolean;
+    latitude?: number;
+    longitude?: number;
+    place_name?: string;
+    remind_at?: string;
 }
 
 export interface UpdateNoteInput {
@@ -37,9 +68,13 @@ export interface UpdateNoteInput {
     color?: string;
     is_pinned?: boolean;
     is_archived?: boolean;
+    latitude?: number | null;
+    longitude?: number | null;
+    place_name?: string;
+    remind_at?: string | null;
 }
 
-export function useNotes(params?: { pinned?: boolean; archived?: boolean; tag?: string }) {
+export function useNotes(params?: { pinned?: boolean; archived?: boolean | "all"; tag?: string }) {
     // Construct query string
     const searchParams = new URLSearchParams();
     if (params?.pinned !== undefined) searchParams.set("pinned", String(params.pinned));
@@ -48,14 +83,15 @@ export function useNotes(params?: { pinned?: boolean; archived?: boolean; tag?:
 
     return useQuery({
         queryKey: ["notes", params],
-        queryFn: () => api.get(`/notes?${searchParams.toString()}`),
+        queryFn: async () => ((await api.get(`/notes?${searchParams.toString()}`)) as NoteList).items,
     });
 }
 
-export function useSearchNotes(query: string) {
+export function useSearchNotes(query: string, includeArchived = false) {
     return useQuery({
-        queryKey: ["notes", "search", query],
-        queryFn: () => api.get(`/search?q=${encodeURIComponent(query)}`),
+        queryKey: ["notes", "search", query, includeArchived],
+        queryFn: () =>
+            api.get(`/search?q=${encodeURIComponent(query)}&include_archived=${includeArchived}`),
         enabled: query.length > 0,
     });
 }
@@ -177,6 +213,17 @@ export function useUpdateNote() {
     });
 }
 
+export function useDuplicateNote() {
+    const queryClient = useQueryClient();
+
+    return useMutation({
+        mutationFn: (id: string) => api.post(`/notes/${id}/duplicate`, {}),
+        onSuccess: () => {
+            queryClient.invalidateQueries({ queryKey: ["notes"] });
+        },
+    });
+}
+
 export function useDeleteNote() {
     const queryClient = useQueryClient();
 
@@ -4,11 +4,33 @@ import { api } from "@/lib/api";
 
 export type AuthMode = 'session' | 'jwt' | 'both';
 
+export type RegistrationMode = 'open' | 'invite';
+
+export interface OidcProvider {
+    id: string;
+    issuer: string;
+    login_url: string;
+}
+
+export interface ChallengeConfig {
+    provider: 'hcaptcha' | 'turnstile' | 'pow';
+    site_key: string | null;
+}
+
 export interface ConfigResponse {
+    version: string;
     allow_registration: boolean;
+    registration_mode: RegistrationMode;
     auth_mode: AuthMode;
     oidc_enabled: boolean;
+    oidc_providers: OidcProvider[];
     password_login_enabled: boolean;
+    jwt_enabled: boolean;
+    smart_features: boolean;
+    attachments: boolean;
+    max_upload_bytes: number;
+    read_only: boolean;
+    challenge: ChallengeConfig | null;
 }
 
 export function useConfig() {
@@ -139,6 +139,30 @@ export const api = {
         if (!response.ok) throw new ApiError(response.status, "Failed to export data");
         return response.blob();
     },
-    importData: (data: any) => api.post("/import", data),
+    importData: (data: any): Promise<Job> => api.post("/import", data),
+    getJob: (id: string): Promise<Job> => api.get(`/jobs/${id}`),
 };
 
+export interface Job {
+    id: string;
+    kind: "import" | "site_publish";
+    status: "running" | "completed" | "failed";
+    processed: number;
+    total: number | null;
+    progress: number | null;
+    error: string | null;
+    result: unknown;
+    created_at: string;
+    updated_at: string;
+    finished_at: string | null;
+}
+
+/** Poll a background job until it completes or fails */
+export async function waitForJob(id: string, intervalMs = 1000): Promise<Job> {
+    for (;;) {
+        const job = await api.getJob(id);
+        if (job.status !== "running") return job;
+        await new Promise((resolve) => setTimeout(resolve, intervalMs));
+    }
+}
+
@@ -0,0 +1,30 @@
+/**
+ * Solver for the server's proof-of-work registration challenge.
+ *
+ * Finds a suffix so that SHA-256(`<challenge>:<suffix>`) starts with
+ * `difficulty` zero bits, and returns the full response to submit.
+ */
+
+function leadingZeroBits(hash: Uint8Array): number {
+    let bits = 0;
+    for (const byte of hash) {
+        if (byte === 0) {
+            bits += 8;
+            continue;
+        }
+        bits += Math.clz32(byte) - 24;
+        break;
+    }
+    return bits;
+}
+
+export async function solveProofOfWork(challenge: string, difficulty: number): Promise<string> {
+    const encoder = new TextEncoder();
+    for (let n = 0; ; n++) {
+        const response = `${challenge}:${n}`;
+        const hash = new Uint8Array(await crypto.subtle.digest("SHA-256", encoder.encode(response)));
+        if (leadingZeroBits(hash) >= difficulty) {
+            return response;
+        }
+    }
+}
@@ -0,0 +1,60 @@
+import { useEffect, useRef, useState } from "react";
+import { Link, useSearchParams } from "react-router-dom";
+import { useQueryClient } from "@tanstack/react-query";
+import { useTranslation } from "react-i18next";
+import { api } from "@/lib/api";
+
+type Status = "pending" | "confirmed" | "failed";
+
+/**
+ * Email change confirmation
+ *
+ * Opened from the link sent to a new email address. Confirms the pending
+ * change for the signed-in user.
+ */
+export default function ConfirmEmailPage() {
+    const [searchParams] = useSearchParams();
+    const queryClient = useQueryClient();
+    const { t } = useTranslation();
+    const [status, setStatus] = useState<Status>("pending");
+    const [error, setError] = useState<string | null>(null);
+    const submitted = useRef(false);
+
+    useEffect(() => {
+        // Tokens are single-use; guard against double effects in strict mode
+        if (submitted.current) return;
+        submitted.current = true;
+
+        const token = searchParams.get("token") ?? "";
+        api.post("/me/email/confirm", { token })
+            .then(() => {
+                setStatus("confirmed");
+                queryClient.invalidateQueries({ queryKey: ["user"] });
+            })
+            .catch((err: Error) => {
+                setStatus("failed");
+                setError(err.message);
+            });
+    }, [searchParams, queryClient]);
+
+    return (
+        <div className="mx-auto max-w-md py-16 text-center space-y-4">
+            {status === "pending" && (
+                <p className="text-muted-foreground">{t("Confirming your email address...")}</p>
+            )}
+            {status === "confirmed" && (
+                <p>{t("Your email address has been updated.")}</p>
+            )}
+            {status === "failed" && (
+                <p className="text-destructive">
+                    {t("Could not confirm your email address.")} {error}
+                </p>
+            )}
+            {status !== "pending" && (
+                <Link to="/settings" className="underline">
+                    {t("Back to settings")}
+                </Link>
+            )}
+        </div>
+    );
+}
@@ -56,7 +56,7 @@ export default function DashboardPage() {
     );
 
     // Fetch search results if searching
-    const { data: searchResults, isLoading: searchLoading } = useSearchNotes(searchQuery);
+    const { data: searchResults, isLoading: searchLoading } = useSearchNotes(searchQuery, isArchive);
 
     const displayNotes = searchQuery ? searchResults : notes;
     const isLoading = searchQuery ? searchLoading : notesLoading;
@@ -2,9 +2,10 @@ import { useState, useEffect } from "react";
 import { useForm } from "react-hook-form";
 import { Settings } from "lucide-react";
 import { SettingsDialog } from "@/components/settings-dialog";
+import { ChallengeWidget } from "@/components/challenge-widget";
 import { zodResolver } from "@hookform/resolvers/zod";
 import { z } from "zod";
-import { Link, useNavigate } from "react-router-dom";
+import { Link, useNavigate, useSearchParams } from "react-router-dom";
 import { useRegister } from "@/hooks/use-auth";
 import { useConfig } from "@/hooks/useConfig";
 import { Button } from "@/components/ui/button";
@@ -30,6 +31,9 @@ export default function RegisterPage() {
   const { mutate: register, isPending } = useRegister();
   const { data: config, isLoading: isConfigLoading } = useConfig();
   const navigate = useNavigate();
+  const [searchParams] = useSearchParams();
+  // Invitation links look like /register?invite=<code>
+  const inviteCode = searchParams.get("invite") ?? undefined;
   const { t } = useTranslation();
 
   useEffect(() => {
@@ -56,12 +60,21 @@ export default function RegisterPage() {
     },
   });
 
+  const [challengeResponse, setChallengeResponse] = useState<string>();
+  // Each response is accepted once, so a failed attempt needs a new one
+  const [challengeKey, setChallengeKey] = useState(0);
+  const challengePending = !!config?.challenge && !challengeResponse;
+
   const onSubmit = (data: RegisterFormValues) => {
     register({
       email: data.email,
       password: data.password,
+      invite_code: inviteCode,
+      challenge_response: challengeResponse,
     }, {
       onError: (error: any) => {
+        setChallengeResponse(undefined);
+        setChallengeKey((key) => key + 1);
         if (error instanceof ApiError) {
           toast.error(error.message);
         } else {
@@ -129,7 +142,14 @@ export default function RegisterPage() {
                   </FormItem>
                 )}
               />
-              <Button type="submit" className="w-full" disabled={isPending}>
+              {config?.challenge && (
+                <ChallengeWidget
+                  key={challengeKey}
+                  config={config.challenge}
+                  onSolved={setChallengeResponse}
+                />
+              )}
+              <Button type="submit" className="w-full" disabled={isPending || challengePending}>
                 {isPending ? t("Creating account...") : t("Create account")}
               </Button>
             </form>
@@ -0,0 +1,2 @@
+-- Per-user preferences, stored as a JSON document
+ALTER TABLE users ADD COLUMN settings TEXT NOT NULL DEFAULT '{}';
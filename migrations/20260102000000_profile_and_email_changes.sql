@@ -0,0 +1,11 @@
+-- Profile fields
+ALTER TABLE users ADD COLUMN display_name TEXT;
+ALTER TABLE users ADD COLUMN avatar_url TEXT;
+
+-- Pending email changes awaiting confirmation (one per user)
+CREATE TABLE IF NOT EXISTS email_changes (
+    user_id TEXT PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    new_email TEXT NOT NULL,
+    token TEXT UNIQUE NOT NULL,
+    expires_at TEXT NOT NULL
+);
@@ -0,0 +1,6 @@
+-- Instance-wide settings shared by the API and the worker (e.g. maintenance mode)
+CREATE TABLE IF NOT EXISTS instance_settings (
+    key TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
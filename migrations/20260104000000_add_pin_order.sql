@@ -0,0 +1,11 @@
+-- Explicit ordering of pinned notes (lowest first, NULL when unpinned)
+ALTER TABLE notes ADD COLUMN pin_order INTEGER;
+
+-- Keep the previous order (most recently updated first) for existing pins
+UPDATE notes SET pin_order = (
+    SELECT COUNT(*) FROM notes AS newer
+    WHERE newer.user_id = notes.user_id
+      AND newer.is_pinned = 1
+      AND newer.updated_at > notes.updated_at
+)
+WHERE is_pinned = 1;
@@ -0,0 +1,4 @@
+-- Soft delete: deleted notes stay in the trash until the purge job removes them
+ALTER TABLE notes ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX idx_notes_deleted_at ON notes(deleted_at);
@@ -0,0 +1,3 @@
+-- Remember who made the edit a version was snapshotted for, so bursts of
+-- edits by the same user can share one version
+ALTER TABLE note_versions ADD COLUMN created_by TEXT REFERENCES users(id) ON DELETE SET NULL;
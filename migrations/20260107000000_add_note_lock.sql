@@ -0,0 +1,2 @@
+-- Locked notes reject edits and deletion until they are unlocked
+ALTER TABLE notes ADD COLUMN is_locked INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,19 @@
+-- Full-text index over note versions, so search can include history
+CREATE VIRTUAL TABLE IF NOT EXISTS note_versions_fts USING fts5(
+    title,
+    content,
+    content='note_versions',
+    content_rowid='rowid'
+);
+
+INSERT INTO note_versions_fts(rowid, title, content)
+SELECT rowid, title, content FROM note_versions;
+
+-- Versions are never edited, only added and removed
+CREATE TRIGGER note_versions_ai AFTER INSERT ON note_versions BEGIN
+    INSERT INTO note_versions_fts(rowid, title, content) VALUES (NEW.rowid, NEW.title, NEW.content);
+END;
+
+CREATE TRIGGER note_versions_ad AFTER DELETE ON note_versions BEGIN
+    INSERT INTO note_versions_fts(note_versions_fts, rowid, title, content) VALUES('delete', OLD.rowid, OLD.title, OLD.content);
+END;
@@ -0,0 +1,21 @@
+-- Fold diacritics when indexing so "café" matches "cafe" (the tokenizer can
+-- be switched to trigram at startup with SEARCH_TOKENIZER)
+DROP TABLE IF EXISTS notes_fts;
+CREATE VIRTUAL TABLE notes_fts USING fts5(
+    title,
+    content,
+    content='notes',
+    content_rowid='rowid',
+    tokenize='unicode61 remove_diacritics 2'
+);
+INSERT INTO notes_fts(notes_fts) VALUES('rebuild');
+
+DROP TABLE IF EXISTS note_versions_fts;
+CREATE VIRTUAL TABLE note_versions_fts USING fts5(
+    title,
+    content,
+    content='note_versions',
+    content_rowid='rowid',
+    tokenize='unicode61 remove_diacritics 2'
+);
+INSERT INTO note_versions_fts(note_versions_fts) VALUES('rebuild');
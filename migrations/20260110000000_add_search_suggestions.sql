@@ -0,0 +1,13 @@
+-- Queries each user searched for, feeding search suggestions
+CREATE TABLE IF NOT EXISTS search_queries (
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    query TEXT NOT NULL,
+    last_searched_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, query)
+);
+
+CREATE INDEX idx_search_queries_recent ON search_queries(user_id, last_searched_at);
+
+-- Prefix lookups for as-you-type suggestions
+CREATE INDEX idx_notes_user_title ON notes(user_id, title COLLATE NOCASE);
+CREATE INDEX idx_tags_user_name ON tags(user_id, name);
@@ -0,0 +1,2 @@
+-- Count repeated searches so frequent queries can be suggested first
+ALTER TABLE search_queries ADD COLUMN search_count INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,22 @@
+-- Banners posted by administrators
+CREATE TABLE IF NOT EXISTS announcements (
+    id TEXT PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    level TEXT NOT NULL,
+    starts_at TEXT,
+    ends_at TEXT,
+    created_by TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_announcements_created ON announcements(created_at);
+
+-- Announcements each user has hidden
+CREATE TABLE IF NOT EXISTS announcement_dismissals (
+    announcement_id TEXT NOT NULL REFERENCES announcements(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    dismissed_at TEXT NOT NULL,
+    PRIMARY KEY (announcement_id, user_id)
+);
@@ -0,0 +1,18 @@
+-- Codes accepted by invite-only registration
+CREATE TABLE IF NOT EXISTS invitations (
+    id TEXT PRIMARY KEY NOT NULL,
+    code TEXT NOT NULL UNIQUE,
+    created_by TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    max_uses INTEGER,
+    uses INTEGER NOT NULL DEFAULT 0,
+    expires_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+-- Accounts registered with each invitation
+CREATE TABLE IF NOT EXISTS invitation_redemptions (
+    invitation_id TEXT NOT NULL REFERENCES invitations(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    redeemed_at TEXT NOT NULL,
+    PRIMARY KEY (invitation_id, user_id)
+);
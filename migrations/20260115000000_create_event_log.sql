@@ -0,0 +1,14 @@
+-- Typed record of what users did, for auditing and activity feeds
+CREATE TABLE IF NOT EXISTS event_log (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    -- Not a foreign key: purge events outlive the note
+    note_id TEXT,
+    -- JSON payload of the event
+    data TEXT NOT NULL,
+    occurred_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_event_log_user_occurred ON event_log(user_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_event_log_note ON event_log(note_id);
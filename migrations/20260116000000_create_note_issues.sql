@@ -0,0 +1,13 @@
+-- Problems the lint job found in note content, replaced on every run
+CREATE TABLE IF NOT EXISTS note_issues (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    target TEXT NOT NULL,
+    detail TEXT,
+    found_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_note_issues_note ON note_issues(note_id);
+CREATE INDEX IF NOT EXISTS idx_note_issues_user ON note_issues(user_id);
@@ -0,0 +1,4 @@
+-- Previews of bookmarked URLs, written by the worker without touching
+-- updated_at; link_previews_at is the updated_at they were made for
+ALTER TABLE notes ADD COLUMN link_previews TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE notes ADD COLUMN link_previews_at TEXT;
@@ -0,0 +1,33 @@
+-- Where a note was written, in WGS 84 degrees
+ALTER TABLE notes ADD COLUMN latitude REAL;
+ALTER TABLE notes ADD COLUMN longitude REAL;
+ALTER TABLE notes ADD COLUMN place_name TEXT;
+
+-- Spatial index over located notes, keyed by notes.rowid like notes_fts
+CREATE VIRTUAL TABLE IF NOT EXISTS note_locations USING rtree(
+    id,
+    min_lat, max_lat,
+    min_lon, max_lon
+);
+
+INSERT INTO note_locations(id, min_lat, max_lat, min_lon, max_lon)
+SELECT rowid, latitude, latitude, longitude, longitude FROM notes
+WHERE latitude IS NOT NULL AND longitude IS NOT NULL;
+
+-- Triggers to keep the spatial index in sync
+CREATE TRIGGER notes_locations_ai AFTER INSERT ON notes
+WHEN NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL BEGIN
+    INSERT INTO note_locations(id, min_lat, max_lat, min_lon, max_lon)
+    VALUES (NEW.rowid, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude);
+END;
+
+CREATE TRIGGER notes_locations_ad AFTER DELETE ON notes BEGIN
+    DELETE FROM note_locations WHERE id = OLD.rowid;
+END;
+
+CREATE TRIGGER notes_locations_au AFTER UPDATE OF latitude, longitude ON notes BEGIN
+    DELETE FROM note_locations WHERE id = OLD.rowid;
+    INSERT INTO note_locations(id, min_lat, max_lat, min_lon, max_lon)
+    SELECT NEW.rowid, NEW.latitude, NEW.latitude, NEW.longitude, NEW.longitude
+    WHERE NEW.latitude IS NOT NULL AND NEW.longitude IS NOT NULL;
+END;
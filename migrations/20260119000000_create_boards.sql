@@ -0,0 +1,22 @@
+-- Kanban boards over a user's notes
+CREATE TABLE IF NOT EXISTS boards (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_boards_user ON boards(user_id);
+
+-- Columns select notes by tag (kind 'tag', value the tag name) or by
+-- status (kind 'status', value 'pinned' or 'archived')
+CREATE TABLE IF NOT EXISTS board_columns (
+    id TEXT NOT NULL,
+    board_id TEXT NOT NULL REFERENCES boards(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (board_id, id)
+);
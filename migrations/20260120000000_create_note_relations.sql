@@ -0,0 +1,14 @@
+-- Typed relations between a user's notes; kind is 'parent_of',
+-- 'references' or 'blocked_by'
+CREATE TABLE IF NOT EXISTS note_relations (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    source_note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    target_note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (source_note_id, target_note_id, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_note_relations_user ON note_relations(user_id);
+CREATE INDEX IF NOT EXISTS idx_note_relations_target ON note_relations(target_note_id);
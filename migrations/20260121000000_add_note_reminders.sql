@@ -0,0 +1,4 @@
+-- When the user wants to be reminded of a note (RFC 3339, UTC)
+ALTER TABLE notes ADD COLUMN remind_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_notes_remind_at ON notes(remind_at) WHERE remind_at IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Alternative names resolving to one of the user's tags, e.g. 'js' for
+-- 'javascript'; alias names are stored normalized like tag names
+CREATE TABLE IF NOT EXISTS tag_aliases (
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    alias TEXT NOT NULL,
+    tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, alias)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tag_aliases_tag ON tag_aliases(tag_id);
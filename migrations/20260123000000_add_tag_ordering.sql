@@ -0,0 +1,4 @@
+-- Sidebar ordering of tags: pinned tags first, then by sort_order (lowest
+-- first, NULL until the user reorders their tags), then by name
+ALTER TABLE tags ADD COLUMN is_pinned INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE tags ADD COLUMN sort_order INTEGER;
@@ -0,0 +1,14 @@
+-- Administrators acting as users, kept as an audit trail
+CREATE TABLE IF NOT EXISTS impersonations (
+    id TEXT PRIMARY KEY NOT NULL,
+    admin_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    ended_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_impersonations_admin_user
+    ON impersonations(admin_id, user_id, expires_at);
+CREATE INDEX IF NOT EXISTS idx_impersonations_started ON impersonations(started_at);
@@ -0,0 +1,17 @@
+-- Terms and privacy policy published by administrators; every version is kept
+CREATE TABLE IF NOT EXISTS legal_documents (
+    kind TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    published_at TEXT NOT NULL,
+    PRIMARY KEY (kind, version)
+);
+
+-- Versions each user has accepted, and when
+CREATE TABLE IF NOT EXISTS legal_acceptances (
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    accepted_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, kind, version)
+);
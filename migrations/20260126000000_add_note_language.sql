@@ -0,0 +1,4 @@
+-- Dominant language of a note's content (ISO 639-1), detected on save
+ALTER TABLE notes ADD COLUMN language TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_notes_language ON notes(user_id, language) WHERE language IS NOT NULL;
@@ -0,0 +1,12 @@
+-- Voice memos waiting for the worker to transcribe them into their notes
+CREATE TABLE IF NOT EXISTS audio_uploads (
+    id TEXT PRIMARY KEY NOT NULL,
+    note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content_type TEXT NOT NULL,
+    audio BLOB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audio_uploads_created ON audio_uploads(created_at);
@@ -0,0 +1,37 @@
+-- Allow NULL titles in note_versions, as untitled notes are versioned too
+-- SQLite doesn't support ALTER COLUMN, so we need to recreate the table
+
+-- Step 1: Create new table with nullable title
+CREATE TABLE note_versions_new (
+    id TEXT PRIMARY KEY,
+    note_id TEXT NOT NULL,
+    title TEXT,  -- Now nullable
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+
+-- Step 2: Copy data from old table, keeping rowids for the search index
+INSERT INTO note_versions_new (rowid, id, note_id, title, content, created_at, created_by)
+SELECT rowid, id, note_id, title, content, created_at, created_by FROM note_versions;
+
+-- Step 3: Drop old table (its triggers go with it)
+DROP TABLE note_versions;
+
+-- Step 4: Rename new table
+ALTER TABLE note_versions_new RENAME TO note_versions;
+
+-- Step 5: Recreate indexes
+CREATE INDEX idx_note_versions_note_id ON note_versions(note_id);
+
+-- Step 6: Recreate FTS triggers and reindex
+CREATE TRIGGER note_versions_ai AFTER INSERT ON note_versions BEGIN
+    INSERT INTO note_versions_fts(rowid, title, content) VALUES (NEW.rowid, NEW.title, NEW.content);
+END;
+
+CREATE TRIGGER note_versions_ad AFTER DELETE ON note_versions BEGIN
+    INSERT INTO note_versions_fts(note_versions_fts, rowid, title, content) VALUES('delete', OLD.rowid, OLD.title, OLD.content);
+END;
+
+INSERT INTO note_versions_fts(note_versions_fts) VALUES('rebuild');
@@ -6,7 +6,7 @@
 use std::sync::Arc;
 
 #[cfg(feature = "auth-axum-login")]
-use notes_domain::UserRepository;
+use notes_domain::UserService;
 #[cfg(feature = "auth-axum-login")]
 use notes_infra::session_store::{InfraSessionStore, SessionManagerLayer};
 
@@ -19,9 +19,45 @@ pub use notes_infra::auth::axum_login::{AuthManagerLayer, AuthSession, AuthUser,
 #[cfg(feature = "auth-axum-login")]
 pub async fn setup_auth_layer(
     session_layer: SessionManagerLayer<InfraSessionStore>,
-    user_repo: Arc<dyn UserRepository>,
+    user_service: Arc<UserService>,
 ) -> Result<AuthManagerLayer, ApiError> {
-    notes_infra::auth::axum_login::setup_auth_layer(session_layer, user_repo)
+    notes_infra::auth::axum_login::setup_auth_layer(session_layer, user_service)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))
 }
+
+/// `WWW-Authenticate` challenge of 401 responses to requests without a token
+#[cfg(feature = "auth-jwt")]
+const BEARER_CHALLENGE: &str = r#"Bearer realm="k-notes""#;
+
+/// Challenge of 401 responses to requests whose token was rejected
+#[cfg(feature = "auth-jwt")]
+const INVALID_TOKEN_CHALLENGE: &str = r#"Bearer realm="k-notes", error="invalid_token""#;
+
+/// Middleware adding an RFC 6750 `WWW-Authenticate` challenge to 401
+/// responses, telling token clients to authenticate with a Bearer token;
+/// applied when JWT authentication is enabled
+#[cfg(feature = "auth-jwt")]
+pub async fn bearer_challenge(
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use axum::http::{HeaderValue, StatusCode, header};
+
+    let sent_token = req.headers().contains_key(header::AUTHORIZATION);
+    let mut response = next.run(req).await;
+    if response.status() == StatusCode::UNAUTHORIZED
+        && !response.headers().contains_key(header::WWW_AUTHENTICATE)
+    {
+        let challenge = if sent_token {
+            INVALID_TOKEN_CHALLENGE
+        } else {
+            BEARER_CHALLENGE
+        };
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            HeaderValue::from_static(challenge),
+        );
+    }
+    response
+}
@@ -1,8 +1,33 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use notes_domain::{DEFAULT_MAX_PINNED_NOTES, DEFAULT_VERSION_DEBOUNCE_MINUTES};
+use notes_infra::challenge::pow::DEFAULT_POW_DIFFICULTY;
+#[cfg(feature = "mqtt")]
+use notes_infra::factory::MqttConfig;
+use notes_infra::factory::{
+    BrokerProvider, CacheProvider, ChallengeProvider, DiagramProvider, ImageProxyProvider,
+    LinkPreviewProvider, MailProvider, PasswordHashConfig, PdfProvider, ProofreaderProvider,
+    SpeechProvider, TextGeneratorProvider,
+};
 #[cfg(feature = "smart-features")]
-use notes_infra::factory::{EmbeddingProvider, VectorProvider};
+use notes_infra::factory::{EmbeddingProvider, QdrantConfig, VectorProvider};
+use notes_infra::password::argon2id::Argon2Config;
+use notes_infra::render::html::{DEFAULT_CODE_THEME, DEFAULT_KATEX_URL, RenderOptions};
+#[cfg(feature = "sqlite")]
+use notes_infra::search_index::SearchTokenizer;
 use serde::{Deserialize, Serialize};
 use std::env;
 
+/// Request body limit when `MAX_UPLOAD_BYTES` is not set (axum's own default)
+pub const DEFAULT_MAX_UPLOAD_BYTES: usize = 2 * 1024 * 1024;
+
+/// Where selection exports are kept when `EXPORT_DIR` is not set
+fn default_export_dir() -> String {
+    env::temp_dir()
+        .join("k-notes-exports")
+        .display()
+        .to_string()
+}
+
 /// Authentication mode - determines how the API authenticates requests
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -27,21 +52,50 @@ impl AuthMode {
     }
 }
 
+/// Who can create an account with the register endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationMode {
+    /// Anyone can register (while registration is allowed)
+    #[default]
+    Open,
+    /// Registering requires an invitation code from an administrator
+    Invite,
+}
+
+impl RegistrationMode {
+    /// Parse registration mode from string
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "invite" => RegistrationMode::Invite,
+            _ => RegistrationMode::Open,
+        }
+    }
+}
+
 /// Server configuration
 #[derive(Debug, Clone)]
 pub struct Config {
     pub host: String,
     pub port: u16,
     pub database_url: String,
+    /// Read-only replica serving repository reads, e.g. a LiteFS replica
+    pub database_read_url: Option<String>,
     pub session_secret: String,
     pub cors_allowed_origins: Vec<String>,
     pub allow_registration: bool,
+    pub registration_mode: RegistrationMode,
+    /// Whether visitors without an account get a scratch note kept in their session
+    pub guest_scratchpad: bool,
     #[cfg(feature = "smart-features")]
     pub embedding_provider: EmbeddingProvider,
     #[cfg(feature = "smart-features")]
     pub vector_provider: VectorProvider,
     pub broker_url: String,
 
+    /// MQTT broker that note events are mirrored to (disabled unless configured)
+    pub mqtt_provider: BrokerProvider,
+
     pub secure_cookie: bool,
 
     pub db_max_connections: u32,
@@ -69,6 +123,97 @@ pub struct Config {
 
     /// Frontend URL for OIDC redirect (defaults to first CORS origin)
     pub frontend_url: String,
+
+    /// How Markdown is rendered in printed, exported and published notes
+    pub render: RenderOptions,
+
+    /// PDF export backend (disabled unless configured)
+    pub pdf_provider: PdfProvider,
+
+    /// Spelling and grammar checks of notes (disabled unless configured)
+    pub proofreader_provider: ProofreaderProvider,
+
+    /// Model translating notes (translation disabled unless configured)
+    pub text_generator_provider: TextGeneratorProvider,
+
+    /// Voice reading notes aloud (disabled unless configured)
+    pub speech_provider: SpeechProvider,
+
+    /// Whether voice memos are accepted; the worker transcribes them
+    pub voice_memos: bool,
+
+    /// Server drawing diagrams in rendered notes (disabled unless configured)
+    pub diagram_provider: DiagramProvider,
+
+    /// Fetcher of link previews clients unfurl while editing
+    pub link_preview_provider: LinkPreviewProvider,
+
+    /// Fetcher of external images readers load through the instance
+    /// (disabled unless configured)
+    pub image_proxy_provider: ImageProxyProvider,
+
+    /// Directory static sites are published to (publishing disabled if unset)
+    pub site_publish_dir: Option<String>,
+
+    /// Directory selection exports are kept in until they expire
+    pub export_dir: String,
+
+    /// Email delivery backend (logs emails unless SMTP is configured)
+    pub mail_provider: MailProvider,
+
+    /// Bot check on registration (disabled unless configured)
+    pub challenge_provider: ChallengeProvider,
+
+    /// Password hashing parameters for local accounts
+    pub password_hash: PasswordHashConfig,
+
+    /// Emails of users allowed to use admin endpoints (lowercased)
+    pub admin_emails: Vec<String>,
+
+    /// Maintenance mode at startup; `None` keeps the persisted state
+    pub read_only: Option<bool>,
+
+    /// Maximum number of pinned notes per user
+    pub max_pinned_notes: usize,
+
+    /// Minutes in which repeated edits by the same user share one version
+    pub version_debounce_minutes: u32,
+
+    /// JSON file with welcome notes and starter tags for new accounts
+    pub onboarding_template: Option<String>,
+
+    /// Largest request body accepted, which bounds imports
+    pub max_upload_bytes: usize,
+
+    /// When API v1 was deprecated; v1 responses say so once set
+    pub api_v1_deprecated_at: Option<DateTime<Utc>>,
+
+    /// When API v1 is to be removed, announced with the deprecation
+    pub api_v1_sunset_at: Option<DateTime<Utc>>,
+
+    /// Cache for hot note and tag reads (disabled unless configured)
+    pub cache_provider: CacheProvider,
+
+    /// Networks allowed to reach the API; everyone when empty
+    pub ip_allowlist: Vec<String>,
+
+    /// Networks always rejected
+    pub ip_denylist: Vec<String>,
+
+    /// Reverse proxies whose `X-Forwarded-For` header is believed
+    pub trusted_proxies: Vec<String>,
+
+    /// MaxMind country database used for country blocking
+    #[cfg(feature = "geoip")]
+    pub geoip_database: Option<String>,
+
+    /// ISO country codes rejected (uppercased)
+    #[cfg(feature = "geoip")]
+    pub geoip_blocked_countries: Vec<String>,
+
+    /// Tokenizer for the full-text search index
+    #[cfg(feature = "sqlite")]
+    pub search_tokenizer: SearchTokenizer,
 }
 
 impl Default for Config {
@@ -77,18 +222,19 @@ impl Default for Config {
             host: "127.0.0.1".to_string(),
             port: 3000,
             database_url: "sqlite:data.db?mode=rwc".to_string(),
+            database_read_url: None,
             session_secret: "k-notes-super-secret-key-must-be-at-least-64-bytes-long!!!!"
                 .to_string(),
             cors_allowed_origins: vec!["http://localhost:5173".to_string()],
             allow_registration: true,
+            registration_mode: RegistrationMode::default(),
+            guest_scratchpad: false,
             #[cfg(feature = "smart-features")]
-            embedding_provider: EmbeddingProvider::FastEmbed,
+            embedding_provider: EmbeddingProvider::FastEmbed { pool_size: 1 },
             #[cfg(feature = "smart-features")]
-            vector_provider: VectorProvider::Qdrant {
-                url: "http://localhost:6334".to_string(),
-                collection: "notes".to_string(),
-            },
+            vector_provider: VectorProvider::Qdrant(QdrantConfig::default()),
             broker_url: "nats://localhost:4222".to_string(),
+            mqtt_provider: BrokerProvider::None,
             secure_cookie: false,
             db_max_connections: 5,
             db_min_connections: 1,
@@ -104,6 +250,38 @@ impl Default for Config {
             jwt_expiry_hours: 24,
             is_production: false,
             frontend_url: "http://localhost:5173".to_string(),
+            render: RenderOptions::default(),
+            pdf_provider: PdfProvider::None,
+            proofreader_provider: ProofreaderProvider::None,
+            text_generator_provider: TextGeneratorProvider::None,
+            speech_provider: SpeechProvider::None,
+            voice_memos: false,
+            diagram_provider: DiagramProvider::None,
+            link_preview_provider: LinkPreviewProvider::None,
+            image_proxy_provider: ImageProxyProvider::None,
+            site_publish_dir: None,
+            export_dir: default_export_dir(),
+            mail_provider: MailProvider::Log,
+            challenge_provider: ChallengeProvider::None,
+            password_hash: PasswordHashConfig::default(),
+            admin_emails: vec![],
+            read_only: None,
+            max_pinned_notes: DEFAULT_MAX_PINNED_NOTES,
+            version_debounce_minutes: DEFAULT_VERSION_DEBOUNCE_MINUTES,
+            onboarding_template: None,
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES,
+            api_v1_deprecated_at: None,
+            api_v1_sunset_at: None,
+            cache_provider: CacheProvider::None,
+            ip_allowlist: vec![],
+            ip_denylist: vec![],
+            trusted_proxies: vec![],
+            #[cfg(feature = "geoip")]
+            geoip_database: None,
+            #[cfg(feature = "geoip")]
+            geoip_blocked_countries: vec![],
+            #[cfg(feature = "sqlite")]
+            search_tokenizer: SearchTokenizer::default(),
         }
     }
 }
@@ -121,6 +299,9 @@ impl Config {
 
         let database_url =
             env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:data.db?mode=rwc".to_string());
+        let database_read_url = env::var("DATABASE_READ_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty());
 
         let session_secret = env::var("SESSION_SECRET").unwrap_or_else(|_| {
             "k-notes-super-secret-key-must-be-at-least-64-bytes-long!!!!".to_string()
@@ -139,24 +320,174 @@ impl Config {
             .map(|s| s.to_lowercase() == "true")
             .unwrap_or(true);
 
+        let registration_mode = env::var("REGISTRATION_MODE")
+            .map(|s| RegistrationMode::from_str(&s))
+            .unwrap_or_default();
+
+        let guest_scratchpad = env::var("GUEST_SCRATCHPAD")
+            .map(|v| v == "1" || v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let admin_emails = env::var("ADMIN_EMAILS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let read_only = env::var("READ_ONLY")
+            .ok()
+            .map(|v| v == "1" || v.to_lowercase() == "true");
+
+        let max_pinned_notes = env::var("MAX_PINNED_NOTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_PINNED_NOTES);
+
+        let version_debounce_minutes = env::var("VERSION_DEBOUNCE_MINUTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_VERSION_DEBOUNCE_MINUTES);
+
+        let max_upload_bytes = env::var("MAX_UPLOAD_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_UPLOAD_BYTES);
+
+        // RFC 3339 timestamps or plain dates, read as midnight UTC
+        let date = |name: &str| -> Option<DateTime<Utc>> {
+            let value = env::var(name).ok()?;
+            let value = value.trim();
+            DateTime::parse_from_rfc3339(value)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|d| d.and_hms_opt(0, 0, 0))
+                        .map(|dt| dt.and_utc())
+                })
+        };
+        let api_v1_deprecated_at = date("API_V1_DEPRECATED_AT");
+        let api_v1_sunset_at = date("API_V1_SUNSET_AT");
+
+        let list = |name: &str| -> Vec<String> {
+            env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        let ip_allowlist = list("IP_ALLOWLIST");
+        let ip_denylist = list("IP_DENYLIST");
+        let trusted_proxies = list("TRUSTED_PROXIES");
+        #[cfg(feature = "geoip")]
+        let geoip_database = env::var("GEOIP_DATABASE").ok().filter(|p| !p.is_empty());
+        #[cfg(feature = "geoip")]
+        let geoip_blocked_countries = list("GEOIP_BLOCKED_COUNTRIES")
+            .into_iter()
+            .map(|code| code.to_uppercase())
+            .collect();
+
+        #[cfg(any(feature = "cache-moka", feature = "cache-redis"))]
+        let cache_ttl = std::time::Duration::from_secs(
+            env::var("CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+        );
+        let cache_provider = match env::var("CACHE_PROVIDER").unwrap_or_default().as_str() {
+            #[cfg(feature = "cache-moka")]
+            "moka" => CacheProvider::Moka {
+                max_entries: env::var("CACHE_MAX_ENTRIES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10_000),
+                ttl: cache_ttl,
+            },
+            #[cfg(feature = "cache-redis")]
+            "redis" => CacheProvider::Redis {
+                url: env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
+                ttl: cache_ttl,
+            },
+            _ => CacheProvider::None,
+        };
+
+        #[cfg(feature = "sqlite")]
+        let search_tokenizer = env::var("SEARCH_TOKENIZER")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default();
+
         #[cfg(feature = "smart-features")]
         let embedding_provider = match env::var("EMBEDDING_PROVIDER").unwrap_or_default().as_str() {
             // Future: "ollama" => EmbeddingProvider::Ollama(...),
-            _ => EmbeddingProvider::FastEmbed,
+            _ => EmbeddingProvider::FastEmbed {
+                pool_size: env::var("EMBEDDING_POOL_SIZE")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1),
+            },
         };
 
         #[cfg(feature = "smart-features")]
         let vector_provider = match env::var("VECTOR_PROVIDER").unwrap_or_default().as_str() {
             // Future: "postgres" => ...
-            _ => VectorProvider::Qdrant {
-                url: env::var("QDRANT_URL").unwrap_or_else(|_| "http://localhost:6334".to_string()),
-                collection: env::var("QDRANT_COLLECTION").unwrap_or_else(|_| "notes".to_string()),
-            },
+            _ => {
+                let defaults = QdrantConfig::default();
+                let secs = |name: &str, default: std::time::Duration| {
+                    env::var(name)
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .map(std::time::Duration::from_secs)
+                        .unwrap_or(default)
+                };
+                VectorProvider::Qdrant(QdrantConfig {
+                    url: env::var("QDRANT_URL").unwrap_or(defaults.url.clone()),
+                    collection: env::var("QDRANT_COLLECTION")
+                        .unwrap_or(defaults.collection.clone()),
+                    api_key: env::var("QDRANT_API_KEY").ok(),
+                    require_tls: env::var("QDRANT_REQUIRE_TLS")
+                        .map(|v| v == "1" || v.to_lowercase() == "true")
+                        .unwrap_or(false),
+                    timeout: secs("QDRANT_TIMEOUT_SECS", defaults.timeout),
+                    connect_timeout: secs("QDRANT_CONNECT_TIMEOUT_SECS", defaults.connect_timeout),
+                    vector_name: env::var("QDRANT_VECTOR_NAME").ok(),
+                    vector_size: env::var("QDRANT_VECTOR_SIZE")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.vector_size),
+                    distance: env::var("QDRANT_DISTANCE")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.distance),
+                })
+            }
         };
 
         let broker_url =
             env::var("BROKER_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
 
+        let mqtt_provider = match env::var("MQTT_HOST") {
+            #[cfg(feature = "mqtt")]
+            Ok(host) if !host.is_empty() => {
+                let defaults = MqttConfig::default();
+                BrokerProvider::Mqtt(MqttConfig {
+                    host,
+                    port: env::var("MQTT_PORT")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.port),
+                    client_id: env::var("MQTT_CLIENT_ID").unwrap_or(defaults.client_id),
+                    topic_prefix: env::var("MQTT_TOPIC_PREFIX").unwrap_or(defaults.topic_prefix),
+                    username: env::var("MQTT_USERNAME").ok(),
+                    password: env::var("MQTT_PASSWORD").ok(),
+                })
+            }
+            _ => BrokerProvider::None,
+        };
+
         let secure_cookie = env::var("SECURE_COOKIE")
             .ok()
             .and_then(|s| s.parse().ok())
@@ -197,18 +528,246 @@ impl Config {
             .map(|v| v.to_lowercase() == "production" || v == "1" || v == "true")
             .unwrap_or(false);
 
+        let render = RenderOptions {
+            math: env::var("RENDER_MATH")
+                .map(|v| v.to_lowercase() == "true" || v == "1")
+                .unwrap_or(false),
+            katex_url: env::var("KATEX_URL").unwrap_or_else(|_| DEFAULT_KATEX_URL.to_string()),
+            code_theme: match env::var("CODE_THEME") {
+                Ok(theme) if theme.eq_ignore_ascii_case("none") => None,
+                Ok(theme) if !theme.is_empty() => Some(theme),
+                _ => Some(DEFAULT_CODE_THEME.to_string()),
+            },
+        };
+
+        let pdf_provider = match env::var("PDF_RENDERER").unwrap_or_default().as_str() {
+            "chromium" => PdfProvider::Chromium {
+                binary: env::var("CHROMIUM_PATH").unwrap_or_else(|_| "chromium".to_string()),
+                render: render.clone(),
+            },
+            _ => PdfProvider::None,
+        };
+
+        let proofreader_provider = match env::var("PROOFREADER_PROVIDER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            #[cfg(feature = "proofreading")]
+            "languagetool" => ProofreaderProvider::LanguageTool {
+                base_url: env::var("LANGUAGETOOL_URL")
+                    .unwrap_or_else(|_| "http://localhost:8081".to_string()),
+                timeout: std::time::Duration::from_secs(
+                    env::var("PROOFREADER_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(10),
+                ),
+            },
+            _ => ProofreaderProvider::None,
+        };
+
+        // Note content leaves the instance, so a model is opt-in; the worker
+        // reads the same variables
+        let text_generator_provider = match env::var("TEXT_GENERATOR_PROVIDER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            #[cfg(feature = "text-generation")]
+            "openai" => TextGeneratorProvider::OpenAi {
+                base_url: env::var("TEXT_GENERATOR_URL")
+                    .unwrap_or_else(|_| "https://api.openai.com/v1".to_string()),
+                model: env::var("TEXT_GENERATOR_MODEL").unwrap_or_default(),
+                api_key: env::var("TEXT_GENERATOR_API_KEY").ok(),
+                timeout: std::time::Duration::from_secs(
+                    env::var("TEXT_GENERATOR_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(30),
+                ),
+            },
+            _ => TextGeneratorProvider::None,
+        };
+
+        let speech_provider = match env::var("SPEECH_PROVIDER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            // A local voice has to be downloaded first, so there is no default
+            #[cfg(feature = "speech")]
+            "piper" if env::var("PIPER_VOICE").is_ok() => SpeechProvider::Piper {
+                binary: env::var("PIPER_BINARY").unwrap_or_else(|_| "piper".to_string()),
+                ffmpeg: env::var("FFMPEG_BINARY").unwrap_or_else(|_| "ffmpeg".to_string()),
+                voice: env::var("PIPER_VOICE").unwrap_or_default().into(),
+                // e.g. `pl=/voices/pl_PL-gosia-medium.onnx,de=/voices/de_DE-thorsten-medium.onnx`
+                voices: env::var("PIPER_VOICES")
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter_map(|entry| entry.split_once('='))
+                    .map(|(code, voice)| (code.trim().to_lowercase(), voice.trim().into()))
+                    .collect(),
+            },
+            #[cfg(feature = "speech")]
+            "openai" => SpeechProvider::OpenAi {
+                base_url: env::var("SPEECH_URL")
+                    .unwrap_or_else(|_| "https://api.openai.com/v1".to_string()),
+                model: env::var("SPEECH_MODEL").unwrap_or_else(|_| "tts-1".to_string()),
+                voice: env::var("SPEECH_VOICE").unwrap_or_else(|_| "alloy".to_string()),
+                api_key: env::var("SPEECH_API_KEY").ok(),
+                timeout: std::time::Duration::from_secs(
+                    env::var("SPEECH_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(120),
+                ),
+            },
+            _ => SpeechProvider::None,
+        };
+
+        // The worker reads the same setting to build its transcriber
+        let voice_memos = matches!(
+            env::var("TRANSCRIBER_PROVIDER")
+                .unwrap_or_default()
+                .to_lowercase()
+                .as_str(),
+            "whisper" | "openai"
+        );
+
+        let diagram_provider = match env::var("DIAGRAM_RENDERER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            #[cfg(feature = "diagrams")]
+            "kroki" => DiagramProvider::Kroki {
+                base_url: env::var("KROKI_URL")
+                    .unwrap_or_else(|_| "http://localhost:8000".to_string()),
+                timeout: std::time::Duration::from_secs(
+                    env::var("DIAGRAM_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(10),
+                ),
+            },
+            _ => DiagramProvider::None,
+        };
+
+        // Same variable as the worker, which fetches previews of saved bookmarks
+        #[cfg(feature = "web-fetch")]
+        let link_preview_provider = LinkPreviewProvider::Http {
+            timeout: std::time::Duration::from_secs(
+                env::var("LINK_PREVIEW_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10),
+            ),
+        };
+        #[cfg(not(feature = "web-fetch"))]
+        let link_preview_provider = LinkPreviewProvider::None;
+
+        #[cfg(feature = "web-fetch")]
+        let image_proxy_provider =
+            match env::var("IMAGE_PROXY").map(|v| v == "1" || v.to_lowercase() == "true") {
+                Ok(true) => ImageProxyProvider::Http {
+                    timeout: std::time::Duration::from_secs(
+                        env::var("IMAGE_PROXY_TIMEOUT_SECS")
+                            .ok()
+                            .and_then(|v| v.parse().ok())
+                            .unwrap_or(10),
+                    ),
+                    secret: env::var("IMAGE_PROXY_SECRET").ok(),
+                },
+                _ => ImageProxyProvider::None,
+            };
+        #[cfg(not(feature = "web-fetch"))]
+        let image_proxy_provider = ImageProxyProvider::None;
+
+        #[cfg(feature = "mail-smtp")]
+        let mail_provider = match env::var("SMTP_HOST") {
+            Ok(host) => MailProvider::Smtp {
+                host,
+                port: env::var("SMTP_PORT")
+                    .ok()
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(587),
+                credentials: env::var("SMTP_USERNAME")
+                    .ok()
+                    .zip(env::var("SMTP_PASSWORD").ok()),
+                from: env::var("SMTP_FROM")
+                    .unwrap_or_else(|_| "K-Notes <no-reply@localhost>".to_string()),
+            },
+            Err(_) => MailProvider::Log,
+        };
+        #[cfg(not(feature = "mail-smtp"))]
+        let mail_provider = MailProvider::Log;
+
+        let challenge_secret = env::var("CHALLENGE_SECRET").ok().filter(|s| !s.is_empty());
+        #[cfg(feature = "captcha")]
+        let challenge_site_key = env::var("CHALLENGE_SITE_KEY").unwrap_or_default();
+        let challenge_provider = match env::var("CHALLENGE_PROVIDER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            #[cfg(feature = "captcha")]
+            "hcaptcha" => ChallengeProvider::HCaptcha {
+                site_key: challenge_site_key,
+                secret: challenge_secret.unwrap_or_default(),
+            },
+            #[cfg(feature = "captcha")]
+            "turnstile" => ChallengeProvider::Turnstile {
+                site_key: challenge_site_key,
+                secret: challenge_secret.unwrap_or_default(),
+            },
+            "pow" => ChallengeProvider::ProofOfWork {
+                secret: challenge_secret,
+                difficulty: env::var("POW_DIFFICULTY")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_POW_DIFFICULTY),
+            },
+            _ => ChallengeProvider::None,
+        };
+
+        let argon2_defaults = Argon2Config::default();
+        let password_hash = PasswordHashConfig {
+            argon2: Argon2Config {
+                memory_kib: env::var("ARGON2_MEMORY_KIB")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(argon2_defaults.memory_kib),
+                iterations: env::var("ARGON2_ITERATIONS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(argon2_defaults.iterations),
+                parallelism: env::var("ARGON2_PARALLELISM")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(argon2_defaults.parallelism),
+            },
+            bcrypt_compat: env::var("PASSWORD_BCRYPT_COMPAT")
+                .map(|v| v == "1" || v.to_lowercase() == "true")
+                .unwrap_or(false),
+        };
+
         Self {
             host,
             port,
             database_url,
+            database_read_url,
             session_secret,
             cors_allowed_origins,
             allow_registration,
+            registration_mode,
+            guest_scratchpad,
             #[cfg(feature = "smart-features")]
             embedding_provider,
             #[cfg(feature = "smart-features")]
             vector_provider,
             broker_url,
+            mqtt_provider,
             secure_cookie,
             db_max_connections,
             db_min_connections,
@@ -225,6 +784,38 @@ impl Config {
             is_production,
             frontend_url: env::var("FRONTEND_URL")
                 .unwrap_or_else(|_| "http://localhost:5173".to_string()),
+            render,
+            pdf_provider,
+            proofreader_provider,
+            text_generator_provider,
+            speech_provider,
+            voice_memos,
+            diagram_provider,
+            link_preview_provider,
+            image_proxy_provider,
+            site_publish_dir: env::var("SITE_PUBLISH_DIR").ok(),
+            export_dir: env::var("EXPORT_DIR").unwrap_or_else(|_| default_export_dir()),
+            mail_provider,
+            challenge_provider,
+            password_hash,
+            admin_emails,
+            read_only,
+            max_pinned_notes,
+            version_debounce_minutes,
+            onboarding_template: env::var("ONBOARDING_TEMPLATE").ok(),
+            max_upload_bytes,
+            api_v1_deprecated_at,
+            api_v1_sunset_at,
+            cache_provider,
+            ip_allowlist,
+            ip_denylist,
+            trusted_proxies,
+            #[cfg(feature = "geoip")]
+            geoip_database,
+            #[cfg(feature = "geoip")]
+            geoip_blocked_countries,
+            #[cfg(feature = "sqlite")]
+            search_tokenizer,
         }
     }
 }
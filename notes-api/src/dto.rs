@@ -1,13 +1,42 @@
 //! Request and Response DTOs for notes API
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use notes_domain::{Email, Note, Password, Tag};
+use notes_domain::{
+    AnnouncementRequest as DomainAnnouncementRequest, ChallengeTicket, EditorPreferences, Email,
+    Note, NoteCounts, NoteSortOrder, Password, Tag, User,
+    announcements::{Announcement, AnnouncementLevel},
+    boards::{Board, BoardColumn, BoardColumnNotes, ColumnSource, NoteStatus},
+    bookmarks::LinkPreview,
+    event_log::{ActivityPage, LoggedEvent, LoggedEventKind},
+    geo::NearbyNote,
+    graph::{EdgeKind, NoteGraph},
+    housekeeping::HousekeepingReport,
+    impersonation::Impersonation,
+    instance::{InstanceSettings, InstanceSettingsUpdate, MarkdownOptions},
+    invitations::Invitation,
+    jobs::{Job, JobKind, JobStatus},
+    language::Language,
+    legal::{LegalDocument, LegalDocumentKind},
+    lint::{IssueReport, NoteIssue, NoteIssueKind},
+    overview::{DailyCount, JobCounts, UserCounts},
+    query::NotePredicate,
+    relations::{NoteRelation, RelationKind},
+    scratchpad::Scratchpad,
+    search::{SearchHistoryEntry, SearchHit, SearchHitKind, SearchSuggestions},
+    speech::AudioFormat,
+    tag_aliases::TagAlias,
+    transcription::AudioUpload,
+    trash::StorageStats,
+    writing::{ProofreadSuggestion, Proofreading, WritingStats},
+};
 
-use crate::config::AuthMode;
+use crate::config::{AuthMode, RegistrationMode};
 
 /// Request to create a new note
 #[derive(Debug, Deserialize, Validate)]
@@ -26,6 +55,17 @@ pub struct CreateNoteRequest {
 
     #[serde(default)]
     pub is_pinned: bool,
+
+    /// Degrees; given together with `longitude`
+    pub latitude: Option<f64>,
+
+    pub longitude: Option<f64>,
+
+    pub place_name: Option<String>,
+
+    /// RFC 3339 or natural language such as `next friday 9am`, read in the
+    /// user's timezone
+    pub remind_at: Option<String>,
 }
 
 /// Request to update an existing note (all fields optional)
@@ -42,21 +82,330 @@ pub struct UpdateNoteRequest {
     pub color: Option<String>,
     pub is_pinned: Option<bool>,
     pub is_archived: Option<bool>,
+
+    /// Degrees; given together with `longitude`, both `null` removes the location
+    #[serde(default, deserialize_with = "double_option")]
+    pub latitude: Option<Option<f64>>,
+
+    #[serde(default, deserialize_with = "double_option")]
+    pub longitude: Option<Option<f64>>,
+
+    /// Empty string removes the place name
+    pub place_name: Option<String>,
+
+    /// Same formats as on create; `null` removes the reminder
+    #[serde(default, deserialize_with = "double_option")]
+    pub remind_at: Option<Option<String>>,
+}
+
+/// Tell an explicit `null` (`Some(None)`) apart from a missing field (`None`)
+fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Free text to turn into a note, e.g. `Call Bob #work !pinned @tomorrow`
+#[derive(Debug, Deserialize)]
+pub struct CaptureRequest {
+    pub text: String,
+}
+
+/// New content of the guest scratch note
+#[derive(Debug, Deserialize)]
+pub struct ScratchpadRequest {
+    pub content: String,
+}
+
+/// The guest scratch note; empty until something is written
+#[derive(Debug, Serialize)]
+pub struct ScratchpadResponse {
+    pub content: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Option<Scratchpad>> for ScratchpadResponse {
+    fn from(scratchpad: Option<Scratchpad>) -> Self {
+        match scratchpad {
+            Some(scratchpad) => Self {
+                content: scratchpad.content,
+                updated_at: Some(scratchpad.updated_at),
+            },
+            None => Self {
+                content: String::new(),
+                updated_at: None,
+            },
+        }
+    }
+}
+
+/// Query parameters for finding notes near a place
+#[derive(Debug, Deserialize)]
+pub struct NearbyQuery {
+    pub lat: f64,
+    pub lon: f64,
+    /// Kilometres; defaults to 10
+    pub radius: Option<f64>,
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// Query parameters for duplicating a note
+#[derive(Debug, Deserialize)]
+pub struct DuplicateNoteQuery {
+    /// Prefix the copy's title with "Copy of" (default: true)
+    #[serde(default = "default_true")]
+    pub prefix_title: bool,
+}
+
+/// Query parameters for translating a note
+#[derive(Debug, Deserialize)]
+pub struct TranslateQuery {
+    /// ISO 639-1 code of the language to translate into, like `pl`
+    pub to: Language,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Request to rearrange the pinned notes
+#[derive(Debug, Deserialize)]
+pub struct ReorderPinsRequest {
+    /// Every pinned note, in the desired order
+    pub note_ids: Vec<Uuid>,
 }
 
 /// Query parameters for listing notes
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ListNotesQuery {
     pub pinned: Option<bool>,
-    pub archived: Option<bool>,
+    /// Archived notes are hidden unless `archived=true` or `archived=all`
+    #[serde(default)]
+    pub archived: ArchivedFilter,
     /// Tag name to filter by (will be looked up by route handler)
     pub tag: Option<String>,
+    /// List the trash instead of live notes
+    #[serde(default)]
+    pub trashed: bool,
+}
+
+/// Which notes `?archived=` selects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchivedFilter {
+    /// Only archived notes
+    True,
+    /// Only notes that are not archived
+    #[default]
+    False,
+    /// Archived or not
+    All,
+}
+
+impl ArchivedFilter {
+    /// The equivalent `NoteFilter::is_archived` value
+    pub fn as_filter(self) -> Option<bool> {
+        match self {
+            Self::True => Some(true),
+            Self::False => Some(false),
+            Self::All => None,
+        }
+    }
+}
+
+/// A page of listed notes together with counts of all the user's notes
+#[derive(Debug, Serialize)]
+pub struct NoteListResponse {
+    pub items: Vec<NoteResponse>,
+    /// Notes outside the trash, whatever the filter
+    pub total: usize,
+    /// Pinned notes that are not archived
+    pub pinned_count: usize,
+    pub archived_count: usize,
+    /// The filter the items were selected with, defaults included
+    pub filter: ListNotesQuery,
+}
+
+impl NoteListResponse {
+    pub fn new(notes: Vec<Note>, counts: NoteCounts, filter: ListNotesQuery) -> Self {
+        Self {
+            items: notes.into_iter().map(NoteResponse::from).collect(),
+            total: counts.total,
+            pinned_count: counts.pinned,
+            archived_count: counts.archived,
+            filter,
+        }
+    }
 }
 
 /// Query parameters for search
 #[derive(Debug, Deserialize)]
 pub struct SearchQuery {
     pub q: String,
+    /// Also match archived notes
+    #[serde(default)]
+    pub include_archived: bool,
+    /// Comma-separated scopes (`notes`, `versions`); returns ranked hits when set
+    pub scope: Option<String>,
+}
+
+/// Query parameters for search suggestions
+#[derive(Debug, Deserialize)]
+pub struct SuggestQuery {
+    /// What the user has typed so far
+    pub q: String,
+}
+
+/// Query parameters for search history
+#[derive(Debug, Deserialize)]
+pub struct SearchHistoryQuery {
+    /// Defaults to the 20 most recent queries
+    pub limit: Option<usize>,
+}
+
+/// Query parameters for the auto-archive preview
+#[derive(Debug, Deserialize)]
+pub struct AutoArchivePreviewQuery {
+    /// Period to preview; defaults to the user's configured policy
+    pub after_days: Option<u32>,
+}
+
+/// Notes that the auto-archive policy would archive
+#[derive(Debug, Serialize)]
+pub struct AutoArchivePreviewResponse {
+    pub after_days: u32,
+    /// Notes last updated before this instant are archived
+    pub cutoff: DateTime<Utc>,
+    pub notes: Vec<NoteResponse>,
+}
+
+/// Output format for single-note export
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteExportFormat {
+    Pdf,
+    Markdown,
+}
+
+/// Query parameters for exporting a single note
+#[derive(Debug, Deserialize)]
+pub struct ExportNoteQuery {
+    pub format: NoteExportFormat,
+}
+
+/// Query parameters for unfurling a link
+#[derive(Debug, Deserialize)]
+pub struct UnfurlQuery {
+    pub url: String,
+}
+
+/// Query parameters of an image loaded through the image proxy
+#[derive(Debug, Deserialize)]
+pub struct ProxyImageQuery {
+    pub url: String,
+    /// Signature handed out with the proxy URL
+    pub sig: String,
+}
+
+/// Request to load external images through the image proxy
+#[derive(Debug, Deserialize, Validate)]
+pub struct SignImagesRequest {
+    #[validate(length(max = 100, message = "At most 100 images can be signed at once"))]
+    pub urls: Vec<String>,
+}
+
+/// Proxy URLs of images, by their original URL; URLs the proxy does not
+/// load are left out
+#[derive(Debug, Serialize)]
+pub struct SignImagesResponse {
+    pub images: HashMap<String, String>,
+}
+
+/// Query parameters for reading a note aloud
+#[derive(Debug, Deserialize)]
+pub struct NoteAudioQuery {
+    /// `mp3` (default) or `ogg`
+    #[serde(default)]
+    pub format: AudioFormat,
+}
+
+/// A voice memo waiting to be transcribed into its note
+#[derive(Debug, Serialize)]
+pub struct VoiceMemoResponse {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    pub content_type: String,
+    /// Size of the audio in bytes
+    pub size: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AudioUpload> for VoiceMemoResponse {
+    fn from(upload: AudioUpload) -> Self {
+        Self {
+            id: upload.id,
+            note_id: upload.note_id,
+            content_type: upload.content_type,
+            size: upload.audio.len(),
+            created_at: upload.created_at,
+        }
+    }
+}
+
+/// Query parameters for imports
+#[derive(Debug, Deserialize, Default)]
+pub struct ImportQuery {
+    /// Name of a registered import format, like `simplenote`; taken from the
+    /// Content-Type when omitted, falling back to a K-Notes backup
+    pub format: Option<String>,
+    /// Options of the format, like the CSV column mapping
+    #[serde(flatten)]
+    pub options: HashMap<String, String>,
+}
+
+/// Query parameters for bulk exports
+#[derive(Debug, Deserialize, Default)]
+pub struct ExportScopeQuery {
+    /// Tag name to restrict the export to (all notes if omitted)
+    pub tag: Option<String>,
+}
+
+/// Request to export selected notes
+#[derive(Debug, Deserialize)]
+pub struct SelectionExportRequest {
+    /// Notes to export, in this order
+    #[serde(default)]
+    pub note_ids: Vec<Uuid>,
+    /// Export the notes matching this filter instead of `note_ids`
+    pub filter: Option<NotePredicate>,
+    /// Name of a registered export format, like `markdown`
+    pub format: String,
+}
+
+/// Result of a completed selection export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SelectionExportResponse {
+    pub filename: String,
+    pub content_type: String,
+    /// Archive size in bytes
+    pub size: usize,
+    /// Number of notes exported
+    pub notes: usize,
+    pub download_url: String,
+    /// The archive is kept at least until then
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Result of publishing the static site to the server
+#[derive(Debug, Serialize)]
+pub struct SitePublishResponse {
+    /// Directory the site was written to
+    pub path: String,
+    /// Number of files written
+    pub files: usize,
 }
 
 /// Tag response DTO
@@ -64,6 +413,8 @@ pub struct SearchQuery {
 pub struct TagResponse {
     pub id: Uuid,
     pub name: String,
+    pub is_pinned: bool,
+    pub sort_order: Option<i32>,
 }
 
 impl From<Tag> for TagResponse {
@@ -71,6 +422,8 @@ impl From<Tag> for TagResponse {
         Self {
             id: tag.id,
             name: tag.name.into_inner(), // Convert TagName to String
+            is_pinned: tag.is_pinned,
+            sort_order: tag.sort_order,
         }
     }
 }
@@ -83,10 +436,21 @@ pub struct NoteResponse {
     pub content: String,
     pub color: String,
     pub is_pinned: bool,
+    pub pin_order: Option<i32>,
     pub is_archived: bool,
+    pub is_locked: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
     pub tags: Vec<TagResponse>,
+    /// Cards for URLs on a line of their own, once the worker fetched them
+    pub link_previews: Vec<LinkPreviewResponse>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub place_name: Option<String>,
+    pub remind_at: Option<DateTime<Utc>>,
+    /// ISO 639-1 code of the language the content is written in, if detected
+    pub language: Option<String>,
 }
 
 impl From<Note> for NoteResponse {
@@ -97,14 +461,78 @@ impl From<Note> for NoteResponse {
             content: note.content,
             color: note.color,
             is_pinned: note.is_pinned,
+            pin_order: note.pin_order,
             is_archived: note.is_archived,
+            is_locked: note.is_locked,
             created_at: note.created_at,
             updated_at: note.updated_at,
+            deleted_at: note.deleted_at,
             tags: note.tags.into_iter().map(TagResponse::from).collect(),
+            link_previews: note
+                .link_previews
+                .into_iter()
+                .map(LinkPreviewResponse::from)
+                .collect(),
+            latitude: note.location.map(|l| l.latitude.degrees()),
+            longitude: note.location.map(|l| l.longitude.degrees()),
+            place_name: note.place_name.map(|p| p.as_ref().to_string()),
+            remind_at: note.remind_at,
+            language: note.language.map(String::from),
+        }
+    }
+}
+
+/// A note found near a place
+#[derive(Debug, Serialize)]
+pub struct NearbyNoteResponse {
+    #[serde(flatten)]
+    pub note: NoteResponse,
+    pub distance_km: f64,
+}
+
+impl From<NearbyNote> for NearbyNoteResponse {
+    fn from(nearby: NearbyNote) -> Self {
+        Self {
+            note: NoteResponse::from(nearby.note),
+            distance_km: nearby.distance_km,
+        }
+    }
+}
+
+/// What a bookmarked page says about itself
+#[derive(Debug, Serialize)]
+pub struct LinkPreviewResponse {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub favicon_url: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl From<LinkPreview> for LinkPreviewResponse {
+    fn from(preview: LinkPreview) -> Self {
+        Self {
+            url: preview.url,
+            title: preview.title,
+            description: preview.description,
+            favicon_url: preview.favicon_url,
+            image_url: preview.image_url,
+            site_name: preview.site_name,
+            fetched_at: preview.fetched_at,
         }
     }
 }
 
+/// Query parameters for listing tags
+#[derive(Debug, Deserialize, Default)]
+pub struct ListTagsQuery {
+    /// Only tags that no note uses, to review before they are cleaned up
+    #[serde(default)]
+    pub unused: bool,
+}
+
 /// Request to create a new tag
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateTagRequest {
@@ -112,25 +540,88 @@ pub struct CreateTagRequest {
     pub name: String,
 }
 
-/// Request to rename a tag
+/// Request to rename or pin a tag
 #[derive(Debug, Deserialize, Validate)]
-pub struct RenameTagRequest {
+pub struct UpdateTagRequest {
     #[validate(length(min = 1, max = 50, message = "Tag name must be 1-50 characters"))]
-    pub name: String,
+    pub name: Option<String>,
+    /// Keep the tag at the top of the sidebar
+    pub is_pinned: Option<bool>,
 }
 
-/// Login request
+/// Request to rearrange the tags in the sidebar
 #[derive(Debug, Deserialize)]
+pub struct ReorderTagsRequest {
+    /// Every tag, in the desired order
+    pub tag_ids: Vec<Uuid>,
+}
+
+/// Request to add an alternative name for a tag
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateTagAliasRequest {
+    #[validate(length(min = 1, max = 50, message = "Tag alias must be 1-50 characters"))]
+    pub alias: String,
+}
+
+/// Tag alias response DTO
+#[derive(Debug, Serialize)]
+pub struct TagAliasResponse {
+    pub alias: String,
+    pub tag_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<TagAlias> for TagAliasResponse {
+    fn from(alias: TagAlias) -> Self {
+        Self {
+            alias: alias.alias.into_inner(),
+            tag_id: alias.tag_id,
+            created_at: alias.created_at,
+        }
+    }
+}
+
+/// Login request
+#[derive(Debug, Deserialize, Validate)]
 pub struct LoginRequest {
     pub email: Email,
     pub password: Password,
+    /// Legal documents the user agreed to while signing in
+    #[serde(default)]
+    pub accepted_documents: Vec<AcceptedDocument>,
 }
 
 /// Register request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct RegisterRequest {
     pub email: Email,
     pub password: Password,
+    /// Required when the instance only accepts invited users
+    pub invite_code: Option<String>,
+    /// Solved bot check, required when a challenge provider is configured
+    pub challenge_response: Option<String>,
+    /// Current version of every published legal document
+    #[serde(default)]
+    pub accepted_documents: Vec<AcceptedDocument>,
+}
+
+/// Proof-of-work challenge to solve before registering
+#[derive(Debug, Serialize)]
+pub struct ChallengeResponse {
+    pub challenge: String,
+    /// Leading zero bits the SHA-256 hash of `<challenge>:<solution>` must have
+    pub difficulty: u8,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<ChallengeTicket> for ChallengeResponse {
+    fn from(ticket: ChallengeTicket) -> Self {
+        Self {
+            challenge: ticket.challenge,
+            difficulty: ticket.difficulty,
+            expires_at: ticket.expires_at,
+        }
+    }
 }
 
 /// User response DTO
@@ -138,9 +629,107 @@ pub struct RegisterRequest {
 pub struct UserResponse {
     pub id: Uuid,
     pub email: Email,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            display_name: user.display_name,
+            avatar_url: user.avatar_url,
+            created_at: user.created_at,
+        }
+    }
+}
+
+/// Request to update the current user's profile (all fields optional)
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateProfileRequest {
+    /// Empty string clears the display name
+    #[validate(length(max = 100, message = "Display name must be at most 100 characters"))]
+    pub display_name: Option<String>,
+
+    /// Empty string clears the avatar
+    #[validate(length(max = 2048, message = "Avatar URL must be at most 2048 characters"))]
+    pub avatar_url: Option<String>,
+
+    /// New email address; applied only after confirmation
+    pub email: Option<Email>,
+}
+
+/// Response to a profile update
+#[derive(Debug, Serialize)]
+pub struct UpdateProfileResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    /// Email address awaiting confirmation, if a change was requested
+    pub pending_email: Option<Email>,
+}
+
+/// Request to confirm an email change
+#[derive(Debug, Deserialize)]
+pub struct ConfirmEmailRequest {
+    pub token: String,
+}
+
+/// Request to change the current user's password
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: Password,
+    /// New password (validated by the Password newtype)
+    pub new_password: Password,
+}
+
+/// Request to update user settings (all fields optional)
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateSettingsRequest {
+    #[validate(length(min = 1, max = 32, message = "Color must be 1-32 characters"))]
+    pub default_note_color: Option<String>,
+
+    pub default_sort: Option<NoteSortOrder>,
+
+    #[validate(length(min = 2, max = 35, message = "Locale must be 2-35 characters"))]
+    pub locale: Option<String>,
+
+    #[validate(length(min = 1, max = 64, message = "Timezone must be 1-64 characters"))]
+    pub timezone: Option<String>,
+
+    /// Replaces all editor preferences when present
+    pub editor: Option<EditorPreferences>,
+
+    pub smart_features_enabled: Option<bool>,
+
+    /// Archive notes untouched for this many days; `0` disables
+    pub auto_archive_after_days: Option<u32>,
+
+    pub search_history_enabled: Option<bool>,
+
+    /// Title untitled notes after their content
+    pub auto_title_enabled: Option<bool>,
+}
+
+impl From<UpdateSettingsRequest> for notes_domain::UpdateSettingsRequest {
+    fn from(req: UpdateSettingsRequest) -> Self {
+        Self {
+            default_note_color: req.default_note_color,
+            default_sort: req.default_sort,
+            locale: req.locale,
+            timezone: req.timezone,
+            editor: req.editor,
+            smart_features_enabled: req.smart_features_enabled,
+            auto_archive_after_days: req
+                .auto_archive_after_days
+                .map(|days| (days > 0).then_some(days)),
+            search_history_enabled: req.search_history_enabled,
+            auto_title_enabled: req.auto_title_enabled,
+        }
+    }
+}
+
 /// Note Version response DTO
 #[derive(Debug, Serialize)]
 pub struct NoteVersionResponse {
@@ -163,31 +752,1000 @@ impl From<notes_domain::NoteVersion> for NoteVersionResponse {
     }
 }
 
+/// A ranked hit from a scoped search
+#[derive(Debug, Serialize)]
+pub struct SearchHitResponse {
+    pub kind: SearchHitKind,
+    pub score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<NoteResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<NoteVersionResponse>,
+}
+
+impl From<SearchHit> for SearchHitResponse {
+    fn from(hit: SearchHit) -> Self {
+        let (kind, score) = (hit.kind(), hit.score());
+        let (note, version) = match hit {
+            SearchHit::Note { note, .. } => (Some(NoteResponse::from(note)), None),
+            SearchHit::Version { version, .. } => (None, Some(NoteVersionResponse::from(version))),
+        };
+        Self {
+            kind,
+            score,
+            note,
+            version,
+        }
+    }
+}
+
+/// Plain note list, or ranked hits when a scope was requested
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum SearchResponse {
+    Notes(Vec<NoteResponse>),
+    Hits(Vec<SearchHitResponse>),
+}
+
 /// System configuration response
 #[derive(Debug, Serialize)]
 pub struct ConfigResponse {
+    /// Server version
+    pub version: &'static str,
     pub allow_registration: bool,
+    /// Whether registering needs an invitation code
+    pub registration_mode: RegistrationMode,
     pub auth_mode: AuthMode,
     pub oidc_enabled: bool,
+    /// Single sign-on providers the login page can offer
+    pub oidc_providers: Vec<OidcProviderResponse>,
     pub password_login_enabled: bool,
+    /// Whether login can return a bearer token
+    pub jwt_enabled: bool,
+    /// Semantic search and related notes
+    pub smart_features: bool,
+    /// File attachments on notes (not supported by this server yet)
+    pub attachments: bool,
+    /// Largest request body accepted, e.g. for imports
+    pub max_upload_bytes: usize,
+    pub read_only: bool,
+    /// External images of notes can be loaded through `/api/v1/proxy/image`
+    pub image_proxy: bool,
+    /// Instance defaults for notes and the editor
+    pub note_defaults: NoteDefaultsResponse,
+    /// Bot check the register page must show; `None` when disabled
+    pub challenge: Option<ChallengeConfigResponse>,
 }
 
-/// Note Link response DTO
+/// Single sign-on provider advertised in the config
 #[derive(Debug, Serialize)]
-pub struct NoteLinkResponse {
-    pub source_note_id: Uuid,
-    pub target_note_id: Uuid,
-    pub score: f32,
-    pub created_at: DateTime<Utc>,
+pub struct OidcProviderResponse {
+    pub id: &'static str,
+    pub issuer: String,
+    /// Path that starts the login flow
+    pub login_url: &'static str,
+}
+
+/// Challenge provider advertised in the config
+#[derive(Debug, Serialize)]
+pub struct ChallengeConfigResponse {
+    /// `hcaptcha`, `turnstile` or `pow`
+    pub provider: &'static str,
+    /// Widget key for hosted captchas
+    pub site_key: Option<String>,
+}
+
+/// Maintenance mode status
+#[derive(Debug, Serialize)]
+pub struct MaintenanceResponse {
+    pub read_only: bool,
+}
+
+/// Request to toggle read-only maintenance mode
+#[derive(Debug, Deserialize)]
+pub struct UpdateMaintenanceRequest {
+    pub read_only: bool,
+}
+
+/// Instance settings in effect
+#[derive(Debug, Serialize)]
+pub struct InstanceSettingsResponse {
+    pub allow_registration: bool,
+    pub max_pinned_notes: usize,
+    pub smart_features_enabled: bool,
+    pub print_logo_url: Option<String>,
+    pub print_accent_color: Option<String>,
+    #[serde(flatten)]
+    pub note_defaults: NoteDefaultsResponse,
+    pub read_only: bool,
+}
+
+impl InstanceSettingsResponse {
+    pub fn new(settings: InstanceSettings, read_only: bool) -> Self {
+        Self {
+            allow_registration: settings.allow_registration,
+            max_pinned_notes: settings.max_pinned_notes,
+            smart_features_enabled: settings.smart_features_enabled,
+            print_logo_url: settings.print_logo_url.clone(),
+            print_accent_color: settings.print_accent_color.clone(),
+            note_defaults: NoteDefaultsResponse::from(settings),
+            read_only,
+        }
+    }
+}
+
+/// How clients present notes and the editor on this instance
+#[derive(Debug, Serialize)]
+pub struct NoteDefaultsResponse {
+    /// Colours to offer for notes, as names like `RED` or hex colours
+    pub note_colors: Vec<String>,
+    /// Whether note lists show archived notes until the user chooses
+    pub show_archived: bool,
+    /// Most characters a note's content can hold; `null` if unlimited
+    pub max_note_length: Option<usize>,
+    /// MIME types files attached to notes can have, such as `image/*`
+    pub attachment_types: Vec<String>,
+    pub markdown: MarkdownOptions,
 }
 
-impl From<notes_domain::entities::NoteLink> for NoteLinkResponse {
-    fn from(link: notes_domain::entities::NoteLink) -> Self {
+impl From<InstanceSettings> for NoteDefaultsResponse {
+    fn from(settings: InstanceSettings) -> Self {
         Self {
-            source_note_id: link.source_note_id,
-            target_note_id: link.target_note_id,
-            score: link.score,
-            created_at: link.created_at,
+            note_colors: settings.note_colors,
+            show_archived: settings.show_archived,
+            max_note_length: settings.max_note_length,
+            attachment_types: settings.attachment_types,
+            markdown: settings.markdown,
+        }
+    }
+}
+
+/// Request to change instance settings; omitted fields are left unchanged
+#[derive(Debug, Deserialize)]
+pub struct UpdateInstanceSettingsRequest {
+    pub allow_registration: Option<bool>,
+    pub max_pinned_notes: Option<usize>,
+    pub smart_features_enabled: Option<bool>,
+    /// Empty string removes the logo
+    pub print_logo_url: Option<String>,
+    /// Empty string restores the default colours
+    pub print_accent_color: Option<String>,
+    /// Replaces the whole palette
+    pub note_colors: Option<Vec<String>>,
+    pub show_archived: Option<bool>,
+    /// `0` lifts the limit
+    pub max_note_length: Option<usize>,
+    /// Replaces the whole list; an empty list allows no attachments
+    pub attachment_types: Option<Vec<String>>,
+    /// Replaces every option; omitted options take their defaults
+    pub markdown: Option<MarkdownOptions>,
+    pub read_only: Option<bool>,
+}
+
+impl UpdateInstanceSettingsRequest {
+    pub fn settings_update(&self) -> InstanceSettingsUpdate {
+        InstanceSettingsUpdate {
+            allow_registration: self.allow_registration,
+            max_pinned_notes: self.max_pinned_notes,
+            smart_features_enabled: self.smart_features_enabled,
+            print_logo_url: self.print_logo_url.clone(),
+            print_accent_color: self.print_accent_color.clone(),
+            note_colors: self.note_colors.clone(),
+            show_archived: self.show_archived,
+            max_note_length: self.max_note_length,
+            attachment_types: self.attachment_types.clone(),
+            markdown: self.markdown,
         }
     }
 }
+
+/// Storage reclaimed by the trash purge job
+#[derive(Debug, Serialize)]
+pub struct StorageStatsResponse {
+    pub purged_notes: u64,
+    pub reclaimed_bytes: u64,
+    pub last_purged_at: Option<DateTime<Utc>>,
+}
+
+impl From<StorageStats> for StorageStatsResponse {
+    fn from(stats: StorageStats) -> Self {
+        Self {
+            purged_notes: stats.purged_notes,
+            reclaimed_bytes: stats.reclaimed_bytes,
+            last_purged_at: stats.last_purged_at,
+        }
+    }
+}
+
+/// Leftovers in the instance's data, or the ones housekeeping fixed
+#[derive(Debug, Serialize)]
+pub struct HousekeepingReportResponse {
+    pub orphaned_note_tags: u64,
+    pub fts_drift: u64,
+    /// `null` without a vector store
+    pub orphaned_vectors: Option<u64>,
+    /// When running housekeeping, the notes queued for embedding; `null`
+    /// without a vector store
+    pub notes_without_embeddings: Option<u64>,
+    pub expired_sessions: u64,
+    pub stale_jobs: u64,
+}
+
+impl From<HousekeepingReport> for HousekeepingReportResponse {
+    fn from(report: HousekeepingReport) -> Self {
+        Self {
+            orphaned_note_tags: report.orphaned_note_tags,
+            fts_drift: report.fts_drift,
+            orphaned_vectors: report.orphaned_vectors,
+            notes_without_embeddings: report.notes_without_embeddings,
+            expired_sessions: report.expired_sessions,
+            stale_jobs: report.stale_jobs,
+        }
+    }
+}
+
+/// Query parameters for the admin overview
+#[derive(Debug, Deserialize)]
+pub struct AdminOverviewQuery {
+    /// Days covered by windowed metrics (default 30, max 365)
+    pub days: Option<u32>,
+}
+
+/// Instance-wide metrics for the admin dashboard
+#[derive(Debug, Serialize)]
+pub struct AdminOverviewResponse {
+    pub generated_at: DateTime<Utc>,
+    pub window_days: u32,
+    pub users: UserCountsResponse,
+    pub notes: NoteCountsResponse,
+    pub storage: StorageUsageResponse,
+    pub jobs: JobCountsResponse,
+    /// Number of indexed vectors, when smart features are available
+    pub vector_points: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserCountsResponse {
+    pub total: u64,
+    pub new: u64,
+    pub active: u64,
+}
+
+impl From<UserCounts> for UserCountsResponse {
+    fn from(users: UserCounts) -> Self {
+        Self {
+            total: users.total,
+            new: users.new,
+            active: users.active,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoteCountsResponse {
+    pub total: u64,
+    pub trashed: u64,
+    /// Notes created per day over the window, oldest first
+    pub created_per_day: Vec<DailyCountResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyCountResponse {
+    pub date: chrono::NaiveDate,
+    pub count: u64,
+}
+
+impl From<DailyCount> for DailyCountResponse {
+    fn from(day: DailyCount) -> Self {
+        Self {
+            date: day.date,
+            count: day.count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageUsageResponse {
+    pub database_bytes: u64,
+    pub content_bytes: u64,
+    pub trash: StorageStatsResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobCountsResponse {
+    pub running: u64,
+    pub failed: u64,
+}
+
+impl From<JobCounts> for JobCountsResponse {
+    fn from(jobs: JobCounts) -> Self {
+        Self {
+            running: jobs.running,
+            failed: jobs.failed,
+        }
+    }
+}
+
+/// Query parameters for related notes
+#[derive(Debug, Deserialize)]
+pub struct RelatedNotesQuery {
+    /// Maximum number of related notes (default 5, max 50)
+    pub limit: Option<usize>,
+    /// Minimum similarity score (0.0 to 1.0)
+    pub min_score: Option<f32>,
+}
+
+/// Unsaved note text to find similar notes for
+#[derive(Debug, Deserialize, Validate)]
+pub struct SimilarNotesRequest {
+    #[validate(length(min = 1, message = "Content must not be empty"))]
+    pub content: String,
+    /// The note being edited, left out of the results
+    pub note_id: Option<Uuid>,
+    /// Maximum number of similar notes (default 5, max 50)
+    pub limit: Option<usize>,
+    /// Minimum similarity score (0.0 to 1.0)
+    pub min_score: Option<f32>,
+}
+
+/// Query parameters for the note graph
+#[derive(Debug, Deserialize)]
+pub struct GraphQuery {
+    /// Restrict the graph to notes around this one
+    pub root: Option<Uuid>,
+    /// Hops from `root` (default 1, max 5)
+    pub depth: Option<usize>,
+    /// Minimum score for semantic edges (0.0 to 1.0)
+    pub min_score: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphNodeResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphEdgeResponse {
+    pub source: Uuid,
+    pub target: Uuid,
+    pub weight: f32,
+    pub kind: EdgeKind,
+}
+
+/// Notes and the links between them
+#[derive(Debug, Serialize)]
+pub struct GraphResponse {
+    pub nodes: Vec<GraphNodeResponse>,
+    pub edges: Vec<GraphEdgeResponse>,
+}
+
+impl From<NoteGraph> for GraphResponse {
+    fn from(graph: NoteGraph) -> Self {
+        Self {
+            nodes: graph
+                .nodes
+                .into_iter()
+                .map(|node| GraphNodeResponse {
+                    id: node.id,
+                    title: node.title,
+                    tags: node.tags,
+                })
+                .collect(),
+            edges: graph
+                .edges
+                .into_iter()
+                .map(|edge| GraphEdgeResponse {
+                    source: edge.source,
+                    target: edge.target,
+                    weight: edge.weight,
+                    kind: edge.kind,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Note Link response DTO
+#[derive(Debug, Serialize)]
+pub struct NoteLinkResponse {
+    pub source_note_id: Uuid,
+    pub target_note_id: Uuid,
+    pub score: f32,
+    pub created_at: DateTime<Utc>,
+    /// Title of the target note (empty when untitled)
+    pub target_title: String,
+    /// Start of the target note's content
+    pub target_snippet: String,
+}
+
+impl From<notes_domain::entities::RelatedNote> for NoteLinkResponse {
+    fn from(related: notes_domain::entities::RelatedNote) -> Self {
+        Self {
+            source_note_id: related.link.source_note_id,
+            target_note_id: related.link.target_note_id,
+            score: related.link.score,
+            created_at: related.link.created_at,
+            target_title: related.title,
+            target_snippet: related.snippet,
+        }
+    }
+}
+
+/// An existing note similar to unsaved text
+#[derive(Debug, Serialize)]
+pub struct SimilarNoteResponse {
+    pub note_id: Uuid,
+    pub score: f32,
+    /// Title of the note (empty when untitled)
+    pub title: String,
+    /// Start of the note's content
+    pub snippet: String,
+}
+
+impl SimilarNoteResponse {
+    pub fn new(note: &Note, score: f32) -> Self {
+        Self {
+            note_id: note.id,
+            score,
+            title: note.title_str().to_string(),
+            snippet: note
+                .content
+                .chars()
+                .take(notes_domain::entities::RELATED_NOTE_SNIPPET_LENGTH)
+                .collect(),
+        }
+    }
+}
+
+/// A note title suggestion
+#[derive(Debug, Serialize)]
+pub struct TitleSuggestionResponse {
+    pub id: Uuid,
+    pub title: String,
+}
+
+/// As-you-type search suggestions
+#[derive(Debug, Serialize)]
+pub struct SuggestionsResponse {
+    pub titles: Vec<TitleSuggestionResponse>,
+    pub tags: Vec<TagResponse>,
+    pub queries: Vec<String>,
+}
+
+impl From<SearchSuggestions> for SuggestionsResponse {
+    fn from(suggestions: SearchSuggestions) -> Self {
+        Self {
+            titles: suggestions
+                .titles
+                .into_iter()
+                .map(|t| TitleSuggestionResponse {
+                    id: t.note_id,
+                    title: t.title,
+                })
+                .collect(),
+            tags: suggestions
+                .tags
+                .into_iter()
+                .map(TagResponse::from)
+                .collect(),
+            queries: suggestions.queries,
+        }
+    }
+}
+
+/// A remembered search query
+#[derive(Debug, Serialize)]
+pub struct SearchHistoryEntryResponse {
+    pub query: String,
+    pub last_searched_at: DateTime<Utc>,
+    pub search_count: u32,
+}
+
+impl From<SearchHistoryEntry> for SearchHistoryEntryResponse {
+    fn from(entry: SearchHistoryEntry) -> Self {
+        Self {
+            query: entry.query,
+            last_searched_at: entry.last_searched_at,
+            search_count: entry.search_count,
+        }
+    }
+}
+
+/// Query parameters for listing jobs
+#[derive(Debug, Deserialize)]
+pub struct JobListQuery {
+    /// Defaults to the 20 most recent jobs
+    pub limit: Option<usize>,
+}
+
+/// A background job and its progress
+#[derive(Debug, Serialize)]
+pub struct JobResponse {
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub processed: u64,
+    pub total: Option<u64>,
+    /// Fraction done between 0 and 1, when the total is known
+    pub progress: Option<f64>,
+    pub error: Option<String>,
+    pub result: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<Job> for JobResponse {
+    fn from(job: Job) -> Self {
+        Self {
+            progress: job.progress(),
+            id: job.id,
+            kind: job.kind,
+            status: job.status,
+            processed: job.processed,
+            total: job.total,
+            error: job.error,
+            result: job.result,
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+            finished_at: job.finished_at,
+        }
+    }
+}
+
+/// Request to post or replace an announcement
+#[derive(Debug, Deserialize)]
+pub struct AnnouncementRequest {
+    pub title: String,
+    /// Markdown body
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub level: AnnouncementLevel,
+    /// Shown from this time on; immediately when omitted
+    pub starts_at: Option<DateTime<Utc>>,
+    /// Hidden from this time on; never expires when omitted
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl From<AnnouncementRequest> for DomainAnnouncementRequest {
+    fn from(req: AnnouncementRequest) -> Self {
+        Self {
+            title: req.title,
+            body: req.body,
+            level: req.level,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+        }
+    }
+}
+
+/// An announcement banner
+#[derive(Debug, Serialize)]
+pub struct AnnouncementResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub level: AnnouncementLevel,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Announcement> for AnnouncementResponse {
+    fn from(announcement: Announcement) -> Self {
+        Self {
+            id: announcement.id,
+            title: announcement.title,
+            body: announcement.body,
+            level: announcement.level,
+            starts_at: announcement.starts_at,
+            ends_at: announcement.ends_at,
+            created_at: announcement.created_at,
+            updated_at: announcement.updated_at,
+        }
+    }
+}
+
+/// Request to create an invitation
+#[derive(Debug, Deserialize)]
+pub struct CreateInvitationRequest {
+    /// Accounts that can register with it; unlimited when omitted
+    pub max_uses: Option<u32>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A registration invitation
+#[derive(Debug, Serialize)]
+pub struct InvitationResponse {
+    pub id: Uuid,
+    pub code: String,
+    /// Registration link to share with the invitee
+    pub url: String,
+    pub created_by: Uuid,
+    pub max_uses: Option<u32>,
+    pub uses: u32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl InvitationResponse {
+    pub fn new(invitation: Invitation, frontend_url: &str) -> Self {
+        Self {
+            url: format!(
+                "{}/register?invite={}",
+                frontend_url.trim_end_matches('/'),
+                invitation.code
+            ),
+            id: invitation.id,
+            code: invitation.code,
+            created_by: invitation.created_by,
+            max_uses: invitation.max_uses,
+            uses: invitation.uses,
+            expires_at: invitation.expires_at,
+            created_at: invitation.created_at,
+        }
+    }
+}
+
+/// An invitation with the accounts registered with it
+#[derive(Debug, Serialize)]
+pub struct InvitationDetailResponse {
+    #[serde(flatten)]
+    pub invitation: InvitationResponse,
+    pub invited_users: Vec<Uuid>,
+}
+
+/// Request to start impersonating a user
+#[derive(Debug, Deserialize)]
+pub struct StartImpersonationRequest {
+    pub user_id: Uuid,
+    /// Why access is needed, kept in the audit trail
+    pub reason: String,
+    /// Defaults to 30 minutes (max 240)
+    pub minutes: Option<i64>,
+}
+
+/// Query parameters for the impersonation audit trail
+#[derive(Debug, Deserialize)]
+pub struct ImpersonationListQuery {
+    /// Defaults to the 50 most recent impersonations (max 200)
+    pub limit: Option<usize>,
+}
+
+/// An administrator's impersonation of a user
+#[derive(Debug, Serialize)]
+pub struct ImpersonationResponse {
+    pub id: Uuid,
+    pub admin_id: Uuid,
+    pub user_id: Uuid,
+    pub reason: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+}
+
+impl From<Impersonation> for ImpersonationResponse {
+    fn from(impersonation: Impersonation) -> Self {
+        Self {
+            is_active: impersonation.is_active(Utc::now()),
+            id: impersonation.id,
+            admin_id: impersonation.admin_id,
+            user_id: impersonation.user_id,
+            reason: impersonation.reason,
+            started_at: impersonation.started_at,
+            expires_at: impersonation.expires_at,
+            ended_at: impersonation.ended_at,
+        }
+    }
+}
+
+/// Query parameters for the activity feed
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    /// Only events before this instant; `next_before` of the previous page
+    pub before: Option<DateTime<Utc>>,
+    /// Defaults to 50 events (max 200)
+    pub limit: Option<usize>,
+}
+
+/// Something the user did, with `type` and `data` describing what
+#[derive(Debug, Serialize)]
+pub struct ActivityEventResponse {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: LoggedEventKind,
+}
+
+impl From<LoggedEvent> for ActivityEventResponse {
+    fn from(event: LoggedEvent) -> Self {
+        Self {
+            id: event.id,
+            occurred_at: event.occurred_at,
+            kind: event.kind,
+        }
+    }
+}
+
+/// A page of the activity feed, newest first
+#[derive(Debug, Serialize)]
+pub struct ActivityResponse {
+    pub items: Vec<ActivityEventResponse>,
+    /// Pass as `before` to get the next page; `null` on the last page
+    pub next_before: Option<DateTime<Utc>>,
+}
+
+impl From<ActivityPage> for ActivityResponse {
+    fn from(page: ActivityPage) -> Self {
+        Self {
+            items: page
+                .events
+                .into_iter()
+                .map(ActivityEventResponse::from)
+                .collect(),
+            next_before: page.next_before,
+        }
+    }
+}
+
+/// A problem the lint job found in a note
+#[derive(Debug, Serialize)]
+pub struct NoteIssueResponse {
+    pub note_id: Uuid,
+    pub kind: NoteIssueKind,
+    pub target: String,
+    pub detail: Option<String>,
+    pub found_at: DateTime<Utc>,
+}
+
+impl From<NoteIssue> for NoteIssueResponse {
+    fn from(issue: NoteIssue) -> Self {
+        Self {
+            note_id: issue.note_id,
+            kind: issue.kind,
+            target: issue.target,
+            detail: issue.detail,
+            found_at: issue.found_at,
+        }
+    }
+}
+
+/// Writing statistics of a note, with spelling and grammar suggestions
+#[derive(Debug, Serialize)]
+pub struct ProofreadResponse {
+    /// ISO 639-1 code of the language the note is written in, if detected
+    pub language: Option<String>,
+    pub stats: WritingStats,
+    /// `null` when the instance has no proofreader configured
+    pub suggestions: Option<Vec<ProofreadSuggestion>>,
+}
+
+impl From<Proofreading> for ProofreadResponse {
+    fn from(proofreading: Proofreading) -> Self {
+        Self {
+            language: proofreading.language.map(String::from),
+            stats: proofreading.stats,
+            suggestions: proofreading.suggestions,
+        }
+    }
+}
+
+/// Issue counts of one note
+#[derive(Debug, Serialize)]
+pub struct NoteIssueSummaryResponse {
+    pub note_id: Uuid,
+    pub broken_urls: usize,
+    pub dangling_wiki_links: usize,
+}
+
+/// Issues found across the user's notes
+#[derive(Debug, Serialize)]
+pub struct IssueReportResponse {
+    pub broken_urls: usize,
+    pub dangling_wiki_links: usize,
+    /// Notes with at least one issue, most issues first
+    pub notes: Vec<NoteIssueSummaryResponse>,
+    /// `null` until the lint job has found something
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+impl From<IssueReport> for IssueReportResponse {
+    fn from(report: IssueReport) -> Self {
+        Self {
+            broken_urls: report.broken_urls,
+            dangling_wiki_links: report.dangling_wiki_links,
+            notes: report
+                .notes
+                .into_iter()
+                .map(|n| NoteIssueSummaryResponse {
+                    note_id: n.note_id,
+                    broken_urls: n.broken_urls,
+                    dangling_wiki_links: n.dangling_wiki_links,
+                })
+                .collect(),
+            checked_at: report.checked_at,
+        }
+    }
+}
+
+/// Column of a board; exactly one of `tag` and `status` selects its notes
+#[derive(Debug, Deserialize)]
+pub struct BoardColumnRequest {
+    /// Keeps an existing column's ID; omit for new columns
+    pub id: Option<Uuid>,
+    pub name: String,
+    pub tag: Option<String>,
+    pub status: Option<NoteStatus>,
+}
+
+/// Request to create or replace a board
+#[derive(Debug, Deserialize)]
+pub struct BoardRequest {
+    pub name: String,
+    pub columns: Vec<BoardColumnRequest>,
+}
+
+/// Request to move a note to a column
+#[derive(Debug, Deserialize)]
+pub struct MoveCardRequest {
+    pub note_id: Uuid,
+    pub column_id: Uuid,
+}
+
+/// A column of a board
+#[derive(Debug, Serialize)]
+pub struct BoardColumnResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub tag: Option<String>,
+    pub status: Option<NoteStatus>,
+}
+
+impl From<BoardColumn> for BoardColumnResponse {
+    fn from(column: BoardColumn) -> Self {
+        let (tag, status) = match column.source {
+            ColumnSource::Tag(tag) => (Some(tag.into_inner()), None),
+            ColumnSource::Status(status) => (None, Some(status)),
+        };
+        Self {
+            id: column.id,
+            name: column.name,
+            tag,
+            status,
+        }
+    }
+}
+
+/// A kanban board
+#[derive(Debug, Serialize)]
+pub struct BoardResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub columns: Vec<BoardColumnResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Board> for BoardResponse {
+    fn from(board: Board) -> Self {
+        Self {
+            id: board.id,
+            name: board.name,
+            columns: board
+                .columns
+                .into_iter()
+                .map(BoardColumnResponse::from)
+                .collect(),
+            created_at: board.created_at,
+            updated_at: board.updated_at,
+        }
+    }
+}
+
+/// A column with the notes it holds
+#[derive(Debug, Serialize)]
+pub struct BoardColumnCardsResponse {
+    #[serde(flatten)]
+    pub column: BoardColumnResponse,
+    pub notes: Vec<NoteResponse>,
+}
+
+impl From<BoardColumnNotes> for BoardColumnCardsResponse {
+    fn from(cards: BoardColumnNotes) -> Self {
+        Self {
+            column: BoardColumnResponse::from(cards.column),
+            notes: cards.notes.into_iter().map(NoteResponse::from).collect(),
+        }
+    }
+}
+
+/// A board's notes grouped by column
+#[derive(Debug, Serialize)]
+pub struct BoardCardsResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub columns: Vec<BoardColumnCardsResponse>,
+}
+
+/// Request to relate a note to another one
+#[derive(Debug, Deserialize)]
+pub struct CreateRelationRequest {
+    pub target_id: Uuid,
+    pub kind: RelationKind,
+}
+
+/// A typed relation between two notes
+#[derive(Debug, Serialize)]
+pub struct NoteRelationResponse {
+    pub id: Uuid,
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+    pub kind: RelationKind,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<NoteRelation> for NoteRelationResponse {
+    fn from(relation: NoteRelation) -> Self {
+        Self {
+            id: relation.id,
+            source_id: relation.source_id,
+            target_id: relation.target_id,
+            kind: relation.kind,
+            created_at: relation.created_at,
+        }
+    }
+}
+
+/// A published legal document
+#[derive(Debug, Serialize)]
+pub struct LegalDocumentResponse {
+    pub kind: LegalDocumentKind,
+    pub version: u32,
+    /// Markdown content
+    pub content: String,
+    pub published_at: DateTime<Utc>,
+}
+
+impl From<LegalDocument> for LegalDocumentResponse {
+    fn from(document: LegalDocument) -> Self {
+        Self {
+            kind: document.kind,
+            version: document.version,
+            content: document.content,
+            published_at: document.published_at,
+        }
+    }
+}
+
+/// Request to publish a new version of a legal document
+#[derive(Debug, Deserialize)]
+pub struct PublishLegalDocumentRequest {
+    /// Markdown content
+    pub content: String,
+}
+
+/// Version of a legal document the user agrees to
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct AcceptedDocument {
+    pub kind: LegalDocumentKind,
+    pub version: u32,
+}
+
+/// Request to accept the current legal documents
+#[derive(Debug, Deserialize)]
+pub struct AcceptLegalRequest {
+    pub documents: Vec<AcceptedDocument>,
+}
+
+/// Pairs of document and version, as the legal service takes them
+pub fn accepted_versions(documents: &[AcceptedDocument]) -> Vec<(LegalDocumentKind, u32)> {
+    documents
+        .iter()
+        .map(|document| (document.kind, document.version))
+        .collect()
+}
@@ -1,16 +1,17 @@
 //! API error handling
 //!
-//! Maps domain errors to HTTP responses
+//! Maps domain errors to RFC 9457 problem details (`application/problem+json`)
 
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use validator::ValidationErrors;
 
-use notes_domain::DomainError;
+use notes_domain::{DomainError, RepositoryError};
 
 /// API-level errors
 #[derive(Debug, Error)]
@@ -21,95 +22,280 @@ pub enum ApiError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// Fields of the request that failed validation
+    #[error("Validation error: {}", field_summary(.0))]
+    InvalidFields(Vec<FieldError>),
+
     #[error("Internal server error")]
     Internal(String),
 
-    #[error("Forbidden: {0}")]
-    Forbidden(String),
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+}
+
+/// Field a request was rejected for, in a validation problem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    /// Which rule failed, like `length` or `email`
+    pub code: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// One line per failed field, like `title: Title must be at most 200 characters`
+fn field_summary(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// RFC 9457 problem details, the body of every error response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Problem {
+    /// Identifies the kind of problem; one per `code`
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Summary of the kind of problem, the same for every occurrence
+    pub title: String,
+    pub status: u16,
+    /// What went wrong this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Path of the request that failed; filled in by
+    /// [`crate::versioning::compat_shim`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Machine-readable error code clients can branch on, like
+    /// `note_not_found`
+    pub code: String,
+    /// The fields a validation problem was found in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
 
-    #[error("Unauthorized: {0}")]
-    Unauthorized(String),
+impl Problem {
+    pub fn new(status: StatusCode, code: &str, title: &str, detail: Option<String>) -> Self {
+        Self {
+            problem_type: format!("urn:k-notes:problem:{}", code),
+            title: title.to_string(),
+            status: status.as_u16(),
+            detail,
+            instance: None,
+            code: code.to_string(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// A problem for a bare status, like the ones axum's own rejections
+    /// answer with
+    pub fn from_status(status: StatusCode, detail: Option<String>) -> Self {
+        let title = status.canonical_reason().unwrap_or("Error");
+        let code = title.to_ascii_lowercase().replace([' ', '-'], "_");
+        Self::new(status, &code, title, detail)
+    }
 }
 
-/// Error response body
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub details: Option<String>,
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (
+            status,
+            [(header::CONTENT_TYPE, PROBLEM_CONTENT_TYPE)],
+            Json(self),
+        )
+            .into_response()
+    }
+}
+
+/// Status, code and title of a domain error
+fn domain_problem(error: &DomainError) -> (StatusCode, &'static str, &'static str) {
+    match error {
+        DomainError::NoteNotFound(_) => (StatusCode::NOT_FOUND, "note_not_found", "Note not found"),
+        DomainError::UserNotFound(_) => (StatusCode::NOT_FOUND, "user_not_found", "User not found"),
+        DomainError::TagNotFound(_) => (StatusCode::NOT_FOUND, "tag_not_found", "Tag not found"),
+        DomainError::JobNotFound(_) => (StatusCode::NOT_FOUND, "job_not_found", "Job not found"),
+        DomainError::AnnouncementNotFound(_) => (
+            StatusCode::NOT_FOUND,
+            "announcement_not_found",
+            "Announcement not found",
+        ),
+        DomainError::InvitationNotFound(_) => (
+            StatusCode::NOT_FOUND,
+            "invitation_not_found",
+            "Invitation not found",
+        ),
+        DomainError::BoardNotFound(_) => {
+            (StatusCode::NOT_FOUND, "board_not_found", "Board not found")
+        }
+        DomainError::RelationNotFound(_) => (
+            StatusCode::NOT_FOUND,
+            "relation_not_found",
+            "Relation not found",
+        ),
+        DomainError::TagAliasNotFound(_) => (
+            StatusCode::NOT_FOUND,
+            "tag_alias_not_found",
+            "Tag alias not found",
+        ),
+        DomainError::ImpersonationNotFound(_) => (
+            StatusCode::NOT_FOUND,
+            "impersonation_not_found",
+            "Impersonation not found",
+        ),
+        DomainError::LegalDocumentNotFound(_) => (
+            StatusCode::NOT_FOUND,
+            "legal_document_not_found",
+            "Legal document not found",
+        ),
+
+        DomainError::NoteLocked(_) => (StatusCode::LOCKED, "note_locked", "Note is locked"),
+
+        DomainError::UserAlreadyExists(_) => (
+            StatusCode::CONFLICT,
+            "user_already_exists",
+            "User already exists",
+        ),
+        DomainError::TagAlreadyExists(_) => (
+            StatusCode::CONFLICT,
+            "tag_already_exists",
+            "Tag already exists",
+        ),
+        DomainError::NothingToUndo => (StatusCode::CONFLICT, "nothing_to_undo", "Nothing to undo"),
+        DomainError::NothingToRedo => (StatusCode::CONFLICT, "nothing_to_redo", "Nothing to redo"),
+
+        DomainError::TagLimitExceeded { .. } => (
+            StatusCode::BAD_REQUEST,
+            "tag_limit_exceeded",
+            "Tag limit exceeded",
+        ),
+        DomainError::PinLimitExceeded { .. } => (
+            StatusCode::BAD_REQUEST,
+            "pin_limit_exceeded",
+            "Pin limit exceeded",
+        ),
+        DomainError::ValidationError(_) => (
+            StatusCode::BAD_REQUEST,
+            "validation_error",
+            "Validation error",
+        ),
+
+        DomainError::Unauthenticated(_) => (
+            StatusCode::UNAUTHORIZED,
+            "unauthenticated",
+            "Unauthenticated",
+        ),
+        DomainError::Forbidden(_) => (StatusCode::FORBIDDEN, "forbidden", "Forbidden"),
+        DomainError::ConsentRequired(_) => (
+            StatusCode::FORBIDDEN,
+            "consent_required",
+            "Consent required",
+        ),
+
+        DomainError::RepositoryError(RepositoryError::Conflict(_)) => {
+            (StatusCode::CONFLICT, "conflict", "Repository error")
+        }
+        DomainError::RepositoryError(RepositoryError::NotFound(_)) => {
+            (StatusCode::NOT_FOUND, "not_found", "Repository error")
+        }
+        DomainError::RepositoryError(RepositoryError::Connection(_)) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "database_unavailable",
+            "Repository error",
+        ),
+        DomainError::RepositoryError(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "repository_error",
+            "Repository error",
+        ),
+        DomainError::InfrastructureError(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "infrastructure_error",
+            "Infrastructure error",
+        ),
+    }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, error_response) = match &self {
+        let problem = match self {
             ApiError::Domain(domain_error) => {
-                let status = match domain_error {
-                    DomainError::NoteNotFound(_)
-                    | DomainError::UserNotFound(_)
-                    | DomainError::TagNotFound(_) => StatusCode::NOT_FOUND,
-
-                    DomainError::UserAlreadyExists(_) | DomainError::TagAlreadyExists(_) => {
-                        StatusCode::CONFLICT
-                    }
-
-                    DomainError::TagLimitExceeded { .. } | DomainError::ValidationError(_) => {
-                        StatusCode::BAD_REQUEST
-                    }
-
-                    DomainError::Unauthorized(_) => StatusCode::FORBIDDEN,
-
-                    DomainError::RepositoryError(_) | DomainError::InfrastructureError(_) => {
-                        StatusCode::INTERNAL_SERVER_ERROR
-                    }
-                };
-
-                (
-                    status,
-                    ErrorResponse {
-                        error: domain_error.to_string(),
-                        details: None,
-                    },
-                )
+                let (status, code, title) = domain_problem(&domain_error);
+                Problem::new(status, code, title, Some(domain_error.to_string()))
             }
 
-            ApiError::Validation(msg) => (
+            ApiError::Validation(msg) => Problem::new(
                 StatusCode::BAD_REQUEST,
-                ErrorResponse {
-                    error: "Validation error".to_string(),
-                    details: Some(msg.clone()),
-                },
+                "validation_error",
+                "Validation error",
+                Some(msg),
             ),
 
+            ApiError::InvalidFields(errors) => Problem {
+                detail: Some(field_summary(&errors)),
+                errors,
+                ..Problem::new(
+                    StatusCode::BAD_REQUEST,
+                    "validation_error",
+                    "Validation error",
+                    None,
+                )
+            },
+
             ApiError::Internal(msg) => {
                 // Log internal errors but don't expose details
                 tracing::error!("Internal error: {}", msg);
-                (
+                Problem::new(
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    ErrorResponse {
-                        error: "Internal server error".to_string(),
-                        details: None,
-                    },
+                    "internal_error",
+                    "Internal server error",
+                    None,
                 )
             }
 
-            ApiError::Forbidden(msg) => (
-                StatusCode::FORBIDDEN,
-                ErrorResponse {
-                    error: "Forbidden".to_string(),
-                    details: Some(msg.clone()),
-                },
-            ),
-
-            ApiError::Unauthorized(msg) => (
-                StatusCode::UNAUTHORIZED,
-                ErrorResponse {
-                    error: "Unauthorized".to_string(),
-                    details: Some(msg.clone()),
-                },
+            ApiError::ServiceUnavailable(msg) => Problem::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service_unavailable",
+                "Service unavailable",
+                Some(msg),
             ),
         };
 
-        (status, Json(error_response)).into_response()
+        problem.into_response()
+    }
+}
+
+/// Failed rules of a request body, ordered by field
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields: Vec<FieldError> = errors
+            .field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors.iter().map(move |error| {
+                    let message = error
+                        .message
+                        .as_ref()
+                        .map_or_else(|| error.code.to_string(), |m| m.to_string());
+                    FieldError::new(field.to_string(), &error.code, message)
+                })
+            })
+            .collect();
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        Self::InvalidFields(fields)
     }
 }
 
@@ -121,6 +307,21 @@ impl ApiError {
     pub fn internal(msg: impl Into<String>) -> Self {
         Self::Internal(msg.into())
     }
+
+    /// The request carries no valid credentials; answered with 401
+    pub fn unauthenticated(msg: impl Into<String>) -> Self {
+        Self::Domain(DomainError::unauthenticated(msg))
+    }
+
+    /// The user may not do this; answered with 403
+    pub fn forbidden(msg: impl Into<String>) -> Self {
+        Self::Domain(DomainError::forbidden(msg))
+    }
+
+    /// A request field that failed a check made outside its validation rules
+    pub fn invalid_field(field: impl Into<String>, message: impl std::fmt::Display) -> Self {
+        Self::InvalidFields(vec![FieldError::new(field, "invalid", message.to_string())])
+    }
 }
 
 /// Result type alias for API handlers
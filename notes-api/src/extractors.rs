@@ -1,9 +1,14 @@
 //! Auth extractors for API handlers
 //!
-//! Provides the `CurrentUser` extractor that works with both session and JWT auth.
+//! Provides the `CurrentUser` extractor that works with both session and JWT auth,
+//! and the `Scoped` wrapper for routes that tokens with limited scopes may use.
+//! Both refuse users who have not accepted the current legal documents.
+
+use std::marker::PhantomData;
 
 use axum::{extract::FromRequestParts, http::request::Parts};
-use notes_domain::User;
+use notes_domain::scopes::{Scope, Scopes};
+use notes_domain::{DomainError, User};
 
 use crate::config::AuthMode;
 use crate::error::ApiError;
@@ -15,6 +20,9 @@ use crate::state::AppState;
 /// - `Session`: Uses axum-login session cookies
 /// - `Jwt`: Uses Bearer token in Authorization header
 /// - `Both`: Tries JWT first, then falls back to session
+///
+/// Only full access is accepted; routes open to tokens with limited scopes
+/// use [`Scoped`] instead.
 pub struct CurrentUser(pub User);
 
 impl FromRequestParts<AppState> for CurrentUser {
@@ -24,47 +32,263 @@ impl FromRequestParts<AppState> for CurrentUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let auth_mode = state.config.auth_mode;
-
-        // Try JWT first if enabled
-        #[cfg(feature = "auth-jwt")]
-        if matches!(auth_mode, AuthMode::Jwt | AuthMode::Both) {
-            match try_jwt_auth(parts, state).await {
-                Ok(Some(user)) => return Ok(CurrentUser(user)),
-                Ok(None) => {
-                    // No JWT token present, continue to session auth if Both mode
-                    if auth_mode == AuthMode::Jwt {
-                        return Err(ApiError::Unauthorized(
-                            "Missing or invalid Authorization header".to_string(),
-                        ));
-                    }
+        let (user, scopes) = authenticate(parts, state).await?;
+        if !scopes.is_full() {
+            return Err(ApiError::forbidden(
+                "This endpoint requires a token with full access",
+            ));
+        }
+        Ok(CurrentUser(user))
+    }
+}
+
+/// Extracted current user who may not have accepted the current legal
+/// documents yet.
+///
+/// Only for the routes needed to review and accept them. Requires full
+/// access and ignores `X-Impersonate-User`.
+pub struct PendingConsentUser(pub User);
+
+impl FromRequestParts<AppState> for PendingConsentUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let (user, scopes) = authenticate_credentials(parts, state).await?;
+        if !scopes.is_full() {
+            return Err(ApiError::forbidden(
+                "This endpoint requires a token with full access",
+            ));
+        }
+        Ok(PendingConsentUser(user))
+    }
+}
+
+/// Marker types naming the scope a [`Scoped`] route requires
+pub mod scope {
+    use notes_domain::scopes::Scope;
+
+    pub trait RequiredScope {
+        const SCOPE: Scope;
+    }
+
+    pub struct NotesRead;
+    pub struct NotesWrite;
+    pub struct Export;
+    pub struct Admin;
+
+    impl RequiredScope for NotesRead {
+        const SCOPE: Scope = Scope::NotesRead;
+    }
+
+    impl RequiredScope for NotesWrite {
+        const SCOPE: Scope = Scope::NotesWrite;
+    }
+
+    impl RequiredScope for Export {
+        const SCOPE: Scope = Scope::Export;
+    }
+
+    impl RequiredScope for Admin {
+        const SCOPE: Scope = Scope::Admin;
+    }
+}
+
+/// Extracted current user whose credentials grant the scope `S`.
+///
+/// Sessions and tokens without scopes have every scope.
+pub struct Scoped<S>(pub User, pub PhantomData<S>);
+
+impl<S: scope::RequiredScope> FromRequestParts<AppState> for Scoped<S> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let (user, scopes) = authenticate(parts, state).await?;
+        if !scopes.allows(S::SCOPE) {
+            return Err(ApiError::forbidden(format!(
+                "Token lacks the {} scope",
+                S::SCOPE
+            )));
+        }
+        Ok(Scoped(user, PhantomData))
+    }
+}
+
+/// Extracted current user who is listed in `ADMIN_EMAILS`.
+///
+/// Rejects authenticated non-admins, and tokens without the `admin` scope, with 403.
+pub struct AdminUser(pub User);
+
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let Scoped(user, _) = Scoped::<scope::Admin>::from_request_parts(parts, state).await?;
+
+        if !is_admin(state, &user) {
+            return Err(ApiError::forbidden("Admin access required"));
+        }
+
+        Ok(AdminUser(user))
+    }
+}
+
+/// Header naming the user an administrator acts as
+pub const IMPERSONATE_USER_HEADER: &str = "x-impersonate-user";
+
+fn is_admin(state: &AppState, user: &User) -> bool {
+    let email = user.email.as_ref().to_lowercase();
+    state.config.admin_emails.contains(&email)
+}
+
+/// Authenticate the request, returning the user it is served as and the
+/// scopes granted
+///
+/// The signed-in user has to have accepted the current legal documents.
+/// With `X-Impersonate-User`, an administrator with an active impersonation
+/// of that user is served as them, with the administrator's own scopes.
+pub(crate) async fn authenticate(
+    parts: &mut Parts,
+    state: &AppState,
+) -> Result<(User, Scopes), ApiError> {
+    if let Some(ServedAs(user, scopes)) = parts.extensions.get::<ServedAs>() {
+        return Ok((user.clone(), scopes.clone()));
+    }
+    let (user, scopes) = serve_as(parts, state).await?;
+    parts
+        .extensions
+        .insert(ServedAs(user.clone(), scopes.clone()));
+    Ok((user, scopes))
+}
+
+/// The user and scopes a request is served as, kept in its extensions like
+/// [`Authenticated`]
+#[derive(Clone)]
+struct ServedAs(User, Scopes);
+
+async fn serve_as(parts: &mut Parts, state: &AppState) -> Result<(User, Scopes), ApiError> {
+    let (user, scopes) = authenticate_credentials(parts, state).await?;
+    state.services.legal.ensure_accepted(user.id).await?;
+
+    let Some(header) = parts.headers.get(IMPERSONATE_USER_HEADER) else {
+        return Ok((user, scopes));
+    };
+    let target_id: uuid::Uuid = header
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| ApiError::Validation("X-Impersonate-User must be a user ID".to_string()))?;
+    if !is_admin(state, &user) || !scopes.allows(Scope::Admin) {
+        return Err(ApiError::forbidden(
+            "Only administrators can impersonate users",
+        ));
+    }
+
+    let impersonation = state
+        .services
+        .impersonations
+        .active(user.id, target_id)
+        .await
+        .map_err(|e| match e {
+            DomainError::Forbidden(_) => ApiError::forbidden(
+                "No active impersonation of this user; start one at /api/v1/admin/impersonations"
+                    .to_string(),
+            ),
+            e => e.into(),
+        })?;
+    let target = state.services.users.find_by_id(target_id).await?;
+    tracing::info!(
+        admin_id = %user.id,
+        user_id = %target.id,
+        impersonation_id = %impersonation.id,
+        method = %parts.method,
+        path = %parts.uri.path(),
+        "Impersonated request"
+    );
+
+    Ok((target, scopes))
+}
+
+/// The user and scopes a request authenticated as, kept in its extensions
+/// so every extractor and layer after the first reuses them
+#[derive(Clone)]
+struct Authenticated(User, Scopes);
+
+/// Authenticate the credentials sent with the request, once per request
+pub(crate) async fn authenticate_credentials(
+    parts: &mut Parts,
+    state: &AppState,
+) -> Result<(User, Scopes), ApiError> {
+    if let Some(Authenticated(user, scopes)) = parts.extensions.get::<Authenticated>() {
+        return Ok((user.clone(), scopes.clone()));
+    }
+    let (user, scopes) = verify_credentials(parts, state).await?;
+    parts
+        .extensions
+        .insert(Authenticated(user.clone(), scopes.clone()));
+    Ok((user, scopes))
+}
+
+/// Check the session or token of the request, as the auth mode allows
+async fn verify_credentials(
+    parts: &mut Parts,
+    state: &AppState,
+) -> Result<(User, Scopes), ApiError> {
+    let auth_mode = state.config.auth_mode;
+    #[allow(unused_mut)] // only set with auth-jwt
+    let mut rejection = ApiError::unauthenticated("Not authenticated");
+
+    // Try JWT first if enabled
+    #[cfg(feature = "auth-jwt")]
+    if matches!(auth_mode, AuthMode::Jwt | AuthMode::Both) {
+        match try_jwt_auth(parts, state).await {
+            Ok(Some(authenticated)) => return Ok(authenticated),
+            Ok(None) => {
+                // No JWT token present, continue to session auth if Both mode
+                if auth_mode == AuthMode::Jwt {
+                    return Err(ApiError::unauthenticated(
+                        "Missing or invalid Authorization header",
+                    ));
                 }
-                Err(e) => {
-                    // JWT was present but invalid
-                    tracing::debug!("JWT auth failed: {}", e);
-                    if auth_mode == AuthMode::Jwt {
-                        return Err(e);
-                    }
-                    // In Both mode, continue to try session
+            }
+            Err(e) => {
+                // JWT was present but invalid
+                tracing::debug!("JWT auth failed: {}", e);
+                if auth_mode == AuthMode::Jwt {
+                    return Err(e);
                 }
+                // In Both mode, continue to try session, and say why the
+                // token was rejected if there is none
+                rejection = e;
             }
         }
+    }
 
-        // Try session auth if enabled
-        #[cfg(feature = "auth-axum-login")]
-        if matches!(auth_mode, AuthMode::Session | AuthMode::Both) {
-            if let Some(user) = try_session_auth(parts).await? {
-                return Ok(CurrentUser(user));
-            }
+    // Try session auth if enabled
+    #[cfg(feature = "auth-axum-login")]
+    if matches!(auth_mode, AuthMode::Session | AuthMode::Both) {
+        if let Some(user) = try_session_auth(parts).await? {
+            return Ok((user, Scopes::Full));
         }
-
-        Err(ApiError::Unauthorized("Not authenticated".to_string()))
     }
+
+    Err(rejection)
 }
 
 /// Try to authenticate using JWT Bearer token
 #[cfg(feature = "auth-jwt")]
-async fn try_jwt_auth(parts: &mut Parts, state: &AppState) -> Result<Option<User>, ApiError> {
+async fn try_jwt_auth(
+    parts: &mut Parts,
+    state: &AppState,
+) -> Result<Option<(User, Scopes)>, ApiError> {
     use axum::http::header::AUTHORIZATION;
 
     // Get Authorization header
@@ -75,12 +299,12 @@ async fn try_jwt_auth(parts: &mut Parts, state: &AppState) -> Result<Option<User
 
     let auth_str = auth_header
         .to_str()
-        .map_err(|_| ApiError::Unauthorized("Invalid Authorization header encoding".to_string()))?;
+        .map_err(|_| ApiError::unauthenticated("Invalid Authorization header encoding"))?;
 
     // Extract Bearer token
-    let token = auth_str.strip_prefix("Bearer ").ok_or_else(|| {
-        ApiError::Unauthorized("Authorization header must use Bearer scheme".to_string())
-    })?;
+    let token = auth_str
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| ApiError::unauthenticated("Authorization header must use Bearer scheme"))?;
 
     // Get JWT validator
     let validator = state
@@ -92,29 +316,36 @@ async fn try_jwt_auth(parts: &mut Parts, state: &AppState) -> Result<Option<User
     let claims = validator.validate_token(token).map_err(|e| {
         tracing::debug!("JWT validation failed: {:?}", e);
         match e {
-            notes_infra::auth::jwt::JwtError::Expired => {
-                ApiError::Unauthorized("Token expired".to_string())
-            }
+            notes_infra::auth::jwt::JwtError::Expired => ApiError::unauthenticated("Token expired"),
             notes_infra::auth::jwt::JwtError::InvalidFormat => {
-                ApiError::Unauthorized("Invalid token format".to_string())
+                ApiError::unauthenticated("Invalid token format")
             }
-            _ => ApiError::Unauthorized("Token validation failed".to_string()),
+            _ => ApiError::unauthenticated("Token validation failed"),
         }
     })?;
 
+    let scopes = claims
+        .scopes()
+        .map_err(|_| ApiError::unauthenticated("Invalid scopes in token"))?;
+
     // Fetch user from database by ID (subject contains user ID)
     let user_id: uuid::Uuid = claims
         .sub
         .parse()
-        .map_err(|_| ApiError::Unauthorized("Invalid user ID in token".to_string()))?;
+        .map_err(|_| ApiError::unauthenticated("Invalid user ID in token"))?;
 
+    // Tokens outlive deleted accounts
     let user = state
-        .user_service
+        .services
+        .users
         .find_by_id(user_id)
         .await
-        .map_err(|e| ApiError::Internal(format!("Failed to fetch user: {}", e)))?;
+        .map_err(|e| match e {
+            e if e.is_not_found() => ApiError::unauthenticated("Token user no longer exists"),
+            e => ApiError::Internal(format!("Failed to fetch user: {}", e)),
+        })?;
 
-    Ok(Some(user))
+    Ok(Some((user, scopes)))
 }
 
 /// Try to authenticate using session cookie
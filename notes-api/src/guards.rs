@@ -0,0 +1,108 @@
+//! Route protection groups
+//!
+//! Routes are grouped by who may call them, and each group is guarded as a
+//! whole with [`require_auth`], [`require_admin`] or [`require_scope`],
+//! checked by the [`enforce`] middleware:
+//!
+//! ```ignore
+//! notes_routes().route_layer(from_fn_with_state(require_scope(state, Scope::NotesWrite), enforce))
+//! ```
+//!
+//! A route added to a guarded group cannot be reached without the group's
+//! credentials, whatever extractor its handler uses. Handlers still take the
+//! user from [`crate::extractors`], which reuse the credentials the guard
+//! checked.
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::request::Parts,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use notes_domain::scopes::Scope;
+
+use crate::error::ApiError;
+use crate::extractors::{AdminUser, authenticate, authenticate_credentials};
+use crate::state::AppState;
+
+/// What a guarded group requires of a request
+#[derive(Debug, Clone, Copy)]
+enum Requirement {
+    /// Signed in with any scopes, having accepted the legal documents
+    SignedIn,
+    /// Signed in, whether or not the legal documents were accepted
+    Credentials,
+    /// Signed in as an administrator with the `admin` scope
+    Admin,
+    /// Signed in with credentials granting the scope
+    Scope(Scope),
+}
+
+impl Requirement {
+    async fn check(self, parts: &mut Parts, state: &AppState) -> Result<(), ApiError> {
+        match self {
+            Requirement::SignedIn => authenticate(parts, state).await.map(|_| ()),
+            Requirement::Credentials => authenticate_credentials(parts, state).await.map(|_| ()),
+            Requirement::Admin => AdminUser::from_request_parts(parts, state)
+                .await
+                .map(|_| ()),
+            Requirement::Scope(scope) => {
+                let (_, scopes) = authenticate(parts, state).await?;
+                if !scopes.allows(scope) {
+                    return Err(ApiError::forbidden(format!(
+                        "Token lacks the {} scope",
+                        scope
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Guard a group of routes so only signed-in users reach them
+pub fn require_auth(state: &AppState) -> Guard {
+    Guard::new(state, Requirement::SignedIn)
+}
+
+/// Guard a group of routes so only signed-in users reach them, including
+/// those who still have to accept the current legal documents
+pub fn require_credentials(state: &AppState) -> Guard {
+    Guard::new(state, Requirement::Credentials)
+}
+
+/// Guard a group of routes so only administrators reach them
+pub fn require_admin(state: &AppState) -> Guard {
+    Guard::new(state, Requirement::Admin)
+}
+
+/// Guard a group of routes so only credentials granting `scope` reach them
+pub fn require_scope(state: &AppState, scope: Scope) -> Guard {
+    Guard::new(state, Requirement::Scope(scope))
+}
+
+/// A group's [`Requirement`], the state of the [`enforce`] middleware
+#[derive(Clone)]
+pub struct Guard {
+    state: AppState,
+    requirement: Requirement,
+}
+
+impl Guard {
+    fn new(state: &AppState, requirement: Requirement) -> Self {
+        Self {
+            state: state.clone(),
+            requirement,
+        }
+    }
+}
+
+/// Middleware that rejects requests not meeting the group's requirement
+pub async fn enforce(State(guard): State<Guard>, req: Request, next: Next) -> Response {
+    let (mut parts, body) = req.into_parts();
+    if let Err(e) = guard.requirement.check(&mut parts, &guard.state).await {
+        return e.into_response();
+    }
+    next.run(Request::from_parts(parts, body)).await
+}
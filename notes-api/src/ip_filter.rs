@@ -0,0 +1,170 @@
+//! IP allow/deny lists and country blocking
+//!
+//! Runs before authentication, so blocked clients never reach a login or
+//! session lookup. Every blocked request is logged on the `audit` target.
+//!
+//! Behind a reverse proxy the peer address is the proxy's. Requests from
+//! `TRUSTED_PROXIES` are attributed to the last untrusted address in their
+//! `X-Forwarded-For` header instead.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+
+use crate::config::Config;
+use crate::error::ApiError;
+
+/// Client address checks applied to every request
+pub struct IpFilter {
+    /// When non-empty, only these networks are let through
+    allow: Vec<IpNet>,
+    deny: Vec<IpNet>,
+    trusted_proxies: Vec<IpNet>,
+    #[cfg(feature = "geoip")]
+    geo: Option<CountryBlocker>,
+}
+
+#[cfg(feature = "geoip")]
+struct CountryBlocker {
+    reader: maxminddb::Reader<Vec<u8>>,
+    /// Uppercase ISO 3166-1 alpha-2 codes
+    blocked: Vec<String>,
+}
+
+/// Parse a list of networks; single addresses are taken as host networks
+fn parse_networks(name: &str, entries: &[String]) -> anyhow::Result<Vec<IpNet>> {
+    entries
+        .iter()
+        .map(|entry| {
+            entry
+                .parse::<IpNet>()
+                .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| anyhow::anyhow!("Invalid network in {}: {}", name, entry))
+        })
+        .collect()
+}
+
+impl IpFilter {
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        #[cfg(feature = "geoip")]
+        let geo = match &config.geoip_database {
+            Some(path) if !config.geoip_blocked_countries.is_empty() => Some(CountryBlocker {
+                reader: maxminddb::Reader::open_readfile(path).map_err(|e| {
+                    anyhow::anyhow!("Failed to open GeoIP database {}: {}", path, e)
+                })?,
+                blocked: config.geoip_blocked_countries.clone(),
+            }),
+            _ => None,
+        };
+
+        Ok(Self {
+            allow: parse_networks("IP_ALLOWLIST", &config.ip_allowlist)?,
+            deny: parse_networks("IP_DENYLIST", &config.ip_denylist)?,
+            trusted_proxies: parse_networks("TRUSTED_PROXIES", &config.trusted_proxies)?,
+            #[cfg(feature = "geoip")]
+            geo,
+        })
+    }
+
+    /// Whether any check is configured; otherwise the middleware is skipped
+    pub fn is_enabled(&self) -> bool {
+        #[cfg(feature = "geoip")]
+        if self.geo.is_some() {
+            return true;
+        }
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.trusted_proxies.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Address of the client, looking through trusted proxies
+    fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        // Proxies append to the header, so the entries the client controls
+        // come first; walk back from the closest hop
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|entry| entry.trim().parse::<IpAddr>().ok())
+            .collect::<Vec<_>>();
+        let mut client = peer;
+        for ip in forwarded.into_iter().rev() {
+            client = ip;
+            if !self.is_trusted(ip) {
+                break;
+            }
+        }
+        client
+    }
+
+    /// Why `ip` is blocked, or `None` if it may pass
+    fn check(&self, ip: IpAddr) -> Option<String> {
+        // IPv4 clients on dual-stack sockets show up as IPv4-mapped IPv6
+        let ip = ip.to_canonical();
+
+        if self.deny.iter().any(|net| net.contains(&ip)) {
+            return Some("denylisted".to_string());
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|net| net.contains(&ip)) {
+            return Some("not allowlisted".to_string());
+        }
+
+        #[cfg(feature = "geoip")]
+        if let Some(geo) = &self.geo {
+            let country = geo
+                .reader
+                .lookup::<maxminddb::geoip2::Country>(ip)
+                .ok()
+                .flatten()
+                .and_then(|record| record.country)
+                .and_then(|country| country.iso_code);
+            if let Some(code) = country
+                && geo.blocked.iter().any(|blocked| blocked == code)
+            {
+                return Some(format!("country {}", code));
+            }
+        }
+
+        None
+    }
+}
+
+/// Middleware that rejects requests from blocked addresses
+pub async fn ip_guard(
+    State(filter): State<Arc<IpFilter>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let ip = filter.client_ip(peer.ip(), req.headers());
+
+    match filter.check(ip) {
+        None => next.run(req).await,
+        Some(reason) => {
+            tracing::warn!(
+                target: "audit",
+                ip = %ip,
+                method = %req.method(),
+                path = %req.uri().path(),
+                reason = %reason,
+                "Blocked request"
+            );
+            ApiError::forbidden("Access from your network is not allowed").into_response()
+        }
+    }
+}
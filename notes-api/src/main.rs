@@ -12,6 +12,8 @@ use tower_sessions::{Expiry, SessionManagerLayer};
 
 use axum::Router;
 
+use notes_domain::InstanceSettingsService;
+use notes_infra::factory::build_session_store;
 use notes_infra::run_migrations;
 
 mod auth;
@@ -19,10 +21,17 @@ mod config;
 mod dto;
 mod error;
 mod extractors;
+mod guards;
+mod ip_filter;
+mod maintenance;
 mod routes;
+mod services;
 mod state;
+mod validation;
+mod versioning;
 
 use config::Config;
+use services::ServiceRegistry;
 use state::AppState;
 
 use crate::config::AuthMode;
@@ -43,73 +52,38 @@ async fn main() -> anyhow::Result<()> {
         acquire_timeout: StdDuration::from_secs(30),
     };
 
-    let db_pool = k_core::db::connect(&db_config).await?;
-
-    run_migrations(&db_pool).await?;
-
-    #[cfg(feature = "smart-features")]
-    use notes_infra::factory::build_link_repository;
-    use notes_infra::factory::{
-        build_note_repository, build_session_store, build_tag_repository, build_user_repository,
-    };
-
-    // Create repositories via factory
-    let note_repo = build_note_repository(&db_pool)
-        .await
-        .map_err(|e| anyhow::anyhow!(e))?;
-    let tag_repo = build_tag_repository(&db_pool)
-        .await
-        .map_err(|e| anyhow::anyhow!(e))?;
-    let user_repo = build_user_repository(&db_pool)
-        .await
-        .map_err(|e| anyhow::anyhow!(e))?;
-    #[cfg(feature = "smart-features")]
-    let link_repo = build_link_repository(&db_pool)
-        .await
-        .map_err(|e| anyhow::anyhow!(e))?;
-
-    // Connect to message broker via factory
-    #[cfg(feature = "smart-features")]
-    let message_broker = {
-        use notes_infra::factory::{BrokerProvider, build_message_broker};
-        tracing::info!("Connecting to message broker: {}", config.broker_url);
-        let provider = BrokerProvider::Nats {
-            url: config.broker_url.clone(),
-        };
-        build_message_broker(&provider)
-            .await
-            .map_err(|e| anyhow::anyhow!("Broker connection failed: {}", e))?
-    };
-
-    // Create services
-    use notes_domain::{NoteService, TagService, UserService};
-
-    // Build NoteService with optional MessageBroker
-    #[cfg(feature = "smart-features")]
-    let note_service = match message_broker {
-        Some(broker) => Arc::new(
-            NoteService::new(note_repo.clone(), tag_repo.clone()).with_message_broker(broker),
-        ),
-        None => Arc::new(NoteService::new(note_repo.clone(), tag_repo.clone())),
+    let db_pool = notes_infra::db::connect(&db_config).await?;
+
+    let read_pool = match config.database_read_url {
+        Some(ref url) => {
+            tracing::info!("Connecting to read replica: {}", url);
+            let replica_config = k_core::db::DatabaseConfig {
+                url: url.clone(),
+                max_connections: db_config.max_connections,
+                min_connections: db_config.min_connections,
+                acquire_timeout: db_config.acquire_timeout,
+            };
+            Some(notes_infra::db::connect_replica(&replica_config).await?)
+        }
+        None => None,
     };
-    #[cfg(not(feature = "smart-features"))]
-    let note_service = Arc::new(NoteService::new(note_repo.clone(), tag_repo.clone()));
 
-    let tag_service = Arc::new(TagService::new(tag_repo.clone()));
-    let user_service = Arc::new(UserService::new(user_repo.clone()));
+    run_migrations(&db_pool).await?;
+    #[cfg(feature = "sqlite")]
+    notes_infra::search_index::ensure_search_tokenizer(&db_pool, config.search_tokenizer).await?;
+
+    let services = ServiceRegistry::build(&config, &db_pool, read_pool.as_ref()).await?;
+    services.maintenance.clone().spawn_refresh();
+    spawn_settings_refresh(services.settings.clone());
+    // Jobs run inside the API process, so any still marked running were cut
+    // off by the previous shutdown
+    let interrupted = services.jobs.fail_interrupted().await?;
+    if interrupted > 0 {
+        tracing::warn!("Marked {} interrupted jobs as failed", interrupted);
+    }
 
     // Create application state
-    let state = AppState::new(
-        note_repo,
-        tag_repo,
-        #[cfg(feature = "smart-features")]
-        link_repo,
-        note_service,
-        tag_service,
-        user_service,
-        config.clone(),
-    )
-    .await?;
+    let state = AppState::new(services, config.clone()).await?;
 
     // Build session store (needed for OIDC flow even in JWT mode)
     let session_store = build_session_store(&db_pool)
@@ -131,7 +105,7 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // Build the app with appropriate auth layers based on config
-    let app = build_app(state, session_layer, user_repo, &config).await?;
+    let app = build_app(state, session_layer, &config).await?;
     let app = apply_standard_middleware(app, &server_config);
 
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
@@ -139,23 +113,58 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("🚀 API server running at http://{}", addr);
     log_auth_info(&config);
-    tracing::info!("📝 API endpoints available at /api/v1/...");
+    tracing::info!("📝 API endpoints available at /api/v1/... and /api/v2/...");
 
-    axum::serve(listener, app).await?;
+    // Peer addresses are needed for IP filtering
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
 
+/// Periodically re-read the instance settings to pick up changes made
+/// through other API instances
+fn spawn_settings_refresh(settings: Arc<InstanceSettingsService>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(maintenance::REFRESH_INTERVAL);
+        // The first tick completes immediately and the settings were just loaded
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = settings.refresh().await {
+                tracing::warn!("Failed to refresh instance settings: {}", e);
+            }
+        }
+    });
+}
+
 /// Build the application router with appropriate auth layers
-#[allow(unused_variables)] // config/user_repo used conditionally based on features
+#[allow(unused_variables)] // config used conditionally based on features
 async fn build_app(
     state: AppState,
     session_layer: SessionManagerLayer<notes_infra::session_store::InfraSessionStore>,
-    user_repo: std::sync::Arc<dyn notes_domain::UserRepository>,
     config: &Config,
 ) -> anyhow::Result<Router> {
+    let user_service = state.services.users.clone();
     let app = Router::new()
-        .nest("/api/v1", routes::api_v1_router())
+        .nest(
+            versioning::V1_PREFIX,
+            routes::api_v1_router(&state).layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                versioning::deprecation_headers,
+            )),
+        )
+        .nest(versioning::V2_PREFIX, routes::api_v2_router(&state))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            maintenance::read_only_guard,
+        ))
+        .layer(axum::extract::DefaultBodyLimit::max(
+            config.max_upload_bytes,
+        ))
         .with_state(state);
 
     // When auth-axum-login feature is enabled, always apply the auth layer.
@@ -164,17 +173,37 @@ async fn build_app(
     // 2. Session-based login/register routes use it
     // 3. The "JWT mode" just changes what the login endpoint returns, not the underlying session support
     #[cfg(feature = "auth-axum-login")]
-    {
-        let auth_layer = auth::setup_auth_layer(session_layer, user_repo).await?;
-        return Ok(app.layer(auth_layer));
-    }
+    let app = app.layer(auth::setup_auth_layer(session_layer, user_service).await?);
 
     // When auth-axum-login is not compiled in, just use session layer for OIDC flow
     #[cfg(not(feature = "auth-axum-login"))]
-    {
-        let _ = user_repo; // Suppress unused warning
-        Ok(app.layer(session_layer))
-    }
+    let app = {
+        let _ = user_service; // Suppress unused warning
+        app.layer(session_layer)
+    };
+
+    // Token clients are told how to authenticate when they get a 401
+    #[cfg(feature = "auth-jwt")]
+    let app = if matches!(config.auth_mode, AuthMode::Jwt | AuthMode::Both) {
+        app.layer(axum::middleware::from_fn(auth::bearer_challenge))
+    } else {
+        app
+    };
+
+    // Added after the other guards so blocked clients are turned away before
+    // authentication
+    let ip_filter = ip_filter::IpFilter::from_config(config)?;
+    let app = if ip_filter.is_enabled() {
+        app.layer(axum::middleware::from_fn_with_state(
+            Arc::new(ip_filter),
+            ip_filter::ip_guard,
+        ))
+    } else {
+        app
+    };
+
+    // Outermost, so the errors of every guard get their version's shape
+    Ok(app.layer(axum::middleware::from_fn(versioning::compat_shim)))
 }
 
 /// Log authentication info based on enabled features and config
@@ -0,0 +1,113 @@
+//! Read-only maintenance mode
+//!
+//! While enabled, safe requests (GET/HEAD/OPTIONS) keep working and every
+//! mutation is rejected with 503. The flag is persisted in the database so
+//! other API instances and the worker pick it up as well.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, Method, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use notes_domain::{DomainResult, InstanceSettingsRepository};
+
+use crate::error::ApiError;
+use crate::state::AppState;
+use crate::versioning::endpoint_path;
+
+/// How often the persisted flag is re-read (changes made by other instances)
+pub(crate) const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Paths that stay writable while read-only, in any API version (prefix match).
+/// Signing image URLs changes nothing but is needed to read notes.
+const EXEMPT_PATHS: &[&str] = &[
+    "/admin/",
+    "/auth/login",
+    "/auth/logout",
+    "/proxy/image/sign",
+];
+
+const READ_ONLY_MESSAGE: &str = "K-Notes is in read-only maintenance mode. Your notes are safe; please try again in a few minutes.";
+
+/// Cached maintenance flag backed by the instance settings repository
+pub struct MaintenanceMode {
+    repo: Arc<dyn InstanceSettingsRepository>,
+    read_only: AtomicBool,
+}
+
+impl MaintenanceMode {
+    /// Load the persisted flag, overriding it with `initial` if configured.
+    pub async fn load(
+        repo: Arc<dyn InstanceSettingsRepository>,
+        initial: Option<bool>,
+    ) -> DomainResult<Self> {
+        let read_only = match initial {
+            Some(read_only) => {
+                repo.set_read_only(read_only).await?;
+                read_only
+            }
+            None => repo.is_read_only().await?,
+        };
+
+        if read_only {
+            tracing::warn!("🚧 Starting in read-only maintenance mode");
+        }
+
+        Ok(Self {
+            repo,
+            read_only: AtomicBool::new(read_only),
+        })
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    /// Persist a new flag value and apply it to this instance immediately
+    pub async fn set_read_only(&self, read_only: bool) -> DomainResult<()> {
+        self.repo.set_read_only(read_only).await?;
+        self.read_only.store(read_only, Ordering::Relaxed);
+        tracing::warn!(
+            "Read-only maintenance mode {}",
+            if read_only { "enabled" } else { "disabled" }
+        );
+        Ok(())
+    }
+
+    /// Periodically re-read the persisted flag
+    pub fn spawn_refresh(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                match self.repo.is_read_only().await {
+                    Ok(read_only) => self.read_only.store(read_only, Ordering::Relaxed),
+                    Err(e) => tracing::warn!("Failed to refresh maintenance mode: {}", e),
+                }
+            }
+        });
+    }
+}
+
+/// Middleware that rejects mutations while in read-only mode
+pub async fn read_only_guard(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let is_safe = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let endpoint = endpoint_path(req.uri().path());
+    let is_exempt = EXEMPT_PATHS.iter().any(|path| endpoint.starts_with(path));
+
+    if is_safe || is_exempt || !state.services.maintenance.is_read_only() {
+        return next.run(req).await;
+    }
+
+    let mut response = ApiError::ServiceUnavailable(READ_ONLY_MESSAGE.to_string()).into_response();
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from_static("120"));
+    response
+}
@@ -0,0 +1,27 @@
+//! Activity feed route handlers
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+
+use crate::dto::{ActivityQuery, ActivityResponse};
+use crate::error::ApiResult;
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+
+/// The current user's recent actions, newest first
+/// GET /api/v1/activity?before=&limit=50
+pub async fn list_activity(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<ActivityQuery>,
+) -> ApiResult<Json<ActivityResponse>> {
+    let page = state
+        .services
+        .activity
+        .feed(user.id, query.before, query.limit)
+        .await?;
+
+    Ok(Json(ActivityResponse::from(page)))
+}
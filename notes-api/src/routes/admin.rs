@@ -0,0 +1,170 @@
+//! Admin route handlers
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use chrono::{Duration, Utc};
+use notes_domain::overview::{daily_series, overview_window};
+
+use crate::dto::{
+    AdminOverviewQuery, AdminOverviewResponse, HousekeepingReportResponse,
+    InstanceSettingsResponse, MaintenanceResponse, NoteCountsResponse, StorageStatsResponse,
+    StorageUsageResponse, UpdateInstanceSettingsRequest, UpdateMaintenanceRequest,
+};
+use crate::error::ApiResult;
+use crate::extractors::AdminUser;
+use crate::state::AppState;
+
+/// Get maintenance mode status
+/// GET /api/v1/admin/maintenance
+pub async fn get_maintenance(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> ApiResult<Json<MaintenanceResponse>> {
+    Ok(Json(MaintenanceResponse {
+        read_only: state.services.maintenance.is_read_only(),
+    }))
+}
+
+/// Enable or disable read-only maintenance mode
+/// PUT /api/v1/admin/maintenance
+pub async fn set_maintenance(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Json(payload): Json<UpdateMaintenanceRequest>,
+) -> ApiResult<Json<MaintenanceResponse>> {
+    tracing::info!(admin_id = %admin.id, read_only = payload.read_only, "Changing maintenance mode");
+    state
+        .services
+        .maintenance
+        .set_read_only(payload.read_only)
+        .await?;
+
+    Ok(Json(MaintenanceResponse {
+        read_only: payload.read_only,
+    }))
+}
+
+/// Get the instance settings in effect
+/// GET /api/v1/admin/settings
+pub async fn get_settings(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> ApiResult<Json<InstanceSettingsResponse>> {
+    Ok(Json(InstanceSettingsResponse::new(
+        state.services.settings.current(),
+        state.services.maintenance.is_read_only(),
+    )))
+}
+
+/// Change instance settings without a restart
+/// PATCH /api/v1/admin/settings
+pub async fn update_settings(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Json(payload): Json<UpdateInstanceSettingsRequest>,
+) -> ApiResult<Json<InstanceSettingsResponse>> {
+    tracing::info!(admin_id = %admin.id, ?payload, "Changing instance settings");
+    let settings = state
+        .services
+        .settings
+        .update(&payload.settings_update())
+        .await?;
+    if let Some(read_only) = payload.read_only {
+        state.services.maintenance.set_read_only(read_only).await?;
+    }
+
+    Ok(Json(InstanceSettingsResponse::new(
+        settings,
+        state.services.maintenance.is_read_only(),
+    )))
+}
+
+/// Get storage reclaimed by the trash purge job
+/// GET /api/v1/admin/stats
+pub async fn get_storage_stats(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> ApiResult<Json<StorageStatsResponse>> {
+    let stats = state.services.instance_settings.storage_stats().await?;
+
+    Ok(Json(StorageStatsResponse::from(stats)))
+}
+
+/// Get instance-wide metrics for the admin dashboard
+/// GET /api/v1/admin/overview
+pub async fn get_overview(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<AdminOverviewQuery>,
+) -> ApiResult<Json<AdminOverviewResponse>> {
+    let window_days = overview_window(query.days)?;
+    let now = Utc::now();
+    // The window covers today and the `window_days - 1` full days before it
+    let from = now.date_naive() - Duration::days(i64::from(window_days) - 1);
+    let since = from.and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+    let metrics = state
+        .services
+        .instance_settings
+        .database_metrics(since)
+        .await?;
+    let trash = state.services.instance_settings.storage_stats().await?;
+
+    // The dashboard should still load when the vector store is down
+    let vector_points = match &state.services.vector_store {
+        Some(store) => match store.count().await {
+            Ok(count) => Some(count),
+            Err(e) => {
+                tracing::warn!("Failed to count vectors: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(Json(AdminOverviewResponse {
+        generated_at: now,
+        window_days,
+        users: metrics.users.into(),
+        notes: NoteCountsResponse {
+            total: metrics.notes,
+            trashed: metrics.trashed_notes,
+            created_per_day: daily_series(&metrics.notes_created, from, now.date_naive())
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        },
+        storage: StorageUsageResponse {
+            database_bytes: metrics.database_bytes,
+            content_bytes: metrics.content_bytes,
+            trash: trash.into(),
+        },
+        jobs: metrics.jobs.into(),
+        vector_points,
+    }))
+}
+
+/// Count leftovers in the instance's data that housekeeping would fix
+/// GET /api/v1/admin/housekeeping
+pub async fn get_housekeeping(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> ApiResult<Json<HousekeepingReportResponse>> {
+    let report = state.services.housekeeping.report().await?;
+
+    Ok(Json(HousekeepingReportResponse::from(report)))
+}
+
+/// Fix every kind of leftover, returning how many of each were fixed
+/// POST /api/v1/admin/housekeeping/run
+pub async fn run_housekeeping(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+) -> ApiResult<Json<HousekeepingReportResponse>> {
+    tracing::info!(admin_id = %admin.id, "Running housekeeping");
+    let report = state.services.housekeeping.run().await?;
+
+    Ok(Json(HousekeepingReportResponse::from(report)))
+}
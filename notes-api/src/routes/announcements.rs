@@ -0,0 +1,106 @@
+//! Announcement route handlers
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use uuid::Uuid;
+
+use crate::dto::{AnnouncementRequest, AnnouncementResponse};
+use crate::error::ApiResult;
+use crate::extractors::{AdminUser, CurrentUser};
+use crate::state::AppState;
+
+/// List active announcements the current user has not dismissed
+/// GET /api/v1/announcements
+pub async fn list_announcements(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> ApiResult<Json<Vec<AnnouncementResponse>>> {
+    let announcements = state.services.announcements.list_active(user.id).await?;
+
+    Ok(Json(
+        announcements
+            .into_iter()
+            .map(AnnouncementResponse::from)
+            .collect(),
+    ))
+}
+
+/// Hide an announcement for the current user
+/// POST /api/v1/announcements/:id/dismiss
+pub async fn dismiss_announcement(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    state.services.announcements.dismiss(id, user.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List all announcements, including scheduled and expired ones
+/// GET /api/v1/admin/announcements
+pub async fn list_all_announcements(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> ApiResult<Json<Vec<AnnouncementResponse>>> {
+    let announcements = state.services.announcements.list_all().await?;
+
+    Ok(Json(
+        announcements
+            .into_iter()
+            .map(AnnouncementResponse::from)
+            .collect(),
+    ))
+}
+
+/// Post an announcement
+/// POST /api/v1/admin/announcements
+pub async fn create_announcement(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Json(payload): Json<AnnouncementRequest>,
+) -> ApiResult<(StatusCode, Json<AnnouncementResponse>)> {
+    let announcement = state
+        .services
+        .announcements
+        .create(admin.id, payload.into())
+        .await?;
+    tracing::info!(admin_id = %admin.id, announcement_id = %announcement.id, "Posted announcement");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AnnouncementResponse::from(announcement)),
+    ))
+}
+
+/// Replace an announcement's content and schedule
+/// PUT /api/v1/admin/announcements/:id
+pub async fn update_announcement(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AnnouncementRequest>,
+) -> ApiResult<Json<AnnouncementResponse>> {
+    let announcement = state
+        .services
+        .announcements
+        .update(id, payload.into())
+        .await?;
+
+    Ok(Json(AnnouncementResponse::from(announcement)))
+}
+
+/// Delete an announcement
+/// DELETE /api/v1/admin/announcements/:id
+pub async fn delete_announcement(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    state.services.announcements.delete(id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -3,6 +3,8 @@
 //! Provides login, register, logout, and token endpoints.
 //! Supports both session-based and JWT-based authentication.
 
+#[cfg(feature = "auth-jwt")]
+use axum::extract::Query;
 #[cfg(feature = "auth-oidc")]
 use axum::response::Response;
 use axum::{
@@ -12,17 +14,22 @@ use axum::{
     response::IntoResponse,
     routing::{get, post},
 };
+#[cfg(feature = "auth-jwt")]
+use notes_domain::scopes::Scopes;
+#[cfg(feature = "auth-jwt")]
+use serde::Deserialize;
 use serde::Serialize;
 #[cfg(feature = "auth-oidc")]
 use tower_sessions::Session;
 
 #[cfg(feature = "auth-axum-login")]
-use crate::config::AuthMode;
+use crate::config::{AuthMode, RegistrationMode};
 use crate::{
-    dto::{LoginRequest, RegisterRequest, UserResponse},
+    dto::{ChallengeResponse, LoginRequest, RegisterRequest, UserResponse, accepted_versions},
     error::ApiError,
     extractors::CurrentUser,
     state::AppState,
+    validation::ValidatedJson,
 };
 #[cfg(feature = "auth-axum-login")]
 use notes_domain::DomainError;
@@ -35,6 +42,14 @@ pub struct TokenResponse {
     pub expires_in: u64,
 }
 
+/// Query parameters for requesting a JWT token
+#[cfg(feature = "auth-jwt")]
+#[derive(Debug, Deserialize)]
+pub struct TokenQuery {
+    /// Comma-separated scopes to limit the token to (full access if omitted)
+    pub scope: Option<String>,
+}
+
 /// Login response that can be either a user (session mode) or a token (JWT mode)
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
@@ -47,6 +62,7 @@ pub fn router() -> Router<AppState> {
     let r = Router::new()
         .route("/login", post(login))
         .route("/register", post(register))
+        .route("/challenge", get(get_challenge))
         .route("/logout", post(logout))
         .route("/me", get(me));
 
@@ -71,7 +87,7 @@ pub fn router() -> Router<AppState> {
 async fn login(
     State(state): State<AppState>,
     mut auth_session: crate::auth::AuthSession,
-    Json(payload): Json<LoginRequest>,
+    ValidatedJson(payload): ValidatedJson<LoginRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     let user = match auth_session
         .authenticate(crate::auth::Credentials {
@@ -85,6 +101,14 @@ async fn login(
         None => return Err(ApiError::Validation("Invalid credentials".to_string())),
     };
 
+    if !payload.accepted_documents.is_empty() {
+        state
+            .services
+            .legal
+            .accept(user.0.id, &accepted_versions(&payload.accepted_documents))
+            .await?;
+    }
+
     let auth_mode = state.config.auth_mode;
 
     // In session or both mode, create session
@@ -112,11 +136,7 @@ async fn login(
     // Session mode: return user info
     Ok((
         StatusCode::OK,
-        Json(LoginResponse::User(UserResponse {
-            id: user.0.id,
-            email: user.0.email,
-            created_at: user.0.created_at,
-        })),
+        Json(LoginResponse::User(UserResponse::from(user.0))),
     ))
 }
 
@@ -126,7 +146,7 @@ async fn login(
 #[cfg(not(feature = "auth-axum-login"))]
 async fn login(
     State(_state): State<AppState>,
-    Json(_payload): Json<LoginRequest>,
+    ValidatedJson(_payload): ValidatedJson<LoginRequest>,
 ) -> Result<(StatusCode, Json<LoginResponse>), ApiError> {
     Err(ApiError::Internal(
         "Password-based login not available. auth-axum-login feature is required. Use OIDC login at /api/v1/auth/login/oidc instead.".to_string(),
@@ -138,13 +158,36 @@ async fn login(
 async fn register(
     State(state): State<AppState>,
     mut auth_session: crate::auth::AuthSession,
-    Json(payload): Json<RegisterRequest>,
+    ValidatedJson(payload): ValidatedJson<RegisterRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    if !state.services.settings.current().allow_registration {
+        return Err(ApiError::forbidden(
+            "Registration is disabled on this instance",
+        ));
+    }
+
+    require_challenge(&state, payload.challenge_response.as_deref()).await?;
+
+    let accepted = accepted_versions(&payload.accepted_documents);
+    state.services.legal.check_accepted(&accepted).await?;
+
+    let invitation = match state.config.registration_mode {
+        RegistrationMode::Open => None,
+        RegistrationMode::Invite => {
+            let code = payload
+                .invite_code
+                .as_deref()
+                .ok_or_else(|| ApiError::forbidden("An invitation code is required to register"))?;
+            Some(state.services.invitations.check_code(code).await?)
+        }
+    };
+
     // Email is already validated by the newtype deserialization
     let email = payload.email;
 
     if state
-        .user_service
+        .services
+        .users
         .find_by_email(email.as_ref())
         .await?
         .is_some()
@@ -154,15 +197,33 @@ async fn register(
         )));
     }
 
-    // Hash password
-    let password_hash = notes_infra::auth::axum_login::hash_password(payload.password.as_ref());
-
-    // Create user with password
+    // Create user with password (hashed by the service)
     let user = state
-        .user_service
-        .create_local(email.as_ref(), &password_hash)
+        .services
+        .users
+        .create_local(email.as_ref(), &payload.password)
         .await?;
 
+    if let Some(invitation) = invitation
+        && let Err(e) = state
+            .services
+            .invitations
+            .redeem(&invitation, user.id)
+            .await
+    {
+        // Another registration took the last use since the code was checked
+        if let Err(e) = state.services.users.delete_user(user.id).await {
+            tracing::error!(user_id = %user.id, "Failed to remove uninvited user: {}", e);
+        }
+        return Err(e.into());
+    }
+
+    // The versions were checked above, so this only fails if a document was
+    // published meanwhile, which the user is then asked to accept
+    if let Err(e) = state.services.legal.accept(user.id, &accepted).await {
+        tracing::warn!(user_id = %user.id, "Failed to record legal consent: {}", e);
+    }
+
     let auth_mode = state.config.auth_mode;
 
     // In session or both mode, create session
@@ -190,25 +251,54 @@ async fn register(
 
     Ok((
         StatusCode::CREATED,
-        Json(LoginResponse::User(UserResponse {
-            id: user.id,
-            email: user.email,
-            created_at: user.created_at,
-        })),
+        Json(LoginResponse::User(UserResponse::from(user))),
     ))
 }
 
+/// Reject the request unless it carries a solved challenge, when a
+/// challenge provider is configured
+#[cfg(feature = "auth-axum-login")]
+async fn require_challenge(state: &AppState, response: Option<&str>) -> Result<(), ApiError> {
+    let Some(challenge) = &state.services.challenge else {
+        return Ok(());
+    };
+    let response = response
+        .filter(|r| !r.is_empty())
+        .ok_or_else(|| ApiError::forbidden("A challenge response is required"))?;
+
+    if !challenge.verify(response).await? {
+        return Err(ApiError::forbidden("Challenge verification failed"));
+    }
+    Ok(())
+}
+
 /// Fallback register when auth-axum-login is not enabled
 #[cfg(not(feature = "auth-axum-login"))]
 async fn register(
     State(_state): State<AppState>,
-    Json(_payload): Json<RegisterRequest>,
+    ValidatedJson(_payload): ValidatedJson<RegisterRequest>,
 ) -> Result<(StatusCode, Json<LoginResponse>), ApiError> {
     Err(ApiError::Internal(
         "Session-based registration not available. Use JWT token endpoint.".to_string(),
     ))
 }
 
+/// Issue a proof-of-work challenge for the register form
+///
+/// Only the `pow` provider issues its own challenges; hosted captchas are
+/// solved in their widget.
+async fn get_challenge(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let ticket = match &state.services.challenge {
+        Some(challenge) => challenge.issue().await?,
+        None => None,
+    };
+    let ticket = ticket.ok_or_else(|| {
+        ApiError::Validation("This instance does not issue challenges".to_string())
+    })?;
+
+    Ok(Json(ChallengeResponse::from(ticket)))
+}
+
 /// Logout endpoint
 #[cfg(feature = "auth-axum-login")]
 async fn logout(mut auth_session: crate::auth::AuthSession) -> impl IntoResponse {
@@ -228,22 +318,32 @@ async fn logout() -> impl IntoResponse {
 
 /// Get current user info
 async fn me(CurrentUser(user): CurrentUser) -> Result<impl IntoResponse, ApiError> {
-    Ok(Json(UserResponse {
-        id: user.id,
-        email: user.email,
-        created_at: user.created_at,
-    }))
+    Ok(Json(UserResponse::from(user)))
 }
 
 /// Get a JWT token for the current session user
 ///
 /// This allows session-authenticated users to obtain a JWT for API access.
+/// `?scope=notes:read,export` limits the token to those scopes, for example
+/// for a read-only widget.
 #[cfg(feature = "auth-jwt")]
 async fn get_token(
     State(state): State<AppState>,
     CurrentUser(user): CurrentUser,
+    Query(query): Query<TokenQuery>,
 ) -> Result<impl IntoResponse, ApiError> {
-    let token = create_jwt_for_user(&user, &state)?;
+    let token = match query.scope {
+        Some(ref scope) => {
+            let scopes = Scopes::parse(scope)?;
+            state
+                .jwt_validator
+                .as_ref()
+                .ok_or_else(|| ApiError::Internal("JWT not configured".to_string()))?
+                .create_scoped_token(&user, &scopes)
+                .map_err(|e| ApiError::Internal(format!("Failed to create token: {}", e)))?
+        }
+        None => create_jwt_for_user(&user, &state)?,
+    };
 
     Ok(Json(TokenResponse {
         access_token: token,
@@ -358,7 +458,8 @@ async fn oidc_callback(
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
     let user = state
-        .user_service
+        .services
+        .users
         .find_or_create(&oidc_user.subject, &oidc_user.email)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
@@ -445,7 +546,8 @@ async fn oidc_callback(
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
     let user = state
-        .user_service
+        .services
+        .users
         .find_or_create(&oidc_user.subject, &oidc_user.email)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
@@ -0,0 +1,158 @@
+//! Kanban board route handlers
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use uuid::Uuid;
+
+use notes_domain::{
+    BoardColumnRequest as DomainBoardColumn, BoardRequest as DomainBoardRequest, TagName,
+    boards::ColumnSource,
+};
+
+use crate::dto::{
+    BoardCardsResponse, BoardColumnCardsResponse, BoardRequest, BoardResponse, MoveCardRequest,
+    NoteResponse,
+};
+use crate::error::{ApiError, ApiResult};
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+
+/// Turn the request's columns into tag or status sources
+fn parse_board_request(req: BoardRequest) -> ApiResult<DomainBoardRequest> {
+    let columns = req
+        .columns
+        .into_iter()
+        .map(|column| {
+            let source = match (column.tag, column.status) {
+                (Some(tag), None) => ColumnSource::Tag(
+                    TagName::try_from(tag)
+                        .map_err(|e| ApiError::validation(format!("Invalid tag: {}", e)))?,
+                ),
+                (None, Some(status)) => ColumnSource::Status(status),
+                _ => {
+                    return Err(ApiError::validation(format!(
+                        "Column \"{}\" needs either a tag or a status",
+                        column.name
+                    )));
+                }
+            };
+            Ok(DomainBoardColumn {
+                id: column.id,
+                name: column.name,
+                source,
+            })
+        })
+        .collect::<ApiResult<Vec<_>>>()?;
+
+    Ok(DomainBoardRequest {
+        name: req.name,
+        columns,
+    })
+}
+
+/// List the current user's boards
+/// GET /api/v1/boards
+pub async fn list_boards(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> ApiResult<Json<Vec<BoardResponse>>> {
+    let boards = state.services.boards.list(user.id).await?;
+
+    Ok(Json(boards.into_iter().map(BoardResponse::from).collect()))
+}
+
+/// Create a board
+/// POST /api/v1/boards
+pub async fn create_board(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(payload): Json<BoardRequest>,
+) -> ApiResult<(StatusCode, Json<BoardResponse>)> {
+    let board = state
+        .services
+        .boards
+        .create(user.id, parse_board_request(payload)?)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(BoardResponse::from(board))))
+}
+
+/// Get a board
+/// GET /api/v1/boards/:id
+pub async fn get_board(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<BoardResponse>> {
+    let board = state.services.boards.get(id, user.id).await?;
+
+    Ok(Json(BoardResponse::from(board)))
+}
+
+/// Replace a board's name and columns
+/// PUT /api/v1/boards/:id
+pub async fn update_board(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<BoardRequest>,
+) -> ApiResult<Json<BoardResponse>> {
+    let board = state
+        .services
+        .boards
+        .update(id, user.id, parse_board_request(payload)?)
+        .await?;
+
+    Ok(Json(BoardResponse::from(board)))
+}
+
+/// Delete a board, keeping its notes
+/// DELETE /api/v1/boards/:id
+pub async fn delete_board(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    state.services.boards.delete(id, user.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List a board's notes grouped by column
+/// GET /api/v1/boards/:id/notes
+pub async fn list_board_notes(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<BoardCardsResponse>> {
+    let (board, columns) = state.services.boards.cards(id, user.id).await?;
+
+    Ok(Json(BoardCardsResponse {
+        id: board.id,
+        name: board.name,
+        columns: columns
+            .into_iter()
+            .map(BoardColumnCardsResponse::from)
+            .collect(),
+    }))
+}
+
+/// Move a note to another column, updating its tags and status together
+/// POST /api/v1/boards/:id/move
+pub async fn move_card(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<MoveCardRequest>,
+) -> ApiResult<Json<NoteResponse>> {
+    let note = state
+        .services
+        .boards
+        .move_note(id, user.id, payload.note_id, payload.column_id)
+        .await?;
+
+    Ok(Json(NoteResponse::from(note)))
+}
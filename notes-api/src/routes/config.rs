@@ -2,19 +2,59 @@
 
 use axum::{Json, extract::State};
 
-use crate::dto::ConfigResponse;
+use crate::dto::{
+    ChallengeConfigResponse, ConfigResponse, NoteDefaultsResponse, OidcProviderResponse,
+};
 use crate::error::ApiResult;
 use crate::state::AppState;
 
 /// Get system configuration
+///
+/// Advertises the capabilities this server was built and configured with, so
+/// clients can adapt without knowing its feature flags.
 pub async fn get_config(State(state): State<AppState>) -> ApiResult<Json<ConfigResponse>> {
+    #[cfg(feature = "auth-oidc")]
+    let oidc_providers: Vec<OidcProviderResponse> =
+        match (&state.oidc_service, &state.config.oidc_issuer) {
+            (Some(_), Some(issuer)) => vec![OidcProviderResponse {
+                id: "oidc",
+                issuer: issuer.clone(),
+                login_url: "/api/v1/auth/login/oidc",
+            }],
+            _ => Vec::new(),
+        };
+    #[cfg(not(feature = "auth-oidc"))]
+    let oidc_providers: Vec<OidcProviderResponse> = Vec::new();
+
+    #[cfg(feature = "auth-jwt")]
+    let jwt_enabled = state.jwt_validator.is_some();
+    #[cfg(not(feature = "auth-jwt"))]
+    let jwt_enabled = false;
+
+    let settings = state.services.settings.current();
+
     Ok(Json(ConfigResponse {
-        allow_registration: state.config.allow_registration,
+        version: env!("CARGO_PKG_VERSION"),
+        allow_registration: settings.allow_registration,
+        registration_mode: state.config.registration_mode,
         auth_mode: state.config.auth_mode,
-        #[cfg(feature = "auth-oidc")]
-        oidc_enabled: state.oidc_service.is_some(),
-        #[cfg(not(feature = "auth-oidc"))]
-        oidc_enabled: false,
+        oidc_enabled: !oidc_providers.is_empty(),
+        oidc_providers,
         password_login_enabled: cfg!(feature = "auth-axum-login"),
+        jwt_enabled,
+        smart_features: cfg!(feature = "smart-features") && settings.smart_features_enabled,
+        attachments: false,
+        max_upload_bytes: state.config.max_upload_bytes,
+        read_only: state.services.maintenance.is_read_only(),
+        image_proxy: state.services.images.is_some(),
+        note_defaults: NoteDefaultsResponse::from(settings),
+        challenge: state
+            .services
+            .challenge
+            .as_ref()
+            .map(|challenge| ChallengeConfigResponse {
+                provider: challenge.provider(),
+                site_key: challenge.site_key().map(str::to_string),
+            }),
     }))
 }
@@ -0,0 +1,57 @@
+//! Note graph route handlers
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+
+use notes_domain::{
+    NoteFilter,
+    graph::{DEFAULT_GRAPH_DEPTH, GraphOptions, MAX_GRAPH_DEPTH, NoteGraph},
+};
+
+use crate::dto::{GraphQuery, GraphResponse};
+use crate::error::{ApiError, ApiResult};
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+
+/// Get the graph of the user's notes (wiki-links, relations and semantic links)
+/// GET /api/v1/graph?root=&depth=&min_score=
+pub async fn get_graph(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<GraphQuery>,
+) -> ApiResult<Json<GraphResponse>> {
+    let depth = query.depth.unwrap_or(DEFAULT_GRAPH_DEPTH);
+    if depth > MAX_GRAPH_DEPTH {
+        return Err(ApiError::validation(format!(
+            "depth must be at most {}",
+            MAX_GRAPH_DEPTH
+        )));
+    }
+    if query.min_score.is_some_and(|s| !(0.0..=1.0).contains(&s)) {
+        return Err(ApiError::validation("min_score must be between 0 and 1"));
+    }
+
+    let notes = state
+        .services
+        .notes
+        .list_notes(user.id, NoteFilter::new())
+        .await?;
+
+    #[cfg(feature = "smart-features")]
+    let semantic_links = state.services.links.get_links_for_user(user.id).await?;
+    #[cfg(not(feature = "smart-features"))]
+    let semantic_links = Vec::new();
+
+    let relations = state.services.relations.list(user.id).await?;
+
+    let options = GraphOptions {
+        root: query.root,
+        depth,
+        min_score: query.min_score,
+    };
+    let graph = NoteGraph::build(&notes, &semantic_links, &relations, options);
+
+    Ok(Json(GraphResponse::from(graph)))
+}
@@ -0,0 +1,75 @@
+//! Impersonation route handlers for administrators
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use uuid::Uuid;
+
+use crate::dto::{ImpersonationListQuery, ImpersonationResponse, StartImpersonationRequest};
+use crate::error::ApiResult;
+use crate::extractors::AdminUser;
+use crate::state::AppState;
+
+/// List the latest impersonations by any administrator, newest first
+/// GET /api/v1/admin/impersonations?limit=
+pub async fn list_impersonations(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<ImpersonationListQuery>,
+) -> ApiResult<Json<Vec<ImpersonationResponse>>> {
+    let impersonations = state
+        .services
+        .impersonations
+        .list_recent(query.limit)
+        .await?;
+
+    Ok(Json(
+        impersonations
+            .into_iter()
+            .map(ImpersonationResponse::from)
+            .collect(),
+    ))
+}
+
+/// Start impersonating a user; requests with `X-Impersonate-User` are then
+/// served as that user until it expires
+/// POST /api/v1/admin/impersonations
+pub async fn start_impersonation(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Json(payload): Json<StartImpersonationRequest>,
+) -> ApiResult<(StatusCode, Json<ImpersonationResponse>)> {
+    let impersonation = state
+        .services
+        .impersonations
+        .start(admin.id, payload.user_id, &payload.reason, payload.minutes)
+        .await?;
+    tracing::warn!(
+        admin_id = %admin.id,
+        user_id = %impersonation.user_id,
+        impersonation_id = %impersonation.id,
+        expires_at = %impersonation.expires_at,
+        reason = %impersonation.reason,
+        "Started impersonation"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ImpersonationResponse::from(impersonation)),
+    ))
+}
+
+/// End an impersonation before it expires
+/// DELETE /api/v1/admin/impersonations/:id
+pub async fn end_impersonation(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ImpersonationResponse>> {
+    let impersonation = state.services.impersonations.end(id).await?;
+    tracing::info!(admin_id = %admin.id, impersonation_id = %id, "Ended impersonation");
+
+    Ok(Json(ImpersonationResponse::from(impersonation)))
+}
@@ -1,42 +1,176 @@
-use axum::{Json, extract::State, http::StatusCode};
-use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use crate::error::ApiResult;
-use crate::extractors::CurrentUser;
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Response},
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::dto::{
+    ExportNoteQuery, ExportScopeQuery, ImportQuery, JobResponse, NoteAudioQuery, NoteExportFormat,
+    SelectionExportRequest, SelectionExportResponse, SitePublishResponse,
+};
+use crate::error::{ApiError, ApiResult};
+use crate::extractors::{Scoped, scope};
+use crate::routes::jobs::finish_job;
+use crate::routes::web::IMAGE_PROXY_PATH;
 use crate::state::AppState;
-use notes_domain::{Note, NoteFilter, Tag};
+use notes_domain::jobs::{Job, JobKind, JobStatus};
+use notes_domain::query::{MAX_QUERY_LIMIT, NotePredicate, NoteQuery};
+use notes_domain::{DomainError, DomainResult, Note, NoteFilter, PdfRenderer};
+use notes_infra::formats::Exporter;
+use notes_infra::formats::k_notes::BackupData;
+use notes_infra::import::{ImportedData, SkippedItem};
+use notes_infra::render::html::{PrintTheme, render_print_view};
+use notes_infra::render::site::{build_site, write_to_directory};
 
-#[derive(Serialize, Deserialize)]
-pub struct BackupData {
-    pub notes: Vec<Note>,
-    pub tags: Vec<Tag>,
-}
+/// Most notes a selection export can name by ID
+const MAX_SELECTED_NOTES: usize = 1000;
+
+/// Selection exports are deleted once they are older than this
+const EXPORT_RETENTION_HOURS: u64 = 24;
 
 /// Export user data
 /// GET /api/v1/export
 pub async fn export_data(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::Export>,
 ) -> ApiResult<Json<BackupData>> {
     let user_id = user.id;
 
     let notes = state
-        .note_repo
+        .services
+        .backups
+        .notes
         .find_by_user(user_id, NoteFilter::default())
         .await?;
-    let tags = state.tag_repo.find_by_user(user_id).await?;
+    let tags = state.services.backups.tags.find_by_user(user_id).await?;
 
     Ok(Json(BackupData { notes, tags }))
 }
 
-/// Import user data
-/// POST /api/v1/import
+/// Import user data in the background
+/// POST /api/v1/import?format=k_notes|standard_notes|simplenote|html|markdown|csv&...
+///
+/// Query parameters besides `format` are passed to the format as options.
+///
+/// Responds with 202 Accepted and the job running the import; its progress
+/// counts tags and notes imported, and its result lists the items of the
+/// export that were skipped.
 pub async fn import_data(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
-    Json(payload): Json<BackupData>,
-) -> ApiResult<StatusCode> {
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Query(query): Query<ImportQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<(StatusCode, Json<JobResponse>)> {
+    let importer = match query.format {
+        Some(ref name) => state.services.formats.importer(name).ok_or_else(|| {
+            ApiError::validation(format!(
+                "Unknown import format {:?}; expected one of: {}",
+                name,
+                state.services.formats.importer_names().join(", ")
+            ))
+        })?,
+        None => {
+            let content_type = headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            state
+                .services
+                .formats
+                .importer_for_content_type(content_type)
+                .ok_or_else(|| ApiError::internal("No import formats are registered"))?
+        }
+    };
+
+    // Parsing unpacks and walks the whole export, so it runs off the runtime
+    let restores_backup = importer.restores_backup();
+    let options = query.options;
     let user_id = user.id;
+    let data = tokio::task::spawn_blocking(move || importer.import(&body, user_id, &options))
+        .await
+        .map_err(|e| ApiError::internal(format!("Import failed: {}", e)))??;
+    let (payload, skipped) = if restores_backup {
+        let ImportedData {
+            notes,
+            tags,
+            skipped,
+        } = data;
+        (BackupData { notes, tags }, skipped)
+    } else {
+        adopt_existing_tags(&state, user.id, data).await?
+    };
+
+    let total = (payload.tags.len() + payload.notes.len()) as u64;
+    let job = state
+        .services
+        .jobs
+        .start(user.id, JobKind::Import, Some(total))
+        .await?;
+    let response = JobResponse::from(job.clone());
+
+    tokio::spawn(async move {
+        let mut job = job;
+        let outcome = import_backup(&state, user.id, payload, skipped, &mut job).await;
+        finish_job(&state, job, outcome).await;
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// Use the user's existing tags for imported tags of the same name, so
+/// imports from other apps do not clash with them
+async fn adopt_existing_tags(
+    state: &AppState,
+    user_id: Uuid,
+    data: ImportedData,
+) -> DomainResult<(BackupData, Vec<SkippedItem>)> {
+    let ImportedData {
+        mut notes,
+        mut tags,
+        skipped,
+    } = data;
+
+    for tag in &mut tags {
+        let Some(existing) = state
+            .services
+            .backups
+            .tags
+            .find_by_name(user_id, tag.name_str())
+            .await?
+        else {
+            continue;
+        };
+        for note in &mut notes {
+            for note_tag in note.tags.iter_mut().filter(|t| t.id == tag.id) {
+                *note_tag = existing.clone();
+            }
+        }
+        *tag = existing;
+    }
+
+    Ok((BackupData { notes, tags }, skipped))
+}
+
+async fn import_backup(
+    state: &AppState,
+    user_id: Uuid,
+    payload: BackupData,
+    skipped: Vec<SkippedItem>,
+    job: &mut Job,
+) -> DomainResult<Option<serde_json::Value>> {
+    let tag_count = payload.tags.len();
+    let note_count = payload.notes.len();
+    let mut processed = 0;
 
     // 1. Import standalone tags (to ensure even unused tags are restored)
     for tag in payload.tags {
@@ -46,10 +180,13 @@ pub async fn import_data(
             // Safer to skip or force user_id. Let's force user_id to current user to allow migrating data between accounts.
             let mut tag = tag;
             tag.user_id = user_id;
-            state.tag_repo.save(&tag).await?;
+            state.services.backups.tags.save(&tag).await?;
         } else {
-            state.tag_repo.save(&tag).await?;
+            state.services.backups.tags.save(&tag).await?;
         }
+
+        processed += 1;
+        state.services.jobs.report_progress(job, processed).await?;
     }
 
     // 2. Import notes
@@ -58,7 +195,7 @@ pub async fn import_data(
         note.user_id = user_id; // Force ownership to current user
 
         // Save note content
-        state.note_repo.save(&note).await?;
+        state.services.backups.notes.save(&note).await?;
 
         // 3. Re-establish tag associations
         // Note: note.tags contains the tags associated with this note
@@ -66,12 +203,531 @@ pub async fn import_data(
             tag.user_id = user_id; // Force ownership
 
             // Ensure tag exists (upsert) - might be redundant if in payload.tags but safe
-            state.tag_repo.save(&tag).await?;
+            state.services.backups.tags.save(&tag).await?;
 
             // Link tag to note
-            state.tag_repo.add_to_note(tag.id, note.id).await?;
+            state
+                .services
+                .backups
+                .tags
+                .add_to_note(tag.id, note.id)
+                .await?;
+        }
+
+        processed += 1;
+        state.services.jobs.report_progress(job, processed).await?;
+    }
+
+    Ok(Some(serde_json::json!({
+        "tags": tag_count,
+        "notes": note_count,
+        "skipped": skipped,
+    })))
+}
+
+/// Export a single note as a downloadable file
+/// GET /api/v1/notes/:id/export?format=pdf|markdown
+pub async fn export_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::Export>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ExportNoteQuery>,
+) -> ApiResult<Response> {
+    let note = state.services.notes.get_note(id, user.id).await?;
+    let filename = export_filename(&note);
+
+    match query.format {
+        NoteExportFormat::Pdf => {
+            let renderer = pdf_renderer(&state)?;
+            let tz = state
+                .services
+                .users
+                .get_settings(user.id)
+                .await?
+                .time_zone();
+            let notes = rendered_notes(&state, user.id, vec![note]).await?;
+            let pdf = renderer
+                .render_pdf(notes[0].title_str(), &notes, tz)
+                .await?;
+            Ok(attachment_response(
+                "application/pdf",
+                &format!("{}.pdf", filename),
+                pdf,
+            ))
+        }
+        NoteExportFormat::Markdown => {
+            let mut markdown = String::new();
+            if !note.title_str().is_empty() {
+                markdown.push_str(&format!("# {}\n\n", note.title_str()));
+            }
+            markdown.push_str(&note.content);
+            Ok(attachment_response(
+                "text/markdown; charset=utf-8",
+                &format!("{}.md", filename),
+                markdown.into_bytes(),
+            ))
+        }
+    }
+}
+
+/// Printable HTML view of a note, branded with the instance's print theme
+/// GET /api/v1/notes/:id/print
+pub async fn print_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::Export>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Html<String>> {
+    let note = state.services.notes.get_note(id, user.id).await?;
+    let mut notes = rendered_notes(&state, user.id, vec![note]).await?;
+    // Only the print view is loaded from this instance; exported files keep
+    // their images' own URLs
+    if let Some(images) = &state.services.images {
+        notes = images.proxy_images(notes, IMAGE_PROXY_PATH);
+    }
+    let note = notes.remove(0);
+    let settings = state.services.settings.current();
+    let theme = PrintTheme {
+        logo_url: settings.print_logo_url,
+        accent_color: settings.print_accent_color,
+    };
+    let tz = state
+        .services
+        .users
+        .get_settings(user.id)
+        .await?
+        .time_zone();
+
+    Ok(Html(render_print_view(
+        &note,
+        &theme,
+        tz,
+        &state.config.render,
+    )))
+}
+
+/// Export notes in a registered format, optionally restricted to one tag;
+/// formats like the static site leave archived notes out
+/// GET /api/v1/export/{format}?tag=work
+pub async fn export_format(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::Export>,
+    Path(format): Path<String>,
+    Query(query): Query<ExportScopeQuery>,
+) -> ApiResult<Response> {
+    let exporter = exporter(&state, &format)?;
+
+    let (title, filter) = export_scope(&state, user.id, &query).await?;
+    let filter = if exporter.includes_archived() {
+        filter
+    } else {
+        filter.not_archived()
+    };
+    let mut notes = state.services.notes.list_notes(user.id, filter).await?;
+    if exporter.renders_content() {
+        notes = rendered_notes(&state, user.id, notes).await?;
+    }
+    let tz = state
+        .services
+        .users
+        .get_settings(user.id)
+        .await?
+        .time_zone();
+    let body = exporter.export(&title, notes, tz).await?;
+
+    Ok(attachment_response(
+        exporter.content_type(),
+        &format!("k-notes.{}", exporter.extension()),
+        body,
+    ))
+}
+
+/// Publish non-archived notes as a static HTML site on the server, in the
+/// background
+/// POST /api/v1/export/site/publish?tag=work
+///
+/// Responds with 202 Accepted and the job; once it completes, its result
+/// holds the directory and number of files written.
+pub async fn publish_site(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::Export>,
+    Query(query): Query<ExportScopeQuery>,
+) -> ApiResult<(StatusCode, Json<JobResponse>)> {
+    let publish_dir = state.config.site_publish_dir.clone().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Site publishing is not enabled on this instance".to_string())
+    })?;
+
+    // Resolve the scope up front so an unknown tag is reported right away
+    let (title, filter) = export_scope(&state, user.id, &query).await?;
+    let job = state
+        .services
+        .jobs
+        .start(user.id, JobKind::SitePublish, None)
+        .await?;
+    let response = JobResponse::from(job.clone());
+
+    tokio::spawn(async move {
+        let outcome = write_site(&state, user.id, &title, filter, &publish_dir).await;
+        finish_job(&state, job, outcome).await;
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+async fn write_site(
+    state: &AppState,
+    user_id: Uuid,
+    title: &str,
+    filter: NoteFilter,
+    publish_dir: &str,
+) -> DomainResult<Option<serde_json::Value>> {
+    let notes = state
+        .services
+        .notes
+        .list_notes(user_id, filter.not_archived())
+        .await?;
+    let notes = rendered_notes(state, user_id, notes).await?;
+    let tz = state
+        .services
+        .users
+        .get_settings(user_id)
+        .await?
+        .time_zone();
+
+    let files = build_site(title, &notes, tz, &state.config.render);
+    let root = std::path::Path::new(publish_dir).join(user_id.to_string());
+    write_to_directory(&files, &root).await?;
+
+    let published = SitePublishResponse {
+        path: root.display().to_string(),
+        files: files.len(),
+    };
+    serde_json::to_value(published)
+        .map(Some)
+        .map_err(|e| DomainError::InfrastructureError(e.to_string()))
+}
+
+/// Export the notes with the given IDs, or matching a filter, in the
+/// background
+/// POST /api/v1/export/selection
+///
+/// Responds with 202 Accepted and the job; once it completes, its result
+/// describes the archive to download from `GET /api/v1/export/selection/:id`.
+pub async fn export_selection(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::Export>,
+    Json(payload): Json<SelectionExportRequest>,
+) -> ApiResult<(StatusCode, Json<JobResponse>)> {
+    let exporter = exporter(&state, &payload.format)?;
+
+    // Resolve the selection up front so unknown notes are reported right away
+    let notes = select_notes(&state, user.id, payload.note_ids, payload.filter).await?;
+    let job = state
+        .services
+        .jobs
+        .start(user.id, JobKind::SelectionExport, Some(notes.len() as u64))
+        .await?;
+    let response = JobResponse::from(job.clone());
+
+    tokio::spawn(async move {
+        let outcome = write_selection(&state, user.id, job.id, notes, exporter.as_ref()).await;
+        finish_job(&state, job, outcome).await;
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// Download the archive of a completed selection export
+/// GET /api/v1/export/selection/:id
+pub async fn download_selection(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::Export>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Response> {
+    let job = state.services.jobs.get_job(id, user.id).await?;
+    if job.kind != JobKind::SelectionExport {
+        return Err(DomainError::JobNotFound(id).into());
+    }
+    let export: SelectionExportResponse = match (job.status, job.result) {
+        (JobStatus::Completed, Some(result)) => {
+            serde_json::from_value(result).map_err(|e| ApiError::internal(e.to_string()))?
+        }
+        (JobStatus::Running, _) => return Err(ApiError::validation("The export is still running")),
+        _ => return Err(ApiError::validation("The export failed")),
+    };
+
+    let body = match tokio::fs::read(export_path(&state, id)).await {
+        Ok(body) => body,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ApiError::validation(
+                "The export has expired; start a new one",
+            ));
+        }
+        Err(e) => return Err(ApiError::internal(format!("Failed to read export: {}", e))),
+    };
+
+    Ok(attachment_response(
+        &export.content_type,
+        &export.filename,
+        body,
+    ))
+}
+
+/// The notes named by `note_ids`, in that order, or those matching `filter`
+async fn select_notes(
+    state: &AppState,
+    user_id: Uuid,
+    note_ids: Vec<Uuid>,
+    filter: Option<NotePredicate>,
+) -> ApiResult<Vec<Note>> {
+    match (note_ids.is_empty(), filter) {
+        (false, None) => {
+            if note_ids.len() > MAX_SELECTED_NOTES {
+                return Err(ApiError::validation(format!(
+                    "At most {} notes can be selected; use a filter instead",
+                    MAX_SELECTED_NOTES
+                )));
+            }
+            let mut seen = HashSet::new();
+            let mut notes = Vec::with_capacity(note_ids.len());
+            for id in note_ids {
+                if seen.insert(id) {
+                    notes.push(state.services.notes.get_note(id, user_id).await?);
+                }
+            }
+            Ok(notes)
+        }
+        (true, Some(filter)) => {
+            let mut query = NoteQuery {
+                filter: Some(filter),
+                limit: Some(MAX_QUERY_LIMIT),
+                ..Default::default()
+            };
+            let mut notes = Vec::new();
+            loop {
+                let page = state.services.notes.query_notes(user_id, &query).await?;
+                let is_last = page.len() < MAX_QUERY_LIMIT;
+                notes.extend(page);
+                if is_last {
+                    return Ok(notes);
+                }
+                query.offset += MAX_QUERY_LIMIT;
+            }
+        }
+        _ => Err(ApiError::validation("Give either note_ids or a filter")),
+    }
+}
+
+fn export_path(state: &AppState, job_id: Uuid) -> PathBuf {
+    FsPath::new(&state.config.export_dir).join(job_id.to_string())
+}
+
+async fn write_selection(
+    state: &AppState,
+    user_id: Uuid,
+    job_id: Uuid,
+    notes: Vec<Note>,
+    exporter: &dyn Exporter,
+) -> DomainResult<Option<serde_json::Value>> {
+    let to_error = |e: &dyn std::fmt::Display| {
+        DomainError::InfrastructureError(format!("Failed to write export: {}", e))
+    };
+    let note_count = notes.len();
+    let tz = state
+        .services
+        .users
+        .get_settings(user_id)
+        .await?
+        .time_zone();
+
+    let notes = if exporter.renders_content() {
+        rendered_notes(state, user_id, notes).await?
+    } else {
+        notes
+    };
+    let archive = exporter.export("K-Notes", notes, tz).await?;
+
+    let dir = FsPath::new(&state.config.export_dir);
+    prune_exports(dir).await;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| to_error(&e))?;
+    tokio::fs::write(export_path(state, job_id), &archive)
+        .await
+        .map_err(|e| to_error(&e))?;
+
+    let export = SelectionExportResponse {
+        filename: format!("k-notes-selection.{}", exporter.extension()),
+        content_type: exporter.content_type().to_string(),
+        size: archive.len(),
+        notes: note_count,
+        download_url: format!("/api/v1/export/selection/{}", job_id),
+        expires_at: Utc::now() + chrono::Duration::hours(EXPORT_RETENTION_HOURS as i64),
+    };
+    serde_json::to_value(export)
+        .map(Some)
+        .map_err(|e| DomainError::InfrastructureError(e.to_string()))
+}
+
+/// Delete selection exports older than the retention window, as far as
+/// possible
+async fn prune_exports(dir: &FsPath) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    let Some(cutoff) =
+        SystemTime::now().checked_sub(Duration::from_secs(EXPORT_RETENTION_HOURS * 3600))
+    else {
+        return;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let expired = entry
+            .metadata()
+            .await
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified < cutoff);
+        if expired && let Err(e) = tokio::fs::remove_file(entry.path()).await {
+            tracing::warn!(path = %entry.path().display(), "Failed to delete expired export: {}", e);
         }
     }
+}
+
+/// Resolve the optional `?tag=` scope of a bulk export into a title and filter
+async fn export_scope(
+    state: &AppState,
+    user_id: Uuid,
+    query: &ExportScopeQuery,
+) -> ApiResult<(String, NoteFilter)> {
+    match query.tag {
+        Some(ref tag_name) => {
+            let tag = state
+                .services
+                .backups
+                .tags
+                .find_by_name(user_id, tag_name)
+                .await?
+                .ok_or_else(|| ApiError::validation(format!("Unknown tag: {}", tag_name)))?;
+            Ok((
+                format!("K-Notes #{}", tag.name_str()),
+                NoteFilter::new().with_tag(tag.id),
+            ))
+        }
+        None => Ok(("K-Notes".to_string(), NoteFilter::new())),
+    }
+}
+
+/// A note read aloud as MP3 or Ogg audio. Clients sending back the `ETag`
+/// in `If-None-Match` get `304 Not Modified` until the note changes.
+/// GET /api/v1/notes/{id}/audio?format=mp3|ogg
+pub async fn note_audio(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::Export>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<NoteAudioQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let speech = state.services.speech.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable(
+            "Reading notes aloud is not enabled on this instance".to_string(),
+        )
+    })?;
+    let spoken = speech.speak(id, user.id, query.format).await?;
+
+    let etag = format!("\"{}\"", spoken.fingerprint);
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "private, no-cache".to_string()),
+    ];
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag));
+    if unchanged {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let filename = format!("note-{}.{}", id, spoken.format.extension());
+    Ok((
+        cache_headers,
+        [
+            (
+                header::CONTENT_TYPE,
+                spoken.format.content_type().to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", filename),
+            ),
+        ],
+        spoken.audio.to_vec(),
+    )
+        .into_response())
+}
+
+/// The registered exporter named `name`
+fn exporter(state: &AppState, name: &str) -> ApiResult<Arc<dyn Exporter>> {
+    if let Some(exporter) = state.services.formats.exporter(name) {
+        return Ok(exporter);
+    }
+    if name == "pdf" {
+        pdf_renderer(state)?;
+    }
+    Err(ApiError::validation(format!(
+        "Unknown export format {:?}; expected one of: {}",
+        name,
+        state.services.formats.exporter_names().join(", ")
+    )))
+}
+
+/// `notes` as rendered outputs show them: with their includes expanded and,
+/// where the instance draws them, diagrams in place of their code blocks
+async fn rendered_notes(
+    state: &AppState,
+    user_id: Uuid,
+    notes: Vec<Note>,
+) -> DomainResult<Vec<Note>> {
+    let notes = state.services.notes.expand_includes(user_id, notes).await?;
+    Ok(match &state.services.diagrams {
+        Some(diagrams) => diagrams.draw_diagrams(notes).await,
+        None => notes,
+    })
+}
+
+fn pdf_renderer(state: &AppState) -> ApiResult<&Arc<dyn PdfRenderer>> {
+    state.services.pdf_renderer.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("PDF export is not enabled on this instance".to_string())
+    })
+}
+
+/// Build an ASCII-only file name from the note title
+fn export_filename(note: &Note) -> String {
+    let mut slug = String::new();
+    for c in note.title_str().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        format!("note-{}", note.id)
+    } else {
+        slug.to_string()
+    }
+}
 
-    Ok(StatusCode::OK)
+fn attachment_response(content_type: &str, filename: &str, body: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response()
 }
@@ -0,0 +1,81 @@
+//! Invitation route handlers for administrators
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use uuid::Uuid;
+
+use crate::dto::{CreateInvitationRequest, InvitationDetailResponse, InvitationResponse};
+use crate::error::ApiResult;
+use crate::extractors::AdminUser;
+use crate::state::AppState;
+
+/// List all invitations, newest first
+/// GET /api/v1/admin/invitations
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> ApiResult<Json<Vec<InvitationResponse>>> {
+    let invitations = state.services.invitations.list_all().await?;
+
+    Ok(Json(
+        invitations
+            .into_iter()
+            .map(|invitation| InvitationResponse::new(invitation, &state.config.frontend_url))
+            .collect(),
+    ))
+}
+
+/// Create an invitation
+/// POST /api/v1/admin/invitations
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Json(payload): Json<CreateInvitationRequest>,
+) -> ApiResult<(StatusCode, Json<InvitationResponse>)> {
+    let invitation = state
+        .services
+        .invitations
+        .create(admin.id, payload.max_uses, payload.expires_at)
+        .await?;
+    tracing::info!(admin_id = %admin.id, invitation_id = %invitation.id, "Created invitation");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(InvitationResponse::new(
+            invitation,
+            &state.config.frontend_url,
+        )),
+    ))
+}
+
+/// Get an invitation and the accounts registered with it
+/// GET /api/v1/admin/invitations/:id
+pub async fn get_invitation(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<InvitationDetailResponse>> {
+    let invitation = state.services.invitations.get(id).await?;
+    let invited_users = state.services.invitations.invited_users(id).await?;
+
+    Ok(Json(InvitationDetailResponse {
+        invitation: InvitationResponse::new(invitation, &state.config.frontend_url),
+        invited_users,
+    }))
+}
+
+/// Revoke an invitation
+/// DELETE /api/v1/admin/invitations/:id
+pub async fn delete_invitation(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    state.services.invitations.delete(id).await?;
+    tracing::info!(admin_id = %admin.id, invitation_id = %id, "Revoked invitation");
+
+    Ok(StatusCode::NO_CONTENT)
+}
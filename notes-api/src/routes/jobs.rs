@@ -0,0 +1,57 @@
+//! Background job route handlers
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use uuid::Uuid;
+
+use notes_domain::{DomainResult, jobs::Job};
+
+use crate::dto::{JobListQuery, JobResponse};
+use crate::error::ApiResult;
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+
+/// List the current user's jobs, newest first
+/// GET /api/v1/jobs?limit=20
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Query(query): Query<JobListQuery>,
+) -> ApiResult<Json<Vec<JobResponse>>> {
+    let jobs = state.services.jobs.list_jobs(user.id, query.limit).await?;
+
+    Ok(Json(jobs.into_iter().map(JobResponse::from).collect()))
+}
+
+/// Get the status and progress of one of the current user's jobs
+/// GET /api/v1/jobs/:id
+pub async fn get_job(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<JobResponse>> {
+    let job = state.services.jobs.get_job(id, user.id).await?;
+
+    Ok(Json(JobResponse::from(job)))
+}
+
+/// Record how a background job ended
+pub(crate) async fn finish_job(
+    state: &AppState,
+    mut job: Job,
+    outcome: DomainResult<Option<serde_json::Value>>,
+) {
+    let recorded = match outcome {
+        Ok(result) => state.services.jobs.complete(&mut job, result).await,
+        Err(e) => {
+            tracing::warn!(job_id = %job.id, kind = job.kind.as_str(), "Job failed: {}", e);
+            state.services.jobs.fail(&mut job, e.to_string()).await
+        }
+    };
+
+    if let Err(e) = recorded {
+        tracing::error!(job_id = %job.id, "Failed to record job outcome: {}", e);
+    }
+}
@@ -0,0 +1,99 @@
+//! Legal document route handlers
+//!
+//! Anyone can read the published documents. Signed-in users who have not
+//! accepted the current versions are refused elsewhere until they accept
+//! them here.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use notes_domain::legal::LegalDocumentKind;
+
+use crate::dto::{
+    AcceptLegalRequest, LegalDocumentResponse, PublishLegalDocumentRequest, accepted_versions,
+};
+use crate::error::ApiResult;
+use crate::extractors::{AdminUser, PendingConsentUser};
+use crate::state::AppState;
+
+/// List the current version of every published document
+/// GET /api/v1/legal
+pub async fn list_documents(
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<LegalDocumentResponse>>> {
+    let documents = state.services.legal.list().await?;
+
+    Ok(Json(
+        documents
+            .into_iter()
+            .map(LegalDocumentResponse::from)
+            .collect(),
+    ))
+}
+
+/// Get the current version of a document
+/// GET /api/v1/legal/:kind
+pub async fn get_document(
+    State(state): State<AppState>,
+    Path(kind): Path<LegalDocumentKind>,
+) -> ApiResult<Json<LegalDocumentResponse>> {
+    let document = state.services.legal.get(kind).await?;
+
+    Ok(Json(LegalDocumentResponse::from(document)))
+}
+
+/// List the documents the current user still has to accept
+/// GET /api/v1/legal/pending
+pub async fn list_pending_documents(
+    State(state): State<AppState>,
+    PendingConsentUser(user): PendingConsentUser,
+) -> ApiResult<Json<Vec<LegalDocumentResponse>>> {
+    let documents = state.services.legal.pending(user.id).await?;
+
+    Ok(Json(
+        documents
+            .into_iter()
+            .map(LegalDocumentResponse::from)
+            .collect(),
+    ))
+}
+
+/// Accept the current versions of documents
+/// POST /api/v1/legal/accept
+pub async fn accept_documents(
+    State(state): State<AppState>,
+    PendingConsentUser(user): PendingConsentUser,
+    Json(payload): Json<AcceptLegalRequest>,
+) -> ApiResult<StatusCode> {
+    state
+        .services
+        .legal
+        .accept(user.id, &accepted_versions(&payload.documents))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Publish a new version of a document; every user has to accept it again
+/// PUT /api/v1/admin/legal/:kind
+pub async fn publish_document(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Path(kind): Path<LegalDocumentKind>,
+    Json(payload): Json<PublishLegalDocumentRequest>,
+) -> ApiResult<(StatusCode, Json<LegalDocumentResponse>)> {
+    let document = state.services.legal.publish(kind, &payload.content).await?;
+    tracing::info!(
+        admin_id = %admin.id,
+        kind = %kind,
+        version = document.version,
+        "Published legal document"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(LegalDocumentResponse::from(document)),
+    ))
+}
@@ -0,0 +1,165 @@
+//! Current user route handlers
+
+use axum::{Json, extract::State, http::StatusCode};
+
+use notes_domain::{
+    EMAIL_CHANGE_VALIDITY_HOURS, EmailChange, EmailMessage,
+    UpdateProfileRequest as DomainUpdateProfile, UserSettings,
+};
+
+use crate::dto::{
+    ChangePasswordRequest, ConfirmEmailRequest, UpdateProfileRequest, UpdateProfileResponse,
+    UpdateSettingsRequest, UserResponse,
+};
+use crate::error::{ApiError, ApiResult};
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+use crate::validation::ValidatedJson;
+
+/// Update the current user's profile
+/// PATCH /api/v1/me
+///
+/// Display name and avatar change immediately. A new email address only
+/// takes effect after the link sent to it is confirmed.
+pub async fn update_profile(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    ValidatedJson(payload): ValidatedJson<UpdateProfileRequest>,
+) -> ApiResult<Json<UpdateProfileResponse>> {
+    // Empty strings clear the field
+    let non_empty = |value: String| {
+        let value = value.trim().to_string();
+        (!value.is_empty()).then_some(value)
+    };
+
+    let updated = state
+        .services
+        .users
+        .update_profile(
+            user.id,
+            DomainUpdateProfile {
+                display_name: payload.display_name.map(non_empty),
+                avatar_url: payload.avatar_url.map(non_empty),
+            },
+        )
+        .await?;
+
+    let pending_email = match payload.email {
+        Some(email) if email != updated.email => {
+            let change = state
+                .services
+                .users
+                .request_email_change(user.id, email)
+                .await?;
+            send_email_confirmation(&state, &change).await?;
+            Some(change.new_email)
+        }
+        _ => None,
+    };
+
+    Ok(Json(UpdateProfileResponse {
+        user: UserResponse::from(updated),
+        pending_email,
+    }))
+}
+
+/// Confirm a pending email change
+/// POST /api/v1/me/email/confirm
+pub async fn confirm_email(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(payload): Json<ConfirmEmailRequest>,
+) -> ApiResult<Json<UserResponse>> {
+    let user = state
+        .services
+        .users
+        .confirm_email_change(user.id, payload.token.trim())
+        .await?;
+
+    Ok(Json(UserResponse::from(user)))
+}
+
+/// Change the current user's password
+/// POST /api/v1/me/password
+///
+/// Sessions are bound to the password hash, so all other sessions are
+/// invalidated; the current session is kept logged in. Issued JWTs stay
+/// valid until they expire.
+pub async fn change_password(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    #[cfg(feature = "auth-axum-login")] mut auth_session: crate::auth::AuthSession,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> ApiResult<StatusCode> {
+    let updated = state
+        .services
+        .users
+        .change_password(
+            user.id,
+            payload.current_password.as_ref(),
+            &payload.new_password,
+        )
+        .await?;
+
+    #[cfg(feature = "auth-axum-login")]
+    if auth_session.user.is_some() {
+        auth_session
+            .login(&crate::auth::AuthUser(updated))
+            .await
+            .map_err(|_| ApiError::Internal("Login failed".to_string()))?;
+    }
+    #[cfg(not(feature = "auth-axum-login"))]
+    let _ = updated; // Only needed to refresh the session
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the current user's settings
+/// GET /api/v1/me/settings
+pub async fn get_settings(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> ApiResult<Json<UserSettings>> {
+    let settings = state.services.users.get_settings(user.id).await?;
+
+    Ok(Json(settings))
+}
+
+/// Update the current user's settings
+/// PATCH /api/v1/me/settings
+pub async fn update_settings(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    ValidatedJson(payload): ValidatedJson<UpdateSettingsRequest>,
+) -> ApiResult<Json<UserSettings>> {
+    let settings = state
+        .services
+        .users
+        .update_settings(user.id, payload.into())
+        .await?;
+
+    Ok(Json(settings))
+}
+
+/// Send the confirmation link for an email change to the new address
+async fn send_email_confirmation(state: &AppState, change: &EmailChange) -> ApiResult<()> {
+    let link = format!(
+        "{}/confirm-email?token={}",
+        state.config.frontend_url.trim_end_matches('/'),
+        change.token
+    );
+
+    let message = EmailMessage {
+        to: change.new_email.clone(),
+        subject: "Confirm your new K-Notes email address".to_string(),
+        body: format!(
+            "Someone (hopefully you) asked to use this address for a K-Notes account.\n\n\
+             Confirm the change by opening:\n{}\n\n\
+             The link expires in {} hours. If you did not request this, ignore this email.\n",
+            link, EMAIL_CHANGE_VALIDITY_HOURS
+        ),
+    };
+
+    state.services.email_sender.send(&message).await?;
+    Ok(())
+}
@@ -1,48 +1,283 @@
 //! Route definitions and module structure
 
+pub mod activity;
+pub mod admin;
+pub mod announcements;
 pub mod auth;
+pub mod boards;
 pub mod config;
+pub mod graph;
+pub mod impersonations;
 pub mod import_export;
+pub mod invitations;
+pub mod jobs;
+pub mod legal;
+pub mod me;
 pub mod notes;
+pub mod relations;
+pub mod scratchpad;
 pub mod tags;
+pub mod undo;
+pub mod web;
 
 use axum::{
     Router,
-    routing::{delete, get, post},
+    extract::DefaultBodyLimit,
+    middleware::from_fn_with_state,
+    routing::{delete, get, patch, post, put},
 };
+use notes_domain::scopes::Scope;
+use notes_domain::transcription::MAX_AUDIO_BYTES;
 
+use crate::guards::{
+    Guard, enforce, require_admin, require_auth, require_credentials, require_scope,
+};
 use crate::state::AppState;
 
+/// Create the API v2 router
+///
+/// v2 runs the v1 handlers; [`crate::versioning::compat_shim`] reshapes
+/// their responses. Endpoints that only exist in v2 are added here.
+pub fn api_v2_router(state: &AppState) -> Router<AppState> {
+    api_v1_router(state)
+}
+
 /// Create the API v1 router
-pub fn api_v1_router() -> Router<AppState> {
-    let router = Router::new()
-        // Auth routes
+///
+/// Routes are grouped by who may call them and every group but the public
+/// one is guarded as a whole, so a new route belongs in the group matching
+/// its access. Paths served by two groups, like `GET` and `POST /notes`,
+/// are guarded per method.
+pub fn api_v1_router(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .merge(public_routes())
+        .merge(guarded(consent_routes(), require_credentials(state)))
+        .merge(guarded(account_routes(), require_auth(state)))
+        .merge(guarded(
+            note_read_routes(),
+            require_scope(state, Scope::NotesRead),
+        ))
+        .merge(guarded(
+            note_write_routes(),
+            require_scope(state, Scope::NotesWrite),
+        ))
+        .merge(guarded(
+            export_routes(),
+            require_scope(state, Scope::Export),
+        ))
+        .merge(guarded(admin_routes(), require_admin(state)))
+}
+
+/// `routes` behind `guard`
+fn guarded(routes: Router<AppState>, guard: Guard) -> Router<AppState> {
+    routes.route_layer(from_fn_with_state(guard, enforce))
+}
+
+/// Routes anyone may call
+fn public_routes() -> Router<AppState> {
+    Router::new()
+        // Auth routes; those needing a user take it from their extractor
         .nest("/auth", auth::router())
-        // Note routes
-        .route("/notes", get(notes::list_notes).post(notes::create_note))
+        // Guest scratchpad, kept in the session
         .route(
-            "/notes/{id}",
-            get(notes::get_note)
-                .patch(notes::update_note)
-                .delete(notes::delete_note),
+            "/scratchpad",
+            get(scratchpad::get_scratchpad)
+                .put(scratchpad::update_scratchpad)
+                .delete(scratchpad::delete_scratchpad),
         )
-        .route("/notes/{id}/versions", get(notes::list_note_versions));
+        // External images of notes, authorized by their signature
+        .route("/proxy/image", get(web::proxy_image))
+        // Legal documents
+        .route("/legal", get(legal::list_documents))
+        .route("/legal/{kind}", get(legal::get_document))
+        // System Config
+        .route("/config", get(config::get_config))
+}
 
-    #[cfg(feature = "smart-features")]
-    let router = router.route("/notes/{id}/related", get(notes::get_related_notes));
+/// Routes for signed-in users who may not have accepted the current legal
+/// documents yet
+fn consent_routes() -> Router<AppState> {
+    Router::new()
+        .route("/legal/pending", get(legal::list_pending_documents))
+        .route("/legal/accept", post(legal::accept_documents))
+}
 
-    router
+/// Routes for signed-in users
+fn account_routes() -> Router<AppState> {
+    Router::new()
+        // Current user routes
+        .route("/me", patch(me::update_profile))
+        .route("/me/email/confirm", post(me::confirm_email))
+        .route("/me/password", post(me::change_password))
+        .route(
+            "/me/settings",
+            get(me::get_settings).patch(me::update_settings),
+        )
+        .route(
+            "/notes/{id}/relations",
+            get(relations::list_relations).post(relations::create_relation),
+        )
+        .route("/relations/{id}", delete(relations::delete_relation))
+        // Graph route
+        .route("/graph", get(graph::get_graph))
+        // Background job routes
+        .route("/jobs", get(jobs::list_jobs))
+        .route("/jobs/{id}", get(jobs::get_job))
+        // Kanban boards
+        .route(
+            "/boards",
+            get(boards::list_boards).post(boards::create_board),
+        )
+        .route(
+            "/boards/{id}",
+            get(boards::get_board)
+                .put(boards::update_board)
+                .delete(boards::delete_board),
+        )
+        .route("/boards/{id}/notes", get(boards::list_board_notes))
+        .route("/boards/{id}/move", post(boards::move_card))
+        .route("/scratchpad/claim", post(scratchpad::claim_scratchpad))
+        // Activity feed
+        .route("/activity", get(activity::list_activity))
+        // Undo and redo
+        .route("/undo", post(undo::undo))
+        .route("/redo", post(undo::redo))
+        // Link cards while editing
+        .route("/unfurl", get(web::unfurl))
+        // Images of notes rendered by clients, loaded through the instance
+        .route("/proxy/image/sign", post(web::sign_images))
+        // Announcements
+        .route("/announcements", get(announcements::list_announcements))
+        .route(
+            "/announcements/{id}/dismiss",
+            post(announcements::dismiss_announcement),
+        )
+}
+
+/// Routes reading notes and tags
+fn note_read_routes() -> Router<AppState> {
+    let router = Router::new()
+        .route("/notes", get(notes::list_notes))
+        .route("/notes/query", post(notes::query_notes))
+        .route("/notes/issues", get(notes::get_issue_report))
+        .route("/notes/nearby", get(notes::find_nearby_notes))
+        .route(
+            "/notes/auto-archive/preview",
+            get(notes::preview_auto_archive),
+        )
+        .route("/notes/{id}", get(notes::get_note))
+        .route("/notes/{id}/versions", get(notes::list_note_versions))
+        .route("/notes/{id}/issues", get(notes::list_note_issues))
+        .route("/notes/{id}/proofread", post(notes::proofread_note))
         // Search route
         .route("/search", get(notes::search_notes))
-        // Import/Export routes
-        .route("/export", get(import_export::export_data))
+        .route("/search/suggest", get(notes::suggest_search))
+        .route("/search/history", get(notes::get_search_history))
+        // Tag routes
+        .route("/tags", get(tags::list_tags))
+        .route("/tags/aliases", get(tags::list_aliases));
+
+    #[cfg(feature = "smart-features")]
+    let router = router
+        .route("/notes/{id}/related", get(notes::get_related_notes))
+        .route("/notes/similar", post(notes::find_similar_notes));
+
+    router
+}
+
+/// Routes changing notes and tags
+fn note_write_routes() -> Router<AppState> {
+    let router = Router::new()
+        .route("/notes", post(notes::create_note))
+        .route("/notes/pins/reorder", patch(notes::reorder_pins))
+        .route(
+            "/notes/{id}",
+            patch(notes::update_note).delete(notes::delete_note),
+        )
+        .route("/notes/{id}/duplicate", post(notes::duplicate_note))
+        .route("/notes/{id}/restore", post(notes::restore_note))
+        .route("/notes/{id}/translate", post(notes::translate_note))
+        .route("/notes/{id}/lock", post(notes::lock_note))
+        .route("/notes/{id}/unlock", post(notes::unlock_note))
+        // Recordings outgrow the request limit of other uploads
+        .route(
+            "/notes/{id}/voice-memos",
+            post(notes::upload_voice_memo).layer(DefaultBodyLimit::max(MAX_AUDIO_BYTES)),
+        )
+        // Quick capture
+        .route("/capture", post(notes::capture_note))
+        .route("/search/history", delete(notes::clear_search_history))
         .route("/import", post(import_export::import_data))
         // Tag routes
-        .route("/tags", get(tags::list_tags).post(tags::create_tag))
+        .route("/tags", post(tags::create_tag))
+        .route("/tags/reorder", patch(tags::reorder_tags))
+        .route("/tags/aliases/{alias}", delete(tags::delete_alias))
         .route(
             "/tags/{id}",
-            delete(tags::delete_tag).patch(tags::rename_tag),
+            delete(tags::delete_tag).patch(tags::update_tag),
+        )
+        .route("/tags/{id}/aliases", post(tags::create_alias));
+
+    #[cfg(feature = "smart-features")]
+    let router = router.route("/notes/{id}/process", post(notes::process_note));
+
+    router
+}
+
+/// Routes exporting notes
+fn export_routes() -> Router<AppState> {
+    Router::new()
+        .route("/notes/{id}/export", get(import_export::export_note))
+        .route("/notes/{id}/print", get(import_export::print_note))
+        .route("/notes/{id}/audio", get(import_export::note_audio))
+        .route("/export", get(import_export::export_data))
+        .route("/export/{format}", get(import_export::export_format))
+        .route("/export/site/publish", post(import_export::publish_site))
+        .route("/export/selection", post(import_export::export_selection))
+        .route(
+            "/export/selection/{id}",
+            get(import_export::download_selection),
+        )
+}
+
+/// Routes for administrators
+fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/admin/maintenance",
+            get(admin::get_maintenance).put(admin::set_maintenance),
+        )
+        .route(
+            "/admin/settings",
+            get(admin::get_settings).patch(admin::update_settings),
+        )
+        .route("/admin/stats", get(admin::get_storage_stats))
+        .route(
+            "/admin/invitations",
+            get(invitations::list_invitations).post(invitations::create_invitation),
+        )
+        .route(
+            "/admin/invitations/{id}",
+            get(invitations::get_invitation).delete(invitations::delete_invitation),
+        )
+        .route(
+            "/admin/announcements",
+            get(announcements::list_all_announcements).post(announcements::create_announcement),
+        )
+        .route(
+            "/admin/announcements/{id}",
+            put(announcements::update_announcement).delete(announcements::delete_announcement),
+        )
+        .route("/admin/overview", get(admin::get_overview))
+        .route("/admin/housekeeping", get(admin::get_housekeeping))
+        .route("/admin/housekeeping/run", post(admin::run_housekeeping))
+        .route("/admin/legal/{kind}", put(legal::publish_document))
+        .route(
+            "/admin/impersonations",
+            get(impersonations::list_impersonations).post(impersonations::start_impersonation),
+        )
+        .route(
+            "/admin/impersonations/{id}",
+            delete(impersonations::end_impersonation),
         )
-        // System Config
-        .route("/config", get(config::get_config))
 }
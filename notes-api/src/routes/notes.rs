@@ -2,87 +2,116 @@
 
 use axum::{
     Json,
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
 };
 use uuid::Uuid;
-use validator::Validate;
 
 use notes_domain::{
-    CreateNoteRequest as DomainCreateNote, NoteTitle, TagName,
+    CreateNoteRequest as DomainCreateNote, Latitude, Longitude, NoteTitle, PlaceName, TagName,
     UpdateNoteRequest as DomainUpdateNote,
+    archive_policy::{AutoArchivePolicy, MAX_AUTO_ARCHIVE_DAYS},
+    geo::{DEFAULT_NEARBY_RADIUS_KM, GeoPoint},
+    query::NoteQuery,
+    search::SearchScope,
 };
 
 use crate::error::{ApiError, ApiResult};
 use crate::state::AppState;
+use crate::validation::ValidatedJson;
 use crate::{
-    dto::{CreateNoteRequest, ListNotesQuery, NoteResponse, SearchQuery, UpdateNoteRequest},
-    extractors::CurrentUser,
+    dto::{
+        AutoArchivePreviewQuery, AutoArchivePreviewResponse, CaptureRequest, CreateNoteRequest,
+        DuplicateNoteQuery, IssueReportResponse, ListNotesQuery, NearbyNoteResponse, NearbyQuery,
+        NoteIssueResponse, NoteListResponse, NoteResponse, ProofreadResponse, ReorderPinsRequest,
+        SearchHistoryEntryResponse, SearchHistoryQuery, SearchHitResponse, SearchQuery,
+        SearchResponse, SuggestQuery, SuggestionsResponse, TranslateQuery, UpdateNoteRequest,
+        VoiceMemoResponse,
+    },
+    extractors::{Scoped, scope},
 };
 
-/// List notes with optional filtering
+/// Related notes returned when no limit is given
+#[cfg(feature = "smart-features")]
+const DEFAULT_RELATED_LIMIT: usize = 5;
+#[cfg(feature = "smart-features")]
+const MAX_RELATED_LIMIT: usize = 50;
+
+/// List notes with optional filtering, together with counts of all notes
 /// GET /api/v1/notes
 pub async fn list_notes(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesRead>,
     Query(query): Query<ListNotesQuery>,
-) -> ApiResult<Json<Vec<NoteResponse>>> {
+) -> ApiResult<Json<NoteListResponse>> {
     let user_id = user.id;
+    let counts = state.services.notes.count_notes(user_id).await?;
 
     // Build the filter, looking up tag_id by name if needed
     let mut filter = notes_domain::NoteFilter::new();
     filter.is_pinned = query.pinned;
-    filter.is_archived = query.archived;
+    filter.is_archived = query.archived.as_filter();
+    filter.trashed = query.trashed;
 
     // Look up tag by name if provided
     if let Some(ref tag_name) = query.tag {
-        if let Ok(Some(tag)) = state.tag_repo.find_by_name(user_id, tag_name).await {
+        if let Ok(Some(tag)) = state.services.tags.find_by_name(user_id, tag_name).await {
             filter.tag_id = Some(tag.id);
         } else {
             // Tag not found, return empty results
-            return Ok(Json(vec![]));
+            return Ok(Json(NoteListResponse::new(Vec::new(), counts, query)));
         }
     }
 
-    let notes = state.note_service.list_notes(user_id, filter).await?;
-    let response: Vec<NoteResponse> = notes.into_iter().map(NoteResponse::from).collect();
+    let notes = state.services.notes.list_notes(user_id, filter).await?;
 
-    Ok(Json(response))
+    Ok(Json(NoteListResponse::new(notes, counts, query)))
+}
+
+/// Query notes with a JSON filter document
+/// POST /api/v1/notes/query
+pub async fn query_notes(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Json(query): Json<NoteQuery>,
+) -> ApiResult<Json<Vec<NoteResponse>>> {
+    let notes = state.services.notes.query_notes(user.id, &query).await?;
+
+    Ok(Json(notes.into_iter().map(NoteResponse::from).collect()))
 }
 
 /// Create a new note
 /// POST /api/v1/notes
 pub async fn create_note(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
-    Json(payload): Json<CreateNoteRequest>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    ValidatedJson(payload): ValidatedJson<CreateNoteRequest>,
 ) -> ApiResult<(StatusCode, Json<NoteResponse>)> {
     let user_id = user.id;
 
-    // Validate input
-    payload
-        .validate()
-        .map_err(|e| ApiError::validation(e.to_string()))?;
-
     // Parse title into NoteTitle (optional - empty string becomes None)
     let title: Option<NoteTitle> = if payload.title.trim().is_empty() {
         None
     } else {
-        Some(
-            NoteTitle::try_from(payload.title)
-                .map_err(|e| ApiError::validation(format!("Invalid title: {}", e)))?,
-        )
+        Some(NoteTitle::try_from(payload.title).map_err(|e| ApiError::invalid_field("title", e))?)
     };
 
     // Parse tags into TagName values
     let tags: Vec<TagName> = payload
         .tags
         .into_iter()
-        .map(|s| {
-            TagName::try_from(s).map_err(|e| ApiError::validation(format!("Invalid tag: {}", e)))
+        .enumerate()
+        .map(|(i, s)| {
+            TagName::try_from(s).map_err(|e| ApiError::invalid_field(format!("tags[{}]", i), e))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    let remind_at = match payload.remind_at {
+        Some(text) => Some(state.services.notes.parse_reminder(user_id, &text).await?),
+        None => None,
+    };
+
     let domain_req = DomainCreateNote {
         user_id,
         title,
@@ -90,25 +119,99 @@ pub async fn create_note(
         tags,
         color: payload.color,
         is_pinned: payload.is_pinned,
+        location: parse_location(payload.latitude, payload.longitude)?,
+        place_name: payload
+            .place_name
+            .map(parse_place_name)
+            .transpose()?
+            .flatten(),
+        remind_at,
     };
 
-    let note = state.note_service.create_note(domain_req).await?;
+    let note = state.services.notes.create_note(domain_req).await?;
 
     // Event publishing is now handled in NoteService via MessageBroker
 
     Ok((StatusCode::CREATED, Json(NoteResponse::from(note))))
 }
 
+/// Create a note from quick-capture text with inline `#tag`, `!pinned` and
+/// `@when` markers
+/// POST /api/v1/capture
+pub async fn capture_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Json(payload): Json<CaptureRequest>,
+) -> ApiResult<(StatusCode, Json<NoteResponse>)> {
+    let note = state.services.notes.capture(user.id, &payload.text).await?;
+
+    Ok((StatusCode::CREATED, Json(NoteResponse::from(note))))
+}
+
+/// Validate a pair of coordinates in degrees
+fn parse_point(latitude: f64, longitude: f64) -> ApiResult<GeoPoint> {
+    let latitude = Latitude::new(latitude)
+        .map_err(|e| ApiError::validation(format!("Invalid location: {}", e)))?;
+    let longitude = Longitude::new(longitude)
+        .map_err(|e| ApiError::validation(format!("Invalid location: {}", e)))?;
+    Ok(GeoPoint::new(latitude, longitude))
+}
+
+/// Validate coordinates from a request body; both or neither must be given
+fn parse_location(latitude: Option<f64>, longitude: Option<f64>) -> ApiResult<Option<GeoPoint>> {
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => parse_point(latitude, longitude).map(Some),
+        (None, None) => Ok(None),
+        _ => Err(ApiError::validation(
+            "Latitude and longitude must be given together",
+        )),
+    }
+}
+
+/// Parse a place name; an empty string means none
+fn parse_place_name(name: String) -> ApiResult<Option<PlaceName>> {
+    if name.trim().is_empty() {
+        return Ok(None);
+    }
+    PlaceName::try_from(name)
+        .map(Some)
+        .map_err(|e| ApiError::validation(format!("Invalid place name: {}", e)))
+}
+
+/// Find notes written near a place, closest first
+/// GET /api/v1/notes/nearby?lat=&lon=&radius=&include_archived=
+pub async fn find_nearby_notes(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Query(query): Query<NearbyQuery>,
+) -> ApiResult<Json<Vec<NearbyNoteResponse>>> {
+    let center = parse_point(query.lat, query.lon)?;
+    let nearby = state
+        .services
+        .notes
+        .find_nearby(
+            user.id,
+            center,
+            query.radius.unwrap_or(DEFAULT_NEARBY_RADIUS_KM),
+            query.include_archived,
+        )
+        .await?;
+
+    Ok(Json(
+        nearby.into_iter().map(NearbyNoteResponse::from).collect(),
+    ))
+}
+
 /// Get a single note by ID
 /// GET /api/v1/notes/:id
 pub async fn get_note(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesRead>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<NoteResponse>> {
     let user_id = user.id;
 
-    let note = state.note_service.get_note(id, user_id).await?;
+    let note = state.services.notes.get_note(id, user_id).await?;
 
     Ok(Json(NoteResponse::from(note)))
 }
@@ -117,25 +220,18 @@ pub async fn get_note(
 /// PATCH /api/v1/notes/:id
 pub async fn update_note(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateNoteRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateNoteRequest>,
 ) -> ApiResult<Json<NoteResponse>> {
     let user_id = user.id;
 
-    // Validate input
-    payload
-        .validate()
-        .map_err(|e| ApiError::validation(e.to_string()))?;
-
     // Parse optional title - Some(string) -> Some(Some(NoteTitle)) or Some(None) for empty
     let title: Option<Option<NoteTitle>> = match payload.title {
         Some(t) if t.trim().is_empty() => Some(None), // Set title to None
-        Some(t) => {
-            Some(Some(NoteTitle::try_from(t).map_err(|e| {
-                ApiError::validation(format!("Invalid title: {}", e))
-            })?))
-        }
+        Some(t) => Some(Some(
+            NoteTitle::try_from(t).map_err(|e| ApiError::invalid_field("title", e))?,
+        )),
         None => None, // Don't update title
     };
 
@@ -144,15 +240,24 @@ pub async fn update_note(
         Some(tag_strings) => Some(
             tag_strings
                 .into_iter()
-                .map(|s| {
+                .enumerate()
+                .map(|(i, s)| {
                     TagName::try_from(s)
-                        .map_err(|e| ApiError::validation(format!("Invalid tag: {}", e)))
+                        .map_err(|e| ApiError::invalid_field(format!("tags[{}]", i), e))
                 })
                 .collect::<Result<Vec<_>, _>>()?,
         ),
         None => None,
     };
 
+    let remind_at = match payload.remind_at {
+        Some(Some(text)) => Some(Some(
+            state.services.notes.parse_reminder(user_id, &text).await?,
+        )),
+        Some(None) => Some(None),
+        None => None,
+    };
+
     let domain_req = DomainUpdateNote {
         id,
         user_id,
@@ -162,54 +267,124 @@ pub async fn update_note(
         is_archived: payload.is_archived,
         color: payload.color,
         tags,
+        location: match (payload.latitude, payload.longitude) {
+            (None, None) => None,
+            (latitude, longitude) => Some(parse_location(latitude.flatten(), longitude.flatten())?),
+        },
+        place_name: payload.place_name.map(parse_place_name).transpose()?,
+        remind_at,
     };
 
-    let note = state.note_service.update_note(domain_req).await?;
+    let note = state.services.notes.update_note(domain_req).await?;
 
     // Event publishing is now handled in NoteService via MessageBroker
 
     Ok(Json(NoteResponse::from(note)))
 }
 
-/// Delete a note
+/// Move a note to the trash
 /// DELETE /api/v1/notes/:id
 pub async fn delete_note(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<StatusCode> {
     let user_id = user.id;
 
-    state.note_service.delete_note(id, user_id).await?;
+    state.services.notes.delete_note(id, user_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Search notes
-/// GET /api/v1/notes/search
+/// Search notes, optionally across versions too
+/// GET /api/v1/search?q=&include_archived=&scope=notes,versions
 pub async fn search_notes(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesRead>,
     Query(query): Query<SearchQuery>,
-) -> ApiResult<Json<Vec<NoteResponse>>> {
+) -> ApiResult<Json<SearchResponse>> {
     let user_id = user.id;
 
-    let notes = state.note_service.search_notes(user_id, &query.q).await?;
+    if let Some(ref scope) = query.scope {
+        let scope: SearchScope = scope.parse()?;
+        let hits = state
+            .services
+            .notes
+            .search_scoped(user_id, &query.q, scope, query.include_archived)
+            .await?;
+        return Ok(Json(SearchResponse::Hits(
+            hits.into_iter().map(SearchHitResponse::from).collect(),
+        )));
+    }
+
+    let notes = state
+        .services
+        .notes
+        .search_notes(user_id, &query.q, query.include_archived)
+        .await?;
     let response: Vec<NoteResponse> = notes.into_iter().map(NoteResponse::from).collect();
 
-    Ok(Json(response))
+    Ok(Json(SearchResponse::Notes(response)))
+}
+
+/// Suggest titles, tags and earlier queries for a search prefix
+/// GET /api/v1/search/suggest?q=
+pub async fn suggest_search(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Query(query): Query<SuggestQuery>,
+) -> ApiResult<Json<SuggestionsResponse>> {
+    let suggestions = state
+        .services
+        .notes
+        .suggest_search(user.id, &query.q)
+        .await?;
+
+    Ok(Json(SuggestionsResponse::from(suggestions)))
+}
+
+/// List the current user's recent searches
+/// GET /api/v1/search/history?limit=
+pub async fn get_search_history(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Query(query): Query<SearchHistoryQuery>,
+) -> ApiResult<Json<Vec<SearchHistoryEntryResponse>>> {
+    let history = state
+        .services
+        .notes
+        .search_history(user.id, query.limit)
+        .await?;
+
+    Ok(Json(
+        history
+            .into_iter()
+            .map(SearchHistoryEntryResponse::from)
+            .collect(),
+    ))
+}
+
+/// Clear the current user's search history
+/// DELETE /api/v1/search/history
+pub async fn clear_search_history(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+) -> ApiResult<StatusCode> {
+    state.services.notes.clear_search_history(user.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// List versions of a note
 /// GET /api/v1/notes/:id/versions
 pub async fn list_note_versions(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesRead>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<Json<Vec<crate::dto::NoteVersionResponse>>> {
     let user_id = user.id;
 
-    let versions = state.note_service.list_note_versions(id, user_id).await?;
+    let versions = state.services.notes.list_note_versions(id, user_id).await?;
     let response: Vec<crate::dto::NoteVersionResponse> = versions
         .into_iter()
         .map(crate::dto::NoteVersionResponse::from)
@@ -219,22 +394,119 @@ pub async fn list_note_versions(
 }
 
 /// Get related notes
+/// The `limit` and `min_score` of a related notes request, checked
+#[cfg(feature = "smart-features")]
+fn related_bounds(limit: Option<usize>, min_score: Option<f32>) -> ApiResult<(usize, f32)> {
+    let limit = limit.unwrap_or(DEFAULT_RELATED_LIMIT);
+    if limit == 0 || limit > MAX_RELATED_LIMIT {
+        return Err(ApiError::validation(format!(
+            "limit must be between 1 and {}",
+            MAX_RELATED_LIMIT
+        )));
+    }
+    let min_score = min_score.unwrap_or(0.0);
+    if !(0.0..=1.0).contains(&min_score) {
+        return Err(ApiError::validation("min_score must be between 0 and 1"));
+    }
+    Ok((limit, min_score))
+}
+
 /// GET /api/v1/notes/:id/related
 /// Get related notes
-/// GET /api/v1/notes/:id/related
+/// GET /api/v1/notes/:id/related?limit=&min_score=
 #[cfg(feature = "smart-features")]
 pub async fn get_related_notes(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesRead>,
     Path(id): Path<Uuid>,
+    Query(query): Query<crate::dto::RelatedNotesQuery>,
 ) -> ApiResult<Json<Vec<crate::dto::NoteLinkResponse>>> {
     let user_id = user.id;
+    let (limit, min_score) = related_bounds(query.limit, query.min_score)?;
 
     // Verify access to the source note
-    state.note_service.get_note(id, user_id).await?;
+    state.services.notes.get_note(id, user_id).await?;
+
+    // Links stored before smart features were switched off are stale
+    if !state.services.settings.current().smart_features_enabled {
+        return Ok(Json(Vec::new()));
+    }
 
     // Get links
-    let links = state.link_repo.get_links_for_note(id).await?;
+    let links = state
+        .services
+        .links
+        .get_links_for_note(id, limit, min_score)
+        .await?;
+    let response: Vec<crate::dto::NoteLinkResponse> = links
+        .into_iter()
+        .map(crate::dto::NoteLinkResponse::from)
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// Existing notes similar to unsaved text, for "you already wrote about
+/// this" hints while editing
+/// POST /api/v1/notes/similar
+#[cfg(feature = "smart-features")]
+pub async fn find_similar_notes(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    ValidatedJson(payload): ValidatedJson<crate::dto::SimilarNotesRequest>,
+) -> ApiResult<Json<Vec<crate::dto::SimilarNoteResponse>>> {
+    let (limit, min_score) = related_bounds(payload.limit, payload.min_score)?;
+
+    if !state.services.settings.current().smart_features_enabled {
+        return Ok(Json(Vec::new()));
+    }
+    let smart = state.services.smart.get().await?.ok_or_else(|| {
+        ApiError::ServiceUnavailable("Similar notes need a vector store".to_string())
+    })?;
+
+    let similar = smart
+        .find_similar_to_text(user.id, &payload.content, payload.note_id, limit)
+        .await?;
+    let mut response = Vec::new();
+    for (id, score) in similar.into_iter().filter(|(_, score)| *score >= min_score) {
+        // Vectors of deleted notes stay until the worker drops them
+        match state.services.notes.get_note(id, user.id).await {
+            Ok(note) if !note.is_trashed() => {
+                response.push(crate::dto::SimilarNoteResponse::new(&note, score))
+            }
+            Ok(_) => {}
+            Err(e) if e.is_not_found() => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(Json(response))
+}
+
+/// Embed a note and find its related notes again, without waiting for the
+/// worker to pick up its change
+/// POST /api/v1/notes/:id/process
+#[cfg(feature = "smart-features")]
+pub async fn process_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<crate::dto::NoteLinkResponse>>> {
+    let note = state.services.notes.get_note(id, user.id).await?;
+
+    if !state.services.settings.current().smart_features_enabled {
+        return Err(ApiError::ServiceUnavailable(
+            "Smart features are disabled on this instance".to_string(),
+        ));
+    }
+    let smart = state.services.smart.get().await?.ok_or_else(|| {
+        ApiError::ServiceUnavailable("Note processing needs a vector store".to_string())
+    })?;
+
+    smart.refresh_note(&note).await?;
+    let links = smart
+        .get_related_notes(id, DEFAULT_RELATED_LIMIT, 0.0)
+        .await?;
     let response: Vec<crate::dto::NoteLinkResponse> = links
         .into_iter()
         .map(crate::dto::NoteLinkResponse::from)
@@ -242,3 +514,200 @@ pub async fn get_related_notes(
 
     Ok(Json(response))
 }
+
+/// Writing statistics of a note, with spelling and grammar suggestions when
+/// the instance has a proofreader
+/// POST /api/v1/notes/{id}/proofread
+pub async fn proofread_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<ProofreadResponse>> {
+    let proofreading = state.services.proofreading.proofread(id, user.id).await?;
+
+    Ok(Json(ProofreadResponse::from(proofreading)))
+}
+
+/// Translate a note into a child note, or update the translation made before
+/// POST /api/v1/notes/{id}/translate?to=
+pub async fn translate_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<TranslateQuery>,
+) -> ApiResult<(StatusCode, Json<NoteResponse>)> {
+    let translations = state.services.translations.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Translation needs a text generator".to_string())
+    })?;
+    let translation = translations.translate(id, user.id, query.to).await?;
+
+    let status = if translation.created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(NoteResponse::from(translation.note))))
+}
+
+/// Upload a voice memo, whose transcript the worker appends to the note.
+/// The body is the audio, with its type in `Content-Type`.
+/// POST /api/v1/notes/{id}/voice-memos
+pub async fn upload_voice_memo(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<(StatusCode, Json<VoiceMemoResponse>)> {
+    let transcription = state.services.transcription.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Voice memos are not enabled on this instance".to_string())
+    })?;
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let upload = transcription
+        .upload(id, user.id, content_type, body.to_vec())
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(VoiceMemoResponse::from(upload))))
+}
+
+/// Duplicate a note
+/// POST /api/v1/notes/{id}/duplicate?prefix_title=
+pub async fn duplicate_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DuplicateNoteQuery>,
+) -> ApiResult<(StatusCode, Json<NoteResponse>)> {
+    let note = state
+        .services
+        .notes
+        .duplicate_note(id, user.id, query.prefix_title)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(NoteResponse::from(note))))
+}
+
+/// Restore a note from the trash
+/// POST /api/v1/notes/{id}/restore
+pub async fn restore_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<NoteResponse>> {
+    let note = state.services.notes.restore_note(id, user.id).await?;
+
+    Ok(Json(NoteResponse::from(note)))
+}
+
+/// Lock a note against edits and deletion
+/// POST /api/v1/notes/{id}/lock
+pub async fn lock_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<NoteResponse>> {
+    let note = state
+        .services
+        .notes
+        .set_note_locked(id, user.id, true)
+        .await?;
+
+    Ok(Json(NoteResponse::from(note)))
+}
+
+/// Unlock a locked note
+/// POST /api/v1/notes/{id}/unlock
+pub async fn unlock_note(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<NoteResponse>> {
+    let note = state
+        .services
+        .notes
+        .set_note_locked(id, user.id, false)
+        .await?;
+
+    Ok(Json(NoteResponse::from(note)))
+}
+
+/// Rearrange the pinned notes
+/// PATCH /api/v1/notes/pins/reorder
+pub async fn reorder_pins(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Json(payload): Json<ReorderPinsRequest>,
+) -> ApiResult<Json<Vec<NoteResponse>>> {
+    let notes = state
+        .services
+        .notes
+        .reorder_pins(user.id, payload.note_ids)
+        .await?;
+
+    Ok(Json(notes.into_iter().map(NoteResponse::from).collect()))
+}
+
+/// Preview which notes the auto-archive policy would archive
+/// GET /api/v1/notes/auto-archive/preview?after_days=
+pub async fn preview_auto_archive(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Query(query): Query<AutoArchivePreviewQuery>,
+) -> ApiResult<Json<AutoArchivePreviewResponse>> {
+    let policy = match query.after_days {
+        Some(days) if days == 0 || days > MAX_AUTO_ARCHIVE_DAYS => {
+            return Err(ApiError::validation(format!(
+                "after_days must be between 1 and {}",
+                MAX_AUTO_ARCHIVE_DAYS
+            )));
+        }
+        Some(days) => AutoArchivePolicy::new(days),
+        None => {
+            let settings = state.services.users.get_settings(user.id).await?;
+            AutoArchivePolicy::from_settings(&settings).ok_or_else(|| {
+                ApiError::validation("Auto-archive is disabled; pass after_days to preview")
+            })?
+        }
+    };
+
+    let now = chrono::Utc::now();
+    let notes = state
+        .services
+        .notes
+        .auto_archive_candidates(user.id, policy, now)
+        .await?;
+
+    Ok(Json(AutoArchivePreviewResponse {
+        after_days: policy.after_days,
+        cutoff: policy.cutoff(now),
+        notes: notes.into_iter().map(NoteResponse::from).collect(),
+    }))
+}
+
+/// List problems the lint job found in a note
+/// GET /api/v1/notes/{id}/issues
+pub async fn list_note_issues(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<NoteIssueResponse>>> {
+    let issues = state.services.lint.note_issues(id, user.id).await?;
+
+    Ok(Json(
+        issues.into_iter().map(NoteIssueResponse::from).collect(),
+    ))
+}
+
+/// Summarize problems found across the current user's notes
+/// GET /api/v1/notes/issues
+pub async fn get_issue_report(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+) -> ApiResult<Json<IssueReportResponse>> {
+    let report = state.services.lint.report(user.id).await?;
+
+    Ok(Json(IssueReportResponse::from(report)))
+}
@@ -0,0 +1,62 @@
+//! Note relation route handlers
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use uuid::Uuid;
+
+use crate::dto::{CreateRelationRequest, NoteRelationResponse};
+use crate::error::ApiResult;
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+
+/// List a note's relations in both directions
+/// GET /api/v1/notes/:id/relations
+pub async fn list_relations(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Vec<NoteRelationResponse>>> {
+    let relations = state.services.relations.list_for_note(id, user.id).await?;
+
+    Ok(Json(
+        relations
+            .into_iter()
+            .map(NoteRelationResponse::from)
+            .collect(),
+    ))
+}
+
+/// Relate a note to another one
+/// POST /api/v1/notes/:id/relations
+pub async fn create_relation(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateRelationRequest>,
+) -> ApiResult<(StatusCode, Json<NoteRelationResponse>)> {
+    let relation = state
+        .services
+        .relations
+        .create(user.id, id, payload.kind, payload.target_id)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(NoteRelationResponse::from(relation)),
+    ))
+}
+
+/// Remove a relation
+/// DELETE /api/v1/relations/:id
+pub async fn delete_relation(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    state.services.relations.delete(id, user.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,116 @@
+//! Guest scratchpad route handlers
+//!
+//! Visitors without an account keep one scratch note in their session. Once
+//! they have registered, the note can be claimed into their account.
+
+use axum::{Json, extract::State, http::StatusCode};
+use notes_domain::{CreateNoteRequest, scratchpad::Scratchpad};
+use tower_sessions::Session;
+
+use crate::dto::{NoteResponse, ScratchpadRequest, ScratchpadResponse};
+use crate::error::{ApiError, ApiResult};
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+
+/// Session key the scratch note is stored under
+const SCRATCHPAD_KEY: &str = "guest_scratchpad";
+
+fn ensure_enabled(state: &AppState) -> ApiResult<()> {
+    if !state.config.guest_scratchpad {
+        return Err(ApiError::ServiceUnavailable(
+            "The guest scratchpad is not enabled on this instance".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn load(session: &Session) -> ApiResult<Option<Scratchpad>> {
+    session
+        .get(SCRATCHPAD_KEY)
+        .await
+        .map_err(|_| ApiError::Internal("Session error".into()))
+}
+
+/// Get the visitor's scratch note
+/// GET /api/v1/scratchpad
+pub async fn get_scratchpad(
+    State(state): State<AppState>,
+    session: Session,
+) -> ApiResult<Json<ScratchpadResponse>> {
+    ensure_enabled(&state)?;
+
+    Ok(Json(ScratchpadResponse::from(load(&session).await?)))
+}
+
+/// Replace the visitor's scratch note
+/// PUT /api/v1/scratchpad
+pub async fn update_scratchpad(
+    State(state): State<AppState>,
+    session: Session,
+    Json(payload): Json<ScratchpadRequest>,
+) -> ApiResult<Json<ScratchpadResponse>> {
+    ensure_enabled(&state)?;
+
+    let scratchpad = Scratchpad::new(payload.content)?;
+    session
+        .insert(SCRATCHPAD_KEY, &scratchpad)
+        .await
+        .map_err(|_| ApiError::Internal("Session error".into()))?;
+
+    Ok(Json(ScratchpadResponse::from(Some(scratchpad))))
+}
+
+/// Discard the visitor's scratch note
+/// DELETE /api/v1/scratchpad
+pub async fn delete_scratchpad(
+    State(state): State<AppState>,
+    session: Session,
+) -> ApiResult<StatusCode> {
+    ensure_enabled(&state)?;
+
+    let _: Option<Scratchpad> = session
+        .remove(SCRATCHPAD_KEY)
+        .await
+        .map_err(|_| ApiError::Internal("Session error".into()))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Turn the scratch note into a note of the now signed-in user
+/// POST /api/v1/scratchpad/claim
+pub async fn claim_scratchpad(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    session: Session,
+) -> ApiResult<(StatusCode, Json<NoteResponse>)> {
+    ensure_enabled(&state)?;
+
+    let scratchpad = load(&session)
+        .await?
+        .filter(|scratchpad| !scratchpad.is_empty())
+        .ok_or_else(|| ApiError::validation("The scratchpad is empty"))?;
+
+    let note = state
+        .services
+        .notes
+        .create_note(CreateNoteRequest {
+            user_id: user.id,
+            title: None,
+            content: scratchpad.content,
+            tags: Vec::new(),
+            color: None,
+            is_pinned: false,
+            location: None,
+            place_name: None,
+            remind_at: None,
+        })
+        .await?;
+
+    let _: Option<Scratchpad> = session
+        .remove(SCRATCHPAD_KEY)
+        .await
+        .map_err(|_| ApiError::Internal("Session error".into()))?;
+    tracing::info!(user_id = %user.id, note_id = %note.id, "Claimed guest scratchpad");
+
+    Ok((StatusCode::CREATED, Json(NoteResponse::from(note))))
+}
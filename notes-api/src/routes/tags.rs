@@ -2,30 +2,38 @@
 
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
 };
 use uuid::Uuid;
-use validator::Validate;
 
 use notes_domain::TagName;
 
 use crate::error::{ApiError, ApiResult};
 use crate::state::AppState;
+use crate::validation::ValidatedJson;
 use crate::{
-    dto::{CreateTagRequest, RenameTagRequest, TagResponse},
-    extractors::CurrentUser,
+    dto::{
+        CreateTagAliasRequest, CreateTagRequest, ListTagsQuery, ReorderTagsRequest,
+        TagAliasResponse, TagResponse, UpdateTagRequest,
+    },
+    extractors::{Scoped, scope},
 };
 
-/// List all tags for the user
+/// List all tags for the user, or only unused ones with `unused=true`
 /// GET /api/v1/tags
 pub async fn list_tags(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+    Query(query): Query<ListTagsQuery>,
 ) -> ApiResult<Json<Vec<TagResponse>>> {
     let user_id = user.id;
 
-    let tags = state.tag_service.list_tags(user_id).await?;
+    let tags = if query.unused {
+        state.services.tags.list_unused_tags(user_id).await?
+    } else {
+        state.services.tags.list_tags(user_id).await?
+    };
     let response: Vec<TagResponse> = tags.into_iter().map(TagResponse::from).collect();
 
     Ok(Json(response))
@@ -35,57 +43,130 @@ pub async fn list_tags(
 /// POST /api/v1/tags
 pub async fn create_tag(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
-    Json(payload): Json<CreateTagRequest>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    ValidatedJson(payload): ValidatedJson<CreateTagRequest>,
 ) -> ApiResult<(StatusCode, Json<TagResponse>)> {
     let user_id = user.id;
 
-    payload
-        .validate()
-        .map_err(|e| ApiError::validation(e.to_string()))?;
-
     // Parse string to TagName at API boundary
-    let tag_name = TagName::try_from(payload.name)
-        .map_err(|e| ApiError::validation(format!("Invalid tag name: {}", e)))?;
+    let tag_name =
+        TagName::try_from(payload.name).map_err(|e| ApiError::invalid_field("name", e))?;
 
-    let tag = state.tag_service.create_tag(user_id, tag_name).await?;
+    let tag = state.services.tags.create_tag(user_id, tag_name).await?;
 
     Ok((StatusCode::CREATED, Json(TagResponse::from(tag))))
 }
 
-/// Rename a tag
+/// Rename a tag and/or pin it to the top of the sidebar
 /// PATCH /api/v1/tags/:id
-pub async fn rename_tag(
+pub async fn update_tag(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<RenameTagRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateTagRequest>,
 ) -> ApiResult<Json<TagResponse>> {
     let user_id = user.id;
 
-    payload
-        .validate()
-        .map_err(|e| ApiError::validation(e.to_string()))?;
+    let mut tag = None;
+    if let Some(name) = payload.name {
+        // Parse string to TagName at API boundary
+        let new_name = TagName::try_from(name).map_err(|e| ApiError::invalid_field("name", e))?;
+        tag = Some(
+            state
+                .services
+                .tags
+                .rename_tag(id, user_id, new_name)
+                .await?,
+        );
+    }
+    if let Some(is_pinned) = payload.is_pinned {
+        tag = Some(
+            state
+                .services
+                .tags
+                .set_tag_pinned(id, user_id, is_pinned)
+                .await?,
+        );
+    }
+    let tag = tag.ok_or_else(|| ApiError::validation("Nothing to update"))?;
 
-    // Parse string to TagName at API boundary
-    let new_name = TagName::try_from(payload.name)
-        .map_err(|e| ApiError::validation(format!("Invalid tag name: {}", e)))?;
+    Ok(Json(TagResponse::from(tag)))
+}
 
-    let tag = state.tag_service.rename_tag(id, user_id, new_name).await?;
+/// Rearrange the tags in the sidebar
+/// PATCH /api/v1/tags/reorder
+pub async fn reorder_tags(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Json(payload): Json<ReorderTagsRequest>,
+) -> ApiResult<Json<Vec<TagResponse>>> {
+    let tags = state
+        .services
+        .tags
+        .reorder_tags(user.id, payload.tag_ids)
+        .await?;
 
-    Ok(Json(TagResponse::from(tag)))
+    Ok(Json(tags.into_iter().map(TagResponse::from).collect()))
 }
 
 /// Delete a tag
 /// DELETE /api/v1/tags/:id
 pub async fn delete_tag(
     State(state): State<AppState>,
-    CurrentUser(user): CurrentUser,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
     Path(id): Path<Uuid>,
 ) -> ApiResult<StatusCode> {
     let user_id = user.id;
 
-    state.tag_service.delete_tag(id, user_id).await?;
+    state.services.tags.delete_tag(id, user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the user's tag aliases
+/// GET /api/v1/tags/aliases
+pub async fn list_aliases(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesRead>,
+) -> ApiResult<Json<Vec<TagAliasResponse>>> {
+    let aliases = state.services.tag_aliases.list(user.id).await?;
+
+    Ok(Json(
+        aliases.into_iter().map(TagAliasResponse::from).collect(),
+    ))
+}
+
+/// Add an alternative name for a tag, merging any tag already using it
+/// POST /api/v1/tags/:id/aliases
+pub async fn create_alias(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateTagAliasRequest>,
+) -> ApiResult<(StatusCode, Json<TagAliasResponse>)> {
+    let alias =
+        TagName::try_from(payload.alias).map_err(|e| ApiError::invalid_field("alias", e))?;
+
+    let alias = state
+        .services
+        .tag_aliases
+        .create(user.id, id, alias)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(TagAliasResponse::from(alias))))
+}
+
+/// Remove a tag alias
+/// DELETE /api/v1/tags/aliases/:alias
+pub async fn delete_alias(
+    State(state): State<AppState>,
+    Scoped(user, _): Scoped<scope::NotesWrite>,
+    Path(alias): Path<String>,
+) -> ApiResult<StatusCode> {
+    let alias = TagName::try_from(alias)
+        .map_err(|e| ApiError::validation(format!("Invalid tag alias: {}", e)))?;
+
+    state.services.tag_aliases.delete(user.id, &alias).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
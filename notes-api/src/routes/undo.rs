@@ -0,0 +1,30 @@
+//! Undo and redo route handlers
+
+use axum::{Json, extract::State};
+
+use crate::dto::NoteResponse;
+use crate::error::ApiResult;
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+
+/// Revert the current user's latest trashing, archiving or tag change
+/// POST /api/v1/undo
+pub async fn undo(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> ApiResult<Json<Vec<NoteResponse>>> {
+    let notes = state.services.undo.undo(user.id).await?;
+
+    Ok(Json(notes.into_iter().map(NoteResponse::from).collect()))
+}
+
+/// Reapply what the last undo reverted
+/// POST /api/v1/redo
+pub async fn redo(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+) -> ApiResult<Json<Vec<NoteResponse>>> {
+    let notes = state.services.undo.redo(user.id).await?;
+
+    Ok(Json(notes.into_iter().map(NoteResponse::from).collect()))
+}
@@ -0,0 +1,104 @@
+//! Route handlers fetching from the web on behalf of clients
+//!
+//! Browsers cannot read most pages across origins, so the server fetches
+//! them, through clients that refuse private network addresses. Images are
+//! loaded the same way, so image hosts do not learn who reads a note.
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use notes_domain::ImageProxyService;
+use notes_domain::bookmarks::UNFURL_CACHE_MINUTES;
+use notes_domain::images::IMAGE_CACHE_SECONDS;
+
+use crate::dto::{
+    LinkPreviewResponse, ProxyImageQuery, SignImagesRequest, SignImagesResponse, UnfurlQuery,
+};
+use crate::error::{ApiError, ApiResult};
+use crate::extractors::CurrentUser;
+use crate::state::AppState;
+use crate::validation::ValidatedJson;
+
+/// Where the image proxy is served, which proxied image URLs point at
+pub const IMAGE_PROXY_PATH: &str = "/api/v1/proxy/image";
+
+/// What a page says about itself, for a link card while editing; 204 No
+/// Content if it cannot be loaded or is not HTML
+/// GET /api/v1/unfurl?url=
+pub async fn unfurl(
+    State(state): State<AppState>,
+    CurrentUser(_): CurrentUser,
+    Query(query): Query<UnfurlQuery>,
+) -> ApiResult<Response> {
+    let unfurl = state.services.unfurl.as_ref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Unfurling links is not enabled on this instance".to_string())
+    })?;
+    let cache_control = format!("private, max-age={}", UNFURL_CACHE_MINUTES * 60);
+
+    let response = match unfurl.unfurl(&query.url).await? {
+        Some(preview) => Json(LinkPreviewResponse::from(preview)).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    };
+    Ok(([(header::CACHE_CONTROL, cache_control)], response).into_response())
+}
+
+fn image_proxy(state: &AppState) -> ApiResult<&ImageProxyService> {
+    state.services.images.as_deref().ok_or_else(|| {
+        ApiError::ServiceUnavailable("The image proxy is not enabled on this instance".to_string())
+    })
+}
+
+/// An external image loaded for the reader; 404 Not Found if it cannot be
+/// loaded or is not a supported image. Public, since `<img>` requests carry
+/// no credentials, and limited to URLs the instance signed.
+/// GET /api/v1/proxy/image?url=&sig=
+pub async fn proxy_image(
+    State(state): State<AppState>,
+    Query(query): Query<ProxyImageQuery>,
+) -> ApiResult<Response> {
+    let Some(image) = image_proxy(&state)?.fetch(&query.url, &query.sig).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, image.content_type.clone()),
+            (
+                header::CACHE_CONTROL,
+                format!("public, max-age={}", IMAGE_CACHE_SECONDS),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (
+                header::CONTENT_SECURITY_POLICY,
+                "default-src 'none'".to_string(),
+            ),
+        ],
+        image.bytes.clone(),
+    )
+        .into_response())
+}
+
+/// Proxy URLs for the external images of notes clients render themselves
+/// POST /api/v1/proxy/image/sign
+pub async fn sign_images(
+    State(state): State<AppState>,
+    CurrentUser(_): CurrentUser,
+    ValidatedJson(payload): ValidatedJson<SignImagesRequest>,
+) -> ApiResult<Json<SignImagesResponse>> {
+    let images = image_proxy(&state)?;
+
+    Ok(Json(SignImagesResponse {
+        images: payload
+            .urls
+            .into_iter()
+            .filter_map(|url| {
+                let proxied = images.proxy_url(IMAGE_PROXY_PATH, &url)?;
+                Some((url, proxied))
+            })
+            .collect(),
+    }))
+}
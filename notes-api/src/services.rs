@@ -0,0 +1,504 @@
+//! Composition root
+//!
+//! Every repository, adapter and service the API uses is built here, once at
+//! startup, from the configuration, and handed to handlers through
+//! [`crate::state::AppState::services`]. Routes take what they need from the
+//! [`ServiceRegistry`] instead of wiring repositories themselves; a new
+//! service is built in [`ServiceRegistry::build`] and added as a field.
+
+use std::sync::Arc;
+
+use k_core::db::DatabasePool;
+use notes_domain::{
+    ActivityService, AnnouncementService, BoardService, ChallengeVerifier, DiagramService,
+    EmailSender, EventDispatcher, HousekeepingService, ImageProxyService, ImpersonationService,
+    InstanceSettingsRepository, InstanceSettingsService, InvitationService, JobService,
+    LegalService, NoteLintService, NoteRelationService, NoteRepository, NoteService,
+    OnboardingService, PdfRenderer, ProofreadService, SpeechService, TagAliasService,
+    TagRepository, TagService, TranscriptionService, TranslationService, UndoService,
+    UnfurlService, UserService, instance::InstanceSettings, onboarding::OnboardingTemplate,
+    ports::VectorStore,
+};
+#[cfg(feature = "smart-features")]
+use notes_domain::{DomainError, DomainResult, SmartNoteService};
+use notes_infra::factory::{
+    CacheableRepositories, ReplicableRepositories, build_announcement_repository,
+    build_audio_upload_repository, build_board_repository, build_cache, build_challenge_verifier,
+    build_diagram_renderer, build_email_sender, build_event_log_repository,
+    build_housekeeping_repository, build_image_proxy, build_impersonation_repository,
+    build_instance_settings_repository, build_invitation_repository, build_job_repository,
+    build_legal_repository, build_link_preview_fetcher, build_message_broker,
+    build_note_issue_repository, build_note_relation_repository, build_note_repository,
+    build_password_hasher, build_pdf_renderer, build_proofreader, build_search_history_repository,
+    build_speech_synthesizer, build_tag_alias_repository, build_tag_repository,
+    build_text_generator, build_unit_of_work, build_user_repository, mirror_message_broker,
+};
+#[cfg(feature = "smart-features")]
+use notes_infra::factory::{EmbeddingProvider, build_embedding_generator, build_link_repository};
+use notes_infra::formats::FormatRegistry;
+#[cfg(feature = "smart-features")]
+use tokio::sync::OnceCell;
+
+use crate::config::Config;
+use crate::maintenance::MaintenanceMode;
+
+/// The services and adapters handlers use
+pub struct ServiceRegistry {
+    pub notes: Arc<NoteService>,
+    pub tags: Arc<TagService>,
+    pub users: Arc<UserService>,
+    pub jobs: Arc<JobService>,
+    pub announcements: Arc<AnnouncementService>,
+    pub invitations: Arc<InvitationService>,
+    pub activity: Arc<ActivityService>,
+    pub undo: Arc<UndoService>,
+    pub lint: Arc<NoteLintService>,
+    pub boards: Arc<BoardService>,
+    pub relations: Arc<NoteRelationService>,
+    pub tag_aliases: Arc<TagAliasService>,
+    pub impersonations: Arc<ImpersonationService>,
+    pub legal: Arc<LegalService>,
+    pub proofreading: Arc<ProofreadService>,
+    /// `None` without a text generator to translate with
+    pub translations: Option<Arc<TranslationService>>,
+    /// `None` without a speech synthesizer to read notes aloud with
+    pub speech: Option<Arc<SpeechService>>,
+    /// `None` unless the worker transcribes voice memos
+    pub transcription: Option<Arc<TranscriptionService>>,
+    /// `None` without a renderer to draw diagrams of rendered notes with
+    pub diagrams: Option<Arc<DiagramService>>,
+    /// `None` without a fetcher to unfurl links with
+    pub unfurl: Option<Arc<UnfurlService>>,
+    /// `None` without a fetcher to load external images of notes with
+    pub images: Option<Arc<ImageProxyService>>,
+    pub housekeeping: Arc<HousekeepingService>,
+    pub pdf_renderer: Option<Arc<dyn PdfRenderer>>,
+    /// Import and export formats by name
+    pub formats: Arc<FormatRegistry>,
+    pub email_sender: Arc<dyn EmailSender>,
+    /// Bot check on registration; `None` when disabled
+    pub challenge: Option<Arc<dyn ChallengeVerifier>>,
+    pub instance_settings: Arc<dyn InstanceSettingsRepository>,
+    /// Used for admin metrics and [`SmartProcessing`]; searches go through
+    /// the note service
+    pub vector_store: Option<Arc<dyn VectorStore>>,
+    pub maintenance: Arc<MaintenanceMode>,
+    /// Runtime settings; prefer these over the matching `config` fields
+    pub settings: Arc<InstanceSettingsService>,
+    /// Backups are exported and restored as stored, past the services'
+    /// rules; everything else goes through the services
+    pub backups: BackupRepositories,
+    /// Semantic links between notes, found by the worker
+    #[cfg(feature = "smart-features")]
+    pub links: Arc<dyn notes_domain::ports::LinkRepository>,
+    /// Embeds notes on request, ahead of the worker
+    #[cfg(feature = "smart-features")]
+    pub smart: SmartProcessing,
+}
+
+/// Repositories backups are read from and restored into
+pub struct BackupRepositories {
+    pub notes: Arc<dyn NoteRepository>,
+    pub tags: Arc<dyn TagRepository>,
+}
+
+/// The [`SmartNoteService`] of the API, built on first use: loading the
+/// embedding model is slow and costs memory most instances never need, as
+/// the worker embeds notes on its own
+#[cfg(feature = "smart-features")]
+pub struct SmartProcessing {
+    provider: EmbeddingProvider,
+    vector_store: Option<Arc<dyn VectorStore>>,
+    links: Arc<dyn notes_domain::ports::LinkRepository>,
+    service: OnceCell<Arc<SmartNoteService>>,
+}
+
+#[cfg(feature = "smart-features")]
+impl SmartProcessing {
+    /// The service, or `None` without a vector store to embed into
+    pub async fn get(&self) -> DomainResult<Option<Arc<SmartNoteService>>> {
+        let Some(vector_store) = &self.vector_store else {
+            return Ok(None);
+        };
+        let service = self
+            .service
+            .get_or_try_init(|| async {
+                tracing::info!("Loading embedding model for on-demand processing");
+                let embedder = build_embedding_generator(&self.provider)
+                    .await
+                    .map_err(|e| {
+                        DomainError::InfrastructureError(format!(
+                            "Embedding model unavailable: {}",
+                            e
+                        ))
+                    })?;
+                Ok::<_, DomainError>(Arc::new(SmartNoteService::new(
+                    embedder,
+                    vector_store.clone(),
+                    self.links.clone(),
+                )))
+            })
+            .await?;
+        Ok(Some(service.clone()))
+    }
+}
+
+impl ServiceRegistry {
+    /// Build every service on `pool`, reading from `read_pool` where
+    /// repositories can
+    pub async fn build(
+        config: &Config,
+        pool: &DatabasePool,
+        read_pool: Option<&DatabasePool>,
+    ) -> anyhow::Result<Self> {
+        // Create repositories via factory
+        let note_repo = build_note_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let tag_repo = build_tag_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let user_repo = build_user_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let unit_of_work = build_unit_of_work(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let ReplicableRepositories {
+            note_repo,
+            tag_repo,
+            user_repo,
+        } = ReplicableRepositories {
+            note_repo,
+            tag_repo,
+            user_repo,
+        }
+        .with_replica(read_pool)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+        let cache = build_cache(&config.cache_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!("Cache connection failed: {}", e))?;
+        let CacheableRepositories {
+            note_repo,
+            tag_repo,
+            unit_of_work,
+        } = CacheableRepositories {
+            note_repo,
+            tag_repo,
+            unit_of_work,
+        }
+        .with_cache(cache);
+        let search_history = build_search_history_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        #[cfg(feature = "smart-features")]
+        let link_repo = build_link_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        // Connect to message broker via factory
+        #[cfg(feature = "smart-features")]
+        let message_broker = {
+            use notes_infra::factory::BrokerProvider;
+            tracing::info!("Connecting to message broker: {}", config.broker_url);
+            let provider = BrokerProvider::Nats {
+                url: config.broker_url.clone(),
+            };
+            build_message_broker(&provider)
+                .await
+                .map_err(|e| anyhow::anyhow!("Broker connection failed: {}", e))?
+        };
+        #[cfg(not(feature = "smart-features"))]
+        let message_broker = None;
+        // Home-automation integrations receive a copy of every event
+        let message_broker = mirror_message_broker(
+            message_broker,
+            build_message_broker(&config.mqtt_provider)
+                .await
+                .map_err(|e| anyhow::anyhow!("MQTT setup failed: {}", e))?,
+        );
+
+        let instance_settings = build_instance_settings_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let maintenance = Arc::new(
+            MaintenanceMode::load(instance_settings.clone(), config.read_only)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+        );
+
+        // Settings changed by administrators take precedence over the environment
+        let settings = Arc::new(
+            InstanceSettingsService::load(
+                instance_settings.clone(),
+                InstanceSettings {
+                    allow_registration: config.allow_registration,
+                    max_pinned_notes: config.max_pinned_notes,
+                    ..Default::default()
+                },
+            )
+            .await?,
+        );
+
+        // Create services
+        let event_log = build_event_log_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let events = Arc::new(EventDispatcher::new().with_event_log(event_log.clone()));
+
+        let tag_alias_repo = build_tag_alias_repository(pool)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        // Build NoteService with user settings and optional MessageBroker
+        let note_service = NoteService::new(note_repo.clone(), tag_repo.clone())
+            .with_user_repository(user_repo.clone())
+            .with_tag_aliases(tag_alias_repo.clone())
+            .with_event_dispatcher(events.clone())
+            .with_unit_of_work(unit_of_work)
+            .with_search_history(search_history)
+            .with_instance_settings(settings.clone())
+            .with_version_debounce_minutes(config.version_debounce_minutes);
+        let tag_service = TagService::new(tag_repo.clone()).with_event_dispatcher(events.clone());
+        let password_hasher =
+            build_password_hasher(&config.password_hash).map_err(|e| anyhow::anyhow!(e))?;
+        let user_service = UserService::new(user_repo.clone(), password_hasher);
+        let (note_service, tag_service, user_service) = match message_broker.clone() {
+            Some(broker) => (
+                note_service.with_message_broker(broker.clone()),
+                tag_service.with_message_broker(broker.clone()),
+                user_service.with_message_broker(broker),
+            ),
+            None => (note_service, tag_service, user_service),
+        };
+        let note_service = Arc::new(note_service);
+        let tag_service = Arc::new(tag_service);
+        let user_service = match &config.onboarding_template {
+            Some(path) => {
+                let template: OnboardingTemplate =
+                    serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+                        anyhow::anyhow!("Invalid onboarding template {}: {}", path, e)
+                    })?;
+                tracing::info!(path = %path, notes = template.notes.len(), "Loaded onboarding template");
+                user_service.with_onboarding(Arc::new(
+                    OnboardingService::new(note_service.clone(), tag_service.clone(), template)
+                        .map_err(|e| anyhow::anyhow!(e))?,
+                ))
+            }
+            None => user_service,
+        };
+        let user_service = Arc::new(user_service);
+
+        let job_service = Arc::new(JobService::new(
+            build_job_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+        ));
+
+        let announcement_service = Arc::new(AnnouncementService::new(
+            build_announcement_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+        ));
+
+        let invitation_service = Arc::new(InvitationService::new(
+            build_invitation_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+        ));
+
+        let impersonation_service = Arc::new(ImpersonationService::new(
+            build_impersonation_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+            user_repo.clone(),
+        ));
+
+        let legal_service = Arc::new(LegalService::new(
+            build_legal_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+        ));
+
+        let board_service = Arc::new(BoardService::new(
+            build_board_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+            note_service.clone(),
+        ));
+
+        let relation_service = Arc::new(NoteRelationService::new(
+            build_note_relation_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+            note_service.clone(),
+        ));
+
+        let proofread_service = ProofreadService::new(note_service.clone());
+        let proofread_service = match build_proofreader(&config.proofreader_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+        {
+            Some(proofreader) => proofread_service.with_proofreader(proofreader),
+            None => proofread_service,
+        };
+        let proofread_service = Arc::new(proofread_service);
+
+        let translation_service = build_text_generator(&config.text_generator_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .map(|text_generator| {
+                Arc::new(TranslationService::new(
+                    note_service.clone(),
+                    relation_service.clone(),
+                    text_generator,
+                ))
+            });
+
+        let speech_service = build_speech_synthesizer(&config.speech_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .map(|synthesizer| Arc::new(SpeechService::new(note_service.clone(), synthesizer)));
+
+        let transcription_service = if config.voice_memos {
+            Some(Arc::new(TranscriptionService::new(
+                note_service.clone(),
+                build_audio_upload_repository(pool)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))?,
+            )))
+        } else {
+            None
+        };
+
+        let diagram_service = build_diagram_renderer(&config.diagram_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .map(|renderer| Arc::new(DiagramService::new(renderer)));
+
+        let unfurl_service = build_link_preview_fetcher(&config.link_preview_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .map(|fetcher| Arc::new(UnfurlService::new(fetcher)));
+
+        let image_proxy_service = build_image_proxy(&config.image_proxy_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .map(|(fetcher, signer)| Arc::new(ImageProxyService::new(fetcher, signer)));
+
+        let tag_alias_service = Arc::new(
+            TagAliasService::new(tag_alias_repo, tag_repo.clone())
+                .with_event_dispatcher(events.clone()),
+        );
+
+        let undo_service = Arc::new(
+            UndoService::new(note_service.clone(), event_log.clone()).with_event_dispatcher(events),
+        );
+        let activity_service = Arc::new(ActivityService::new(event_log));
+        // The worker runs the checks; the API only reads what they found
+        let lint_service = Arc::new(NoteLintService::new(
+            note_repo.clone(),
+            build_note_issue_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+        ));
+
+        let pdf_renderer = build_pdf_renderer(&config.pdf_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let formats = Arc::new(FormatRegistry::builtin(
+            pdf_renderer.clone(),
+            config.render.clone(),
+        ));
+        let email_sender = build_email_sender(&config.mail_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let challenge = build_challenge_verifier(&config.challenge_provider)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        // The worker owns indexing; the API only reads the vector count and
+        // embeds single notes on request, so a missing vector store is not
+        // fatal
+        #[cfg(feature = "smart-features")]
+        let vector_store = match notes_infra::factory::build_vector_store(&config.vector_provider)
+            .await
+        {
+            Ok(store) => Some(store),
+            Err(e) => {
+                tracing::warn!(
+                    "Vector store unavailable, admin overview and note processing will omit it: {}",
+                    e
+                );
+                None
+            }
+        };
+        #[cfg(not(feature = "smart-features"))]
+        let vector_store = None;
+
+        // Notes without embeddings are queued for the worker through the broker
+        let housekeeping_service = HousekeepingService::new(
+            build_housekeeping_repository(pool)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?,
+            note_repo.clone(),
+            user_repo.clone(),
+        );
+        let housekeeping_service = match &vector_store {
+            Some(store) => housekeeping_service.with_vector_store(store.clone()),
+            None => housekeeping_service,
+        };
+        let housekeeping_service = match message_broker {
+            Some(broker) => housekeeping_service.with_message_broker(broker),
+            None => housekeeping_service,
+        };
+        let housekeeping_service = Arc::new(housekeeping_service);
+
+        Ok(Self {
+            notes: note_service,
+            tags: tag_service,
+            users: user_service,
+            jobs: job_service,
+            announcements: announcement_service,
+            invitations: invitation_service,
+            activity: activity_service,
+            undo: undo_service,
+            lint: lint_service,
+            boards: board_service,
+            relations: relation_service,
+            tag_aliases: tag_alias_service,
+            impersonations: impersonation_service,
+            legal: legal_service,
+            proofreading: proofread_service,
+            translations: translation_service,
+            speech: speech_service,
+            transcription: transcription_service,
+            diagrams: diagram_service,
+            unfurl: unfurl_service,
+            images: image_proxy_service,
+            housekeeping: housekeeping_service,
+            pdf_renderer,
+            formats,
+            email_sender,
+            challenge,
+            instance_settings,
+            #[cfg(feature = "smart-features")]
+            smart: SmartProcessing {
+                provider: config.embedding_provider.clone(),
+                vector_store: vector_store.clone(),
+                links: link_repo.clone(),
+                service: OnceCell::new(),
+            },
+            vector_store,
+            maintenance,
+            settings,
+            backups: BackupRepositories {
+                notes: note_repo,
+                tags: tag_repo,
+            },
+            #[cfg(feature = "smart-features")]
+            links: link_repo,
+        })
+    }
+}
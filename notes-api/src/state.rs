@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::config::{AuthMode, Config};
-use notes_domain::{NoteRepository, NoteService, TagRepository, TagService, UserService};
+use crate::services::ServiceRegistry;
 
 #[cfg(feature = "auth-jwt")]
 use notes_infra::auth::jwt::{JwtConfig, JwtValidator};
@@ -11,13 +11,8 @@ use notes_infra::auth::oidc::OidcService;
 /// Application state holding all dependencies
 #[derive(Clone)]
 pub struct AppState {
-    pub note_repo: Arc<dyn NoteRepository>,
-    pub tag_repo: Arc<dyn TagRepository>,
-    #[cfg(feature = "smart-features")]
-    pub link_repo: Arc<dyn notes_domain::ports::LinkRepository>,
-    pub note_service: Arc<NoteService>,
-    pub tag_service: Arc<TagService>,
-    pub user_service: Arc<UserService>,
+    /// Services built by the composition root
+    pub services: Arc<ServiceRegistry>,
     pub config: Config,
     #[cfg(feature = "auth-oidc")]
     pub oidc_service: Option<Arc<OidcService>>,
@@ -26,15 +21,7 @@ pub struct AppState {
 }
 
 impl AppState {
-    pub async fn new(
-        note_repo: Arc<dyn NoteRepository>,
-        tag_repo: Arc<dyn TagRepository>,
-        #[cfg(feature = "smart-features")] link_repo: Arc<dyn notes_domain::ports::LinkRepository>,
-        note_service: Arc<NoteService>,
-        tag_service: Arc<TagService>,
-        user_service: Arc<UserService>,
-        config: Config,
-    ) -> anyhow::Result<Self> {
+    pub async fn new(services: ServiceRegistry, config: Config) -> anyhow::Result<Self> {
         #[cfg(feature = "auth-oidc")]
         let oidc_service = if let (Some(issuer), Some(id), secret, Some(redirect), resource_id) = (
             &config.oidc_issuer,
@@ -106,13 +93,7 @@ impl AppState {
         };
 
         Ok(Self {
-            note_repo,
-            tag_repo,
-            #[cfg(feature = "smart-features")]
-            link_repo,
-            note_service,
-            tag_service,
-            user_service,
+            services: Arc::new(services),
             config,
             #[cfg(feature = "auth-oidc")]
             oidc_service,
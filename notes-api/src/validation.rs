@@ -0,0 +1,89 @@
+//! Request body validation
+//!
+//! [`ValidatedJson`] reads a JSON body and checks its `validator` rules,
+//! rejecting it with the fields that failed, so frontends can point at the
+//! offending input. Fields are named by their path in the body, like
+//! `title` or `tags[2]`.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::header,
+};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::error::{ApiError, FieldError};
+
+/// JSON request body that deserialized and passed its validation rules
+pub struct ValidatedJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                let essence = value.split(';').next().unwrap_or_default().trim();
+                essence == "application/json"
+                    || (essence.starts_with("application/") && essence.ends_with("+json"))
+            });
+        if !is_json {
+            return Err(ApiError::validation(
+                "Expected a request with `Content-Type: application/json`",
+            ));
+        }
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| ApiError::validation(e.body_text()))?;
+        let deserializer = &mut serde_json::Deserializer::from_slice(&body);
+        let value: T = serde_path_to_error::deserialize(deserializer).map_err(body_error)?;
+        value.validate()?;
+
+        Ok(Self(value))
+    }
+}
+
+/// The field a body failed to deserialize at, when there is one
+fn body_error(error: serde_path_to_error::Error<serde_json::Error>) -> ApiError {
+    let path = error.path().to_string();
+    let error = error.into_inner();
+    if error.is_syntax() || error.is_eof() {
+        return ApiError::validation(format!("Malformed JSON: {}", error));
+    }
+
+    // serde_json appends the position, which means little to users
+    let message = error.to_string();
+    let message = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(message, _)| message);
+
+    // Missing fields are reported on their parent
+    if let Some(field) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'))
+    {
+        let field = match path.as_str() {
+            "." => field.to_string(),
+            parent => format!("{}.{}", parent, field),
+        };
+        return ApiError::InvalidFields(vec![FieldError::new(
+            field,
+            "required",
+            "This field is required",
+        )]);
+    }
+
+    if path == "." {
+        return ApiError::validation(message);
+    }
+    ApiError::InvalidFields(vec![FieldError::new(path, "invalid", message)])
+}
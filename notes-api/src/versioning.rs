@@ -0,0 +1,199 @@
+//! API versions
+//!
+//! `/api/v2` serves the same handlers as `/api/v1`; [`compat_shim`] rewrites
+//! their responses into the v2 shapes, so response changes land in v2 while
+//! v1 clients keep getting what they always got:
+//!
+//! - lists are wrapped in a pagination envelope,
+//!   `{"data": [...], "pagination": {"limit", "offset", "count"}}`
+//! - errors are RFC 9457 problem details with the request path as
+//!   `instance`, including the plain-text ones axum writes for malformed
+//!   requests
+//!
+//! v1 errors are turned back into the `{"error", "details"}` bodies v1 has
+//! always answered with.
+//!
+//! Once `API_V1_DEPRECATED_AT` is set, v1 responses carry `Deprecation`,
+//! `Sunset` and successor `Link` headers so clients can find out in time.
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::{Value, json};
+
+use crate::error::{PROBLEM_CONTENT_TYPE, Problem};
+use crate::state::AppState;
+
+pub const V1_PREFIX: &str = "/api/v1";
+pub const V2_PREFIX: &str = "/api/v2";
+
+/// The path of a request within its API version, like `/notes` for
+/// `/api/v2/notes`
+pub fn endpoint_path(path: &str) -> &str {
+    [V1_PREFIX, V2_PREFIX]
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+        .unwrap_or(path)
+}
+
+/// Middleware adding deprecation headers to v1 responses, once configured;
+/// runs within the v1 router, which sees paths without the prefix
+pub async fn deprecation_headers(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(deprecated_at) = state.config.api_v1_deprecated_at else {
+        return next.run(req).await;
+    };
+    let successor = format!("{}{}", V2_PREFIX, endpoint_path(req.uri().path()));
+    let mut response = next.run(req).await;
+
+    let headers = response.headers_mut();
+    // RFC 9745 structured date, seconds since the epoch
+    insert(
+        headers,
+        "deprecation",
+        format!("@{}", deprecated_at.timestamp()),
+    );
+    if let Some(sunset_at) = state.config.api_v1_sunset_at {
+        // RFC 8594 HTTP date
+        insert(
+            headers,
+            "sunset",
+            sunset_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        );
+    }
+    insert(
+        headers,
+        "link",
+        format!("<{}>; rel=\"successor-version\"", successor),
+    );
+    response
+}
+
+fn insert(headers: &mut HeaderMap, name: &'static str, value: String) {
+    if let Ok(value) = HeaderValue::try_from(value) {
+        headers.append(name, value);
+    }
+}
+
+/// Middleware rewriting responses into the shapes of their API version
+pub async fn compat_shim(req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let is_v2 = path.starts_with(V2_PREFIX);
+    if !is_v2 && !path.starts_with(V1_PREFIX) {
+        return next.run(req).await;
+    }
+    let page = Page::from_query(req.uri().query());
+    let response = next.run(req).await;
+
+    let status = response.status();
+    let is_error = status.is_client_error() || status.is_server_error();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let is_problem = content_type.starts_with(PROBLEM_CONTENT_TYPE);
+    let is_json = content_type.starts_with("application/json");
+    // v1 keeps plain-text errors and every success as they are
+    let reshapes = match (is_v2, is_error) {
+        (true, true) => true,
+        (true, false) => is_json,
+        (false, true) => is_problem,
+        (false, false) => false,
+    };
+    if !reshapes {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read response body: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let (content_type, reshaped) = if is_error {
+        let problem = if is_problem {
+            serde_json::from_slice(&bytes).ok()
+        } else {
+            None
+        };
+        let mut problem = problem.unwrap_or_else(|| {
+            let text = String::from_utf8_lossy(&bytes).trim().to_string();
+            Problem::from_status(status, Some(text).filter(|t| !t.is_empty()))
+        });
+        if is_v2 {
+            problem.instance = Some(path);
+            (PROBLEM_CONTENT_TYPE, json!(problem))
+        } else {
+            ("application/json", legacy_error(problem))
+        }
+    } else {
+        match serde_json::from_slice(&bytes) {
+            Ok(Value::Array(items)) => ("application/json", page.envelope(items)),
+            _ => return Response::from_parts(parts, Body::from(bytes)),
+        }
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    Response::from_parts(parts, Body::from(reshaped.to_string()))
+}
+
+/// The v1 error body of a problem: domain errors had their whole message
+/// as `error`, other errors a summary with the message as `details`. Failed
+/// fields are added as `errors`, which older clients ignore.
+fn legacy_error(problem: Problem) -> Value {
+    let mut body = match problem.detail {
+        Some(detail) if detail.starts_with(&problem.title) => json!({ "error": detail }),
+        Some(detail) => json!({ "error": problem.title, "details": detail }),
+        None => json!({ "error": problem.title }),
+    };
+    if !problem.errors.is_empty() {
+        body["errors"] = json!(problem.errors);
+    }
+    body
+}
+
+/// The `limit` and `offset` a list was requested with
+#[derive(Default)]
+struct Page {
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl Page {
+    fn from_query(query: Option<&str>) -> Self {
+        let mut page = Self::default();
+        for pair in query.unwrap_or_default().split('&') {
+            match pair.split_once('=') {
+                Some(("limit", value)) => page.limit = value.parse().ok(),
+                Some(("offset", value)) => page.offset = value.parse().ok(),
+                _ => {}
+            }
+        }
+        page
+    }
+
+    fn envelope(&self, items: Vec<Value>) -> Value {
+        json!({
+            "pagination": {
+                "limit": self.limit,
+                "offset": self.offset.unwrap_or(0),
+                "count": items.len(),
+            },
+            "data": items,
+        })
+    }
+}
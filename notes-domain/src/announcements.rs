@@ -0,0 +1,377 @@
+//! Instance-wide announcements shown as banners
+//!
+//! Administrators post announcements such as maintenance windows or
+//! changelog highlights. Each one is shown to every user while it is active
+//! until that user dismisses it.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{DomainError, DomainResult};
+use crate::repositories::AnnouncementRepository;
+
+/// Maximum length of an announcement title in characters
+pub const MAX_ANNOUNCEMENT_TITLE_LENGTH: usize = 200;
+
+/// Maximum length of an announcement body in characters
+pub const MAX_ANNOUNCEMENT_BODY_LENGTH: usize = 5000;
+
+/// How prominently clients should show an announcement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementLevel {
+    #[default]
+    Info,
+    Warning,
+    /// Planned downtime or read-only periods
+    Maintenance,
+}
+
+impl AnnouncementLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Maintenance => "maintenance",
+        }
+    }
+}
+
+impl FromStr for AnnouncementLevel {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "maintenance" => Ok(Self::Maintenance),
+            other => Err(DomainError::validation(format!(
+                "Unknown announcement level: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A banner posted by an administrator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub id: Uuid,
+    pub title: String,
+    /// Markdown body
+    pub body: String,
+    pub level: AnnouncementLevel,
+    /// Shown from this time on; immediately when `None`
+    pub starts_at: Option<DateTime<Utc>>,
+    /// Hidden from this time on; never expires when `None`
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Administrator who posted it
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Announcement {
+    pub fn new(created_by: Uuid, title: impl Into<String>, body: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            title: title.into(),
+            body: body.into(),
+            level: AnnouncementLevel::default(),
+            starts_at: None,
+            ends_at: None,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Whether the announcement should be shown at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at.is_none_or(|starts| starts <= now)
+            && self.ends_at.is_none_or(|ends| now < ends)
+    }
+
+    pub fn validate(&self) -> DomainResult<()> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err(DomainError::validation(
+                "Announcement title cannot be empty",
+            ));
+        }
+        if title.chars().count() > MAX_ANNOUNCEMENT_TITLE_LENGTH {
+            return Err(DomainError::validation(format!(
+                "Announcement title cannot exceed {} characters",
+                MAX_ANNOUNCEMENT_TITLE_LENGTH
+            )));
+        }
+        if self.body.chars().count() > MAX_ANNOUNCEMENT_BODY_LENGTH {
+            return Err(DomainError::validation(format!(
+                "Announcement body cannot exceed {} characters",
+                MAX_ANNOUNCEMENT_BODY_LENGTH
+            )));
+        }
+        if let (Some(starts), Some(ends)) = (self.starts_at, self.ends_at)
+            && ends <= starts
+        {
+            return Err(DomainError::validation(
+                "Announcement must end after it starts",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Content and schedule of an announcement
+#[derive(Debug, Clone)]
+pub struct AnnouncementRequest {
+    pub title: String,
+    pub body: String,
+    pub level: AnnouncementLevel,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl AnnouncementRequest {
+    fn apply_to(self, announcement: &mut Announcement) {
+        announcement.title = self.title.trim().to_string();
+        announcement.body = self.body;
+        announcement.level = self.level;
+        announcement.starts_at = self.starts_at;
+        announcement.ends_at = self.ends_at;
+    }
+}
+
+/// Service for announcements posted by administrators
+pub struct AnnouncementService {
+    announcement_repo: Arc<dyn AnnouncementRepository>,
+}
+
+impl AnnouncementService {
+    pub fn new(announcement_repo: Arc<dyn AnnouncementRepository>) -> Self {
+        Self { announcement_repo }
+    }
+
+    /// Post a new announcement
+    pub async fn create(
+        &self,
+        created_by: Uuid,
+        req: AnnouncementRequest,
+    ) -> DomainResult<Announcement> {
+        let mut announcement = Announcement::new(created_by, "", "");
+        req.apply_to(&mut announcement);
+        announcement.validate()?;
+
+        self.announcement_repo.save(&announcement).await?;
+        Ok(announcement)
+    }
+
+    /// Replace the content and schedule of an announcement. Users who
+    /// dismissed it keep it dismissed.
+    pub async fn update(&self, id: Uuid, req: AnnouncementRequest) -> DomainResult<Announcement> {
+        let mut announcement = self.get(id).await?;
+        req.apply_to(&mut announcement);
+        announcement.validate()?;
+        announcement.updated_at = Utc::now();
+
+        self.announcement_repo.save(&announcement).await?;
+        Ok(announcement)
+    }
+
+    pub async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        self.get(id).await?;
+        self.announcement_repo.delete(id).await
+    }
+
+    pub async fn get(&self, id: Uuid) -> DomainResult<Announcement> {
+        self.announcement_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::AnnouncementNotFound(id))
+    }
+
+    /// Every announcement, for administrators
+    pub async fn list_all(&self) -> DomainResult<Vec<Announcement>> {
+        self.announcement_repo.find_all().await
+    }
+
+    /// Announcements to show the user now
+    pub async fn list_active(&self, user_id: Uuid) -> DomainResult<Vec<Announcement>> {
+        self.announcement_repo
+            .find_active_for_user(user_id, Utc::now())
+            .await
+    }
+
+    /// Stop showing the announcement to the user
+    pub async fn dismiss(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
+        self.get(id).await?;
+        self.announcement_repo.dismiss(id, user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_is_active_within_window() {
+        let now = Utc::now();
+        let mut announcement = Announcement::new(Uuid::new_v4(), "Upgrade", "");
+        assert!(announcement.is_active(now));
+
+        announcement.starts_at = Some(now + Duration::hours(1));
+        assert!(!announcement.is_active(now));
+
+        announcement.starts_at = Some(now - Duration::hours(1));
+        announcement.ends_at = Some(now);
+        assert!(!announcement.is_active(now));
+        assert!(announcement.is_active(now - Duration::minutes(1)));
+    }
+
+    #[test]
+    fn test_validate() {
+        let now = Utc::now();
+        let mut announcement = Announcement::new(Uuid::new_v4(), "  ", "");
+        assert!(announcement.validate().is_err());
+
+        announcement.title = "Upgrade".to_string();
+        assert!(announcement.validate().is_ok());
+
+        announcement.starts_at = Some(now);
+        announcement.ends_at = Some(now);
+        assert!(announcement.validate().is_err());
+    }
+
+    #[test]
+    fn test_level_round_trips_through_strings() {
+        for level in [
+            AnnouncementLevel::Info,
+            AnnouncementLevel::Warning,
+            AnnouncementLevel::Maintenance,
+        ] {
+            assert_eq!(level.as_str().parse::<AnnouncementLevel>().unwrap(), level);
+        }
+    }
+
+    mod announcement_service_tests {
+        use super::*;
+        use std::collections::{HashMap, HashSet};
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockAnnouncementRepository {
+            announcements: Mutex<HashMap<Uuid, Announcement>>,
+            dismissed: Mutex<HashSet<(Uuid, Uuid)>>,
+        }
+
+        #[async_trait::async_trait]
+        impl AnnouncementRepository for MockAnnouncementRepository {
+            async fn save(&self, announcement: &Announcement) -> DomainResult<()> {
+                self.announcements
+                    .lock()
+                    .unwrap()
+                    .insert(announcement.id, announcement.clone());
+                Ok(())
+            }
+
+            async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Announcement>> {
+                Ok(self.announcements.lock().unwrap().get(&id).cloned())
+            }
+
+            async fn find_all(&self) -> DomainResult<Vec<Announcement>> {
+                let mut all: Vec<Announcement> = self
+                    .announcements
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .cloned()
+                    .collect();
+                all.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+                Ok(all)
+            }
+
+            async fn find_active_for_user(
+                &self,
+                user_id: Uuid,
+                now: DateTime<Utc>,
+            ) -> DomainResult<Vec<Announcement>> {
+                let all = self.find_all().await?;
+                let dismissed = self.dismissed.lock().unwrap();
+                Ok(all
+                    .into_iter()
+                    .filter(|a| a.is_active(now) && !dismissed.contains(&(a.id, user_id)))
+                    .collect())
+            }
+
+            async fn delete(&self, id: Uuid) -> DomainResult<()> {
+                self.announcements.lock().unwrap().remove(&id);
+                Ok(())
+            }
+
+            async fn dismiss(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
+                self.dismissed.lock().unwrap().insert((id, user_id));
+                Ok(())
+            }
+        }
+
+        fn request(title: &str) -> AnnouncementRequest {
+            AnnouncementRequest {
+                title: title.to_string(),
+                body: "Details".to_string(),
+                level: AnnouncementLevel::Maintenance,
+                starts_at: None,
+                ends_at: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_dismissed_announcements_are_hidden_per_user() {
+            let service = AnnouncementService::new(Arc::new(MockAnnouncementRepository::default()));
+            let admin_id = Uuid::new_v4();
+            let (user, other_user) = (Uuid::new_v4(), Uuid::new_v4());
+
+            let announcement = service
+                .create(admin_id, request(" Upgrade "))
+                .await
+                .unwrap();
+            assert_eq!(announcement.title, "Upgrade");
+            assert_eq!(announcement.created_by, admin_id);
+
+            service.dismiss(announcement.id, user).await.unwrap();
+
+            assert!(service.list_active(user).await.unwrap().is_empty());
+            assert_eq!(service.list_active(other_user).await.unwrap().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_update_validates_and_keeps_identity() {
+            let service = AnnouncementService::new(Arc::new(MockAnnouncementRepository::default()));
+            let announcement = service
+                .create(Uuid::new_v4(), request("Upgrade"))
+                .await
+                .unwrap();
+
+            let updated = service
+                .update(announcement.id, request("Upgrade tonight"))
+                .await
+                .unwrap();
+            assert_eq!(updated.id, announcement.id);
+            assert_eq!(updated.title, "Upgrade tonight");
+
+            assert!(matches!(
+                service.update(announcement.id, request("")).await,
+                Err(DomainError::ValidationError(_))
+            ));
+            assert!(matches!(
+                service.dismiss(Uuid::new_v4(), Uuid::new_v4()).await,
+                Err(DomainError::AnnouncementNotFound(_))
+            ));
+        }
+    }
+}
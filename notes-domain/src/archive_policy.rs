@@ -0,0 +1,92 @@
+//! Automatic archival policy
+//!
+//! Users can opt in to having notes archived once they have not been
+//! touched for a number of days. Pinned and locked notes are never
+//! auto-archived.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::entities::{Note, UserSettings};
+
+/// Upper bound for `auto_archive_after_days` (ten years)
+pub const MAX_AUTO_ARCHIVE_DAYS: u32 = 3650;
+
+/// Archives notes not updated within `after_days`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoArchivePolicy {
+    pub after_days: u32,
+}
+
+impl AutoArchivePolicy {
+    pub fn new(after_days: u32) -> Self {
+        Self { after_days }
+    }
+
+    /// The user's policy, or `None` if they have not opted in
+    pub fn from_settings(settings: &UserSettings) -> Option<Self> {
+        settings.auto_archive_after_days.map(Self::new)
+    }
+
+    /// Notes last updated before this instant are stale
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(i64::from(self.after_days))
+    }
+
+    /// Whether the policy would archive this note at `now`
+    pub fn applies_to(&self, note: &Note, now: DateTime<Utc>) -> bool {
+        !note.is_pinned
+            && !note.is_archived
+            && !note.is_locked
+            && note.updated_at < self.cutoff(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn note_updated_days_ago(days: i64) -> Note {
+        let mut note = Note::new(Uuid::new_v4(), None, "content");
+        note.updated_at = Utc::now() - Duration::days(days);
+        note
+    }
+
+    #[test]
+    fn test_stale_notes_are_candidates() {
+        let policy = AutoArchivePolicy::new(30);
+        let now = Utc::now();
+
+        assert!(policy.applies_to(&note_updated_days_ago(31), now));
+        assert!(!policy.applies_to(&note_updated_days_ago(29), now));
+    }
+
+    #[test]
+    fn test_pinned_archived_and_locked_notes_are_excluded() {
+        let policy = AutoArchivePolicy::new(30);
+        let now = Utc::now();
+
+        let mut pinned = note_updated_days_ago(90);
+        pinned.is_pinned = true;
+        let mut archived = note_updated_days_ago(90);
+        archived.is_archived = true;
+        let mut locked = note_updated_days_ago(90);
+        locked.is_locked = true;
+
+        assert!(!policy.applies_to(&pinned, now));
+        assert!(!policy.applies_to(&archived, now));
+        assert!(!policy.applies_to(&locked, now));
+    }
+
+    #[test]
+    fn test_policy_is_opt_in() {
+        let mut settings = UserSettings::default();
+        assert_eq!(AutoArchivePolicy::from_settings(&settings), None);
+
+        settings.auto_archive_after_days = Some(14);
+        assert_eq!(
+            AutoArchivePolicy::from_settings(&settings),
+            Some(AutoArchivePolicy::new(14))
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Who may do what to which resource
+//!
+//! Services describe each access as a subject (the acting user), an
+//! [`Action`] and a [`Resource`] and ask the [`AuthorizationPolicy`] port
+//! whether it is allowed, instead of comparing owner IDs themselves. Sharing
+//! or role-based rules then only change the policy. [`OwnerPolicy`], the
+//! default, lets users do anything with what they own and nothing else.
+//!
+//! [`AuthorizationPolicy`]: crate::ports::AuthorizationPolicy
+
+use std::fmt;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::boards::Board;
+use crate::entities::{Note, Tag};
+use crate::errors::{DomainError, DomainResult};
+use crate::jobs::Job;
+use crate::ports::AuthorizationPolicy;
+use crate::relations::NoteRelation;
+
+/// What the subject wants to do with a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Read,
+    Update,
+    Delete,
+}
+
+impl Action {
+    fn verb(&self) -> &'static str {
+        match self {
+            Self::Read => "access",
+            Self::Update => "modify",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// The kinds of resources users own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Note,
+    Tag,
+    Board,
+    Relation,
+    Job,
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Note => "note",
+            Self::Tag => "tag",
+            Self::Board => "board",
+            Self::Relation => "relation",
+            Self::Job => "job",
+        })
+    }
+}
+
+/// A resource an action is performed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resource {
+    pub kind: ResourceKind,
+    pub id: Uuid,
+    pub owner_id: Uuid,
+}
+
+impl Resource {
+    pub fn new(kind: ResourceKind, id: Uuid, owner_id: Uuid) -> Self {
+        Self { kind, id, owner_id }
+    }
+
+    pub fn note(note: &Note) -> Self {
+        Self::new(ResourceKind::Note, note.id, note.user_id)
+    }
+
+    pub fn tag(tag: &Tag) -> Self {
+        Self::new(ResourceKind::Tag, tag.id, tag.user_id)
+    }
+
+    pub fn board(board: &Board) -> Self {
+        Self::new(ResourceKind::Board, board.id, board.user_id)
+    }
+
+    pub fn relation(relation: &NoteRelation) -> Self {
+        Self::new(ResourceKind::Relation, relation.id, relation.user_id)
+    }
+
+    pub fn job(job: &Job) -> Self {
+        Self::new(ResourceKind::Job, job.id, job.user_id)
+    }
+}
+
+/// The error for a denied action, e.g. "Cannot delete another user's note"
+pub fn denied(action: Action, resource: &Resource) -> DomainError {
+    DomainError::forbidden(format!(
+        "Cannot {} another user's {}",
+        action.verb(),
+        resource.kind
+    ))
+}
+
+/// Users may do anything with their own resources and nothing with others'
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OwnerPolicy;
+
+#[async_trait]
+impl AuthorizationPolicy for OwnerPolicy {
+    async fn authorize(
+        &self,
+        subject: Uuid,
+        action: Action,
+        resource: &Resource,
+    ) -> DomainResult<()> {
+        if resource.owner_id == subject {
+            Ok(())
+        } else {
+            Err(denied(action, resource))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_owner_policy_only_allows_owners() {
+        let owner = Uuid::new_v4();
+        let note = Note::new(owner, None, "content");
+        let resource = Resource::note(&note);
+
+        for action in [Action::Read, Action::Update, Action::Delete] {
+            assert!(
+                OwnerPolicy
+                    .authorize(owner, action, &resource)
+                    .await
+                    .is_ok()
+            );
+        }
+        let result = OwnerPolicy
+            .authorize(Uuid::new_v4(), Action::Delete, &resource)
+            .await;
+        assert!(
+            matches!(result, Err(DomainError::Forbidden(message)) if message == "Cannot delete another user's note")
+        );
+    }
+}
@@ -0,0 +1,656 @@
+//! Kanban boards built from notes
+//!
+//! A board does not own its cards: each column selects the user's notes by a
+//! tag or by a status such as archived, and a note shows up in the first
+//! column it matches. Moving a card changes the note so it matches the
+//! target column and none of the others.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authorization::{Action, OwnerPolicy, Resource};
+use crate::entities::{Note, NoteFilter};
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::AuthorizationPolicy;
+use crate::repositories::BoardRepository;
+use crate::services::{NoteService, UpdateNoteRequest};
+use crate::value_objects::TagName;
+
+/// Maximum length of a board or column name in characters
+pub const MAX_BOARD_NAME_LENGTH: usize = 100;
+
+/// Maximum number of columns on a board
+pub const MAX_BOARD_COLUMNS: usize = 20;
+
+/// Note flags a column can be mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteStatus {
+    Pinned,
+    Archived,
+}
+
+impl NoteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pinned => "pinned",
+            Self::Archived => "archived",
+        }
+    }
+
+    pub fn matches(&self, note: &Note) -> bool {
+        match self {
+            Self::Pinned => note.is_pinned,
+            Self::Archived => note.is_archived,
+        }
+    }
+}
+
+impl fmt::Display for NoteStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NoteStatus {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pinned" => Ok(Self::Pinned),
+            "archived" => Ok(Self::Archived),
+            other => Err(DomainError::validation(format!(
+                "Unknown note status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Which notes a column holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnSource {
+    /// Notes with this tag
+    Tag(TagName),
+    /// Notes with this status
+    Status(NoteStatus),
+}
+
+impl ColumnSource {
+    pub fn matches(&self, note: &Note) -> bool {
+        match self {
+            Self::Tag(tag) => note.tags.iter().any(|t| t.name == *tag),
+            Self::Status(status) => status.matches(note),
+        }
+    }
+}
+
+/// A column of a board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardColumn {
+    pub id: Uuid,
+    pub name: String,
+    pub source: ColumnSource,
+}
+
+impl BoardColumn {
+    pub fn new(name: impl Into<String>, source: ColumnSource) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            source,
+        }
+    }
+}
+
+/// A user's kanban board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// Left to right
+    pub columns: Vec<BoardColumn>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn validate_name(kind: &str, name: &str) -> DomainResult<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DomainError::validation(format!(
+            "{} name cannot be empty",
+            kind
+        )));
+    }
+    if name.chars().count() > MAX_BOARD_NAME_LENGTH {
+        return Err(DomainError::validation(format!(
+            "{} name cannot exceed {} characters",
+            kind, MAX_BOARD_NAME_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+impl Board {
+    pub fn new(user_id: Uuid, name: impl Into<String>, columns: Vec<BoardColumn>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            name: name.into(),
+            columns,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> DomainResult<()> {
+        validate_name("Board", &self.name)?;
+        if self.columns.is_empty() {
+            return Err(DomainError::validation("A board needs at least one column"));
+        }
+        if self.columns.len() > MAX_BOARD_COLUMNS {
+            return Err(DomainError::validation(format!(
+                "A board cannot have more than {} columns",
+                MAX_BOARD_COLUMNS
+            )));
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            validate_name("Column", &column.name)?;
+            let earlier = &self.columns[..i];
+            if earlier.iter().any(|c| c.id == column.id) {
+                return Err(DomainError::validation(format!(
+                    "Duplicate column ID: {}",
+                    column.id
+                )));
+            }
+            if earlier.iter().any(|c| c.source == column.source) {
+                return Err(DomainError::validation(format!(
+                    "Column \"{}\" selects the same notes as an earlier column",
+                    column.name.trim()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn column(&self, column_id: Uuid) -> Option<&BoardColumn> {
+        self.columns.iter().find(|c| c.id == column_id)
+    }
+
+    /// The column a note shows up in: the first one it matches
+    pub fn column_for(&self, note: &Note) -> Option<&BoardColumn> {
+        self.columns.iter().find(|c| c.source.matches(note))
+    }
+
+    /// Sort notes into the board's columns; notes matching no column are left out
+    pub fn group(&self, notes: Vec<Note>) -> Vec<BoardColumnNotes> {
+        let mut columns: Vec<BoardColumnNotes> = self
+            .columns
+            .iter()
+            .map(|column| BoardColumnNotes {
+                column: column.clone(),
+                notes: Vec::new(),
+            })
+            .collect();
+        for note in notes {
+            if let Some(index) = self.columns.iter().position(|c| c.source.matches(&note)) {
+                columns[index].notes.push(note);
+            }
+        }
+        columns
+    }
+
+    /// Changes that move `note` into the column `column_id`
+    pub fn placement(&self, note: &Note, column_id: Uuid) -> DomainResult<Placement> {
+        let target = self
+            .column(column_id)
+            .ok_or_else(|| DomainError::validation(format!("Board has no column {}", column_id)))?;
+
+        let mut placement = Placement::default();
+        for column in &self.columns {
+            let wanted = column.id == target.id;
+            if column.source.matches(note) == wanted {
+                continue;
+            }
+            match &column.source {
+                ColumnSource::Tag(tag) if wanted => placement.add_tag = Some(tag.clone()),
+                ColumnSource::Tag(tag) => placement.remove_tags.push(tag.clone()),
+                ColumnSource::Status(NoteStatus::Pinned) => placement.pinned = Some(wanted),
+                ColumnSource::Status(NoteStatus::Archived) => placement.archived = Some(wanted),
+            }
+        }
+        Ok(placement)
+    }
+}
+
+/// How a note has to change to move to another column
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Placement {
+    pub add_tag: Option<TagName>,
+    pub remove_tags: Vec<TagName>,
+    pub pinned: Option<bool>,
+    pub archived: Option<bool>,
+}
+
+impl Placement {
+    /// Whether the note is already in place
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The note's tag names after the move, or `None` if they stay the same
+    pub fn tags_for(&self, note: &Note) -> Option<Vec<TagName>> {
+        if self.add_tag.is_none() && self.remove_tags.is_empty() {
+            return None;
+        }
+        let mut tags: Vec<TagName> = note
+            .tags
+            .iter()
+            .map(|t| t.name.clone())
+            .filter(|name| !self.remove_tags.contains(name))
+            .collect();
+        tags.extend(self.add_tag.clone());
+        Some(tags)
+    }
+}
+
+/// A column with the notes it currently holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardColumnNotes {
+    pub column: BoardColumn,
+    pub notes: Vec<Note>,
+}
+
+/// Column of a board as sent by a client
+#[derive(Debug, Clone)]
+pub struct BoardColumnRequest {
+    /// Keeps the ID of an existing column; new columns get one
+    pub id: Option<Uuid>,
+    pub name: String,
+    pub source: ColumnSource,
+}
+
+/// Name and columns of a board
+#[derive(Debug, Clone)]
+pub struct BoardRequest {
+    pub name: String,
+    pub columns: Vec<BoardColumnRequest>,
+}
+
+impl BoardRequest {
+    fn apply_to(self, board: &mut Board) {
+        board.name = self.name.trim().to_string();
+        board.columns = self
+            .columns
+            .into_iter()
+            .map(|column| BoardColumn {
+                id: column.id.unwrap_or_else(Uuid::new_v4),
+                name: column.name.trim().to_string(),
+                source: column.source,
+            })
+            .collect();
+    }
+}
+
+/// Service for kanban boards over a user's notes
+pub struct BoardService {
+    board_repo: Arc<dyn BoardRepository>,
+    notes: Arc<NoteService>,
+    policy: Arc<dyn AuthorizationPolicy>,
+}
+
+impl BoardService {
+    pub fn new(board_repo: Arc<dyn BoardRepository>, notes: Arc<NoteService>) -> Self {
+        Self {
+            board_repo,
+            notes,
+            policy: Arc::new(OwnerPolicy),
+        }
+    }
+
+    /// Decide who may view and change boards; owners only by default
+    pub fn with_authorization_policy(mut self, policy: Arc<dyn AuthorizationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub async fn create(&self, user_id: Uuid, req: BoardRequest) -> DomainResult<Board> {
+        let mut board = Board::new(user_id, "", Vec::new());
+        req.apply_to(&mut board);
+        board.validate()?;
+
+        self.board_repo.save(&board).await?;
+        Ok(board)
+    }
+
+    /// Replace the name and columns of a board. Notes are not changed.
+    pub async fn update(&self, id: Uuid, user_id: Uuid, req: BoardRequest) -> DomainResult<Board> {
+        let mut board = self.authorized(id, user_id, Action::Update).await?;
+        req.apply_to(&mut board);
+        board.validate()?;
+        board.updated_at = Utc::now();
+
+        self.board_repo.save(&board).await?;
+        Ok(board)
+    }
+
+    /// Delete a board; the notes on it are kept
+    pub async fn delete(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
+        self.authorized(id, user_id, Action::Delete).await?;
+        self.board_repo.delete(id).await
+    }
+
+    pub async fn get(&self, id: Uuid, user_id: Uuid) -> DomainResult<Board> {
+        self.authorized(id, user_id, Action::Read).await
+    }
+
+    /// The board, if the user may perform `action` on it
+    async fn authorized(&self, id: Uuid, user_id: Uuid, action: Action) -> DomainResult<Board> {
+        let board = self
+            .board_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::BoardNotFound(id))?;
+
+        self.policy
+            .authorize(user_id, action, &Resource::board(&board))
+            .await?;
+
+        Ok(board)
+    }
+
+    pub async fn list(&self, user_id: Uuid) -> DomainResult<Vec<Board>> {
+        self.board_repo.find_by_user(user_id).await
+    }
+
+    /// The board's live notes, grouped by column
+    pub async fn cards(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+    ) -> DomainResult<(Board, Vec<BoardColumnNotes>)> {
+        let board = self.get(id, user_id).await?;
+        let notes = self.notes.list_notes(user_id, NoteFilter::new()).await?;
+        let columns = board.group(notes);
+        Ok((board, columns))
+    }
+
+    /// Move a note to a column of the board
+    ///
+    /// Tags and status change in a single note update, so the note is never
+    /// left in two columns or in none.
+    pub async fn move_note(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        note_id: Uuid,
+        column_id: Uuid,
+    ) -> DomainResult<Note> {
+        let board = self.get(id, user_id).await?;
+        let note = self.notes.get_note(note_id, user_id).await?;
+        let placement = board.placement(&note, column_id)?;
+        if placement.is_empty() {
+            return Ok(note);
+        }
+
+        self.notes
+            .update_note(UpdateNoteRequest {
+                id: note_id,
+                user_id,
+                title: None,
+                content: None,
+                is_pinned: placement.pinned,
+                is_archived: placement.archived,
+                color: None,
+                tags: placement.tags_for(&note),
+                location: None,
+                place_name: None,
+                remind_at: None,
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Tag;
+
+    fn tag_column(name: &str) -> BoardColumn {
+        BoardColumn::new(name, ColumnSource::Tag(TagName::try_from(name).unwrap()))
+    }
+
+    fn tagged(user_id: Uuid, tags: &[&str]) -> Note {
+        let mut note = Note::new(user_id, None, "");
+        note.tags = tags
+            .iter()
+            .map(|name| Tag::new(TagName::try_from(*name).unwrap(), user_id))
+            .collect();
+        note
+    }
+
+    fn board(user_id: Uuid) -> Board {
+        Board::new(
+            user_id,
+            "Tasks",
+            vec![
+                tag_column("todo"),
+                tag_column("doing"),
+                BoardColumn::new("Done", ColumnSource::Status(NoteStatus::Archived)),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_validate() {
+        let user_id = Uuid::new_v4();
+        assert!(board(user_id).validate().is_ok());
+        assert!(Board::new(user_id, "Empty", vec![]).validate().is_err());
+        assert!(
+            Board::new(user_id, " ", vec![tag_column("todo")])
+                .validate()
+                .is_err()
+        );
+        assert!(
+            Board::new(
+                user_id,
+                "Twice",
+                vec![tag_column("todo"), tag_column("todo")]
+            )
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_group_puts_notes_in_first_matching_column() {
+        let user_id = Uuid::new_v4();
+        let board = board(user_id);
+        let todo = tagged(user_id, &["todo", "work"]);
+        let both = tagged(user_id, &["doing", "todo"]);
+        let mut done = tagged(user_id, &["work"]);
+        done.is_archived = true;
+        let unrelated = tagged(user_id, &["work"]);
+
+        let columns = board.group(vec![todo.clone(), both.clone(), done.clone(), unrelated]);
+
+        let ids = |i: usize| columns[i].notes.iter().map(|n| n.id).collect::<Vec<_>>();
+        assert_eq!(ids(0), vec![todo.id, both.id]);
+        assert!(ids(1).is_empty());
+        assert_eq!(ids(2), vec![done.id]);
+    }
+
+    #[test]
+    fn test_placement_leaves_only_the_target_column_matching() {
+        let user_id = Uuid::new_v4();
+        let board = board(user_id);
+        let doing = board.columns[1].id;
+        let done = board.columns[2].id;
+        let mut note = tagged(user_id, &["todo", "work"]);
+        note.is_archived = true;
+
+        let placement = board.placement(&note, doing).unwrap();
+        assert_eq!(placement.add_tag, TagName::try_from("doing").ok());
+        assert_eq!(
+            placement.remove_tags,
+            vec![TagName::try_from("todo").unwrap()]
+        );
+        assert_eq!(placement.archived, Some(false));
+        assert_eq!(
+            placement.tags_for(&note).unwrap(),
+            vec![
+                TagName::try_from("work").unwrap(),
+                TagName::try_from("doing").unwrap(),
+            ]
+        );
+
+        let placement = board.placement(&note, done).unwrap();
+        assert_eq!(placement.archived, None);
+        assert_eq!(placement.tags_for(&note).unwrap().len(), 1);
+        assert!(board.placement(&note, Uuid::new_v4()).is_err());
+    }
+
+    mod board_service_tests {
+        use super::*;
+        use crate::boards::NoteStatus;
+        use crate::repositories::tests::MockNoteRepository;
+        use crate::services::CreateNoteRequest;
+        use crate::services::tests::*;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockBoardRepository {
+            boards: Mutex<HashMap<Uuid, Board>>,
+        }
+
+        #[async_trait::async_trait]
+        impl BoardRepository for MockBoardRepository {
+            async fn save(&self, board: &Board) -> DomainResult<()> {
+                self.boards.lock().unwrap().insert(board.id, board.clone());
+                Ok(())
+            }
+
+            async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Board>> {
+                Ok(self.boards.lock().unwrap().get(&id).cloned())
+            }
+
+            async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Board>> {
+                let mut boards: Vec<Board> = self
+                    .boards
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .filter(|b| b.user_id == user_id)
+                    .cloned()
+                    .collect();
+                boards.sort_by_key(|b| b.created_at);
+                Ok(boards)
+            }
+
+            async fn delete(&self, id: Uuid) -> DomainResult<()> {
+                self.boards.lock().unwrap().remove(&id);
+                Ok(())
+            }
+        }
+
+        fn create_board_service() -> (BoardService, Arc<NoteService>) {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let service =
+                BoardService::new(Arc::new(MockBoardRepository::default()), notes.clone());
+            (service, notes)
+        }
+
+        fn request() -> BoardRequest {
+            let column = |name: &str, source| BoardColumnRequest {
+                id: None,
+                name: name.to_string(),
+                source,
+            };
+            BoardRequest {
+                name: " Tasks ".to_string(),
+                columns: vec![
+                    column(
+                        "To do",
+                        ColumnSource::Tag(TagName::try_from("todo").unwrap()),
+                    ),
+                    column(
+                        "Doing",
+                        ColumnSource::Tag(TagName::try_from("doing").unwrap()),
+                    ),
+                    column("Done", ColumnSource::Status(NoteStatus::Archived)),
+                ],
+            }
+        }
+
+        #[tokio::test]
+        async fn test_move_note_between_columns() {
+            let (service, notes) = create_board_service();
+            let user_id = Uuid::new_v4();
+            let board = service.create(user_id, request()).await.unwrap();
+            assert_eq!(board.name, "Tasks");
+
+            let note = notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: "Write report".to_string(),
+                    tags: vec![
+                        TagName::try_from("todo").unwrap(),
+                        TagName::try_from("work").unwrap(),
+                    ],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+
+            let (_, columns) = service.cards(board.id, user_id).await.unwrap();
+            assert_eq!(columns[0].notes.len(), 1);
+
+            let moved = service
+                .move_note(board.id, user_id, note.id, board.columns[1].id)
+                .await
+                .unwrap();
+            let mut tags: Vec<&str> = moved.tags.iter().map(|t| t.name.as_ref()).collect();
+            tags.sort();
+            assert_eq!(tags, vec!["doing", "work"]);
+
+            let done = service
+                .move_note(board.id, user_id, note.id, board.columns[2].id)
+                .await
+                .unwrap();
+            assert!(done.is_archived);
+            let (_, columns) = service.cards(board.id, user_id).await.unwrap();
+            assert!(columns[0].notes.is_empty() && columns[1].notes.is_empty());
+            assert_eq!(columns[2].notes[0].id, note.id);
+        }
+
+        #[tokio::test]
+        async fn test_boards_are_private() {
+            let (service, _) = create_board_service();
+            let owner = Uuid::new_v4();
+            let board = service.create(owner, request()).await.unwrap();
+
+            let err = service.get(board.id, Uuid::new_v4()).await.unwrap_err();
+            assert!(matches!(err, DomainError::Forbidden(_)));
+
+            service.delete(board.id, owner).await.unwrap();
+            let err = service.get(board.id, owner).await.unwrap_err();
+            assert!(matches!(err, DomainError::BoardNotFound(_)));
+        }
+    }
+}
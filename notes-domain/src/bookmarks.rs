@@ -0,0 +1,383 @@
+//! Link previews for URLs pasted into notes
+//!
+//! A URL on a line of its own is a bookmark: the worker fetches the page's
+//! title, description and favicon and stores them with the note, so clients
+//! can render the link as a card instead of bare text. Clients can also
+//! unfurl a link before it is saved, to show its card while editing.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::LinkPreviewFetcher;
+use crate::repositories::NoteRepository;
+
+/// Most bookmarks per note that get a preview; later ones stay plain links
+pub const MAX_LINK_PREVIEWS_PER_NOTE: usize = 20;
+
+/// Longest title or description kept from a page, in characters
+pub const MAX_PREVIEW_TEXT_CHARS: usize = 300;
+
+/// Longest URL clients can unfurl
+pub const MAX_UNFURL_URL_LENGTH: usize = 2048;
+
+/// Unfurled links kept, including pages that had no preview
+pub const UNFURL_CACHE_ENTRIES: usize = 1024;
+
+/// How long an unfurled link is served from the cache
+pub const UNFURL_CACHE_MINUTES: i64 = 60;
+
+/// What a bookmarked page says about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkPreview {
+    /// The URL as written in the note
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Absolute URL of the site icon
+    pub favicon_url: Option<String>,
+    /// Absolute URL of the page's Open Graph image
+    #[serde(default)]
+    pub image_url: Option<String>,
+    /// Name of the site, from Open Graph
+    #[serde(default)]
+    pub site_name: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl LinkPreview {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            title: None,
+            description: None,
+            favicon_url: None,
+            image_url: None,
+            site_name: None,
+            fetched_at: Utc::now(),
+        }
+    }
+}
+
+/// Collapse whitespace and cut page text to [`MAX_PREVIEW_TEXT_CHARS`];
+/// `None` if nothing is left
+pub fn clean_preview_text(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    match text.char_indices().nth(MAX_PREVIEW_TEXT_CHARS) {
+        Some((end, _)) => Some(format!("{}…", text[..end].trim_end())),
+        None => Some(text),
+    }
+}
+
+/// URLs that stand on a line of their own, optionally in `<...>`, without
+/// duplicates and at most [`MAX_LINK_PREVIEWS_PER_NOTE`]
+///
+/// Links inside prose or markdown link syntax are left alone; the author
+/// chose how those read.
+pub fn bare_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let url = line
+            .strip_prefix('<')
+            .and_then(|l| l.strip_suffix('>'))
+            .unwrap_or(line);
+        let is_url = (url.starts_with("http://") || url.starts_with("https://"))
+            && !url.contains(char::is_whitespace)
+            && url
+                .split_once("://")
+                .is_some_and(|(_, after)| !after.is_empty() && !after.starts_with('/'));
+        if is_url && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+            if urls.len() == MAX_LINK_PREVIEWS_PER_NOTE {
+                break;
+            }
+        }
+    }
+
+    urls
+}
+
+/// Service keeping the link previews of bookmarked URLs up to date
+pub struct LinkPreviewService {
+    note_repo: Arc<dyn NoteRepository>,
+    fetcher: Arc<dyn LinkPreviewFetcher>,
+}
+
+impl LinkPreviewService {
+    pub fn new(note_repo: Arc<dyn NoteRepository>, fetcher: Arc<dyn LinkPreviewFetcher>) -> Self {
+        Self { note_repo, fetcher }
+    }
+
+    /// Refresh the previews of up to `limit` notes whose content changed.
+    /// Returns the number of notes looked at.
+    pub async fn refresh_stale(&self, limit: usize) -> DomainResult<usize> {
+        let notes = self.note_repo.find_stale_link_previews(limit).await?;
+
+        for note in &notes {
+            let mut previews: Vec<LinkPreview> = Vec::new();
+            for url in bare_urls(&note.content) {
+                // Pages rarely change what they say about themselves; only
+                // newly pasted links are fetched
+                let known = note.link_previews.iter().find(|p| p.url == url).cloned();
+                let preview = match known {
+                    Some(preview) => Some(preview),
+                    None => self.fetcher.fetch(&url).await?,
+                };
+                previews.extend(preview);
+            }
+
+            self.note_repo
+                .save_link_previews(note.id, &previews, note.updated_at)
+                .await?;
+        }
+
+        Ok(notes.len())
+    }
+}
+
+/// Service fetching previews of links clients are about to save, so they
+/// can show link cards while editing
+pub struct UnfurlService {
+    fetcher: Arc<dyn LinkPreviewFetcher>,
+    /// When each URL was unfurled and its preview, emptied when full
+    previews: Mutex<HashMap<String, (DateTime<Utc>, Option<LinkPreview>)>>,
+}
+
+impl UnfurlService {
+    pub fn new(fetcher: Arc<dyn LinkPreviewFetcher>) -> Self {
+        Self {
+            fetcher,
+            previews: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// What the page at `url` says about itself, or `None` if it cannot be
+    /// loaded or is not HTML. Pages are fetched at most once an hour.
+    pub async fn unfurl(&self, url: &str) -> DomainResult<Option<LinkPreview>> {
+        let url = url.trim();
+        if url.len() > MAX_UNFURL_URL_LENGTH {
+            return Err(DomainError::validation(format!(
+                "url must be at most {} characters",
+                MAX_UNFURL_URL_LENGTH
+            )));
+        }
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(DomainError::validation("url must be an http or https URL"));
+        }
+
+        let now = Utc::now();
+        let fresh_after = now - chrono::Duration::minutes(UNFURL_CACHE_MINUTES);
+        let cached = self
+            .previews
+            .lock()
+            .unwrap()
+            .get(url)
+            .filter(|(fetched_at, _)| *fetched_at > fresh_after)
+            .map(|(_, preview)| preview.clone());
+        if let Some(preview) = cached {
+            return Ok(preview);
+        }
+
+        let preview = self.fetcher.fetch(url).await?;
+        let mut cache = self.previews.lock().unwrap();
+        if cache.len() >= UNFURL_CACHE_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(url.to_string(), (now, preview.clone()));
+        Ok(preview)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bare_urls_only_match_whole_lines() {
+        let content = "Reading list\n\
+                       https://example.com/article\n  \
+                       <https://docs.rs/axum>  \n\
+                       See https://inline.example for more\n\
+                       [guide](https://guide.example)\n\
+                       ```\nhttps://code.example\n```\n\
+                       https://example.com/article\n\
+                       https://";
+
+        assert_eq!(
+            bare_urls(content),
+            vec!["https://example.com/article", "https://docs.rs/axum"]
+        );
+    }
+
+    #[test]
+    fn test_clean_preview_text_collapses_and_truncates() {
+        assert_eq!(
+            clean_preview_text("  Rust \n\t Blog "),
+            Some("Rust Blog".to_string())
+        );
+        assert_eq!(clean_preview_text(" \n "), None);
+
+        let long = "word ".repeat(100);
+        let cleaned = clean_preview_text(&long).unwrap();
+        assert!(cleaned.ends_with('…'));
+        assert!(cleaned.chars().count() <= MAX_PREVIEW_TEXT_CHARS + 1);
+    }
+
+    mod link_preview_service_tests {
+        use super::*;
+        use crate::entities::Note;
+        use crate::repositories::tests::MockNoteRepository;
+        use uuid::Uuid;
+
+        /// Titles every page after its URL and counts fetches; pages on
+        /// `down.example` cannot be loaded
+        #[derive(Default)]
+        struct MockLinkPreviewFetcher {
+            fetches: Mutex<Vec<String>>,
+        }
+
+        #[async_trait::async_trait]
+        impl LinkPreviewFetcher for MockLinkPreviewFetcher {
+            async fn fetch(&self, url: &str) -> DomainResult<Option<LinkPreview>> {
+                self.fetches.lock().unwrap().push(url.to_string());
+                if url.contains("down.example") {
+                    return Ok(None);
+                }
+                let mut preview = LinkPreview::new(url);
+                preview.title = Some(format!("Title of {}", url));
+                Ok(Some(preview))
+            }
+        }
+
+        #[tokio::test]
+        async fn test_refresh_stale_fetches_new_bookmarks_once() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let fetcher = Arc::new(MockLinkPreviewFetcher::default());
+            let service = LinkPreviewService::new(note_repo.clone(), fetcher.clone());
+            let user_id = Uuid::new_v4();
+
+            let mut note = Note::new(
+                user_id,
+                None,
+                "https://a.example
+https://down.example
+inline https://b.example",
+            );
+            note_repo.save(&note).await.unwrap();
+            note_repo
+                .save(&Note::new(user_id, None, "no links"))
+                .await
+                .unwrap();
+
+            assert_eq!(service.refresh_stale(10).await.unwrap(), 1);
+            let stored = note_repo.find_by_id(note.id).await.unwrap().unwrap();
+            let urls: Vec<&str> = stored
+                .link_previews
+                .iter()
+                .map(|p| p.url.as_str())
+                .collect();
+            assert_eq!(urls, vec!["https://a.example"]);
+            assert_eq!(service.refresh_stale(10).await.unwrap(), 0);
+
+            // Editing keeps known previews and only fetches the new link
+            note.link_previews = stored.link_previews;
+            note.content = "https://a.example
+https://c.example"
+                .to_string();
+            note.updated_at = Utc::now() + chrono::Duration::seconds(1);
+            note_repo.save(&note).await.unwrap();
+            assert_eq!(service.refresh_stale(10).await.unwrap(), 1);
+
+            let stored = note_repo.find_by_id(note.id).await.unwrap().unwrap();
+            assert_eq!(stored.link_previews.len(), 2);
+            assert_eq!(
+                *fetcher.fetches.lock().unwrap(),
+                vec![
+                    "https://a.example",
+                    "https://down.example",
+                    "https://c.example"
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn test_refresh_stale_drops_previews_of_removed_links() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let service = LinkPreviewService::new(
+                note_repo.clone(),
+                Arc::new(MockLinkPreviewFetcher::default()),
+            );
+            let mut note = Note::new(Uuid::new_v4(), None, "plain text now");
+            note.link_previews = vec![LinkPreview::new("https://gone.example")];
+            note_repo.save(&note).await.unwrap();
+
+            assert_eq!(service.refresh_stale(10).await.unwrap(), 1);
+            let stored = note_repo.find_by_id(note.id).await.unwrap().unwrap();
+            assert!(stored.link_previews.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_unfurl_fetches_each_url_once() {
+            let fetcher = Arc::new(MockLinkPreviewFetcher::default());
+            let service = UnfurlService::new(fetcher.clone());
+
+            let preview = service.unfurl(" https://a.example ").await.unwrap();
+            assert_eq!(
+                preview.and_then(|p| p.title).as_deref(),
+                Some("Title of https://a.example")
+            );
+            assert!(service.unfurl("https://a.example").await.unwrap().is_some());
+            assert!(
+                service
+                    .unfurl("https://down.example")
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+            assert!(
+                service
+                    .unfurl("https://down.example")
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+
+            assert_eq!(
+                *fetcher.fetches.lock().unwrap(),
+                vec!["https://a.example", "https://down.example"]
+            );
+        }
+
+        #[tokio::test]
+        async fn test_unfurl_rejects_other_schemes_and_long_urls() {
+            let fetcher = Arc::new(MockLinkPreviewFetcher::default());
+            let service = UnfurlService::new(fetcher.clone());
+            let long = format!("https://a.example/{}", "a".repeat(MAX_UNFURL_URL_LENGTH));
+
+            for url in ["file:///etc/passwd", "javascript:alert(1)", long.as_str()] {
+                assert!(matches!(
+                    service.unfurl(url).await,
+                    Err(DomainError::ValidationError(_))
+                ));
+            }
+            assert!(fetcher.fetches.lock().unwrap().is_empty());
+        }
+    }
+}
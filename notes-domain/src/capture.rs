@@ -0,0 +1,178 @@
+//! Quick capture of notes from a single piece of text
+//!
+//! Capture widgets and chat bots send free text with inline markers: `#tag`
+//! tags the note, `!pinned` pins it and `@when` sets a reminder. The reminder
+//! takes any date [`parse_when`] understands, with hyphens for spaces:
+//! `@tomorrow`, `@friday`, `@next-week`, `@in-3-days` or `@2026-03-14`.
+//! Markers are taken out of the text and the rest becomes the note's content.
+//! Words that only look like markers, such as Markdown headings, issue
+//! numbers or email addresses, are left alone.
+
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+
+use crate::dates::parse_when;
+use crate::errors::{DomainError, DomainResult};
+use crate::value_objects::TagName;
+
+/// Local hour at which reminders given as a bare date go off
+pub const DEFAULT_REMINDER_HOUR: u32 = 9;
+
+/// Punctuation that may follow a marker, as in `call back @tomorrow.`
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+/// A note described by captured text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub content: String,
+    pub tags: Vec<TagName>,
+    pub is_pinned: bool,
+    pub remind_at: Option<DateTime<Utc>>,
+}
+
+/// Local time of day for reminders that name a day but no time
+pub fn default_reminder_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(DEFAULT_REMINDER_HOUR, 0, 0).unwrap_or(NaiveTime::MIN)
+}
+
+/// Reminder time for the `@when` marker, relative to `now` in `tz`
+fn reminder_at(when: &str, now: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+    if when.is_empty() {
+        return None;
+    }
+    parse_when(when, now, tz, default_reminder_time())
+        .or_else(|_| parse_when(&when.replace('-', " "), now, tz, default_reminder_time()))
+        .ok()
+}
+
+impl Capture {
+    /// Parse captured text; `now` in the user's timezone `tz` anchors
+    /// relative dates such as `@tomorrow`
+    ///
+    /// Later reminders override earlier ones. Fails when nothing but markers
+    /// is left.
+    pub fn parse(text: &str, now: DateTime<Utc>, tz: Tz) -> DomainResult<Self> {
+        let mut capture = Self {
+            content: String::new(),
+            tags: Vec::new(),
+            is_pinned: false,
+            remind_at: None,
+        };
+
+        let lines: Vec<String> = text
+            .lines()
+            .map(|line| {
+                line.split_whitespace()
+                    .filter(|word| !capture.apply_marker(word, now, tz))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        capture.content = lines.join("\n").trim().to_string();
+
+        if capture.content.is_empty() {
+            return Err(DomainError::validation("Captured text has no content"));
+        }
+        Ok(capture)
+    }
+
+    /// Apply `word` if it is a marker, returning whether it was one
+    fn apply_marker(&mut self, word: &str, now: DateTime<Utc>, tz: Tz) -> bool {
+        let marker = word.trim_end_matches(TRAILING_PUNCTUATION);
+
+        if let Some(name) = marker.strip_prefix('#') {
+            if !name.starts_with(char::is_alphabetic) {
+                return false;
+            }
+            let Ok(tag) = TagName::try_from(name) else {
+                return false;
+            };
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+            return true;
+        }
+
+        if let Some(when) = marker.strip_prefix('@') {
+            return match reminder_at(when, now, tz) {
+                Some(at) => {
+                    self.remind_at = Some(at);
+                    true
+                }
+                None => false,
+            };
+        }
+
+        if marker.eq_ignore_ascii_case("!pinned") {
+            self.is_pinned = true;
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 14, 18, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_takes_markers_out_of_the_text() {
+        let capture = Capture::parse(
+            "Call the plumber #home #Errands #home !pinned @tomorrow.",
+            now(),
+            Tz::UTC,
+        )
+        .unwrap();
+
+        assert_eq!(capture.content, "Call the plumber");
+        assert_eq!(
+            capture.tags,
+            vec![
+                TagName::try_from("home").unwrap(),
+                TagName::try_from("errands").unwrap(),
+            ]
+        );
+        assert!(capture.is_pinned);
+        assert_eq!(
+            capture.remind_at,
+            Some(Utc.with_ymd_and_hms(2026, 3, 15, 9, 0, 0).unwrap())
+        );
+
+        let dated = Capture::parse("Renew passport @2026-04-01", now(), Tz::UTC).unwrap();
+        assert_eq!(
+            dated.remind_at,
+            Some(Utc.with_ymd_and_hms(2026, 4, 1, 9, 0, 0).unwrap())
+        );
+
+        let warsaw = "Europe/Warsaw".parse().unwrap();
+        let relative = Capture::parse("Water plants @next-friday-6pm", now(), warsaw).unwrap();
+        assert_eq!(relative.content, "Water plants");
+        assert_eq!(
+            relative.remind_at,
+            Some(Utc.with_ymd_and_hms(2026, 3, 20, 17, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_leaves_lookalikes_alone() {
+        let text = "# Standup\n\nFix #12, mail bob@example.com @someday";
+        let capture = Capture::parse(text, now(), Tz::UTC).unwrap();
+
+        assert_eq!(capture.content, text);
+        assert!(capture.tags.is_empty());
+        assert!(!capture.is_pinned);
+        assert_eq!(capture.remind_at, None);
+    }
+
+    #[test]
+    fn test_parse_rejects_markers_without_content() {
+        assert!(Capture::parse("#todo !pinned", now(), Tz::UTC).is_err());
+        assert!(Capture::parse("   ", now(), Tz::UTC).is_err());
+    }
+}
@@ -0,0 +1,356 @@
+//! Natural-language dates
+//!
+//! Reminders and search filters accept what people type, not just RFC 3339:
+//! `tomorrow`, `next friday 9am`, `in 3 days`, `2 weeks ago`, `last month`
+//! or a plain `2026-03-14`. Relative words are read in the user's timezone,
+//! so `tomorrow` starts at midnight where the user lives, and the result is
+//! returned in UTC.
+//!
+//! When only a day is given, the caller decides the time of day: reminders go
+//! off in the morning while `after:` filters start at midnight.
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+use crate::errors::{DomainError, DomainResult};
+
+/// The user's timezone, or UTC for names chrono-tz does not know
+pub fn parse_timezone(name: &str) -> Tz {
+    name.parse().unwrap_or(Tz::UTC)
+}
+
+/// Check that `name` is an IANA timezone such as `Europe/Warsaw`
+pub fn validate_timezone(name: &str) -> DomainResult<Tz> {
+    name.parse()
+        .map_err(|_| DomainError::validation(format!("Unknown timezone: {}", name)))
+}
+
+/// Parse `input` relative to `now` in `tz`
+///
+/// `default_time` is the local time of day used when the input names a day
+/// but no time. Weekdays on their own (`friday`) and with `next` mean the
+/// next such day after today; with `last`, the latest one before today.
+/// `next week`, `next month` and `next year` start on their first day.
+pub fn parse_when(
+    input: &str,
+    now: DateTime<Utc>,
+    tz: Tz,
+    default_time: NaiveTime,
+) -> DomainResult<DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+        return Ok(at.with_timezone(&Utc));
+    }
+
+    parse_words(input, now, tz, default_time)
+        .ok_or_else(|| DomainError::validation(format!("Could not understand the date: {}", input)))
+}
+
+fn parse_words(
+    input: &str,
+    now: DateTime<Utc>,
+    tz: Tz,
+    default_time: NaiveTime,
+) -> Option<DateTime<Utc>> {
+    let mut words = tokenize(input);
+    if words.is_empty() {
+        return None;
+    }
+
+    let time = take_time(&mut words)?;
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let today = now.with_timezone(&tz).date_naive();
+
+    let date = match words.as_slice() {
+        [] if time.is_some() => today,
+        ["now"] if time.is_none() => return Some(now),
+        ["today"] => today,
+        ["tomorrow"] => today.checked_add_days(Days::new(1))?,
+        ["yesterday"] => today.checked_sub_days(Days::new(1))?,
+        ["next", unit] => next(today, unit)?,
+        ["last", unit] => last(today, unit)?,
+        ["in", amount, unit] if time.is_none() && is_clock_unit(unit) => {
+            return Some(now + clock_offset(amount, unit)?);
+        }
+        [amount, unit, "ago"] if time.is_none() && is_clock_unit(unit) => {
+            return Some(now - clock_offset(amount, unit)?);
+        }
+        ["in", amount, unit] => shift(today, count(amount)?, unit, true)?,
+        [amount, unit, "ago"] => shift(today, count(amount)?, unit, false)?,
+        [word] => match word.parse::<Weekday>() {
+            Ok(weekday) => upcoming(today, weekday),
+            Err(_) => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()?,
+        },
+        _ => return None,
+    };
+
+    Some(to_utc(date.and_time(time.unwrap_or(default_time)), tz))
+}
+
+/// Lowercase words, with `9 am` joined into `9am`
+fn tokenize(input: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for word in input.to_lowercase().replace(',', " ").split_whitespace() {
+        let joins_previous = matches!(word, "am" | "pm")
+            && words
+                .last()
+                .is_some_and(|w| w.chars().all(|c| c.is_ascii_digit() || c == ':'));
+        match words.last_mut() {
+            Some(previous) if joins_previous => previous.push_str(word),
+            _ => words.push(word.to_string()),
+        }
+    }
+    words
+}
+
+/// Take the time of day out of `words`, if there is one
+///
+/// Returns `None` when the words name more than one time.
+fn take_time(words: &mut Vec<String>) -> Option<Option<NaiveTime>> {
+    let Some(index) = words.iter().position(|w| parse_time(w).is_some()) else {
+        return Some(None);
+    };
+    let time = parse_time(&words[index]);
+    words.remove(index);
+    if index > 0 && words[index - 1] == "at" {
+        words.remove(index - 1);
+    }
+    if words.iter().any(|w| parse_time(w).is_some()) {
+        return None;
+    }
+    Some(time)
+}
+
+/// `9am`, `9:30pm`, `14:00`, `noon` or `midnight`
+///
+/// Bare numbers are not times, so that `in 3 days` keeps its count.
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+
+    let (clock, meridiem) = match word.strip_suffix("am") {
+        Some(clock) => (clock, Some(false)),
+        None => match word.strip_suffix("pm") {
+            Some(clock) => (clock, Some(true)),
+            None => (word, None),
+        },
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse().ok()?),
+        Some(_) => return None,
+        None if meridiem.is_some() => (clock, 0),
+        None => return None,
+    };
+    if hour.is_empty() || hour.len() > 2 || !hour.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hour: u32 = hour.parse().ok()?;
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) => hour % 12,
+        Some(true) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// `3`, `a` or `an`
+fn count(amount: &str) -> Option<u32> {
+    match amount {
+        "a" | "an" => Some(1),
+        other => other.parse().ok(),
+    }
+}
+
+fn is_clock_unit(unit: &str) -> bool {
+    matches!(
+        unit,
+        "minute" | "minutes" | "min" | "mins" | "hour" | "hours" | "hr" | "hrs"
+    )
+}
+
+fn clock_offset(amount: &str, unit: &str) -> Option<Duration> {
+    let amount = i64::from(count(amount)?);
+    if unit.starts_with('h') {
+        Duration::try_hours(amount)
+    } else {
+        Duration::try_minutes(amount)
+    }
+}
+
+/// `date` moved `amount` days, weeks, months or years forward or back
+fn shift(date: NaiveDate, amount: u32, unit: &str, forward: bool) -> Option<NaiveDate> {
+    let days = |n: u64| {
+        if forward {
+            date.checked_add_days(Days::new(n))
+        } else {
+            date.checked_sub_days(Days::new(n))
+        }
+    };
+    let months = |n: u32| {
+        if forward {
+            date.checked_add_months(Months::new(n))
+        } else {
+            date.checked_sub_months(Months::new(n))
+        }
+    };
+    match unit.trim_end_matches('s') {
+        "day" => days(u64::from(amount)),
+        "week" => days(u64::from(amount) * 7),
+        "month" => months(amount),
+        "year" => months(amount.checked_mul(12)?),
+        _ => None,
+    }
+}
+
+/// The first `weekday` after `today`
+fn upcoming(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Days::new(if ahead == 0 { 7 } else { u64::from(ahead) })
+}
+
+/// The last `weekday` before `today`
+fn previous(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let behind = (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+    today - Days::new(if behind == 0 { 7 } else { u64::from(behind) })
+}
+
+fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
+
+fn next(today: NaiveDate, unit: &str) -> Option<NaiveDate> {
+    match unit {
+        "week" => start_of_week(today).checked_add_days(Days::new(7)),
+        "month" => today.with_day(1)?.checked_add_months(Months::new(1)),
+        "year" => NaiveDate::from_ymd_opt(today.year() + 1, 1, 1),
+        other => Some(upcoming(today, other.parse().ok()?)),
+    }
+}
+
+fn last(today: NaiveDate, unit: &str) -> Option<NaiveDate> {
+    match unit {
+        "week" => start_of_week(today).checked_sub_days(Days::new(7)),
+        "month" => today.with_day(1)?.checked_sub_months(Months::new(1)),
+        "year" => NaiveDate::from_ymd_opt(today.year() - 1, 1, 1),
+        other => Some(previous(today, other.parse().ok()?)),
+    }
+}
+
+/// Local time in `tz` as UTC
+///
+/// Times skipped by a daylight saving jump move forward by the size of the
+/// gap; times that happen twice resolve to the first of them.
+fn to_utc(local: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    let resolved = match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => Some(at),
+        LocalResult::None => tz
+            .from_local_datetime(&(local + Duration::hours(1)))
+            .earliest(),
+    };
+    resolved
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nine() -> NaiveTime {
+        NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+    }
+
+    fn midnight() -> NaiveTime {
+        NaiveTime::MIN
+    }
+
+    /// Saturday 2026-03-14, 18:30 UTC
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 14, 18, 30, 0).unwrap()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn parse(input: &str) -> DateTime<Utc> {
+        parse_when(input, now(), Tz::UTC, nine()).unwrap()
+    }
+
+    #[test]
+    fn test_parses_days_and_times() {
+        assert_eq!(parse("tomorrow"), utc(2026, 3, 15, 9, 0));
+        assert_eq!(parse("next friday 9:30pm"), utc(2026, 3, 20, 21, 30));
+        assert_eq!(parse("Friday at 14:00"), utc(2026, 3, 20, 14, 0));
+        assert_eq!(parse("saturday"), utc(2026, 3, 21, 9, 0));
+        assert_eq!(parse("last monday noon"), utc(2026, 3, 9, 12, 0));
+        assert_eq!(parse("7 am"), utc(2026, 3, 14, 7, 0));
+        assert_eq!(parse("2026-04-01"), utc(2026, 4, 1, 9, 0));
+        assert_eq!(parse("2026-04-01T10:15:00+02:00"), utc(2026, 4, 1, 8, 15));
+    }
+
+    #[test]
+    fn test_parses_relative_periods() {
+        assert_eq!(parse("in 3 days"), utc(2026, 3, 17, 9, 0));
+        assert_eq!(parse("2 weeks ago"), utc(2026, 2, 28, 9, 0));
+        assert_eq!(parse("in an hour"), utc(2026, 3, 14, 19, 30));
+        assert_eq!(parse("90 minutes ago"), utc(2026, 3, 14, 17, 0));
+        assert_eq!(parse("next week"), utc(2026, 3, 16, 9, 0));
+        assert_eq!(
+            parse_when("last week", now(), Tz::UTC, midnight()).unwrap(),
+            utc(2026, 3, 2, 0, 0)
+        );
+        assert_eq!(parse("last month"), utc(2026, 2, 1, 9, 0));
+        assert_eq!(parse("next year"), utc(2027, 1, 1, 9, 0));
+    }
+
+    #[test]
+    fn test_reads_relative_words_in_the_users_timezone() {
+        let tokyo = parse_timezone("Asia/Tokyo");
+
+        // 18:30 UTC is already Sunday morning in Tokyo
+        assert_eq!(
+            parse_when("tomorrow 9am", now(), tokyo, nine()).unwrap(),
+            utc(2026, 3, 16, 0, 0)
+        );
+
+        // 02:30 on 2026-03-08 does not exist in New York
+        let new_york = parse_timezone("America/New_York");
+        assert_eq!(
+            parse_when("2026-03-08 2:30am", now(), new_york, nine()).unwrap(),
+            utc(2026, 3, 8, 7, 30)
+        );
+        assert_eq!(
+            parse_when("last sunday 2:30am", now(), new_york, nine()).unwrap(),
+            utc(2026, 3, 8, 7, 30)
+        );
+
+        assert_eq!(parse_timezone("Mars/Olympus_Mons"), Tz::UTC);
+        assert!(validate_timezone("Mars/Olympus_Mons").is_err());
+        assert_eq!(validate_timezone("Asia/Tokyo").unwrap(), tokyo);
+    }
+
+    #[test]
+    fn test_rejects_what_it_does_not_understand() {
+        for input in [
+            "",
+            "someday",
+            "next fortnight",
+            "in 3 parsecs",
+            "9am 10am",
+            "25:00",
+        ] {
+            assert!(
+                parse_when(input, now(), Tz::UTC, nine()).is_err(),
+                "{input}"
+            );
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Diagrams drawn from code blocks
+//!
+//! Code blocks fenced as ` ```mermaid ` or ` ```plantuml ` hold diagram
+//! sources. Instances with a [`DiagramRenderer`]
+//! draw them as SVG in rendered outputs (print view, PDF export and static
+//! site); everywhere else, and when drawing fails, they stay code blocks.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use base64::prelude::{BASE64_STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::Note;
+use crate::ports::{DiagramRenderer, content_hash};
+
+/// Drawn diagrams kept, by source
+pub const DIAGRAM_CACHE_ENTRIES: usize = 256;
+
+/// Diagram languages code blocks can be drawn from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramKind {
+    Mermaid,
+    PlantUml,
+}
+
+impl DiagramKind {
+    /// The kind a code block's language names, like `mermaid` in ` ```mermaid `
+    pub fn from_info(info: &str) -> Option<Self> {
+        match info.to_ascii_lowercase().as_str() {
+            "mermaid" => Some(Self::Mermaid),
+            "plantuml" | "puml" => Some(Self::PlantUml),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mermaid => "mermaid",
+            Self::PlantUml => "plantuml",
+        }
+    }
+}
+
+/// The fence opening a code block on `line`, with the block's language
+fn opening_fence(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start();
+    // Indented by four spaces, the fence is code itself
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let fence_length = trimmed.chars().take_while(|&c| c == marker).count();
+    if fence_length < 3 {
+        return None;
+    }
+    let info = trimmed[fence_length..]
+        .split_whitespace()
+        .next()
+        .unwrap_or("");
+    Some((&trimmed[..fence_length], info))
+}
+
+fn closes(line: &str, fence: &str) -> bool {
+    let line = line.trim();
+    line.starts_with(fence) && line.chars().all(|c| fence.starts_with(c))
+}
+
+/// Replace every diagram code block with the output of `replace` for its
+/// kind and source, keeping the block where `replace` returns `None`.
+///
+/// Diagram fences inside other code blocks and blocks left unclosed are
+/// not diagrams.
+pub fn replace_diagrams(
+    content: &str,
+    mut replace: impl FnMut(DiagramKind, &str) -> Option<String>,
+) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut lines = content.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let Some((fence, info)) = opening_fence(line) else {
+            output.push_str(line);
+            continue;
+        };
+
+        let mut block = String::from(line);
+        let mut source = String::new();
+        let mut closed = false;
+        for line in lines.by_ref() {
+            block.push_str(line);
+            if closes(line, fence) {
+                closed = true;
+                break;
+            }
+            source.push_str(line);
+        }
+
+        let drawn = DiagramKind::from_info(info)
+            .filter(|_| closed)
+            .and_then(|kind| replace(kind, &source));
+        match drawn {
+            Some(html) => output.push_str(&html),
+            None => output.push_str(&block),
+        }
+    }
+
+    output
+}
+
+/// The diagrams in `content`, in order of appearance
+pub fn extract_diagrams(content: &str) -> Vec<(DiagramKind, String)> {
+    let mut diagrams = Vec::new();
+    replace_diagrams(content, |kind, source| {
+        diagrams.push((kind, source.to_string()));
+        None
+    });
+    diagrams
+}
+
+/// The Markdown image a drawn diagram replaces its code block with.
+///
+/// Rendered outputs leave raw HTML in notes as text, so the SVG is shown
+/// as a `data:` URL image; browsers draw such images without running their
+/// scripts or loading anything they link to.
+pub fn diagram_markdown(kind: DiagramKind, svg: &str) -> Option<String> {
+    // Drop the XML prolog and doctype some renderers start with
+    let svg = &svg[svg.find("<svg")?..];
+    Some(format!(
+        "![{} diagram](data:image/svg+xml;base64,{})\n\n",
+        kind.name(),
+        BASE64_STANDARD.encode(svg)
+    ))
+}
+
+/// Service drawing the diagrams in notes with a diagram renderer
+pub struct DiagramService {
+    renderer: Arc<dyn DiagramRenderer>,
+    /// Drawn diagrams as Markdown, by kind and source hash, emptied when full
+    figures: Mutex<HashMap<String, String>>,
+}
+
+impl DiagramService {
+    pub fn new(renderer: Arc<dyn DiagramRenderer>) -> Self {
+        Self {
+            renderer,
+            figures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// `notes` with their diagram code blocks replaced by the drawn SVGs,
+    /// for rendering. Diagrams that cannot be drawn stay code blocks.
+    pub async fn draw_diagrams(&self, notes: Vec<Note>) -> Vec<Note> {
+        let mut drawn: HashMap<String, String> = HashMap::new();
+        for note in &notes {
+            for (kind, source) in extract_diagrams(&note.content) {
+                let key = Self::key(kind, &source);
+                if drawn.contains_key(&key) {
+                    continue;
+                }
+                if let Some(svg) = self.draw(kind, &source, &key).await {
+                    drawn.insert(key, svg);
+                }
+            }
+        }
+        if drawn.is_empty() {
+            return notes;
+        }
+
+        notes
+            .into_iter()
+            .map(|mut note| {
+                note.content = replace_diagrams(&note.content, |kind, source| {
+                    drawn.get(&Self::key(kind, source)).cloned()
+                });
+                note
+            })
+            .collect()
+    }
+
+    fn key(kind: DiagramKind, source: &str) -> String {
+        format!("{}:{}", kind.name(), content_hash(source))
+    }
+
+    async fn draw(&self, kind: DiagramKind, source: &str, key: &str) -> Option<String> {
+        if let Some(figure) = self.figures.lock().unwrap().get(key) {
+            return Some(figure.clone());
+        }
+
+        let figure = match self.renderer.render_svg(kind, source).await {
+            Ok(svg) => diagram_markdown(kind, &svg)?,
+            Err(e) => {
+                tracing::warn!("Failed to draw {} diagram: {}", kind.name(), e);
+                return None;
+            }
+        };
+        let mut cache = self.figures.lock().unwrap();
+        if cache.len() >= DIAGRAM_CACHE_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(key.to_string(), figure.clone());
+        Some(figure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagram_blocks_are_found_by_language() {
+        let content = "Flow:\n\n```mermaid\ngraph TD\n  A --> B\n```\n\n~~~puml\n@startuml\nA -> B\n@enduml\n~~~\n\n```rust\nfn main() {}\n```\n";
+
+        assert_eq!(
+            extract_diagrams(content),
+            vec![
+                (DiagramKind::Mermaid, "graph TD\n  A --> B\n".to_string()),
+                (
+                    DiagramKind::PlantUml,
+                    "@startuml\nA -> B\n@enduml\n".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blocks_inside_code_and_unclosed_blocks_are_kept() {
+        let nested = "````markdown\n```mermaid\ngraph TD\n```\n````\n";
+        assert!(extract_diagrams(nested).is_empty());
+
+        let unclosed = "```mermaid\ngraph TD\n";
+        assert_eq!(
+            replace_diagrams(unclosed, |_, _| Some("drawn".to_string())),
+            unclosed
+        );
+    }
+
+    #[test]
+    fn test_diagrams_are_replaced_in_place() {
+        let content = "Before\n```mermaid\ngraph TD\n```\nAfter";
+        let replaced = replace_diagrams(content, |kind, source| {
+            Some(format!("<{}:{}>\n", kind.name(), source.trim()))
+        });
+        assert_eq!(replaced, "Before\n<mermaid:graph TD>\nAfter");
+    }
+
+    #[test]
+    fn test_diagram_markdown_is_an_image_without_prolog() {
+        let svg = "<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>";
+        let markdown = diagram_markdown(DiagramKind::PlantUml, svg).unwrap();
+
+        let encoded = markdown
+            .strip_prefix("![plantuml diagram](data:image/svg+xml;base64,")
+            .and_then(|rest| rest.strip_suffix(")\n\n"))
+            .unwrap();
+        assert_eq!(
+            BASE64_STANDARD.decode(encoded).unwrap(),
+            b"<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>"
+        );
+        assert_eq!(diagram_markdown(DiagramKind::Mermaid, "Syntax error"), None);
+    }
+
+    mod diagram_service_tests {
+        use super::*;
+        use crate::errors::{DomainError, DomainResult};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use uuid::Uuid;
+
+        /// Draws Mermaid sources as an SVG holding their text, and fails
+        /// on PlantUML
+        #[derive(Default)]
+        struct TextRenderer {
+            calls: AtomicUsize,
+        }
+
+        #[async_trait::async_trait]
+        impl DiagramRenderer for TextRenderer {
+            async fn render_svg(&self, kind: DiagramKind, source: &str) -> DomainResult<String> {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                match kind {
+                    DiagramKind::Mermaid => {
+                        Ok(format!("<svg><text>{}</text></svg>", source.trim()))
+                    }
+                    DiagramKind::PlantUml => Err(DomainError::InfrastructureError(
+                        "Kroki is down".to_string(),
+                    )),
+                }
+            }
+        }
+
+        #[tokio::test]
+        async fn test_diagrams_are_drawn_once_and_failures_stay_code() {
+            let renderer = Arc::new(TextRenderer::default());
+            let service = DiagramService::new(renderer.clone());
+            let user_id = Uuid::new_v4();
+            let flow = "```mermaid\ngraph TD\n```\n";
+            let content = format!("{}\n```plantuml\nA -> B\n```\n", flow);
+            let notes = vec![
+                Note::new(user_id, None, content.as_str()),
+                Note::new(user_id, None, flow),
+            ];
+
+            let drawn = service.draw_diagrams(notes.clone()).await;
+            let figure =
+                diagram_markdown(DiagramKind::Mermaid, "<svg><text>graph TD</text></svg>").unwrap();
+            assert_eq!(
+                drawn[0].content,
+                format!("{}\n```plantuml\nA -> B\n```\n", figure)
+            );
+            assert_eq!(drawn[1].content, figure);
+            // The shared Mermaid source is drawn once; PlantUML is tried
+            assert_eq!(renderer.calls.load(Ordering::SeqCst), 2);
+
+            service.draw_diagrams(notes).await;
+            assert_eq!(renderer.calls.load(Ordering::SeqCst), 3);
+        }
+    }
+}
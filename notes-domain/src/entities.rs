@@ -4,14 +4,26 @@
 //! These represent the core business concepts of the application.
 
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::value_objects::{Email, NoteTitle, TagName};
+use crate::bookmarks::LinkPreview;
+use crate::dates::parse_timezone;
+use crate::geo::{BoundingBox, GeoPoint};
+use crate::language::Language;
+use crate::value_objects::{Email, NoteTitle, PlaceName, TagName};
 
 /// Maximum number of tags allowed per note (business rule)
 pub const MAX_TAGS_PER_NOTE: usize = 10;
 
+/// Default maximum number of pinned notes per user (configurable per instance)
+pub const DEFAULT_MAX_PINNED_NOTES: usize = 10;
+
+/// Default window in which repeated edits by the same user share one version
+/// snapshot (configurable per instance)
+pub const DEFAULT_VERSION_DEBOUNCE_MINUTES: u32 = 10;
+
 /// A user in the system.
 ///
 /// Designed to be OIDC-ready: the `subject` field stores the OIDC subject claim
@@ -26,6 +38,12 @@ pub struct User {
     pub email: Email,
     /// Password hash for local authentication (Argon2 etc.)
     pub password_hash: Option<String>,
+    /// Optional name shown instead of the email address
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Optional URL of the user's avatar image
+    #[serde(default)]
+    pub avatar_url: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -37,6 +55,8 @@ impl User {
             subject: subject.into(),
             email,
             password_hash: None,
+            display_name: None,
+            avatar_url: None,
             created_at: Utc::now(),
         }
     }
@@ -49,6 +69,8 @@ impl User {
             subject, // Use email as subject for local auth
             email,
             password_hash: Some(password_hash.into()),
+            display_name: None,
+            avatar_url: None,
             created_at: Utc::now(),
         }
     }
@@ -67,6 +89,8 @@ impl User {
             subject: subject.into(),
             email,
             password_hash,
+            display_name: None,
+            avatar_url: None,
             created_at,
         }
     }
@@ -75,32 +99,178 @@ impl User {
     pub fn email_str(&self) -> &str {
         self.email.as_ref()
     }
+
+    /// Change the user's email address.
+    ///
+    /// Local accounts use their email as subject, so the subject follows
+    /// the email to keep future registrations of the old address possible.
+    pub fn set_email(&mut self, email: Email) {
+        if self.password_hash.is_some() && self.subject == self.email_str() {
+            self.subject = email.as_ref().to_string();
+        }
+        self.email = email;
+    }
+}
+
+/// How long an email change confirmation token stays valid
+pub const EMAIL_CHANGE_VALIDITY_HOURS: i64 = 24;
+
+/// A pending change of a user's email address.
+///
+/// The change only takes effect once the token sent to the new address
+/// has been confirmed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailChange {
+    pub user_id: Uuid,
+    pub new_email: Email,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl EmailChange {
+    /// Start a new email change with a random token
+    pub fn new(user_id: Uuid, new_email: Email) -> Self {
+        Self {
+            user_id,
+            new_email,
+            token: Uuid::new_v4().simple().to_string(),
+            expires_at: Utc::now() + chrono::Duration::hours(EMAIL_CHANGE_VALIDITY_HOURS),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at < Utc::now()
+    }
+}
+
+/// Default ordering of note lists in clients
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSortOrder {
+    #[default]
+    UpdatedDesc,
+    CreatedDesc,
+    TitleAsc,
+}
+
+/// Editor preferences, interpreted by clients
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorPreferences {
+    pub spell_check: bool,
+    pub vim_mode: bool,
+    pub show_line_numbers: bool,
+}
+
+impl Default for EditorPreferences {
+    fn default() -> Self {
+        Self {
+            spell_check: true,
+            vim_mode: false,
+            show_line_numbers: false,
+        }
+    }
+}
+
+/// Per-user preferences.
+///
+/// Stored as a single JSON document; every field has a default so settings
+/// written by older versions (or missing entirely) still deserialize.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// Color applied to new notes when none is given
+    pub default_note_color: String,
+    pub default_sort: NoteSortOrder,
+    /// BCP 47 language tag, e.g. `en-US`
+    pub locale: String,
+    /// IANA timezone name, e.g. `Europe/Warsaw`
+    pub timezone: String,
+    pub editor: EditorPreferences,
+    /// Whether notes are sent for embedding and related-note linking
+    pub smart_features_enabled: bool,
+    /// Archive notes untouched for this many days (`None` = disabled)
+    pub auto_archive_after_days: Option<u32>,
+    /// Whether search queries are remembered for history and suggestions
+    pub search_history_enabled: bool,
+    /// Whether untitled notes are given a title generated from their content
+    pub auto_title_enabled: bool,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            default_note_color: default_color(),
+            default_sort: NoteSortOrder::default(),
+            locale: "en-US".to_string(),
+            timezone: "UTC".to_string(),
+            editor: EditorPreferences::default(),
+            smart_features_enabled: true,
+            auto_archive_after_days: None,
+            search_history_enabled: true,
+            auto_title_enabled: false,
+        }
+    }
+}
+
+impl UserSettings {
+    /// The configured timezone, or UTC if it is not a known IANA name
+    pub fn time_zone(&self) -> Tz {
+        parse_timezone(&self.timezone)
+    }
 }
 
 /// A tag that can be attached to notes.
 ///
 /// Tags are user-scoped, meaning each user has their own set of tags.
+/// Sidebars list pinned tags first, then by `sort_order`; tags the user
+/// never reordered come last, by name.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Tag {
     pub id: Uuid,
     /// Validated tag name (1-50 chars, trimmed, lowercase)
     pub name: TagName,
     pub user_id: Uuid,
+    #[serde(default)]
+    pub is_pinned: bool,
+    /// Position set by the last reorder (lowest first)
+    #[serde(default)]
+    pub sort_order: Option<i32>,
 }
 
 impl Tag {
     /// Create a new tag for a user
     pub fn new(name: TagName, user_id: Uuid) -> Self {
+        Self::with_id(Uuid::new_v4(), name, user_id)
+    }
+
+    /// Create a tag with a specific ID (for reconstruction from storage)
+    pub fn with_id(id: Uuid, name: TagName, user_id: Uuid) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id,
             name,
             user_id,
+            is_pinned: false,
+            sort_order: None,
         }
     }
 
-    /// Create a tag with a specific ID (for reconstruction from storage)
-    pub fn with_id(id: Uuid, name: TagName, user_id: Uuid) -> Self {
-        Self { id, name, user_id }
+    /// Sort tags the way sidebars list them
+    pub fn sort_for_sidebar(tags: &mut [Tag]) {
+        tags.sort_by(|a, b| {
+            (
+                !a.is_pinned,
+                a.sort_order.is_none(),
+                a.sort_order,
+                a.name_str(),
+            )
+                .cmp(&(
+                    !b.is_pinned,
+                    b.sort_order.is_none(),
+                    b.sort_order,
+                    b.name_str(),
+                ))
+        });
     }
 
     /// Get name as string reference (convenience method)
@@ -126,10 +296,34 @@ pub struct Note {
     #[serde(default = "default_color")]
     pub color: String,
     pub is_pinned: bool,
+    /// Position within the pinned section (lowest first); `None` when unpinned
+    #[serde(default)]
+    pub pin_order: Option<i32>,
     pub is_archived: bool,
+    /// Locked notes reject edits and deletion until unlocked
+    #[serde(default)]
+    pub is_locked: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the note was moved to the trash; `None` for live notes
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
     pub tags: Vec<Tag>,
+    /// Previews of the note's bookmarks, filled in by the worker
+    #[serde(default)]
+    pub link_previews: Vec<LinkPreview>,
+    /// Where the note was written
+    #[serde(default)]
+    pub location: Option<GeoPoint>,
+    /// Human-readable name of the location, e.g. `Lisbon`
+    #[serde(default)]
+    pub place_name: Option<PlaceName>,
+    /// When the user wants to be reminded of the note
+    #[serde(default)]
+    pub remind_at: Option<DateTime<Utc>>,
+    /// Dominant language of the content, detected whenever it changes
+    #[serde(default)]
+    pub language: Option<Language>,
 }
 
 fn default_color() -> String {
@@ -140,32 +334,88 @@ impl Note {
     /// Create a new note with the current timestamp
     pub fn new(user_id: Uuid, title: Option<NoteTitle>, content: impl Into<String>) -> Self {
         let now = Utc::now();
+        let content = content.into();
         Self {
             id: Uuid::new_v4(),
             user_id,
             title,
-            content: content.into(),
+            language: Language::detect(&content),
+            content,
             color: default_color(),
             is_pinned: false,
+            pin_order: None,
             is_archived: false,
+            is_locked: false,
             created_at: now,
             updated_at: now,
+            deleted_at: None,
             tags: Vec::new(),
+            link_previews: Vec::new(),
+            location: None,
+            place_name: None,
+            remind_at: None,
         }
     }
 
+    /// Whether the note is in the trash
+    pub fn is_trashed(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Move the note to the trash, unpinning it
+    pub fn move_to_trash(&mut self) {
+        self.is_pinned = false;
+        self.pin_order = None;
+        self.deleted_at = Some(Utc::now());
+    }
+
+    /// Take the note back out of the trash
+    pub fn restore(&mut self) {
+        self.deleted_at = None;
+    }
+
     /// Set the color of the note
     pub fn set_color(&mut self, color: impl Into<String>) {
         self.color = color.into();
         self.updated_at = Utc::now();
     }
 
+    /// Set or clear where the note was written
+    pub fn set_location(&mut self, location: Option<GeoPoint>) {
+        self.location = location;
+        self.updated_at = Utc::now();
+    }
+
+    /// Set or clear the name of the note's location
+    pub fn set_place_name(&mut self, place_name: Option<PlaceName>) {
+        self.place_name = place_name;
+        self.updated_at = Utc::now();
+    }
+
+    /// Set or clear the note's reminder
+    pub fn set_remind_at(&mut self, remind_at: Option<DateTime<Utc>>) {
+        self.remind_at = remind_at;
+        self.updated_at = Utc::now();
+    }
+
     /// Pin or unpin the note
+    ///
+    /// Unpinning clears the pin position; the service assigns one when pinning.
     pub fn set_pinned(&mut self, pinned: bool) {
         self.is_pinned = pinned;
+        if !pinned {
+            self.pin_order = None;
+        }
         self.updated_at = Utc::now();
     }
 
+    /// Lock or unlock the note
+    ///
+    /// Locking is not an edit, so `updated_at` is left alone.
+    pub fn set_locked(&mut self, locked: bool) {
+        self.is_locked = locked;
+    }
+
     /// Archive or unarchive the note
     pub fn set_archived(&mut self, archived: bool) {
         self.is_archived = archived;
@@ -178,9 +428,10 @@ impl Note {
         self.updated_at = Utc::now();
     }
 
-    /// Update the note's content
+    /// Update the note's content, detecting its language again
     pub fn set_content(&mut self, content: impl Into<String>) {
         self.content = content.into();
+        self.language = Language::detect(&self.content);
         self.updated_at = Utc::now();
     }
 
@@ -209,6 +460,9 @@ pub struct NoteVersion {
     pub title: Option<String>,
     pub content: String,
     pub created_at: DateTime<Utc>,
+    /// User whose edit replaced this state, if known
+    #[serde(default)]
+    pub created_by: Option<Uuid>,
 }
 
 impl NoteVersion {
@@ -219,6 +473,19 @@ impl NoteVersion {
             title,
             content,
             created_at: Utc::now(),
+            created_by: None,
+        }
+    }
+
+    /// Snapshot of `note` taken before an edit by `actor`
+    pub fn snapshot(note: &Note, actor: Uuid) -> Self {
+        Self {
+            created_by: Some(actor),
+            ..Self::new(
+                note.id,
+                note.title.as_ref().map(|t| t.as_ref().to_string()),
+                note.content.clone(),
+            )
         }
     }
 }
@@ -244,12 +511,38 @@ impl NoteLink {
     }
 }
 
+/// Maximum length (in characters) of related note snippets
+pub const RELATED_NOTE_SNIPPET_LENGTH: usize = 160;
+
+/// A link together with a preview of the note it points to
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelatedNote {
+    pub link: NoteLink,
+    /// Target note title (empty when untitled)
+    pub title: String,
+    /// Start of the target note's content
+    pub snippet: String,
+}
+
+/// How many notes a user has outside the trash
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteCounts {
+    pub total: usize,
+    /// Pinned notes that are not archived
+    pub pinned: usize,
+    pub archived: usize,
+}
+
 /// Filter options for querying notes
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NoteFilter {
     pub is_pinned: Option<bool>,
     pub is_archived: Option<bool>,
     pub tag_id: Option<Uuid>,
+    /// List trashed notes instead of live ones
+    pub trashed: bool,
+    /// Only notes located inside this box
+    pub within: Option<BoundingBox>,
 }
 
 impl NoteFilter {
@@ -276,6 +569,16 @@ impl NoteFilter {
         self.tag_id = Some(tag_id);
         self
     }
+
+    pub fn trashed(mut self) -> Self {
+        self.trashed = true;
+        self
+    }
+
+    pub fn within(mut self, bbox: BoundingBox) -> Self {
+        self.within = Some(bbox);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -330,6 +633,36 @@ mod tests {
         }
     }
 
+    mod email_change_tests {
+        use super::*;
+
+        #[test]
+        fn test_set_email_moves_subject_for_local_users() {
+            let mut user = User::new_local(Email::try_from("old@example.com").unwrap(), "hash");
+            user.set_email(Email::try_from("new@example.com").unwrap());
+
+            assert_eq!(user.email_str(), "new@example.com");
+            assert_eq!(user.subject, "new@example.com");
+        }
+
+        #[test]
+        fn test_set_email_keeps_oidc_subject() {
+            let mut user = User::new("oidc|123", Email::try_from("old@example.com").unwrap());
+            user.set_email(Email::try_from("new@example.com").unwrap());
+
+            assert_eq!(user.subject, "oidc|123");
+        }
+
+        #[test]
+        fn test_new_email_change_is_not_expired() {
+            let change =
+                EmailChange::new(Uuid::new_v4(), Email::try_from("new@example.com").unwrap());
+
+            assert!(!change.is_expired());
+            assert_eq!(change.token.len(), 32);
+        }
+    }
+
     mod tag_tests {
         use super::*;
 
@@ -365,6 +698,28 @@ mod tests {
             assert_eq!(tag.name_str(), "my-tag");
             assert_eq!(tag.user_id, user_id);
         }
+
+        #[test]
+        fn test_sort_for_sidebar() {
+            let user_id = Uuid::new_v4();
+            let tag = |name: &str, is_pinned: bool, sort_order: Option<i32>| Tag {
+                is_pinned,
+                sort_order,
+                ..Tag::new(TagName::try_from(name).unwrap(), user_id)
+            };
+            let mut tags = vec![
+                tag("alpha", false, None),
+                tag("beta", false, Some(1)),
+                tag("gamma", true, Some(2)),
+                tag("delta", false, Some(0)),
+                tag("aardvark", false, None),
+            ];
+
+            Tag::sort_for_sidebar(&mut tags);
+
+            let names: Vec<&str> = tags.iter().map(Tag::name_str).collect();
+            assert_eq!(names, vec!["gamma", "delta", "beta", "aardvark", "alpha"]);
+        }
     }
 
     mod note_tests {
@@ -434,6 +789,22 @@ mod tests {
             assert!(note.updated_at > original_updated_at);
         }
 
+        #[test]
+        fn test_move_to_trash_unpins_and_restore_clears() {
+            let user_id = Uuid::new_v4();
+            let mut note = Note::new(user_id, None, "Content");
+            note.is_pinned = true;
+            note.pin_order = Some(0);
+
+            note.move_to_trash();
+            assert!(note.is_trashed());
+            assert!(!note.is_pinned);
+            assert_eq!(note.pin_order, None);
+
+            note.restore();
+            assert!(!note.is_trashed());
+        }
+
         #[test]
         fn test_note_can_add_tag_when_under_limit() {
             let user_id = Uuid::new_v4();
@@ -511,4 +882,38 @@ mod tests {
             assert_eq!(filter.tag_id, Some(tag_id));
         }
     }
+
+    mod user_settings_tests {
+        use super::*;
+
+        #[test]
+        fn test_missing_fields_fall_back_to_defaults() {
+            let settings: UserSettings =
+                serde_json::from_str(r#"{"locale":"pl-PL","editor":{"vim_mode":true}}"#).unwrap();
+
+            assert_eq!(settings.locale, "pl-PL");
+            assert!(settings.editor.vim_mode);
+            assert!(settings.editor.spell_check);
+            assert_eq!(settings.timezone, "UTC");
+            assert!(settings.smart_features_enabled);
+            assert!(settings.search_history_enabled);
+            assert!(!settings.auto_title_enabled);
+        }
+
+        #[test]
+        fn test_settings_round_trip_through_json() {
+            let settings = UserSettings {
+                default_sort: NoteSortOrder::TitleAsc,
+                smart_features_enabled: false,
+                ..UserSettings::default()
+            };
+
+            let json = serde_json::to_string(&settings).unwrap();
+            assert!(json.contains("\"title_asc\""));
+            assert_eq!(
+                serde_json::from_str::<UserSettings>(&json).unwrap(),
+                settings
+            );
+        }
+    }
 }
@@ -9,6 +9,38 @@ use uuid::Uuid;
 
 use crate::entities::MAX_TAGS_PER_NOTE;
 
+/// Why a repository operation failed
+///
+/// Lets callers tell conflicts from outages without parsing driver messages.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RepositoryError {
+    /// A uniqueness or foreign key constraint was violated
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    /// A row the operation depends on does not exist
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    /// The database could not be reached or is busy; worth retrying
+    #[error("connection error: {0}")]
+    Connection(String),
+
+    /// Stored data could not be encoded or decoded
+    #[error("serialization error: {0}")]
+    Serialization(String),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl RepositoryError {
+    /// Whether retrying the operation later may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, RepositoryError::Connection(_))
+    }
+}
+
 /// Domain-level errors for K-Notes operations
 #[derive(Debug, Error)]
 pub enum DomainError {
@@ -16,6 +48,10 @@ pub enum DomainError {
     #[error("Note not found: {0}")]
     NoteNotFound(Uuid),
 
+    /// The note is locked against edits and deletion
+    #[error("Note is locked: {0}")]
+    NoteLocked(Uuid),
+
     /// The requested user was not found
     #[error("User not found: {0}")]
     UserNotFound(Uuid),
@@ -24,6 +60,38 @@ pub enum DomainError {
     #[error("Tag not found: {0}")]
     TagNotFound(Uuid),
 
+    /// The requested job was not found
+    #[error("Job not found: {0}")]
+    JobNotFound(Uuid),
+
+    /// The requested announcement was not found
+    #[error("Announcement not found: {0}")]
+    AnnouncementNotFound(Uuid),
+
+    /// The requested invitation was not found
+    #[error("Invitation not found: {0}")]
+    InvitationNotFound(Uuid),
+
+    /// The requested board was not found
+    #[error("Board not found: {0}")]
+    BoardNotFound(Uuid),
+
+    /// The requested note relation was not found
+    #[error("Relation not found: {0}")]
+    RelationNotFound(Uuid),
+
+    /// The user has no tag alias with this name
+    #[error("Tag alias not found: {0}")]
+    TagAliasNotFound(String),
+
+    /// The requested impersonation was not found
+    #[error("Impersonation not found: {0}")]
+    ImpersonationNotFound(Uuid),
+
+    /// No version of this legal document has been published
+    #[error("Legal document not found: {0}")]
+    LegalDocumentNotFound(String),
+
     /// User with this email/subject already exists
     #[error("User already exists: {0}")]
     UserAlreadyExists(String),
@@ -36,17 +104,38 @@ pub enum DomainError {
     #[error("Tag limit exceeded: maximum {max} tags allowed, note has {current}")]
     TagLimitExceeded { max: usize, current: usize },
 
+    /// Attempted to pin more notes than allowed
+    #[error("Pin limit exceeded: maximum {max} pinned notes allowed")]
+    PinLimitExceeded { max: usize },
+
+    /// There is no recent change to undo
+    #[error("Nothing to undo")]
+    NothingToUndo,
+
+    /// The latest change was not an undo
+    #[error("Nothing to redo")]
+    NothingToRedo,
+
     /// A validation error occurred
     #[error("Validation error: {0}")]
     ValidationError(String),
 
-    /// User is not authorized to perform this action
-    #[error("Unauthorized: {0}")]
-    Unauthorized(String),
+    /// The request does not say who is making it, or its credentials are
+    /// invalid
+    #[error("Unauthenticated: {0}")]
+    Unauthenticated(String),
+
+    /// The user is known but not allowed to perform this action
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// The user has to accept the current legal documents first
+    #[error("Consent required: accept the current {0}")]
+    ConsentRequired(String),
 
     /// A repository/infrastructure error occurred
     #[error("Repository error: {0}")]
-    RepositoryError(String),
+    RepositoryError(#[from] RepositoryError),
 
     /// An infrastructure adapter error occurred
     #[error("Infrastructure error: {0}")]
@@ -67,9 +156,14 @@ impl DomainError {
         Self::ValidationError(message.into())
     }
 
-    /// Create an unauthorized error
-    pub fn unauthorized(message: impl Into<String>) -> Self {
-        Self::Unauthorized(message.into())
+    /// Create an unauthenticated error
+    pub fn unauthenticated(message: impl Into<String>) -> Self {
+        Self::Unauthenticated(message.into())
+    }
+
+    /// Create a forbidden error
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::Forbidden(message.into())
     }
 
     /// Check if this error indicates a "not found" condition
@@ -79,6 +173,14 @@ impl DomainError {
             DomainError::NoteNotFound(_)
                 | DomainError::UserNotFound(_)
                 | DomainError::TagNotFound(_)
+                | DomainError::JobNotFound(_)
+                | DomainError::AnnouncementNotFound(_)
+                | DomainError::InvitationNotFound(_)
+                | DomainError::BoardNotFound(_)
+                | DomainError::RelationNotFound(_)
+                | DomainError::TagAliasNotFound(_)
+                | DomainError::ImpersonationNotFound(_)
+                | DomainError::LegalDocumentNotFound(_)
         )
     }
 
@@ -86,7 +188,11 @@ impl DomainError {
     pub fn is_conflict(&self) -> bool {
         matches!(
             self,
-            DomainError::UserAlreadyExists(_) | DomainError::TagAlreadyExists(_)
+            DomainError::UserAlreadyExists(_)
+                | DomainError::TagAlreadyExists(_)
+                | DomainError::NothingToUndo
+                | DomainError::NothingToRedo
+                | DomainError::RepositoryError(RepositoryError::Conflict(_))
         )
     }
 }
@@ -131,6 +237,14 @@ mod tests {
         assert!(DomainError::NoteNotFound(Uuid::new_v4()).is_not_found());
         assert!(DomainError::UserNotFound(Uuid::new_v4()).is_not_found());
         assert!(DomainError::TagNotFound(Uuid::new_v4()).is_not_found());
+        assert!(DomainError::JobNotFound(Uuid::new_v4()).is_not_found());
+        assert!(DomainError::AnnouncementNotFound(Uuid::new_v4()).is_not_found());
+        assert!(DomainError::InvitationNotFound(Uuid::new_v4()).is_not_found());
+        assert!(DomainError::BoardNotFound(Uuid::new_v4()).is_not_found());
+        assert!(DomainError::RelationNotFound(Uuid::new_v4()).is_not_found());
+        assert!(DomainError::TagAliasNotFound("js".to_string()).is_not_found());
+        assert!(DomainError::ImpersonationNotFound(Uuid::new_v4()).is_not_found());
+        assert!(DomainError::LegalDocumentNotFound("terms".to_string()).is_not_found());
         assert!(!DomainError::validation("test").is_not_found());
     }
 
@@ -138,6 +252,28 @@ mod tests {
     fn test_is_conflict() {
         assert!(DomainError::UserAlreadyExists("test@example.com".into()).is_conflict());
         assert!(DomainError::TagAlreadyExists("work".into()).is_conflict());
+        assert!(DomainError::from(RepositoryError::Conflict("UNIQUE".into())).is_conflict());
         assert!(!DomainError::NoteNotFound(Uuid::new_v4()).is_conflict());
     }
+
+    #[test]
+    fn test_unauthenticated_and_forbidden_are_distinct() {
+        let error = DomainError::unauthenticated("Token expired");
+        assert_eq!(error.to_string(), "Unauthenticated: Token expired");
+        assert!(matches!(error, DomainError::Unauthenticated(_)));
+
+        let error = DomainError::forbidden("Cannot delete another user's note");
+        assert_eq!(
+            error.to_string(),
+            "Forbidden: Cannot delete another user's note"
+        );
+        assert!(matches!(error, DomainError::Forbidden(_)));
+    }
+
+    #[test]
+    fn test_only_connection_errors_are_retryable() {
+        assert!(RepositoryError::Connection("database is locked".into()).is_retryable());
+        assert!(!RepositoryError::Conflict("UNIQUE".into()).is_retryable());
+        assert!(!RepositoryError::Other("boom".into()).is_retryable());
+    }
 }
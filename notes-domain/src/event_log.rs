@@ -0,0 +1,468 @@
+//! Typed record of what users did to their notes and tags
+//!
+//! Services describe every change as a [`LoggedEvent`] and hand it to the
+//! [`EventDispatcher`], which passes it to
+//! in-process handlers such as the persistent event log. Events carry enough
+//! of the previous state (tag names, old titles) to describe or revert the
+//! change without the note itself.
+//!
+//! Unlike [`DomainEvent`](crate::events::DomainEvent), which carries whole
+//! notes to the message broker for background processing, these events stay
+//! in the process and only carry what changed.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{Note, Tag};
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::EventHandler;
+use crate::repositories::EventLogRepository;
+
+/// Events returned per activity page when no limit is given
+pub const DEFAULT_ACTIVITY_LIMIT: usize = 50;
+
+/// Maximum number of events returned per activity page
+pub const MAX_ACTIVITY_LIMIT: usize = 200;
+
+/// How far back undo and redo reach
+pub const UNDO_WINDOW_MINUTES: i64 = 10;
+
+/// Undoable changes this close to the latest one are undone with it, so a
+/// bulk action sent as one request per note is undone as a whole
+pub const UNDO_GROUP_SECONDS: i64 = 5;
+
+/// What the user did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum LoggedEventKind {
+    NoteCreated {
+        note_id: Uuid,
+        title: Option<String>,
+    },
+    /// Title or content changed
+    NoteEdited {
+        note_id: Uuid,
+        title: Option<String>,
+        previous_title: Option<String>,
+    },
+    NoteTagged {
+        note_id: Uuid,
+        tag: String,
+    },
+    NoteUntagged {
+        note_id: Uuid,
+        tag: String,
+    },
+    NotePinned {
+        note_id: Uuid,
+    },
+    NoteUnpinned {
+        note_id: Uuid,
+    },
+    NoteArchived {
+        note_id: Uuid,
+    },
+    NoteUnarchived {
+        note_id: Uuid,
+    },
+    NoteLocked {
+        note_id: Uuid,
+    },
+    NoteUnlocked {
+        note_id: Uuid,
+    },
+    /// Moved to the trash
+    NoteTrashed {
+        note_id: Uuid,
+    },
+    /// Taken back out of the trash
+    NoteRestored {
+        note_id: Uuid,
+    },
+    /// Deleted for good when the trash was purged
+    NotePurged {
+        note_id: Uuid,
+    },
+    NoteDuplicated {
+        note_id: Uuid,
+        source_id: Uuid,
+    },
+    TagCreated {
+        tag_id: Uuid,
+        name: String,
+    },
+    TagRenamed {
+        tag_id: Uuid,
+        name: String,
+        previous_name: String,
+    },
+    TagDeleted {
+        tag_id: Uuid,
+        name: String,
+    },
+    /// The listed changes were reverted
+    Undone {
+        reverted: Vec<LoggedEventKind>,
+    },
+}
+
+impl LoggedEventKind {
+    /// Serialized type name, e.g. `note_tagged`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoteCreated { .. } => "note_created",
+            Self::NoteEdited { .. } => "note_edited",
+            Self::NoteTagged { .. } => "note_tagged",
+            Self::NoteUntagged { .. } => "note_untagged",
+            Self::NotePinned { .. } => "note_pinned",
+            Self::NoteUnpinned { .. } => "note_unpinned",
+            Self::NoteArchived { .. } => "note_archived",
+            Self::NoteUnarchived { .. } => "note_unarchived",
+            Self::NoteLocked { .. } => "note_locked",
+            Self::NoteUnlocked { .. } => "note_unlocked",
+            Self::NoteTrashed { .. } => "note_trashed",
+            Self::NoteRestored { .. } => "note_restored",
+            Self::NotePurged { .. } => "note_purged",
+            Self::NoteDuplicated { .. } => "note_duplicated",
+            Self::TagCreated { .. } => "tag_created",
+            Self::TagRenamed { .. } => "tag_renamed",
+            Self::TagDeleted { .. } => "tag_deleted",
+            Self::Undone { .. } => "undone",
+        }
+    }
+
+    /// Note the event is about, if any
+    pub fn note_id(&self) -> Option<Uuid> {
+        match self {
+            Self::NoteCreated { note_id, .. }
+            | Self::NoteEdited { note_id, .. }
+            | Self::NoteTagged { note_id, .. }
+            | Self::NoteUntagged { note_id, .. }
+            | Self::NotePinned { note_id }
+            | Self::NoteUnpinned { note_id }
+            | Self::NoteArchived { note_id }
+            | Self::NoteUnarchived { note_id }
+            | Self::NoteLocked { note_id }
+            | Self::NoteUnlocked { note_id }
+            | Self::NoteTrashed { note_id }
+            | Self::NoteRestored { note_id }
+            | Self::NotePurged { note_id }
+            | Self::NoteDuplicated { note_id, .. } => Some(*note_id),
+            Self::TagCreated { .. }
+            | Self::TagRenamed { .. }
+            | Self::TagDeleted { .. }
+            | Self::Undone { .. } => None,
+        }
+    }
+
+    /// Whether undo can revert it: trashing, archiving and tag changes
+    pub fn is_undoable(&self) -> bool {
+        matches!(
+            self,
+            Self::NoteTrashed { .. }
+                | Self::NoteArchived { .. }
+                | Self::NoteTagged { .. }
+                | Self::NoteUntagged { .. }
+        )
+    }
+
+    pub fn note_created(note: &Note) -> Self {
+        Self::NoteCreated {
+            note_id: note.id,
+            title: title_of(note),
+        }
+    }
+
+    pub fn tag_created(tag: &Tag) -> Self {
+        Self::TagCreated {
+            tag_id: tag.id,
+            name: tag.name.as_ref().to_string(),
+        }
+    }
+
+    /// Events describing how `after` differs from `before`
+    pub fn note_changes(before: &Note, after: &Note) -> Vec<Self> {
+        let note_id = after.id;
+        let mut events = Vec::new();
+
+        if before.title != after.title || before.content != after.content {
+            events.push(Self::NoteEdited {
+                note_id,
+                title: title_of(after),
+                previous_title: title_of(before),
+            });
+        }
+        if before.is_pinned != after.is_pinned {
+            events.push(if after.is_pinned {
+                Self::NotePinned { note_id }
+            } else {
+                Self::NoteUnpinned { note_id }
+            });
+        }
+        if before.is_archived != after.is_archived {
+            events.push(if after.is_archived {
+                Self::NoteArchived { note_id }
+            } else {
+                Self::NoteUnarchived { note_id }
+            });
+        }
+
+        let has_tag = |note: &Note, name: &str| note.tags.iter().any(|t| t.name.as_ref() == name);
+        for tag in &before.tags {
+            if !has_tag(after, tag.name.as_ref()) {
+                events.push(Self::NoteUntagged {
+                    note_id,
+                    tag: tag.name.as_ref().to_string(),
+                });
+            }
+        }
+        for tag in &after.tags {
+            if !has_tag(before, tag.name.as_ref()) {
+                events.push(Self::NoteTagged {
+                    note_id,
+                    tag: tag.name.as_ref().to_string(),
+                });
+            }
+        }
+
+        events
+    }
+}
+
+fn title_of(note: &Note) -> Option<String> {
+    note.title.as_ref().map(|t| t.as_ref().to_string())
+}
+
+/// An entry in a user's event log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub id: Uuid,
+    /// User who acted (and owns the affected data)
+    pub user_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: LoggedEventKind,
+}
+
+impl LoggedEvent {
+    pub fn new(user_id: Uuid, kind: LoggedEventKind) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            occurred_at: Utc::now(),
+            kind,
+        }
+    }
+}
+
+/// A page of a user's activity, newest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityPage {
+    pub events: Vec<LoggedEvent>,
+    /// Pass as `before` to get the next page; `None` on the last page
+    pub next_before: Option<DateTime<Utc>>,
+}
+
+/// Passes logged events to in-process handlers
+///
+/// Handlers run in registration order. A failing handler is logged and does
+/// not stop the others or fail the change that caused the event.
+#[derive(Default)]
+pub struct EventDispatcher {
+    handlers: Vec<Arc<dyn EventHandler>>,
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to add a handler
+    pub fn with_handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    /// Builder method to persist every event to the event log
+    pub fn with_event_log(self, event_log: Arc<dyn EventLogRepository>) -> Self {
+        self.with_handler(Arc::new(PersistEvents(event_log)))
+    }
+
+    /// Record what `user_id` did
+    pub async fn dispatch(&self, user_id: Uuid, kinds: Vec<LoggedEventKind>) {
+        for kind in kinds {
+            let event = LoggedEvent::new(user_id, kind);
+            for handler in &self.handlers {
+                if let Err(e) = handler.handle(&event).await {
+                    tracing::error!(
+                        event_id = %event.id,
+                        event_type = event.kind.as_str(),
+                        "Failed to handle event: {}",
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Handler appending events to the event log
+struct PersistEvents(Arc<dyn EventLogRepository>);
+
+#[async_trait::async_trait]
+impl EventHandler for PersistEvents {
+    async fn handle(&self, event: &LoggedEvent) -> DomainResult<()> {
+        self.0.append(event).await
+    }
+}
+
+/// Service reading users' event logs
+pub struct ActivityService {
+    event_log: Arc<dyn EventLogRepository>,
+}
+
+impl ActivityService {
+    pub fn new(event_log: Arc<dyn EventLogRepository>) -> Self {
+        Self { event_log }
+    }
+
+    /// The user's actions before `before` (the latest when `None`), newest first
+    pub async fn feed(
+        &self,
+        user_id: Uuid,
+        before: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> DomainResult<ActivityPage> {
+        let limit = limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT);
+        if limit == 0 || limit > MAX_ACTIVITY_LIMIT {
+            return Err(DomainError::validation(format!(
+                "limit must be between 1 and {}",
+                MAX_ACTIVITY_LIMIT
+            )));
+        }
+
+        // One extra event tells whether another page follows
+        let mut events = self
+            .event_log
+            .find_by_user(user_id, before, limit + 1)
+            .await?;
+        let next_before = if events.len() > limit {
+            events.truncate(limit);
+            events.last().map(|e| e.occurred_at)
+        } else {
+            None
+        };
+
+        Ok(ActivityPage {
+            events,
+            next_before,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_objects::{NoteTitle, TagName};
+
+    fn tag(name: &str, user_id: Uuid) -> Tag {
+        Tag::new(TagName::try_from(name).unwrap(), user_id)
+    }
+
+    #[test]
+    fn test_note_changes_describe_each_difference() {
+        let user_id = Uuid::new_v4();
+        let mut before = Note::new(user_id, None, "content");
+        before.tags = vec![tag("work", user_id), tag("ideas", user_id)];
+
+        let mut after = before.clone();
+        after.title = Some(NoteTitle::try_from("Plan").unwrap());
+        after.is_archived = true;
+        after.tags = vec![tag("work", user_id), tag("todo", user_id)];
+
+        let note_id = before.id;
+        assert_eq!(
+            LoggedEventKind::note_changes(&before, &after),
+            vec![
+                LoggedEventKind::NoteEdited {
+                    note_id,
+                    title: Some("Plan".to_string()),
+                    previous_title: None,
+                },
+                LoggedEventKind::NoteArchived { note_id },
+                LoggedEventKind::NoteUntagged {
+                    note_id,
+                    tag: "ideas".to_string(),
+                },
+                LoggedEventKind::NoteTagged {
+                    note_id,
+                    tag: "todo".to_string(),
+                },
+            ]
+        );
+        assert!(LoggedEventKind::note_changes(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_serialized_type_matches_as_str() {
+        let event = LoggedEvent::new(
+            Uuid::new_v4(),
+            LoggedEventKind::NotePinned {
+                note_id: Uuid::new_v4(),
+            },
+        );
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["type"], event.kind.as_str());
+        assert_eq!(serde_json::from_value::<LoggedEvent>(json).unwrap(), event);
+    }
+
+    mod activity_service_tests {
+        use super::*;
+        use crate::services::tests::*;
+
+        #[tokio::test]
+        async fn test_feed_pages_until_exhausted() {
+            let repo = Arc::new(MockEventLogRepository::default());
+            let user_id = Uuid::new_v4();
+            let start = Utc::now() - chrono::Duration::hours(1);
+            for minutes in 0..3 {
+                let mut event = LoggedEvent::new(
+                    user_id,
+                    LoggedEventKind::NotePinned {
+                        note_id: Uuid::new_v4(),
+                    },
+                );
+                event.occurred_at = start + chrono::Duration::minutes(minutes);
+                repo.append(&event).await.unwrap();
+            }
+            let service = ActivityService::new(repo);
+
+            let first = service.feed(user_id, None, Some(2)).await.unwrap();
+            assert_eq!(first.events.len(), 2);
+            assert_eq!(first.next_before, Some(first.events[1].occurred_at));
+
+            let last = service
+                .feed(user_id, first.next_before, Some(2))
+                .await
+                .unwrap();
+            assert_eq!(last.events.len(), 1);
+            assert_eq!(last.events[0].occurred_at, start);
+            assert_eq!(last.next_before, None);
+        }
+
+        #[tokio::test]
+        async fn test_feed_rejects_invalid_limit() {
+            let service = ActivityService::new(Arc::new(MockEventLogRepository::default()));
+
+            let result = service
+                .feed(Uuid::new_v4(), None, Some(MAX_ACTIVITY_LIMIT + 1))
+                .await;
+
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+        }
+    }
+}
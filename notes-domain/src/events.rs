@@ -0,0 +1,191 @@
+//! Domain events published to the message broker
+//!
+//! Every message is a [`DomainEvent`] envelope carrying an event ID, schema
+//! version and owner next to the typed payload:
+//!
+//! ```json
+//! {"id": "…", "version": 1, "occurred_at": "…", "user_id": "…",
+//!  "type": "note_updated", "data": {…}}
+//! ```
+//!
+//! Consumers skip event types they do not know, so new kinds can be added
+//! without redeploying every consumer first.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{Note, Tag};
+
+/// Schema version written into new envelopes
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Version assigned to messages published before envelopes existed
+pub const LEGACY_EVENT_VERSION: u32 = 0;
+
+/// What happened, with the data consumers need to react
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum DomainEventKind {
+    /// A note was created or updated
+    NoteUpdated(Note),
+    /// A note was permanently deleted (not just moved to the trash)
+    NoteDeleted { note_id: Uuid },
+    /// A tag was created or renamed
+    TagUpdated(Tag),
+    /// A user was deleted together with all of their data
+    UserDeleted,
+}
+
+impl DomainEventKind {
+    /// Broker subject the event is published on
+    pub fn subject(&self) -> &'static str {
+        match self {
+            Self::NoteUpdated(_) => "notes.updated",
+            Self::NoteDeleted { .. } => "notes.deleted",
+            Self::TagUpdated(_) => "tags.updated",
+            Self::UserDeleted => "users.deleted",
+        }
+    }
+}
+
+/// Versioned envelope around a [`DomainEventKind`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomainEvent {
+    /// Unique per event, for deduplication and tracing
+    pub id: Uuid,
+    pub version: u32,
+    pub occurred_at: DateTime<Utc>,
+    /// Owner of the affected data
+    pub user_id: Uuid,
+    #[serde(flatten)]
+    pub kind: DomainEventKind,
+}
+
+impl DomainEvent {
+    pub fn new(user_id: Uuid, kind: DomainEventKind) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            version: EVENT_SCHEMA_VERSION,
+            occurred_at: Utc::now(),
+            user_id,
+            kind,
+        }
+    }
+
+    pub fn note_updated(note: Note) -> Self {
+        Self::new(note.user_id, DomainEventKind::NoteUpdated(note))
+    }
+
+    pub fn note_deleted(user_id: Uuid, note_id: Uuid) -> Self {
+        Self::new(user_id, DomainEventKind::NoteDeleted { note_id })
+    }
+
+    pub fn tag_updated(tag: Tag) -> Self {
+        Self::new(tag.user_id, DomainEventKind::TagUpdated(tag))
+    }
+
+    pub fn user_deleted(user_id: Uuid) -> Self {
+        Self::new(user_id, DomainEventKind::UserDeleted)
+    }
+
+    /// Broker subjects carrying every known event kind
+    pub const SUBJECTS: &'static [&'static str] = &[
+        "notes.updated",
+        "notes.deleted",
+        "tags.updated",
+        "users.deleted",
+    ];
+
+    pub fn subject(&self) -> &'static str {
+        self.kind.subject()
+    }
+
+    pub fn encode(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(self)
+    }
+
+    /// Decode an envelope, also accepting the bare note JSON that was
+    /// published on `notes.updated` before envelopes were introduced
+    pub fn decode(bytes: &[u8]) -> serde_json::Result<Self> {
+        let error = match serde_json::from_slice::<Self>(bytes) {
+            Ok(event) => return Ok(event),
+            Err(e) => e,
+        };
+
+        match serde_json::from_slice::<Note>(bytes) {
+            Ok(note) => Ok(Self {
+                id: Uuid::new_v4(),
+                version: LEGACY_EVENT_VERSION,
+                occurred_at: note.updated_at,
+                user_id: note.user_id,
+                kind: DomainEventKind::NoteUpdated(note),
+            }),
+            Err(_) => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note() -> Note {
+        Note::new(Uuid::new_v4(), None, "content")
+    }
+
+    #[test]
+    fn test_envelope_round_trips() {
+        let event = DomainEvent::note_updated(note());
+        let json: serde_json::Value = serde_json::from_slice(&event.encode().unwrap()).unwrap();
+
+        assert_eq!(json["type"], "note_updated");
+        assert_eq!(json["version"], EVENT_SCHEMA_VERSION);
+        assert_eq!(
+            DomainEvent::decode(&event.encode().unwrap()).unwrap(),
+            event
+        );
+    }
+
+    #[test]
+    fn test_every_kind_is_on_a_subscribed_subject() {
+        let user_id = Uuid::new_v4();
+        let events = [
+            DomainEvent::note_updated(note()),
+            DomainEvent::note_deleted(user_id, Uuid::new_v4()),
+            DomainEvent::user_deleted(user_id),
+        ];
+
+        for event in events {
+            assert!(DomainEvent::SUBJECTS.contains(&event.subject()));
+            assert_eq!(
+                DomainEvent::decode(&event.encode().unwrap()).unwrap(),
+                event
+            );
+        }
+    }
+
+    #[test]
+    fn test_decodes_legacy_bare_note() {
+        let note = note();
+        let event = DomainEvent::decode(&serde_json::to_vec(&note).unwrap()).unwrap();
+
+        assert_eq!(event.version, LEGACY_EVENT_VERSION);
+        assert_eq!(event.user_id, note.user_id);
+        assert_eq!(event.kind, DomainEventKind::NoteUpdated(note));
+    }
+
+    #[test]
+    fn test_rejects_unknown_event_types() {
+        let json = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "version": 2,
+            "occurred_at": Utc::now(),
+            "user_id": Uuid::new_v4(),
+            "type": "note_exploded",
+            "data": {},
+        });
+
+        assert!(DomainEvent::decode(&serde_json::to_vec(&json).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Where notes were written
+//!
+//! Notes may carry a [`GeoPoint`]. Nearby searches first narrow candidates
+//! with a [`BoundingBox`], which storage can answer from a spatial index, and
+//! then keep the notes within the exact great-circle distance.
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::Note;
+use crate::value_objects::{Latitude, Longitude};
+
+/// Mean radius of the Earth in kilometres
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Search radius used when none is given, in kilometres
+pub const DEFAULT_NEARBY_RADIUS_KM: f64 = 10.0;
+
+/// Largest search radius, in kilometres
+pub const MAX_NEARBY_RADIUS_KM: f64 = 1000.0;
+
+/// A position on the Earth's surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub latitude: Latitude,
+    pub longitude: Longitude,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: Latitude, longitude: Longitude) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// Great-circle distance to `other` in kilometres (haversine formula)
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let lat1 = self.latitude.degrees().to_radians();
+        let lat2 = other.latitude.degrees().to_radians();
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude.degrees() - self.longitude.degrees()).to_radians();
+
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// A latitude/longitude rectangle in degrees.
+///
+/// `min_longitude > max_longitude` means the box crosses the antimeridian
+/// and covers both ends of the longitude range.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min_latitude: f64,
+    pub max_latitude: f64,
+    pub min_longitude: f64,
+    pub max_longitude: f64,
+}
+
+impl BoundingBox {
+    /// Smallest box containing every point within `radius_km` of `center`
+    pub fn around(center: &GeoPoint, radius_km: f64) -> Self {
+        let angle = radius_km / EARTH_RADIUS_KM;
+        let lat = center.latitude.degrees();
+        let lon = center.longitude.degrees();
+        let dlat = angle.to_degrees();
+        let (min_latitude, max_latitude) = (lat - dlat, lat + dlat);
+
+        // Circles reaching a pole span every longitude
+        let ratio = angle.sin() / lat.to_radians().cos();
+        if min_latitude <= -90.0 || max_latitude >= 90.0 || ratio >= 1.0 {
+            return Self {
+                min_latitude: min_latitude.max(-90.0),
+                max_latitude: max_latitude.min(90.0),
+                min_longitude: -180.0,
+                max_longitude: 180.0,
+            };
+        }
+
+        let dlon = ratio.asin().to_degrees();
+        let wrap = |l: f64| {
+            if l < -180.0 {
+                l + 360.0
+            } else if l > 180.0 {
+                l - 360.0
+            } else {
+                l
+            }
+        };
+        Self {
+            min_latitude,
+            max_latitude,
+            min_longitude: wrap(lon - dlon),
+            max_longitude: wrap(lon + dlon),
+        }
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min_longitude > self.max_longitude
+    }
+
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        let lat = point.latitude.degrees();
+        let lon = point.longitude.degrees();
+        let in_longitude = if self.crosses_antimeridian() {
+            lon >= self.min_longitude || lon <= self.max_longitude
+        } else {
+            (self.min_longitude..=self.max_longitude).contains(&lon)
+        };
+        (self.min_latitude..=self.max_latitude).contains(&lat) && in_longitude
+    }
+}
+
+/// A note found by a nearby search
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearbyNote {
+    pub note: Note,
+    pub distance_km: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(lat: f64, lon: f64) -> GeoPoint {
+        GeoPoint::new(Latitude::new(lat).unwrap(), Longitude::new(lon).unwrap())
+    }
+
+    #[test]
+    fn test_distance_between_cities() {
+        let lisbon = point(38.7223, -9.1393);
+        let madrid = point(40.4168, -3.7038);
+
+        let distance = lisbon.distance_km(&madrid);
+        assert!((distance - 503.0).abs() < 5.0, "got {}", distance);
+        assert_eq!(lisbon.distance_km(&lisbon), 0.0);
+    }
+
+    #[test]
+    fn test_bounding_box_contains_circle() {
+        let center = point(38.7223, -9.1393);
+        let bbox = BoundingBox::around(&center, 50.0);
+
+        assert!(bbox.contains(&center));
+        assert!(bbox.contains(&point(38.5244, -8.8882))); // Setúbal, ~30 km
+        assert!(!bbox.contains(&point(40.4168, -3.7038))); // Madrid
+    }
+
+    #[test]
+    fn test_bounding_box_wraps_at_antimeridian_and_poles() {
+        let fiji = BoundingBox::around(&point(-17.7, 179.9), 100.0);
+        assert!(fiji.crosses_antimeridian());
+        assert!(fiji.contains(&point(-17.7, -179.8)));
+        assert!(fiji.contains(&point(-17.7, 179.5)));
+        assert!(!fiji.contains(&point(-17.7, 0.0)));
+
+        let arctic = BoundingBox::around(&point(89.9, 0.0), 50.0);
+        assert_eq!(arctic.max_latitude, 90.0);
+        assert!(arctic.contains(&point(89.8, 180.0)));
+    }
+}
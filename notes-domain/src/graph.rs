@@ -0,0 +1,305 @@
+//! Note graph
+//!
+//! Combines explicit `[[wiki-links]]`, typed note relations and semantic
+//! similarity links into a weighted graph of a user's notes, for
+//! Obsidian-style graph views.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{Note, NoteLink};
+use crate::relations::{NoteRelation, RelationKind};
+use crate::wiki_links::{LinkTargets, extract_wiki_links};
+
+/// Hops from the root note when none are requested
+pub const DEFAULT_GRAPH_DEPTH: usize = 1;
+
+/// Maximum number of hops from the root note
+pub const MAX_GRAPH_DEPTH: usize = 5;
+
+/// Weight given to explicit wiki-links and relations
+const WIKI_LINK_WEIGHT: f32 = 1.0;
+
+/// Where an edge comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// `[[wiki-link]]` written in the source note (directed)
+    WikiLink,
+    /// Similarity link found by smart features (undirected)
+    Semantic,
+    /// The source note is the parent of the target (directed)
+    ParentOf,
+    /// The source note references the target (directed)
+    References,
+    /// The source note is blocked by the target (directed)
+    BlockedBy,
+}
+
+impl From<RelationKind> for EdgeKind {
+    fn from(kind: RelationKind) -> Self {
+        match kind {
+            RelationKind::ParentOf => Self::ParentOf,
+            RelationKind::References => Self::References,
+            RelationKind::BlockedBy => Self::BlockedBy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub id: Uuid,
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub source: Uuid,
+    pub target: Uuid,
+    pub weight: f32,
+    pub kind: EdgeKind,
+}
+
+/// Filters applied while building the graph
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GraphOptions {
+    /// Only keep notes within `depth` hops of this note
+    pub root: Option<Uuid>,
+    /// Hops from `root` (ignored without a root)
+    pub depth: usize,
+    /// Drop semantic edges scoring below this value
+    pub min_score: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoteGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl NoteGraph {
+    /// Build the graph of `notes`.
+    ///
+    /// Edges pointing outside `notes` are dropped, as are self-links.
+    /// Semantic links stored in both directions collapse into one edge
+    /// carrying the higher score.
+    pub fn build(
+        notes: &[Note],
+        semantic_links: &[NoteLink],
+        relations: &[NoteRelation],
+        options: GraphOptions,
+    ) -> Self {
+        let ids: HashSet<Uuid> = notes.iter().map(|n| n.id).collect();
+        let targets = LinkTargets::new(notes);
+
+        let mut wiki_edges: HashSet<(Uuid, Uuid)> = HashSet::new();
+        for note in notes {
+            for link in extract_wiki_links(&note.content) {
+                if let Some(target) = targets.resolve(&link.target).filter(|t| *t != note.id) {
+                    wiki_edges.insert((note.id, target));
+                }
+            }
+        }
+
+        let mut semantic_edges: HashMap<(Uuid, Uuid), f32> = HashMap::new();
+        for link in semantic_links {
+            let (a, b) = (link.source_note_id, link.target_note_id);
+            if a == b || !ids.contains(&a) || !ids.contains(&b) {
+                continue;
+            }
+            if options.min_score.is_some_and(|min| link.score < min) {
+                continue;
+            }
+            let score = semantic_edges.entry((a.min(b), a.max(b))).or_insert(0.0);
+            *score = score.max(link.score);
+        }
+
+        let mut edges: Vec<GraphEdge> = wiki_edges
+            .into_iter()
+            .map(|(source, target)| GraphEdge {
+                source,
+                target,
+                weight: WIKI_LINK_WEIGHT,
+                kind: EdgeKind::WikiLink,
+            })
+            .chain(
+                semantic_edges
+                    .into_iter()
+                    .map(|((source, target), weight)| GraphEdge {
+                        source,
+                        target,
+                        weight,
+                        kind: EdgeKind::Semantic,
+                    }),
+            )
+            .chain(
+                relations
+                    .iter()
+                    .filter(|r| ids.contains(&r.source_id) && ids.contains(&r.target_id))
+                    .map(|r| GraphEdge {
+                        source: r.source_id,
+                        target: r.target_id,
+                        weight: WIKI_LINK_WEIGHT,
+                        kind: EdgeKind::from(r.kind),
+                    }),
+            )
+            .collect();
+
+        let reachable = match options.root {
+            // Unknown root: nothing is reachable
+            Some(root) if !ids.contains(&root) => return Self::default(),
+            Some(root) => Some(reachable_within(
+                root,
+                options.depth.min(MAX_GRAPH_DEPTH),
+                &edges,
+            )),
+            None => None,
+        };
+
+        if let Some(ref reachable) = reachable {
+            edges.retain(|e| reachable.contains(&e.source) && reachable.contains(&e.target));
+        }
+        // Stable output regardless of hash iteration order
+        edges.sort_by_key(|e| (e.source, e.target, e.kind));
+
+        let nodes = notes
+            .iter()
+            .filter(|n| reachable.as_ref().is_none_or(|r| r.contains(&n.id)))
+            .map(|n| GraphNode {
+                id: n.id,
+                title: n.title_str().to_string(),
+                tags: n.tags.iter().map(|t| t.name_str().to_string()).collect(),
+            })
+            .collect();
+
+        Self { nodes, edges }
+    }
+}
+
+/// Notes within `depth` hops of `root`, treating every edge as undirected
+fn reachable_within(root: Uuid, depth: usize, edges: &[GraphEdge]) -> HashSet<Uuid> {
+    let mut neighbours: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for edge in edges {
+        neighbours.entry(edge.source).or_default().push(edge.target);
+        neighbours.entry(edge.target).or_default().push(edge.source);
+    }
+
+    let mut seen = HashSet::from([root]);
+    let mut queue = VecDeque::from([(root, 0)]);
+    while let Some((id, distance)) = queue.pop_front() {
+        if distance == depth {
+            continue;
+        }
+        for next in neighbours.get(&id).into_iter().flatten() {
+            if seen.insert(*next) {
+                queue.push_back((*next, distance + 1));
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_objects::NoteTitle;
+
+    fn note(title: &str, content: &str) -> Note {
+        Note::new(Uuid::nil(), NoteTitle::try_from(title).ok(), content)
+    }
+
+    #[test]
+    fn test_wiki_links_become_directed_edges() {
+        let a = note("A", "links to [[b]] and [[Missing]] and [[A]]");
+        let b = note("B", "");
+
+        let graph = NoteGraph::build(&[a.clone(), b.clone()], &[], &[], GraphOptions::default());
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(
+            graph.edges,
+            vec![GraphEdge {
+                source: a.id,
+                target: b.id,
+                weight: 1.0,
+                kind: EdgeKind::WikiLink,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_semantic_links_are_deduplicated_and_filtered_by_score() {
+        let a = note("A", "");
+        let b = note("B", "");
+        let c = note("C", "");
+        let links = vec![
+            NoteLink::new(a.id, b.id, 0.8),
+            NoteLink::new(b.id, a.id, 0.9),
+            NoteLink::new(a.id, c.id, 0.3),
+        ];
+        let options = GraphOptions {
+            min_score: Some(0.5),
+            ..Default::default()
+        };
+
+        let graph = NoteGraph::build(&[a, b, c], &links, &[], options);
+
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].kind, EdgeKind::Semantic);
+        assert_eq!(graph.edges[0].weight, 0.9);
+    }
+
+    #[test]
+    fn test_depth_limits_nodes_around_root() {
+        let a = note("A", "[[B]]");
+        let b = note("B", "[[C]]");
+        let c = note("C", "");
+        let d = note("D", "");
+        let options = GraphOptions {
+            root: Some(a.id),
+            depth: 1,
+            min_score: None,
+        };
+
+        let graph = NoteGraph::build(&[a.clone(), b.clone(), c, d], &[], &[], options);
+        let ids: HashSet<Uuid> = graph.nodes.iter().map(|n| n.id).collect();
+
+        assert_eq!(ids, HashSet::from([a.id, b.id]));
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_relations_become_typed_edges() {
+        let parent = note("Trip", "");
+        let child = note("Day 1", "");
+        let relations = vec![
+            NoteRelation::new(Uuid::nil(), parent.id, RelationKind::ParentOf, child.id),
+            NoteRelation::new(
+                Uuid::nil(),
+                child.id,
+                RelationKind::References,
+                Uuid::new_v4(),
+            ),
+        ];
+
+        let graph = NoteGraph::build(
+            &[parent.clone(), child.clone()],
+            &[],
+            &relations,
+            GraphOptions::default(),
+        );
+
+        assert_eq!(
+            graph.edges,
+            vec![GraphEdge {
+                source: parent.id,
+                target: child.id,
+                weight: 1.0,
+                kind: EdgeKind::ParentOf,
+            }]
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Housekeeping of the instance's data
+//!
+//! Over time stored data drifts apart: tag associations outlive their notes
+//! when foreign keys were off, the full-text index misses writes made
+//! around its triggers, vectors outlive deleted notes, notes are saved while
+//! the worker is down and never embedded, expired sessions pile up and jobs
+//! interrupted by a crash stay running. The housekeeping report counts each
+//! of these, and running housekeeping fixes them all in one go.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::errors::DomainResult;
+use crate::ports::{MessageBroker, VectorStore};
+use crate::repositories::{HousekeepingRepository, NoteRepository, UserRepository};
+
+/// Running jobs that have not saved progress for this long are stale.
+/// Jobs save progress every few seconds at most while they run.
+pub const STALE_JOB_MINUTES: i64 = 60;
+
+/// Error recorded on the stale jobs housekeeping stops
+pub const STALE_JOB_ERROR: &str = "The job stopped making progress and was stopped";
+
+/// Leftovers found in the instance's data, or fixed by running housekeeping
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HousekeepingReport {
+    /// Tag associations of notes or tags that no longer exist
+    pub orphaned_note_tags: u64,
+    /// Notes and versions missing from the full-text index, plus index
+    /// entries of ones that no longer exist
+    pub fts_drift: u64,
+    /// Vectors of notes that no longer exist; `None` without a vector store
+    pub orphaned_vectors: Option<u64>,
+    /// Notes outside the trash without a vector, of users who have smart
+    /// features on; when fixing, the notes queued for embedding. `None`
+    /// without a vector store.
+    pub notes_without_embeddings: Option<u64>,
+    pub expired_sessions: u64,
+    /// Running jobs without progress for [`STALE_JOB_MINUTES`]
+    pub stale_jobs: u64,
+}
+
+/// A stored note, as far as housekeeping compares notes to vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoredNote {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub is_trashed: bool,
+}
+
+/// Vectors and notes that do not match up
+struct VectorDrift {
+    /// Vectors of notes that no longer exist
+    orphaned_vectors: Vec<Uuid>,
+    /// Notes that should have a vector but have none
+    unembedded_notes: Vec<Uuid>,
+}
+
+/// Service finding leftovers in the instance's data and fixing them
+pub struct HousekeepingService {
+    repo: Arc<dyn HousekeepingRepository>,
+    note_repo: Arc<dyn NoteRepository>,
+    user_repo: Arc<dyn UserRepository>,
+    vector_store: Option<Arc<dyn VectorStore>>,
+    message_broker: Option<Arc<dyn MessageBroker>>,
+}
+
+impl HousekeepingService {
+    pub fn new(
+        repo: Arc<dyn HousekeepingRepository>,
+        note_repo: Arc<dyn NoteRepository>,
+        user_repo: Arc<dyn UserRepository>,
+    ) -> Self {
+        Self {
+            repo,
+            note_repo,
+            user_repo,
+            vector_store: None,
+            message_broker: None,
+        }
+    }
+
+    /// Builder method to compare notes with the vectors of smart features
+    pub fn with_vector_store(mut self, vector_store: Arc<dyn VectorStore>) -> Self {
+        self.vector_store = Some(vector_store);
+        self
+    }
+
+    /// Builder method to queue notes without embeddings for the worker,
+    /// which embeds notes as their update events arrive
+    pub fn with_message_broker(mut self, broker: Arc<dyn MessageBroker>) -> Self {
+        self.message_broker = Some(broker);
+        self
+    }
+
+    /// The leftovers running housekeeping would fix
+    pub async fn report(&self) -> DomainResult<HousekeepingReport> {
+        let now = Utc::now();
+        let drift = self.vector_drift().await?;
+
+        Ok(HousekeepingReport {
+            orphaned_note_tags: self.repo.count_orphaned_note_tags().await?,
+            fts_drift: self.repo.count_fts_drift().await?,
+            orphaned_vectors: drift.as_ref().map(|d| d.orphaned_vectors.len() as u64),
+            notes_without_embeddings: drift.as_ref().map(|d| d.unembedded_notes.len() as u64),
+            expired_sessions: self.repo.count_expired_sessions(now).await?,
+            stale_jobs: self.repo.count_stale_jobs(stale_job_cutoff(now)).await?,
+        })
+    }
+
+    /// Fix every kind of leftover, returning how many of each were fixed.
+    ///
+    /// Notes without embeddings are only queued; they are embedded once the
+    /// worker gets to them, and not at all without a message broker.
+    pub async fn run(&self) -> DomainResult<HousekeepingReport> {
+        let now = Utc::now();
+
+        let orphaned_note_tags = self.repo.delete_orphaned_note_tags().await?;
+        let fts_drift = self.repo.count_fts_drift().await?;
+        if fts_drift > 0 {
+            self.repo.rebuild_fts().await?;
+        }
+
+        let (orphaned_vectors, notes_without_embeddings) =
+            match (self.vector_drift().await?, &self.vector_store) {
+                (Some(drift), Some(vector_store)) => {
+                    for id in &drift.orphaned_vectors {
+                        vector_store.delete(*id).await?;
+                    }
+                    let queued = self.queue_for_embedding(&drift.unembedded_notes).await?;
+                    (Some(drift.orphaned_vectors.len() as u64), Some(queued))
+                }
+                _ => (None, None),
+            };
+
+        let report = HousekeepingReport {
+            orphaned_note_tags,
+            fts_drift,
+            orphaned_vectors,
+            notes_without_embeddings,
+            expired_sessions: self.repo.delete_expired_sessions(now).await?,
+            stale_jobs: self
+                .repo
+                .fail_stale_jobs(stale_job_cutoff(now), STALE_JOB_ERROR)
+                .await?,
+        };
+        tracing::info!(?report, "Housekeeping finished");
+        Ok(report)
+    }
+
+    /// `None` without a vector store to compare notes with, or while it is
+    /// down, so the other leftovers can still be fixed
+    async fn vector_drift(&self) -> DomainResult<Option<VectorDrift>> {
+        let Some(vector_store) = &self.vector_store else {
+            return Ok(None);
+        };
+        let vectors: HashSet<Uuid> = match vector_store.ids().await {
+            Ok(ids) => ids.into_iter().collect(),
+            Err(e) => {
+                tracing::warn!("Failed to list vectors for housekeeping: {}", e);
+                return Ok(None);
+            }
+        };
+        let notes = self.repo.find_stored_notes().await?;
+        let note_ids: HashSet<Uuid> = notes.iter().map(|n| n.id).collect();
+
+        // Like the worker, notes of users who turned smart features off are
+        // not embedded
+        let mut smart_features = HashMap::new();
+        let mut unembedded_notes = Vec::new();
+        for note in notes
+            .iter()
+            .filter(|n| !n.is_trashed && !vectors.contains(&n.id))
+        {
+            let enabled = match smart_features.get(&note.user_id) {
+                Some(enabled) => *enabled,
+                None => {
+                    let settings = self.user_repo.find_settings(note.user_id).await?;
+                    smart_features.insert(note.user_id, settings.smart_features_enabled);
+                    settings.smart_features_enabled
+                }
+            };
+            if enabled {
+                unembedded_notes.push(note.id);
+            }
+        }
+
+        Ok(Some(VectorDrift {
+            orphaned_vectors: vectors
+                .into_iter()
+                .filter(|id| !note_ids.contains(id))
+                .collect(),
+            unembedded_notes,
+        }))
+    }
+
+    /// Publish the notes again for the worker to embed, returning how many
+    /// were published
+    async fn queue_for_embedding(&self, ids: &[Uuid]) -> DomainResult<u64> {
+        let Some(broker) = &self.message_broker else {
+            return Ok(0);
+        };
+        let mut queued = 0;
+        for id in ids {
+            // The note may have been deleted in the meantime
+            let Some(note) = self.note_repo.find_by_id(*id).await? else {
+                continue;
+            };
+            broker.publish_note_updated(&note).await?;
+            queued += 1;
+        }
+        Ok(queued)
+    }
+}
+
+/// Running jobs last saved before this are stale
+fn stale_job_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - chrono::Duration::minutes(STALE_JOB_MINUTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{Note, UserSettings};
+    use crate::events::DomainEventKind;
+    use crate::housekeeping::StoredNote;
+    use crate::ports::VectorPayload;
+    use crate::repositories::tests::MockNoteRepository;
+    use crate::services::tests::smart_note_service_tests::MockVectorStore;
+    use crate::services::tests::*;
+    use std::sync::Mutex;
+
+    /// Reports fixed counts of database leftovers and clears them when fixed
+    #[derive(Default)]
+    struct MockHousekeepingRepository {
+        notes: Vec<StoredNote>,
+        orphaned_note_tags: Mutex<u64>,
+        fts_drift: Mutex<u64>,
+        expired_sessions: Mutex<u64>,
+        stale_jobs: Mutex<u64>,
+    }
+
+    #[async_trait::async_trait]
+    impl HousekeepingRepository for MockHousekeepingRepository {
+        async fn count_orphaned_note_tags(&self) -> DomainResult<u64> {
+            Ok(*self.orphaned_note_tags.lock().unwrap())
+        }
+
+        async fn delete_orphaned_note_tags(&self) -> DomainResult<u64> {
+            Ok(std::mem::take(
+                &mut *self.orphaned_note_tags.lock().unwrap(),
+            ))
+        }
+
+        async fn count_fts_drift(&self) -> DomainResult<u64> {
+            Ok(*self.fts_drift.lock().unwrap())
+        }
+
+        async fn rebuild_fts(&self) -> DomainResult<()> {
+            *self.fts_drift.lock().unwrap() = 0;
+            Ok(())
+        }
+
+        async fn find_stored_notes(&self) -> DomainResult<Vec<StoredNote>> {
+            Ok(self.notes.clone())
+        }
+
+        async fn count_expired_sessions(&self, _now: DateTime<Utc>) -> DomainResult<u64> {
+            Ok(*self.expired_sessions.lock().unwrap())
+        }
+
+        async fn delete_expired_sessions(&self, _now: DateTime<Utc>) -> DomainResult<u64> {
+            Ok(std::mem::take(&mut *self.expired_sessions.lock().unwrap()))
+        }
+
+        async fn count_stale_jobs(&self, _before: DateTime<Utc>) -> DomainResult<u64> {
+            Ok(*self.stale_jobs.lock().unwrap())
+        }
+
+        async fn fail_stale_jobs(&self, _before: DateTime<Utc>, error: &str) -> DomainResult<u64> {
+            assert_eq!(error, STALE_JOB_ERROR);
+            Ok(std::mem::take(&mut *self.stale_jobs.lock().unwrap()))
+        }
+    }
+
+    fn stored(note: &Note, is_trashed: bool) -> StoredNote {
+        StoredNote {
+            id: note.id,
+            user_id: note.user_id,
+            is_trashed,
+        }
+    }
+
+    /// A service over an embedded note, an unembedded one, a trashed
+    /// one, a note of a user without smart features and the vector of a
+    /// deleted note
+    async fn setup() -> (
+        HousekeepingService,
+        Arc<MockVectorStore>,
+        Arc<MockMessageBroker>,
+        Note,
+        Uuid,
+    ) {
+        let user_id = Uuid::new_v4();
+        let embedded = Note::new(user_id, None, "embedded");
+        let unembedded = Note::new(user_id, None, "unembedded");
+        let trashed = Note::new(user_id, None, "trashed");
+        let opted_out = Note::new(Uuid::new_v4(), None, "private");
+
+        let note_repo = Arc::new(MockNoteRepository::new());
+        for note in [&embedded, &unembedded, &trashed, &opted_out] {
+            note_repo.save(note).await.unwrap();
+        }
+        let user_repo = Arc::new(MockUserRepository::new());
+        let settings = UserSettings {
+            smart_features_enabled: false,
+            ..Default::default()
+        };
+        user_repo
+            .save_settings(opted_out.user_id, &settings)
+            .await
+            .unwrap();
+
+        let deleted_id = Uuid::new_v4();
+        let vectors = Arc::new(MockVectorStore::default());
+        for id in [embedded.id, deleted_id] {
+            let payload = VectorPayload::for_note(&embedded, "mock-v1");
+            vectors.upsert(id, &[1.0], &payload).await.unwrap();
+        }
+
+        let repo = MockHousekeepingRepository {
+            notes: vec![
+                stored(&embedded, false),
+                stored(&unembedded, false),
+                stored(&trashed, true),
+                stored(&opted_out, false),
+            ],
+            orphaned_note_tags: Mutex::new(2),
+            fts_drift: Mutex::new(3),
+            expired_sessions: Mutex::new(4),
+            stale_jobs: Mutex::new(1),
+        };
+        let broker = Arc::new(MockMessageBroker::default());
+        let service = HousekeepingService::new(Arc::new(repo), note_repo, user_repo)
+            .with_vector_store(vectors.clone())
+            .with_message_broker(broker.clone());
+
+        (service, vectors, broker, unembedded, deleted_id)
+    }
+
+    /// What [`setup`] leaves to fix
+    fn leftovers() -> HousekeepingReport {
+        HousekeepingReport {
+            orphaned_note_tags: 2,
+            fts_drift: 3,
+            orphaned_vectors: Some(1),
+            notes_without_embeddings: Some(1),
+            expired_sessions: 4,
+            stale_jobs: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_skips_trashed_notes_and_users_without_smart_features() {
+        let (service, _, _, _, _) = setup().await;
+
+        assert_eq!(service.report().await.unwrap(), leftovers());
+    }
+
+    #[tokio::test]
+    async fn test_run_fixes_leftovers_and_queues_unembedded_notes() {
+        let (service, vectors, broker, unembedded, deleted_id) = setup().await;
+
+        let fixed = service.run().await.unwrap();
+
+        assert_eq!(fixed, leftovers());
+        assert!(!vectors.vectors.lock().unwrap().contains_key(&deleted_id));
+        let events = broker.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0].kind,
+            DomainEventKind::NoteUpdated(note) if note.id == unembedded.id
+        ));
+        drop(events);
+
+        let remaining = service.report().await.unwrap();
+        assert_eq!(
+            remaining,
+            HousekeepingReport {
+                orphaned_vectors: Some(0),
+                // Embedded once the worker gets to it
+                notes_without_embeddings: Some(1),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vectors_are_skipped_without_a_vector_store() {
+        let service = HousekeepingService::new(
+            Arc::new(MockHousekeepingRepository::default()),
+            Arc::new(MockNoteRepository::new()),
+            Arc::new(MockUserRepository::new()),
+        );
+
+        let report = service.run().await.unwrap();
+        assert_eq!(report.orphaned_vectors, None);
+        assert_eq!(report.notes_without_embeddings, None);
+    }
+}
@@ -0,0 +1,333 @@
+//! External images loaded through the instance
+//!
+//! Images in notes usually live on other hosts, which learn the address of
+//! everyone reading the note and are blocked by strict Content Security
+//! Policies. Instances with an [`ImageFetcher`]
+//! serve them from their own origin instead: rendered notes point remote
+//! images at the image proxy, with a signature so the proxy only loads URLs
+//! the instance handed out and cannot be used to fetch anything else.
+//! Raw HTML `<img>` tags need no rewriting: rendered outputs show raw HTML
+//! in notes as text, so they never load.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::entities::Note;
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::{ImageFetcher, Signer};
+
+/// Largest image the proxy loads, in bytes
+pub const MAX_PROXIED_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Images kept for readers loading them again, in bytes
+pub const IMAGE_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// How long browsers may keep a proxied image, in seconds
+pub const IMAGE_CACHE_SECONDS: u64 = 24 * 60 * 60;
+
+/// Longest image URL the proxy loads
+pub const MAX_PROXIED_URL_LENGTH: usize = 2048;
+
+/// Image types the proxy serves. SVG can carry scripts and is left out.
+pub const PROXIED_IMAGE_TYPES: &[&str] = &[
+    "image/avif",
+    "image/gif",
+    "image/jpeg",
+    "image/png",
+    "image/webp",
+];
+
+/// An image loaded for a reader
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchedImage {
+    pub bytes: Vec<u8>,
+    /// One of [`PROXIED_IMAGE_TYPES`]
+    pub content_type: String,
+}
+
+/// Whether the proxy may load `url`: an `http(s)` URL of bounded length
+pub fn is_proxiable_url(url: &str) -> bool {
+    url.len() <= MAX_PROXIED_URL_LENGTH
+        && (url.starts_with("http://") || url.starts_with("https://"))
+        && !url.contains(char::is_whitespace)
+}
+
+/// Replace the URL of every remote Markdown image (`![alt](https://…)`)
+/// with the output of `replace`, keeping it where `replace` returns `None`.
+///
+/// Images in fenced code blocks are code, not images, and stay as typed, as
+/// do images missing their closing parenthesis.
+pub fn replace_image_urls(
+    content: &str,
+    mut replace: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut in_code_block = false;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            output.push_str(line);
+            continue;
+        }
+        if in_code_block {
+            output.push_str(line);
+            continue;
+        }
+        replace_in_line(line, &mut replace, &mut output);
+    }
+
+    output
+}
+
+fn replace_in_line(
+    line: &str,
+    replace: &mut impl FnMut(&str) -> Option<String>,
+    output: &mut String,
+) {
+    let mut rest = line;
+    while let Some(start) = rest.find("![") {
+        let after = &rest[start + 2..];
+        let Some(label_end) = after.find("](").filter(|&end| !after[..end].contains(']')) else {
+            output.push_str(&rest[..start + 2]);
+            rest = after;
+            continue;
+        };
+
+        let target_start = start + 2 + label_end + 2;
+        let target = &rest[target_start..];
+        // Unclosed, it is not an image
+        if !target.contains(')') {
+            break;
+        }
+        // The URL ends at the optional title, as in `![](url "title")`
+        let url_end = target
+            .find(|c: char| c.is_whitespace() || c == ')')
+            .unwrap_or(target.len());
+        let url = &target[..url_end];
+
+        output.push_str(&rest[..target_start]);
+        match Some(url)
+            .filter(|u| is_proxiable_url(u))
+            .and_then(&mut *replace)
+        {
+            Some(replaced) => output.push_str(&replaced),
+            None => output.push_str(url),
+        }
+        rest = &target[url_end..];
+    }
+    output.push_str(rest);
+}
+
+/// Service loading external images of notes on behalf of their readers
+pub struct ImageProxyService {
+    fetcher: Arc<dyn ImageFetcher>,
+    signer: Arc<dyn Signer>,
+    /// Images by URL, emptied when full
+    images: Mutex<HashMap<String, Arc<FetchedImage>>>,
+}
+
+impl ImageProxyService {
+    pub fn new(fetcher: Arc<dyn ImageFetcher>, signer: Arc<dyn Signer>) -> Self {
+        Self {
+            fetcher,
+            signer,
+            images: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Where readers load the image at `url` through the proxy served at
+    /// `endpoint`, such as `/api/v1/proxy/image`; `None` for URLs the proxy
+    /// does not load
+    pub fn proxy_url(&self, endpoint: &str, url: &str) -> Option<String> {
+        if !is_proxiable_url(url) {
+            return None;
+        }
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("url", url)
+            .append_pair("sig", &self.signer.sign(url))
+            .finish();
+        Some(format!("{}?{}", endpoint, query))
+    }
+
+    /// `notes` with their remote images loaded through `endpoint`, for
+    /// rendering
+    pub fn proxy_images(&self, notes: Vec<Note>, endpoint: &str) -> Vec<Note> {
+        notes
+            .into_iter()
+            .map(|mut note| {
+                note.content =
+                    replace_image_urls(&note.content, |url| self.proxy_url(endpoint, url));
+                note
+            })
+            .collect()
+    }
+
+    /// The image at `url`, or `None` if it cannot be loaded. Only URLs
+    /// signed by [`ImageProxyService::proxy_url`] are loaded.
+    pub async fn fetch(
+        &self,
+        url: &str,
+        signature: &str,
+    ) -> DomainResult<Option<Arc<FetchedImage>>> {
+        if !self.signer.verify(url, signature) {
+            return Err(DomainError::forbidden(
+                "The image URL was not signed by this instance",
+            ));
+        }
+        if !is_proxiable_url(url) {
+            return Err(DomainError::validation("url must be an http or https URL"));
+        }
+
+        let cached = self.images.lock().unwrap().get(url).cloned();
+        if let Some(image) = cached {
+            return Ok(Some(image));
+        }
+
+        let Some(image) = self
+            .fetcher
+            .fetch_image(url, MAX_PROXIED_IMAGE_BYTES)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let image = Arc::new(image);
+        let mut cache = self.images.lock().unwrap();
+        let cached_bytes: usize = cache.values().map(|i| i.bytes.len()).sum();
+        if cached_bytes + image.bytes.len() > IMAGE_CACHE_BYTES {
+            cache.clear();
+        }
+        cache.insert(url.to_string(), image.clone());
+        Ok(Some(image))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxied(content: &str) -> String {
+        replace_image_urls(content, |url| Some(format!("/proxy?url={}", url)))
+    }
+
+    #[test]
+    fn test_remote_images_are_replaced() {
+        assert_eq!(
+            proxied(
+                "A ![cat](https://cats.example/c.png \"Cat\") and ![](http://x.example/y.gif)."
+            ),
+            "A ![cat](/proxy?url=https://cats.example/c.png \"Cat\") and ![](/proxy?url=http://x.example/y.gif)."
+        );
+    }
+
+    #[test]
+    fn test_local_images_links_and_code_are_kept() {
+        let content = "![local](/files/a.png) [link](https://a.example) ![data](data:image/png;base64,AA)\n\
+                       ```\n![code](https://a.example/c.png)\n```\n\
+                       ![broken](https://a.example";
+        assert_eq!(proxied(content), content);
+    }
+
+    #[test]
+    fn test_only_http_urls_of_bounded_length_are_proxiable() {
+        assert!(is_proxiable_url("https://a.example/a.png"));
+        assert!(!is_proxiable_url("file:///etc/passwd"));
+        assert!(!is_proxiable_url("https://a.example/a b.png"));
+        assert!(!is_proxiable_url(&format!(
+            "https://a.example/{}",
+            "a".repeat(MAX_PROXIED_URL_LENGTH)
+        )));
+    }
+
+    mod image_proxy_service_tests {
+        use super::*;
+        use uuid::Uuid;
+
+        /// Signs values by reversing them
+        struct ReversingSigner;
+
+        impl Signer for ReversingSigner {
+            fn sign(&self, value: &str) -> String {
+                value.chars().rev().collect()
+            }
+
+            fn verify(&self, value: &str, signature: &str) -> bool {
+                self.sign(value) == signature
+            }
+        }
+
+        /// Serves a one-byte PNG for every URL but those on `down.example`
+        #[derive(Default)]
+        struct MockImageFetcher {
+            fetches: Mutex<Vec<String>>,
+        }
+
+        #[async_trait::async_trait]
+        impl ImageFetcher for MockImageFetcher {
+            async fn fetch_image(
+                &self,
+                url: &str,
+                _max_bytes: usize,
+            ) -> DomainResult<Option<FetchedImage>> {
+                self.fetches.lock().unwrap().push(url.to_string());
+                if url.contains("down.example") {
+                    return Ok(None);
+                }
+                Ok(Some(FetchedImage {
+                    bytes: vec![0x89],
+                    content_type: "image/png".to_string(),
+                }))
+            }
+        }
+
+        fn service(fetcher: Arc<MockImageFetcher>) -> ImageProxyService {
+            ImageProxyService::new(fetcher, Arc::new(ReversingSigner))
+        }
+
+        #[test]
+        fn test_remote_images_point_at_the_proxy() {
+            let service = service(Arc::new(MockImageFetcher::default()));
+            let note = Note::new(
+                Uuid::new_v4(),
+                None,
+                "![a](https://a.example/a.png) ![b](/local.png)",
+            );
+
+            let proxied = service.proxy_images(vec![note], "/proxy");
+            assert_eq!(
+                proxied[0].content,
+                "![a](/proxy?url=https%3A%2F%2Fa.example%2Fa.png&sig=gnp.a%2Felpmaxe.a%2F%2F%3Asptth) ![b](/local.png)"
+            );
+            assert_eq!(service.proxy_url("/proxy", "ftp://a.example/a.png"), None);
+        }
+
+        #[tokio::test]
+        async fn test_signed_images_are_fetched_once() {
+            let fetcher = Arc::new(MockImageFetcher::default());
+            let service = service(fetcher.clone());
+            let url = "https://a.example/a.png";
+            let signature = ReversingSigner.sign(url);
+
+            let image = service.fetch(url, &signature).await.unwrap().unwrap();
+            assert_eq!(image.content_type, "image/png");
+            assert!(service.fetch(url, &signature).await.unwrap().is_some());
+
+            let down = "https://down.example/a.png";
+            let down_signature = ReversingSigner.sign(down);
+            assert!(
+                service
+                    .fetch(down, &down_signature)
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+
+            assert!(matches!(
+                service.fetch(url, "forged").await,
+                Err(DomainError::Forbidden(_))
+            ));
+            assert_eq!(*fetcher.fetches.lock().unwrap(), vec![url, down]);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Administrators acting as another user
+//!
+//! To debug a user's problem, such as missing notes or broken search, an
+//! administrator starts an impersonation with a reason. While it lasts,
+//! requests that name the user in the `X-Impersonate-User` header are
+//! served as that user. Impersonations expire on their own and are kept
+//! afterwards as an audit trail of who looked at whose account and why.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{DomainError, DomainResult};
+use crate::repositories::{ImpersonationRepository, UserRepository};
+
+/// How long an impersonation lasts when no duration is given
+pub const DEFAULT_IMPERSONATION_MINUTES: i64 = 30;
+
+/// Longest an impersonation may last
+pub const MAX_IMPERSONATION_MINUTES: i64 = 240;
+
+/// Maximum length of the reason in characters
+pub const MAX_IMPERSONATION_REASON_LENGTH: usize = 500;
+
+/// Impersonations returned per audit page when no limit is given
+pub const DEFAULT_IMPERSONATION_LIMIT: usize = 50;
+
+/// Maximum number of impersonations returned per audit page
+pub const MAX_IMPERSONATION_LIMIT: usize = 200;
+
+/// An administrator's time-limited permission to act as a user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Impersonation {
+    pub id: Uuid,
+    pub admin_id: Uuid,
+    /// The impersonated user
+    pub user_id: Uuid,
+    /// Why the administrator needs access
+    pub reason: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// When it was ended before expiring
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl Impersonation {
+    /// Start impersonating `user_id` for `minutes` (the default when `None`)
+    pub fn new(
+        admin_id: Uuid,
+        user_id: Uuid,
+        reason: impl Into<String>,
+        minutes: Option<i64>,
+    ) -> DomainResult<Self> {
+        if admin_id == user_id {
+            return Err(DomainError::validation(
+                "Administrators cannot impersonate themselves",
+            ));
+        }
+        let reason = reason.into().trim().to_string();
+        if reason.is_empty() {
+            return Err(DomainError::validation(
+                "A reason is required to impersonate a user",
+            ));
+        }
+        if reason.chars().count() > MAX_IMPERSONATION_REASON_LENGTH {
+            return Err(DomainError::validation(format!(
+                "Reason cannot exceed {} characters",
+                MAX_IMPERSONATION_REASON_LENGTH
+            )));
+        }
+        let minutes = minutes.unwrap_or(DEFAULT_IMPERSONATION_MINUTES);
+        if !(1..=MAX_IMPERSONATION_MINUTES).contains(&minutes) {
+            return Err(DomainError::validation(format!(
+                "Impersonation must last between 1 and {} minutes",
+                MAX_IMPERSONATION_MINUTES
+            )));
+        }
+
+        let now = Utc::now();
+        Ok(Self {
+            id: Uuid::new_v4(),
+            admin_id,
+            user_id,
+            reason,
+            started_at: now,
+            expires_at: now + Duration::minutes(minutes),
+            ended_at: None,
+        })
+    }
+
+    /// Whether requests may still be served as the user at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.ended_at.is_none() && now < self.expires_at
+    }
+}
+
+/// Service for administrators impersonating users
+pub struct ImpersonationService {
+    impersonation_repo: Arc<dyn ImpersonationRepository>,
+    user_repo: Arc<dyn UserRepository>,
+}
+
+impl ImpersonationService {
+    pub fn new(
+        impersonation_repo: Arc<dyn ImpersonationRepository>,
+        user_repo: Arc<dyn UserRepository>,
+    ) -> Self {
+        Self {
+            impersonation_repo,
+            user_repo,
+        }
+    }
+
+    /// Let `admin_id` act as `user_id` for `minutes` (the default when `None`)
+    pub async fn start(
+        &self,
+        admin_id: Uuid,
+        user_id: Uuid,
+        reason: &str,
+        minutes: Option<i64>,
+    ) -> DomainResult<Impersonation> {
+        let impersonation = Impersonation::new(admin_id, user_id, reason, minutes)?;
+        self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or(DomainError::UserNotFound(user_id))?;
+
+        self.impersonation_repo.save(&impersonation).await?;
+        Ok(impersonation)
+    }
+
+    pub async fn get(&self, id: Uuid) -> DomainResult<Impersonation> {
+        self.impersonation_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::ImpersonationNotFound(id))
+    }
+
+    /// End an impersonation before it expires; ending it twice is a no-op
+    pub async fn end(&self, id: Uuid) -> DomainResult<Impersonation> {
+        let mut impersonation = self.get(id).await?;
+        let now = Utc::now();
+        if impersonation.is_active(now) {
+            impersonation.ended_at = Some(now);
+            self.impersonation_repo.save(&impersonation).await?;
+        }
+        Ok(impersonation)
+    }
+
+    /// The impersonation that lets `admin_id` act as `user_id` right now
+    pub async fn active(&self, admin_id: Uuid, user_id: Uuid) -> DomainResult<Impersonation> {
+        self.impersonation_repo
+            .find_active(admin_id, user_id, Utc::now())
+            .await?
+            .ok_or_else(|| DomainError::forbidden("No active impersonation of this user"))
+    }
+
+    /// The latest impersonations, for auditing; `limit` is capped
+    pub async fn list_recent(&self, limit: Option<usize>) -> DomainResult<Vec<Impersonation>> {
+        let limit = limit
+            .unwrap_or(DEFAULT_IMPERSONATION_LIMIT)
+            .clamp(1, MAX_IMPERSONATION_LIMIT);
+        self.impersonation_repo.find_recent(limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impersonation_expires_or_ends() {
+        let now = Utc::now();
+        let mut impersonation =
+            Impersonation::new(Uuid::new_v4(), Uuid::new_v4(), "Missing notes", Some(10)).unwrap();
+        assert!(impersonation.is_active(now));
+        assert!(!impersonation.is_active(now + Duration::minutes(11)));
+
+        impersonation.ended_at = Some(now);
+        assert!(!impersonation.is_active(now));
+    }
+
+    #[test]
+    fn test_new_validates_reason_and_duration() {
+        let (admin, user) = (Uuid::new_v4(), Uuid::new_v4());
+        assert!(Impersonation::new(admin, user, "  ", None).is_err());
+        assert!(Impersonation::new(admin, admin, "Debugging", None).is_err());
+        assert!(Impersonation::new(admin, user, "Debugging", Some(0)).is_err());
+        assert!(
+            Impersonation::new(
+                admin,
+                user,
+                "Debugging",
+                Some(MAX_IMPERSONATION_MINUTES + 1)
+            )
+            .is_err()
+        );
+
+        let impersonation = Impersonation::new(admin, user, " Broken search ", None).unwrap();
+        assert_eq!(impersonation.reason, "Broken search");
+        assert_eq!(
+            impersonation.expires_at - impersonation.started_at,
+            Duration::minutes(DEFAULT_IMPERSONATION_MINUTES)
+        );
+    }
+
+    mod impersonation_service_tests {
+        use super::*;
+        use crate::entities::User;
+        use crate::services::tests::*;
+        use crate::value_objects::Email;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockImpersonationRepository {
+            impersonations: Mutex<HashMap<Uuid, Impersonation>>,
+        }
+
+        #[async_trait::async_trait]
+        impl ImpersonationRepository for MockImpersonationRepository {
+            async fn save(&self, impersonation: &Impersonation) -> DomainResult<()> {
+                self.impersonations
+                    .lock()
+                    .unwrap()
+                    .insert(impersonation.id, impersonation.clone());
+                Ok(())
+            }
+
+            async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Impersonation>> {
+                Ok(self.impersonations.lock().unwrap().get(&id).cloned())
+            }
+
+            async fn find_active(
+                &self,
+                admin_id: Uuid,
+                user_id: Uuid,
+                now: DateTime<Utc>,
+            ) -> DomainResult<Option<Impersonation>> {
+                Ok(self
+                    .impersonations
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .filter(|i| i.admin_id == admin_id && i.user_id == user_id)
+                    .find(|i| i.is_active(now))
+                    .cloned())
+            }
+
+            async fn find_recent(&self, limit: usize) -> DomainResult<Vec<Impersonation>> {
+                let mut impersonations: Vec<Impersonation> = self
+                    .impersonations
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .cloned()
+                    .collect();
+                impersonations.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+                impersonations.truncate(limit);
+                Ok(impersonations)
+            }
+        }
+
+        #[tokio::test]
+        async fn test_impersonation_is_limited_to_its_admin_and_lifetime() {
+            let user_repo = Arc::new(MockUserRepository::new());
+            let user = User::new("test|user", Email::try_from("user@example.com").unwrap());
+            user_repo.save(&user).await.unwrap();
+            let service = ImpersonationService::new(
+                Arc::new(MockImpersonationRepository::default()),
+                user_repo,
+            );
+            let (admin, other_admin) = (Uuid::new_v4(), Uuid::new_v4());
+
+            let impersonation = service
+                .start(admin, user.id, "Search returns nothing", Some(15))
+                .await
+                .unwrap();
+            assert_eq!(
+                service.active(admin, user.id).await.unwrap().id,
+                impersonation.id
+            );
+            assert!(matches!(
+                service.active(other_admin, user.id).await,
+                Err(DomainError::Forbidden(_))
+            ));
+            assert!(matches!(
+                service.start(admin, Uuid::new_v4(), "Typo", None).await,
+                Err(DomainError::UserNotFound(_))
+            ));
+
+            let ended = service.end(impersonation.id).await.unwrap();
+            assert!(ended.ended_at.is_some());
+            assert!(service.active(admin, user.id).await.is_err());
+            // Ended impersonations stay in the audit trail
+            assert_eq!(service.list_recent(None).await.unwrap(), vec![ended]);
+        }
+    }
+}
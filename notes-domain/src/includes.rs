@@ -0,0 +1,160 @@
+//! Notes included in other notes
+//!
+//! `{{include:Snippet}}` in a note's content stands for the content of the
+//! note titled `Snippet` (case-insensitive) or with that ID, resolved like a
+//! `[[wiki-link]]`. Rendered outputs such as the print view, PDF export and
+//! the static site replace the directive with that content, so text kept in
+//! one note can be reused by many. Included notes may include others, up to
+//! [`MAX_INCLUDE_DEPTH`] levels deep.
+//!
+//! Directives naming no note, a note already being included (a cycle) or
+//! nested too deeply are left as typed. Stored notes and raw exports always
+//! keep their directives.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::entities::Note;
+use crate::wiki_links::LinkTargets;
+
+/// Levels of notes included in included notes
+pub const MAX_INCLUDE_DEPTH: usize = 5;
+
+const DIRECTIVE_OPEN: &str = "{{include:";
+const DIRECTIVE_CLOSE: &str = "}}";
+
+/// Whether `content` has include directives to expand
+pub fn has_includes(content: &str) -> bool {
+    content.contains(DIRECTIVE_OPEN)
+}
+
+/// Replace every include directive with the output of `replace` for its
+/// target, keeping the directive where `replace` returns `None`.
+///
+/// Directives spanning lines or naming no target are left untouched.
+pub fn replace_includes(content: &str, mut replace: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find(DIRECTIVE_OPEN) {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + DIRECTIVE_OPEN.len()..];
+        let Some(end) = after_open
+            .find(DIRECTIVE_CLOSE)
+            .filter(|&end| !after_open[..end].contains('\n'))
+        else {
+            output.push_str(DIRECTIVE_OPEN);
+            rest = after_open;
+            continue;
+        };
+
+        let target = after_open[..end].trim();
+        let directive_end = start + DIRECTIVE_OPEN.len() + end + DIRECTIVE_CLOSE.len();
+        match Some(target)
+            .filter(|t| !t.is_empty())
+            .and_then(&mut replace)
+        {
+            Some(text) => output.push_str(&text),
+            None => output.push_str(&rest[start..directive_end]),
+        }
+        rest = &rest[directive_end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// `note`'s content with its include directives expanded from `notes`
+pub fn expand_includes(note: &Note, notes: &[Note]) -> String {
+    let targets = LinkTargets::new(notes);
+    let by_id: HashMap<Uuid, &Note> = notes.iter().map(|n| (n.id, n)).collect();
+    let mut including = vec![note.id];
+    expand(&note.content, &targets, &by_id, &mut including)
+}
+
+/// `including` holds the notes whose content is being expanded, outermost
+/// first, so a note cannot end up inside itself
+fn expand(
+    content: &str,
+    targets: &LinkTargets,
+    by_id: &HashMap<Uuid, &Note>,
+    including: &mut Vec<Uuid>,
+) -> String {
+    replace_includes(content, |target| {
+        let included = targets.resolve(target).and_then(|id| by_id.get(&id))?;
+        if including.contains(&included.id) || including.len() > MAX_INCLUDE_DEPTH {
+            return None;
+        }
+
+        including.push(included.id);
+        let text = expand(&included.content, targets, by_id, including);
+        including.pop();
+        Some(text.trim_end().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_objects::NoteTitle;
+
+    fn note(title: &str, content: &str) -> Note {
+        Note::new(Uuid::new_v4(), NoteTitle::try_from(title).ok(), content)
+    }
+
+    #[test]
+    fn test_includes_resolve_by_title_and_id() {
+        let signature = note("Signature", "Best,\nAnna\n");
+        let address = note("Address", "Main St 1");
+        let letter = note(
+            "Letter",
+            &format!(
+                "Hi!\n\n{{{{include: signature }}}}\n\n{{{{include:{}}}}}",
+                address.id
+            ),
+        );
+        let notes = [signature, address, letter.clone()];
+
+        assert_eq!(
+            expand_includes(&letter, &notes),
+            "Hi!\n\nBest,\nAnna\n\nMain St 1"
+        );
+    }
+
+    #[test]
+    fn test_cycles_and_unknown_notes_keep_their_directives() {
+        let a = note("A", "a {{include:B}}");
+        let b = note("B", "b {{include:A}} {{include:Nowhere}}");
+        let notes = [a.clone(), b];
+
+        assert_eq!(
+            expand_includes(&a, &notes),
+            "a b {{include:A}} {{include:Nowhere}}"
+        );
+    }
+
+    #[test]
+    fn test_nesting_stops_at_the_depth_limit() {
+        let mut notes = vec![note("Level 0", "0")];
+        for level in 1..=MAX_INCLUDE_DEPTH + 1 {
+            let content = format!("{} {{{{include:Level {}}}}}", level, level - 1);
+            notes.push(note(&format!("Level {}", level), &content));
+        }
+        let top = notes.last().unwrap().clone();
+
+        let expanded = expand_includes(&top, &notes);
+        assert!(expanded.starts_with("6 5 4 3 2 1"));
+        assert!(expanded.ends_with("1 {{include:Level 0}}"));
+    }
+
+    #[test]
+    fn test_malformed_directives_are_left_alone() {
+        let content = "{{include:}} {{include:multi\nline}} {{include:open";
+        assert_eq!(
+            replace_includes(content, |_| Some("x".to_string())),
+            content
+        );
+        assert!(!has_includes("{{ include:Note }}"));
+    }
+}
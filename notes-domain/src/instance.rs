@@ -0,0 +1,493 @@
+//! Instance-wide settings that administrators change at runtime
+//!
+//! Environment configuration provides the defaults; values changed through
+//! the admin API are stored in the database and take precedence, so they
+//! survive restarts without editing the environment.
+//!
+//! Some settings only describe how clients should behave, such as the note
+//! colours to offer and the Markdown syntax to edit with; the config
+//! endpoint hands them out so clients do not hard-code them.
+
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::DEFAULT_MAX_PINNED_NOTES;
+use crate::errors::{DomainError, DomainResult};
+use crate::repositories::InstanceSettingsRepository;
+
+/// Highest pin limit an administrator can set
+pub const MAX_PIN_LIMIT: usize = 1000;
+
+/// Maximum length of the logo URL shown on printable views
+pub const MAX_LOGO_URL_LENGTH: usize = 2048;
+
+/// Most colours the note colour palette can hold
+pub const MAX_NOTE_COLORS: usize = 32;
+
+/// Most MIME types attachments can be limited to
+pub const MAX_ATTACHMENT_TYPES: usize = 100;
+
+/// Colours offered for notes unless changed, by name
+pub const DEFAULT_NOTE_COLORS: &[&str] = &[
+    "DEFAULT", "RED", "ORANGE", "YELLOW", "GREEN", "TEAL", "BLUE", "INDIGO",
+];
+
+/// Types of files that can be attached to notes unless changed
+pub const DEFAULT_ATTACHMENT_TYPES: &[&str] = &["image/*", "application/pdf", "text/plain"];
+
+/// Markdown syntax clients recognise when editing and rendering notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkdownOptions {
+    pub tables: bool,
+    pub task_lists: bool,
+    pub strikethrough: bool,
+    pub footnotes: bool,
+    /// `[[Note title]]` links between notes
+    pub wiki_links: bool,
+    /// Single line breaks kept as breaks instead of joining lines
+    pub hard_breaks: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            task_lists: true,
+            strikethrough: true,
+            footnotes: true,
+            wiki_links: true,
+            hard_breaks: false,
+        }
+    }
+}
+
+/// Settings currently in effect for the instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceSettings {
+    /// Whether new accounts can sign up with a password
+    pub allow_registration: bool,
+    /// Maximum number of pinned notes per user
+    pub max_pinned_notes: usize,
+    /// Whether notes are embedded and linked at all; users can only opt out
+    /// individually while this is on
+    pub smart_features_enabled: bool,
+    /// Logo shown at the top of printable note views
+    pub print_logo_url: Option<String>,
+    /// `#rgb` or `#rrggbb` colour for headings on printable note views
+    pub print_accent_color: Option<String>,
+    /// Colours offered for notes, as names like `RED` or hex colours
+    pub note_colors: Vec<String>,
+    /// Whether note lists show archived notes until the user chooses
+    pub show_archived: bool,
+    /// Most characters a note's content can hold (`None` = unlimited)
+    pub max_note_length: Option<usize>,
+    /// MIME types files attached to notes can have, such as `image/*`
+    pub attachment_types: Vec<String>,
+    pub markdown: MarkdownOptions,
+}
+
+impl Default for InstanceSettings {
+    fn default() -> Self {
+        Self {
+            allow_registration: true,
+            max_pinned_notes: DEFAULT_MAX_PINNED_NOTES,
+            smart_features_enabled: true,
+            print_logo_url: None,
+            print_accent_color: None,
+            note_colors: DEFAULT_NOTE_COLORS.iter().map(|c| c.to_string()).collect(),
+            show_archived: false,
+            max_note_length: None,
+            attachment_types: DEFAULT_ATTACHMENT_TYPES
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            markdown: MarkdownOptions::default(),
+        }
+    }
+}
+
+/// `update` if given, where an empty string removes the value, else `current`
+fn replace_text(update: &Option<String>, current: &Option<String>) -> Option<String> {
+    match update {
+        Some(value) if value.is_empty() => None,
+        Some(value) => Some(value.clone()),
+        None => current.clone(),
+    }
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// A colour name such as `RED`, as notes store them, or a hex colour
+fn is_note_color(color: &str) -> bool {
+    let is_name = color.len() <= 32
+        && color.starts_with(|c: char| c.is_ascii_uppercase())
+        && color
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    is_name || is_hex_color(color)
+}
+
+/// A MIME type such as `application/pdf`, or a wildcard like `image/*`
+fn is_media_type(media_type: &str) -> bool {
+    let is_token = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "!#$&-^_.+".contains(c))
+    };
+    media_type
+        .split_once('/')
+        .is_some_and(|(kind, subtype)| is_token(kind) && (subtype == "*" || is_token(subtype)))
+}
+
+impl InstanceSettings {
+    /// These settings with the values set in `update` replaced
+    pub fn apply(&self, update: &InstanceSettingsUpdate) -> Self {
+        Self {
+            allow_registration: update.allow_registration.unwrap_or(self.allow_registration),
+            max_pinned_notes: update.max_pinned_notes.unwrap_or(self.max_pinned_notes),
+            smart_features_enabled: update
+                .smart_features_enabled
+                .unwrap_or(self.smart_features_enabled),
+            print_logo_url: replace_text(&update.print_logo_url, &self.print_logo_url),
+            print_accent_color: replace_text(&update.print_accent_color, &self.print_accent_color),
+            note_colors: update
+                .note_colors
+                .clone()
+                .unwrap_or_else(|| self.note_colors.clone()),
+            show_archived: update.show_archived.unwrap_or(self.show_archived),
+            max_note_length: match update.max_note_length {
+                Some(0) => None,
+                Some(max) => Some(max),
+                None => self.max_note_length,
+            },
+            attachment_types: update
+                .attachment_types
+                .clone()
+                .unwrap_or_else(|| self.attachment_types.clone()),
+            markdown: update.markdown.unwrap_or(self.markdown),
+        }
+    }
+
+    /// Reject note content longer than [`Self::max_note_length`]
+    pub fn check_note_length(&self, content: &str) -> DomainResult<()> {
+        match self.max_note_length {
+            Some(max) if content.chars().count() > max => Err(DomainError::validation(format!(
+                "Note content cannot exceed {} characters",
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Settings to change; `None` leaves a setting as it is. Also used for the
+/// values stored in the database, where `None` means the default applies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceSettingsUpdate {
+    pub allow_registration: Option<bool>,
+    pub max_pinned_notes: Option<usize>,
+    pub smart_features_enabled: Option<bool>,
+    /// An empty string removes the logo
+    pub print_logo_url: Option<String>,
+    /// An empty string restores the default colours
+    pub print_accent_color: Option<String>,
+    /// Replaces the whole palette
+    pub note_colors: Option<Vec<String>>,
+    pub show_archived: Option<bool>,
+    /// `0` lifts the limit
+    pub max_note_length: Option<usize>,
+    /// Replaces the whole list; an empty list allows no attachments
+    pub attachment_types: Option<Vec<String>>,
+    /// Replaces every option
+    pub markdown: Option<MarkdownOptions>,
+}
+
+impl InstanceSettingsUpdate {
+    pub fn validate(&self) -> DomainResult<()> {
+        if let Some(max) = self.max_pinned_notes
+            && max > MAX_PIN_LIMIT
+        {
+            return Err(DomainError::validation(format!(
+                "max_pinned_notes must be at most {}",
+                MAX_PIN_LIMIT
+            )));
+        }
+        if let Some(url) = self.print_logo_url.as_deref().filter(|u| !u.is_empty()) {
+            let is_http = url.starts_with("https://") || url.starts_with("http://");
+            if !is_http || url.chars().any(char::is_whitespace) {
+                return Err(DomainError::validation(
+                    "print_logo_url must be an http(s) URL",
+                ));
+            }
+            if url.len() > MAX_LOGO_URL_LENGTH {
+                return Err(DomainError::validation(format!(
+                    "print_logo_url cannot exceed {} characters",
+                    MAX_LOGO_URL_LENGTH
+                )));
+            }
+        }
+        if let Some(color) = self.print_accent_color.as_deref().filter(|c| !c.is_empty())
+            && !is_hex_color(color)
+        {
+            return Err(DomainError::validation(
+                "print_accent_color must be a hex colour such as #1f6feb",
+            ));
+        }
+        if let Some(colors) = &self.note_colors {
+            if colors.is_empty() || colors.len() > MAX_NOTE_COLORS {
+                return Err(DomainError::validation(format!(
+                    "note_colors must hold between 1 and {} colours",
+                    MAX_NOTE_COLORS
+                )));
+            }
+            if let Some(color) = colors.iter().find(|c| !is_note_color(c)) {
+                return Err(DomainError::validation(format!(
+                    "{} is not a colour name such as RED or a hex colour",
+                    color
+                )));
+            }
+        }
+        if let Some(types) = &self.attachment_types {
+            if types.len() > MAX_ATTACHMENT_TYPES {
+                return Err(DomainError::validation(format!(
+                    "attachment_types can hold at most {} types",
+                    MAX_ATTACHMENT_TYPES
+                )));
+            }
+            if let Some(media_type) = types.iter().find(|t| !is_media_type(t)) {
+                return Err(DomainError::validation(format!(
+                    "{} is not a MIME type such as application/pdf or image/*",
+                    media_type
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Service holding the instance settings in effect.
+///
+/// Settings are cached in memory; changes made through [`Self::update`] apply
+/// to this process right away and other processes pick them up on their next
+/// [`Self::refresh`].
+pub struct InstanceSettingsService {
+    settings_repo: Arc<dyn InstanceSettingsRepository>,
+    defaults: InstanceSettings,
+    current: RwLock<InstanceSettings>,
+}
+
+impl InstanceSettingsService {
+    /// Load the stored settings on top of `defaults`
+    pub async fn load(
+        settings_repo: Arc<dyn InstanceSettingsRepository>,
+        defaults: InstanceSettings,
+    ) -> DomainResult<Self> {
+        let current = defaults.apply(&settings_repo.settings_overrides().await?);
+        Ok(Self {
+            settings_repo,
+            defaults,
+            current: RwLock::new(current),
+        })
+    }
+
+    pub fn current(&self) -> InstanceSettings {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Validate and store `update`, returning the settings now in effect
+    pub async fn update(&self, update: &InstanceSettingsUpdate) -> DomainResult<InstanceSettings> {
+        update.validate()?;
+        self.settings_repo.update_settings(update).await?;
+        self.refresh().await
+    }
+
+    /// Re-read the stored settings, e.g. after another process changed them
+    pub async fn refresh(&self) -> DomainResult<InstanceSettings> {
+        let settings = self
+            .defaults
+            .apply(&self.settings_repo.settings_overrides().await?);
+        *self.current.write().unwrap() = settings.clone();
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_only_replaces_given_values() {
+        let defaults = InstanceSettings::default();
+        let update = InstanceSettingsUpdate {
+            allow_registration: Some(false),
+            ..Default::default()
+        };
+
+        let settings = defaults.apply(&update);
+
+        assert!(!settings.allow_registration);
+        assert_eq!(settings.max_pinned_notes, defaults.max_pinned_notes);
+        assert!(settings.smart_features_enabled);
+    }
+
+    #[test]
+    fn test_validate_rejects_excessive_pin_limit() {
+        let update = InstanceSettingsUpdate {
+            max_pinned_notes: Some(MAX_PIN_LIMIT + 1),
+            ..Default::default()
+        };
+        assert!(update.validate().is_err());
+    }
+
+    #[test]
+    fn test_print_theme_is_validated_and_cleared_with_empty_strings() {
+        let invalid = [
+            ("javascript:alert(1)", "#fff"),
+            ("https://example.com/logo.png", "red; background: url(x)"),
+            ("https://example.com/logo.png", "#12345"),
+        ];
+        for (url, color) in invalid {
+            let update = InstanceSettingsUpdate {
+                print_logo_url: Some(url.to_string()),
+                print_accent_color: Some(color.to_string()),
+                ..Default::default()
+            };
+            assert!(update.validate().is_err(), "{} {}", url, color);
+        }
+
+        let update = InstanceSettingsUpdate {
+            print_logo_url: Some("https://example.com/logo.png".to_string()),
+            print_accent_color: Some("#1F6FEB".to_string()),
+            ..Default::default()
+        };
+        assert!(update.validate().is_ok());
+        let themed = InstanceSettings::default().apply(&update);
+        assert_eq!(themed.print_accent_color.as_deref(), Some("#1F6FEB"));
+
+        let cleared = themed.apply(&InstanceSettingsUpdate {
+            print_logo_url: Some(String::new()),
+            ..Default::default()
+        });
+        assert_eq!(cleared.print_logo_url, None);
+        assert_eq!(cleared.print_accent_color, themed.print_accent_color);
+    }
+
+    #[test]
+    fn test_note_defaults_are_validated() {
+        let invalid = [
+            InstanceSettingsUpdate {
+                note_colors: Some(Vec::new()),
+                ..Default::default()
+            },
+            InstanceSettingsUpdate {
+                note_colors: Some(vec!["RED".to_string(), "light red".to_string()]),
+                ..Default::default()
+            },
+            InstanceSettingsUpdate {
+                attachment_types: Some(vec!["pdf".to_string()]),
+                ..Default::default()
+            },
+            InstanceSettingsUpdate {
+                attachment_types: Some(vec!["*/*".to_string()]),
+                ..Default::default()
+            },
+        ];
+        for update in invalid {
+            assert!(update.validate().is_err(), "{:?}", update);
+        }
+
+        let update = InstanceSettingsUpdate {
+            note_colors: Some(vec!["DEFAULT".to_string(), "#ffcc00".to_string()]),
+            attachment_types: Some(vec![
+                "image/*".to_string(),
+                "application/vnd.oasis.opendocument.text".to_string(),
+            ]),
+            ..Default::default()
+        };
+        assert!(update.validate().is_ok());
+    }
+
+    #[test]
+    fn test_max_note_length_counts_characters_and_zero_lifts_it() {
+        let limited = InstanceSettings::default().apply(&InstanceSettingsUpdate {
+            max_note_length: Some(3),
+            ..Default::default()
+        });
+        assert!(limited.check_note_length("żół").is_ok());
+        assert!(limited.check_note_length("four").is_err());
+
+        let unlimited = limited.apply(&InstanceSettingsUpdate {
+            max_note_length: Some(0),
+            ..Default::default()
+        });
+        assert_eq!(unlimited.max_note_length, None);
+        assert!(unlimited.check_note_length(&"a".repeat(100_000)).is_ok());
+    }
+
+    mod instance_settings_service_tests {
+        use super::*;
+        use crate::services::tests::*;
+
+        #[tokio::test]
+        async fn test_stored_settings_override_defaults_and_refresh() {
+            let repo = Arc::new(MockInstanceSettingsRepository::default());
+            let defaults = InstanceSettings {
+                allow_registration: false,
+                ..Default::default()
+            };
+            let service = InstanceSettingsService::load(repo.clone(), defaults)
+                .await
+                .unwrap();
+            assert!(!service.current().allow_registration);
+
+            let settings = service
+                .update(&InstanceSettingsUpdate {
+                    allow_registration: Some(true),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            assert!(settings.allow_registration);
+            assert_eq!(service.current(), settings);
+
+            // Another process turns smart features off
+            repo.update_settings(&InstanceSettingsUpdate {
+                smart_features_enabled: Some(false),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+            assert!(service.current().smart_features_enabled);
+            service.refresh().await.unwrap();
+            assert!(!service.current().smart_features_enabled);
+            assert!(service.current().allow_registration);
+        }
+
+        #[tokio::test]
+        async fn test_update_rejects_invalid_settings() {
+            let service = InstanceSettingsService::load(
+                Arc::new(MockInstanceSettingsRepository::default()),
+                InstanceSettings::default(),
+            )
+            .await
+            .unwrap();
+
+            let result = service
+                .update(&InstanceSettingsUpdate {
+                    max_pinned_notes: Some(crate::instance::MAX_PIN_LIMIT + 1),
+                    ..Default::default()
+                })
+                .await;
+
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+            assert_eq!(service.current(), InstanceSettings::default());
+        }
+    }
+}
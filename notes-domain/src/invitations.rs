@@ -0,0 +1,278 @@
+//! Invitations for invite-only registration
+//!
+//! Administrators create invitation codes, optionally limited in uses and
+//! lifetime. Each account registered with a code is recorded against the
+//! invitation, which tracks it back to the administrator who sent it.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{DomainError, DomainResult};
+use crate::repositories::InvitationRepository;
+
+/// An invitation code accepted at registration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Invitation {
+    pub id: Uuid,
+    /// Secret code to register with
+    pub code: String,
+    /// Administrator who created it
+    pub created_by: Uuid,
+    /// Accounts that can register with it; unlimited when `None`
+    pub max_uses: Option<u32>,
+    /// Accounts registered with it so far
+    pub uses: u32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    pub fn new(
+        created_by: Uuid,
+        max_uses: Option<u32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> DomainResult<Self> {
+        if max_uses == Some(0) {
+            return Err(DomainError::validation("max_uses must be at least 1"));
+        }
+        let now = Utc::now();
+        if expires_at.is_some_and(|expires| expires <= now) {
+            return Err(DomainError::validation("expires_at must be in the future"));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            // 122 random bits, hard to guess and URL-safe
+            code: Uuid::new_v4().simple().to_string(),
+            created_by,
+            max_uses,
+            uses: 0,
+            expires_at,
+            created_at: now,
+        })
+    }
+
+    /// Whether an account can still register with it at `now`
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.max_uses.is_none_or(|max| self.uses < max)
+            && self.expires_at.is_none_or(|expires| now < expires)
+    }
+}
+
+const INVALID_INVITATION: &str = "Invalid or expired invitation code";
+
+/// Service for registration invitations
+pub struct InvitationService {
+    invitation_repo: Arc<dyn InvitationRepository>,
+}
+
+impl InvitationService {
+    pub fn new(invitation_repo: Arc<dyn InvitationRepository>) -> Self {
+        Self { invitation_repo }
+    }
+
+    /// Create an invitation on behalf of an administrator
+    pub async fn create(
+        &self,
+        created_by: Uuid,
+        max_uses: Option<u32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> DomainResult<Invitation> {
+        let invitation = Invitation::new(created_by, max_uses, expires_at)?;
+        self.invitation_repo.save(&invitation).await?;
+        Ok(invitation)
+    }
+
+    pub async fn get(&self, id: Uuid) -> DomainResult<Invitation> {
+        self.invitation_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::InvitationNotFound(id))
+    }
+
+    pub async fn list_all(&self) -> DomainResult<Vec<Invitation>> {
+        self.invitation_repo.find_all().await
+    }
+
+    /// Revoke an invitation so no more accounts can register with it
+    pub async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        self.get(id).await?;
+        self.invitation_repo.delete(id).await
+    }
+
+    /// Users who registered with the invitation
+    pub async fn invited_users(&self, id: Uuid) -> DomainResult<Vec<Uuid>> {
+        self.get(id).await?;
+        self.invitation_repo.find_invited_users(id).await
+    }
+
+    /// Find the invitation for `code`, failing unless it can still be used
+    pub async fn check_code(&self, code: &str) -> DomainResult<Invitation> {
+        self.invitation_repo
+            .find_by_code(code.trim())
+            .await?
+            .filter(|invitation| invitation.is_usable(Utc::now()))
+            .ok_or_else(|| DomainError::forbidden(INVALID_INVITATION))
+    }
+
+    /// Record that `user_id` registered with the invitation. Fails if another
+    /// registration used it up since [`Self::check_code`].
+    pub async fn redeem(&self, invitation: &Invitation, user_id: Uuid) -> DomainResult<()> {
+        if self
+            .invitation_repo
+            .redeem(invitation.id, user_id, Utc::now())
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(DomainError::forbidden(INVALID_INVITATION))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_is_usable_until_exhausted_or_expired() {
+        let now = Utc::now();
+        let mut invitation =
+            Invitation::new(Uuid::new_v4(), Some(2), Some(now + Duration::days(1))).unwrap();
+        assert!(invitation.is_usable(now));
+
+        invitation.uses = 2;
+        assert!(!invitation.is_usable(now));
+
+        invitation.uses = 1;
+        assert!(!invitation.is_usable(now + Duration::days(2)));
+    }
+
+    #[test]
+    fn test_new_rejects_unusable_limits() {
+        let admin = Uuid::new_v4();
+        assert!(Invitation::new(admin, Some(0), None).is_err());
+        assert!(Invitation::new(admin, None, Some(Utc::now() - Duration::hours(1))).is_err());
+
+        let a = Invitation::new(admin, None, None).unwrap();
+        let b = Invitation::new(admin, None, None).unwrap();
+        assert_ne!(a.code, b.code);
+    }
+
+    mod invitation_service_tests {
+        use super::*;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockInvitationRepository {
+            invitations: Mutex<HashMap<Uuid, Invitation>>,
+            invited: Mutex<Vec<(Uuid, Uuid)>>,
+        }
+
+        #[async_trait::async_trait]
+        impl InvitationRepository for MockInvitationRepository {
+            async fn save(&self, invitation: &Invitation) -> DomainResult<()> {
+                self.invitations
+                    .lock()
+                    .unwrap()
+                    .insert(invitation.id, invitation.clone());
+                Ok(())
+            }
+
+            async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Invitation>> {
+                Ok(self.invitations.lock().unwrap().get(&id).cloned())
+            }
+
+            async fn find_by_code(&self, code: &str) -> DomainResult<Option<Invitation>> {
+                Ok(self
+                    .invitations
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .find(|i| i.code == code)
+                    .cloned())
+            }
+
+            async fn find_all(&self) -> DomainResult<Vec<Invitation>> {
+                Ok(self.invitations.lock().unwrap().values().cloned().collect())
+            }
+
+            async fn delete(&self, id: Uuid) -> DomainResult<()> {
+                self.invitations.lock().unwrap().remove(&id);
+                Ok(())
+            }
+
+            async fn redeem(
+                &self,
+                id: Uuid,
+                user_id: Uuid,
+                now: DateTime<Utc>,
+            ) -> DomainResult<bool> {
+                let mut invitations = self.invitations.lock().unwrap();
+                match invitations.get_mut(&id) {
+                    Some(invitation) if invitation.is_usable(now) => {
+                        invitation.uses += 1;
+                        self.invited.lock().unwrap().push((id, user_id));
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+
+            async fn find_invited_users(&self, id: Uuid) -> DomainResult<Vec<Uuid>> {
+                Ok(self
+                    .invited
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|(invitation_id, _)| *invitation_id == id)
+                    .map(|(_, user_id)| *user_id)
+                    .collect())
+            }
+        }
+
+        #[tokio::test]
+        async fn test_single_use_invitation_is_tracked_and_used_up() {
+            let service = InvitationService::new(Arc::new(MockInvitationRepository::default()));
+            let invitation = service.create(Uuid::new_v4(), Some(1), None).await.unwrap();
+
+            let checked = service.check_code(&invitation.code).await.unwrap();
+            let user_id = Uuid::new_v4();
+            service.redeem(&checked, user_id).await.unwrap();
+
+            assert_eq!(
+                service.invited_users(invitation.id).await.unwrap(),
+                vec![user_id]
+            );
+            assert!(matches!(
+                service.check_code(&invitation.code).await,
+                Err(DomainError::Forbidden(_))
+            ));
+            // A registration that checked the code before it was used up
+            assert!(matches!(
+                service.redeem(&checked, Uuid::new_v4()).await,
+                Err(DomainError::Forbidden(_))
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_revoked_and_unknown_codes_are_rejected() {
+            let service = InvitationService::new(Arc::new(MockInvitationRepository::default()));
+            let invitation = service.create(Uuid::new_v4(), None, None).await.unwrap();
+
+            service.delete(invitation.id).await.unwrap();
+
+            assert!(service.check_code(&invitation.code).await.is_err());
+            assert!(service.check_code("made-up").await.is_err());
+            assert!(matches!(
+                service.delete(invitation.id).await,
+                Err(DomainError::InvitationNotFound(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//! Background jobs and their progress
+//!
+//! Long-running operations such as imports return a [`Job`] right away and
+//! run in the background, recording their progress on it as they go.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authorization::{Action, OwnerPolicy, Resource};
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::AuthorizationPolicy;
+use crate::repositories::JobRepository;
+
+/// Jobs returned when no limit is given
+pub const DEFAULT_JOB_LIMIT: usize = 20;
+
+/// Maximum number of jobs returned at once
+pub const MAX_JOB_LIMIT: usize = 100;
+
+/// Progress is saved at most this often, so per-item updates of large jobs
+/// do not add a write for every item
+pub const PROGRESS_SAVE_INTERVAL_MS: i64 = 1000;
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Importing a backup of notes and tags
+    Import,
+    /// Publishing notes as a static site on the server
+    SitePublish,
+    /// Packaging selected notes into an archive to download
+    SelectionExport,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Import => "import",
+            Self::SitePublish => "site_publish",
+            Self::SelectionExport => "selection_export",
+        }
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "import" => Ok(Self::Import),
+            "site_publish" => Ok(Self::SitePublish),
+            "selection_export" => Ok(Self::SelectionExport),
+            other => Err(DomainError::validation(format!(
+                "Unknown job kind: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Where a job is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Whether the job has stopped and will not change again
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Self::Running)
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(Self::Running),
+            "completed" => Ok(Self::Completed),
+            "failed" => Ok(Self::Failed),
+            other => Err(DomainError::validation(format!(
+                "Unknown job status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A long-running operation started by a user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Items handled so far
+    pub processed: u64,
+    /// Items to handle, when known up front
+    pub total: Option<u64>,
+    /// Why the job failed
+    pub error: Option<String>,
+    /// Outcome of a completed job, shaped by its kind
+    pub result: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl Job {
+    pub fn new(user_id: Uuid, kind: JobKind, total: Option<u64>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            kind,
+            status: JobStatus::Running,
+            processed: 0,
+            total,
+            error: None,
+            result: None,
+            created_at: now,
+            updated_at: now,
+            finished_at: None,
+        }
+    }
+
+    /// Fraction of the work done, when the total is known
+    pub fn progress(&self) -> Option<f64> {
+        match (self.status, self.total) {
+            (JobStatus::Completed, _) => Some(1.0),
+            (_, Some(0)) => Some(0.0),
+            (_, Some(total)) => Some((self.processed.min(total) as f64) / total as f64),
+            (_, None) => None,
+        }
+    }
+
+    pub(crate) fn finish(&mut self, status: JobStatus) {
+        let now = Utc::now();
+        self.status = status;
+        self.updated_at = now;
+        self.finished_at = Some(now);
+    }
+}
+
+/// Service tracking background jobs and their progress
+pub struct JobService {
+    job_repo: Arc<dyn JobRepository>,
+    policy: Arc<dyn AuthorizationPolicy>,
+}
+
+impl JobService {
+    pub fn new(job_repo: Arc<dyn JobRepository>) -> Self {
+        Self {
+            job_repo,
+            policy: Arc::new(OwnerPolicy),
+        }
+    }
+
+    /// Decide who may look up a job's status; whoever queued it by default
+    pub fn with_authorization_policy(mut self, policy: Arc<dyn AuthorizationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Record a new running job; `total` is the number of items, if known
+    pub async fn start(
+        &self,
+        user_id: Uuid,
+        kind: JobKind,
+        total: Option<u64>,
+    ) -> DomainResult<Job> {
+        let job = Job::new(user_id, kind, total);
+        self.job_repo.save(&job).await?;
+        Ok(job)
+    }
+
+    /// Record that `processed` items are done.
+    ///
+    /// Saved at most once per [`PROGRESS_SAVE_INTERVAL_MS`] (and when the
+    /// last item is done); finishing the job always saves the final count.
+    pub async fn report_progress(&self, job: &mut Job, processed: u64) -> DomainResult<()> {
+        job.processed = processed;
+
+        let now = Utc::now();
+        let due = now - job.updated_at >= chrono::Duration::milliseconds(PROGRESS_SAVE_INTERVAL_MS);
+        if due || job.total == Some(processed) {
+            job.updated_at = now;
+            self.job_repo.save(job).await?;
+        }
+        Ok(())
+    }
+
+    /// Mark the job as completed with an optional kind-specific result
+    pub async fn complete(
+        &self,
+        job: &mut Job,
+        result: Option<serde_json::Value>,
+    ) -> DomainResult<()> {
+        job.result = result;
+        job.finish(JobStatus::Completed);
+        self.job_repo.save(job).await
+    }
+
+    /// Mark the job as failed
+    pub async fn fail(&self, job: &mut Job, error: impl Into<String>) -> DomainResult<()> {
+        job.error = Some(error.into());
+        job.finish(JobStatus::Failed);
+        self.job_repo.save(job).await
+    }
+
+    /// Get one of the user's jobs; other users' jobs are reported as missing
+    pub async fn get_job(&self, id: Uuid, user_id: Uuid) -> DomainResult<Job> {
+        let job = self
+            .job_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::JobNotFound(id))?;
+
+        match self
+            .policy
+            .authorize(user_id, Action::Read, &Resource::job(&job))
+            .await
+        {
+            Ok(()) => Ok(job),
+            Err(DomainError::Forbidden(_)) => Err(DomainError::JobNotFound(id)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// List the user's jobs, newest first
+    pub async fn list_jobs(&self, user_id: Uuid, limit: Option<usize>) -> DomainResult<Vec<Job>> {
+        let limit = limit.unwrap_or(DEFAULT_JOB_LIMIT);
+        if limit == 0 || limit > MAX_JOB_LIMIT {
+            return Err(DomainError::validation(format!(
+                "limit must be between 1 and {}",
+                MAX_JOB_LIMIT
+            )));
+        }
+        self.job_repo.find_by_user(user_id, limit).await
+    }
+
+    /// Fail jobs left running by a previous process, which can no longer
+    /// finish them. Call once at startup.
+    pub async fn fail_interrupted(&self) -> DomainResult<u64> {
+        self.job_repo
+            .fail_running("Interrupted by a server restart")
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_and_status_round_trip_through_strings() {
+        for kind in [
+            JobKind::Import,
+            JobKind::SitePublish,
+            JobKind::SelectionExport,
+        ] {
+            assert_eq!(kind.as_str().parse::<JobKind>().unwrap(), kind);
+        }
+        for status in [JobStatus::Running, JobStatus::Completed, JobStatus::Failed] {
+            assert_eq!(status.as_str().parse::<JobStatus>().unwrap(), status);
+        }
+        assert!("reindex".parse::<JobKind>().is_err());
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut job = Job::new(Uuid::new_v4(), JobKind::Import, Some(4));
+        assert_eq!(job.progress(), Some(0.0));
+
+        job.processed = 1;
+        assert_eq!(job.progress(), Some(0.25));
+
+        job.finish(JobStatus::Completed);
+        assert_eq!(job.progress(), Some(1.0));
+
+        let unknown = Job::new(Uuid::new_v4(), JobKind::SitePublish, None);
+        assert_eq!(unknown.progress(), None);
+    }
+
+    mod job_service_tests {
+        use super::*;
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockJobRepository {
+            jobs: Mutex<HashMap<Uuid, Job>>,
+            saves: Mutex<usize>,
+        }
+
+        #[async_trait::async_trait]
+        impl JobRepository for MockJobRepository {
+            async fn save(&self, job: &Job) -> DomainResult<()> {
+                *self.saves.lock().unwrap() += 1;
+                self.jobs.lock().unwrap().insert(job.id, job.clone());
+                Ok(())
+            }
+
+            async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Job>> {
+                Ok(self.jobs.lock().unwrap().get(&id).cloned())
+            }
+
+            async fn find_by_user(&self, user_id: Uuid, limit: usize) -> DomainResult<Vec<Job>> {
+                let mut jobs: Vec<Job> = self
+                    .jobs
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .filter(|j| j.user_id == user_id)
+                    .cloned()
+                    .collect();
+                jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+                jobs.truncate(limit);
+                Ok(jobs)
+            }
+
+            async fn fail_running(&self, error: &str) -> DomainResult<u64> {
+                let mut failed = 0;
+                for job in self.jobs.lock().unwrap().values_mut() {
+                    if job.status == JobStatus::Running {
+                        job.error = Some(error.to_string());
+                        job.finish(JobStatus::Failed);
+                        failed += 1;
+                    }
+                }
+                Ok(failed)
+            }
+        }
+
+        #[tokio::test]
+        async fn test_other_users_jobs_are_not_found() {
+            let service = JobService::new(Arc::new(MockJobRepository::default()));
+            let job = service
+                .start(Uuid::new_v4(), JobKind::Import, Some(1))
+                .await
+                .unwrap();
+
+            assert_eq!(service.get_job(job.id, job.user_id).await.unwrap(), job);
+            assert!(matches!(
+                service.get_job(job.id, Uuid::new_v4()).await,
+                Err(DomainError::JobNotFound(_))
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_progress_saves_are_throttled() {
+            let repo = Arc::new(MockJobRepository::default());
+            let service = JobService::new(repo.clone());
+            let mut job = service
+                .start(Uuid::new_v4(), JobKind::Import, Some(3))
+                .await
+                .unwrap();
+
+            service.report_progress(&mut job, 1).await.unwrap();
+            service.report_progress(&mut job, 2).await.unwrap();
+            // Start, then the last item; the updates in between are skipped
+            service.report_progress(&mut job, 3).await.unwrap();
+            assert_eq!(*repo.saves.lock().unwrap(), 2);
+
+            service.complete(&mut job, None).await.unwrap();
+            let stored = service.get_job(job.id, job.user_id).await.unwrap();
+            assert_eq!(stored.status, JobStatus::Completed);
+            assert_eq!(stored.processed, 3);
+        }
+
+        #[tokio::test]
+        async fn test_fail_interrupted_fails_running_jobs() {
+            let service = JobService::new(Arc::new(MockJobRepository::default()));
+            let user_id = Uuid::new_v4();
+            let running = service.start(user_id, JobKind::Import, None).await.unwrap();
+            let mut done = service
+                .start(user_id, JobKind::SitePublish, None)
+                .await
+                .unwrap();
+            service.complete(&mut done, None).await.unwrap();
+
+            assert_eq!(service.fail_interrupted().await.unwrap(), 1);
+            let running = service.get_job(running.id, user_id).await.unwrap();
+            assert_eq!(running.status, JobStatus::Failed);
+            assert!(running.error.is_some());
+        }
+
+        #[tokio::test]
+        async fn test_list_jobs_rejects_invalid_limit() {
+            let service = JobService::new(Arc::new(MockJobRepository::default()));
+
+            let result = service.list_jobs(Uuid::new_v4(), Some(0)).await;
+
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+        }
+    }
+}
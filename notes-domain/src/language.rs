@@ -0,0 +1,204 @@
+//! The language notes are written in
+//!
+//! A note's dominant language is detected from its content whenever the
+//! content changes, and kept as an ISO 639-1 code like `pl`. Searches filter
+//! by it with `lang:pl`, and it hints search indexing and embedding at how
+//! to treat the text. Short or mixed texts may have no reliable language;
+//! their notes are left without one.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::value_objects::ValidationError;
+
+/// Characters of a note looked at to detect its language
+const DETECTION_SAMPLE_LENGTH: usize = 2000;
+
+/// ISO 639-3 codes of the languages detection knows, with their ISO 639-1 codes
+const LANGUAGE_CODES: &[(&str, &str)] = &[
+    ("afr", "af"),
+    ("aka", "ak"),
+    ("amh", "am"),
+    ("ara", "ar"),
+    ("aze", "az"),
+    ("bel", "be"),
+    ("ben", "bn"),
+    ("bul", "bg"),
+    ("cat", "ca"),
+    ("ces", "cs"),
+    ("cmn", "zh"),
+    ("dan", "da"),
+    ("deu", "de"),
+    ("ell", "el"),
+    ("eng", "en"),
+    ("epo", "eo"),
+    ("est", "et"),
+    ("fin", "fi"),
+    ("fra", "fr"),
+    ("guj", "gu"),
+    ("heb", "he"),
+    ("hin", "hi"),
+    ("hrv", "hr"),
+    ("hun", "hu"),
+    ("hye", "hy"),
+    ("ind", "id"),
+    ("ita", "it"),
+    ("jav", "jv"),
+    ("jpn", "ja"),
+    ("kan", "kn"),
+    ("kat", "ka"),
+    ("khm", "km"),
+    ("kor", "ko"),
+    ("lat", "la"),
+    ("lav", "lv"),
+    ("lit", "lt"),
+    ("mal", "ml"),
+    ("mar", "mr"),
+    ("mkd", "mk"),
+    ("mya", "my"),
+    ("nep", "ne"),
+    ("nld", "nl"),
+    ("nob", "nb"),
+    ("ori", "or"),
+    ("pan", "pa"),
+    ("pes", "fa"),
+    ("pol", "pl"),
+    ("por", "pt"),
+    ("ron", "ro"),
+    ("rus", "ru"),
+    ("sin", "si"),
+    ("slk", "sk"),
+    ("slv", "sl"),
+    ("sna", "sn"),
+    ("spa", "es"),
+    ("srp", "sr"),
+    ("swe", "sv"),
+    ("tam", "ta"),
+    ("tel", "te"),
+    ("tgl", "tl"),
+    ("tha", "th"),
+    ("tuk", "tk"),
+    ("tur", "tr"),
+    ("ukr", "uk"),
+    ("urd", "ur"),
+    ("uzb", "uz"),
+    ("vie", "vi"),
+    ("yid", "yi"),
+    ("zul", "zu"),
+];
+
+/// Languages written without spaces between words
+const UNSPACED_LANGUAGES: &[&str] = &["ja", "km", "my", "th", "zh"];
+
+/// A language notes can be detected as, by ISO 639-1 code
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(into = "String")]
+pub struct Language(&'static str);
+
+impl Language {
+    pub fn new(code: &str) -> Result<Self, ValidationError> {
+        let code = code.trim().to_ascii_lowercase();
+        LANGUAGE_CODES
+            .iter()
+            .find(|(_, iso_639_1)| *iso_639_1 == code)
+            .map(|(_, iso_639_1)| Self(iso_639_1))
+            .ok_or(ValidationError::UnknownLanguage(code))
+    }
+
+    /// The dominant language of `text`, when it can be told reliably
+    pub fn detect(text: &str) -> Option<Self> {
+        let sample: String = text.chars().take(DETECTION_SAMPLE_LENGTH).collect();
+        let info = whatlang::detect(&sample).filter(|info| info.is_reliable())?;
+        let code = info.lang().code();
+        LANGUAGE_CODES
+            .iter()
+            .find(|(iso_639_3, _)| *iso_639_3 == code)
+            .map(|(_, iso_639_1)| Self(iso_639_1))
+    }
+
+    /// The ISO 639-1 code, like `pl`
+    pub fn code(&self) -> &'static str {
+        self.0
+    }
+
+    /// The English name, like `Polish`
+    pub fn name(&self) -> &'static str {
+        LANGUAGE_CODES
+            .iter()
+            .find(|(_, iso_639_1)| *iso_639_1 == self.0)
+            .and_then(|&(iso_639_3, _)| whatlang::Lang::from_code(iso_639_3))
+            .map_or(self.0, |lang| lang.eng_name())
+    }
+
+    /// Whether the language is written without spaces between words, so
+    /// words cannot be told apart by whitespace
+    pub fn is_unspaced(&self) -> bool {
+        UNSPACED_LANGUAGES.contains(&self.0)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = ValidationError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl TryFrom<&str> for Language {
+    type Error = ValidationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+/// Deserialized by hand: the derived `try_from` cannot hand out the
+/// `'static` code borrowed from the input
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Self::new(&code).map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<Language> for String {
+    fn from(val: Language) -> Self {
+        val.0.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_the_dominant_language() {
+        let polish = "Jutro rano idziemy na spacer do parku, a potem zjemy obiad u babci. Wieczorem czytamy książki i pijemy herbatę z cytryną.";
+        assert_eq!(Language::detect(polish), Language::new("pl").ok());
+        let english = "Tomorrow morning we are going for a walk in the park before lunch.";
+        assert_eq!(Language::detect(english), Language::new("en").ok());
+        assert_eq!(Language::detect("ok"), None);
+    }
+
+    #[test]
+    fn test_codes_are_iso_639_1() {
+        assert_eq!(Language::new(" PL ").unwrap().code(), "pl");
+        assert!(Language::new("pol").is_err());
+        assert!(Language::new("xx").is_err());
+        assert!(Language::new("zh").unwrap().is_unspaced());
+        assert!(!Language::new("de").unwrap().is_unspaced());
+        assert_eq!(Language::new("pl").unwrap().name(), "Polish");
+
+        let json = serde_json::to_string(&Language::new("de").unwrap()).unwrap();
+        assert_eq!(json, "\"de\"");
+        assert!(serde_json::from_str::<Language>("\"qq\"").is_err());
+    }
+}
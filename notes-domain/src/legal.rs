@@ -0,0 +1,361 @@
+//! Terms of service and privacy policy users have to accept
+//!
+//! Public instances can publish legal documents as markdown. Publishing a
+//! document again gives it a new version, and users keep being refused until
+//! they have accepted the current version of every published document.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{DomainError, DomainResult};
+use crate::repositories::LegalRepository;
+
+/// Maximum length of a legal document in characters
+pub const MAX_LEGAL_DOCUMENT_LENGTH: usize = 100_000;
+
+/// Which legal document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegalDocumentKind {
+    Terms,
+    Privacy,
+}
+
+impl LegalDocumentKind {
+    pub const ALL: [LegalDocumentKind; 2] = [Self::Terms, Self::Privacy];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Terms => "terms",
+            Self::Privacy => "privacy",
+        }
+    }
+}
+
+impl fmt::Display for LegalDocumentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LegalDocumentKind {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "terms" => Ok(Self::Terms),
+            "privacy" => Ok(Self::Privacy),
+            other => Err(DomainError::validation(format!(
+                "Unknown legal document: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// One published version of a legal document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegalDocument {
+    pub kind: LegalDocumentKind,
+    /// Starts at 1 and grows with every publication
+    pub version: u32,
+    /// Markdown content
+    pub content: String,
+    pub published_at: DateTime<Utc>,
+}
+
+impl LegalDocument {
+    /// The version following `previous`, or the first version
+    pub fn publish(
+        kind: LegalDocumentKind,
+        content: impl Into<String>,
+        previous: Option<&LegalDocument>,
+    ) -> DomainResult<Self> {
+        let content = content.into();
+        if content.trim().is_empty() {
+            return Err(DomainError::validation(format!(
+                "The {} cannot be empty",
+                kind
+            )));
+        }
+        if content.chars().count() > MAX_LEGAL_DOCUMENT_LENGTH {
+            return Err(DomainError::validation(format!(
+                "The {} cannot exceed {} characters",
+                kind, MAX_LEGAL_DOCUMENT_LENGTH
+            )));
+        }
+
+        Ok(Self {
+            kind,
+            version: previous.map_or(1, |previous| previous.version + 1),
+            content,
+            published_at: Utc::now(),
+        })
+    }
+}
+
+/// A user's acceptance of one version of a legal document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegalAcceptance {
+    pub user_id: Uuid,
+    pub kind: LegalDocumentKind,
+    pub version: u32,
+    pub accepted_at: DateTime<Utc>,
+}
+
+impl LegalAcceptance {
+    pub fn new(user_id: Uuid, kind: LegalDocumentKind, version: u32) -> Self {
+        Self {
+            user_id,
+            kind,
+            version,
+            accepted_at: Utc::now(),
+        }
+    }
+}
+
+/// Names of the documents, for messages
+fn document_names(documents: &[LegalDocument]) -> String {
+    documents
+        .iter()
+        .map(|document| document.kind.as_str())
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Service for legal documents and users' consent to them
+pub struct LegalService {
+    legal_repo: Arc<dyn LegalRepository>,
+}
+
+impl LegalService {
+    pub fn new(legal_repo: Arc<dyn LegalRepository>) -> Self {
+        Self { legal_repo }
+    }
+
+    /// Publish a new version of the document, which every user has to
+    /// accept again
+    pub async fn publish(
+        &self,
+        kind: LegalDocumentKind,
+        content: &str,
+    ) -> DomainResult<LegalDocument> {
+        let previous = self.legal_repo.find_current(kind).await?;
+        let document = LegalDocument::publish(kind, content, previous.as_ref())?;
+
+        self.legal_repo.save_document(&document).await?;
+        Ok(document)
+    }
+
+    /// The current version of the document
+    pub async fn get(&self, kind: LegalDocumentKind) -> DomainResult<LegalDocument> {
+        self.legal_repo
+            .find_current(kind)
+            .await?
+            .ok_or_else(|| DomainError::LegalDocumentNotFound(kind.to_string()))
+    }
+
+    /// The current version of every published document
+    pub async fn list(&self) -> DomainResult<Vec<LegalDocument>> {
+        self.legal_repo.find_all_current().await
+    }
+
+    /// Check that every version in `accepted` is the current one
+    async fn check_current(&self, accepted: &[(LegalDocumentKind, u32)]) -> DomainResult<()> {
+        for &(kind, version) in accepted {
+            let current = self.get(kind).await?;
+            if current.version != version {
+                return Err(DomainError::validation(format!(
+                    "Version {} of the {} is outdated; review version {}",
+                    version, kind, current.version
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `accepted` names the current version of every published
+    /// document and nothing else, before an account is created
+    pub async fn check_accepted(&self, accepted: &[(LegalDocumentKind, u32)]) -> DomainResult<()> {
+        self.check_current(accepted).await?;
+        let missing: Vec<LegalDocument> = self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|document| !accepted.contains(&(document.kind, document.version)))
+            .collect();
+        if !missing.is_empty() {
+            return Err(DomainError::ConsentRequired(document_names(&missing)));
+        }
+        Ok(())
+    }
+
+    /// Record that the user accepted these versions; only current versions
+    /// can be accepted
+    pub async fn accept(
+        &self,
+        user_id: Uuid,
+        accepted: &[(LegalDocumentKind, u32)],
+    ) -> DomainResult<()> {
+        self.check_current(accepted).await?;
+        for &(kind, version) in accepted {
+            self.legal_repo
+                .save_acceptance(&LegalAcceptance::new(user_id, kind, version))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Current documents the user has not accepted yet
+    pub async fn pending(&self, user_id: Uuid) -> DomainResult<Vec<LegalDocument>> {
+        self.legal_repo.find_unaccepted(user_id).await
+    }
+
+    /// Refuse users who have not accepted the current documents
+    pub async fn ensure_accepted(&self, user_id: Uuid) -> DomainResult<()> {
+        let pending = self.pending(user_id).await?;
+        if !pending.is_empty() {
+            return Err(DomainError::ConsentRequired(document_names(&pending)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publishing_again_bumps_the_version() {
+        let first = LegalDocument::publish(LegalDocumentKind::Terms, "# Terms", None).unwrap();
+        assert_eq!(first.version, 1);
+        let second =
+            LegalDocument::publish(LegalDocumentKind::Terms, "# New terms", Some(&first)).unwrap();
+        assert_eq!(second.version, 2);
+
+        assert!(LegalDocument::publish(LegalDocumentKind::Privacy, " \n", None).is_err());
+        assert_eq!(
+            "privacy".parse::<LegalDocumentKind>().unwrap(),
+            LegalDocumentKind::Privacy
+        );
+        assert!("cookies".parse::<LegalDocumentKind>().is_err());
+    }
+
+    mod legal_service_tests {
+        use super::*;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockLegalRepository {
+            documents: Mutex<Vec<LegalDocument>>,
+            acceptances: Mutex<Vec<LegalAcceptance>>,
+        }
+
+        #[async_trait::async_trait]
+        impl LegalRepository for MockLegalRepository {
+            async fn save_document(&self, document: &LegalDocument) -> DomainResult<()> {
+                self.documents.lock().unwrap().push(document.clone());
+                Ok(())
+            }
+
+            async fn find_current(
+                &self,
+                kind: LegalDocumentKind,
+            ) -> DomainResult<Option<LegalDocument>> {
+                Ok(self
+                    .documents
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|d| d.kind == kind)
+                    .max_by_key(|d| d.version)
+                    .cloned())
+            }
+
+            async fn find_all_current(&self) -> DomainResult<Vec<LegalDocument>> {
+                let mut current = Vec::new();
+                for kind in LegalDocumentKind::ALL {
+                    current.extend(self.find_current(kind).await?);
+                }
+                Ok(current)
+            }
+
+            async fn save_acceptance(&self, acceptance: &LegalAcceptance) -> DomainResult<()> {
+                self.acceptances.lock().unwrap().push(acceptance.clone());
+                Ok(())
+            }
+
+            async fn find_unaccepted(&self, user_id: Uuid) -> DomainResult<Vec<LegalDocument>> {
+                let acceptances = self.acceptances.lock().unwrap().clone();
+                Ok(self
+                    .find_all_current()
+                    .await?
+                    .into_iter()
+                    .filter(|d| {
+                        !acceptances.iter().any(|a| {
+                            a.user_id == user_id && a.kind == d.kind && a.version == d.version
+                        })
+                    })
+                    .collect())
+            }
+        }
+
+        #[tokio::test]
+        async fn test_new_versions_have_to_be_accepted_again() {
+            let service = LegalService::new(Arc::new(MockLegalRepository::default()));
+            let user_id = Uuid::new_v4();
+
+            // Nothing to accept until something is published
+            service.check_accepted(&[]).await.unwrap();
+            service.ensure_accepted(user_id).await.unwrap();
+
+            let terms = service
+                .publish(LegalDocumentKind::Terms, "# Terms")
+                .await
+                .unwrap();
+            service
+                .publish(LegalDocumentKind::Privacy, "# Privacy")
+                .await
+                .unwrap();
+            assert!(matches!(
+                service
+                    .check_accepted(&[(LegalDocumentKind::Terms, 1)])
+                    .await,
+                Err(DomainError::ConsentRequired(names)) if names == "privacy"
+            ));
+            let accepted = [
+                (LegalDocumentKind::Terms, 1),
+                (LegalDocumentKind::Privacy, 1),
+            ];
+            service.check_accepted(&accepted).await.unwrap();
+            service.accept(user_id, &accepted).await.unwrap();
+            service.ensure_accepted(user_id).await.unwrap();
+
+            let revised = service
+                .publish(LegalDocumentKind::Terms, "# Terms, revised")
+                .await
+                .unwrap();
+            assert_eq!(revised.version, terms.version + 1);
+            assert!(matches!(
+                service.ensure_accepted(user_id).await,
+                Err(DomainError::ConsentRequired(names)) if names == "terms"
+            ));
+            assert!(
+                service
+                    .accept(user_id, &[(LegalDocumentKind::Terms, 1)])
+                    .await
+                    .is_err()
+            );
+            service
+                .accept(user_id, &[(LegalDocumentKind::Terms, 2)])
+                .await
+                .unwrap();
+            assert!(service.pending(user_id).await.unwrap().is_empty());
+        }
+    }
+}
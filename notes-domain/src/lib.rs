@@ -3,23 +3,116 @@
 //! This crate contains pure domain logic with no I/O dependencies.
 //! It follows hexagonal architecture principles where:
 //!
+//! - **Announcements**: Banners administrators show to every user
+//! - **Authorization**: Which user may perform which action on a resource
+//! - **Boards**: Kanban boards whose columns select notes by tag or status
+//! - **Bookmarks**: Previews of links pasted on a line of their own, or unfurled while editing
+//! - **Capture**: Quick capture of notes from text with inline markers
+//! - **Dates**: Natural-language dates read in the user's timezone
+//! - **Diagrams**: Mermaid and PlantUML code blocks drawn as SVG
 //! - **Entities**: Core business objects (Note, Tag, User)
 //! - **Errors**: Domain-specific error types
+//! - **Event Log**: Typed record of user actions for auditing and activity feeds
+//! - **Events**: Versioned domain events published to the message broker
+//! - **Geo**: Note locations and nearby searches
+//! - **Housekeeping**: Leftovers in the instance's data and fixing them
+//! - **Images**: External images loaded through the instance's image proxy
+//! - **Impersonation**: Time-limited, audited access of administrators to a user's account
+//! - **Includes**: Notes included in other notes with `{{include:…}}`
+//! - **Instance**: Runtime settings administrators manage for the whole instance
+//! - **Invitations**: Codes for invite-only registration
+//! - **Jobs**: Long-running operations and their progress
+//! - **Language**: The dominant language of note content
+//! - **Legal**: Terms and privacy policy users have to accept
+//! - **Lint**: Broken links and dangling wiki-links found in note content
+//! - **Onboarding**: Welcome notes and starter tags for new accounts
+//! - **Overview**: Instance-wide metrics for administrators
+//! - **Relations**: Typed links between notes (parent/child, references, blockers)
 //! - **Repositories**: Port traits defining data access interfaces
+//! - **Scopes**: Permissions carried by access tokens
+//! - **Scratchpad**: Scratch notes for visitors without an account
 //! - **Services**: Use cases orchestrating business logic
+//! - **Speech**: Notes read aloud as audio
+//! - **Tag Aliases**: Alternative names that resolve to a canonical tag
+//! - **Tag Cleanup**: What happens to tags that no note uses
+//! - **Titles**: Titles generated for untitled notes
+//! - **Transcription**: Voice memos transcribed into their notes
+//! - **Translation**: Translations of notes kept as their child notes
+//! - **Undo**: Reverting and reapplying the latest note changes
 //! - **Value Objects**: Validated newtypes for domain primitives
+//! - **Writing**: Writing statistics and proofreading of notes
 
+pub mod announcements;
+pub mod archive_policy;
+pub mod authorization;
+pub mod boards;
+pub mod bookmarks;
+pub mod capture;
+pub mod dates;
+pub mod diagrams;
 pub mod entities;
 pub mod errors;
+pub mod event_log;
+pub mod events;
+pub mod geo;
+pub mod graph;
+pub mod housekeeping;
+pub mod images;
+pub mod impersonation;
+pub mod includes;
+pub mod instance;
+pub mod invitations;
+pub mod jobs;
+pub mod language;
+pub mod legal;
+pub mod lint;
+pub mod onboarding;
+pub mod overview;
 pub mod ports;
+pub mod query;
+pub mod relations;
 pub mod repositories;
+pub mod scopes;
+pub mod scratchpad;
+pub mod search;
 pub mod services;
+pub mod speech;
+pub mod tag_aliases;
+pub mod tag_cleanup;
+pub mod titles;
+pub mod transcription;
+pub mod translation;
+pub mod trash;
+pub mod undo;
 pub mod value_objects;
+pub mod wiki_links;
+pub mod writing;
 
 // Re-export commonly used types at crate root
+pub use announcements::{AnnouncementRequest, AnnouncementService};
+pub use boards::{BoardColumnRequest, BoardRequest, BoardService};
+pub use bookmarks::{LinkPreviewService, UnfurlService};
+pub use diagrams::DiagramService;
 pub use entities::*;
-pub use errors::{DomainError, DomainResult};
+pub use errors::{DomainError, DomainResult, RepositoryError};
+pub use event_log::{ActivityService, EventDispatcher};
+pub use housekeeping::HousekeepingService;
+pub use images::ImageProxyService;
+pub use impersonation::ImpersonationService;
+pub use instance::InstanceSettingsService;
+pub use invitations::InvitationService;
+pub use jobs::JobService;
+pub use legal::LegalService;
+pub use lint::NoteLintService;
+pub use onboarding::OnboardingService;
 pub use ports::*;
+pub use relations::NoteRelationService;
 pub use repositories::*;
 pub use services::*;
+pub use speech::SpeechService;
+pub use tag_aliases::TagAliasService;
+pub use transcription::TranscriptionService;
+pub use translation::TranslationService;
+pub use undo::UndoService;
 pub use value_objects::*;
+pub use writing::ProofreadService;
@@ -0,0 +1,475 @@
+//! Content checks for long-lived notes
+//!
+//! The lint job looks for external links that no longer resolve and for
+//! `[[wiki-links]]` whose target note does not exist, and stores what it finds
+//! as [`NoteIssue`]s until the next run replaces them.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authorization::{Action, OwnerPolicy, Resource};
+use crate::entities::{Note, NoteFilter};
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::{AuthorizationPolicy, UrlChecker};
+use crate::repositories::{NoteIssueRepository, NoteRepository};
+use crate::wiki_links::{LinkTargets, extract_wiki_links};
+
+/// What is wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteIssueKind {
+    /// An `http(s)` link that failed to load
+    BrokenUrl,
+    /// A `[[wiki-link]]` that matches no note title or ID
+    DanglingWikiLink,
+}
+
+impl NoteIssueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BrokenUrl => "broken_url",
+            Self::DanglingWikiLink => "dangling_wiki_link",
+        }
+    }
+}
+
+impl fmt::Display for NoteIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NoteIssueKind {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "broken_url" => Ok(Self::BrokenUrl),
+            "dangling_wiki_link" => Ok(Self::DanglingWikiLink),
+            other => Err(DomainError::validation(format!(
+                "Unknown note issue kind: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A problem found in a note's content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteIssue {
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub kind: NoteIssueKind,
+    /// The URL or wiki-link target as written in the note
+    pub target: String,
+    /// Why a URL counts as broken, e.g. `HTTP 404`
+    pub detail: Option<String>,
+    pub found_at: DateTime<Utc>,
+}
+
+impl NoteIssue {
+    pub fn new(note: &Note, kind: NoteIssueKind, target: impl Into<String>) -> Self {
+        Self {
+            note_id: note.id,
+            user_id: note.user_id,
+            kind,
+            target: target.into(),
+            detail: None,
+            found_at: Utc::now(),
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Characters that end a URL in markdown or prose
+fn ends_url(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | '`' | ')' | ']' | '|')
+}
+
+/// External `http(s)` URLs in note content, without duplicates
+///
+/// Finds bare URLs as well as markdown and autolink targets. Punctuation
+/// directly after a URL (`see https://example.com.`) is not part of it.
+pub fn extract_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("http") {
+        let candidate = &rest[start..];
+        let is_url = candidate.starts_with("http://") || candidate.starts_with("https://");
+        if !is_url {
+            rest = &candidate[4..];
+            continue;
+        }
+
+        let end = candidate.find(ends_url).unwrap_or(candidate.len());
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let has_host = url
+            .split_once("://")
+            .is_some_and(|(_, after)| !after.is_empty() && !after.starts_with('/'));
+        if has_host && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+        rest = &candidate[end..];
+    }
+
+    urls
+}
+
+/// Wiki-link targets in `note` that resolve to no note, without duplicates
+pub fn dangling_wiki_links(note: &Note, targets: &LinkTargets) -> Vec<String> {
+    let mut dangling: Vec<String> = Vec::new();
+    for link in extract_wiki_links(&note.content) {
+        if targets.resolve(&link.target).is_none() && !dangling.contains(&link.target) {
+            dangling.push(link.target);
+        }
+    }
+    dangling
+}
+
+/// Issues of one note, for the aggregate report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteIssueSummary {
+    pub note_id: Uuid,
+    pub broken_urls: usize,
+    pub dangling_wiki_links: usize,
+}
+
+/// Every issue found in a user's notes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IssueReport {
+    pub broken_urls: usize,
+    pub dangling_wiki_links: usize,
+    /// Notes with at least one issue, most issues first
+    pub notes: Vec<NoteIssueSummary>,
+    /// When the newest finding was recorded
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+impl IssueReport {
+    pub fn new(issues: &[NoteIssue]) -> Self {
+        let mut report = Self::default();
+        for issue in issues {
+            let index = match report.notes.iter().position(|n| n.note_id == issue.note_id) {
+                Some(index) => index,
+                None => {
+                    report.notes.push(NoteIssueSummary {
+                        note_id: issue.note_id,
+                        broken_urls: 0,
+                        dangling_wiki_links: 0,
+                    });
+                    report.notes.len() - 1
+                }
+            };
+            let summary = &mut report.notes[index];
+            match issue.kind {
+                NoteIssueKind::BrokenUrl => {
+                    summary.broken_urls += 1;
+                    report.broken_urls += 1;
+                }
+                NoteIssueKind::DanglingWikiLink => {
+                    summary.dangling_wiki_links += 1;
+                    report.dangling_wiki_links += 1;
+                }
+            }
+            report.checked_at = report.checked_at.max(Some(issue.found_at));
+        }
+
+        report
+            .notes
+            .sort_by_key(|n| std::cmp::Reverse(n.broken_urls + n.dangling_wiki_links));
+        report
+    }
+}
+
+/// Service finding broken links and dangling wiki-links in notes
+pub struct NoteLintService {
+    note_repo: Arc<dyn NoteRepository>,
+    issue_repo: Arc<dyn NoteIssueRepository>,
+    url_checker: Option<Arc<dyn UrlChecker>>,
+    policy: Arc<dyn AuthorizationPolicy>,
+}
+
+impl NoteLintService {
+    pub fn new(
+        note_repo: Arc<dyn NoteRepository>,
+        issue_repo: Arc<dyn NoteIssueRepository>,
+    ) -> Self {
+        Self {
+            note_repo,
+            issue_repo,
+            url_checker: None,
+            policy: Arc::new(OwnerPolicy),
+        }
+    }
+
+    /// Decide whose notes may be linted; their owners only by default
+    pub fn with_authorization_policy(mut self, policy: Arc<dyn AuthorizationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Builder method to check external URLs too; without a checker only
+    /// wiki-links are checked
+    pub fn with_url_checker(mut self, url_checker: Arc<dyn UrlChecker>) -> Self {
+        self.url_checker = Some(url_checker);
+        self
+    }
+
+    /// Check all of the user's live notes and replace their stored issues.
+    /// Returns the number of issues found.
+    pub async fn lint_user(&self, user_id: Uuid) -> DomainResult<usize> {
+        let notes = self
+            .note_repo
+            .find_by_user(user_id, NoteFilter::new())
+            .await?;
+        let targets = LinkTargets::new(&notes);
+        // Notes often share links; check each URL once per run
+        let mut checked: HashMap<String, Option<String>> = HashMap::new();
+        let mut found = 0;
+
+        for note in &notes {
+            let mut issues: Vec<NoteIssue> = dangling_wiki_links(note, &targets)
+                .into_iter()
+                .map(|target| NoteIssue::new(note, NoteIssueKind::DanglingWikiLink, target))
+                .collect();
+
+            if let Some(ref checker) = self.url_checker {
+                for url in extract_urls(&note.content) {
+                    let broken = match checked.get(&url) {
+                        Some(broken) => broken.clone(),
+                        None => {
+                            let broken = checker.check(&url).await?;
+                            checked.insert(url.clone(), broken.clone());
+                            broken
+                        }
+                    };
+                    if let Some(reason) = broken {
+                        issues.push(
+                            NoteIssue::new(note, NoteIssueKind::BrokenUrl, url).with_detail(reason),
+                        );
+                    }
+                }
+            }
+
+            found += issues.len();
+            self.issue_repo.replace_for_note(note.id, &issues).await?;
+        }
+
+        Ok(found)
+    }
+
+    /// Issues last found in one of the user's notes
+    pub async fn note_issues(&self, note_id: Uuid, user_id: Uuid) -> DomainResult<Vec<NoteIssue>> {
+        let note = self
+            .note_repo
+            .find_by_id(note_id)
+            .await?
+            .ok_or(DomainError::NoteNotFound(note_id))?;
+        self.policy
+            .authorize(user_id, Action::Read, &Resource::note(&note))
+            .await?;
+
+        self.issue_repo.find_by_note(note_id).await
+    }
+
+    /// Issues across all of the user's notes
+    pub async fn report(&self, user_id: Uuid) -> DomainResult<IssueReport> {
+        let issues = self.issue_repo.find_by_user(user_id).await?;
+        Ok(IssueReport::new(&issues))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_objects::NoteTitle;
+
+    #[test]
+    fn test_extract_urls_from_prose_and_markdown() {
+        let content = "See https://example.com. Docs: [guide](https://docs.rs/axum/latest) \
+                       and <http://old.example.org/page?x=1>, again https://example.com \
+                       but not https:// or httpx://nope or https:///path";
+
+        assert_eq!(
+            extract_urls(content),
+            vec![
+                "https://example.com",
+                "https://docs.rs/axum/latest",
+                "http://old.example.org/page?x=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dangling_wiki_links_skip_existing_notes() {
+        let user_id = Uuid::new_v4();
+        let target = Note::new(user_id, NoteTitle::try_from("Meeting Notes").ok(), "");
+        let note = Note::new(
+            user_id,
+            None,
+            "[[meeting notes]], [[Missing]], [[Missing|again]]",
+        );
+        let targets = LinkTargets::new(&[target, note.clone()]);
+
+        assert_eq!(dangling_wiki_links(&note, &targets), vec!["Missing"]);
+    }
+
+    #[test]
+    fn test_report_counts_issues_per_note() {
+        let user_id = Uuid::new_v4();
+        let quiet = Note::new(user_id, None, "");
+        let noisy = Note::new(user_id, None, "");
+        let issues = vec![
+            NoteIssue::new(&quiet, NoteIssueKind::DanglingWikiLink, "Missing"),
+            NoteIssue::new(&noisy, NoteIssueKind::BrokenUrl, "https://a.example")
+                .with_detail("HTTP 404"),
+            NoteIssue::new(&noisy, NoteIssueKind::DanglingWikiLink, "Gone"),
+        ];
+
+        let report = IssueReport::new(&issues);
+
+        assert_eq!(report.broken_urls, 1);
+        assert_eq!(report.dangling_wiki_links, 2);
+        assert_eq!(report.notes[0].note_id, noisy.id);
+        assert_eq!(report.notes[1].note_id, quiet.id);
+        assert_eq!(report.checked_at, Some(issues[2].found_at));
+    }
+
+    mod note_lint_service_tests {
+        use super::*;
+        use crate::repositories::tests::MockNoteRepository;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockNoteIssueRepository {
+            issues: Mutex<HashMap<Uuid, Vec<NoteIssue>>>,
+        }
+
+        #[async_trait::async_trait]
+        impl NoteIssueRepository for MockNoteIssueRepository {
+            async fn replace_for_note(
+                &self,
+                note_id: Uuid,
+                issues: &[NoteIssue],
+            ) -> DomainResult<()> {
+                self.issues.lock().unwrap().insert(note_id, issues.to_vec());
+                Ok(())
+            }
+
+            async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<NoteIssue>> {
+                Ok(self
+                    .issues
+                    .lock()
+                    .unwrap()
+                    .get(&note_id)
+                    .cloned()
+                    .unwrap_or_default())
+            }
+
+            async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteIssue>> {
+                Ok(self
+                    .issues
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .flatten()
+                    .filter(|issue| issue.user_id == user_id)
+                    .cloned()
+                    .collect())
+            }
+        }
+
+        /// Treats URLs containing `broken` as broken and counts checks
+        #[derive(Default)]
+        struct MockUrlChecker {
+            checks: Mutex<usize>,
+        }
+
+        #[async_trait::async_trait]
+        impl UrlChecker for MockUrlChecker {
+            async fn check(&self, url: &str) -> DomainResult<Option<String>> {
+                *self.checks.lock().unwrap() += 1;
+                Ok(url.contains("broken").then(|| "HTTP 404".to_string()))
+            }
+        }
+
+        #[tokio::test]
+        async fn test_lint_user_records_broken_urls_and_dangling_links() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let checker = Arc::new(MockUrlChecker::default());
+            let service = NoteLintService::new(
+                note_repo.clone(),
+                Arc::new(MockNoteIssueRepository::default()),
+            )
+            .with_url_checker(checker.clone());
+            let user_id = Uuid::new_v4();
+
+            let target = Note::new(user_id, NoteTitle::try_from("Plan").ok(), "");
+            let note = Note::new(
+                user_id,
+                None,
+                "[[Plan]] [[Missing]] https://broken.example https://ok.example",
+            );
+            let other = Note::new(user_id, None, "Same link: https://broken.example");
+            for n in [&target, &note, &other] {
+                note_repo.save(n).await.unwrap();
+            }
+
+            assert_eq!(service.lint_user(user_id).await.unwrap(), 3);
+            assert_eq!(*checker.checks.lock().unwrap(), 2);
+
+            let issues = service.note_issues(note.id, user_id).await.unwrap();
+            let found: Vec<(NoteIssueKind, &str)> = issues
+                .iter()
+                .map(|issue| (issue.kind, issue.target.as_str()))
+                .collect();
+            assert_eq!(
+                found,
+                vec![
+                    (NoteIssueKind::DanglingWikiLink, "Missing"),
+                    (NoteIssueKind::BrokenUrl, "https://broken.example"),
+                ]
+            );
+            assert!(matches!(
+                service.note_issues(note.id, Uuid::new_v4()).await,
+                Err(DomainError::Forbidden(_))
+            ));
+
+            let report = service.report(user_id).await.unwrap();
+            assert_eq!(report.broken_urls, 2);
+            assert_eq!(report.dangling_wiki_links, 1);
+            assert_eq!(report.notes[0].note_id, note.id);
+        }
+
+        #[tokio::test]
+        async fn test_lint_user_without_checker_only_checks_wiki_links() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let service = NoteLintService::new(
+                note_repo.clone(),
+                Arc::new(MockNoteIssueRepository::default()),
+            );
+            let user_id = Uuid::new_v4();
+            note_repo
+                .save(&Note::new(
+                    user_id,
+                    None,
+                    "https://broken.example [[Missing]]",
+                ))
+                .await
+                .unwrap();
+
+            assert_eq!(service.lint_user(user_id).await.unwrap(), 1);
+        }
+    }
+}
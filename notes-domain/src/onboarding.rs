@@ -0,0 +1,183 @@
+//! Welcome content for new accounts
+//!
+//! Each instance can describe starter tags and welcome notes that every new
+//! account gets, so the first visit does not open on an empty screen. Note
+//! titles and contents may use `{{email}}`, which is replaced with the new
+//! user's email address.
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::entities::User;
+use crate::errors::{DomainError, DomainResult};
+use crate::services::{CreateNoteRequest, NoteService, TagService};
+use crate::value_objects::{NoteTitle, TagName};
+
+/// Placeholder replaced with the new user's email address
+pub const EMAIL_PLACEHOLDER: &str = "{{email}}";
+
+/// What new accounts start with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct OnboardingTemplate {
+    /// Tags created even when no welcome note uses them
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Welcome notes, listed top to bottom as they should appear
+    #[serde(default)]
+    pub notes: Vec<WelcomeNote>,
+}
+
+/// A note every new account gets
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WelcomeNote {
+    pub title: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub is_pinned: bool,
+}
+
+/// A welcome note filled in for one user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedWelcomeNote {
+    pub title: Option<NoteTitle>,
+    pub content: String,
+    pub tags: Vec<TagName>,
+    pub is_pinned: bool,
+}
+
+fn parse_tags(names: &[String]) -> DomainResult<Vec<TagName>> {
+    names
+        .iter()
+        .map(|name| TagName::try_from(name.as_str()).map_err(Into::into))
+        .collect()
+}
+
+impl OnboardingTemplate {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.notes.is_empty()
+    }
+
+    /// Check every tag and title, so a broken template is caught at startup
+    /// rather than on each registration
+    pub fn validate(&self) -> DomainResult<()> {
+        self.starter_tags()?;
+        for note in &self.notes {
+            note.render("user@example.com")?;
+        }
+        Ok(())
+    }
+
+    pub fn starter_tags(&self) -> DomainResult<Vec<TagName>> {
+        parse_tags(&self.tags)
+    }
+}
+
+impl WelcomeNote {
+    /// Fill in the placeholders for the user with this email address
+    pub fn render(&self, email: &str) -> DomainResult<RenderedWelcomeNote> {
+        let fill = |text: &str| text.replace(EMAIL_PLACEHOLDER, email);
+        let title = self
+            .title
+            .as_deref()
+            .map(|title| NoteTitle::try_from(fill(title)))
+            .transpose()?;
+
+        Ok(RenderedWelcomeNote {
+            title,
+            content: fill(&self.content),
+            tags: parse_tags(&self.tags)?,
+            is_pinned: self.is_pinned,
+        })
+    }
+}
+
+/// Service that gives new accounts the instance's welcome notes and starter tags
+pub struct OnboardingService {
+    note_service: Arc<NoteService>,
+    tag_service: Arc<TagService>,
+    template: OnboardingTemplate,
+}
+
+impl OnboardingService {
+    /// Fails if the template has invalid tags or titles
+    pub fn new(
+        note_service: Arc<NoteService>,
+        tag_service: Arc<TagService>,
+        template: OnboardingTemplate,
+    ) -> DomainResult<Self> {
+        template.validate()?;
+        Ok(Self {
+            note_service,
+            tag_service,
+            template,
+        })
+    }
+
+    /// Create the welcome content for a new user
+    pub async fn seed(&self, user: &User) -> DomainResult<()> {
+        for name in self.template.starter_tags()? {
+            match self.tag_service.create_tag(user.id, name).await {
+                Ok(_) | Err(DomainError::TagAlreadyExists(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Notes are listed newest first, so the first one is created last
+        for note in self.template.notes.iter().rev() {
+            let note = note.render(user.email_str())?;
+            self.note_service
+                .create_note(CreateNoteRequest {
+                    user_id: user.id,
+                    title: note.title,
+                    content: note.content,
+                    tags: note.tags,
+                    color: None,
+                    is_pinned: note.is_pinned,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(json: &str) -> OnboardingTemplate {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_render_fills_in_the_email() {
+        let template = template(
+            r#"{"tags": ["ideas"], "notes": [{"title": "Welcome", "content": "Signed in as {{email}}", "tags": ["Getting-Started"]}]}"#,
+        );
+        let note = template.notes[0].render("ada@example.com").unwrap();
+        assert_eq!(note.content, "Signed in as ada@example.com");
+        assert_eq!(note.title.unwrap().as_ref(), "Welcome");
+        assert_eq!(
+            note.tags,
+            vec![TagName::try_from("getting-started").unwrap()]
+        );
+        assert!(!note.is_pinned);
+        assert!(template.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_tags() {
+        assert!(template(r#"{"tags": [""]}"#).validate().is_err());
+        assert!(
+            template(r#"{"notes": [{"content": "", "tags": [" "]}]}"#)
+                .validate()
+                .is_err()
+        );
+        assert!(OnboardingTemplate::default().is_empty());
+    }
+}
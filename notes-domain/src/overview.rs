@@ -0,0 +1,117 @@
+//! Instance-wide metrics for the admin dashboard
+
+use chrono::{Duration, NaiveDate};
+
+use crate::errors::{DomainError, DomainResult};
+
+/// Days covered by the overview when no window is given
+pub const DEFAULT_OVERVIEW_DAYS: u32 = 30;
+
+/// Longest window the overview can cover
+pub const MAX_OVERVIEW_DAYS: u32 = 365;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserCounts {
+    pub total: u64,
+    /// Users who signed up within the window
+    pub new: u64,
+    /// Users who created or edited a note within the window
+    pub active: u64,
+}
+
+/// Number of items on one UTC day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyCount {
+    pub date: NaiveDate,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobCounts {
+    pub running: u64,
+    /// Jobs that failed within the window
+    pub failed: u64,
+}
+
+/// Metrics read from the database for the window
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatabaseMetrics {
+    pub users: UserCounts,
+    /// Notes outside the trash
+    pub notes: u64,
+    pub trashed_notes: u64,
+    /// Notes created per day; days without notes may be missing
+    pub notes_created: Vec<DailyCount>,
+    /// Size of the database file
+    pub database_bytes: u64,
+    /// Approximate bytes of note and version titles and content
+    pub content_bytes: u64,
+    pub jobs: JobCounts,
+}
+
+/// Validate the requested window, falling back to the default
+pub fn overview_window(days: Option<u32>) -> DomainResult<u32> {
+    match days.unwrap_or(DEFAULT_OVERVIEW_DAYS) {
+        days @ 1..=MAX_OVERVIEW_DAYS => Ok(days),
+        _ => Err(DomainError::validation(format!(
+            "Window must be between 1 and {} days",
+            MAX_OVERVIEW_DAYS
+        ))),
+    }
+}
+
+/// One entry per day from `from` to `to` (inclusive), taking counts from
+/// `counts` and zero for the days it is missing
+pub fn daily_series(counts: &[DailyCount], from: NaiveDate, to: NaiveDate) -> Vec<DailyCount> {
+    let mut series = Vec::new();
+    let mut date = from;
+    while date <= to {
+        let count = counts
+            .iter()
+            .find(|c| c.date == date)
+            .map_or(0, |c| c.count);
+        series.push(DailyCount { date, count });
+        date += Duration::days(1);
+    }
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, d).unwrap()
+    }
+
+    #[test]
+    fn test_overview_window() {
+        assert_eq!(overview_window(None).unwrap(), DEFAULT_OVERVIEW_DAYS);
+        assert_eq!(overview_window(Some(7)).unwrap(), 7);
+        assert!(overview_window(Some(0)).is_err());
+        assert!(overview_window(Some(MAX_OVERVIEW_DAYS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_daily_series_fills_missing_days() {
+        let counts = vec![
+            DailyCount {
+                date: day(2),
+                count: 3,
+            },
+            // Outside the range
+            DailyCount {
+                date: day(9),
+                count: 1,
+            },
+        ];
+
+        let series = daily_series(&counts, day(1), day(3));
+
+        assert_eq!(
+            series.iter().map(|c| c.count).collect::<Vec<_>>(),
+            vec![0, 3, 0]
+        );
+        assert_eq!(series[0].date, day(1));
+    }
+}
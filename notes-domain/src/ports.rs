@@ -1,25 +1,167 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use uuid::Uuid;
 
-use crate::entities::{Note, NoteLink};
+use crate::authorization::{Action, Resource};
+use crate::bookmarks::LinkPreview;
+use crate::diagrams::DiagramKind;
+use crate::entities::{Note, NoteLink, RelatedNote, Tag};
 use crate::errors::DomainResult;
+use crate::event_log::LoggedEvent;
+use crate::events::DomainEvent;
+use crate::images::FetchedImage;
+use crate::language::Language;
+use crate::speech::AudioFormat;
+use crate::value_objects::Email;
+use crate::writing::ProofreadSuggestion;
 
 /// Defines how to generate vector embeddings from text.
 #[async_trait]
 pub trait EmbeddingGenerator: Send + Sync {
     /// Generate a vector embedding for the given text.
     async fn generate_embedding(&self, text: &str) -> DomainResult<Vec<f32>>;
+
+    /// Name and version of the model, recorded with stored vectors so they
+    /// can be recomputed when the model changes
+    fn model_name(&self) -> &str;
+
+    /// Generate embeddings for several texts, in the same order.
+    ///
+    /// Adapters backed by a model should override this to run one batched
+    /// inference instead of one per text.
+    async fn generate_embeddings(&self, texts: &[String]) -> DomainResult<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.generate_embedding(text).await?);
+        }
+        Ok(embeddings)
+    }
+
+    /// Generate embeddings for several texts, each with the language it is
+    /// written in when known.
+    ///
+    /// Adapters that can pick a model or instruction per language should
+    /// override this; the default ignores the hints.
+    async fn generate_embeddings_in(
+        &self,
+        texts: &[String],
+        _languages: &[Option<Language>],
+    ) -> DomainResult<Vec<Vec<f32>>> {
+        self.generate_embeddings(texts).await
+    }
+}
+
+/// Metadata stored alongside a vector
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorPayload {
+    pub user_id: Uuid,
+    pub note_id: Uuid,
+    /// Position of the embedded chunk within the note (0 for whole notes)
+    pub chunk_index: u32,
+    /// When the embedded note content was last updated
+    pub updated_at: DateTime<Utc>,
+    /// Embedding model that produced the vector
+    pub model: String,
+    /// [`content_hash`] of the embedded text
+    pub content_hash: String,
+    /// Language the embedded text is written in, when detected
+    pub language: Option<Language>,
+}
+
+impl VectorPayload {
+    /// Payload for an embedding of the whole note
+    pub fn for_note(note: &Note, model: impl Into<String>) -> Self {
+        Self {
+            user_id: note.user_id,
+            note_id: note.id,
+            chunk_index: 0,
+            updated_at: note.updated_at,
+            model: model.into(),
+            content_hash: content_hash(&note.content),
+            language: note.language.clone(),
+        }
+    }
+
+    /// Whether the vector was computed by `model` from exactly this text
+    pub fn embeds(&self, text: &str, model: &str) -> bool {
+        self.model == model && self.content_hash == content_hash(text)
+    }
+}
+
+/// Stable fingerprint of embedded text (64-bit FNV-1a, hex encoded).
+///
+/// Only used to detect unchanged content, so it need not be cryptographic,
+/// but it must not change between releases or every note is re-embedded.
+pub fn content_hash(text: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let hash = text.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Restricts which stored vectors a similarity search considers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VectorFilter {
+    /// Only vectors of this user's notes
+    pub user_id: Option<Uuid>,
+    /// Skip vectors of this note
+    pub exclude_note_id: Option<Uuid>,
+}
+
+impl VectorFilter {
+    /// Other notes of the note's owner
+    pub fn related_to(note: &Note) -> Self {
+        Self {
+            user_id: Some(note.user_id),
+            exclude_note_id: Some(note.id),
+        }
+    }
+
+    /// Notes of `user_id`
+    pub fn owned_by(user_id: Uuid) -> Self {
+        Self {
+            user_id: Some(user_id),
+            exclude_note_id: None,
+        }
+    }
 }
 
 /// Defines how to store and retrieve vectors.
 #[async_trait]
 pub trait VectorStore: Send + Sync {
-    /// Upsert a vector for a given note ID.
-    async fn upsert(&self, id: Uuid, vector: &[f32]) -> DomainResult<()>;
+    /// Upsert a vector for a given note ID, replacing its payload.
+    async fn upsert(&self, id: Uuid, vector: &[f32], payload: &VectorPayload) -> DomainResult<()>;
 
-    /// Find similar items to the given vector.
+    /// Payloads stored for the given IDs; IDs without a vector (or with a
+    /// payload that cannot be read) are left out
+    async fn get_payloads(&self, ids: &[Uuid]) -> DomainResult<HashMap<Uuid, VectorPayload>>;
+
+    /// Find items similar to the given vector among those matching `filter`.
     /// Returns a list of (NoteID, Score) tuples.
-    async fn find_similar(&self, vector: &[f32], limit: usize) -> DomainResult<Vec<(Uuid, f32)>>;
+    async fn find_similar(
+        &self,
+        vector: &[f32],
+        limit: usize,
+        filter: &VectorFilter,
+    ) -> DomainResult<Vec<(Uuid, f32)>>;
+
+    /// Remove the vector stored for an ID (no-op if there is none)
+    async fn delete(&self, id: Uuid) -> DomainResult<()>;
+
+    /// Remove every vector of a user's notes
+    async fn delete_by_user(&self, user_id: Uuid) -> DomainResult<()>;
+
+    /// Number of stored vectors
+    async fn count(&self) -> DomainResult<u64>;
+
+    /// IDs of every stored vector
+    async fn ids(&self) -> DomainResult<Vec<Uuid>>;
 }
 
 /// Defines how to persist note links.
@@ -32,7 +174,200 @@ pub trait LinkRepository: Send + Sync {
     async fn delete_links_for_source(&self, source_note_id: Uuid) -> DomainResult<()>;
 
     /// Get links for a specific source note.
-    async fn get_links_for_note(&self, source_note_id: Uuid) -> DomainResult<Vec<NoteLink>>;
+    /// Links scoring below `min_score` are skipped; at most `limit` are returned,
+    /// best first, each with a preview of the linked note.
+    async fn get_links_for_note(
+        &self,
+        source_note_id: Uuid,
+        limit: usize,
+        min_score: f32,
+    ) -> DomainResult<Vec<RelatedNote>>;
+
+    /// Get all links between notes owned by a user
+    async fn get_links_for_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteLink>>;
+}
+
+/// Defines how to render notes into a printable PDF document.
+#[async_trait]
+pub trait PdfRenderer: Send + Sync {
+    /// Render the given notes into a single PDF, one note per page, with
+    /// timestamps shown in `tz`.
+    async fn render_pdf(&self, title: &str, notes: &[Note], tz: Tz) -> DomainResult<Vec<u8>>;
+}
+
+/// Defines how user passwords are hashed and verified.
+///
+/// Hashing is slow on purpose; implementations keep it off the async
+/// runtime.
+#[async_trait]
+pub trait PasswordHasher: Send + Sync {
+    /// Hash a password with the current parameters.
+    async fn hash(&self, password: &str) -> DomainResult<String>;
+
+    /// Check a password against a stored hash.
+    /// Returns `Ok(false)` on mismatch and for hashes in formats it cannot
+    /// verify, so those fail like a wrong password.
+    async fn verify(&self, password: &str, hash: &str) -> DomainResult<bool>;
+
+    /// Whether a stored hash uses outdated parameters or algorithms and
+    /// should be replaced after the next successful login.
+    fn needs_rehash(&self, hash: &str) -> bool;
+}
+
+/// A plain-text email to deliver to a user
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: Email,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Defines how to deliver transactional emails (verification links etc.).
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    /// Send a single email.
+    async fn send(&self, message: &EmailMessage) -> DomainResult<()>;
+}
+
+/// A puzzle issued by a self-hosted challenge provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeTicket {
+    /// Opaque challenge string the client solves
+    pub challenge: String,
+    /// Leading zero bits the solution's hash must have
+    pub difficulty: u8,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Defines how to tell people from bots on public endpoints such as registration.
+#[async_trait]
+pub trait ChallengeVerifier: Send + Sync {
+    /// Provider name advertised to clients, so they show the matching widget.
+    fn provider(&self) -> &'static str;
+
+    /// Public key the client widget is configured with, for hosted providers.
+    fn site_key(&self) -> Option<&str> {
+        None
+    }
+
+    /// Issue a challenge for the client to solve.
+    /// Hosted providers issue their own in the widget and return `None`.
+    async fn issue(&self) -> DomainResult<Option<ChallengeTicket>> {
+        Ok(None)
+    }
+
+    /// Check a client's response.
+    /// Returns `Ok(false)` for responses that are invalid, expired or already used.
+    async fn verify(&self, response: &str) -> DomainResult<bool>;
+}
+
+/// Generates text from a prompt, such as a language model.
+#[async_trait]
+pub trait TextGenerator: Send + Sync {
+    /// The generated answer to `prompt`
+    async fn generate(&self, prompt: &str) -> DomainResult<String>;
+}
+
+/// Points out spelling and grammar mistakes, such as a LanguageTool server.
+#[async_trait]
+pub trait Proofreader: Send + Sync {
+    /// Suggestions for `text`, in the order they appear. `language` is a
+    /// hint; adapters detect the language themselves without one.
+    async fn check(
+        &self,
+        text: &str,
+        language: Option<&Language>,
+    ) -> DomainResult<Vec<ProofreadSuggestion>>;
+}
+
+/// Draws diagrams from their source, such as a Kroki server.
+#[async_trait]
+pub trait DiagramRenderer: Send + Sync {
+    /// The diagram `source` describes, as an SVG document.
+    async fn render_svg(&self, kind: DiagramKind, source: &str) -> DomainResult<String>;
+}
+
+/// Turns text into speech, such as a local Piper voice or a hosted API.
+#[async_trait]
+pub trait SpeechSynthesizer: Send + Sync {
+    /// `text` read aloud and encoded as `format`. `language` picks a voice
+    /// where the adapter has several; otherwise its default voice reads.
+    async fn synthesize(
+        &self,
+        text: &str,
+        language: Option<&Language>,
+        format: AudioFormat,
+    ) -> DomainResult<Vec<u8>>;
+}
+
+/// Turns speech into text, such as a local whisper.cpp model or a hosted API.
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    /// What is said in `audio`, encoded as `content_type`. `language` is a
+    /// hint; adapters detect the language themselves without one.
+    async fn transcribe(
+        &self,
+        audio: &[u8],
+        content_type: &str,
+        language: Option<&Language>,
+    ) -> DomainResult<String>;
+}
+
+/// Checks whether external links still load.
+#[async_trait]
+pub trait UrlChecker: Send + Sync {
+    /// Returns why the URL is broken (e.g. `HTTP 404`), or `None` if it loads.
+    /// Errors are reserved for failures of the checker itself, not the URL.
+    async fn check(&self, url: &str) -> DomainResult<Option<String>>;
+}
+
+/// Fetches what a bookmarked page says about itself.
+#[async_trait]
+pub trait LinkPreviewFetcher: Send + Sync {
+    /// Returns `None` if the page cannot be loaded or is not HTML.
+    /// Errors are reserved for failures of the fetcher itself, not the page.
+    async fn fetch(&self, url: &str) -> DomainResult<Option<LinkPreview>>;
+}
+
+/// Loads external images for readers of notes.
+#[async_trait]
+pub trait ImageFetcher: Send + Sync {
+    /// Returns `None` if the image cannot be loaded, is not one of the
+    /// [`PROXIED_IMAGE_TYPES`](crate::images::PROXIED_IMAGE_TYPES) or is
+    /// larger than `max_bytes`. Errors are reserved for failures of the
+    /// fetcher itself, not the image.
+    async fn fetch_image(&self, url: &str, max_bytes: usize) -> DomainResult<Option<FetchedImage>>;
+}
+
+/// Signs values so the instance can tell the ones it handed out, like the
+/// image URLs of the image proxy.
+pub trait Signer: Send + Sync {
+    fn sign(&self, value: &str) -> String;
+
+    /// Whether `signature` is what [`Signer::sign`] gives for `value`
+    fn verify(&self, value: &str, signature: &str) -> bool;
+}
+
+/// Decides whether a user may perform an action on a resource.
+/// Services consult it for every access to a single resource.
+#[async_trait]
+pub trait AuthorizationPolicy: Send + Sync {
+    /// `Ok` if `subject` may perform `action`, otherwise an
+    /// `Forbidden` error describing what was denied
+    async fn authorize(
+        &self,
+        subject: Uuid,
+        action: Action,
+        resource: &Resource,
+    ) -> DomainResult<()>;
+}
+
+/// Reacts to logged events in the same process, e.g. by persisting them.
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    /// Handle one event. Errors are logged by the dispatcher and do not fail
+    /// the change that caused the event.
+    async fn handle(&self, event: &LoggedEvent) -> DomainResult<()>;
 }
 
 /// Port for publishing domain events to a message broker.
@@ -40,12 +375,46 @@ pub trait LinkRepository: Send + Sync {
 /// without coupling to a specific messaging implementation.
 #[async_trait]
 pub trait MessageBroker: Send + Sync {
-    /// Publish an event when a note is created or updated.
-    async fn publish_note_updated(&self, note: &Note) -> DomainResult<()>;
+    /// Publish an event on its subject.
+    async fn publish(&self, event: &DomainEvent) -> DomainResult<()>;
 
-    /// Subscribe to note update events.
-    /// Returns a stream of notes that have been updated.
-    async fn subscribe_note_updates(
+    /// Subscribe to all known event subjects.
+    /// Messages that cannot be decoded (e.g. unknown event types) are
+    /// skipped.
+    async fn subscribe(
         &self,
-    ) -> DomainResult<std::pin::Pin<Box<dyn futures_core::Stream<Item = Note> + Send>>>;
+    ) -> DomainResult<std::pin::Pin<Box<dyn futures_core::Stream<Item = DomainEvent> + Send>>>;
+
+    /// Publish that a note was created or updated.
+    async fn publish_note_updated(&self, note: &Note) -> DomainResult<()> {
+        self.publish(&DomainEvent::note_updated(note.clone())).await
+    }
+
+    /// Publish that a note was permanently deleted.
+    async fn publish_note_deleted(&self, user_id: Uuid, note_id: Uuid) -> DomainResult<()> {
+        self.publish(&DomainEvent::note_deleted(user_id, note_id))
+            .await
+    }
+
+    /// Publish that a tag was created or renamed.
+    async fn publish_tag_updated(&self, tag: &Tag) -> DomainResult<()> {
+        self.publish(&DomainEvent::tag_updated(tag.clone())).await
+    }
+
+    /// Publish that a user and their data were deleted.
+    async fn publish_user_deleted(&self, user_id: Uuid) -> DomainResult<()> {
+        self.publish(&DomainEvent::user_deleted(user_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_is_stable_fnv1a() {
+        // Reference values of 64-bit FNV-1a
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_eq!(content_hash("a"), "af63dc4c8601ec8c");
+    }
 }
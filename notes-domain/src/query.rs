@@ -0,0 +1,224 @@
+//! Structured note queries
+//!
+//! A JSON filter document that combines tag, date, color, pin, archive and
+//! text predicates with `and`/`or`/`not`, plus sorting and pagination.
+//! Repository adapters compile it into parameterised queries; it is meant
+//! to back saved searches as well as ad-hoc queries.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{Note, NoteSortOrder};
+use crate::errors::{DomainError, DomainResult};
+
+/// Notes returned when a query sets no limit
+pub const DEFAULT_QUERY_LIMIT: usize = 50;
+
+/// Maximum number of notes a single query may return
+pub const MAX_QUERY_LIMIT: usize = 200;
+
+/// Maximum nesting of `and`/`or`/`not`
+pub const MAX_QUERY_DEPTH: usize = 8;
+
+/// Maximum number of predicates in one filter document
+pub const MAX_QUERY_PREDICATES: usize = 64;
+
+/// A condition on a note, e.g. `{"and": [{"tag": "work"}, {"not": {"pinned": true}}]}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotePredicate {
+    /// All predicates hold (an empty list always matches)
+    And(Vec<NotePredicate>),
+    /// Any predicate holds (an empty list never matches)
+    Or(Vec<NotePredicate>),
+    Not(Box<NotePredicate>),
+    /// The note carries the tag with this name
+    Tag(String),
+    Color(String),
+    Pinned(bool),
+    Archived(bool),
+    /// Title or content contains the text, ignoring ASCII case
+    Text(String),
+    CreatedAfter(DateTime<Utc>),
+    CreatedBefore(DateTime<Utc>),
+    UpdatedAfter(DateTime<Utc>),
+    UpdatedBefore(DateTime<Utc>),
+}
+
+impl NotePredicate {
+    /// Whether the note satisfies the predicate
+    pub fn matches(&self, note: &Note) -> bool {
+        match self {
+            Self::And(predicates) => predicates.iter().all(|p| p.matches(note)),
+            Self::Or(predicates) => predicates.iter().any(|p| p.matches(note)),
+            Self::Not(predicate) => !predicate.matches(note),
+            Self::Tag(name) => {
+                let name = normalize_tag(name);
+                note.tags.iter().any(|t| t.name_str() == name)
+            }
+            Self::Color(color) => note.color == *color,
+            Self::Pinned(pinned) => note.is_pinned == *pinned,
+            Self::Archived(archived) => note.is_archived == *archived,
+            Self::Text(text) => {
+                let text = text.to_ascii_lowercase();
+                note.title_str().to_ascii_lowercase().contains(&text)
+                    || note.content.to_ascii_lowercase().contains(&text)
+            }
+            Self::CreatedAfter(at) => note.created_at > *at,
+            Self::CreatedBefore(at) => note.created_at < *at,
+            Self::UpdatedAfter(at) => note.updated_at > *at,
+            Self::UpdatedBefore(at) => note.updated_at < *at,
+        }
+    }
+
+    /// Nesting depth, counting this predicate
+    fn depth(&self) -> usize {
+        match self {
+            Self::And(predicates) | Self::Or(predicates) => {
+                1 + predicates.iter().map(Self::depth).max().unwrap_or(0)
+            }
+            Self::Not(predicate) => 1 + predicate.depth(),
+            _ => 1,
+        }
+    }
+
+    /// Number of predicates, counting this one
+    fn size(&self) -> usize {
+        match self {
+            Self::And(predicates) | Self::Or(predicates) => {
+                1 + predicates.iter().map(Self::size).sum::<usize>()
+            }
+            Self::Not(predicate) => 1 + predicate.size(),
+            _ => 1,
+        }
+    }
+}
+
+/// Tag names are stored trimmed and lowercase
+pub fn normalize_tag(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// A filter document with sorting and pagination
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoteQuery {
+    /// Matches every note when absent
+    pub filter: Option<NotePredicate>,
+    pub sort: NoteSortOrder,
+    /// Defaults to [`DEFAULT_QUERY_LIMIT`]
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl NoteQuery {
+    /// Reject documents that are too large or deep to run
+    pub fn validate(&self) -> DomainResult<()> {
+        if self.limit.is_some_and(|l| l == 0 || l > MAX_QUERY_LIMIT) {
+            return Err(DomainError::validation(format!(
+                "limit must be between 1 and {}",
+                MAX_QUERY_LIMIT
+            )));
+        }
+        let Some(ref filter) = self.filter else {
+            return Ok(());
+        };
+        if filter.depth() > MAX_QUERY_DEPTH {
+            return Err(DomainError::validation(format!(
+                "Filter is nested deeper than {} levels",
+                MAX_QUERY_DEPTH
+            )));
+        }
+        if filter.size() > MAX_QUERY_PREDICATES {
+            return Err(DomainError::validation(format!(
+                "Filter has more than {} predicates",
+                MAX_QUERY_PREDICATES
+            )));
+        }
+        Ok(())
+    }
+
+    /// Page size to apply
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_QUERY_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Tag;
+    use crate::value_objects::{NoteTitle, TagName};
+    use uuid::Uuid;
+
+    fn note() -> Note {
+        let user_id = Uuid::new_v4();
+        let mut note = Note::new(user_id, NoteTitle::try_from("Groceries").ok(), "Buy milk");
+        note.tags
+            .push(Tag::new(TagName::try_from("home").unwrap(), user_id));
+        note
+    }
+
+    #[test]
+    fn test_parses_nested_document() {
+        let query: NoteQuery = serde_json::from_str(
+            r#"{"filter": {"and": [{"tag": "home"}, {"not": {"pinned": true}}]}, "limit": 10}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.filter,
+            Some(NotePredicate::And(vec![
+                NotePredicate::Tag("home".to_string()),
+                NotePredicate::Not(Box::new(NotePredicate::Pinned(true))),
+            ]))
+        );
+        assert_eq!(query.limit(), 10);
+        assert_eq!(query.sort, NoteSortOrder::UpdatedDesc);
+    }
+
+    #[test]
+    fn test_predicates_match_notes() {
+        let note = note();
+
+        assert!(NotePredicate::Tag(" Home ".to_string()).matches(&note));
+        assert!(NotePredicate::Text("MILK".to_string()).matches(&note));
+        assert!(
+            NotePredicate::Or(vec![
+                NotePredicate::Pinned(true),
+                NotePredicate::Text("grocer".to_string()),
+            ])
+            .matches(&note)
+        );
+        assert!(!NotePredicate::Or(vec![]).matches(&note));
+        assert!(NotePredicate::And(vec![]).matches(&note));
+        assert!(!NotePredicate::CreatedAfter(Utc::now()).matches(&note));
+    }
+
+    #[test]
+    fn test_validate_rejects_oversized_documents() {
+        let mut deep = NotePredicate::Pinned(true);
+        for _ in 0..MAX_QUERY_DEPTH {
+            deep = NotePredicate::Not(Box::new(deep));
+        }
+        let query = NoteQuery {
+            filter: Some(deep),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+
+        let wide = NotePredicate::Or(vec![NotePredicate::Pinned(true); MAX_QUERY_PREDICATES]);
+        let query = NoteQuery {
+            filter: Some(wide),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+
+        let query = NoteQuery {
+            limit: Some(MAX_QUERY_LIMIT + 1),
+            ..Default::default()
+        };
+        assert!(query.validate().is_err());
+        assert!(NoteQuery::default().validate().is_ok());
+    }
+}
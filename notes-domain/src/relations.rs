@@ -0,0 +1,410 @@
+//! Typed relations between notes
+//!
+//! Unlike `[[wiki-links]]`, which live in note content, relations are set
+//! explicitly and carry a meaning: one note is the parent of another,
+//! references it, or is blocked by it. Parent relations form a forest: a
+//! note has at most one parent and can never be its own ancestor.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authorization::{Action, OwnerPolicy, Resource};
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::AuthorizationPolicy;
+use crate::repositories::NoteRelationRepository;
+use crate::services::NoteService;
+
+/// How the source note relates to the target note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    /// The source is the parent of the target
+    ParentOf,
+    /// The source refers to the target
+    References,
+    /// The source cannot proceed until the target is done
+    BlockedBy,
+}
+
+impl RelationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ParentOf => "parent_of",
+            Self::References => "references",
+            Self::BlockedBy => "blocked_by",
+        }
+    }
+}
+
+impl fmt::Display for RelationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RelationKind {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parent_of" => Ok(Self::ParentOf),
+            "references" => Ok(Self::References),
+            "blocked_by" => Ok(Self::BlockedBy),
+            other => Err(DomainError::validation(format!(
+                "Unknown relation kind: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A directed, typed relation between two of a user's notes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteRelation {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+    pub kind: RelationKind,
+    pub created_at: DateTime<Utc>,
+}
+
+impl NoteRelation {
+    pub fn new(user_id: Uuid, source_id: Uuid, kind: RelationKind, target_id: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            source_id,
+            target_id,
+            kind,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Check the relation against the user's `existing` ones before adding it
+    pub fn validate(&self, existing: &[NoteRelation]) -> DomainResult<()> {
+        if self.source_id == self.target_id {
+            return Err(DomainError::validation(
+                "A note cannot be related to itself",
+            ));
+        }
+        if existing.iter().any(|r| {
+            r.kind == self.kind && r.source_id == self.source_id && r.target_id == self.target_id
+        }) {
+            return Err(DomainError::validation(format!(
+                "The notes are already related as {}",
+                self.kind
+            )));
+        }
+        if self.kind != RelationKind::ParentOf {
+            return Ok(());
+        }
+
+        let parents: HashMap<Uuid, Uuid> = existing
+            .iter()
+            .filter(|r| r.kind == RelationKind::ParentOf)
+            .map(|r| (r.target_id, r.source_id))
+            .collect();
+        if parents.contains_key(&self.target_id) {
+            return Err(DomainError::validation("The note already has a parent"));
+        }
+
+        // Walking up from the new parent must not reach the new child
+        let mut seen = HashSet::new();
+        let mut ancestor = Some(self.source_id);
+        while let Some(id) = ancestor {
+            if id == self.target_id {
+                return Err(DomainError::validation(
+                    "A note cannot be the parent of one of its ancestors",
+                ));
+            }
+            if !seen.insert(id) {
+                break;
+            }
+            ancestor = parents.get(&id).copied();
+        }
+        Ok(())
+    }
+}
+
+/// Service for typed relations between a user's notes
+pub struct NoteRelationService {
+    relation_repo: Arc<dyn NoteRelationRepository>,
+    notes: Arc<NoteService>,
+    policy: Arc<dyn AuthorizationPolicy>,
+}
+
+impl NoteRelationService {
+    pub fn new(relation_repo: Arc<dyn NoteRelationRepository>, notes: Arc<NoteService>) -> Self {
+        Self {
+            relation_repo,
+            notes,
+            policy: Arc::new(OwnerPolicy),
+        }
+    }
+
+    /// Decide who may remove relations between notes; owners only by default
+    pub fn with_authorization_policy(mut self, policy: Arc<dyn AuthorizationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Relate `source_id` to `target_id`; both notes must belong to the user
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        source_id: Uuid,
+        kind: RelationKind,
+        target_id: Uuid,
+    ) -> DomainResult<NoteRelation> {
+        self.notes.get_note(source_id, user_id).await?;
+        self.notes.get_note(target_id, user_id).await?;
+
+        let relation = NoteRelation::new(user_id, source_id, kind, target_id);
+        let existing = self.relation_repo.find_by_user(user_id).await?;
+        relation.validate(&existing)?;
+
+        self.relation_repo.save(&relation).await?;
+        Ok(relation)
+    }
+
+    /// Relations of a note, in either direction
+    pub async fn list_for_note(
+        &self,
+        note_id: Uuid,
+        user_id: Uuid,
+    ) -> DomainResult<Vec<NoteRelation>> {
+        self.notes.get_note(note_id, user_id).await?;
+        self.relation_repo.find_by_note(note_id).await
+    }
+
+    pub async fn list(&self, user_id: Uuid) -> DomainResult<Vec<NoteRelation>> {
+        self.relation_repo.find_by_user(user_id).await
+    }
+
+    pub async fn delete(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
+        let relation = self
+            .relation_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::RelationNotFound(id))?;
+
+        self.policy
+            .authorize(user_id, Action::Delete, &Resource::relation(&relation))
+            .await?;
+
+        self.relation_repo.delete(id).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn relation(source: Uuid, kind: RelationKind, target: Uuid) -> NoteRelation {
+        NoteRelation::new(Uuid::nil(), source, kind, target)
+    }
+
+    #[test]
+    fn test_parent_relations_cannot_form_cycles() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let existing = vec![
+            relation(a, RelationKind::ParentOf, b),
+            relation(b, RelationKind::ParentOf, c),
+        ];
+
+        assert!(
+            relation(c, RelationKind::ParentOf, a)
+                .validate(&existing)
+                .is_err()
+        );
+        assert!(
+            relation(a, RelationKind::ParentOf, c)
+                .validate(&existing)
+                .is_err(),
+            "c already has a parent"
+        );
+        assert!(
+            relation(c, RelationKind::References, a)
+                .validate(&existing)
+                .is_ok()
+        );
+        assert!(
+            relation(c, RelationKind::ParentOf, Uuid::new_v4())
+                .validate(&existing)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_rejects_self_and_duplicate_relations() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let existing = vec![relation(a, RelationKind::BlockedBy, b)];
+
+        assert!(
+            relation(a, RelationKind::References, a)
+                .validate(&[])
+                .is_err()
+        );
+        assert!(
+            relation(a, RelationKind::BlockedBy, b)
+                .validate(&existing)
+                .is_err()
+        );
+        assert!(
+            relation(b, RelationKind::BlockedBy, a)
+                .validate(&existing)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_kind_round_trips_through_str() {
+        for kind in [
+            RelationKind::ParentOf,
+            RelationKind::References,
+            RelationKind::BlockedBy,
+        ] {
+            assert_eq!(kind.as_str().parse::<RelationKind>().unwrap(), kind);
+        }
+        assert!("child_of".parse::<RelationKind>().is_err());
+    }
+
+    pub(crate) mod relation_service_tests {
+        use super::*;
+        use crate::entities::Note;
+        use crate::repositories::tests::MockNoteRepository;
+        use crate::services::CreateNoteRequest;
+        use crate::services::tests::*;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        pub(crate) struct MockNoteRelationRepository {
+            relations: Mutex<Vec<NoteRelation>>,
+        }
+
+        #[async_trait::async_trait]
+        impl NoteRelationRepository for MockNoteRelationRepository {
+            async fn save(&self, relation: &NoteRelation) -> DomainResult<()> {
+                self.relations.lock().unwrap().push(relation.clone());
+                Ok(())
+            }
+
+            async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<NoteRelation>> {
+                let relations = self.relations.lock().unwrap();
+                Ok(relations.iter().find(|r| r.id == id).cloned())
+            }
+
+            async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteRelation>> {
+                let relations = self.relations.lock().unwrap();
+                Ok(relations
+                    .iter()
+                    .filter(|r| r.user_id == user_id)
+                    .cloned()
+                    .collect())
+            }
+
+            async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<NoteRelation>> {
+                let relations = self.relations.lock().unwrap();
+                Ok(relations
+                    .iter()
+                    .filter(|r| r.source_id == note_id || r.target_id == note_id)
+                    .cloned()
+                    .collect())
+            }
+
+            async fn delete(&self, id: Uuid) -> DomainResult<()> {
+                self.relations.lock().unwrap().retain(|r| r.id != id);
+                Ok(())
+            }
+        }
+
+        fn create_relation_service() -> (NoteRelationService, Arc<NoteService>) {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let service = NoteRelationService::new(
+                Arc::new(MockNoteRelationRepository::default()),
+                notes.clone(),
+            );
+            (service, notes)
+        }
+
+        async fn note(notes: &NoteService, user_id: Uuid) -> Note {
+            notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: "content".to_string(),
+                    tags: vec![],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_create_rejects_parent_cycles() {
+            let (service, notes) = create_relation_service();
+            let user_id = Uuid::new_v4();
+            let (a, b) = (note(&notes, user_id).await, note(&notes, user_id).await);
+
+            service
+                .create(user_id, a.id, RelationKind::ParentOf, b.id)
+                .await
+                .unwrap();
+            let err = service
+                .create(user_id, b.id, RelationKind::ParentOf, a.id)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, DomainError::ValidationError(_)));
+
+            service
+                .create(user_id, b.id, RelationKind::BlockedBy, a.id)
+                .await
+                .unwrap();
+            assert_eq!(service.list_for_note(a.id, user_id).await.unwrap().len(), 2);
+        }
+
+        #[tokio::test]
+        async fn test_relations_are_private() {
+            let (service, notes) = create_relation_service();
+            let owner = Uuid::new_v4();
+            let other = Uuid::new_v4();
+            let mine = note(&notes, owner).await;
+            let theirs = note(&notes, other).await;
+
+            let err = service
+                .create(owner, mine.id, RelationKind::References, theirs.id)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, DomainError::Forbidden(_)));
+
+            let relation = service
+                .create(
+                    owner,
+                    mine.id,
+                    RelationKind::References,
+                    note(&notes, owner).await.id,
+                )
+                .await
+                .unwrap();
+            let err = service.delete(relation.id, other).await.unwrap_err();
+            assert!(matches!(err, DomainError::Forbidden(_)));
+            service.delete(relation.id, owner).await.unwrap();
+            let err = service.delete(relation.id, owner).await.unwrap_err();
+            assert!(matches!(err, DomainError::RelationNotFound(_)));
+        }
+    }
+}
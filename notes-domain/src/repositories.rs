@@ -5,10 +5,31 @@
 //! Concrete implementations (adapters) live in the `notes-infra` crate.
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::entities::{Note, NoteFilter, Tag, User};
+use crate::announcements::Announcement;
+use crate::boards::Board;
+use crate::bookmarks::LinkPreview;
+use crate::entities::{
+    EmailChange, Note, NoteCounts, NoteFilter, NoteVersion, Tag, User, UserSettings,
+};
 use crate::errors::DomainResult;
+use crate::event_log::LoggedEvent;
+use crate::housekeeping::StoredNote;
+use crate::impersonation::Impersonation;
+use crate::instance::InstanceSettingsUpdate;
+use crate::invitations::Invitation;
+use crate::jobs::Job;
+use crate::legal::{LegalAcceptance, LegalDocument, LegalDocumentKind};
+use crate::lint::NoteIssue;
+use crate::overview::DatabaseMetrics;
+use crate::query::NoteQuery;
+use crate::relations::NoteRelation;
+use crate::search::{SearchHistoryEntry, TitleSuggestion};
+use crate::tag_aliases::TagAlias;
+use crate::transcription::AudioUpload;
+use crate::trash::{StorageStats, TrashPurgeReport};
 
 /// Repository port for Note persistence
 #[async_trait]
@@ -19,14 +40,49 @@ pub trait NoteRepository: Send + Sync {
     /// Find all notes for a user, optionally filtered
     async fn find_by_user(&self, user_id: Uuid, filter: NoteFilter) -> DomainResult<Vec<Note>>;
 
+    /// Count the user's notes outside the trash in one aggregate query
+    async fn count_by_user(&self, user_id: Uuid) -> DomainResult<NoteCounts>;
+
     /// Save a new note or update an existing one
     async fn save(&self, note: &Note) -> DomainResult<()>;
 
-    /// Delete a note by its ID
-    async fn delete(&self, id: Uuid) -> DomainResult<()>;
+    /// Permanently delete a note with its versions, tag associations and links.
+    /// Returns the approximate number of content bytes reclaimed.
+    async fn delete(&self, id: Uuid) -> DomainResult<u64>;
+
+    /// Find notes of all users that were moved to the trash before `cutoff`
+    async fn find_trashed_before(&self, cutoff: DateTime<Utc>) -> DomainResult<Vec<Note>>;
+
+    /// Full-text search across note titles and content.
+    /// Archived notes are skipped unless `include_archived` is set.
+    async fn search(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+    ) -> DomainResult<Vec<Note>>;
+
+    /// Full-text search across historical versions of the user's live notes,
+    /// returning at most `limit` versions
+    async fn search_versions(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+        limit: usize,
+    ) -> DomainResult<Vec<NoteVersion>>;
+
+    /// Find up to `limit` of the user's live notes whose title starts with
+    /// `prefix`, ignoring ASCII case
+    async fn suggest_titles(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<TitleSuggestion>>;
 
-    /// Full-text search across note titles and content
-    async fn search(&self, user_id: Uuid, query: &str) -> DomainResult<Vec<Note>>;
+    /// Find a user's live notes matching a structured query
+    async fn query(&self, user_id: Uuid, query: &NoteQuery) -> DomainResult<Vec<Note>>;
 
     /// Save a note version
     async fn save_version(&self, version: &crate::entities::NoteVersion) -> DomainResult<()>;
@@ -36,6 +92,27 @@ pub trait NoteRepository: Send + Sync {
         &self,
         note_id: Uuid,
     ) -> DomainResult<Vec<crate::entities::NoteVersion>>;
+
+    /// Find the most recent version of a note
+    async fn find_latest_version(&self, note_id: Uuid) -> DomainResult<Option<NoteVersion>>;
+
+    /// Set the pin order of a user's pinned notes in one transaction
+    /// (`note_ids[0]` gets position 0)
+    async fn reorder_pins(&self, user_id: Uuid, note_ids: &[Uuid]) -> DomainResult<()>;
+
+    /// Find up to `limit` live notes of all users that may have bookmarks and
+    /// changed since their link previews were saved, least recently changed first
+    async fn find_stale_link_previews(&self, limit: usize) -> DomainResult<Vec<Note>>;
+
+    /// Replace a note's link previews without changing `updated_at`.
+    /// `as_of` is the `updated_at` of the content they were made for, so an
+    /// edit made while fetching leaves the note stale.
+    async fn save_link_previews(
+        &self,
+        note_id: Uuid,
+        previews: &[LinkPreview],
+        as_of: DateTime<Utc>,
+    ) -> DomainResult<()>;
 }
 
 /// Repository port for User persistence
@@ -55,6 +132,30 @@ pub trait UserRepository: Send + Sync {
 
     /// Delete a user by their ID
     async fn delete(&self, id: Uuid) -> DomainResult<()>;
+
+    /// Load a user's settings (defaults if none have been saved)
+    async fn find_settings(&self, user_id: Uuid) -> DomainResult<UserSettings>;
+
+    /// Persist a user's settings
+    async fn save_settings(&self, user_id: Uuid, settings: &UserSettings) -> DomainResult<()>;
+
+    /// Store a pending email change, replacing any previous one for the user
+    async fn save_email_change(&self, change: &EmailChange) -> DomainResult<()>;
+
+    /// Find a pending email change by its confirmation token
+    async fn find_email_change(&self, token: &str) -> DomainResult<Option<EmailChange>>;
+
+    /// Remove the pending email change of a user
+    async fn delete_email_change(&self, user_id: Uuid) -> DomainResult<()>;
+
+    /// Find IDs of users who opted in to automatic archival
+    async fn find_ids_with_auto_archive(&self) -> DomainResult<Vec<Uuid>>;
+
+    /// Find IDs of users who opted in to generated note titles
+    async fn find_ids_with_auto_title(&self) -> DomainResult<Vec<Uuid>>;
+
+    /// Find IDs of all users
+    async fn find_all_ids(&self) -> DomainResult<Vec<Uuid>>;
 }
 
 /// Repository port for Tag persistence
@@ -63,9 +164,17 @@ pub trait TagRepository: Send + Sync {
     /// Find a tag by its ID
     async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Tag>>;
 
-    /// Find all tags for a user
+    /// Find all tags for a user, in sidebar order
     async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Tag>>;
 
+    /// Find up to `limit` of the user's tags whose name starts with `prefix`
+    async fn find_by_prefix(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<Tag>>;
+
     /// Find a tag by name for a specific user
     async fn find_by_name(&self, user_id: Uuid, name: &str) -> DomainResult<Option<Tag>>;
 
@@ -78,16 +187,329 @@ pub trait TagRepository: Send + Sync {
     /// Add a tag to a note
     async fn add_to_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()>;
 
+    /// Add several tags to a note in a single statement
+    async fn add_many_to_note(&self, tag_ids: &[Uuid], note_id: Uuid) -> DomainResult<()>;
+
     /// Remove a tag from a note
     async fn remove_from_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()>;
 
     /// Get all tags for a specific note
     async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<Tag>>;
+
+    /// Move the notes and aliases of the tag `source_id` to `target_id`
+    /// and delete the source tag, atomically
+    async fn merge(&self, source_id: Uuid, target_id: Uuid) -> DomainResult<()>;
+
+    /// The user's tags that no note uses, trashed notes included, by name
+    async fn find_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>>;
+
+    /// Delete the user's unused tags and their aliases, returning the
+    /// deleted tags; tags attached to a note meanwhile are kept
+    async fn delete_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>>;
+
+    /// Set `sort_order` of the user's tags to their position in `tag_ids`
+    async fn reorder(&self, user_id: Uuid, tag_ids: &[Uuid]) -> DomainResult<()>;
+}
+
+/// Repository port for alternative tag names
+#[async_trait]
+pub trait TagAliasRepository: Send + Sync {
+    /// Save an alias, repointing an existing alias of the same name
+    async fn save(&self, alias: &TagAlias) -> DomainResult<()>;
+
+    async fn find_by_alias(&self, user_id: Uuid, alias: &str) -> DomainResult<Option<TagAlias>>;
+
+    /// The user's aliases, ordered by alias
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<TagAlias>>;
+
+    async fn delete(&self, user_id: Uuid, alias: &str) -> DomainResult<()>;
+}
+
+/// Repository port for the queries users have searched for
+#[async_trait]
+pub trait SearchHistoryRepository: Send + Sync {
+    /// Record that the user searched for `query` at `at`, counting repeats
+    async fn record(&self, user_id: Uuid, query: &str, at: DateTime<Utc>) -> DomainResult<()>;
+
+    /// Find up to `limit` of the user's most recent queries
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        limit: usize,
+    ) -> DomainResult<Vec<SearchHistoryEntry>>;
+
+    /// Find up to `limit` of the user's distinct queries starting with
+    /// `prefix` (ignoring ASCII case), most recent first
+    async fn find_recent(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<String>>;
+
+    /// Find up to `limit` of the user's queries starting with `prefix`
+    /// (ignoring ASCII case) that were searched more than once, most
+    /// searched first
+    async fn find_frequent(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<String>>;
+
+    /// Forget all of the user's queries, returning how many were removed
+    async fn delete_by_user(&self, user_id: Uuid) -> DomainResult<u64>;
+}
+
+/// Port for writing a note together with its dependent rows atomically
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    /// Save the note, replace its tag associations with `note.tags` and store
+    /// `version` in a single transaction. Nothing is written if any step fails.
+    async fn commit_note(&self, note: &Note, version: Option<&NoteVersion>) -> DomainResult<()>;
+}
+
+/// Repository port for instance-wide settings managed by administrators
+#[async_trait]
+pub trait InstanceSettingsRepository: Send + Sync {
+    /// Whether the instance is in read-only maintenance mode
+    async fn is_read_only(&self) -> DomainResult<bool>;
+
+    /// Enable or disable read-only maintenance mode
+    async fn set_read_only(&self, read_only: bool) -> DomainResult<()>;
+
+    /// Add a trash purge to the running storage totals
+    async fn record_trash_purge(&self, report: &TrashPurgeReport) -> DomainResult<()>;
+
+    /// Storage reclaimed by trash purges so far
+    async fn storage_stats(&self) -> DomainResult<StorageStats>;
+
+    /// Instance-wide usage, with windowed metrics counted from `since`
+    async fn database_metrics(&self, since: DateTime<Utc>) -> DomainResult<DatabaseMetrics>;
+
+    /// Settings changed by administrators; `None` for those left at their default
+    async fn settings_overrides(&self) -> DomainResult<InstanceSettingsUpdate>;
+
+    /// Store the values set in `update`, keeping the others
+    async fn update_settings(&self, update: &InstanceSettingsUpdate) -> DomainResult<()>;
+}
+
+/// Repository port for checking and repairing the instance's data as a
+/// whole, across users
+#[async_trait]
+pub trait HousekeepingRepository: Send + Sync {
+    /// Tag associations whose note or tag no longer exists
+    async fn count_orphaned_note_tags(&self) -> DomainResult<u64>;
+
+    /// Delete the orphaned tag associations, returning how many there were
+    async fn delete_orphaned_note_tags(&self) -> DomainResult<u64>;
+
+    /// Rows the full-text indexes miss plus entries of rows that are gone
+    async fn count_fts_drift(&self) -> DomainResult<u64>;
+
+    /// Rebuild the full-text indexes from the rows they index
+    async fn rebuild_fts(&self) -> DomainResult<()>;
+
+    /// Every note, trashed or not
+    async fn find_stored_notes(&self) -> DomainResult<Vec<StoredNote>>;
+
+    /// Login sessions that expired before `now`
+    async fn count_expired_sessions(&self, now: DateTime<Utc>) -> DomainResult<u64>;
+
+    /// Delete sessions that expired before `now`, returning how many
+    async fn delete_expired_sessions(&self, now: DateTime<Utc>) -> DomainResult<u64>;
+
+    /// Running jobs last saved before `before`
+    async fn count_stale_jobs(&self, before: DateTime<Utc>) -> DomainResult<u64>;
+
+    /// Mark running jobs last saved before `before` as failed with `error`,
+    /// returning how many were updated
+    async fn fail_stale_jobs(&self, before: DateTime<Utc>, error: &str) -> DomainResult<u64>;
+}
+
+/// Repository port for announcements and who dismissed them
+#[async_trait]
+pub trait AnnouncementRepository: Send + Sync {
+    /// Save a new announcement or update an existing one
+    async fn save(&self, announcement: &Announcement) -> DomainResult<()>;
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Announcement>>;
+
+    /// All announcements, including past and scheduled ones, newest first
+    async fn find_all(&self) -> DomainResult<Vec<Announcement>>;
+
+    /// Announcements active at `now` that the user has not dismissed, newest
+    /// first
+    async fn find_active_for_user(
+        &self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> DomainResult<Vec<Announcement>>;
+
+    /// Delete an announcement together with its dismissals
+    async fn delete(&self, id: Uuid) -> DomainResult<()>;
+
+    /// Hide the announcement for the user; dismissing twice is a no-op
+    async fn dismiss(&self, id: Uuid, user_id: Uuid) -> DomainResult<()>;
+}
+
+/// Repository port for impersonations, kept as an audit trail
+#[async_trait]
+pub trait ImpersonationRepository: Send + Sync {
+    /// Save a new impersonation or update an existing one
+    async fn save(&self, impersonation: &Impersonation) -> DomainResult<()>;
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Impersonation>>;
+
+    /// The administrator's latest impersonation of the user that is still
+    /// active at `now`
+    async fn find_active(
+        &self,
+        admin_id: Uuid,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> DomainResult<Option<Impersonation>>;
+
+    /// The latest impersonations by any administrator, newest first
+    async fn find_recent(&self, limit: usize) -> DomainResult<Vec<Impersonation>>;
+}
+
+/// Repository port for legal documents and users' acceptance of them
+#[async_trait]
+pub trait LegalRepository: Send + Sync {
+    /// Store a newly published version; earlier versions are kept
+    async fn save_document(&self, document: &LegalDocument) -> DomainResult<()>;
+
+    /// The latest version of the document, if any was published
+    async fn find_current(&self, kind: LegalDocumentKind) -> DomainResult<Option<LegalDocument>>;
+
+    /// The latest version of every published document
+    async fn find_all_current(&self) -> DomainResult<Vec<LegalDocument>>;
+
+    /// Record an acceptance; accepting a version twice is a no-op
+    async fn save_acceptance(&self, acceptance: &LegalAcceptance) -> DomainResult<()>;
+
+    /// Latest versions the user has not accepted
+    async fn find_unaccepted(&self, user_id: Uuid) -> DomainResult<Vec<LegalDocument>>;
+}
+
+/// Repository port for the per-user event log
+#[async_trait]
+pub trait EventLogRepository: Send + Sync {
+    async fn append(&self, event: &LoggedEvent) -> DomainResult<()>;
+
+    /// The user's events that occurred before `before` (all when `None`),
+    /// newest first
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        before: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> DomainResult<Vec<LoggedEvent>>;
+}
+
+/// Repository port for issues found in note content
+#[async_trait]
+pub trait NoteIssueRepository: Send + Sync {
+    /// Replace the note's issues with `issues` (none clears them)
+    async fn replace_for_note(&self, note_id: Uuid, issues: &[NoteIssue]) -> DomainResult<()>;
+
+    async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<NoteIssue>>;
+
+    /// Issues in all of the user's notes
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteIssue>>;
+}
+
+/// Repository port for audio waiting to be transcribed
+#[async_trait]
+pub trait AudioUploadRepository: Send + Sync {
+    async fn save(&self, upload: &AudioUpload) -> DomainResult<()>;
+
+    /// Up to `limit` uploads, oldest first
+    async fn find_pending(&self, limit: usize) -> DomainResult<Vec<AudioUpload>>;
+
+    /// Count a failed transcription of the upload
+    async fn record_failure(&self, id: Uuid) -> DomainResult<()>;
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()>;
+}
+
+/// Repository port for kanban boards
+#[async_trait]
+pub trait BoardRepository: Send + Sync {
+    /// Save a new board or replace an existing one, columns included
+    async fn save(&self, board: &Board) -> DomainResult<()>;
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Board>>;
+
+    /// The user's boards, oldest first
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Board>>;
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()>;
+}
+
+/// Repository port for typed relations between notes
+#[async_trait]
+pub trait NoteRelationRepository: Send + Sync {
+    async fn save(&self, relation: &NoteRelation) -> DomainResult<()>;
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<NoteRelation>>;
+
+    /// All of the user's relations, oldest first
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteRelation>>;
+
+    /// Relations with the note on either side, oldest first
+    async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<NoteRelation>>;
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()>;
+}
+
+/// Repository port for registration invitations
+#[async_trait]
+pub trait InvitationRepository: Send + Sync {
+    /// Store a new invitation
+    async fn save(&self, invitation: &Invitation) -> DomainResult<()>;
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Invitation>>;
+
+    async fn find_by_code(&self, code: &str) -> DomainResult<Option<Invitation>>;
+
+    /// All invitations, newest first
+    async fn find_all(&self) -> DomainResult<Vec<Invitation>>;
+
+    /// Delete an invitation; accounts registered with it are kept
+    async fn delete(&self, id: Uuid) -> DomainResult<()>;
+
+    /// Count the registration of `user_id` against the invitation if it is
+    /// still usable at `now`, atomically. Returns `false` when it was used up,
+    /// expired or deleted in the meantime.
+    async fn redeem(&self, id: Uuid, user_id: Uuid, now: DateTime<Utc>) -> DomainResult<bool>;
+
+    /// Users who registered with the invitation, oldest first
+    async fn find_invited_users(&self, id: Uuid) -> DomainResult<Vec<Uuid>>;
+}
+
+/// Repository port for background jobs
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    /// Save a new job or update an existing one
+    async fn save(&self, job: &Job) -> DomainResult<()>;
+
+    /// Find a job by its ID
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Job>>;
+
+    /// Find up to `limit` of the user's jobs, newest first
+    async fn find_by_user(&self, user_id: Uuid, limit: usize) -> DomainResult<Vec<Job>>;
+
+    /// Mark every running job as failed with `error`, returning how many
+    /// were updated
+    async fn fail_running(&self, error: &str) -> DomainResult<u64>;
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
+    use crate::entities::NoteSortOrder;
     use crate::value_objects::NoteTitle;
     use std::collections::HashMap;
     use std::sync::Mutex;
@@ -96,6 +518,7 @@ pub(crate) mod tests {
     pub struct MockNoteRepository {
         notes: Mutex<HashMap<Uuid, Note>>,
         versions: Mutex<HashMap<Uuid, Vec<crate::entities::NoteVersion>>>,
+        link_previews_at: Mutex<HashMap<Uuid, DateTime<Utc>>>,
     }
 
     impl MockNoteRepository {
@@ -103,6 +526,7 @@ pub(crate) mod tests {
             Self {
                 notes: Mutex::new(HashMap::new()),
                 versions: Mutex::new(HashMap::new()),
+                link_previews_at: Mutex::new(HashMap::new()),
             }
         }
     }
@@ -122,28 +546,75 @@ pub(crate) mod tests {
                 .filter(|n| {
                     filter.is_archived.is_none() || filter.is_archived == Some(n.is_archived)
                 })
+                .filter(|n| n.is_trashed() == filter.trashed)
+                .filter(|n| match (&filter.within, &n.location) {
+                    (None, _) => true,
+                    (Some(bbox), Some(location)) => bbox.contains(location),
+                    (Some(_), None) => false,
+                })
                 .cloned()
                 .collect();
             result.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
             Ok(result)
         }
 
+        async fn count_by_user(&self, user_id: Uuid) -> DomainResult<NoteCounts> {
+            let notes = self.notes.lock().unwrap();
+            let live: Vec<&Note> = notes
+                .values()
+                .filter(|n| n.user_id == user_id && !n.is_trashed())
+                .collect();
+            Ok(NoteCounts {
+                total: live.len(),
+                pinned: live
+                    .iter()
+                    .filter(|n| n.is_pinned && !n.is_archived)
+                    .count(),
+                archived: live.iter().filter(|n| n.is_archived).count(),
+            })
+        }
+
         async fn save(&self, note: &Note) -> DomainResult<()> {
             self.notes.lock().unwrap().insert(note.id, note.clone());
             Ok(())
         }
 
-        async fn delete(&self, id: Uuid) -> DomainResult<()> {
-            self.notes.lock().unwrap().remove(&id);
-            Ok(())
+        async fn delete(&self, id: Uuid) -> DomainResult<u64> {
+            let note = self.notes.lock().unwrap().remove(&id);
+            let versions = self.versions.lock().unwrap().remove(&id);
+
+            let note_bytes = note.map_or(0, |n| n.title_str().len() + n.content.len());
+            let version_bytes: usize = versions
+                .unwrap_or_default()
+                .iter()
+                .map(|v| v.title.as_deref().map_or(0, str::len) + v.content.len())
+                .sum();
+            Ok((note_bytes + version_bytes) as u64)
+        }
+
+        async fn find_trashed_before(&self, cutoff: DateTime<Utc>) -> DomainResult<Vec<Note>> {
+            Ok(self
+                .notes
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|n| n.deleted_at.is_some_and(|deleted_at| deleted_at < cutoff))
+                .cloned()
+                .collect())
         }
 
-        async fn search(&self, user_id: Uuid, query: &str) -> DomainResult<Vec<Note>> {
+        async fn search(
+            &self,
+            user_id: Uuid,
+            query: &str,
+            include_archived: bool,
+        ) -> DomainResult<Vec<Note>> {
             let notes = self.notes.lock().unwrap();
             let query_lower = query.to_lowercase();
             Ok(notes
                 .values()
-                .filter(|n| n.user_id == user_id)
+                .filter(|n| n.user_id == user_id && !n.is_trashed())
+                .filter(|n| include_archived || !n.is_archived)
                 .filter(|n| {
                     n.title_str().to_lowercase().contains(&query_lower)
                         || n.content.to_lowercase().contains(&query_lower)
@@ -152,6 +623,78 @@ pub(crate) mod tests {
                 .collect())
         }
 
+        async fn search_versions(
+            &self,
+            user_id: Uuid,
+            query: &str,
+            include_archived: bool,
+            limit: usize,
+        ) -> DomainResult<Vec<NoteVersion>> {
+            let notes = self.notes.lock().unwrap();
+            let versions = self.versions.lock().unwrap();
+            let query_lower = query.to_lowercase();
+            Ok(versions
+                .values()
+                .flatten()
+                .filter(|v| {
+                    notes.get(&v.note_id).is_some_and(|n| {
+                        n.user_id == user_id
+                            && !n.is_trashed()
+                            && (include_archived || !n.is_archived)
+                    })
+                })
+                .filter(|v| v.content.to_lowercase().contains(&query_lower))
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+
+        async fn suggest_titles(
+            &self,
+            user_id: Uuid,
+            prefix: &str,
+            limit: usize,
+        ) -> DomainResult<Vec<TitleSuggestion>> {
+            let notes = self.notes.lock().unwrap();
+            let prefix = prefix.to_ascii_lowercase();
+            let mut result: Vec<TitleSuggestion> = notes
+                .values()
+                .filter(|n| n.user_id == user_id && !n.is_trashed())
+                .filter(|n| n.title_str().to_ascii_lowercase().starts_with(&prefix))
+                .map(|n| TitleSuggestion {
+                    note_id: n.id,
+                    title: n.title_str().to_string(),
+                })
+                .collect();
+            result.sort_by(|a, b| a.title.cmp(&b.title));
+            result.truncate(limit);
+            Ok(result)
+        }
+
+        async fn query(&self, user_id: Uuid, query: &NoteQuery) -> DomainResult<Vec<Note>> {
+            let notes = self.notes.lock().unwrap();
+            let mut result: Vec<Note> = notes
+                .values()
+                .filter(|n| n.user_id == user_id && !n.is_trashed())
+                .filter(|n| query.filter.as_ref().is_none_or(|f| f.matches(n)))
+                .cloned()
+                .collect();
+            match query.sort {
+                NoteSortOrder::UpdatedDesc => {
+                    result.sort_by(|a, b| b.updated_at.cmp(&a.updated_at))
+                }
+                NoteSortOrder::CreatedDesc => {
+                    result.sort_by(|a, b| b.created_at.cmp(&a.created_at))
+                }
+                NoteSortOrder::TitleAsc => result.sort_by(|a, b| a.title_str().cmp(b.title_str())),
+            }
+            Ok(result
+                .into_iter()
+                .skip(query.offset)
+                .take(query.limit())
+                .collect())
+        }
+
         async fn save_version(&self, version: &crate::entities::NoteVersion) -> DomainResult<()> {
             let mut versions = self.versions.lock().unwrap();
             let note_versions = versions.entry(version.note_id).or_insert_with(Vec::new);
@@ -166,6 +709,52 @@ pub(crate) mod tests {
             let versions = self.versions.lock().unwrap();
             Ok(versions.get(&note_id).cloned().unwrap_or_default())
         }
+
+        async fn find_latest_version(&self, note_id: Uuid) -> DomainResult<Option<NoteVersion>> {
+            let versions = self.versions.lock().unwrap();
+            Ok(versions
+                .get(&note_id)
+                .and_then(|v| v.iter().max_by_key(|v| v.created_at))
+                .cloned())
+        }
+
+        async fn reorder_pins(&self, user_id: Uuid, note_ids: &[Uuid]) -> DomainResult<()> {
+            let mut notes = self.notes.lock().unwrap();
+            for (position, id) in note_ids.iter().enumerate() {
+                if let Some(note) = notes.get_mut(id).filter(|n| n.user_id == user_id) {
+                    note.pin_order = Some(position as i32);
+                }
+            }
+            Ok(())
+        }
+
+        async fn find_stale_link_previews(&self, limit: usize) -> DomainResult<Vec<Note>> {
+            let notes = self.notes.lock().unwrap();
+            let refreshed = self.link_previews_at.lock().unwrap();
+            let mut result: Vec<Note> = notes
+                .values()
+                .filter(|n| !n.is_trashed())
+                .filter(|n| n.content.contains("http") || !n.link_previews.is_empty())
+                .filter(|n| refreshed.get(&n.id).is_none_or(|at| n.updated_at > *at))
+                .cloned()
+                .collect();
+            result.sort_by_key(|n| n.updated_at);
+            result.truncate(limit);
+            Ok(result)
+        }
+
+        async fn save_link_previews(
+            &self,
+            note_id: Uuid,
+            previews: &[LinkPreview],
+            as_of: DateTime<Utc>,
+        ) -> DomainResult<()> {
+            if let Some(note) = self.notes.lock().unwrap().get_mut(&note_id) {
+                note.link_previews = previews.to_vec();
+            }
+            self.link_previews_at.lock().unwrap().insert(note_id, as_of);
+            Ok(())
+        }
     }
 
     #[tokio::test]
@@ -214,15 +803,18 @@ pub(crate) mod tests {
         let title1 = NoteTitle::try_from("Shopping List").ok();
         let title2 = NoteTitle::try_from("Meeting Notes").ok();
         let note1 = Note::new(user_id, title1, "Buy milk and eggs");
-        let note2 = Note::new(user_id, title2, "Discuss project timeline");
+        let mut note2 = Note::new(user_id, title2, "Discuss project timeline");
+        note2.is_archived = true;
         repo.save(&note1).await.unwrap();
         repo.save(&note2).await.unwrap();
 
-        let results = repo.search(user_id, "milk").await.unwrap();
+        let results = repo.search(user_id, "milk", false).await.unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].title_str(), "Shopping List");
 
-        let results = repo.search(user_id, "notes").await.unwrap();
+        let results = repo.search(user_id, "notes", false).await.unwrap();
+        assert!(results.is_empty());
+        let results = repo.search(user_id, "notes", true).await.unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].title_str(), "Meeting Notes");
     }
@@ -0,0 +1,142 @@
+//! Permissions carried by access tokens
+//!
+//! A token may be limited to a few scopes, so a read-only widget can list
+//! notes but not delete them. Sessions and tokens issued without scopes have
+//! full access. Writing notes implies reading them; every other scope has to
+//! be granted on its own.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::{DomainError, DomainResult};
+
+/// Something a token may be allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Scope {
+    /// List, search and read notes and tags
+    NotesRead,
+    /// Create, change and delete notes and tags
+    NotesWrite,
+    /// Export notes
+    Export,
+    /// Use the admin endpoints, for users who are administrators
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [
+        Scope::NotesRead,
+        Scope::NotesWrite,
+        Scope::Export,
+        Scope::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotesRead => "notes:read",
+            Self::NotesWrite => "notes:write",
+            Self::Export => "export",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "notes:read" => Ok(Self::NotesRead),
+            "notes:write" => Ok(Self::NotesWrite),
+            "export" => Ok(Self::Export),
+            "admin" => Ok(Self::Admin),
+            other => Err(DomainError::validation(format!("Unknown scope: {}", other))),
+        }
+    }
+}
+
+/// The scopes granted to a request, or full access
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scopes {
+    /// Everything the user may do
+    Full,
+    /// Only these scopes
+    Limited(Vec<Scope>),
+}
+
+impl Scopes {
+    /// Parse a list separated by spaces or commas, such as `notes:read export`
+    pub fn parse(s: &str) -> DomainResult<Self> {
+        let mut scopes = Vec::new();
+        for part in s.split(|c: char| c == ',' || c.is_whitespace()) {
+            if part.is_empty() {
+                continue;
+            }
+            let scope: Scope = part.parse()?;
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        if scopes.is_empty() {
+            return Err(DomainError::validation("At least one scope is required"));
+        }
+        scopes.sort();
+        Ok(Self::Limited(scopes))
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self, Self::Full)
+    }
+
+    pub fn allows(&self, scope: Scope) -> bool {
+        match self {
+            Self::Full => true,
+            Self::Limited(scopes) => {
+                scopes.contains(&scope)
+                    || (scope == Scope::NotesRead && scopes.contains(&Scope::NotesWrite))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scopes: &[Scope] = match self {
+            Self::Full => &Scope::ALL,
+            Self::Limited(scopes) => scopes,
+        };
+        let names: Vec<&str> = scopes.iter().map(Scope::as_str).collect();
+        f.write_str(&names.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_allows() {
+        let scopes = Scopes::parse("notes:write, export notes:write").unwrap();
+        assert_eq!(
+            scopes,
+            Scopes::Limited(vec![Scope::NotesWrite, Scope::Export])
+        );
+        assert_eq!(scopes.to_string(), "notes:write export");
+        assert!(scopes.allows(Scope::NotesRead));
+        assert!(scopes.allows(Scope::Export));
+        assert!(!scopes.allows(Scope::Admin));
+
+        let read_only = Scopes::parse("notes:read").unwrap();
+        assert!(!read_only.allows(Scope::NotesWrite));
+        assert!(Scopes::Full.allows(Scope::Admin));
+
+        assert!(Scopes::parse("notes:delete").is_err());
+        assert!(Scopes::parse(" , ").is_err());
+    }
+}
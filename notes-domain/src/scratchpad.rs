@@ -0,0 +1,55 @@
+//! Scratch notes for visitors without an account
+//!
+//! Instances can let visitors try the editor before signing up. Their single
+//! scratch note is kept in their session rather than the database, so it
+//! disappears with the session unless they register and claim it, which
+//! turns it into a regular note.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{DomainError, DomainResult};
+
+/// Maximum length of a scratch note in characters
+pub const MAX_SCRATCHPAD_LENGTH: usize = 20_000;
+
+/// A visitor's scratch note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scratchpad {
+    pub content: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Scratchpad {
+    pub fn new(content: impl Into<String>) -> DomainResult<Self> {
+        let content = content.into();
+        if content.chars().count() > MAX_SCRATCHPAD_LENGTH {
+            return Err(DomainError::validation(format!(
+                "Scratch note cannot exceed {} characters",
+                MAX_SCRATCHPAD_LENGTH
+            )));
+        }
+        Ok(Self {
+            content,
+            updated_at: Utc::now(),
+        })
+    }
+
+    /// Whether there is anything worth keeping
+    pub fn is_empty(&self) -> bool {
+        self.content.trim().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_limits_length() {
+        assert!(Scratchpad::new("a".repeat(MAX_SCRATCHPAD_LENGTH)).is_ok());
+        assert!(Scratchpad::new("a".repeat(MAX_SCRATCHPAD_LENGTH + 1)).is_err());
+        assert!(Scratchpad::new(" \n ").unwrap().is_empty());
+        assert!(!Scratchpad::new("Groceries").unwrap().is_empty());
+    }
+}
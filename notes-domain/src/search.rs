@@ -0,0 +1,390 @@
+//! Search scopes and unified ranking
+//!
+//! Search can cover current notes and their historical versions. Hits from
+//! every scope are scored by the same relevance function so they can be
+//! returned as one ranked list.
+//!
+//! Queries may narrow results by date with `after:` and `before:` followed by
+//! any date [`parse_when`] understands, e.g. `budget after:last week`, and
+//! by the language notes are written in with `lang:` and an ISO 639-1 code,
+//! e.g. `lang:pl`.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::dates::parse_when;
+use crate::entities::{Note, NoteVersion, Tag};
+use crate::errors::{DomainError, DomainResult};
+use crate::language::Language;
+
+/// Maximum number of version hits considered per search
+pub const MAX_VERSION_HITS: usize = 50;
+
+/// Maximum number of suggestions of each kind
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// History entries returned when no limit is given
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// Maximum number of history entries returned at once
+pub const MAX_HISTORY_LIMIT: usize = 100;
+
+/// Maximum number of words in the value of a date filter, as in
+/// `after:next friday 9am`
+const MAX_DATE_FILTER_WORDS: usize = 4;
+
+/// Weight of a term found in a title or tag relative to one in the content
+const TITLE_WEIGHT: f64 = 3.0;
+
+/// Versions rank below current notes containing the same text
+const VERSION_WEIGHT: f64 = 0.5;
+
+/// What a search covers, parsed from e.g. `notes,versions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchScope {
+    pub notes: bool,
+    pub versions: bool,
+}
+
+impl Default for SearchScope {
+    fn default() -> Self {
+        Self {
+            notes: true,
+            versions: false,
+        }
+    }
+}
+
+impl FromStr for SearchScope {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scope = Self {
+            notes: false,
+            versions: false,
+        };
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part {
+                "notes" => scope.notes = true,
+                "versions" => scope.versions = true,
+                "attachments" => {
+                    return Err(DomainError::validation(
+                        "Attachment search is not supported: notes have no attachments",
+                    ));
+                }
+                other => {
+                    return Err(DomainError::validation(format!(
+                        "Unknown search scope: {}",
+                        other
+                    )));
+                }
+            }
+        }
+        if !scope.notes && !scope.versions {
+            return Err(DomainError::validation("Search scope cannot be empty"));
+        }
+        Ok(scope)
+    }
+}
+
+/// Where a search hit was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchHitKind {
+    Note,
+    Version,
+}
+
+/// A ranked search result
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchHit {
+    Note { note: Note, score: f64 },
+    Version { version: NoteVersion, score: f64 },
+}
+
+impl SearchHit {
+    pub fn note(note: Note, query: &str) -> Self {
+        let tags: Vec<&str> = note.tags.iter().map(|t| t.name_str()).collect();
+        let score = relevance(query, note.title_str(), &note.content, &tags);
+        Self::Note { note, score }
+    }
+
+    pub fn version(version: NoteVersion, query: &str) -> Self {
+        let title = version.title.as_deref().unwrap_or_default();
+        let score = VERSION_WEIGHT * relevance(query, title, &version.content, &[]);
+        Self::Version { version, score }
+    }
+
+    pub fn kind(&self) -> SearchHitKind {
+        match self {
+            Self::Note { .. } => SearchHitKind::Note,
+            Self::Version { .. } => SearchHitKind::Version,
+        }
+    }
+
+    pub fn score(&self) -> f64 {
+        match self {
+            Self::Note { score, .. } | Self::Version { score, .. } => *score,
+        }
+    }
+}
+
+/// A note whose title starts with the typed prefix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleSuggestion {
+    pub note_id: Uuid,
+    pub title: String,
+}
+
+/// A query the user searched for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHistoryEntry {
+    pub query: String,
+    pub last_searched_at: DateTime<Utc>,
+    /// How many times the query was searched
+    pub search_count: u32,
+}
+
+/// As-you-type suggestions for a search prefix
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchSuggestions {
+    pub titles: Vec<TitleSuggestion>,
+    pub tags: Vec<Tag>,
+    /// The user's earlier queries starting with the prefix, frequently
+    /// repeated ones first, then the most recent
+    pub queries: Vec<String>,
+}
+
+/// A search query with its filters taken out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedSearch {
+    /// The words to search for
+    pub text: String,
+    /// Only hits created at or after this time
+    pub after: Option<DateTime<Utc>>,
+    /// Only hits created before this time
+    pub before: Option<DateTime<Utc>>,
+    /// Only hits written in this language
+    pub language: Option<Language>,
+}
+
+impl ParsedSearch {
+    /// Take `after:`, `before:` and `lang:` filters out of `query`
+    ///
+    /// A filter's value runs over the following words for as long as they
+    /// read as a date, so `after:last week budget` searches for `budget`.
+    /// Days without a time start at midnight in `tz`.
+    pub fn parse(query: &str, now: DateTime<Utc>, tz: Tz) -> DomainResult<Self> {
+        let mut search = Self {
+            text: String::new(),
+            after: None,
+            before: None,
+            language: None,
+        };
+        let words: Vec<&str> = query.split_whitespace().collect();
+        let mut text = Vec::new();
+        let mut i = 0;
+
+        while i < words.len() {
+            if let Some(code) = words[i].strip_prefix("lang:") {
+                let language = Language::new(code).map_err(|_| {
+                    DomainError::validation(format!(
+                        "Unknown language in {}; use a two-letter code like lang:en",
+                        words[i]
+                    ))
+                })?;
+                search.language = Some(language);
+                i += 1;
+                continue;
+            }
+
+            let filter = words[i]
+                .split_once(':')
+                .filter(|(name, _)| matches!(*name, "after" | "before"));
+            let Some((name, value)) = filter else {
+                text.push(words[i]);
+                i += 1;
+                continue;
+            };
+
+            let following = &words[i + 1..];
+            let max_following = match value {
+                "" => MAX_DATE_FILTER_WORDS,
+                _ => MAX_DATE_FILTER_WORDS - 1,
+            }
+            .min(following.len());
+            let parsed = (0..=max_following).rev().find_map(|taken| {
+                let mut parts: Vec<&str> = Vec::new();
+                parts.extend((!value.is_empty()).then_some(value));
+                parts.extend(&following[..taken]);
+                parse_when(&parts.join(" "), now, tz, NaiveTime::MIN)
+                    .ok()
+                    .map(|at| (at, taken))
+            });
+            let Some((at, taken)) = parsed else {
+                return Err(DomainError::validation(format!(
+                    "Could not understand the date in {}",
+                    words[i]
+                )));
+            };
+
+            match name {
+                "after" => search.after = Some(at),
+                _ => search.before = Some(at),
+            }
+            i += 1 + taken;
+        }
+
+        search.text = text.join(" ");
+        Ok(search)
+    }
+
+    /// Whether something created at `at` passes the date filters
+    pub fn includes(&self, at: DateTime<Utc>) -> bool {
+        self.after.is_none_or(|after| at >= after) && self.before.is_none_or(|before| at < before)
+    }
+
+    /// Whether text in `language` passes the language filter
+    pub fn includes_language(&self, language: Option<&Language>) -> bool {
+        self.language
+            .as_ref()
+            .is_none_or(|wanted| language == Some(wanted))
+    }
+}
+
+/// Sort hits best first
+pub fn rank(hits: &mut [SearchHit]) {
+    hits.sort_by(|a, b| b.score().total_cmp(&a.score()));
+}
+
+/// Relevance of a document to the query terms.
+///
+/// Counts case-insensitive term occurrences, weighting title and tag hits
+/// above content hits, and damps the total by document length so a short
+/// focused note beats a long one mentioning the term in passing.
+pub fn relevance(query: &str, title: &str, content: &str, tags: &[&str]) -> f64 {
+    let title = title.to_lowercase();
+    let content = content.to_lowercase();
+    let words = content.split_whitespace().count() as f64;
+
+    let mut score = 0.0;
+    for term in query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+    {
+        score += TITLE_WEIGHT * title.matches(term.as_str()).count() as f64;
+        score += TITLE_WEIGHT * tags.iter().filter(|t| t.contains(term.as_str())).count() as f64;
+        score += content.matches(term.as_str()).count() as f64;
+    }
+    score / (1.0 + words).ln_1p()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_objects::NoteTitle;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_scope_parsing() {
+        assert_eq!(
+            "notes, versions".parse::<SearchScope>().unwrap(),
+            SearchScope {
+                notes: true,
+                versions: true,
+            }
+        );
+        assert!("attachments".parse::<SearchScope>().is_err());
+        assert!("bogus".parse::<SearchScope>().is_err());
+        assert!("".parse::<SearchScope>().is_err());
+    }
+
+    #[test]
+    fn test_parsed_search_takes_out_date_filters() {
+        use chrono::TimeZone;
+
+        // Saturday
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 18, 30, 0).unwrap();
+        let search =
+            ParsedSearch::parse("budget after:last week before:2026-03-12 q1", now, Tz::UTC)
+                .unwrap();
+
+        assert_eq!(search.text, "budget q1");
+        assert_eq!(
+            search.after,
+            Some(Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            search.before,
+            Some(Utc.with_ymd_and_hms(2026, 3, 12, 0, 0, 0).unwrap())
+        );
+        assert!(search.includes(Utc.with_ymd_and_hms(2026, 3, 5, 12, 0, 0).unwrap()));
+        assert!(!search.includes(Utc.with_ymd_and_hms(2026, 3, 12, 0, 0, 0).unwrap()));
+
+        let warsaw =
+            ParsedSearch::parse("after: yesterday", now, "Europe/Warsaw".parse().unwrap()).unwrap();
+        assert_eq!(warsaw.text, "");
+        assert_eq!(
+            warsaw.after,
+            Some(Utc.with_ymd_and_hms(2026, 3, 12, 23, 0, 0).unwrap())
+        );
+
+        assert_eq!(
+            ParsedSearch::parse("http://example.com", now, Tz::UTC)
+                .unwrap()
+                .text,
+            "http://example.com"
+        );
+        assert!(ParsedSearch::parse("after:someday", now, Tz::UTC).is_err());
+    }
+
+    #[test]
+    fn test_parsed_search_takes_out_language_filter() {
+        let search = ParsedSearch::parse("przepis lang:PL zupa", Utc::now(), Tz::UTC).unwrap();
+
+        assert_eq!(search.text, "przepis zupa");
+        let polish = Language::new("pl").unwrap();
+        assert_eq!(search.language, Some(polish.clone()));
+        assert!(search.includes_language(Some(&polish)));
+        assert!(!search.includes_language(Language::new("en").ok().as_ref()));
+        assert!(!search.includes_language(None));
+        assert!(
+            ParsedSearch::parse("soup", Utc::now(), Tz::UTC)
+                .unwrap()
+                .includes_language(None)
+        );
+        assert!(ParsedSearch::parse("lang:polish", Utc::now(), Tz::UTC).is_err());
+    }
+
+    #[test]
+    fn test_title_hits_outrank_content_hits() {
+        let title = relevance("rust", "Rust notes", "about ownership", &[]);
+        let content = relevance("rust", "Notes", "about rust ownership", &[]);
+
+        assert!(title > content);
+        assert_eq!(
+            relevance("python", "Rust notes", "about ownership", &[]),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_versions_rank_below_identical_notes() {
+        let note = Note::new(Uuid::new_v4(), NoteTitle::try_from("Rust").ok(), "rust");
+        let version = NoteVersion::new(note.id, Some("Rust".to_string()), "rust".to_string());
+
+        let mut hits = vec![
+            SearchHit::version(version, "rust"),
+            SearchHit::note(note, "rust"),
+        ];
+        rank(&mut hits);
+
+        assert_eq!(hits[0].kind(), SearchHitKind::Note);
+        assert_eq!(hits[1].kind(), SearchHitKind::Version);
+    }
+}
@@ -3,14 +3,43 @@
 //! Services orchestrate business logic, enforce rules, and coordinate
 //! between repositories. They are the \"use cases\" of the application.
 
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::collections::HashSet;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::entities::{MAX_TAGS_PER_NOTE, Note, NoteFilter, NoteVersion, Tag, User};
-use crate::errors::{DomainError, DomainResult};
-use crate::ports::MessageBroker;
-use crate::repositories::{NoteRepository, TagRepository, UserRepository};
-use crate::value_objects::{Email, NoteTitle, TagName};
+use crate::archive_policy::{AutoArchivePolicy, MAX_AUTO_ARCHIVE_DAYS};
+use crate::authorization::{Action, OwnerPolicy, Resource};
+use crate::capture::{Capture, default_reminder_time};
+use crate::dates::{parse_when, validate_timezone};
+use crate::entities::{
+    DEFAULT_MAX_PINNED_NOTES, DEFAULT_VERSION_DEBOUNCE_MINUTES, EditorPreferences, EmailChange,
+    MAX_TAGS_PER_NOTE, Note, NoteCounts, NoteFilter, NoteSortOrder, NoteVersion, Tag, User,
+    UserSettings,
+};
+use crate::errors::{DomainError, DomainResult, RepositoryError};
+use crate::event_log::{EventDispatcher, LoggedEventKind};
+use crate::events::DomainEvent;
+use crate::geo::{BoundingBox, GeoPoint, MAX_NEARBY_RADIUS_KM, NearbyNote};
+use crate::includes::{expand_includes, has_includes};
+use crate::instance::InstanceSettingsService;
+use crate::language::Language;
+use crate::onboarding::OnboardingService;
+use crate::ports::{AuthorizationPolicy, MessageBroker, PasswordHasher, TextGenerator};
+use crate::query::NoteQuery;
+use crate::repositories::{
+    NoteRepository, SearchHistoryRepository, TagAliasRepository, TagRepository, UnitOfWork,
+    UserRepository,
+};
+use crate::search::{
+    DEFAULT_HISTORY_LIMIT, MAX_HISTORY_LIMIT, MAX_SUGGESTIONS, MAX_VERSION_HITS, ParsedSearch,
+    SearchHistoryEntry, SearchHit, SearchScope, SearchSuggestions, rank,
+};
+use crate::tag_cleanup::UnusedTagPolicy;
+use crate::titles::{self, AUTO_TITLE_QUIET_MINUTES};
+use crate::trash::TrashPurgeReport;
+use crate::value_objects::{Email, MAX_NOTE_TITLE_LENGTH, NoteTitle, Password, PlaceName, TagName};
 
 /// Request to create a new note
 #[derive(Debug, Clone)]
@@ -23,6 +52,9 @@ pub struct CreateNoteRequest {
     pub tags: Vec<TagName>,
     pub color: Option<String>,
     pub is_pinned: bool,
+    pub location: Option<GeoPoint>,
+    pub place_name: Option<PlaceName>,
+    pub remind_at: Option<DateTime<Utc>>,
 }
 
 /// Request to update an existing note
@@ -38,6 +70,38 @@ pub struct UpdateNoteRequest {
     pub color: Option<String>,
     /// Pre-validated TagName values
     pub tags: Option<Vec<TagName>>,
+    /// `Some(None)` removes the location
+    pub location: Option<Option<GeoPoint>>,
+    /// `Some(None)` removes the place name
+    pub place_name: Option<Option<PlaceName>>,
+    /// `Some(None)` removes the reminder
+    pub remind_at: Option<Option<DateTime<Utc>>>,
+}
+
+/// Request to update user settings
+///
+/// `None` fields are left unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSettingsRequest {
+    pub default_note_color: Option<String>,
+    pub default_sort: Option<NoteSortOrder>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub editor: Option<EditorPreferences>,
+    pub smart_features_enabled: Option<bool>,
+    /// `Some(None)` disables auto-archival
+    pub auto_archive_after_days: Option<Option<u32>>,
+    pub search_history_enabled: Option<bool>,
+    pub auto_title_enabled: Option<bool>,
+}
+
+/// Request to update a user's profile
+///
+/// `None` means "don't change", `Some(None)` clears the field.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateProfileRequest {
+    pub display_name: Option<Option<String>>,
+    pub avatar_url: Option<Option<String>>,
 }
 
 /// Service for Note operations
@@ -45,6 +109,16 @@ pub struct NoteService {
     note_repo: Arc<dyn NoteRepository>,
     tag_repo: Arc<dyn TagRepository>,
     message_broker: Option<Arc<dyn MessageBroker>>,
+    events: Option<Arc<EventDispatcher>>,
+    user_repo: Option<Arc<dyn UserRepository>>,
+    unit_of_work: Option<Arc<dyn UnitOfWork>>,
+    search_history: Option<Arc<dyn SearchHistoryRepository>>,
+    tag_aliases: Option<Arc<dyn TagAliasRepository>>,
+    instance_settings: Option<Arc<InstanceSettingsService>>,
+    text_generator: Option<Arc<dyn TextGenerator>>,
+    pub(crate) policy: Arc<dyn AuthorizationPolicy>,
+    max_pinned_notes: usize,
+    version_debounce: chrono::Duration,
 }
 
 impl NoteService {
@@ -53,26 +127,199 @@ impl NoteService {
             note_repo,
             tag_repo,
             message_broker: None,
+            events: None,
+            user_repo: None,
+            unit_of_work: None,
+            search_history: None,
+            tag_aliases: None,
+            instance_settings: None,
+            text_generator: None,
+            policy: Arc::new(OwnerPolicy),
+            max_pinned_notes: DEFAULT_MAX_PINNED_NOTES,
+            version_debounce: chrono::Duration::minutes(i64::from(
+                DEFAULT_VERSION_DEBOUNCE_MINUTES,
+            )),
         }
     }
 
+    /// Decide who may read, update and delete notes; owners only by default
+    pub fn with_authorization_policy(mut self, policy: Arc<dyn AuthorizationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Builder method to override the maximum number of pinned notes per user
+    pub fn with_max_pinned_notes(mut self, max: usize) -> Self {
+        self.max_pinned_notes = max;
+        self
+    }
+
+    /// Builder method to take the pin limit and note length limit from the
+    /// runtime instance settings, overriding [`Self::with_max_pinned_notes`]
+    pub fn with_instance_settings(mut self, settings: Arc<InstanceSettingsService>) -> Self {
+        self.instance_settings = Some(settings);
+        self
+    }
+
+    /// Builder method to override how long repeated edits by the same user
+    /// share one version snapshot (0 snapshots every edit)
+    pub fn with_version_debounce_minutes(mut self, minutes: u32) -> Self {
+        self.version_debounce = chrono::Duration::minutes(i64::from(minutes));
+        self
+    }
+
     /// Builder method to set the message broker
     pub fn with_message_broker(mut self, broker: Arc<dyn MessageBroker>) -> Self {
         self.message_broker = Some(broker);
         self
     }
 
-    /// Helper to publish note update events
-    async fn publish_note_event(&self, note: &Note) {
-        if let Some(ref broker) = self.message_broker {
-            if let Err(e) = broker.publish_note_updated(note).await {
-                tracing::error!(note_id = %note.id, "Failed to publish note event: {}", e);
-            } else {
-                tracing::info!(note_id = %note.id, "Published note.updated event");
+    /// Builder method to set the event dispatcher that user actions are
+    /// recorded with
+    pub fn with_event_dispatcher(mut self, events: Arc<EventDispatcher>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Builder method to set the user repository, enabling per-user settings
+    /// (default note color, smart-features opt-out)
+    pub fn with_user_repository(mut self, user_repo: Arc<dyn UserRepository>) -> Self {
+        self.user_repo = Some(user_repo);
+        self
+    }
+
+    /// Builder method to set the unit of work, making note, tag association
+    /// and version writes atomic
+    pub fn with_unit_of_work(mut self, unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        self.unit_of_work = Some(unit_of_work);
+        self
+    }
+
+    /// Builder method to set the search history repository, enabling
+    /// recent query suggestions
+    pub fn with_search_history(mut self, search_history: Arc<dyn SearchHistoryRepository>) -> Self {
+        self.search_history = Some(search_history);
+        self
+    }
+
+    /// Builder method to set the tag alias repository, so tag names that
+    /// are aliases resolve to their canonical tag
+    pub fn with_tag_aliases(mut self, tag_aliases: Arc<dyn TagAliasRepository>) -> Self {
+        self.tag_aliases = Some(tag_aliases);
+        self
+    }
+
+    /// Builder method to set the text generator untitled notes are named
+    /// with; without one, they are named after their first line
+    pub fn with_text_generator(mut self, text_generator: Arc<dyn TextGenerator>) -> Self {
+        self.text_generator = Some(text_generator);
+        self
+    }
+
+    /// Persist a note with its tag associations and an optional version.
+    ///
+    /// `stale_tags` are associations to drop; they are only needed without a
+    /// unit of work, which replaces all associations anyway.
+    async fn persist_note(
+        &self,
+        note: &Note,
+        stale_tags: &[Tag],
+        version: Option<&NoteVersion>,
+    ) -> DomainResult<()> {
+        if let Some(ref unit_of_work) = self.unit_of_work {
+            return unit_of_work.commit_note(note, version).await;
+        }
+
+        if let Some(version) = version {
+            self.note_repo.save_version(version).await?;
+        }
+        self.note_repo.save(note).await?;
+        for tag in stale_tags {
+            self.tag_repo.remove_from_note(tag.id, note.id).await?;
+        }
+        let tag_ids: Vec<Uuid> = note.tags.iter().map(|t| t.id).collect();
+        if !tag_ids.is_empty() {
+            self.tag_repo.add_many_to_note(&tag_ids, note.id).await?;
+        }
+        Ok(())
+    }
+
+    /// Load the user's settings, falling back to defaults if unavailable
+    async fn user_settings(&self, user_id: Uuid) -> UserSettings {
+        let Some(ref user_repo) = self.user_repo else {
+            return UserSettings::default();
+        };
+
+        match user_repo.find_settings(user_id).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::warn!(%user_id, "Failed to load user settings: {}", e);
+                UserSettings::default()
             }
         }
     }
 
+    /// The user's timezone, for reading relative dates
+    async fn time_zone(&self, user_id: Uuid) -> Tz {
+        self.user_settings(user_id).await.time_zone()
+    }
+
+    /// Reject content longer than the instance settings allow
+    fn check_note_length(&self, content: &str) -> DomainResult<()> {
+        match &self.instance_settings {
+            Some(settings) => settings.current().check_note_length(content),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the pin limit and return the position for a newly pinned note
+    /// (after all currently pinned notes)
+    async fn next_pin_order(&self, user_id: Uuid) -> DomainResult<i32> {
+        let pinned = self
+            .note_repo
+            .find_by_user(user_id, NoteFilter::new().pinned())
+            .await?;
+
+        let max = match &self.instance_settings {
+            Some(settings) => settings.current().max_pinned_notes,
+            None => self.max_pinned_notes,
+        };
+        if pinned.len() >= max {
+            return Err(DomainError::PinLimitExceeded { max });
+        }
+
+        Ok(pinned
+            .iter()
+            .filter_map(|n| n.pin_order)
+            .max()
+            .map_or(0, |max| max + 1))
+    }
+
+    /// Record what the user did, if an event dispatcher is set
+    async fn log_events(&self, user_id: Uuid, kinds: Vec<LoggedEventKind>) {
+        if let Some(ref events) = self.events {
+            events.dispatch(user_id, kinds).await;
+        }
+    }
+
+    /// Helper to publish note update events.
+    ///
+    /// Published regardless of the owner's smart features setting, since
+    /// integrations other than embedding consume them too; the worker
+    /// checks the setting before embedding.
+    async fn publish_note_event(&self, note: &Note) {
+        let Some(ref broker) = self.message_broker else {
+            return;
+        };
+
+        let event = DomainEvent::note_updated(note.clone());
+        if let Err(e) = broker.publish(&event).await {
+            tracing::error!(note_id = %note.id, "Failed to publish note event: {}", e);
+        } else {
+            tracing::info!(note_id = %note.id, event_id = %event.id, "Published note.updated event");
+        }
+    }
+
     /// Create a new note with optional tags
     pub async fn create_note(&self, req: CreateNoteRequest) -> DomainResult<Note> {
         // Title validation is handled by NoteTitle type - no need for runtime check
@@ -82,36 +329,71 @@ impl NoteService {
         if req.tags.len() > MAX_TAGS_PER_NOTE {
             return Err(DomainError::tag_limit_exceeded(req.tags.len()));
         }
+        self.check_note_length(&req.content)?;
 
         // Create the note
         let mut note = Note::new(req.user_id, req.title, req.content);
-        note.is_pinned = req.is_pinned;
-        if let Some(color) = req.color {
-            note.set_color(color);
+        note.location = req.location;
+        note.place_name = req.place_name;
+        note.remind_at = req.remind_at;
+        if req.is_pinned {
+            note.is_pinned = true;
+            note.pin_order = Some(self.next_pin_order(req.user_id).await?);
+        }
+        match req.color {
+            Some(color) => note.set_color(color),
+            None if self.user_repo.is_some() => {
+                note.set_color(self.user_settings(req.user_id).await.default_note_color)
+            }
+            None => {}
         }
 
-        // Process tags
+        // Process tags; an alias and its canonical name yield the tag once
         for tag_name in &req.tags {
             let tag = self
                 .get_or_create_tag(req.user_id, tag_name.clone())
                 .await?;
-            note.tags.push(tag);
+            if !note.tags.iter().any(|t| t.id == tag.id) {
+                note.tags.push(tag);
+            }
         }
 
-        // Save the note
-        self.note_repo.save(&note).await?;
-
-        // Associate tags with the note
-        for tag in &note.tags {
-            self.tag_repo.add_to_note(tag.id, note.id).await?;
-        }
+        // Save the note with its tag associations
+        self.persist_note(&note, &[], None).await?;
 
         // Publish event for smart features processing
         self.publish_note_event(&note).await;
+        self.log_events(note.user_id, vec![LoggedEventKind::note_created(&note)])
+            .await;
 
         Ok(note)
     }
 
+    /// Create a note from quick-capture text such as `Call Bob #work @tomorrow`
+    pub async fn capture(&self, user_id: Uuid, text: &str) -> DomainResult<Note> {
+        let capture = Capture::parse(text, Utc::now(), self.time_zone(user_id).await)?;
+        self.create_note(CreateNoteRequest {
+            user_id,
+            title: None,
+            content: capture.content,
+            tags: capture.tags,
+            color: None,
+            is_pinned: capture.is_pinned,
+            location: None,
+            place_name: None,
+            remind_at: capture.remind_at,
+        })
+        .await
+    }
+
+    /// Read a reminder time such as `next friday 9am` in the user's timezone
+    ///
+    /// Days given without a time resolve to the morning.
+    pub async fn parse_reminder(&self, user_id: Uuid, text: &str) -> DomainResult<DateTime<Utc>> {
+        let tz = self.time_zone(user_id).await;
+        parse_when(text, Utc::now(), tz, default_reminder_time())
+    }
+
     /// Update an existing note
     pub async fn update_note(&self, req: UpdateNoteRequest) -> DomainResult<Note> {
         // Find the note
@@ -121,20 +403,36 @@ impl NoteService {
             .await?
             .ok_or(DomainError::NoteNotFound(req.id))?;
 
-        // Authorization check
-        if note.user_id != req.user_id {
-            return Err(DomainError::unauthorized(
-                "Cannot modify another user's note",
+        self.policy
+            .authorize(req.user_id, Action::Update, &Resource::note(&note))
+            .await?;
+
+        if note.is_trashed() {
+            return Err(DomainError::validation(
+                "Restore the note from the trash before editing it",
             ));
         }
 
-        // Create version snapshot (save current state)
-        let version = NoteVersion::new(
-            note.id,
-            note.title.as_ref().map(|t| t.as_ref().to_string()),
-            note.content.clone(),
-        );
-        self.note_repo.save_version(&version).await?;
+        if note.is_locked {
+            return Err(DomainError::NoteLocked(note.id));
+        }
+
+        // Notes already longer than a lowered limit can still be edited in
+        // other ways
+        if let Some(content) = req.content.as_ref().filter(|c| **c != note.content) {
+            self.check_note_length(content)?;
+        }
+
+        // Snapshot the current state before title or content changes
+        let changes_text = req.title.as_ref().is_some_and(|t| *t != note.title)
+            || req.content.as_ref().is_some_and(|c| *c != note.content);
+        let version = if changes_text {
+            self.version_snapshot(&note, req.user_id).await?
+        } else {
+            None
+        };
+
+        let before = note.clone();
 
         // Apply updates - title is already validated via NoteTitle type
         if let Some(title) = req.title {
@@ -146,6 +444,9 @@ impl NoteService {
         }
 
         if let Some(pinned) = req.is_pinned {
+            if pinned && !note.is_pinned {
+                note.pin_order = Some(self.next_pin_order(note.user_id).await?);
+            }
             note.set_pinned(pinned);
         }
 
@@ -157,34 +458,66 @@ impl NoteService {
             note.set_color(color);
         }
 
+        if let Some(location) = req.location {
+            note.set_location(location);
+        }
+
+        if let Some(place_name) = req.place_name {
+            note.set_place_name(place_name);
+        }
+
+        if let Some(remind_at) = req.remind_at {
+            note.set_remind_at(remind_at);
+        }
+
         // Handle tag updates
+        let mut stale_tags = Vec::new();
         if let Some(tag_names) = req.tags {
             if tag_names.len() > MAX_TAGS_PER_NOTE {
                 return Err(DomainError::tag_limit_exceeded(tag_names.len()));
             }
 
-            // Remove old tags
-            for tag in &note.tags {
-                self.tag_repo.remove_from_note(tag.id, note.id).await?;
-            }
-
-            // Add new tags
-            note.tags.clear();
+            // Replace old tags
+            stale_tags = std::mem::take(&mut note.tags);
             for tag_name in tag_names {
                 let tag = self.get_or_create_tag(note.user_id, tag_name).await?;
-                self.tag_repo.add_to_note(tag.id, note.id).await?;
-                note.tags.push(tag);
+                if !note.tags.iter().any(|t| t.id == tag.id) {
+                    note.tags.push(tag);
+                }
             }
         }
 
-        self.note_repo.save(&note).await?;
+        self.persist_note(&note, &stale_tags, version.as_ref())
+            .await?;
 
         // Publish event for smart features processing
         self.publish_note_event(&note).await;
+        self.log_events(req.user_id, LoggedEventKind::note_changes(&before, &note))
+            .await;
 
         Ok(note)
     }
 
+    /// Version to store before `actor` edits `note`.
+    ///
+    /// Returns `None` while the latest version is by the same user and
+    /// younger than the debounce window, so a burst of edits keeps only the
+    /// state from before the burst.
+    async fn version_snapshot(
+        &self,
+        note: &Note,
+        actor: Uuid,
+    ) -> DomainResult<Option<NoteVersion>> {
+        if !self.version_debounce.is_zero()
+            && let Some(latest) = self.note_repo.find_latest_version(note.id).await?
+            && latest.created_by == Some(actor)
+            && Utc::now() - latest.created_at < self.version_debounce
+        {
+            return Ok(None);
+        }
+        Ok(Some(NoteVersion::snapshot(note, actor)))
+    }
+
     /// Get a note by ID with authorization check
     pub async fn get_note(&self, id: Uuid, user_id: Uuid) -> DomainResult<Note> {
         let note = self
@@ -193,11 +526,9 @@ impl NoteService {
             .await?
             .ok_or(DomainError::NoteNotFound(id))?;
 
-        if note.user_id != user_id {
-            return Err(DomainError::unauthorized(
-                "Cannot access another user's note",
-            ));
-        }
+        self.policy
+            .authorize(user_id, Action::Read, &Resource::note(&note))
+            .await?;
 
         Ok(note)
     }
@@ -219,711 +550,3925 @@ impl NoteService {
         self.note_repo.find_by_user(user_id, filter).await
     }
 
-    /// Delete a note with authorization check
+    /// How many notes the user has outside the trash
+    pub async fn count_notes(&self, user_id: Uuid) -> DomainResult<NoteCounts> {
+        self.note_repo.count_by_user(user_id).await
+    }
+
+    /// Find the user's live notes within `radius_km` of `center`, closest first
+    pub async fn find_nearby(
+        &self,
+        user_id: Uuid,
+        center: GeoPoint,
+        radius_km: f64,
+        include_archived: bool,
+    ) -> DomainResult<Vec<NearbyNote>> {
+        if !(radius_km > 0.0 && radius_km <= MAX_NEARBY_RADIUS_KM) {
+            return Err(DomainError::validation(format!(
+                "Radius must be greater than 0 and at most {} km",
+                MAX_NEARBY_RADIUS_KM
+            )));
+        }
+
+        let mut filter = NoteFilter::new().within(BoundingBox::around(&center, radius_km));
+        if !include_archived {
+            filter = filter.not_archived();
+        }
+
+        // The box also covers its corners; keep only notes inside the circle
+        let mut nearby: Vec<NearbyNote> = self
+            .note_repo
+            .find_by_user(user_id, filter)
+            .await?
+            .into_iter()
+            .filter_map(|note| {
+                let distance_km = center.distance_km(note.location.as_ref()?);
+                (distance_km <= radius_km).then_some(NearbyNote { note, distance_km })
+            })
+            .collect();
+        nearby.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+        Ok(nearby)
+    }
+
+    /// Run a structured query over the user's live notes
+    pub async fn query_notes(&self, user_id: Uuid, query: &NoteQuery) -> DomainResult<Vec<Note>> {
+        query.validate()?;
+        self.note_repo.query(user_id, query).await
+    }
+
+    /// Move a note to the trash with authorization check
+    ///
+    /// Tags and versions are kept so the note can be restored; the purge job
+    /// deletes it for good once the retention window has passed.
     pub async fn delete_note(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
-        let note = self
+        let mut note = self
             .note_repo
             .find_by_id(id)
             .await?
             .ok_or(DomainError::NoteNotFound(id))?;
 
-        if note.user_id != user_id {
-            return Err(DomainError::unauthorized(
-                "Cannot delete another user's note",
-            ));
+        self.policy
+            .authorize(user_id, Action::Delete, &Resource::note(&note))
+            .await?;
+
+        if note.is_locked {
+            return Err(DomainError::NoteLocked(note.id));
         }
 
-        // Remove tag associations
-        for tag in &note.tags {
-            self.tag_repo.remove_from_note(tag.id, id).await?;
+        if note.is_trashed() {
+            return Ok(());
         }
 
-        self.note_repo.delete(id).await
+        note.move_to_trash();
+        self.note_repo.save(&note).await?;
+        self.log_events(user_id, vec![LoggedEventKind::NoteTrashed { note_id: id }])
+            .await;
+        Ok(())
     }
 
-    /// Search notes by query
-    pub async fn search_notes(&self, user_id: Uuid, query: &str) -> DomainResult<Vec<Note>> {
-        if query.trim().is_empty() {
-            return Ok(Vec::new());
+    /// Lock a note against edits and deletion, or unlock it
+    pub async fn set_note_locked(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        locked: bool,
+    ) -> DomainResult<Note> {
+        let mut note = self.get_note(id, user_id).await?;
+
+        if note.is_locked != locked {
+            note.set_locked(locked);
+            self.note_repo.save(&note).await?;
+            let kind = if locked {
+                LoggedEventKind::NoteLocked { note_id: id }
+            } else {
+                LoggedEventKind::NoteUnlocked { note_id: id }
+            };
+            self.log_events(user_id, vec![kind]).await;
         }
-        self.note_repo.search(user_id, query).await
+
+        Ok(note)
     }
 
-    /// Get or create a tag by name
-    ///
-    /// Handles race conditions gracefully: if a concurrent request creates
-    /// the same tag, we catch the unique constraint violation and retry the lookup.
-    async fn get_or_create_tag(&self, user_id: Uuid, name: TagName) -> DomainResult<Tag> {
-        // First, try to find existing tag
-        if let Some(tag) = self.tag_repo.find_by_name(user_id, name.as_ref()).await? {
-            return Ok(tag);
-        }
+    /// Take a note back out of the trash
+    pub async fn restore_note(&self, id: Uuid, user_id: Uuid) -> DomainResult<Note> {
+        let mut note = self.get_note(id, user_id).await?;
 
-        // Tag doesn't exist, try to create it
-        let tag = Tag::new(name.clone(), user_id);
-        match self.tag_repo.save(&tag).await {
-            Ok(()) => Ok(tag),
-            Err(DomainError::RepositoryError(ref e)) if e.contains("UNIQUE constraint") => {
-                // Race condition: another request created the tag between our check and save
-                // Retry the lookup
-                tracing::debug!(tag_name = %name, "Tag creation race condition detected, retrying lookup");
-                self.tag_repo
-                    .find_by_name(user_id, name.as_ref())
-                    .await?
-                    .ok_or_else(|| DomainError::validation("Tag creation race condition"))
-            }
-            Err(e) => Err(e),
+        if note.is_trashed() {
+            note.restore();
+            self.note_repo.save(&note).await?;
+            self.log_events(user_id, vec![LoggedEventKind::NoteRestored { note_id: id }])
+                .await;
         }
+
+        Ok(note)
     }
-}
 
-/// Service for Tag operations
-pub struct TagService {
-    tag_repo: Arc<dyn TagRepository>,
-}
+    /// Permanently delete every note trashed before `cutoff`, across all users
+    pub async fn purge_trash(&self, cutoff: DateTime<Utc>) -> DomainResult<TrashPurgeReport> {
+        let mut report = TrashPurgeReport::default();
 
-impl TagService {
-    pub fn new(tag_repo: Arc<dyn TagRepository>) -> Self {
-        Self { tag_repo }
-    }
+        for note in self.note_repo.find_trashed_before(cutoff).await? {
+            report.reclaimed_bytes += self.note_repo.delete(note.id).await?;
+            report.note_ids.push(note.id);
 
-    /// Create a new tag (TagName is pre-validated)
-    pub async fn create_tag(&self, user_id: Uuid, name: TagName) -> DomainResult<Tag> {
-        // Check if tag already exists
-        if self
-            .tag_repo
-            .find_by_name(user_id, name.as_ref())
-            .await?
-            .is_some()
-        {
-            return Err(DomainError::TagAlreadyExists(name.into_inner()));
+            if let Some(ref broker) = self.message_broker {
+                if let Err(e) = broker.publish_note_deleted(note.user_id, note.id).await {
+                    tracing::error!(note_id = %note.id, "Failed to publish note deletion: {}", e);
+                }
+            }
+            self.log_events(
+                note.user_id,
+                vec![LoggedEventKind::NotePurged { note_id: note.id }],
+            )
+            .await;
         }
 
-        let tag = Tag::new(name, user_id);
-        self.tag_repo.save(&tag).await?;
-        Ok(tag)
+        Ok(report)
     }
 
-    /// List all tags for a user
-    pub async fn list_tags(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
-        self.tag_repo.find_by_user(user_id).await
+    /// `notes` with their `{{include:…}}` directives expanded from the
+    /// user's live notes, for rendering; stored notes keep their directives
+    pub async fn expand_includes(
+        &self,
+        user_id: Uuid,
+        notes: Vec<Note>,
+    ) -> DomainResult<Vec<Note>> {
+        if !notes.iter().any(|note| has_includes(&note.content)) {
+            return Ok(notes);
+        }
+
+        let snippets = self
+            .note_repo
+            .find_by_user(user_id, NoteFilter::new())
+            .await?;
+        Ok(notes
+            .into_iter()
+            .map(|mut note| {
+                if has_includes(&note.content) {
+                    note.content = expand_includes(&note, &snippets);
+                }
+                note
+            })
+            .collect())
     }
 
-    /// Delete a tag
-    pub async fn delete_tag(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
-        let tag = self
-            .tag_repo
-            .find_by_id(id)
-            .await?
-            .ok_or(DomainError::TagNotFound(id))?;
+    /// Duplicate a note with its content, color and tags.
+    ///
+    /// The copy starts unpinned and unarchived. With `prefix_title`, its
+    /// title becomes "Copy of <title>" (truncated to the title limit).
+    pub async fn duplicate_note(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        prefix_title: bool,
+    ) -> DomainResult<Note> {
+        let source = self.get_note(id, user_id).await?;
+
+        let title = match source.title {
+            Some(ref title) if prefix_title => {
+                let prefixed: String = format!("Copy of {}", title.as_ref())
+                    .chars()
+                    .take(MAX_NOTE_TITLE_LENGTH)
+                    .collect();
+                Some(NoteTitle::try_from(prefixed)?)
+            }
+            title => title,
+        };
 
-        if tag.user_id != user_id {
-            return Err(DomainError::unauthorized(
-                "Cannot delete another user's tag",
-            ));
-        }
+        let mut note = Note::new(user_id, title, source.content);
+        note.color = source.color;
+        note.location = source.location;
+        note.place_name = source.place_name;
+        note.tags = source.tags.into_iter().take(MAX_TAGS_PER_NOTE).collect();
+
+        self.persist_note(&note, &[], None).await?;
 
-        self.tag_repo.delete(id).await
+        self.publish_note_event(&note).await;
+        self.log_events(
+            user_id,
+            vec![LoggedEventKind::NoteDuplicated {
+                note_id: note.id,
+                source_id: id,
+            }],
+        )
+        .await;
+
+        Ok(note)
     }
 
-    /// Rename a tag (new_name is pre-validated TagName)
-    pub async fn rename_tag(
+    /// Rearrange the pinned section.
+    ///
+    /// `note_ids` must list every pinned note of the user exactly once, so
+    /// clients with a stale view cannot silently drop notes from the order.
+    /// Returns the pinned notes in their new order.
+    pub async fn reorder_pins(
         &self,
-        id: Uuid,
         user_id: Uuid,
-        new_name: TagName,
-    ) -> DomainResult<Tag> {
-        // Find the existing tag
-        let mut tag = self
-            .tag_repo
-            .find_by_id(id)
-            .await?
-            .ok_or(DomainError::TagNotFound(id))?;
+        note_ids: Vec<Uuid>,
+    ) -> DomainResult<Vec<Note>> {
+        let pinned = self
+            .note_repo
+            .find_by_user(user_id, NoteFilter::new().pinned())
+            .await?;
+
+        let mut requested = note_ids.clone();
+        requested.sort();
+        requested.dedup();
+        let mut expected: Vec<Uuid> = pinned.iter().map(|n| n.id).collect();
+        expected.sort();
 
-        // Authorization check
-        if tag.user_id != user_id {
-            return Err(DomainError::unauthorized(
-                "Cannot rename another user's tag",
+        if requested.len() != note_ids.len() || requested != expected {
+            return Err(DomainError::validation(
+                "Reorder must list each pinned note exactly once",
             ));
         }
 
-        // Check if new name already exists (and it's not the same tag)
-        if let Some(existing) = self
-            .tag_repo
-            .find_by_name(user_id, new_name.as_ref())
-            .await?
-        {
-            if existing.id != id {
-                return Err(DomainError::TagAlreadyExists(new_name.into_inner()));
-            }
-        }
+        self.note_repo.reorder_pins(user_id, &note_ids).await?;
 
-        // Update the name
-        tag.name = new_name;
-        self.tag_repo.save(&tag).await?;
-        Ok(tag)
+        let mut notes = pinned;
+        notes.sort_by_key(|n| note_ids.iter().position(|id| *id == n.id));
+        for (position, note) in notes.iter_mut().enumerate() {
+            note.pin_order = Some(position as i32);
+        }
+        Ok(notes)
     }
-}
-
-/// Service for User operations (OIDC-ready)
-pub struct UserService {
-    user_repo: Arc<dyn UserRepository>,
-}
 
-impl UserService {
-    pub fn new(user_repo: Arc<dyn UserRepository>) -> Self {
-        Self { user_repo }
+    /// Notes the given policy would archive at `now`
+    pub async fn auto_archive_candidates(
+        &self,
+        user_id: Uuid,
+        policy: AutoArchivePolicy,
+        now: DateTime<Utc>,
+    ) -> DomainResult<Vec<Note>> {
+        let mut filter = NoteFilter::new();
+        filter.is_archived = Some(false);
+
+        let notes = self.note_repo.find_by_user(user_id, filter).await?;
+        Ok(notes
+            .into_iter()
+            .filter(|note| policy.applies_to(note, now))
+            .collect())
     }
 
-    pub async fn find_or_create(&self, subject: &str, email: &str) -> DomainResult<User> {
-        // 1. Try to find by subject (OIDC id)
-        if let Some(user) = self.user_repo.find_by_subject(subject).await? {
-            return Ok(user);
+    /// Apply the user's auto-archive policy, returning the number of notes archived.
+    ///
+    /// Does nothing for users who have not opted in.
+    pub async fn run_auto_archive(&self, user_id: Uuid, now: DateTime<Utc>) -> DomainResult<usize> {
+        let Some(policy) = AutoArchivePolicy::from_settings(&self.user_settings(user_id).await)
+        else {
+            return Ok(0);
+        };
+
+        let candidates = self.auto_archive_candidates(user_id, policy, now).await?;
+        for note in &candidates {
+            self.update_note(UpdateNoteRequest {
+                id: note.id,
+                user_id,
+                title: None,
+                content: None,
+                is_pinned: None,
+                is_archived: Some(true),
+                color: None,
+                tags: None,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            })
+            .await?;
         }
 
-        // 2. Try to find by email
-        if let Some(mut user) = self.user_repo.find_by_email(email).await? {
-            // Link subject if missing (account linking logic)
-            if user.subject != subject {
-                user.subject = subject.to_string();
-                self.user_repo.save(&user).await?;
-            }
-            return Ok(user);
+        Ok(candidates.len())
+    }
+
+    /// Title the user's untitled notes after their content, returning the
+    /// number of notes titled.
+    ///
+    /// Does nothing for users who have not opted in. Notes edited within the
+    /// last [`AUTO_TITLE_QUIET_MINUTES`] are left for a later run, so titles
+    /// describe what was written rather than its first words.
+    pub async fn run_auto_title(&self, user_id: Uuid, now: DateTime<Utc>) -> DomainResult<usize> {
+        if !self.user_settings(user_id).await.auto_title_enabled {
+            return Ok(0);
         }
 
-        // 3. Create new user
-        let email = Email::try_from(email)?;
-        let user = User::new(subject, email);
-        self.user_repo.save(&user).await?;
+        let settled_before = now - chrono::Duration::minutes(AUTO_TITLE_QUIET_MINUTES);
+        let candidates: Vec<Note> = self
+            .note_repo
+            .find_by_user(user_id, NoteFilter::new())
+            .await?
+            .into_iter()
+            .filter(|note| {
+                note.title.is_none()
+                    && !note.is_locked
+                    && !note.content.trim().is_empty()
+                    && note.updated_at <= settled_before
+            })
+            .collect();
 
-        Ok(user)
-    }
+        let mut titled = 0;
+        for candidate in candidates {
+            let Some(title) = self.generate_title(&candidate.content).await else {
+                continue;
+            };
+            // Generating takes a while; edits made meanwhile win
+            let Some(mut note) = self.note_repo.find_by_id(candidate.id).await? else {
+                continue;
+            };
+            if note.title.is_some() || note.updated_at != candidate.updated_at {
+                continue;
+            }
 
-    pub async fn find_by_id(&self, id: Uuid) -> DomainResult<User> {
-        self.user_repo
-            .find_by_id(id)
-            .await?
-            .ok_or(DomainError::UserNotFound(id))
-    }
+            // Not an edit by the user, so `updated_at` is left alone
+            note.title = Some(title);
+            self.note_repo.save(&note).await?;
+            self.log_events(
+                user_id,
+                vec![LoggedEventKind::NoteEdited {
+                    note_id: note.id,
+                    title: Some(note.title_str().to_string()),
+                    previous_title: None,
+                }],
+            )
+            .await;
+            titled += 1;
+        }
 
-    pub async fn find_by_email(&self, email: &str) -> DomainResult<Option<User>> {
-        self.user_repo.find_by_email(email).await
+        Ok(titled)
     }
 
-    pub async fn create_local(&self, email: &str, password_hash: &str) -> DomainResult<User> {
-        let email = Email::try_from(email)?;
-        let user = User::new_local(email, password_hash);
-        self.user_repo.save(&user).await?;
-        Ok(user)
+    /// A title for `content` from the text generator, or else its first line
+    async fn generate_title(&self, content: &str) -> Option<NoteTitle> {
+        if let Some(ref generator) = self.text_generator {
+            match generator.generate(&titles::title_prompt(content)).await {
+                Ok(answer) => {
+                    if let Some(title) = titles::generated_title(&answer) {
+                        return Some(title);
+                    }
+                    tracing::warn!("Text generator gave no usable title, using the first line");
+                }
+                Err(e) => tracing::warn!("Title generation failed, using the first line: {}", e),
+            }
+        }
+        titles::heuristic_title(content)
     }
-}
-
-/// Service for Smart Features (Embeddings, Vector Search, Linking)
-pub struct SmartNoteService {
-    embedding_generator: Arc<dyn crate::ports::EmbeddingGenerator>,
-    vector_store: Arc<dyn crate::ports::VectorStore>,
-    link_repo: Arc<dyn crate::ports::LinkRepository>,
-}
 
-impl SmartNoteService {
-    pub fn new(
-        embedding_generator: Arc<dyn crate::ports::EmbeddingGenerator>,
-        vector_store: Arc<dyn crate::ports::VectorStore>,
-        link_repo: Arc<dyn crate::ports::LinkRepository>,
-    ) -> Self {
-        Self {
-            embedding_generator,
-            vector_store,
-            link_repo,
+    /// Search notes by query, skipping archived notes unless `include_archived`
+    pub async fn search_notes(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+    ) -> DomainResult<Vec<Note>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
         }
+        let search = self.parse_search(user_id, query).await?;
+        self.record_search(user_id, query).await;
+
+        let notes = if search.text.is_empty() {
+            let filter = if include_archived {
+                NoteFilter::new()
+            } else {
+                NoteFilter::new().not_archived()
+            };
+            self.note_repo.find_by_user(user_id, filter).await?
+        } else {
+            self.note_repo
+                .search(user_id, &search.text, include_archived)
+                .await?
+        };
+        Ok(notes
+            .into_iter()
+            .filter(|note| {
+                search.includes(note.created_at) && search.includes_language(note.language.as_ref())
+            })
+            .collect())
     }
 
-    /// Process a note to generate embeddings and find similar notes
-    pub async fn process_note(&self, note: &Note) -> DomainResult<()> {
-        // 1. Generate embedding
-        let embedding = self
-            .embedding_generator
-            .generate_embedding(&note.content)
-            .await?;
+    /// Search the given scopes and return one list ranked by relevance.
+    ///
+    /// Only the best matching version of each note is kept.
+    pub async fn search_scoped(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        scope: SearchScope,
+        include_archived: bool,
+    ) -> DomainResult<Vec<SearchHit>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let search = self.parse_search(user_id, query).await?;
+        self.record_search(user_id, query).await;
+        if search.text.is_empty() {
+            return Err(DomainError::validation(
+                "Scoped search needs words to search for besides filters",
+            ));
+        }
+        let text = search.text.as_str();
 
-        // 2. Upsert to vector store
-        self.vector_store.upsert(note.id, &embedding).await?;
+        let mut hits = Vec::new();
+        if scope.notes {
+            let notes = self
+                .note_repo
+                .search(user_id, text, include_archived)
+                .await?;
+            hits.extend(
+                notes
+                    .into_iter()
+                    .filter(|n| {
+                        search.includes(n.created_at)
+                            && search.includes_language(n.language.as_ref())
+                    })
+                    .map(|n| SearchHit::note(n, text)),
+            );
+        }
+        if scope.versions {
+            let versions = self
+                .note_repo
+                .search_versions(user_id, text, include_archived, MAX_VERSION_HITS)
+                .await?;
+            hits.extend(
+                versions
+                    .into_iter()
+                    .filter(|v| {
+                        search.includes(v.created_at)
+                            && (search.language.is_none()
+                                || search.includes_language(Language::detect(&v.content).as_ref()))
+                    })
+                    .map(|v| SearchHit::version(v, text)),
+            );
+        }
 
-        // 3. Find similar notes
-        // TODO: Make limit configurable
-        let similar = self.vector_store.find_similar(&embedding, 5).await?;
+        rank(&mut hits);
+        let mut seen_versions_of = HashSet::new();
+        hits.retain(|hit| match hit {
+            SearchHit::Version { version, .. } => seen_versions_of.insert(version.note_id),
+            SearchHit::Note { .. } => true,
+        });
+        Ok(hits)
+    }
 
-        // 4. Create links
-        let links: Vec<crate::entities::NoteLink> = similar
-            .into_iter()
-            .filter(|(id, _)| *id != note.id) // Exclude self
-            .map(|(target_id, score)| crate::entities::NoteLink::new(note.id, target_id, score))
-            .collect();
+    /// Suggest note titles, tags and earlier queries starting with `prefix`
+    pub async fn suggest_search(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+    ) -> DomainResult<SearchSuggestions> {
+        let prefix = prefix.trim();
+        if prefix.is_empty() {
+            return Ok(SearchSuggestions::default());
+        }
 
-        // 5. Save links (replacing old ones)
-        if !links.is_empty() {
-            self.link_repo.delete_links_for_source(note.id).await?;
-            self.link_repo.save_links(&links).await?;
+        let titles = self
+            .note_repo
+            .suggest_titles(user_id, prefix, MAX_SUGGESTIONS)
+            .await?;
+        let tags = self
+            .tag_repo
+            .find_by_prefix(user_id, &prefix.to_lowercase(), MAX_SUGGESTIONS)
+            .await?;
+        let mut queries = Vec::new();
+        if let Some(ref history) = self.search_history {
+            queries = history
+                .find_frequent(user_id, prefix, MAX_SUGGESTIONS)
+                .await?;
+            for query in history
+                .find_recent(user_id, prefix, MAX_SUGGESTIONS)
+                .await?
+            {
+                if queries.len() < MAX_SUGGESTIONS && !queries.contains(&query) {
+                    queries.push(query);
+                }
+            }
         }
 
-        Ok(())
+        Ok(SearchSuggestions {
+            titles,
+            tags,
+            queries,
+        })
     }
 
-    /// Get related notes for a given note ID
-    pub async fn get_related_notes(
+    /// List the user's recent searches, newest first
+    pub async fn search_history(
         &self,
-        note_id: Uuid,
-    ) -> DomainResult<Vec<crate::entities::NoteLink>> {
-        self.link_repo.get_links_for_note(note_id).await
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::repositories::tests::MockNoteRepository;
-    use std::collections::HashMap;
-    use std::sync::Mutex;
+        user_id: Uuid,
+        limit: Option<usize>,
+    ) -> DomainResult<Vec<SearchHistoryEntry>> {
+        let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+        if limit == 0 || limit > MAX_HISTORY_LIMIT {
+            return Err(DomainError::validation(format!(
+                "limit must be between 1 and {}",
+                MAX_HISTORY_LIMIT
+            )));
+        }
 
-    // Mock implementations for testing
-    struct MockTagRepository {
-        tags: Mutex<HashMap<Uuid, Tag>>,
-        note_tags: Mutex<HashMap<(Uuid, Uuid), ()>>,
+        match self.search_history {
+            Some(ref history) => history.find_by_user(user_id, limit).await,
+            None => Ok(Vec::new()),
+        }
     }
 
-    impl MockTagRepository {
-        fn new() -> Self {
-            Self {
-                tags: Mutex::new(HashMap::new()),
-                note_tags: Mutex::new(HashMap::new()),
-            }
+    /// Forget the user's searches, returning how many were removed
+    pub async fn clear_search_history(&self, user_id: Uuid) -> DomainResult<u64> {
+        match self.search_history {
+            Some(ref history) => history.delete_by_user(user_id).await,
+            None => Ok(0),
         }
     }
 
-    #[async_trait::async_trait]
-    impl TagRepository for MockTagRepository {
-        async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Tag>> {
-            Ok(self.tags.lock().unwrap().get(&id).cloned())
+    /// Take the date filters out of a search query, reading them in the
+    /// user's timezone
+    async fn parse_search(&self, user_id: Uuid, query: &str) -> DomainResult<ParsedSearch> {
+        if !query.contains(':') {
+            return ParsedSearch::parse(query, Utc::now(), Tz::UTC);
         }
+        ParsedSearch::parse(query, Utc::now(), self.time_zone(user_id).await)
+    }
 
-        async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
-            Ok(self
-                .tags
-                .lock()
-                .unwrap()
-                .values()
-                .filter(|t| t.user_id == user_id)
-                .cloned()
-                .collect())
+    /// Remember a query for history and suggestions unless the user opted
+    /// out; failures only get logged
+    async fn record_search(&self, user_id: Uuid, query: &str) {
+        let Some(ref history) = self.search_history else {
+            return;
+        };
+        if !self.user_settings(user_id).await.search_history_enabled {
+            return;
         }
 
-        async fn find_by_name(&self, user_id: Uuid, name: &str) -> DomainResult<Option<Tag>> {
-            Ok(self
-                .tags
-                .lock()
-                .unwrap()
-                .values()
-                .find(|t| t.user_id == user_id && t.name.as_ref() == name)
-                .cloned())
+        if let Err(e) = history.record(user_id, query.trim(), Utc::now()).await {
+            tracing::warn!(%user_id, "Failed to record search query: {}", e);
         }
+    }
 
-        async fn save(&self, tag: &Tag) -> DomainResult<()> {
-            self.tags.lock().unwrap().insert(tag.id, tag.clone());
-            Ok(())
+    /// Get or create a tag by name, resolving aliases to their canonical tag
+    ///
+    /// Handles race conditions gracefully: if a concurrent request creates
+    /// the same tag, we catch the unique constraint violation and retry the lookup.
+    async fn get_or_create_tag(&self, user_id: Uuid, name: TagName) -> DomainResult<Tag> {
+        if let Some(ref tag_aliases) = self.tag_aliases
+            && let Some(alias) = tag_aliases.find_by_alias(user_id, name.as_ref()).await?
+            && let Some(tag) = self.tag_repo.find_by_id(alias.tag_id).await?
+        {
+            return Ok(tag);
         }
 
-        async fn delete(&self, id: Uuid) -> DomainResult<()> {
-            self.tags.lock().unwrap().remove(&id);
-            Ok(())
+        // First, try to find existing tag
+        if let Some(tag) = self.tag_repo.find_by_name(user_id, name.as_ref()).await? {
+            return Ok(tag);
         }
 
-        async fn add_to_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
-            self.note_tags.lock().unwrap().insert((tag_id, note_id), ());
-            Ok(())
+        // Tag doesn't exist, try to create it
+        let tag = Tag::new(name.clone(), user_id);
+        match self.tag_repo.save(&tag).await {
+            Ok(()) => Ok(tag),
+            Err(DomainError::RepositoryError(RepositoryError::Conflict(_))) => {
+                // Race condition: another request created the tag between our check and save
+                // Retry the lookup
+                tracing::debug!(tag_name = %name, "Tag creation race condition detected, retrying lookup");
+                self.tag_repo
+                    .find_by_name(user_id, name.as_ref())
+                    .await?
+                    .ok_or_else(|| DomainError::validation("Tag creation race condition"))
+            }
+            Err(e) => Err(e),
         }
+    }
+}
 
-        async fn remove_from_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
-            self.note_tags.lock().unwrap().remove(&(tag_id, note_id));
-            Ok(())
-        }
+/// Service for Tag operations
+pub struct TagService {
+    tag_repo: Arc<dyn TagRepository>,
+    message_broker: Option<Arc<dyn MessageBroker>>,
+    events: Option<Arc<EventDispatcher>>,
+    policy: Arc<dyn AuthorizationPolicy>,
+}
 
-        async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<Tag>> {
-            let note_tags = self.note_tags.lock().unwrap();
-            let tags = self.tags.lock().unwrap();
-            Ok(note_tags
-                .keys()
-                .filter(|(_, nid)| *nid == note_id)
-                .filter_map(|(tid, _)| tags.get(tid).cloned())
-                .collect())
+impl TagService {
+    pub fn new(tag_repo: Arc<dyn TagRepository>) -> Self {
+        Self {
+            tag_repo,
+            message_broker: None,
+            events: None,
+            policy: Arc::new(OwnerPolicy),
         }
     }
 
-    struct MockUserRepository {
-        users: Mutex<HashMap<Uuid, User>>,
+    /// Decide who may update and delete tags; owners only by default
+    pub fn with_authorization_policy(mut self, policy: Arc<dyn AuthorizationPolicy>) -> Self {
+        self.policy = policy;
+        self
     }
 
-    impl MockUserRepository {
-        fn new() -> Self {
-            Self {
-                users: Mutex::new(HashMap::new()),
-            }
-        }
+    /// Builder method to set the message broker
+    pub fn with_message_broker(mut self, broker: Arc<dyn MessageBroker>) -> Self {
+        self.message_broker = Some(broker);
+        self
     }
 
-    #[async_trait::async_trait]
-    impl UserRepository for MockUserRepository {
-        async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<User>> {
-            Ok(self.users.lock().unwrap().get(&id).cloned())
-        }
+    /// Builder method to set the event dispatcher that user actions are
+    /// recorded with
+    pub fn with_event_dispatcher(mut self, events: Arc<EventDispatcher>) -> Self {
+        self.events = Some(events);
+        self
+    }
 
-        async fn find_by_subject(&self, subject: &str) -> DomainResult<Option<User>> {
-            Ok(self
-                .users
-                .lock()
-                .unwrap()
-                .values()
-                .find(|u| u.subject == subject)
-                .cloned())
+    async fn log_event(&self, user_id: Uuid, kind: LoggedEventKind) {
+        if let Some(ref events) = self.events {
+            events.dispatch(user_id, vec![kind]).await;
         }
+    }
 
-        async fn find_by_email(&self, email: &str) -> DomainResult<Option<User>> {
-            Ok(self
-                .users
-                .lock()
-                .unwrap()
-                .values()
-                .find(|u| u.email_str() == email)
-                .cloned())
+    async fn publish_tag_event(&self, tag: &Tag) {
+        if let Some(ref broker) = self.message_broker {
+            if let Err(e) = broker.publish_tag_updated(tag).await {
+                tracing::error!(tag_id = %tag.id, "Failed to publish tag event: {}", e);
+            }
         }
+    }
 
-        async fn save(&self, user: &User) -> DomainResult<()> {
-            self.users.lock().unwrap().insert(user.id, user.clone());
-            Ok(())
+    /// Create a new tag (TagName is pre-validated)
+    pub async fn create_tag(&self, user_id: Uuid, name: TagName) -> DomainResult<Tag> {
+        // Check if tag already exists
+        if self
+            .tag_repo
+            .find_by_name(user_id, name.as_ref())
+            .await?
+            .is_some()
+        {
+            return Err(DomainError::TagAlreadyExists(name.into_inner()));
         }
 
-        async fn delete(&self, id: Uuid) -> DomainResult<()> {
-            self.users.lock().unwrap().remove(&id);
-            Ok(())
+        let tag = Tag::new(name, user_id);
+        self.tag_repo.save(&tag).await?;
+        self.publish_tag_event(&tag).await;
+        self.log_event(user_id, LoggedEventKind::tag_created(&tag))
+            .await;
+        Ok(tag)
+    }
+
+    /// List all tags for a user
+    pub async fn list_tags(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        self.tag_repo.find_by_user(user_id).await
+    }
+
+    /// The user's tag named `name`, if there is one
+    pub async fn find_by_name(&self, user_id: Uuid, name: &str) -> DomainResult<Option<Tag>> {
+        self.tag_repo.find_by_name(user_id, name).await
+    }
+
+    /// Delete a tag
+    pub async fn delete_tag(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
+        let tag = self
+            .tag_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::TagNotFound(id))?;
+
+        self.policy
+            .authorize(user_id, Action::Delete, &Resource::tag(&tag))
+            .await?;
+
+        self.tag_repo.delete(id).await?;
+        self.log_event(
+            user_id,
+            LoggedEventKind::TagDeleted {
+                tag_id: id,
+                name: tag.name.into_inner(),
+            },
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Pin a tag to the top of the sidebar, or unpin it
+    pub async fn set_tag_pinned(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        is_pinned: bool,
+    ) -> DomainResult<Tag> {
+        let mut tag = self
+            .tag_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::TagNotFound(id))?;
+
+        self.policy
+            .authorize(user_id, Action::Update, &Resource::tag(&tag))
+            .await?;
+
+        if tag.is_pinned != is_pinned {
+            tag.is_pinned = is_pinned;
+            self.tag_repo.save(&tag).await?;
+            self.publish_tag_event(&tag).await;
         }
+        Ok(tag)
     }
 
-    mod note_service_tests {
-        use super::*;
+    /// Rearrange the sidebar.
+    ///
+    /// `tag_ids` must list every tag of the user exactly once, so clients
+    /// with a stale view cannot silently drop tags from the order. Pinned
+    /// tags stay above the others. Returns the tags in sidebar order.
+    pub async fn reorder_tags(&self, user_id: Uuid, tag_ids: Vec<Uuid>) -> DomainResult<Vec<Tag>> {
+        let tags = self.tag_repo.find_by_user(user_id).await?;
+
+        let mut requested = tag_ids.clone();
+        requested.sort();
+        requested.dedup();
+        let mut expected: Vec<Uuid> = tags.iter().map(|t| t.id).collect();
+        expected.sort();
+
+        if requested.len() != tag_ids.len() || requested != expected {
+            return Err(DomainError::validation(
+                "Reorder must list each tag exactly once",
+            ));
+        }
+
+        self.tag_repo.reorder(user_id, &tag_ids).await?;
+
+        let mut tags = tags;
+        for tag in tags.iter_mut() {
+            tag.sort_order = tag_ids
+                .iter()
+                .position(|id| *id == tag.id)
+                .map(|position| position as i32);
+        }
+        Tag::sort_for_sidebar(&mut tags);
+        Ok(tags)
+    }
+
+    /// The user's tags that no note uses
+    pub async fn list_unused_tags(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        self.tag_repo.find_unused(user_id).await
+    }
+
+    /// Apply `policy` to the user's unused tags, returning the tags it
+    /// flagged or deleted
+    pub async fn clean_up_unused_tags(
+        &self,
+        user_id: Uuid,
+        policy: UnusedTagPolicy,
+    ) -> DomainResult<Vec<Tag>> {
+        let deleted = match policy {
+            UnusedTagPolicy::Flag => return self.tag_repo.find_unused(user_id).await,
+            UnusedTagPolicy::Delete => self.tag_repo.delete_unused(user_id).await?,
+        };
+
+        if let Some(ref events) = self.events
+            && !deleted.is_empty()
+        {
+            let kinds = deleted
+                .iter()
+                .map(|tag| LoggedEventKind::TagDeleted {
+                    tag_id: tag.id,
+                    name: tag.name.as_ref().to_string(),
+                })
+                .collect();
+            events.dispatch(user_id, kinds).await;
+        }
+        Ok(deleted)
+    }
+
+    /// Rename a tag (new_name is pre-validated TagName)
+    pub async fn rename_tag(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        new_name: TagName,
+    ) -> DomainResult<Tag> {
+        // Find the existing tag
+        let mut tag = self
+            .tag_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::TagNotFound(id))?;
+
+        self.policy
+            .authorize(user_id, Action::Update, &Resource::tag(&tag))
+            .await?;
+
+        // Check if new name already exists (and it's not the same tag)
+        if let Some(existing) = self
+            .tag_repo
+            .find_by_name(user_id, new_name.as_ref())
+            .await?
+        {
+            if existing.id != id {
+                return Err(DomainError::TagAlreadyExists(new_name.into_inner()));
+            }
+        }
+
+        // Update the name
+        let previous_name = std::mem::replace(&mut tag.name, new_name);
+        self.tag_repo.save(&tag).await?;
+        self.publish_tag_event(&tag).await;
+        if previous_name != tag.name {
+            self.log_event(
+                user_id,
+                LoggedEventKind::TagRenamed {
+                    tag_id: id,
+                    name: tag.name.as_ref().to_string(),
+                    previous_name: previous_name.into_inner(),
+                },
+            )
+            .await;
+        }
+        Ok(tag)
+    }
+}
+
+/// Service for User operations (OIDC-ready)
+pub struct UserService {
+    user_repo: Arc<dyn UserRepository>,
+    password_hasher: Arc<dyn PasswordHasher>,
+    message_broker: Option<Arc<dyn MessageBroker>>,
+    onboarding: Option<Arc<OnboardingService>>,
+}
+
+impl UserService {
+    pub fn new(
+        user_repo: Arc<dyn UserRepository>,
+        password_hasher: Arc<dyn PasswordHasher>,
+    ) -> Self {
+        Self {
+            user_repo,
+            password_hasher,
+            message_broker: None,
+            onboarding: None,
+        }
+    }
+
+    /// Builder method to set the message broker
+    pub fn with_message_broker(mut self, broker: Arc<dyn MessageBroker>) -> Self {
+        self.message_broker = Some(broker);
+        self
+    }
+
+    /// Builder method to give new accounts welcome content
+    pub fn with_onboarding(mut self, onboarding: Arc<OnboardingService>) -> Self {
+        self.onboarding = Some(onboarding);
+        self
+    }
+
+    /// Seed a new account; failures are logged since the account itself works
+    async fn welcome(&self, user: &User) {
+        if let Some(ref onboarding) = self.onboarding
+            && let Err(e) = onboarding.seed(user).await
+        {
+            tracing::warn!(user_id = %user.id, "Failed to create welcome content: {}", e);
+        }
+    }
+
+    pub async fn find_or_create(&self, subject: &str, email: &str) -> DomainResult<User> {
+        // 1. Try to find by subject (OIDC id)
+        if let Some(user) = self.user_repo.find_by_subject(subject).await? {
+            return Ok(user);
+        }
+
+        // 2. Try to find by email
+        if let Some(mut user) = self.user_repo.find_by_email(email).await? {
+            // Link subject if missing (account linking logic)
+            if user.subject != subject {
+                user.subject = subject.to_string();
+                self.user_repo.save(&user).await?;
+            }
+            return Ok(user);
+        }
+
+        // 3. Create new user
+        let email = Email::try_from(email)?;
+        let user = User::new(subject, email);
+        self.user_repo.save(&user).await?;
+        self.welcome(&user).await;
+
+        Ok(user)
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> DomainResult<User> {
+        self.user_repo
+            .find_by_id(id)
+            .await?
+            .ok_or(DomainError::UserNotFound(id))
+    }
+
+    pub async fn find_by_email(&self, email: &str) -> DomainResult<Option<User>> {
+        self.user_repo.find_by_email(email).await
+    }
+
+    /// Delete a user; their notes and tags are removed with them, and the
+    /// worker drops their vectors once it sees the event
+    pub async fn delete_user(&self, user_id: Uuid) -> DomainResult<()> {
+        self.find_by_id(user_id).await?;
+        self.user_repo.delete(user_id).await?;
+
+        if let Some(ref broker) = self.message_broker {
+            if let Err(e) = broker.publish_user_deleted(user_id).await {
+                tracing::error!(user_id = %user_id, "Failed to publish user deletion: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Register a local (password) user
+    pub async fn create_local(&self, email: &str, password: &Password) -> DomainResult<User> {
+        let email = Email::try_from(email)?;
+        let password_hash = self.password_hasher.hash(password.as_ref()).await?;
+        let user = User::new_local(email, password_hash);
+        self.user_repo.save(&user).await?;
+        self.welcome(&user).await;
+        Ok(user)
+    }
+
+    /// Verify email/password credentials of a local user.
+    ///
+    /// On success, hashes using outdated parameters (or imported bcrypt
+    /// hashes) are transparently upgraded.
+    pub async fn authenticate(&self, email: &str, password: &str) -> DomainResult<Option<User>> {
+        let Some(mut user) = self.user_repo.find_by_email(email).await? else {
+            return Ok(None);
+        };
+        let Some(hash) = user.password_hash.clone() else {
+            return Ok(None);
+        };
+
+        if !self.password_hasher.verify(password, &hash).await? {
+            return Ok(None);
+        }
+
+        if self.password_hasher.needs_rehash(&hash) {
+            match self.password_hasher.hash(password).await {
+                Ok(new_hash) => {
+                    user.password_hash = Some(new_hash);
+                    if let Err(e) = self.user_repo.save(&user).await {
+                        // Login should still succeed with the old hash
+                        tracing::warn!(user_id = %user.id, "Failed to store upgraded password hash: {}", e);
+                        user.password_hash = Some(hash);
+                    } else {
+                        tracing::info!(user_id = %user.id, "Upgraded password hash");
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(user_id = %user.id, "Failed to rehash password: {}", e)
+                }
+            }
+        }
+
+        Ok(Some(user))
+    }
+
+    /// Change a local user's password after checking the current one
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        current_password: &str,
+        new_password: &Password,
+    ) -> DomainResult<User> {
+        let mut user = self.find_by_id(user_id).await?;
+
+        let current_hash = user.password_hash.as_deref().ok_or_else(|| {
+            DomainError::validation(
+                "Account has no password; it signs in through an identity provider",
+            )
+        })?;
+
+        if !self
+            .password_hasher
+            .verify(current_password, current_hash)
+            .await?
+        {
+            return Err(DomainError::validation("Current password is incorrect"));
+        }
+
+        user.password_hash = Some(self.password_hasher.hash(new_password.as_ref()).await?);
+        self.user_repo.save(&user).await?;
+        Ok(user)
+    }
+
+    /// Update display name and avatar
+    pub async fn update_profile(
+        &self,
+        user_id: Uuid,
+        req: UpdateProfileRequest,
+    ) -> DomainResult<User> {
+        let mut user = self.find_by_id(user_id).await?;
+
+        if let Some(display_name) = req.display_name {
+            user.display_name = display_name;
+        }
+
+        if let Some(avatar_url) = req.avatar_url {
+            if let Some(ref url) = avatar_url {
+                let parsed = url::Url::parse(url)
+                    .map_err(|e| DomainError::validation(format!("Invalid avatar URL: {}", e)))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(DomainError::validation("Avatar URL must use http or https"));
+                }
+            }
+            user.avatar_url = avatar_url;
+        }
+
+        self.user_repo.save(&user).await?;
+        Ok(user)
+    }
+
+    /// Start changing a user's email; the change is applied by
+    /// [`UserService::confirm_email_change`] once the token is confirmed.
+    pub async fn request_email_change(
+        &self,
+        user_id: Uuid,
+        new_email: Email,
+    ) -> DomainResult<EmailChange> {
+        let user = self.find_by_id(user_id).await?;
+
+        if user.email == new_email {
+            return Err(DomainError::validation("New email matches the current one"));
+        }
+
+        if self
+            .user_repo
+            .find_by_email(new_email.as_ref())
+            .await?
+            .is_some()
+        {
+            return Err(DomainError::UserAlreadyExists(new_email.into_inner()));
+        }
+
+        let change = EmailChange::new(user_id, new_email);
+        self.user_repo.save_email_change(&change).await?;
+        Ok(change)
+    }
+
+    /// Apply a pending email change after the user confirmed the token
+    pub async fn confirm_email_change(&self, user_id: Uuid, token: &str) -> DomainResult<User> {
+        let change = self
+            .user_repo
+            .find_email_change(token)
+            .await?
+            .filter(|c| c.user_id == user_id)
+            .ok_or_else(|| DomainError::validation("Invalid email confirmation token"))?;
+
+        if change.is_expired() {
+            self.user_repo.delete_email_change(user_id).await?;
+            return Err(DomainError::validation(
+                "Email confirmation token has expired",
+            ));
+        }
+
+        // The address may have been claimed since the change was requested
+        if self
+            .user_repo
+            .find_by_email(change.new_email.as_ref())
+            .await?
+            .is_some()
+        {
+            return Err(DomainError::UserAlreadyExists(
+                change.new_email.into_inner(),
+            ));
+        }
+
+        let mut user = self.find_by_id(user_id).await?;
+        user.set_email(change.new_email);
+        self.user_repo.save(&user).await?;
+        self.user_repo.delete_email_change(user_id).await?;
+
+        Ok(user)
+    }
+
+    /// Get a user's settings
+    pub async fn get_settings(&self, user_id: Uuid) -> DomainResult<UserSettings> {
+        self.user_repo.find_settings(user_id).await
+    }
+
+    /// Apply a partial update to a user's settings and return the result
+    pub async fn update_settings(
+        &self,
+        user_id: Uuid,
+        req: UpdateSettingsRequest,
+    ) -> DomainResult<UserSettings> {
+        let mut settings = self.user_repo.find_settings(user_id).await?;
+
+        if let Some(color) = req.default_note_color {
+            if color.trim().is_empty() {
+                return Err(DomainError::validation(
+                    "Default note color cannot be empty",
+                ));
+            }
+            settings.default_note_color = color;
+        }
+
+        if let Some(sort) = req.default_sort {
+            settings.default_sort = sort;
+        }
+
+        if let Some(locale) = req.locale {
+            settings.locale = locale;
+        }
+
+        if let Some(timezone) = req.timezone {
+            validate_timezone(&timezone)?;
+            settings.timezone = timezone;
+        }
+
+        if let Some(editor) = req.editor {
+            settings.editor = editor;
+        }
+
+        if let Some(enabled) = req.smart_features_enabled {
+            settings.smart_features_enabled = enabled;
+        }
+
+        if let Some(enabled) = req.search_history_enabled {
+            settings.search_history_enabled = enabled;
+        }
+
+        if let Some(enabled) = req.auto_title_enabled {
+            settings.auto_title_enabled = enabled;
+        }
+
+        if let Some(days) = req.auto_archive_after_days {
+            if matches!(days, Some(d) if d == 0 || d > MAX_AUTO_ARCHIVE_DAYS) {
+                return Err(DomainError::validation(format!(
+                    "Auto-archive period must be between 1 and {} days",
+                    MAX_AUTO_ARCHIVE_DAYS
+                )));
+            }
+            settings.auto_archive_after_days = days;
+        }
+
+        self.user_repo.save_settings(user_id, &settings).await?;
+        Ok(settings)
+    }
+}
+
+/// Service for Smart Features (Embeddings, Vector Search, Linking)
+pub struct SmartNoteService {
+    embedding_generator: Arc<dyn crate::ports::EmbeddingGenerator>,
+    vector_store: Arc<dyn crate::ports::VectorStore>,
+    link_repo: Arc<dyn crate::ports::LinkRepository>,
+}
+
+impl SmartNoteService {
+    pub fn new(
+        embedding_generator: Arc<dyn crate::ports::EmbeddingGenerator>,
+        vector_store: Arc<dyn crate::ports::VectorStore>,
+        link_repo: Arc<dyn crate::ports::LinkRepository>,
+    ) -> Self {
+        Self {
+            embedding_generator,
+            vector_store,
+            link_repo,
+        }
+    }
+
+    /// Process a note to generate embeddings and find similar notes
+    pub async fn process_note(&self, note: &Note) -> DomainResult<()> {
+        self.process_notes(std::slice::from_ref(note)).await
+    }
+
+    /// Process several notes, embedding them in one batch.
+    ///
+    /// Notes whose content was already embedded by the current model are
+    /// skipped.
+    pub async fn process_notes(&self, notes: &[Note]) -> DomainResult<()> {
+        if notes.is_empty() {
+            return Ok(());
+        }
+
+        // Metadata-only edits (pinning, tags, colour) leave the embedded
+        // content unchanged, so there is nothing to recompute
+        let ids: Vec<Uuid> = notes.iter().map(|n| n.id).collect();
+        let stored = self.vector_store.get_payloads(&ids).await?;
+        let model = self.embedding_generator.model_name();
+        let notes: Vec<&Note> = notes
+            .iter()
+            .filter(|n| {
+                !stored
+                    .get(&n.id)
+                    .is_some_and(|p| p.embeds(&n.content, model))
+            })
+            .collect();
+        self.embed_and_link(&notes).await
+    }
+
+    /// Embed a note and find its similar notes again, even if its content
+    /// was embedded before
+    pub async fn refresh_note(&self, note: &Note) -> DomainResult<()> {
+        self.embed_and_link(&[note]).await
+    }
+
+    /// Embed notes in one batch and link each to its similar notes
+    async fn embed_and_link(&self, notes: &[&Note]) -> DomainResult<()> {
+        if notes.is_empty() {
+            return Ok(());
+        }
+
+        // 1. Generate embeddings
+        let texts: Vec<String> = notes.iter().map(|n| n.content.clone()).collect();
+        let languages: Vec<Option<Language>> = notes.iter().map(|n| n.language.clone()).collect();
+        let embeddings = self
+            .embedding_generator
+            .generate_embeddings_in(&texts, &languages)
+            .await?;
+        if embeddings.len() != notes.len() {
+            return Err(DomainError::InfrastructureError(format!(
+                "Expected {} embeddings, got {}",
+                notes.len(),
+                embeddings.len()
+            )));
+        }
+
+        for (note, embedding) in notes.iter().zip(&embeddings) {
+            self.link_note(note, embedding).await?;
+        }
+        Ok(())
+    }
+
+    /// Store a note's embedding and replace its links to similar notes
+    async fn link_note(&self, note: &Note, embedding: &[f32]) -> DomainResult<()> {
+        // 2. Upsert to vector store
+        let payload =
+            crate::ports::VectorPayload::for_note(note, self.embedding_generator.model_name());
+        self.vector_store
+            .upsert(note.id, embedding, &payload)
+            .await?;
+
+        // 3. Find similar notes among the owner's other notes
+        // TODO: Make limit configurable
+        let similar = self
+            .vector_store
+            .find_similar(embedding, 5, &crate::ports::VectorFilter::related_to(note))
+            .await?;
+
+        // 4. Create links
+        let links: Vec<crate::entities::NoteLink> = similar
+            .into_iter()
+            .filter(|(id, _)| *id != note.id) // Exclude self
+            .map(|(target_id, score)| crate::entities::NoteLink::new(note.id, target_id, score))
+            .collect();
+
+        // 5. Save links (replacing old ones)
+        if !links.is_empty() {
+            self.link_repo.delete_links_for_source(note.id).await?;
+            self.link_repo.save_links(&links).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Notes of `user_id` similar to unsaved text, as (note ID, score),
+    /// best first; `exclude_note_id` leaves out the note being edited
+    pub async fn find_similar_to_text(
+        &self,
+        user_id: Uuid,
+        text: &str,
+        exclude_note_id: Option<Uuid>,
+        limit: usize,
+    ) -> DomainResult<Vec<(Uuid, f32)>> {
+        if text.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let embedding = self.embedding_generator.generate_embedding(text).await?;
+        let filter = crate::ports::VectorFilter {
+            exclude_note_id,
+            ..crate::ports::VectorFilter::owned_by(user_id)
+        };
+        self.vector_store
+            .find_similar(&embedding, limit, &filter)
+            .await
+    }
+
+    /// Drop the embedding of a permanently deleted note
+    ///
+    /// Its links are removed together with the note itself.
+    pub async fn forget_note(&self, note_id: Uuid) -> DomainResult<()> {
+        self.vector_store.delete(note_id).await
+    }
+
+    /// Drop every embedding of a deleted user; their links go with their notes
+    pub async fn forget_user(&self, user_id: Uuid) -> DomainResult<()> {
+        self.vector_store.delete_by_user(user_id).await
+    }
+
+    /// Get related notes for a given note ID
+    pub async fn get_related_notes(
+        &self,
+        note_id: Uuid,
+        limit: usize,
+        min_score: f32,
+    ) -> DomainResult<Vec<crate::entities::RelatedNote>> {
+        self.link_repo
+            .get_links_for_note(note_id, limit, min_score)
+            .await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::event_log::LoggedEvent;
+    use crate::events::DomainEventKind;
+    use crate::instance::InstanceSettingsUpdate;
+    use crate::ports::EventHandler;
+    use crate::repositories::tests::MockNoteRepository;
+    use crate::repositories::{EventLogRepository, InstanceSettingsRepository};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    // Mock implementations for testing
+    pub(crate) struct MockTagRepository {
+        tags: Mutex<HashMap<Uuid, Tag>>,
+        note_tags: Mutex<HashMap<(Uuid, Uuid), ()>>,
+    }
+
+    impl MockTagRepository {
+        pub(crate) fn new() -> Self {
+            Self {
+                tags: Mutex::new(HashMap::new()),
+                note_tags: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TagRepository for MockTagRepository {
+        async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Tag>> {
+            Ok(self.tags.lock().unwrap().get(&id).cloned())
+        }
+
+        async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+            let mut tags: Vec<Tag> = self
+                .tags
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|t| t.user_id == user_id)
+                .cloned()
+                .collect();
+            Tag::sort_for_sidebar(&mut tags);
+            Ok(tags)
+        }
+
+        async fn find_by_prefix(
+            &self,
+            user_id: Uuid,
+            prefix: &str,
+            limit: usize,
+        ) -> DomainResult<Vec<Tag>> {
+            let mut tags: Vec<Tag> = self
+                .tags
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|t| t.user_id == user_id && t.name_str().starts_with(prefix))
+                .cloned()
+                .collect();
+            tags.sort_by(|a, b| a.name_str().cmp(b.name_str()));
+            tags.truncate(limit);
+            Ok(tags)
+        }
+
+        async fn find_by_name(&self, user_id: Uuid, name: &str) -> DomainResult<Option<Tag>> {
+            Ok(self
+                .tags
+                .lock()
+                .unwrap()
+                .values()
+                .find(|t| t.user_id == user_id && t.name.as_ref() == name)
+                .cloned())
+        }
+
+        async fn save(&self, tag: &Tag) -> DomainResult<()> {
+            self.tags.lock().unwrap().insert(tag.id, tag.clone());
+            Ok(())
+        }
+
+        async fn delete(&self, id: Uuid) -> DomainResult<()> {
+            self.tags.lock().unwrap().remove(&id);
+            Ok(())
+        }
+
+        async fn add_to_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
+            self.note_tags.lock().unwrap().insert((tag_id, note_id), ());
+            Ok(())
+        }
+
+        async fn add_many_to_note(&self, tag_ids: &[Uuid], note_id: Uuid) -> DomainResult<()> {
+            let mut note_tags = self.note_tags.lock().unwrap();
+            for tag_id in tag_ids {
+                note_tags.insert((*tag_id, note_id), ());
+            }
+            Ok(())
+        }
+
+        async fn remove_from_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
+            self.note_tags.lock().unwrap().remove(&(tag_id, note_id));
+            Ok(())
+        }
+
+        async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<Tag>> {
+            let note_tags = self.note_tags.lock().unwrap();
+            let tags = self.tags.lock().unwrap();
+            Ok(note_tags
+                .keys()
+                .filter(|(_, nid)| *nid == note_id)
+                .filter_map(|(tid, _)| tags.get(tid).cloned())
+                .collect())
+        }
+
+        async fn merge(&self, source_id: Uuid, target_id: Uuid) -> DomainResult<()> {
+            let mut note_tags = self.note_tags.lock().unwrap();
+            let notes: Vec<Uuid> = note_tags
+                .keys()
+                .filter(|(tid, _)| *tid == source_id)
+                .map(|(_, nid)| *nid)
+                .collect();
+            for note_id in notes {
+                note_tags.remove(&(source_id, note_id));
+                note_tags.insert((target_id, note_id), ());
+            }
+            self.tags.lock().unwrap().remove(&source_id);
+            Ok(())
+        }
+
+        async fn find_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+            let note_tags = self.note_tags.lock().unwrap();
+            let mut tags: Vec<Tag> = self
+                .tags
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|t| t.user_id == user_id)
+                .filter(|t| !note_tags.keys().any(|(tid, _)| *tid == t.id))
+                .cloned()
+                .collect();
+            tags.sort_by(|a, b| a.name_str().cmp(b.name_str()));
+            Ok(tags)
+        }
+
+        async fn delete_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+            let unused = self.find_unused(user_id).await?;
+            let mut tags = self.tags.lock().unwrap();
+            for tag in &unused {
+                tags.remove(&tag.id);
+            }
+            Ok(unused)
+        }
+
+        async fn reorder(&self, user_id: Uuid, tag_ids: &[Uuid]) -> DomainResult<()> {
+            let mut tags = self.tags.lock().unwrap();
+            for (position, id) in tag_ids.iter().enumerate() {
+                if let Some(tag) = tags.get_mut(id).filter(|t| t.user_id == user_id) {
+                    tag.sort_order = Some(position as i32);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    pub(crate) struct MockSearchHistoryRepository {
+        entries: Mutex<Vec<(Uuid, SearchHistoryEntry)>>,
+    }
+
+    impl MockSearchHistoryRepository {
+        /// The user's entries starting with `prefix`, most recent first
+        pub(crate) fn matching(&self, user_id: Uuid, prefix: &str) -> Vec<SearchHistoryEntry> {
+            let prefix = prefix.to_ascii_lowercase();
+            let mut entries: Vec<SearchHistoryEntry> = self
+                .entries
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(uid, e)| {
+                    *uid == user_id && e.query.to_ascii_lowercase().starts_with(&prefix)
+                })
+                .map(|(_, e)| e.clone())
+                .collect();
+            entries.sort_by(|a, b| b.last_searched_at.cmp(&a.last_searched_at));
+            entries
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SearchHistoryRepository for MockSearchHistoryRepository {
+        async fn record(&self, user_id: Uuid, query: &str, at: DateTime<Utc>) -> DomainResult<()> {
+            let mut entries = self.entries.lock().unwrap();
+            match entries
+                .iter_mut()
+                .find(|(uid, e)| *uid == user_id && e.query == query)
+            {
+                Some((_, entry)) => {
+                    entry.last_searched_at = at;
+                    entry.search_count += 1;
+                }
+                None => entries.push((
+                    user_id,
+                    SearchHistoryEntry {
+                        query: query.to_string(),
+                        last_searched_at: at,
+                        search_count: 1,
+                    },
+                )),
+            }
+            Ok(())
+        }
+
+        async fn find_by_user(
+            &self,
+            user_id: Uuid,
+            limit: usize,
+        ) -> DomainResult<Vec<SearchHistoryEntry>> {
+            Ok(self.matching(user_id, "").into_iter().take(limit).collect())
+        }
+
+        async fn find_recent(
+            &self,
+            user_id: Uuid,
+            prefix: &str,
+            limit: usize,
+        ) -> DomainResult<Vec<String>> {
+            Ok(self
+                .matching(user_id, prefix)
+                .into_iter()
+                .map(|e| e.query)
+                .take(limit)
+                .collect())
+        }
+
+        async fn find_frequent(
+            &self,
+            user_id: Uuid,
+            prefix: &str,
+            limit: usize,
+        ) -> DomainResult<Vec<String>> {
+            let mut entries: Vec<SearchHistoryEntry> = self
+                .matching(user_id, prefix)
+                .into_iter()
+                .filter(|e| e.search_count > 1)
+                .collect();
+            entries.sort_by(|a, b| b.search_count.cmp(&a.search_count));
+            Ok(entries.into_iter().map(|e| e.query).take(limit).collect())
+        }
+
+        async fn delete_by_user(&self, user_id: Uuid) -> DomainResult<u64> {
+            let mut entries = self.entries.lock().unwrap();
+            let before = entries.len();
+            entries.retain(|(uid, _)| *uid != user_id);
+            Ok((before - entries.len()) as u64)
+        }
+    }
+
+    pub(crate) struct MockUserRepository {
+        users: Mutex<HashMap<Uuid, User>>,
+        settings: Mutex<HashMap<Uuid, UserSettings>>,
+        email_changes: Mutex<HashMap<Uuid, EmailChange>>,
+    }
+
+    impl MockUserRepository {
+        pub(crate) fn new() -> Self {
+            Self {
+                users: Mutex::new(HashMap::new()),
+                settings: Mutex::new(HashMap::new()),
+                email_changes: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl UserRepository for MockUserRepository {
+        async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<User>> {
+            Ok(self.users.lock().unwrap().get(&id).cloned())
+        }
+
+        async fn find_by_subject(&self, subject: &str) -> DomainResult<Option<User>> {
+            Ok(self
+                .users
+                .lock()
+                .unwrap()
+                .values()
+                .find(|u| u.subject == subject)
+                .cloned())
+        }
+
+        async fn find_by_email(&self, email: &str) -> DomainResult<Option<User>> {
+            Ok(self
+                .users
+                .lock()
+                .unwrap()
+                .values()
+                .find(|u| u.email_str() == email)
+                .cloned())
+        }
+
+        async fn save(&self, user: &User) -> DomainResult<()> {
+            self.users.lock().unwrap().insert(user.id, user.clone());
+            Ok(())
+        }
+
+        async fn delete(&self, id: Uuid) -> DomainResult<()> {
+            self.users.lock().unwrap().remove(&id);
+            Ok(())
+        }
+
+        async fn find_settings(&self, user_id: Uuid) -> DomainResult<UserSettings> {
+            Ok(self
+                .settings
+                .lock()
+                .unwrap()
+                .get(&user_id)
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        async fn save_settings(&self, user_id: Uuid, settings: &UserSettings) -> DomainResult<()> {
+            self.settings
+                .lock()
+                .unwrap()
+                .insert(user_id, settings.clone());
+            Ok(())
+        }
+
+        async fn save_email_change(&self, change: &EmailChange) -> DomainResult<()> {
+            self.email_changes
+                .lock()
+                .unwrap()
+                .insert(change.user_id, change.clone());
+            Ok(())
+        }
+
+        async fn find_email_change(&self, token: &str) -> DomainResult<Option<EmailChange>> {
+            Ok(self
+                .email_changes
+                .lock()
+                .unwrap()
+                .values()
+                .find(|c| c.token == token)
+                .cloned())
+        }
+
+        async fn delete_email_change(&self, user_id: Uuid) -> DomainResult<()> {
+            self.email_changes.lock().unwrap().remove(&user_id);
+            Ok(())
+        }
+
+        async fn find_ids_with_auto_archive(&self) -> DomainResult<Vec<Uuid>> {
+            Ok(self
+                .settings
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, s)| s.auto_archive_after_days.is_some())
+                .map(|(id, _)| *id)
+                .collect())
+        }
+
+        async fn find_ids_with_auto_title(&self) -> DomainResult<Vec<Uuid>> {
+            Ok(self
+                .settings
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, s)| s.auto_title_enabled)
+                .map(|(id, _)| *id)
+                .collect())
+        }
+
+        async fn find_all_ids(&self) -> DomainResult<Vec<Uuid>> {
+            Ok(self.users.lock().unwrap().keys().copied().collect())
+        }
+    }
+
+    /// Records published events instead of sending them
+    #[derive(Default)]
+    pub(crate) struct MockMessageBroker {
+        pub(crate) events: Mutex<Vec<DomainEvent>>,
+    }
+
+    #[async_trait::async_trait]
+    impl MessageBroker for MockMessageBroker {
+        async fn publish(&self, event: &DomainEvent) -> DomainResult<()> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+
+        async fn subscribe(
+            &self,
+        ) -> DomainResult<std::pin::Pin<Box<dyn futures_core::Stream<Item = DomainEvent> + Send>>>
+        {
+            Err(DomainError::InfrastructureError(
+                "Mock broker cannot subscribe".into(),
+            ))
+        }
+    }
+
+    /// Collects dispatched events
+    #[derive(Default)]
+    pub(crate) struct RecordingEventHandler {
+        pub(crate) events: Mutex<Vec<LoggedEvent>>,
+    }
+
+    impl RecordingEventHandler {
+        pub(crate) fn kinds(&self) -> Vec<LoggedEventKind> {
+            let events = self.events.lock().unwrap();
+            events.iter().map(|e| e.kind.clone()).collect()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for RecordingEventHandler {
+        async fn handle(&self, event: &LoggedEvent) -> DomainResult<()> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    /// Keeps logged events in memory
+    #[derive(Default)]
+    pub(crate) struct MockEventLogRepository {
+        pub(crate) events: Mutex<Vec<LoggedEvent>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventLogRepository for MockEventLogRepository {
+        async fn append(&self, event: &LoggedEvent) -> DomainResult<()> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+
+        async fn find_by_user(
+            &self,
+            user_id: Uuid,
+            before: Option<DateTime<Utc>>,
+            limit: usize,
+        ) -> DomainResult<Vec<LoggedEvent>> {
+            let mut events: Vec<LoggedEvent> = self
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|e| e.user_id == user_id)
+                .filter(|e| before.is_none_or(|before| e.occurred_at < before))
+                .cloned()
+                .collect();
+            events.sort_by(|a, b| b.occurred_at.cmp(&a.occurred_at));
+            events.truncate(limit);
+            Ok(events)
+        }
+    }
+
+    /// Keeps stored instance settings in memory
+    #[derive(Default)]
+    pub(crate) struct MockInstanceSettingsRepository {
+        overrides: Mutex<InstanceSettingsUpdate>,
+    }
+
+    #[async_trait::async_trait]
+    impl InstanceSettingsRepository for MockInstanceSettingsRepository {
+        async fn is_read_only(&self) -> DomainResult<bool> {
+            Ok(false)
+        }
+
+        async fn set_read_only(&self, _read_only: bool) -> DomainResult<()> {
+            Ok(())
+        }
+
+        async fn record_trash_purge(&self, _report: &TrashPurgeReport) -> DomainResult<()> {
+            Ok(())
+        }
+
+        async fn storage_stats(&self) -> DomainResult<crate::trash::StorageStats> {
+            Ok(Default::default())
+        }
+
+        async fn database_metrics(
+            &self,
+            _since: DateTime<Utc>,
+        ) -> DomainResult<crate::overview::DatabaseMetrics> {
+            Ok(Default::default())
+        }
+
+        async fn settings_overrides(&self) -> DomainResult<InstanceSettingsUpdate> {
+            Ok(self.overrides.lock().unwrap().clone())
+        }
+
+        async fn update_settings(&self, update: &InstanceSettingsUpdate) -> DomainResult<()> {
+            let mut overrides = self.overrides.lock().unwrap();
+            overrides.allow_registration =
+                update.allow_registration.or(overrides.allow_registration);
+            overrides.max_pinned_notes = update.max_pinned_notes.or(overrides.max_pinned_notes);
+            overrides.smart_features_enabled = update
+                .smart_features_enabled
+                .or(overrides.smart_features_enabled);
+            if update.print_logo_url.is_some() {
+                overrides.print_logo_url = update.print_logo_url.clone();
+            }
+            if update.print_accent_color.is_some() {
+                overrides.print_accent_color = update.print_accent_color.clone();
+            }
+            if update.note_colors.is_some() {
+                overrides.note_colors = update.note_colors.clone();
+            }
+            overrides.show_archived = update.show_archived.or(overrides.show_archived);
+            overrides.max_note_length = update.max_note_length.or(overrides.max_note_length);
+            if update.attachment_types.is_some() {
+                overrides.attachment_types = update.attachment_types.clone();
+            }
+            overrides.markdown = update.markdown.or(overrides.markdown);
+            Ok(())
+        }
+    }
+
+    mod note_service_tests {
+        use super::*;
+        use crate::instance::{InstanceSettings, InstanceSettingsUpdate};
+
+        fn create_note_service() -> (NoteService, Uuid) {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let tag_repo = Arc::new(MockTagRepository::new());
+            let user_id = Uuid::new_v4();
+            (NoteService::new(note_repo, tag_repo), user_id)
+        }
+
+        #[tokio::test]
+        async fn test_create_note_success() {
+            let (service, user_id) = create_note_service();
+
+            let title = NoteTitle::try_from("My Note").ok();
+            let req = CreateNoteRequest {
+                user_id,
+                title,
+                content: "# Hello World".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+
+            let note = service.create_note(req).await.unwrap();
+
+            assert_eq!(note.title_str(), "My Note");
+            assert_eq!(note.content, "# Hello World");
+            assert_eq!(note.user_id, user_id);
+            assert_eq!(note.color, "DEFAULT");
+            assert!(!note.is_pinned);
+        }
+
+        #[tokio::test]
+        async fn test_create_note_uses_default_color_from_settings() {
+            let user_repo = Arc::new(MockUserRepository::new());
+            let user_id = Uuid::new_v4();
+            let settings = UserSettings {
+                default_note_color: "BLUE".to_string(),
+                ..UserSettings::default()
+            };
+            user_repo.save_settings(user_id, &settings).await.unwrap();
+
+            let service = NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            )
+            .with_user_repository(user_repo);
+
+            let req = CreateNoteRequest {
+                user_id,
+                title: None,
+                content: "content".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+
+            let note = service.create_note(req).await.unwrap();
+            assert_eq!(note.color, "BLUE");
+        }
+
+        /// Unit of work that rejects every commit
+        struct FailingUnitOfWork;
+
+        #[async_trait::async_trait]
+        impl UnitOfWork for FailingUnitOfWork {
+            async fn commit_note(
+                &self,
+                _note: &Note,
+                _version: Option<&NoteVersion>,
+            ) -> DomainResult<()> {
+                Err(DomainError::RepositoryError(RepositoryError::Connection(
+                    "database is locked".to_string(),
+                )))
+            }
+        }
+
+        #[tokio::test]
+        async fn test_note_writes_go_through_unit_of_work() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let user_id = Uuid::new_v4();
+            let service = NoteService::new(note_repo.clone(), Arc::new(MockTagRepository::new()))
+                .with_unit_of_work(Arc::new(FailingUnitOfWork));
+
+            let req = CreateNoteRequest {
+                user_id,
+                title: None,
+                content: "content".to_string(),
+                tags: vec![TagName::try_from("work").unwrap()],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+
+            assert!(service.create_note(req).await.is_err());
+            let notes = note_repo
+                .find_by_user(user_id, NoteFilter::new())
+                .await
+                .unwrap();
+            assert!(notes.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_run_auto_archive_archives_only_stale_unpinned_notes() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let user_repo = Arc::new(MockUserRepository::new());
+            let user_id = Uuid::new_v4();
+            let now = chrono::Utc::now();
+
+            let mut stale = Note::new(user_id, None, "stale");
+            stale.updated_at = now - chrono::Duration::days(60);
+            let mut pinned = stale.clone();
+            pinned.id = Uuid::new_v4();
+            pinned.is_pinned = true;
+            let fresh = Note::new(user_id, None, "fresh");
+            for note in [&stale, &pinned, &fresh] {
+                note_repo.save(note).await.unwrap();
+            }
+
+            let service = NoteService::new(note_repo.clone(), Arc::new(MockTagRepository::new()))
+                .with_user_repository(user_repo.clone());
+
+            // Not opted in yet
+            assert_eq!(service.run_auto_archive(user_id, now).await.unwrap(), 0);
+
+            let settings = UserSettings {
+                auto_archive_after_days: Some(30),
+                ..UserSettings::default()
+            };
+            user_repo.save_settings(user_id, &settings).await.unwrap();
+
+            assert_eq!(service.run_auto_archive(user_id, now).await.unwrap(), 1);
+            let stale = note_repo.find_by_id(stale.id).await.unwrap().unwrap();
+            assert!(stale.is_archived);
+            let pinned = note_repo.find_by_id(pinned.id).await.unwrap().unwrap();
+            assert!(!pinned.is_archived);
+        }
+
+        /// Answers every prompt with the same text, or fails without one
+        struct FixedTextGenerator(Option<String>);
+
+        #[async_trait::async_trait]
+        impl TextGenerator for FixedTextGenerator {
+            async fn generate(&self, _prompt: &str) -> DomainResult<String> {
+                self.0
+                    .clone()
+                    .ok_or_else(|| DomainError::InfrastructureError("offline".to_string()))
+            }
+        }
+
+        #[tokio::test]
+        async fn test_run_auto_title_titles_settled_untitled_notes() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let user_repo = Arc::new(MockUserRepository::new());
+            let user_id = Uuid::new_v4();
+            let now = chrono::Utc::now();
+
+            let mut untitled = Note::new(user_id, None, "# Trip plan\n- tickets");
+            untitled.updated_at = now - chrono::Duration::hours(1);
+            let mut titled = untitled.clone();
+            titled.id = Uuid::new_v4();
+            titled.title = NoteTitle::try_from("Mine").ok();
+            let being_written = Note::new(user_id, None, "still typing");
+            for note in [&untitled, &titled, &being_written] {
+                note_repo.save(note).await.unwrap();
+            }
+
+            let service = NoteService::new(note_repo.clone(), Arc::new(MockTagRepository::new()))
+                .with_user_repository(user_repo.clone());
+
+            // Not opted in yet
+            assert_eq!(service.run_auto_title(user_id, now).await.unwrap(), 0);
+
+            let settings = UserSettings {
+                auto_title_enabled: true,
+                ..UserSettings::default()
+            };
+            user_repo.save_settings(user_id, &settings).await.unwrap();
+
+            assert_eq!(service.run_auto_title(user_id, now).await.unwrap(), 1);
+            let untitled = note_repo.find_by_id(untitled.id).await.unwrap().unwrap();
+            assert_eq!(untitled.title_str(), "Trip plan");
+            assert_eq!(untitled.updated_at, now - chrono::Duration::hours(1));
+            let titled = note_repo.find_by_id(titled.id).await.unwrap().unwrap();
+            assert_eq!(titled.title_str(), "Mine");
+            let being_written = note_repo
+                .find_by_id(being_written.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(being_written.title.is_none());
+        }
+
+        #[tokio::test]
+        async fn test_run_auto_title_falls_back_when_generation_fails() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let user_repo = Arc::new(MockUserRepository::new());
+            let user_id = Uuid::new_v4();
+            let settings = UserSettings {
+                auto_title_enabled: true,
+                ..UserSettings::default()
+            };
+            user_repo.save_settings(user_id, &settings).await.unwrap();
+            let now = chrono::Utc::now() + chrono::Duration::hours(1);
+
+            let service = |answer| {
+                NoteService::new(note_repo.clone(), Arc::new(MockTagRepository::new()))
+                    .with_user_repository(user_repo.clone())
+                    .with_text_generator(Arc::new(FixedTextGenerator(answer)))
+            };
+
+            let note = Note::new(user_id, None, "Groceries for the weekend");
+            note_repo.save(&note).await.unwrap();
+            service(Some("Title: \"Weekend shopping\"".to_string()))
+                .run_auto_title(user_id, now)
+                .await
+                .unwrap();
+            let note = note_repo.find_by_id(note.id).await.unwrap().unwrap();
+            assert_eq!(note.title_str(), "Weekend shopping");
+
+            let note = Note::new(user_id, None, "Call the dentist");
+            note_repo.save(&note).await.unwrap();
+            service(None).run_auto_title(user_id, now).await.unwrap();
+            let note = note_repo.find_by_id(note.id).await.unwrap().unwrap();
+            assert_eq!(note.title_str(), "Call the dentist");
+        }
+
+        #[tokio::test]
+        async fn test_duplicate_note_copies_content_tags_and_color() {
+            let (service, user_id) = create_note_service();
+
+            let req = CreateNoteRequest {
+                user_id,
+                title: NoteTitle::try_from("Original").ok(),
+                content: "body".to_string(),
+                tags: vec![TagName::try_from("work").unwrap()],
+                color: Some("RED".to_string()),
+                is_pinned: true,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let original = service.create_note(req).await.unwrap();
+
+            let copy = service
+                .duplicate_note(original.id, user_id, true)
+                .await
+                .unwrap();
+
+            assert_ne!(copy.id, original.id);
+            assert_eq!(copy.title_str(), "Copy of Original");
+            assert_eq!(copy.content, "body");
+            assert_eq!(copy.color, "RED");
+            assert_eq!(copy.tags, original.tags);
+            assert!(!copy.is_pinned);
+
+            let plain = service
+                .duplicate_note(original.id, user_id, false)
+                .await
+                .unwrap();
+            assert_eq!(plain.title_str(), "Original");
+
+            let other_user = service
+                .duplicate_note(original.id, Uuid::new_v4(), true)
+                .await;
+            assert!(matches!(other_user, Err(DomainError::Forbidden(_))));
+        }
+
+        #[tokio::test]
+        async fn test_includes_are_expanded_for_rendering_only() {
+            let (service, user_id) = create_note_service();
+            let request = |title: &str, content: &str| CreateNoteRequest {
+                user_id,
+                title: NoteTitle::try_from(title).ok(),
+                content: content.to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            service
+                .create_note(request("Footer", "Sent from K-Notes"))
+                .await
+                .unwrap();
+            let note = service
+                .create_note(request("Mail", "Hello\n\n{{include:footer}}"))
+                .await
+                .unwrap();
+
+            let rendered = service
+                .expand_includes(user_id, vec![note.clone()])
+                .await
+                .unwrap();
+            assert_eq!(rendered[0].content, "Hello\n\nSent from K-Notes");
+
+            let stored = service.get_note(note.id, user_id).await.unwrap();
+            assert_eq!(stored.content, "Hello\n\n{{include:footer}}");
+
+            // Another user's notes are never included
+            let other = service
+                .expand_includes(Uuid::new_v4(), vec![note])
+                .await
+                .unwrap();
+            assert_eq!(other[0].content, "Hello\n\n{{include:footer}}");
+        }
+
+        fn pinned_note_request(user_id: Uuid) -> CreateNoteRequest {
+            CreateNoteRequest {
+                user_id,
+                title: None,
+                content: "pinned".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: true,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_pin_limit_is_enforced() {
+            let (service, user_id) = create_note_service();
+            let service = service.with_max_pinned_notes(2);
+
+            let first = service
+                .create_note(pinned_note_request(user_id))
+                .await
+                .unwrap();
+            let second = service
+                .create_note(pinned_note_request(user_id))
+                .await
+                .unwrap();
+            assert_eq!(first.pin_order, Some(0));
+            assert_eq!(second.pin_order, Some(1));
+
+            let result = service.create_note(pinned_note_request(user_id)).await;
+            assert!(matches!(
+                result,
+                Err(DomainError::PinLimitExceeded { max: 2 })
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_pin_limit_follows_instance_settings() {
+            let (service, user_id) = create_note_service();
+            let settings = Arc::new(
+                InstanceSettingsService::load(
+                    Arc::new(MockInstanceSettingsRepository::default()),
+                    InstanceSettings::default(),
+                )
+                .await
+                .unwrap(),
+            );
+            let service = service
+                .with_max_pinned_notes(5)
+                .with_instance_settings(settings.clone());
+
+            settings
+                .update(&InstanceSettingsUpdate {
+                    max_pinned_notes: Some(1),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+
+            service
+                .create_note(pinned_note_request(user_id))
+                .await
+                .unwrap();
+            let result = service.create_note(pinned_note_request(user_id)).await;
+            assert!(matches!(
+                result,
+                Err(DomainError::PinLimitExceeded { max: 1 })
+            ));
+        }
+
+        #[tokio::test]
+        async fn test_note_length_follows_instance_settings() {
+            let (service, user_id) = create_note_service();
+            let settings = Arc::new(
+                InstanceSettingsService::load(
+                    Arc::new(MockInstanceSettingsRepository::default()),
+                    InstanceSettings::default(),
+                )
+                .await
+                .unwrap(),
+            );
+            let service = service.with_instance_settings(settings.clone());
+            let note = service
+                .create_note(pinned_note_request(user_id))
+                .await
+                .unwrap();
+
+            settings
+                .update(&InstanceSettingsUpdate {
+                    max_note_length: Some(4),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+
+            let result = service.create_note(pinned_note_request(user_id)).await;
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+
+            // The note kept its content, so it can still be unpinned
+            let update = |content: &str| UpdateNoteRequest {
+                id: note.id,
+                user_id,
+                title: None,
+                content: Some(content.to_string()),
+                is_pinned: Some(false),
+                is_archived: None,
+                color: None,
+                tags: None,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            assert!(service.update_note(update("pinned")).await.is_ok());
+            assert!(service.update_note(update("longer")).await.is_err());
+            assert!(service.update_note(update("pin")).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn test_reorder_pins() {
+            let (service, user_id) = create_note_service();
+            let first = service
+                .create_note(pinned_note_request(user_id))
+                .await
+                .unwrap();
+            let second = service
+                .create_note(pinned_note_request(user_id))
+                .await
+                .unwrap();
+
+            let reordered = service
+                .reorder_pins(user_id, vec![second.id, first.id])
+                .await
+                .unwrap();
+            assert_eq!(reordered[0].id, second.id);
+            assert_eq!(reordered[0].pin_order, Some(0));
+            assert_eq!(reordered[1].pin_order, Some(1));
+
+            // Every pinned note must be listed exactly once
+            let result = service.reorder_pins(user_id, vec![first.id]).await;
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+            let result = service
+                .reorder_pins(user_id, vec![first.id, first.id])
+                .await;
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+        }
+
+        #[tokio::test]
+        async fn test_create_note_without_title() {
+            let (service, user_id) = create_note_service();
+
+            let req = CreateNoteRequest {
+                user_id,
+                title: None,
+                content: "Content without title".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+
+            let note = service.create_note(req).await.unwrap();
+
+            assert!(note.title.is_none());
+            assert_eq!(note.title_str(), "");
+            assert_eq!(note.content, "Content without title");
+        }
+
+        #[tokio::test]
+        async fn test_create_note_with_tags() {
+            let (service, user_id) = create_note_service();
+
+            let title = NoteTitle::try_from("Tagged Note").ok();
+            let tags = vec![
+                TagName::try_from("work").unwrap(),
+                TagName::try_from("important").unwrap(),
+            ];
+            let req = CreateNoteRequest {
+                user_id,
+                title,
+                content: "Content".to_string(),
+                tags,
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+
+            let note = service.create_note(req).await.unwrap();
+
+            assert_eq!(note.tags.len(), 2);
+            assert!(note.tags.iter().any(|t| t.name_str() == "work"));
+            assert!(note.tags.iter().any(|t| t.name_str() == "important"));
+        }
+
+        #[tokio::test]
+        async fn test_capture_creates_tagged_pinned_note_with_reminder() {
+            let (service, user_id) = create_note_service();
+
+            let note = service
+                .capture(user_id, "Book flights #travel !pinned @tomorrow")
+                .await
+                .unwrap();
+
+            assert_eq!(note.content, "Book flights");
+            assert_eq!(note.title, None);
+            assert_eq!(note.tags[0].name_str(), "travel");
+            assert!(note.is_pinned && note.pin_order.is_some());
+            assert!(note.remind_at.is_some_and(|at| at > Utc::now()));
+            assert!(service.capture(user_id, "#travel").await.is_err());
+        }
+
+        #[tokio::test]
+        async fn test_create_note_too_many_tags_fails() {
+            let (service, user_id) = create_note_service();
+
+            let tags: Vec<TagName> = (0..=MAX_TAGS_PER_NOTE)
+                .map(|i| TagName::try_from(format!("tag-{}", i)).unwrap())
+                .collect();
+
+            let title = NoteTitle::try_from("Note").ok();
+            let req = CreateNoteRequest {
+                user_id,
+                title,
+                content: "Content".to_string(),
+                tags,
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+
+            let result = service.create_note(req).await;
+            assert!(matches!(result, Err(DomainError::TagLimitExceeded { .. })));
+        }
+
+        #[tokio::test]
+        async fn test_update_note_success() {
+            let (service, user_id) = create_note_service();
+
+            // Create a note first
+            let title = NoteTitle::try_from("Original").ok();
+            let create_req = CreateNoteRequest {
+                user_id,
+                title,
+                content: "Original content".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let note = service.create_note(create_req).await.unwrap();
+
+            // Update it
+            let new_title = NoteTitle::try_from("Updated").ok();
+            let update_req = UpdateNoteRequest {
+                id: note.id,
+                user_id,
+                title: Some(new_title),
+                content: None,
+                is_pinned: Some(true),
+                is_archived: None,
+                color: Some("red".to_string()),
+                tags: None,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let updated = service.update_note(update_req).await.unwrap();
+
+            assert_eq!(updated.title_str(), "Updated");
+            assert_eq!(updated.content, "Original content"); // Unchanged
+            assert!(updated.is_pinned);
+            assert_eq!(updated.color, "red");
+        }
+
+        #[tokio::test]
+        async fn test_update_note_logs_each_change() {
+            let handler = Arc::new(RecordingEventHandler::default());
+            let service = NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            )
+            .with_event_dispatcher(Arc::new(
+                EventDispatcher::new().with_handler(handler.clone()),
+            ));
+            let user_id = Uuid::new_v4();
+
+            let note = service
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: "Content".to_string(),
+                    tags: vec![TagName::try_from("work").unwrap()],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+            service
+                .update_note(UpdateNoteRequest {
+                    id: note.id,
+                    user_id,
+                    title: None,
+                    content: None,
+                    is_pinned: None,
+                    is_archived: Some(true),
+                    color: None,
+                    tags: Some(vec![TagName::try_from("ideas").unwrap()]),
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+
+            let note_id = note.id;
+            assert_eq!(
+                handler.kinds(),
+                vec![
+                    LoggedEventKind::note_created(&note),
+                    LoggedEventKind::NoteArchived { note_id },
+                    LoggedEventKind::NoteUntagged {
+                        note_id,
+                        tag: "work".to_string(),
+                    },
+                    LoggedEventKind::NoteTagged {
+                        note_id,
+                        tag: "ideas".to_string(),
+                    },
+                ]
+            );
+            assert!(
+                handler
+                    .events
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .all(|e| e.user_id == user_id)
+            );
+        }
+
+        #[tokio::test]
+        async fn test_update_note_forbidden() {
+            let (service, user_id) = create_note_service();
+            let other_user = Uuid::new_v4();
+
+            // Create a note
+            let title = NoteTitle::try_from("My Note").ok();
+            let create_req = CreateNoteRequest {
+                user_id,
+                title,
+                content: "Content".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let note = service.create_note(create_req).await.unwrap();
+
+            // Try to update with different user
+            let new_title = NoteTitle::try_from("Hacked").ok();
+            let update_req = UpdateNoteRequest {
+                id: note.id,
+                user_id: other_user,
+                title: Some(new_title),
+                content: None,
+                is_pinned: None,
+                is_archived: None,
+                color: None,
+                tags: None,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let result = service.update_note(update_req).await;
+
+            assert!(matches!(result, Err(DomainError::Forbidden(_))));
+        }
+
+        #[tokio::test]
+        async fn test_delete_note_success() {
+            let (service, user_id) = create_note_service();
+
+            let title = NoteTitle::try_from("To Delete").ok();
+            let create_req = CreateNoteRequest {
+                user_id,
+                title,
+                content: "Content".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let note = service.create_note(create_req).await.unwrap();
+
+            service.delete_note(note.id, user_id).await.unwrap();
+
+            // Deleted notes move to the trash instead of disappearing
+            let trashed = service.get_note(note.id, user_id).await.unwrap();
+            assert!(trashed.is_trashed());
+            let live = service
+                .list_notes(user_id, NoteFilter::new())
+                .await
+                .unwrap();
+            assert!(live.is_empty());
+            let trash = service
+                .list_notes(user_id, NoteFilter::new().trashed())
+                .await
+                .unwrap();
+            assert_eq!(trash.len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_access_is_decided_by_the_authorization_policy() {
+            /// Lets anyone read, and only owners change anything
+            struct PublicReadPolicy;
+
+            #[async_trait::async_trait]
+            impl AuthorizationPolicy for PublicReadPolicy {
+                async fn authorize(
+                    &self,
+                    subject: Uuid,
+                    action: Action,
+                    resource: &Resource,
+                ) -> DomainResult<()> {
+                    if action == Action::Read {
+                        return Ok(());
+                    }
+                    OwnerPolicy.authorize(subject, action, resource).await
+                }
+            }
+
+            let (service, user_id) = create_note_service();
+            let service = service.with_authorization_policy(Arc::new(PublicReadPolicy));
+            let note = service
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: "Content".to_string(),
+                    tags: vec![],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+            let other_user = Uuid::new_v4();
+
+            assert!(service.get_note(note.id, other_user).await.is_ok());
+            let result = service.delete_note(note.id, other_user).await;
+            assert!(
+                matches!(result, Err(DomainError::Forbidden(message)) if message == "Cannot delete another user's note")
+            );
+        }
+
+        #[tokio::test]
+        async fn test_restore_note_from_trash() {
+            let (service, user_id) = create_note_service();
+            let note = service
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: "Content".to_string(),
+                    tags: vec![],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+            service.delete_note(note.id, user_id).await.unwrap();
+
+            let restored = service.restore_note(note.id, user_id).await.unwrap();
+
+            assert!(!restored.is_trashed());
+            let live = service
+                .list_notes(user_id, NoteFilter::new())
+                .await
+                .unwrap();
+            assert_eq!(live.len(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_purge_trash_only_removes_expired_notes() {
+            let (service, user_id) = create_note_service();
+            let mut ids = Vec::new();
+            for content in ["old", "recent"] {
+                let note = service
+                    .create_note(CreateNoteRequest {
+                        user_id,
+                        title: None,
+                        content: content.to_string(),
+                        tags: vec![],
+                        color: None,
+                        is_pinned: false,
+                        location: None,
+                        place_name: None,
+                        remind_at: None,
+                    })
+                    .await
+                    .unwrap();
+                service.delete_note(note.id, user_id).await.unwrap();
+                ids.push(note.id);
+            }
+            let (old_id, recent_id) = (ids[0], ids[1]);
+
+            // Backdate the first note's trash timestamp past the cutoff
+            let mut old = service.get_note(old_id, user_id).await.unwrap();
+            old.deleted_at = Some(chrono::Utc::now() - chrono::Duration::days(31));
+            service.note_repo.save(&old).await.unwrap();
+
+            let report = service
+                .purge_trash(chrono::Utc::now() - chrono::Duration::days(30))
+                .await
+                .unwrap();
+
+            assert_eq!(report.note_ids, vec![old_id]);
+            assert_eq!(report.reclaimed_bytes, "old".len() as u64);
+            assert!(matches!(
+                service.get_note(old_id, user_id).await,
+                Err(DomainError::NoteNotFound(_))
+            ));
+            assert!(service.get_note(recent_id, user_id).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn test_search_empty_query_returns_empty() {
+            let (service, user_id) = create_note_service();
+
+            let results = service.search_notes(user_id, "   ", false).await.unwrap();
+            assert!(results.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_search_applies_date_filters() {
+            let (service, user_id) = create_note_service();
+            for content in ["budget draft", "budget final"] {
+                service
+                    .create_note(CreateNoteRequest {
+                        user_id,
+                        title: None,
+                        content: content.to_string(),
+                        tags: vec![],
+                        color: None,
+                        is_pinned: false,
+                        location: None,
+                        place_name: None,
+                        remind_at: None,
+                    })
+                    .await
+                    .unwrap();
+            }
+
+            let recent = service
+                .search_notes(user_id, "budget after:yesterday", false)
+                .await
+                .unwrap();
+            assert_eq!(recent.len(), 2);
+            let none = service
+                .search_notes(user_id, "before:last week", false)
+                .await
+                .unwrap();
+            assert!(none.is_empty());
+            assert!(
+                service
+                    .search_notes(user_id, "budget after:whenever", false)
+                    .await
+                    .is_err()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_search_filters_by_detected_language() {
+            let (service, user_id) = create_note_service();
+            let mut ids = Vec::new();
+            for content in [
+                "Jutro rano idziemy na spacer do parku, a potem zjemy obiad u babci. Wieczorem czytamy książki i pijemy herbatę z cytryną.",
+                "Tomorrow morning we are going for a walk in the park before lunch.",
+            ] {
+                let note = service
+                    .create_note(CreateNoteRequest {
+                        user_id,
+                        title: None,
+                        content: content.to_string(),
+                        tags: vec![],
+                        color: None,
+                        is_pinned: false,
+                        location: None,
+                        place_name: None,
+                        remind_at: None,
+                    })
+                    .await
+                    .unwrap();
+                ids.push(note.id);
+            }
+
+            let polish = service
+                .search_notes(user_id, "lang:pl", false)
+                .await
+                .unwrap();
+            assert_eq!(
+                polish.iter().map(|n| n.id).collect::<Vec<_>>(),
+                vec![ids[0]]
+            );
+            assert_eq!(polish[0].language, Language::new("pl").ok());
+            assert!(
+                service
+                    .search_notes(user_id, "park lang:english", false)
+                    .await
+                    .is_err()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_suggest_search_combines_titles_tags_and_queries() {
+            let tag_repo = Arc::new(MockTagRepository::new());
+            let service = NoteService::new(Arc::new(MockNoteRepository::new()), tag_repo.clone())
+                .with_search_history(Arc::new(MockSearchHistoryRepository::default()));
+            let user_id = Uuid::new_v4();
+
+            for title in ["Recipes", "Reading list", "Groceries"] {
+                let req = CreateNoteRequest {
+                    user_id,
+                    title: NoteTitle::try_from(title).ok(),
+                    content: "content".to_string(),
+                    tags: vec![TagName::try_from("reference").unwrap()],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                };
+                service.create_note(req).await.unwrap();
+            }
+            service
+                .search_notes(user_id, "react hooks", false)
+                .await
+                .unwrap();
+            service
+                .search_notes(user_id, "groceries", false)
+                .await
+                .unwrap();
+
+            let suggestions = service.suggest_search(user_id, " Re").await.unwrap();
+            let titles: Vec<&str> = suggestions
+                .titles
+                .iter()
+                .map(|t| t.title.as_str())
+                .collect();
+            assert_eq!(titles, vec!["Reading list", "Recipes"]);
+            assert_eq!(suggestions.tags.len(), 1);
+            assert_eq!(suggestions.queries, vec!["react hooks"]);
+
+            let suggestions = service.suggest_search(user_id, "  ").await.unwrap();
+            assert_eq!(suggestions, SearchSuggestions::default());
+        }
+
+        #[tokio::test]
+        async fn test_frequent_queries_are_suggested_first() {
+            let service = NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            )
+            .with_search_history(Arc::new(MockSearchHistoryRepository::default()));
+            let user_id = Uuid::new_v4();
+
+            for query in ["rust traits", "rust traits", "rust macros"] {
+                service.search_notes(user_id, query, false).await.unwrap();
+            }
+
+            let suggestions = service.suggest_search(user_id, "rust").await.unwrap();
+            assert_eq!(suggestions.queries, vec!["rust traits", "rust macros"]);
+
+            let history = service.search_history(user_id, None).await.unwrap();
+            assert_eq!(history.len(), 2);
+            assert_eq!(history[0].query, "rust macros");
+            assert!(service.search_history(user_id, Some(0)).await.is_err());
+
+            assert_eq!(service.clear_search_history(user_id).await.unwrap(), 2);
+            assert!(
+                service
+                    .search_history(user_id, None)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_search_history_respects_opt_out() {
+            let user_repo = Arc::new(MockUserRepository::new());
+            let user_id = Uuid::new_v4();
+            let settings = UserSettings {
+                search_history_enabled: false,
+                ..UserSettings::default()
+            };
+            user_repo.save_settings(user_id, &settings).await.unwrap();
+
+            let service = NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            )
+            .with_user_repository(user_repo)
+            .with_search_history(Arc::new(MockSearchHistoryRepository::default()));
+
+            service
+                .search_notes(user_id, "private", false)
+                .await
+                .unwrap();
+
+            assert!(
+                service
+                    .search_history(user_id, None)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_update_note_creates_version() {
+            let (service, user_id) = create_note_service();
+
+            // Create original note
+            let title = NoteTitle::try_from("Original Title").ok();
+            let create_req = CreateNoteRequest {
+                user_id,
+                title,
+                content: "Original Content".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let note = service.create_note(create_req).await.unwrap();
+
+            // Update the note
+            let new_title = NoteTitle::try_from("New Title").ok();
+            let update_req = UpdateNoteRequest {
+                id: note.id,
+                user_id,
+                title: Some(new_title),
+                content: Some("New Content".to_string()),
+                is_pinned: None,
+                is_archived: None,
+                color: None,
+                tags: None,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            service.update_note(update_req).await.unwrap();
+
+            // Check if version was saved
+            let versions = service
+                .note_repo
+                .find_versions_by_note_id(note.id)
+                .await
+                .unwrap();
+
+            assert_eq!(versions.len(), 1);
+            let version = &versions[0];
+            assert_eq!(version.title, Some("Original Title".to_string()));
+            assert_eq!(version.content, "Original Content");
+            assert_eq!(version.note_id, note.id);
+            assert_eq!(version.created_by, Some(user_id));
+        }
+
+        fn content_update(note_id: Uuid, user_id: Uuid, content: &str) -> UpdateNoteRequest {
+            UpdateNoteRequest {
+                id: note_id,
+                user_id,
+                title: None,
+                content: Some(content.to_string()),
+                is_pinned: None,
+                is_archived: None,
+                color: None,
+                tags: None,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_update_note_debounces_versions() {
+            let (service, user_id) = create_note_service();
+            let req = CreateNoteRequest {
+                user_id,
+                title: None,
+                content: "v1".to_string(),
+                tags: vec![],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            };
+            let note = service.create_note(req).await.unwrap();
+
+            for content in ["v2", "v3"] {
+                service
+                    .update_note(content_update(note.id, user_id, content))
+                    .await
+                    .unwrap();
+            }
+            // Pinning does not touch the text, so it never snapshots
+            let pin = UpdateNoteRequest {
+                is_pinned: Some(true),
+                content: None,
+                ..content_update(note.id, user_id, "")
+            };
+            service.update_note(pin).await.unwrap();
 
-        fn create_note_service() -> (NoteService, Uuid) {
-            let note_repo = Arc::new(MockNoteRepository::new());
-            let tag_repo = Arc::new(MockTagRepository::new());
-            let user_id = Uuid::new_v4();
-            (NoteService::new(note_repo, tag_repo), user_id)
+            let versions = service
+                .note_repo
+                .find_versions_by_note_id(note.id)
+                .await
+                .unwrap();
+            assert_eq!(versions.len(), 1);
+            assert_eq!(versions[0].content, "v1");
         }
 
         #[tokio::test]
-        async fn test_create_note_success() {
+        async fn test_update_note_without_debounce_snapshots_every_edit() {
             let (service, user_id) = create_note_service();
-
-            let title = NoteTitle::try_from("My Note").ok();
+            let service = service.with_version_debounce_minutes(0);
             let req = CreateNoteRequest {
                 user_id,
-                title,
-                content: "# Hello World".to_string(),
+                title: None,
+                content: "v1".to_string(),
                 tags: vec![],
                 color: None,
                 is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
             };
-
             let note = service.create_note(req).await.unwrap();
 
-            assert_eq!(note.title_str(), "My Note");
-            assert_eq!(note.content, "# Hello World");
-            assert_eq!(note.user_id, user_id);
-            assert_eq!(note.color, "DEFAULT");
-            assert!(!note.is_pinned);
+            for content in ["v2", "v3"] {
+                service
+                    .update_note(content_update(note.id, user_id, content))
+                    .await
+                    .unwrap();
+            }
+
+            let versions = service
+                .note_repo
+                .find_versions_by_note_id(note.id)
+                .await
+                .unwrap();
+            assert_eq!(versions.len(), 2);
         }
 
         #[tokio::test]
-        async fn test_create_note_without_title() {
+        async fn test_locked_note_rejects_edits_and_deletion() {
             let (service, user_id) = create_note_service();
-
             let req = CreateNoteRequest {
                 user_id,
                 title: None,
-                content: "Content without title".to_string(),
+                content: "content".to_string(),
                 tags: vec![],
                 color: None,
                 is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
             };
-
             let note = service.create_note(req).await.unwrap();
 
-            assert!(note.title.is_none());
-            assert_eq!(note.title_str(), "");
-            assert_eq!(note.content, "Content without title");
+            let locked = service
+                .set_note_locked(note.id, user_id, true)
+                .await
+                .unwrap();
+            assert!(locked.is_locked);
+
+            let err = service
+                .update_note(content_update(note.id, user_id, "changed"))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, DomainError::NoteLocked(id) if id == note.id));
+            let err = service.delete_note(note.id, user_id).await.unwrap_err();
+            assert!(matches!(err, DomainError::NoteLocked(_)));
+
+            service
+                .set_note_locked(note.id, user_id, false)
+                .await
+                .unwrap();
+            service
+                .update_note(content_update(note.id, user_id, "changed"))
+                .await
+                .unwrap();
         }
 
         #[tokio::test]
-        async fn test_create_note_with_tags() {
-            let (service, user_id) = create_note_service();
+        async fn test_find_nearby_returns_closest_notes_within_radius() {
+            use crate::value_objects::{Latitude, Longitude};
 
-            let title = NoteTitle::try_from("Tagged Note").ok();
-            let tags = vec![
-                TagName::try_from("work").unwrap(),
-                TagName::try_from("important").unwrap(),
-            ];
-            let req = CreateNoteRequest {
-                user_id,
-                title,
-                content: "Content".to_string(),
-                tags,
-                color: None,
-                is_pinned: false,
+            let (service, user_id) = create_note_service();
+            let point = |lat: f64, lon: f64| {
+                GeoPoint::new(Latitude::new(lat).unwrap(), Longitude::new(lon).unwrap())
             };
+            let lisbon = point(38.7223, -9.1393);
+            let mut ids = Vec::new();
+            for location in [
+                Some(point(38.5244, -8.8882)), // Setúbal, ~30 km
+                Some(point(38.7369, -9.1427)), // ~2 km
+                Some(point(40.4168, -3.7038)), // Madrid
+                None,
+            ] {
+                let note = service
+                    .create_note(CreateNoteRequest {
+                        user_id,
+                        title: None,
+                        content: "content".to_string(),
+                        tags: vec![],
+                        color: None,
+                        is_pinned: false,
+                        location,
+                        place_name: None,
+                        remind_at: None,
+                    })
+                    .await
+                    .unwrap();
+                ids.push(note.id);
+            }
+
+            let nearby = service
+                .find_nearby(user_id, lisbon, 50.0, false)
+                .await
+                .unwrap();
+            assert_eq!(
+                nearby.iter().map(|n| n.note.id).collect::<Vec<_>>(),
+                vec![ids[1], ids[0]]
+            );
+            assert!(nearby[0].distance_km < 3.0);
+
+            let err = service
+                .find_nearby(user_id, lisbon, 0.0, false)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, DomainError::ValidationError(_)));
+        }
+    }
+
+    mod tag_service_tests {
+        use super::*;
+
+        fn create_tag_service() -> (TagService, Uuid) {
+            let tag_repo = Arc::new(MockTagRepository::new());
+            let user_id = Uuid::new_v4();
+            (TagService::new(tag_repo), user_id)
+        }
+
+        #[tokio::test]
+        async fn test_create_tag_success() {
+            let (service, user_id) = create_tag_service();
+
+            let name = TagName::try_from("Work").unwrap();
+            let tag = service.create_tag(user_id, name).await.unwrap();
+
+            assert_eq!(tag.name_str(), "work"); // Lowercase
+            assert_eq!(tag.user_id, user_id);
+        }
+
+        #[tokio::test]
+        async fn test_create_duplicate_tag_fails() {
+            let (service, user_id) = create_tag_service();
+
+            let name1 = TagName::try_from("work").unwrap();
+            service.create_tag(user_id, name1).await.unwrap();
+
+            let name2 = TagName::try_from("WORK").unwrap(); // Case-insensitive
+            let result = service.create_tag(user_id, name2).await;
+
+            assert!(matches!(result, Err(DomainError::TagAlreadyExists(_))));
+        }
+
+        #[tokio::test]
+        async fn test_find_by_name_only_finds_the_users_tags() {
+            let (service, user_id) = create_tag_service();
+            let tag = service
+                .create_tag(user_id, TagName::try_from("work").unwrap())
+                .await
+                .unwrap();
+
+            let found = service.find_by_name(user_id, "work").await.unwrap();
+            assert_eq!(found.map(|t| t.id), Some(tag.id));
+            assert!(
+                service
+                    .find_by_name(Uuid::new_v4(), "work")
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_create_and_rename_publish_tag_events() {
+            let broker = Arc::new(MockMessageBroker::default());
+            let service = TagService::new(Arc::new(MockTagRepository::new()))
+                .with_message_broker(broker.clone());
+            let user_id = Uuid::new_v4();
+
+            let tag = service
+                .create_tag(user_id, TagName::try_from("work").unwrap())
+                .await
+                .unwrap();
+            let renamed = service
+                .rename_tag(tag.id, user_id, TagName::try_from("job").unwrap())
+                .await
+                .unwrap();
+
+            let events = broker.events.lock().unwrap();
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[1].kind, DomainEventKind::TagUpdated(renamed));
+        }
+
+        #[tokio::test]
+        async fn test_rename_logs_previous_name() {
+            let handler = Arc::new(RecordingEventHandler::default());
+            let service = TagService::new(Arc::new(MockTagRepository::new()))
+                .with_event_dispatcher(Arc::new(
+                    EventDispatcher::new().with_handler(handler.clone()),
+                ));
+            let user_id = Uuid::new_v4();
+
+            let tag = service
+                .create_tag(user_id, TagName::try_from("work").unwrap())
+                .await
+                .unwrap();
+            service
+                .rename_tag(tag.id, user_id, TagName::try_from("work").unwrap())
+                .await
+                .unwrap();
+            service
+                .rename_tag(tag.id, user_id, TagName::try_from("job").unwrap())
+                .await
+                .unwrap();
+
+            assert_eq!(
+                handler.kinds(),
+                vec![
+                    LoggedEventKind::tag_created(&tag),
+                    LoggedEventKind::TagRenamed {
+                        tag_id: tag.id,
+                        name: "job".to_string(),
+                        previous_name: "work".to_string(),
+                    },
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn test_pinned_tags_stay_above_reordered_ones() {
+            let (service, user_id) = create_tag_service();
+            let mut created = Vec::new();
+            for name in ["alpha", "beta", "gamma"] {
+                let name = TagName::try_from(name).unwrap();
+                created.push(service.create_tag(user_id, name).await.unwrap());
+            }
+            let (alpha, beta, gamma) = (&created[0], &created[1], &created[2]);
+
+            let pinned = service
+                .set_tag_pinned(beta.id, user_id, true)
+                .await
+                .unwrap();
+            assert!(pinned.is_pinned);
+
+            let reordered = service
+                .reorder_tags(user_id, vec![gamma.id, alpha.id, beta.id])
+                .await
+                .unwrap();
+            let ids: Vec<Uuid> = reordered.iter().map(|t| t.id).collect();
+            assert_eq!(ids, vec![beta.id, gamma.id, alpha.id]);
+            assert_eq!(reordered[1].sort_order, Some(0));
+            assert_eq!(service.list_tags(user_id).await.unwrap(), reordered);
+
+            let result = service
+                .reorder_tags(user_id, vec![gamma.id, alpha.id])
+                .await;
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+            let result = service.set_tag_pinned(alpha.id, Uuid::new_v4(), true).await;
+            assert!(matches!(result, Err(DomainError::Forbidden(_))));
+        }
+
+        #[tokio::test]
+        async fn test_clean_up_unused_tags_follows_policy() {
+            let tag_repo = Arc::new(MockTagRepository::new());
+            let handler = Arc::new(RecordingEventHandler::default());
+            let service = TagService::new(tag_repo.clone()).with_event_dispatcher(Arc::new(
+                EventDispatcher::new().with_handler(handler.clone()),
+            ));
+            let user_id = Uuid::new_v4();
+
+            let used = service
+                .create_tag(user_id, TagName::try_from("work").unwrap())
+                .await
+                .unwrap();
+            let unused = service
+                .create_tag(user_id, TagName::try_from("old").unwrap())
+                .await
+                .unwrap();
+            tag_repo.add_to_note(used.id, Uuid::new_v4()).await.unwrap();
+
+            let flagged = service
+                .clean_up_unused_tags(user_id, UnusedTagPolicy::Flag)
+                .await
+                .unwrap();
+            assert_eq!(flagged, vec![unused.clone()]);
+            assert_eq!(service.list_tags(user_id).await.unwrap().len(), 2);
+
+            let deleted = service
+                .clean_up_unused_tags(user_id, UnusedTagPolicy::Delete)
+                .await
+                .unwrap();
+            assert_eq!(deleted, vec![unused.clone()]);
+            assert_eq!(service.list_tags(user_id).await.unwrap(), vec![used]);
+            assert!(handler.kinds().contains(&LoggedEventKind::TagDeleted {
+                tag_id: unused.id,
+                name: "old".to_string(),
+            }));
+            assert!(service.list_unused_tags(user_id).await.unwrap().is_empty());
+        }
+    }
+
+    mod user_service_tests {
+        use super::*;
+        use crate::onboarding::OnboardingTemplate;
+
+        /// Hashes as `hashed:<pw>`; `legacy:<pw>` simulates outdated hashes
+        struct MockPasswordHasher;
+
+        #[async_trait::async_trait]
+        impl PasswordHasher for MockPasswordHasher {
+            async fn hash(&self, password: &str) -> DomainResult<String> {
+                Ok(format!("hashed:{}", password))
+            }
+
+            async fn verify(&self, password: &str, hash: &str) -> DomainResult<bool> {
+                Ok(
+                    hash == format!("hashed:{}", password)
+                        || hash == format!("legacy:{}", password),
+                )
+            }
+
+            fn needs_rehash(&self, hash: &str) -> bool {
+                hash.starts_with("legacy:")
+            }
+        }
+
+        fn password(value: &str) -> Password {
+            Password::new(value).unwrap()
+        }
+
+        fn create_user_service_with_repo() -> (UserService, Arc<MockUserRepository>) {
+            let user_repo = Arc::new(MockUserRepository::new());
+            let service = UserService::new(user_repo.clone(), Arc::new(MockPasswordHasher));
+            (service, user_repo)
+        }
+
+        fn create_user_service() -> UserService {
+            create_user_service_with_repo().0
+        }
+
+        #[tokio::test]
+        async fn test_create_local_hashes_password() {
+            let service = create_user_service();
+            let user = service
+                .create_local("local@example.com", &password("secret"))
+                .await
+                .unwrap();
+
+            assert_eq!(user.password_hash.as_deref(), Some("hashed:secret"));
+        }
+
+        #[tokio::test]
+        async fn test_new_accounts_get_welcome_content() {
+            let note_repo = Arc::new(MockNoteRepository::new());
+            let tag_repo = Arc::new(MockTagRepository::new());
+            let template: OnboardingTemplate = serde_json::from_str(
+                r#"{
+                    "tags": ["ideas", "work"],
+                    "notes": [
+                        {"title": "Welcome", "content": "Hi {{email}}", "tags": ["work"], "is_pinned": true},
+                        {"content": "Try quick capture"}
+                    ]
+                }"#,
+            )
+            .unwrap();
+            let onboarding = OnboardingService::new(
+                Arc::new(NoteService::new(note_repo.clone(), tag_repo.clone())),
+                Arc::new(TagService::new(tag_repo.clone())),
+                template,
+            )
+            .unwrap();
+            let service = create_user_service().with_onboarding(Arc::new(onboarding));
+
+            let user = service
+                .create_local("new@example.com", &password("secret"))
+                .await
+                .unwrap();
+
+            let notes = note_repo
+                .find_by_user(user.id, NoteFilter::new())
+                .await
+                .unwrap();
+            assert_eq!(notes.len(), 2);
+            let welcome = notes.iter().find(|n| n.is_pinned).unwrap();
+            assert_eq!(welcome.content, "Hi new@example.com");
+            let mut tags: Vec<String> = tag_repo
+                .find_by_user(user.id)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|t| t.name_str().to_string())
+                .collect();
+            tags.sort();
+            assert_eq!(tags, vec!["ideas", "work"]);
+        }
+
+        #[tokio::test]
+        async fn test_authenticate_checks_password() {
+            let service = create_user_service();
+            service
+                .create_local("local@example.com", &password("secret"))
+                .await
+                .unwrap();
+
+            let ok = service
+                .authenticate("local@example.com", "secret")
+                .await
+                .unwrap();
+            let wrong = service
+                .authenticate("local@example.com", "wrong")
+                .await
+                .unwrap();
+            let unknown = service
+                .authenticate("nobody@example.com", "secret")
+                .await
+                .unwrap();
+
+            assert!(ok.is_some());
+            assert!(wrong.is_none());
+            assert!(unknown.is_none());
+        }
+
+        #[tokio::test]
+        async fn test_authenticate_upgrades_legacy_hash() {
+            let (service, user_repo) = create_user_service_with_repo();
+            let user = User::new_local(
+                Email::try_from("legacy@example.com").unwrap(),
+                "legacy:secret",
+            );
+            user_repo.save(&user).await.unwrap();
+
+            let authenticated = service
+                .authenticate("legacy@example.com", "secret")
+                .await
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(
+                authenticated.password_hash.as_deref(),
+                Some("hashed:secret")
+            );
+            let stored = user_repo.find_by_id(user.id).await.unwrap().unwrap();
+            assert_eq!(stored.password_hash.as_deref(), Some("hashed:secret"));
+        }
+
+        #[tokio::test]
+        async fn test_change_password_requires_current_password() {
+            let service = create_user_service();
+            let user = service
+                .create_local("local@example.com", &password("secret"))
+                .await
+                .unwrap();
+
+            let wrong = service
+                .change_password(user.id, "wrong", &password("new-secret"))
+                .await;
+            assert!(matches!(wrong, Err(DomainError::ValidationError(_))));
+
+            let updated = service
+                .change_password(user.id, "secret", &password("new-secret"))
+                .await
+                .unwrap();
+            assert_eq!(updated.password_hash.as_deref(), Some("hashed:new-secret"));
+        }
+
+        #[tokio::test]
+        async fn test_find_or_create_creates_new_user() {
+            let service = create_user_service();
+
+            let email = Email::try_from("test@example.com").unwrap();
+            let user = service
+                .find_or_create("oidc|123", email.as_ref())
+                .await
+                .unwrap();
+
+            assert_eq!(user.subject, "oidc|123");
+            assert_eq!(user.email_str(), "test@example.com");
+        }
+
+        #[tokio::test]
+        async fn test_find_or_create_returns_existing_user() {
+            let service = create_user_service();
+
+            let email1 = Email::try_from("test@example.com").unwrap();
+            let user1 = service
+                .find_or_create("oidc|123", email1.as_ref())
+                .await
+                .unwrap();
 
-            let note = service.create_note(req).await.unwrap();
+            let email2 = Email::try_from("test@example.com").unwrap();
+            let user2 = service
+                .find_or_create("oidc|123", email2.as_ref())
+                .await
+                .unwrap();
 
-            assert_eq!(note.tags.len(), 2);
-            assert!(note.tags.iter().any(|t| t.name_str() == "work"));
-            assert!(note.tags.iter().any(|t| t.name_str() == "important"));
+            assert_eq!(user1.id, user2.id);
         }
 
         #[tokio::test]
-        async fn test_create_note_too_many_tags_fails() {
-            let (service, user_id) = create_note_service();
+        async fn test_email_change_applies_after_confirmation() {
+            let service = create_user_service();
+            let user = service
+                .create_local("old@example.com", &password("secret"))
+                .await
+                .unwrap();
 
-            let tags: Vec<TagName> = (0..=MAX_TAGS_PER_NOTE)
-                .map(|i| TagName::try_from(format!("tag-{}", i)).unwrap())
-                .collect();
+            let change = service
+                .request_email_change(user.id, Email::try_from("new@example.com").unwrap())
+                .await
+                .unwrap();
 
-            let title = NoteTitle::try_from("Note").ok();
-            let req = CreateNoteRequest {
-                user_id,
-                title,
-                content: "Content".to_string(),
-                tags,
-                color: None,
-                is_pinned: false,
-            };
+            // Nothing changes until the token is confirmed
+            let unchanged = service.find_by_id(user.id).await.unwrap();
+            assert_eq!(unchanged.email_str(), "old@example.com");
 
-            let result = service.create_note(req).await;
-            assert!(matches!(result, Err(DomainError::TagLimitExceeded { .. })));
+            let updated = service
+                .confirm_email_change(user.id, &change.token)
+                .await
+                .unwrap();
+            assert_eq!(updated.email_str(), "new@example.com");
+
+            // Tokens are single-use
+            let reused = service.confirm_email_change(user.id, &change.token).await;
+            assert!(matches!(reused, Err(DomainError::ValidationError(_))));
         }
 
         #[tokio::test]
-        async fn test_update_note_success() {
-            let (service, user_id) = create_note_service();
-
-            // Create a note first
-            let title = NoteTitle::try_from("Original").ok();
-            let create_req = CreateNoteRequest {
-                user_id,
-                title,
-                content: "Original content".to_string(),
-                tags: vec![],
-                color: None,
-                is_pinned: false,
-            };
-            let note = service.create_note(create_req).await.unwrap();
+        async fn test_email_change_rejects_taken_address() {
+            let service = create_user_service();
+            let user = service
+                .create_local("a@example.com", &password("secret"))
+                .await
+                .unwrap();
+            service
+                .create_local("b@example.com", &password("secret"))
+                .await
+                .unwrap();
 
-            // Update it
-            let new_title = NoteTitle::try_from("Updated").ok();
-            let update_req = UpdateNoteRequest {
-                id: note.id,
-                user_id,
-                title: Some(new_title),
-                content: None,
-                is_pinned: Some(true),
-                is_archived: None,
-                color: Some("red".to_string()),
-                tags: None,
-            };
-            let updated = service.update_note(update_req).await.unwrap();
+            let result = service
+                .request_email_change(user.id, Email::try_from("b@example.com").unwrap())
+                .await;
 
-            assert_eq!(updated.title_str(), "Updated");
-            assert_eq!(updated.content, "Original content"); // Unchanged
-            assert!(updated.is_pinned);
-            assert_eq!(updated.color, "red");
+            assert!(matches!(result, Err(DomainError::UserAlreadyExists(_))));
         }
 
         #[tokio::test]
-        async fn test_update_note_unauthorized() {
-            let (service, user_id) = create_note_service();
-            let other_user = Uuid::new_v4();
-
-            // Create a note
-            let title = NoteTitle::try_from("My Note").ok();
-            let create_req = CreateNoteRequest {
-                user_id,
-                title,
-                content: "Content".to_string(),
-                tags: vec![],
-                color: None,
-                is_pinned: false,
-            };
-            let note = service.create_note(create_req).await.unwrap();
+        async fn test_email_change_token_is_bound_to_user() {
+            let service = create_user_service();
+            let user = service
+                .create_local("a@example.com", &password("secret"))
+                .await
+                .unwrap();
+            let other = service
+                .create_local("c@example.com", &password("secret"))
+                .await
+                .unwrap();
 
-            // Try to update with different user
-            let new_title = NoteTitle::try_from("Hacked").ok();
-            let update_req = UpdateNoteRequest {
-                id: note.id,
-                user_id: other_user,
-                title: Some(new_title),
-                content: None,
-                is_pinned: None,
-                is_archived: None,
-                color: None,
-                tags: None,
-            };
-            let result = service.update_note(update_req).await;
+            let change = service
+                .request_email_change(user.id, Email::try_from("new@example.com").unwrap())
+                .await
+                .unwrap();
 
-            assert!(matches!(result, Err(DomainError::Unauthorized(_))));
+            let result = service.confirm_email_change(other.id, &change.token).await;
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
         }
 
         #[tokio::test]
-        async fn test_delete_note_success() {
-            let (service, user_id) = create_note_service();
-
-            let title = NoteTitle::try_from("To Delete").ok();
-            let create_req = CreateNoteRequest {
-                user_id,
-                title,
-                content: "Content".to_string(),
-                tags: vec![],
-                color: None,
-                is_pinned: false,
-            };
-            let note = service.create_note(create_req).await.unwrap();
+        async fn test_update_settings_only_changes_given_fields() {
+            let service = create_user_service();
+            let user_id = Uuid::new_v4();
 
-            service.delete_note(note.id, user_id).await.unwrap();
+            let settings = service
+                .update_settings(
+                    user_id,
+                    UpdateSettingsRequest {
+                        timezone: Some("Europe/Warsaw".to_string()),
+                        smart_features_enabled: Some(false),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
 
-            let result = service.get_note(note.id, user_id).await;
-            assert!(matches!(result, Err(DomainError::NoteNotFound(_))));
+            assert_eq!(settings.timezone, "Europe/Warsaw");
+            assert!(!settings.smart_features_enabled);
+            assert_eq!(settings.locale, UserSettings::default().locale);
+            assert_eq!(service.get_settings(user_id).await.unwrap(), settings);
         }
 
         #[tokio::test]
-        async fn test_search_empty_query_returns_empty() {
-            let (service, user_id) = create_note_service();
+        async fn test_update_settings_rejects_empty_color() {
+            let service = create_user_service();
 
-            let results = service.search_notes(user_id, "   ").await.unwrap();
-            assert!(results.is_empty());
+            let result = service
+                .update_settings(
+                    Uuid::new_v4(),
+                    UpdateSettingsRequest {
+                        default_note_color: Some("  ".to_string()),
+                        ..Default::default()
+                    },
+                )
+                .await;
+
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
         }
 
         #[tokio::test]
-        async fn test_update_note_creates_version() {
-            let (service, user_id) = create_note_service();
-
-            // Create original note
-            let title = NoteTitle::try_from("Original Title").ok();
-            let create_req = CreateNoteRequest {
-                user_id,
-                title,
-                content: "Original Content".to_string(),
-                tags: vec![],
-                color: None,
-                is_pinned: false,
-            };
-            let note = service.create_note(create_req).await.unwrap();
+        async fn test_update_settings_rejects_unknown_timezone() {
+            let service = create_user_service();
 
-            // Update the note
-            let new_title = NoteTitle::try_from("New Title").ok();
-            let update_req = UpdateNoteRequest {
-                id: note.id,
-                user_id,
-                title: Some(new_title),
-                content: Some("New Content".to_string()),
-                is_pinned: None,
-                is_archived: None,
-                color: None,
-                tags: None,
-            };
-            service.update_note(update_req).await.unwrap();
+            let result = service
+                .update_settings(
+                    Uuid::new_v4(),
+                    UpdateSettingsRequest {
+                        timezone: Some("Europe/Atlantis".to_string()),
+                        ..Default::default()
+                    },
+                )
+                .await;
+
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+        }
 
-            // Check if version was saved
-            let versions = service
-                .note_repo
-                .find_versions_by_note_id(note.id)
+        #[tokio::test]
+        async fn test_delete_user_publishes_event() {
+            let (service, user_repo) = create_user_service_with_repo();
+            let broker = Arc::new(MockMessageBroker::default());
+            let service = service.with_message_broker(broker.clone());
+            let user = service
+                .create_local("local@example.com", &password("secret"))
                 .await
                 .unwrap();
 
-            assert_eq!(versions.len(), 1);
-            let version = &versions[0];
-            assert_eq!(version.title, Some("Original Title".to_string()));
-            assert_eq!(version.content, "Original Content");
-            assert_eq!(version.note_id, note.id);
+            service.delete_user(user.id).await.unwrap();
+
+            assert!(user_repo.users.lock().unwrap().is_empty());
+            let events = broker.events.lock().unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].user_id, user.id);
+            assert_eq!(events[0].kind, DomainEventKind::UserDeleted);
         }
     }
 
-    mod tag_service_tests {
+    pub(crate) mod smart_note_service_tests {
         use super::*;
+        use crate::entities::{NoteLink, RelatedNote};
+        use crate::ports::{
+            EmbeddingGenerator, LinkRepository, VectorFilter, VectorPayload, VectorStore,
+        };
+
+        /// Embeds a text as `[len]` and counts calls
+        #[derive(Default)]
+        struct MockEmbeddingGenerator {
+            batches: Mutex<Vec<usize>>,
+        }
 
-        fn create_tag_service() -> (TagService, Uuid) {
-            let tag_repo = Arc::new(MockTagRepository::new());
-            let user_id = Uuid::new_v4();
-            (TagService::new(tag_repo), user_id)
+        #[async_trait::async_trait]
+        impl EmbeddingGenerator for MockEmbeddingGenerator {
+            async fn generate_embedding(&self, text: &str) -> DomainResult<Vec<f32>> {
+                self.batches.lock().unwrap().push(1);
+                Ok(vec![text.len() as f32])
+            }
+
+            async fn generate_embeddings(&self, texts: &[String]) -> DomainResult<Vec<Vec<f32>>> {
+                self.batches.lock().unwrap().push(texts.len());
+                Ok(texts.iter().map(|t| vec![t.len() as f32]).collect())
+            }
+
+            fn model_name(&self) -> &str {
+                "mock-v1"
+            }
         }
 
-        #[tokio::test]
-        async fn test_create_tag_success() {
-            let (service, user_id) = create_tag_service();
+        #[derive(Default)]
+        pub(crate) struct MockVectorStore {
+            pub(crate) vectors: Mutex<HashMap<Uuid, (Vec<f32>, VectorPayload)>>,
+        }
 
-            let name = TagName::try_from("Work").unwrap();
-            let tag = service.create_tag(user_id, name).await.unwrap();
+        #[async_trait::async_trait]
+        impl VectorStore for MockVectorStore {
+            async fn upsert(
+                &self,
+                id: Uuid,
+                vector: &[f32],
+                payload: &VectorPayload,
+            ) -> DomainResult<()> {
+                self.vectors
+                    .lock()
+                    .unwrap()
+                    .insert(id, (vector.to_vec(), payload.clone()));
+                Ok(())
+            }
 
-            assert_eq!(tag.name_str(), "work"); // Lowercase
-            assert_eq!(tag.user_id, user_id);
+            async fn get_payloads(
+                &self,
+                ids: &[Uuid],
+            ) -> DomainResult<HashMap<Uuid, VectorPayload>> {
+                let vectors = self.vectors.lock().unwrap();
+                Ok(ids
+                    .iter()
+                    .filter_map(|id| Some((*id, vectors.get(id)?.1.clone())))
+                    .collect())
+            }
+
+            async fn find_similar(
+                &self,
+                _vector: &[f32],
+                limit: usize,
+                filter: &VectorFilter,
+            ) -> DomainResult<Vec<(Uuid, f32)>> {
+                let vectors = self.vectors.lock().unwrap();
+                Ok(vectors
+                    .iter()
+                    .filter(|(_, (_, p))| filter.user_id.is_none_or(|u| p.user_id == u))
+                    .filter(|(id, _)| filter.exclude_note_id != Some(**id))
+                    .take(limit)
+                    .map(|(id, _)| (*id, 0.9))
+                    .collect())
+            }
+
+            async fn delete(&self, id: Uuid) -> DomainResult<()> {
+                self.vectors.lock().unwrap().remove(&id);
+                Ok(())
+            }
+
+            async fn delete_by_user(&self, user_id: Uuid) -> DomainResult<()> {
+                self.vectors
+                    .lock()
+                    .unwrap()
+                    .retain(|_, (_, p)| p.user_id != user_id);
+                Ok(())
+            }
+
+            async fn count(&self) -> DomainResult<u64> {
+                Ok(self.vectors.lock().unwrap().len() as u64)
+            }
+
+            async fn ids(&self) -> DomainResult<Vec<Uuid>> {
+                Ok(self.vectors.lock().unwrap().keys().copied().collect())
+            }
         }
 
-        #[tokio::test]
-        async fn test_create_duplicate_tag_fails() {
-            let (service, user_id) = create_tag_service();
+        #[derive(Default)]
+        struct MockLinkRepository {
+            links: Mutex<Vec<NoteLink>>,
+        }
 
-            let name1 = TagName::try_from("work").unwrap();
-            service.create_tag(user_id, name1).await.unwrap();
+        #[async_trait::async_trait]
+        impl LinkRepository for MockLinkRepository {
+            async fn save_links(&self, links: &[NoteLink]) -> DomainResult<()> {
+                self.links.lock().unwrap().extend_from_slice(links);
+                Ok(())
+            }
 
-            let name2 = TagName::try_from("WORK").unwrap(); // Case-insensitive
-            let result = service.create_tag(user_id, name2).await;
+            async fn delete_links_for_source(&self, source_note_id: Uuid) -> DomainResult<()> {
+                self.links
+                    .lock()
+                    .unwrap()
+                    .retain(|l| l.source_note_id != source_note_id);
+                Ok(())
+            }
 
-            assert!(matches!(result, Err(DomainError::TagAlreadyExists(_))));
+            async fn get_links_for_note(
+                &self,
+                _source_note_id: Uuid,
+                _limit: usize,
+                _min_score: f32,
+            ) -> DomainResult<Vec<RelatedNote>> {
+                Ok(vec![])
+            }
+
+            async fn get_links_for_user(&self, _user_id: Uuid) -> DomainResult<Vec<NoteLink>> {
+                Ok(self.links.lock().unwrap().clone())
+            }
         }
-    }
 
-    mod user_service_tests {
-        use super::*;
+        #[tokio::test]
+        async fn test_process_notes_embeds_in_one_batch() {
+            let embedder = Arc::new(MockEmbeddingGenerator::default());
+            let vectors = Arc::new(MockVectorStore::default());
+            let links = Arc::new(MockLinkRepository::default());
+            let service = SmartNoteService::new(embedder.clone(), vectors.clone(), links.clone());
 
-        fn create_user_service() -> UserService {
-            let user_repo = Arc::new(MockUserRepository::new());
-            UserService::new(user_repo)
+            let user_id = Uuid::new_v4();
+            let notes = vec![
+                Note::new(user_id, None, "first"),
+                Note::new(user_id, None, "second note"),
+            ];
+            service.process_notes(&notes).await.unwrap();
+
+            assert_eq!(*embedder.batches.lock().unwrap(), vec![2]);
+            let (vector, payload) = vectors.vectors.lock().unwrap()[&notes[1].id].clone();
+            assert_eq!(vector, vec![11.0]);
+            assert_eq!(payload, VectorPayload::for_note(&notes[1], "mock-v1"));
+            // The second note links to the first, which was stored before it
+            assert!(
+                links
+                    .links
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .any(|l| l.source_note_id == notes[1].id && l.target_note_id == notes[0].id)
+            );
         }
 
         #[tokio::test]
-        async fn test_find_or_create_creates_new_user() {
-            let service = create_user_service();
+        async fn test_unchanged_content_is_not_embedded_again() {
+            let embedder = Arc::new(MockEmbeddingGenerator::default());
+            let service = SmartNoteService::new(
+                embedder.clone(),
+                Arc::new(MockVectorStore::default()),
+                Arc::new(MockLinkRepository::default()),
+            );
+
+            let mut note = Note::new(Uuid::new_v4(), None, "content");
+            service.process_note(&note).await.unwrap();
+            note.is_pinned = true;
+            service.process_note(&note).await.unwrap();
+            assert_eq!(embedder.batches.lock().unwrap().len(), 1);
+
+            note.content = "edited content".to_string();
+            service.process_note(&note).await.unwrap();
+            assert_eq!(embedder.batches.lock().unwrap().len(), 2);
+        }
 
-            let email = Email::try_from("test@example.com").unwrap();
-            let user = service
-                .find_or_create("oidc|123", email.as_ref())
-                .await
-                .unwrap();
+        #[tokio::test]
+        async fn test_refresh_note_embeds_unchanged_content_again() {
+            let embedder = Arc::new(MockEmbeddingGenerator::default());
+            let links = Arc::new(MockLinkRepository::default());
+            let service = SmartNoteService::new(
+                embedder.clone(),
+                Arc::new(MockVectorStore::default()),
+                links.clone(),
+            );
 
-            assert_eq!(user.subject, "oidc|123");
-            assert_eq!(user.email_str(), "test@example.com");
+            let user_id = Uuid::new_v4();
+            let first = Note::new(user_id, None, "first");
+            let second = Note::new(user_id, None, "second");
+            service.process_note(&first).await.unwrap();
+            service.process_note(&second).await.unwrap();
+            service.refresh_note(&first).await.unwrap();
+
+            assert_eq!(embedder.batches.lock().unwrap().len(), 3);
+            // The first note now links to the one stored after it
+            assert!(
+                links
+                    .links
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .any(|l| l.source_note_id == first.id && l.target_note_id == second.id)
+            );
         }
 
         #[tokio::test]
-        async fn test_find_or_create_returns_existing_user() {
-            let service = create_user_service();
+        async fn test_draft_text_is_matched_against_the_users_notes() {
+            let service = SmartNoteService::new(
+                Arc::new(MockEmbeddingGenerator::default()),
+                Arc::new(MockVectorStore::default()),
+                Arc::new(MockLinkRepository::default()),
+            );
 
-            let email1 = Email::try_from("test@example.com").unwrap();
-            let user1 = service
-                .find_or_create("oidc|123", email1.as_ref())
+            let user_id = Uuid::new_v4();
+            let editing = Note::new(user_id, None, "draft");
+            let other = Note::new(user_id, None, "older note");
+            let theirs = Note::new(Uuid::new_v4(), None, "someone else's");
+            service
+                .process_notes(&[editing.clone(), other.clone(), theirs])
                 .await
                 .unwrap();
 
-            let email2 = Email::try_from("test@example.com").unwrap();
-            let user2 = service
-                .find_or_create("oidc|123", email2.as_ref())
+            let similar = service
+                .find_similar_to_text(user_id, "draft text", Some(editing.id), 5)
+                .await
+                .unwrap();
+            let ids: Vec<Uuid> = similar.into_iter().map(|(id, _)| id).collect();
+            assert_eq!(ids, vec![other.id]);
+            assert!(
+                service
+                    .find_similar_to_text(user_id, "  ", None, 5)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_notes_only_link_within_their_owner() {
+            let vectors = Arc::new(MockVectorStore::default());
+            let links = Arc::new(MockLinkRepository::default());
+            let service = SmartNoteService::new(
+                Arc::new(MockEmbeddingGenerator::default()),
+                vectors,
+                links.clone(),
+            );
+
+            let mine = Note::new(Uuid::new_v4(), None, "shared words");
+            let theirs = Note::new(Uuid::new_v4(), None, "shared words");
+            service.process_note(&mine).await.unwrap();
+            service.process_note(&theirs).await.unwrap();
+
+            assert!(links.links.lock().unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_forget_user_keeps_other_users_vectors() {
+            let vectors = Arc::new(MockVectorStore::default());
+            let service = SmartNoteService::new(
+                Arc::new(MockEmbeddingGenerator::default()),
+                vectors.clone(),
+                Arc::new(MockLinkRepository::default()),
+            );
+
+            let mine = Note::new(Uuid::new_v4(), None, "mine");
+            let theirs = Note::new(Uuid::new_v4(), None, "theirs");
+            service
+                .process_notes(&[mine.clone(), theirs.clone()])
                 .await
                 .unwrap();
+            service.forget_user(mine.user_id).await.unwrap();
 
-            assert_eq!(user1.id, user2.id);
+            let vectors = vectors.vectors.lock().unwrap();
+            assert!(!vectors.contains_key(&mine.id));
+            assert!(vectors.contains_key(&theirs.id));
         }
     }
 }
@@ -0,0 +1,405 @@
+//! Notes read aloud
+//!
+//! Instances with a [`SpeechSynthesizer`]
+//! turn notes into audio, for listening to long notes. The title and content
+//! are read without their Markdown markup, in a voice for the note's language
+//! where the synthesizer has one. Audio is kept per spoken text, so playing
+//! an unchanged note again does not synthesize it again.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::{SpeechSynthesizer, content_hash};
+use crate::services::NoteService;
+
+/// Longest text read aloud, in characters
+pub const MAX_SPEECH_LENGTH: usize = 50_000;
+
+/// Audio kept for notes played again, in bytes
+pub const SPEECH_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// List, quote and task markers taken off the start of a line
+const LINE_MARKERS: &[&str] = &["- ", "* ", "+ ", "> ", "[ ] ", "[x] ", "[X] "];
+
+/// Encodings notes can be read into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    /// Opus in an Ogg container
+    Ogg,
+}
+
+impl AudioFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "audio/mpeg",
+            Self::Ogg => "audio/ogg",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Ogg => "ogg",
+        }
+    }
+}
+
+/// A note read aloud
+#[derive(Debug, Clone)]
+pub struct SpokenNote {
+    pub audio: Arc<Vec<u8>>,
+    pub format: AudioFormat,
+    /// Changes whenever the audio would, so clients can cache it
+    pub fingerprint: String,
+}
+
+/// What is read aloud for a note: its title and content as plain sentences.
+///
+/// Code blocks, link targets and bare URLs are left out, and every line
+/// ends with punctuation so list items and headings get a pause.
+pub fn spoken_text(title: &str, content: &str) -> String {
+    let mut paragraphs = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_code = false;
+
+    if let Some(title) = sentence(title) {
+        paragraphs.push(title);
+    }
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || is_rule(line) {
+            continue;
+        }
+        match sentence(&inline_text(strip_markers(line))) {
+            Some(text) => paragraph.push(text),
+            None if !paragraph.is_empty() => {
+                paragraphs.push(std::mem::take(&mut paragraph).join(" "))
+            }
+            None => {}
+        }
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph.join(" "));
+    }
+    paragraphs.join("\n\n")
+}
+
+/// `text` with collapsed whitespace and closing punctuation
+fn sentence(text: &str) -> Option<String> {
+    let mut text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !text.chars().any(char::is_alphanumeric) {
+        return None;
+    }
+    if !text.ends_with(['.', '!', '?', ':', ';', ',']) {
+        text.push('.');
+    }
+    Some(text)
+}
+
+/// Horizontal rules and table separator rows
+fn is_rule(line: &str) -> bool {
+    line.chars()
+        .filter(|c| matches!(c, '-' | '*' | '_'))
+        .count()
+        >= 3
+        && line
+            .chars()
+            .all(|c| matches!(c, '-' | '*' | '_' | '|' | ':' | ' '))
+}
+
+/// `line` without its heading, list, quote and task markers
+fn strip_markers(line: &str) -> &str {
+    // `#tag` is a tag, not a heading
+    let unheaded = line.trim_start_matches('#');
+    let mut line = if unheaded.len() < line.len() && unheaded.starts_with(' ') {
+        unheaded.trim_start()
+    } else {
+        line
+    };
+    while let Some(rest) = LINE_MARKERS.iter().find_map(|m| line.strip_prefix(m)) {
+        line = rest.trim_start();
+    }
+    line
+}
+
+/// The words of a Markdown line: link and image texts without their
+/// targets, wiki-links by their label, and no emphasis or URLs
+fn inline_text(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("[[")
+            && let Some(end) = after.find("]]")
+        {
+            let link = &after[..end];
+            text.push_str(link.rsplit('|').next().unwrap_or(link));
+            rest = &after[end + 2..];
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("![").or_else(|| rest.strip_prefix('['))
+            && let Some((label, target)) = after.split_once("](")
+            && !label.contains(']')
+            && let Some(end) = target.find(')')
+        {
+            text.push_str(label);
+            rest = &target[end + 1..];
+            continue;
+        }
+        if rest.starts_with("http://") || rest.starts_with("https://") {
+            rest = &rest[rest.find(char::is_whitespace).unwrap_or(rest.len())..];
+            continue;
+        }
+
+        let at_word_edge = text.is_empty()
+            || text.ends_with(char::is_whitespace)
+            || rest[c.len_utf8()..]
+                .chars()
+                .next()
+                .is_none_or(|next| !next.is_alphanumeric());
+        match c {
+            '*' | '`' | '~' | '#' => {}
+            '_' if at_word_edge => {}
+            '|' => text.push_str(", "),
+            _ => text.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    text
+}
+
+/// Service reading notes aloud with a speech synthesizer
+pub struct SpeechService {
+    notes: Arc<NoteService>,
+    synthesizer: Arc<dyn SpeechSynthesizer>,
+    /// Audio by format, language and spoken text hash, emptied when full
+    audio: Mutex<HashMap<String, Arc<Vec<u8>>>>,
+}
+
+impl SpeechService {
+    pub fn new(notes: Arc<NoteService>, synthesizer: Arc<dyn SpeechSynthesizer>) -> Self {
+        Self {
+            notes,
+            synthesizer,
+            audio: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A note's title and content read aloud as `format`
+    pub async fn speak(
+        &self,
+        note_id: Uuid,
+        user_id: Uuid,
+        format: AudioFormat,
+    ) -> DomainResult<SpokenNote> {
+        let note = self.notes.get_note(note_id, user_id).await?;
+        let text = spoken_text(note.title_str(), &note.content);
+        if text.is_empty() {
+            return Err(DomainError::validation(
+                "The note has nothing to read aloud",
+            ));
+        }
+        if text.chars().count() > MAX_SPEECH_LENGTH {
+            return Err(DomainError::validation(format!(
+                "Notes longer than {} characters cannot be read aloud",
+                MAX_SPEECH_LENGTH
+            )));
+        }
+
+        let language = note.language.as_ref();
+        let fingerprint = format!(
+            "{}-{}-{}",
+            format.extension(),
+            language.map_or("auto", |l| l.code()),
+            content_hash(&text)
+        );
+        let cached = self.audio.lock().unwrap().get(&fingerprint).cloned();
+        let audio = match cached {
+            Some(audio) => audio,
+            None => {
+                let audio = Arc::new(self.synthesizer.synthesize(&text, language, format).await?);
+                let mut cache = self.audio.lock().unwrap();
+                let cached_bytes: usize = cache.values().map(|a| a.len()).sum();
+                if cached_bytes + audio.len() > SPEECH_CACHE_BYTES {
+                    cache.clear();
+                }
+                if audio.len() <= SPEECH_CACHE_BYTES {
+                    cache.insert(fingerprint.clone(), audio.clone());
+                }
+                audio
+            }
+        };
+
+        Ok(SpokenNote {
+            audio,
+            format,
+            fingerprint,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spoken_text_drops_markup() {
+        let content = "# Trip\n\nWe leave at **9**, see [the map](https://maps.example/k) \
+                       or https://example.com #travel\n\n- tent\n- [x] snacks\n\n\
+                       ```\nlet x = 1;\n```\n---\nAsk [[Anna|my sister]]";
+
+        assert_eq!(
+            spoken_text("Kraków", content),
+            "Kraków.\n\nTrip.\n\nWe leave at 9, see the map or travel.\n\n\
+             tent. snacks.\n\nAsk my sister."
+        );
+    }
+
+    #[test]
+    fn test_spoken_text_keeps_words_with_underscores() {
+        assert_eq!(
+            spoken_text("", "Rename _old_ to snake_case now!"),
+            "Rename old to snake_case now!"
+        );
+        assert_eq!(spoken_text("", "```\ncode only\n```\n\n***"), "");
+    }
+
+    mod speech_service_tests {
+        use super::*;
+        use crate::language::Language;
+        use crate::repositories::tests::MockNoteRepository;
+        use crate::services::tests::*;
+        use crate::services::{CreateNoteRequest, UpdateNoteRequest};
+        use crate::value_objects::NoteTitle;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Returns the format and text as the audio, counting its calls
+        #[derive(Default)]
+        struct EchoSynthesizer {
+            calls: AtomicUsize,
+        }
+
+        #[async_trait::async_trait]
+        impl SpeechSynthesizer for EchoSynthesizer {
+            async fn synthesize(
+                &self,
+                text: &str,
+                _language: Option<&Language>,
+                format: AudioFormat,
+            ) -> DomainResult<Vec<u8>> {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                Ok(format!("{}:{}", format.extension(), text).into_bytes())
+            }
+        }
+
+        #[tokio::test]
+        async fn test_audio_is_cached_per_format_and_text() {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let synthesizer = Arc::new(EchoSynthesizer::default());
+            let service = SpeechService::new(notes.clone(), synthesizer.clone());
+            let user_id = Uuid::new_v4();
+            let note = notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: NoteTitle::from_optional(Some("Groceries".to_string())).unwrap(),
+                    content: "- **milk**\n- eggs".to_string(),
+                    tags: vec![],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+
+            let first = service
+                .speak(note.id, user_id, AudioFormat::Mp3)
+                .await
+                .unwrap();
+            assert_eq!(
+                first.audio.as_slice(),
+                b"mp3:Groceries.\n\nmilk. eggs.".as_slice()
+            );
+            let again = service
+                .speak(note.id, user_id, AudioFormat::Mp3)
+                .await
+                .unwrap();
+            assert_eq!(again.fingerprint, first.fingerprint);
+            assert_eq!(synthesizer.calls.load(Ordering::SeqCst), 1);
+
+            let ogg = service
+                .speak(note.id, user_id, AudioFormat::Ogg)
+                .await
+                .unwrap();
+            assert_ne!(ogg.fingerprint, first.fingerprint);
+            assert_eq!(synthesizer.calls.load(Ordering::SeqCst), 2);
+
+            notes
+                .update_note(UpdateNoteRequest {
+                    id: note.id,
+                    user_id,
+                    title: None,
+                    content: Some("- milk\n- bread".to_string()),
+                    is_pinned: None,
+                    is_archived: None,
+                    color: None,
+                    tags: None,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+            let edited = service
+                .speak(note.id, user_id, AudioFormat::Mp3)
+                .await
+                .unwrap();
+            assert_ne!(edited.fingerprint, first.fingerprint);
+            assert_eq!(synthesizer.calls.load(Ordering::SeqCst), 3);
+        }
+
+        #[tokio::test]
+        async fn test_notes_without_words_are_refused() {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let synthesizer = Arc::new(EchoSynthesizer::default());
+            let service = SpeechService::new(notes.clone(), synthesizer.clone());
+            let user_id = Uuid::new_v4();
+            let note = notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: "```\nfn main() {}\n```".to_string(),
+                    tags: vec![],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+
+            let result = service.speak(note.id, user_id, AudioFormat::Mp3).await;
+            assert!(matches!(result, Err(DomainError::ValidationError(_))));
+            assert_eq!(synthesizer.calls.load(Ordering::SeqCst), 0);
+        }
+    }
+}
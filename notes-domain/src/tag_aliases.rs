@@ -0,0 +1,314 @@
+//! Alternative names for tags
+//!
+//! An alias such as `js` stands for a canonical tag such as `javascript`:
+//! tagging a note `js` tags it `javascript` instead, so inconsistent naming
+//! does not spread one topic over several tags. Aliases point at the tag
+//! itself rather than its name, so they survive renames.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authorization::{Action, OwnerPolicy, Resource};
+use crate::entities::Tag;
+use crate::errors::{DomainError, DomainResult};
+use crate::event_log::{EventDispatcher, LoggedEventKind};
+use crate::ports::AuthorizationPolicy;
+use crate::repositories::{TagAliasRepository, TagRepository};
+use crate::value_objects::TagName;
+
+/// A name that resolves to one of the user's tags
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagAlias {
+    pub user_id: Uuid,
+    pub alias: TagName,
+    /// The canonical tag
+    pub tag_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TagAlias {
+    pub fn new(user_id: Uuid, alias: TagName, tag_id: Uuid) -> Self {
+        Self {
+            user_id,
+            alias,
+            tag_id,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Check the alias can point at `tag`
+    pub fn validate(&self, tag: &Tag) -> DomainResult<()> {
+        if tag.id != self.tag_id || tag.user_id != self.user_id {
+            return Err(DomainError::validation(
+                "An alias must point at one of the user's tags",
+            ));
+        }
+        if tag.name == self.alias {
+            return Err(DomainError::validation(
+                "A tag cannot be an alias of itself",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Service for defining tag aliases and merging tags into them
+pub struct TagAliasService {
+    alias_repo: Arc<dyn TagAliasRepository>,
+    tag_repo: Arc<dyn TagRepository>,
+    events: Option<Arc<EventDispatcher>>,
+    policy: Arc<dyn AuthorizationPolicy>,
+}
+
+impl TagAliasService {
+    pub fn new(alias_repo: Arc<dyn TagAliasRepository>, tag_repo: Arc<dyn TagRepository>) -> Self {
+        Self {
+            alias_repo,
+            tag_repo,
+            events: None,
+            policy: Arc::new(OwnerPolicy),
+        }
+    }
+
+    /// Decide who may add aliases to a tag; its owner only by default
+    pub fn with_authorization_policy(mut self, policy: Arc<dyn AuthorizationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Builder method to set the event dispatcher that merged tags are
+    /// recorded with
+    pub fn with_event_dispatcher(mut self, events: Arc<EventDispatcher>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Make `alias` resolve to the tag `tag_id`
+    ///
+    /// If the user already has a tag named `alias`, it is merged into the
+    /// target: its notes get the target tag and the tag itself is deleted.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        tag_id: Uuid,
+        alias: TagName,
+    ) -> DomainResult<TagAlias> {
+        let tag = self
+            .tag_repo
+            .find_by_id(tag_id)
+            .await?
+            .ok_or(DomainError::TagNotFound(tag_id))?;
+        self.policy
+            .authorize(user_id, Action::Update, &Resource::tag(&tag))
+            .await?;
+
+        let alias = TagAlias::new(user_id, alias, tag_id);
+        alias.validate(&tag)?;
+
+        if let Some(merged) = self
+            .tag_repo
+            .find_by_name(user_id, alias.alias.as_ref())
+            .await?
+        {
+            self.tag_repo.merge(merged.id, tag_id).await?;
+            if let Some(ref events) = self.events {
+                let kind = LoggedEventKind::TagDeleted {
+                    tag_id: merged.id,
+                    name: merged.name.into_inner(),
+                };
+                events.dispatch(user_id, vec![kind]).await;
+            }
+        }
+
+        self.alias_repo.save(&alias).await?;
+        Ok(alias)
+    }
+
+    /// The user's aliases, ordered by alias
+    pub async fn list(&self, user_id: Uuid) -> DomainResult<Vec<TagAlias>> {
+        self.alias_repo.find_by_user(user_id).await
+    }
+
+    /// Stop `alias` from resolving; the tag it pointed at is kept
+    pub async fn delete(&self, user_id: Uuid, alias: &TagName) -> DomainResult<()> {
+        if self
+            .alias_repo
+            .find_by_alias(user_id, alias.as_ref())
+            .await?
+            .is_none()
+        {
+            return Err(DomainError::TagAliasNotFound(alias.as_ref().to_string()));
+        }
+        self.alias_repo.delete(user_id, alias.as_ref()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(s: &str) -> TagName {
+        TagName::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn test_validate() {
+        let user_id = Uuid::new_v4();
+        let tag = Tag::new(name("javascript"), user_id);
+
+        assert!(
+            TagAlias::new(user_id, name("JS"), tag.id)
+                .validate(&tag)
+                .is_ok()
+        );
+        assert!(
+            TagAlias::new(user_id, name("javascript"), tag.id)
+                .validate(&tag)
+                .is_err()
+        );
+        assert!(
+            TagAlias::new(Uuid::new_v4(), name("js"), tag.id)
+                .validate(&tag)
+                .is_err()
+        );
+    }
+
+    mod tag_alias_service_tests {
+        use super::*;
+        use crate::repositories::tests::MockNoteRepository;
+        use crate::services::tests::*;
+        use crate::services::{CreateNoteRequest, NoteService};
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockTagAliasRepository {
+            aliases: Mutex<Vec<TagAlias>>,
+        }
+
+        #[async_trait::async_trait]
+        impl TagAliasRepository for MockTagAliasRepository {
+            async fn save(&self, alias: &TagAlias) -> DomainResult<()> {
+                let mut aliases = self.aliases.lock().unwrap();
+                aliases.retain(|a| a.user_id != alias.user_id || a.alias != alias.alias);
+                aliases.push(alias.clone());
+                Ok(())
+            }
+
+            async fn find_by_alias(
+                &self,
+                user_id: Uuid,
+                alias: &str,
+            ) -> DomainResult<Option<TagAlias>> {
+                let aliases = self.aliases.lock().unwrap();
+                Ok(aliases
+                    .iter()
+                    .find(|a| a.user_id == user_id && a.alias.as_ref() == alias)
+                    .cloned())
+            }
+
+            async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<TagAlias>> {
+                let aliases = self.aliases.lock().unwrap();
+                Ok(aliases
+                    .iter()
+                    .filter(|a| a.user_id == user_id)
+                    .cloned()
+                    .collect())
+            }
+
+            async fn delete(&self, user_id: Uuid, alias: &str) -> DomainResult<()> {
+                self.aliases
+                    .lock()
+                    .unwrap()
+                    .retain(|a| a.user_id != user_id || a.alias.as_ref() != alias);
+                Ok(())
+            }
+        }
+
+        fn name(s: &str) -> TagName {
+            TagName::try_from(s).unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_aliases_resolve_to_the_canonical_tag() {
+            let tag_repo = Arc::new(MockTagRepository::new());
+            let alias_repo = Arc::new(MockTagAliasRepository::default());
+            let aliases = TagAliasService::new(alias_repo.clone(), tag_repo.clone());
+            let notes = NoteService::new(Arc::new(MockNoteRepository::new()), tag_repo.clone())
+                .with_tag_aliases(alias_repo);
+            let user_id = Uuid::new_v4();
+            let javascript = Tag::new(name("javascript"), user_id);
+            tag_repo.save(&javascript).await.unwrap();
+
+            aliases
+                .create(user_id, javascript.id, name("js"))
+                .await
+                .unwrap();
+            let note = notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: "closures".to_string(),
+                    tags: vec![name("js"), name("javascript"), name("web")],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+
+            let tags: Vec<&str> = note.tags.iter().map(|t| t.name_str()).collect();
+            assert_eq!(tags, vec!["javascript", "web"]);
+            assert!(
+                aliases
+                    .create(user_id, javascript.id, name("javascript"))
+                    .await
+                    .is_err()
+            );
+            assert!(
+                aliases
+                    .create(Uuid::new_v4(), javascript.id, name("ecmascript"))
+                    .await
+                    .is_err()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_alias_merges_an_existing_tag() {
+            let tag_repo = Arc::new(MockTagRepository::new());
+            let aliases = TagAliasService::new(
+                Arc::new(MockTagAliasRepository::default()),
+                tag_repo.clone(),
+            );
+            let user_id = Uuid::new_v4();
+            let note_id = Uuid::new_v4();
+            let javascript = Tag::new(name("javascript"), user_id);
+            let js = Tag::new(name("js"), user_id);
+            tag_repo.save(&javascript).await.unwrap();
+            tag_repo.save(&js).await.unwrap();
+            tag_repo.add_to_note(js.id, note_id).await.unwrap();
+
+            aliases
+                .create(user_id, javascript.id, name("js"))
+                .await
+                .unwrap();
+
+            assert_eq!(tag_repo.find_by_id(js.id).await.unwrap(), None);
+            assert_eq!(
+                tag_repo.find_by_note(note_id).await.unwrap(),
+                vec![javascript]
+            );
+            assert_eq!(aliases.list(user_id).await.unwrap().len(), 1);
+
+            aliases.delete(user_id, &name("js")).await.unwrap();
+            assert!(matches!(
+                aliases.delete(user_id, &name("js")).await,
+                Err(DomainError::TagAliasNotFound(_))
+            ));
+        }
+    }
+}
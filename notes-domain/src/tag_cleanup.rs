@@ -0,0 +1,65 @@
+//! Cleanup of unused tags
+//!
+//! Deleting notes leaves behind tags that no note uses any more. A scheduled
+//! job applies the instance's policy to them: flagging only reports them, so
+//! users can review the list first, while deleting removes them together
+//! with their aliases. Tags of trashed notes still count as used, so a
+//! restored note gets all its tags back.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::DomainError;
+
+/// What happens to tags that no note uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnusedTagPolicy {
+    /// Report unused tags and leave them in place
+    #[default]
+    Flag,
+    /// Delete unused tags
+    Delete,
+}
+
+impl UnusedTagPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Flag => "flag",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+impl fmt::Display for UnusedTagPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UnusedTagPolicy {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flag" => Ok(Self::Flag),
+            "delete" => Ok(Self::Delete),
+            other => Err(DomainError::validation(format!(
+                "Unknown unused tag policy: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_round_trips_through_str() {
+        for policy in [UnusedTagPolicy::Flag, UnusedTagPolicy::Delete] {
+            assert_eq!(policy.as_str().parse::<UnusedTagPolicy>().unwrap(), policy);
+        }
+        assert!("archive".parse::<UnusedTagPolicy>().is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Titles generated for untitled notes
+//!
+//! Users can opt in to having their untitled notes named after what they
+//! say. A [`TextGenerator`](crate::ports::TextGenerator) suggests the title
+//! when the instance has one; without it, or when its answer is unusable,
+//! the note's first heading or first line becomes the title.
+
+use crate::value_objects::NoteTitle;
+
+/// Longest generated title, in characters
+pub const GENERATED_TITLE_LENGTH: usize = 60;
+
+/// Notes edited more recently than this are titled on a later run, once
+/// their content has settled
+pub const AUTO_TITLE_QUIET_MINUTES: i64 = 2;
+
+/// Content sent to the text generator, in characters
+const PROMPT_CONTENT_LENGTH: usize = 2000;
+
+/// List, quote and task markers taken off the start of a line
+const LINE_MARKERS: &[&str] = &["- ", "* ", "+ ", "> ", "[ ] ", "[x] ", "[X] "];
+
+/// The prompt asking a text generator to title `content`
+pub fn title_prompt(content: &str) -> String {
+    let content: String = content.chars().take(PROMPT_CONTENT_LENGTH).collect();
+    format!(
+        "Write a short title of at most {} characters for the note below, in the \
+         language of the note. Answer with the title only.\n\n{}",
+        GENERATED_TITLE_LENGTH, content
+    )
+}
+
+/// The title in a text generator's answer, without the quotes and labels
+/// models like to add
+pub fn generated_title(answer: &str) -> Option<NoteTitle> {
+    let line = answer.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = ["Title:", "title:"]
+        .iter()
+        .find_map(|label| line.strip_prefix(label))
+        .unwrap_or(line);
+    let line = line
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '*' | '`' | '“' | '”'))
+        .trim_start_matches('#')
+        .trim_end_matches('.');
+    shorten(line)
+}
+
+/// The first Markdown heading of `content`, or else its first line
+pub fn heuristic_title(content: &str) -> Option<NoteTitle> {
+    let lines = || content.lines().map(str::trim).filter(|l| !l.is_empty());
+    // `#tag` is a tag, not a heading
+    let heading = lines().find_map(|l| {
+        let text = l.trim_start_matches('#');
+        (text.len() < l.len() && text.starts_with(' ')).then_some(text)
+    });
+    let mut line = heading.or_else(|| lines().next())?.trim();
+    while let Some(rest) = LINE_MARKERS.iter().find_map(|m| line.strip_prefix(m)) {
+        line = rest.trim_start();
+    }
+    shorten(line)
+}
+
+/// `text` as a title of at most [`GENERATED_TITLE_LENGTH`] characters,
+/// ending on a whole word where it can
+fn shorten(text: &str) -> Option<NoteTitle> {
+    let text = text.trim();
+    let title = if text.chars().count() <= GENERATED_TITLE_LENGTH {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(GENERATED_TITLE_LENGTH).collect();
+        match cut.rfind(char::is_whitespace) {
+            Some(end) if end > 0 => cut[..end].trim_end().to_string(),
+            _ => cut,
+        }
+    };
+    NoteTitle::from_optional(Some(title)).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn title(t: Option<NoteTitle>) -> Option<String> {
+        t.map(|t| t.as_ref().to_string())
+    }
+
+    #[test]
+    fn test_heuristic_prefers_the_first_heading() {
+        let content = "#work notes from today\n\n## Quarterly planning\n- budget";
+        assert_eq!(
+            title(heuristic_title(content)),
+            Some("Quarterly planning".to_string())
+        );
+        assert_eq!(
+            title(heuristic_title("\n  - [ ] buy milk\n- eggs")),
+            Some("buy milk".to_string())
+        );
+        assert_eq!(heuristic_title("  \n\n"), None);
+    }
+
+    #[test]
+    fn test_long_titles_end_on_a_whole_word() {
+        let content = "This first line goes on and on about many things until it is far too long";
+        let shortened = title(heuristic_title(content)).unwrap();
+        assert!(shortened.chars().count() <= GENERATED_TITLE_LENGTH);
+        assert!(content.starts_with(&shortened));
+        assert!(content[shortened.len()..].starts_with(' '));
+    }
+
+    #[test]
+    fn test_generated_title_drops_labels_and_quotes() {
+        assert_eq!(
+            title(generated_title(
+                "Title: \"Trip to Kraków.\"\nSomething else"
+            )),
+            Some("Trip to Kraków".to_string())
+        );
+        assert_eq!(
+            title(generated_title("\n**Meeting notes**")),
+            Some("Meeting notes".to_string())
+        );
+        assert_eq!(generated_title("  \n"), None);
+    }
+}
@@ -0,0 +1,418 @@
+//! Voice memos
+//!
+//! Audio uploaded to a note is kept until the worker transcribes it with a
+//! [`Transcriber`]. The transcript is appended to
+//! the note's content, so it is searched and embedded like typed text, and
+//! the audio is dropped afterwards.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::authorization::{Action, Resource};
+use crate::entities::Note;
+use crate::errors::{DomainError, DomainResult};
+use crate::ports::Transcriber;
+use crate::repositories::AudioUploadRepository;
+use crate::services::{NoteService, UpdateNoteRequest};
+
+/// Largest audio upload, in bytes; hosted transcription APIs take no more
+pub const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+/// Attempts at transcribing an upload before it is given up on
+pub const MAX_TRANSCRIPTION_ATTEMPTS: u32 = 3;
+
+/// Content types of the audio notes accept, as recorded by browsers and phones
+const AUDIO_CONTENT_TYPES: &[&str] = &[
+    "audio/flac",
+    "audio/mp4",
+    "audio/mpeg",
+    "audio/ogg",
+    "audio/wav",
+    "audio/webm",
+    "audio/x-m4a",
+    "audio/x-wav",
+];
+
+/// Audio waiting to be transcribed into a note
+#[derive(Debug, Clone)]
+pub struct AudioUpload {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    /// Who uploaded the audio; the transcript is appended on their behalf
+    pub user_id: Uuid,
+    pub content_type: String,
+    pub audio: Vec<u8>,
+    /// Failed transcriptions so far
+    pub attempts: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AudioUpload {
+    pub fn new(note_id: Uuid, user_id: Uuid, content_type: String, audio: Vec<u8>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            note_id,
+            user_id,
+            content_type,
+            audio,
+            attempts: 0,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// `content_type` without its parameters, if notes accept it.
+/// Recorders often add a codec, as in `audio/webm;codecs=opus`.
+pub fn audio_content_type(content_type: &str) -> Option<&'static str> {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    AUDIO_CONTENT_TYPES
+        .iter()
+        .find(|accepted| accepted.eq_ignore_ascii_case(essence))
+        .copied()
+}
+
+/// `content` with `transcript` appended as a paragraph of its own
+pub fn append_transcript(content: &str, transcript: &str) -> String {
+    let content = content.trim_end();
+    if content.is_empty() {
+        transcript.to_string()
+    } else {
+        format!("{}\n\n{}", content, transcript)
+    }
+}
+
+/// Service keeping voice memos until they are transcribed into their notes
+pub struct TranscriptionService {
+    notes: Arc<NoteService>,
+    uploads: Arc<dyn AudioUploadRepository>,
+    transcriber: Option<Arc<dyn Transcriber>>,
+}
+
+impl TranscriptionService {
+    pub fn new(notes: Arc<NoteService>, uploads: Arc<dyn AudioUploadRepository>) -> Self {
+        Self {
+            notes,
+            uploads,
+            transcriber: None,
+        }
+    }
+
+    /// Builder method to transcribe pending uploads, as the worker does
+    pub fn with_transcriber(mut self, transcriber: Arc<dyn Transcriber>) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
+
+    /// Keep audio recorded for a note until it is transcribed into it
+    pub async fn upload(
+        &self,
+        note_id: Uuid,
+        user_id: Uuid,
+        content_type: &str,
+        audio: Vec<u8>,
+    ) -> DomainResult<AudioUpload> {
+        let Some(content_type) = audio_content_type(content_type) else {
+            return Err(DomainError::validation(format!(
+                "Unsupported audio type {:?}",
+                content_type
+            )));
+        };
+        if audio.is_empty() {
+            return Err(DomainError::validation("The audio is empty"));
+        }
+        if audio.len() > MAX_AUDIO_BYTES {
+            return Err(DomainError::validation(format!(
+                "Audio must be at most {} MiB",
+                MAX_AUDIO_BYTES / (1024 * 1024)
+            )));
+        }
+
+        // The transcript is an edit, so the same rules apply now
+        let note = self.notes.get_note(note_id, user_id).await?;
+        self.notes
+            .policy
+            .authorize(user_id, Action::Update, &Resource::note(&note))
+            .await?;
+        if note.is_trashed() {
+            return Err(DomainError::validation(
+                "Restore the note from the trash before editing it",
+            ));
+        }
+        if note.is_locked {
+            return Err(DomainError::NoteLocked(note.id));
+        }
+
+        let upload = AudioUpload::new(note.id, user_id, content_type.to_string(), audio);
+        self.uploads.save(&upload).await?;
+        Ok(upload)
+    }
+
+    /// Transcribe up to `limit` pending uploads into their notes, returning
+    /// the notes that changed.
+    ///
+    /// Uploads that fail are retried by later runs, and dropped after
+    /// [`MAX_TRANSCRIPTION_ATTEMPTS`]. Does nothing without a transcriber.
+    pub async fn transcribe_pending(&self, limit: usize) -> DomainResult<Vec<Note>> {
+        let Some(ref transcriber) = self.transcriber else {
+            return Ok(Vec::new());
+        };
+
+        let mut notes = Vec::new();
+        for upload in self.uploads.find_pending(limit).await? {
+            match self.transcribe(transcriber.as_ref(), &upload).await {
+                Ok(note) => {
+                    self.uploads.delete(upload.id).await?;
+                    notes.extend(note);
+                }
+                Err(e) if upload.attempts + 1 >= MAX_TRANSCRIPTION_ATTEMPTS => {
+                    tracing::warn!(
+                        upload_id = %upload.id,
+                        note_id = %upload.note_id,
+                        "Giving up on transcribing audio: {}",
+                        e
+                    );
+                    self.uploads.delete(upload.id).await?;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        upload_id = %upload.id,
+                        note_id = %upload.note_id,
+                        "Failed to transcribe audio, retrying later: {}",
+                        e
+                    );
+                    self.uploads.record_failure(upload.id).await?;
+                }
+            }
+        }
+
+        Ok(notes)
+    }
+
+    /// The note with the upload's transcript appended, or `None` if the
+    /// audio has no speech
+    async fn transcribe(
+        &self,
+        transcriber: &dyn Transcriber,
+        upload: &AudioUpload,
+    ) -> DomainResult<Option<Note>> {
+        let note = self.notes.get_note(upload.note_id, upload.user_id).await?;
+        let transcript = transcriber
+            .transcribe(&upload.audio, &upload.content_type, note.language.as_ref())
+            .await?;
+        let transcript = transcript.trim();
+        if transcript.is_empty() {
+            return Ok(None);
+        }
+
+        // Transcribing takes a while; edits made meanwhile are kept
+        let note = self.notes.get_note(upload.note_id, upload.user_id).await?;
+        self.notes
+            .update_note(UpdateNoteRequest {
+                id: note.id,
+                user_id: upload.user_id,
+                title: None,
+                content: Some(append_transcript(&note.content, transcript)),
+                is_pinned: None,
+                is_archived: None,
+                color: None,
+                tags: None,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            })
+            .await
+            .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_content_type_ignores_parameters_and_case() {
+        assert_eq!(
+            audio_content_type("audio/webm;codecs=opus"),
+            Some("audio/webm")
+        );
+        assert_eq!(audio_content_type("Audio/MPEG"), Some("audio/mpeg"));
+        assert_eq!(audio_content_type("video/webm"), None);
+        assert_eq!(audio_content_type(""), None);
+    }
+
+    #[test]
+    fn test_append_transcript_starts_a_paragraph() {
+        assert_eq!(
+            append_transcript("Shopping\n", "Buy milk."),
+            "Shopping\n\nBuy milk."
+        );
+        assert_eq!(append_transcript("  \n", "Buy milk."), "Buy milk.");
+    }
+
+    mod transcription_service_tests {
+        use super::*;
+        use crate::language::Language;
+        use crate::repositories::tests::MockNoteRepository;
+        use crate::services::CreateNoteRequest;
+        use crate::services::tests::*;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MockAudioUploadRepository {
+            uploads: Mutex<Vec<AudioUpload>>,
+        }
+
+        #[async_trait::async_trait]
+        impl AudioUploadRepository for MockAudioUploadRepository {
+            async fn save(&self, upload: &AudioUpload) -> DomainResult<()> {
+                self.uploads.lock().unwrap().push(upload.clone());
+                Ok(())
+            }
+
+            async fn find_pending(&self, limit: usize) -> DomainResult<Vec<AudioUpload>> {
+                Ok(self
+                    .uploads
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .take(limit)
+                    .cloned()
+                    .collect())
+            }
+
+            async fn record_failure(&self, id: Uuid) -> DomainResult<()> {
+                let mut uploads = self.uploads.lock().unwrap();
+                if let Some(upload) = uploads.iter_mut().find(|u| u.id == id) {
+                    upload.attempts += 1;
+                }
+                Ok(())
+            }
+
+            async fn delete(&self, id: Uuid) -> DomainResult<()> {
+                self.uploads.lock().unwrap().retain(|u| u.id != id);
+                Ok(())
+            }
+        }
+
+        /// Hears the audio bytes as text, or fails on anything but UTF-8
+        struct EchoTranscriber;
+
+        #[async_trait::async_trait]
+        impl Transcriber for EchoTranscriber {
+            async fn transcribe(
+                &self,
+                audio: &[u8],
+                _content_type: &str,
+                _language: Option<&Language>,
+            ) -> DomainResult<String> {
+                String::from_utf8(audio.to_vec())
+                    .map_err(|_| DomainError::InfrastructureError("Unreadable audio".to_string()))
+            }
+        }
+
+        fn setup() -> (
+            Arc<NoteService>,
+            Arc<MockAudioUploadRepository>,
+            TranscriptionService,
+        ) {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let uploads = Arc::new(MockAudioUploadRepository::default());
+            let service = TranscriptionService::new(notes.clone(), uploads.clone())
+                .with_transcriber(Arc::new(EchoTranscriber));
+            (notes, uploads, service)
+        }
+
+        async fn create_note(notes: &NoteService, user_id: Uuid, content: &str) -> Note {
+            notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: content.to_string(),
+                    tags: vec![],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_transcript_is_appended_and_audio_dropped() {
+            let (notes, uploads, service) = setup();
+            let user_id = Uuid::new_v4();
+            let note = create_note(&notes, user_id, "Shopping\n").await;
+
+            service
+                .upload(
+                    note.id,
+                    user_id,
+                    "audio/webm;codecs=opus",
+                    b" Buy milk. ".to_vec(),
+                )
+                .await
+                .unwrap();
+            let transcribed = service.transcribe_pending(10).await.unwrap();
+
+            assert_eq!(transcribed.len(), 1);
+            assert_eq!(transcribed[0].content, "Shopping\n\nBuy milk.");
+            assert_eq!(
+                notes.get_note(note.id, user_id).await.unwrap().content,
+                "Shopping\n\nBuy milk."
+            );
+            assert!(uploads.uploads.lock().unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_failing_uploads_are_dropped_after_the_last_attempt() {
+            let (notes, uploads, service) = setup();
+            let user_id = Uuid::new_v4();
+            let note = create_note(&notes, user_id, "Shopping").await;
+            service
+                .upload(note.id, user_id, "audio/mpeg", vec![0xff, 0xfe])
+                .await
+                .unwrap();
+
+            for attempt in 1..MAX_TRANSCRIPTION_ATTEMPTS {
+                assert!(service.transcribe_pending(10).await.unwrap().is_empty());
+                assert_eq!(uploads.uploads.lock().unwrap()[0].attempts, attempt);
+            }
+            service.transcribe_pending(10).await.unwrap();
+
+            assert!(uploads.uploads.lock().unwrap().is_empty());
+            assert_eq!(
+                notes.get_note(note.id, user_id).await.unwrap().content,
+                "Shopping"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_uploads_are_checked_before_being_kept() {
+            let (notes, uploads, service) = setup();
+            let user_id = Uuid::new_v4();
+            let note = create_note(&notes, user_id, "Shopping").await;
+
+            let video = service
+                .upload(note.id, user_id, "video/mp4", b"audio".to_vec())
+                .await;
+            assert!(matches!(video, Err(DomainError::ValidationError(_))));
+            let empty = service
+                .upload(note.id, user_id, "audio/ogg", Vec::new())
+                .await;
+            assert!(matches!(empty, Err(DomainError::ValidationError(_))));
+            let other_user = service
+                .upload(note.id, Uuid::new_v4(), "audio/ogg", b"audio".to_vec())
+                .await;
+            assert!(other_user.is_err());
+
+            assert!(uploads.uploads.lock().unwrap().is_empty());
+        }
+    }
+}
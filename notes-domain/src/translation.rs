@@ -0,0 +1,322 @@
+//! Translations of notes
+//!
+//! A [`TextGenerator`] translates a note into
+//! another language. The translation is saved as a child of the original
+//! (a `parent_of` relation), so the two stay connected; translating the
+//! note into the same language again updates that child instead of adding
+//! another one.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::entities::Note;
+use crate::errors::{DomainError, DomainResult};
+use crate::language::Language;
+use crate::ports::TextGenerator;
+use crate::relations::{NoteRelationService, RelationKind};
+use crate::services::{CreateNoteRequest, NoteService, UpdateNoteRequest};
+use crate::value_objects::NoteTitle;
+
+/// Longest note content translated, in characters
+pub const MAX_TRANSLATION_LENGTH: usize = 12_000;
+
+/// A note's translation, and whether it was created or an earlier one updated
+#[derive(Debug, Clone)]
+pub struct Translation {
+    pub note: Note,
+    pub created: bool,
+}
+
+/// The prompt asking a text generator to translate `text` into `to`
+pub fn translation_prompt(text: &str, to: &Language) -> String {
+    format!(
+        "Translate the text below into {}. Keep its Markdown formatting, links, \
+         #tags and [[wiki-links]] as they are. Answer with the translation only.\n\n{}",
+        to.name(),
+        text
+    )
+}
+
+/// The translation in a text generator's answer, without the code fence
+/// models sometimes wrap it in
+pub fn translated_text(answer: &str) -> Option<String> {
+    let answer = answer.trim();
+    let unfenced = answer
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        // The fence may name a language, like ```markdown
+        .map(|inner| inner.split_once('\n').map_or(inner, |(_, body)| body));
+    let text = unfenced.unwrap_or(answer).trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Service translating notes with a text generator
+pub struct TranslationService {
+    notes: Arc<NoteService>,
+    relations: Arc<NoteRelationService>,
+    text_generator: Arc<dyn TextGenerator>,
+}
+
+impl TranslationService {
+    pub fn new(
+        notes: Arc<NoteService>,
+        relations: Arc<NoteRelationService>,
+        text_generator: Arc<dyn TextGenerator>,
+    ) -> Self {
+        Self {
+            notes,
+            relations,
+            text_generator,
+        }
+    }
+
+    /// Translate a note into `to`, as a child note of the original.
+    ///
+    /// A child already written in `to` is updated with the new translation,
+    /// so translating an edited note again keeps one translation per language.
+    pub async fn translate(
+        &self,
+        note_id: Uuid,
+        user_id: Uuid,
+        to: Language,
+    ) -> DomainResult<Translation> {
+        let note = self.notes.get_note(note_id, user_id).await?;
+        if note.is_trashed() {
+            return Err(DomainError::validation(
+                "Notes in the trash cannot be translated",
+            ));
+        }
+        if note.content.trim().is_empty() {
+            return Err(DomainError::validation("The note has nothing to translate"));
+        }
+        if note.language.as_ref() == Some(&to) {
+            return Err(DomainError::validation(format!(
+                "The note is already written in {}",
+                to.name()
+            )));
+        }
+        if note.content.chars().count() > MAX_TRANSLATION_LENGTH {
+            return Err(DomainError::validation(format!(
+                "Notes longer than {} characters cannot be translated",
+                MAX_TRANSLATION_LENGTH
+            )));
+        }
+
+        let content = self
+            .translate_text(&note.content, &to)
+            .await?
+            .ok_or_else(|| {
+                DomainError::InfrastructureError(
+                    "Text generator returned no translation".to_string(),
+                )
+            })?;
+        // A title the generator cannot translate tidily stays as it was
+        let title = match note.title {
+            Some(ref title) => self
+                .translate_text(title.as_ref(), &to)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|t| NoteTitle::from_optional(Some(t)).ok().flatten())
+                .or_else(|| Some(title.clone())),
+            None => None,
+        };
+
+        if let Some(existing) = self.find_translation(&note, user_id, &to).await? {
+            let updated = self
+                .notes
+                .update_note(UpdateNoteRequest {
+                    id: existing.id,
+                    user_id,
+                    title: Some(title),
+                    content: Some(content),
+                    is_pinned: None,
+                    is_archived: None,
+                    color: None,
+                    tags: None,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await?;
+            return Ok(Translation {
+                note: updated,
+                created: false,
+            });
+        }
+
+        let translation = self
+            .notes
+            .create_note(CreateNoteRequest {
+                user_id,
+                title,
+                content,
+                tags: note.tags.iter().map(|t| t.name.clone()).collect(),
+                color: Some(note.color.clone()),
+                is_pinned: false,
+                location: note.location,
+                place_name: note.place_name.clone(),
+                remind_at: None,
+            })
+            .await?;
+        self.relations
+            .create(user_id, note.id, RelationKind::ParentOf, translation.id)
+            .await?;
+        Ok(Translation {
+            note: translation,
+            created: true,
+        })
+    }
+
+    async fn translate_text(&self, text: &str, to: &Language) -> DomainResult<Option<String>> {
+        let answer = self
+            .text_generator
+            .generate(&translation_prompt(text, to))
+            .await?;
+        Ok(translated_text(&answer))
+    }
+
+    /// The live child of `note` written in `language`
+    async fn find_translation(
+        &self,
+        note: &Note,
+        user_id: Uuid,
+        language: &Language,
+    ) -> DomainResult<Option<Note>> {
+        let relations = self.relations.list_for_note(note.id, user_id).await?;
+        for relation in relations
+            .iter()
+            .filter(|r| r.kind == RelationKind::ParentOf && r.source_id == note.id)
+        {
+            match self.notes.get_note(relation.target_id, user_id).await {
+                Ok(child) if !child.is_trashed() && child.language.as_ref() == Some(language) => {
+                    return Ok(Some(child));
+                }
+                Ok(_) => {}
+                Err(e) if e.is_not_found() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_names_the_target_language() {
+        let prompt = translation_prompt("Good morning", &Language::new("pl").unwrap());
+        assert!(prompt.contains("into Polish"));
+        assert!(prompt.ends_with("\n\nGood morning"));
+    }
+
+    #[test]
+    fn test_translated_text_drops_code_fences() {
+        assert_eq!(
+            translated_text("```markdown\n# Dzień dobry\n- kawa\n```"),
+            Some("# Dzień dobry\n- kawa".to_string())
+        );
+        assert_eq!(
+            translated_text("  Dzień dobry \n"),
+            Some("Dzień dobry".to_string())
+        );
+        assert_eq!(translated_text("```\n```"), None);
+    }
+
+    mod translation_service_tests {
+        use super::*;
+        use crate::relations::tests::relation_service_tests::MockNoteRelationRepository;
+        use crate::repositories::tests::MockNoteRepository;
+        use crate::services::tests::*;
+        use crate::value_objects::TagName;
+
+        const ENGLISH: &str = "Tomorrow morning we are going for a walk in the park before lunch.";
+        const POLISH: &str = "Jutro rano idziemy na spacer do parku, a potem zjemy obiad u babci. Wieczorem czytamy książki i pijemy herbatę z cytryną.";
+
+        /// Translates the title `Walk` and answers everything else with
+        /// the Polish text, fenced
+        struct PolishTextGenerator;
+
+        #[async_trait::async_trait]
+        impl TextGenerator for PolishTextGenerator {
+            async fn generate(&self, prompt: &str) -> DomainResult<String> {
+                if prompt.ends_with("\n\nWalk") {
+                    return Ok("Spacer".to_string());
+                }
+                Ok(format!("```markdown\n{}\n```", POLISH))
+            }
+        }
+
+        #[tokio::test]
+        async fn test_translations_are_child_notes_updated_in_place() {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let relations = Arc::new(NoteRelationService::new(
+                Arc::new(MockNoteRelationRepository::default()),
+                notes.clone(),
+            ));
+            let service = TranslationService::new(
+                notes.clone(),
+                relations.clone(),
+                Arc::new(PolishTextGenerator),
+            );
+            let user_id = Uuid::new_v4();
+            let original = notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: NoteTitle::try_from("Walk").ok(),
+                    content: ENGLISH.to_string(),
+                    tags: vec![TagName::try_from("plans").unwrap()],
+                    color: Some("BLUE".to_string()),
+                    is_pinned: true,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+            let polish = Language::new("pl").unwrap();
+
+            let first = service
+                .translate(original.id, user_id, polish.clone())
+                .await
+                .unwrap();
+            assert!(first.created);
+            assert_eq!(first.note.content, POLISH);
+            assert_eq!(first.note.title_str(), "Spacer");
+            assert_eq!(first.note.language.as_ref(), Some(&polish));
+            assert_eq!(first.note.color, "BLUE");
+            assert!(!first.note.is_pinned);
+            assert_eq!(first.note.tags.len(), 1);
+            let linked = relations.list_for_note(original.id, user_id).await.unwrap();
+            assert_eq!(linked.len(), 1);
+            assert_eq!(linked[0].kind, RelationKind::ParentOf);
+            assert_eq!(linked[0].target_id, first.note.id);
+
+            let again = service
+                .translate(original.id, user_id, polish.clone())
+                .await
+                .unwrap();
+            assert!(!again.created);
+            assert_eq!(again.note.id, first.note.id);
+
+            assert!(
+                service
+                    .translate(original.id, user_id, Language::new("en").unwrap())
+                    .await
+                    .is_err()
+            );
+            assert!(
+                service
+                    .translate(original.id, Uuid::new_v4(), polish)
+                    .await
+                    .is_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Trash retention and storage reclamation
+//!
+//! Deleted notes are moved to the trash first and only purged for good once
+//! they have been there longer than the retention window.
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::entities::Note;
+
+/// Default number of days a trashed note is kept before it is purged
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Purges notes that have been in the trash for longer than `days`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrashRetention {
+    pub days: u32,
+}
+
+impl TrashRetention {
+    pub fn new(days: u32) -> Self {
+        Self { days }
+    }
+
+    /// Notes trashed before this instant are due for purging
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(i64::from(self.days))
+    }
+
+    /// Whether the note has expired from the trash at `now`
+    pub fn is_expired(&self, note: &Note, now: DateTime<Utc>) -> bool {
+        note.deleted_at
+            .is_some_and(|deleted_at| deleted_at < self.cutoff(now))
+    }
+}
+
+impl Default for TrashRetention {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRASH_RETENTION_DAYS)
+    }
+}
+
+/// Outcome of a single trash purge
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrashPurgeReport {
+    /// Notes that were permanently deleted
+    pub note_ids: Vec<Uuid>,
+    /// Approximate bytes of note and version content freed
+    pub reclaimed_bytes: u64,
+}
+
+impl TrashPurgeReport {
+    pub fn is_empty(&self) -> bool {
+        self.note_ids.is_empty()
+    }
+}
+
+/// Running totals of storage reclaimed by trash purges
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    pub purged_notes: u64,
+    pub reclaimed_bytes: u64,
+    pub last_purged_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_trashed_days_ago(days: i64) -> Note {
+        let mut note = Note::new(Uuid::new_v4(), None, "content");
+        note.deleted_at = Some(Utc::now() - Duration::days(days));
+        note
+    }
+
+    #[test]
+    fn test_only_notes_past_retention_expire() {
+        let retention = TrashRetention::new(30);
+        let now = Utc::now();
+
+        assert!(retention.is_expired(&note_trashed_days_ago(31), now));
+        assert!(!retention.is_expired(&note_trashed_days_ago(29), now));
+    }
+
+    #[test]
+    fn test_notes_outside_trash_never_expire() {
+        let retention = TrashRetention::new(0);
+        let note = Note::new(Uuid::new_v4(), None, "content");
+
+        assert!(!retention.is_expired(&note, Utc::now()));
+    }
+}
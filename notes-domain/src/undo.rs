@@ -0,0 +1,317 @@
+//! Undo and redo of note changes
+//!
+//! Changes are read back from the event log; see
+//! [`UndoService`] for how far back they go.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::entities::Note;
+use crate::errors::{DomainError, DomainResult};
+use crate::event_log::{
+    EventDispatcher, LoggedEvent, LoggedEventKind, MAX_ACTIVITY_LIMIT, UNDO_GROUP_SECONDS,
+    UNDO_WINDOW_MINUTES,
+};
+use crate::repositories::EventLogRepository;
+use crate::services::{NoteService, UpdateNoteRequest};
+use crate::value_objects::TagName;
+
+/// Service undoing the latest changes recorded in users' event logs
+///
+/// Only one step is kept: undo reverts the changes made since the last undo,
+/// and redo reapplies what the last undo reverted if nothing happened since.
+pub struct UndoService {
+    notes: Arc<NoteService>,
+    event_log: Arc<dyn EventLogRepository>,
+    events: Option<Arc<EventDispatcher>>,
+}
+
+/// How an undo or redo changes one note
+#[derive(Default)]
+struct NoteReversal {
+    trash: bool,
+    archive: bool,
+    add_tags: Vec<String>,
+    remove_tags: Vec<String>,
+}
+
+impl UndoService {
+    pub fn new(notes: Arc<NoteService>, event_log: Arc<dyn EventLogRepository>) -> Self {
+        Self {
+            notes,
+            event_log,
+            events: None,
+        }
+    }
+
+    /// Builder method to record undos in the event log
+    pub fn with_event_dispatcher(mut self, events: Arc<EventDispatcher>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Revert the user's latest trashing, archiving or tag change, along with
+    /// those made within [`UNDO_GROUP_SECONDS`] before it
+    ///
+    /// Returns the notes in their restored state.
+    pub async fn undo(&self, user_id: Uuid) -> DomainResult<Vec<Note>> {
+        let recent = self.recent_events(user_id).await?;
+
+        // Changes from before the last undo are out of reach, including the
+        // ones the undo itself made
+        let undoable: Vec<&LoggedEvent> = recent
+            .iter()
+            .take_while(|e| !matches!(e.kind, LoggedEventKind::Undone { .. }))
+            .filter(|e| e.kind.is_undoable())
+            .collect();
+        let Some(latest) = undoable.first() else {
+            return Err(DomainError::NothingToUndo);
+        };
+        let group_start = latest.occurred_at - chrono::Duration::seconds(UNDO_GROUP_SECONDS);
+        let reverted: Vec<LoggedEventKind> = undoable
+            .iter()
+            .take_while(|e| e.occurred_at >= group_start)
+            .map(|e| e.kind.clone())
+            .collect();
+
+        let notes = self.apply(user_id, &reverted, false).await?;
+        if let Some(ref events) = self.events {
+            events
+                .dispatch(user_id, vec![LoggedEventKind::Undone { reverted }])
+                .await;
+        }
+        Ok(notes)
+    }
+
+    /// Reapply what the last undo reverted, if it is the user's latest action
+    pub async fn redo(&self, user_id: Uuid) -> DomainResult<Vec<Note>> {
+        let recent = self.recent_events(user_id).await?;
+        let Some(LoggedEventKind::Undone { reverted }) = recent.first().map(|e| &e.kind) else {
+            return Err(DomainError::NothingToRedo);
+        };
+
+        // The reapplied changes are logged again, so they can be undone again
+        self.apply(user_id, reverted, true).await
+    }
+
+    /// The user's events within [`UNDO_WINDOW_MINUTES`], newest first
+    async fn recent_events(&self, user_id: Uuid) -> DomainResult<Vec<LoggedEvent>> {
+        let since = Utc::now() - chrono::Duration::minutes(UNDO_WINDOW_MINUTES);
+        let mut events = self
+            .event_log
+            .find_by_user(user_id, None, MAX_ACTIVITY_LIMIT)
+            .await?;
+        events.retain(|e| e.occurred_at >= since);
+        Ok(events)
+    }
+
+    /// Make `changes` again (`forward`) or revert them
+    ///
+    /// Notes purged since are skipped.
+    async fn apply(
+        &self,
+        user_id: Uuid,
+        changes: &[LoggedEventKind],
+        forward: bool,
+    ) -> DomainResult<Vec<Note>> {
+        let mut by_note: Vec<(Uuid, NoteReversal)> = Vec::new();
+        for kind in changes {
+            let Some(note_id) = kind.note_id() else {
+                continue;
+            };
+            let index = match by_note.iter().position(|(id, _)| *id == note_id) {
+                Some(index) => index,
+                None => {
+                    by_note.push((note_id, NoteReversal::default()));
+                    by_note.len() - 1
+                }
+            };
+            let reversal = &mut by_note[index].1;
+            match kind {
+                LoggedEventKind::NoteTrashed { .. } => reversal.trash = true,
+                LoggedEventKind::NoteArchived { .. } => reversal.archive = true,
+                LoggedEventKind::NoteTagged { tag, .. } if forward => {
+                    reversal.add_tags.push(tag.clone())
+                }
+                LoggedEventKind::NoteTagged { tag, .. } => reversal.remove_tags.push(tag.clone()),
+                LoggedEventKind::NoteUntagged { tag, .. } if forward => {
+                    reversal.remove_tags.push(tag.clone())
+                }
+                LoggedEventKind::NoteUntagged { tag, .. } => reversal.add_tags.push(tag.clone()),
+                _ => {}
+            }
+        }
+
+        let mut notes = Vec::with_capacity(by_note.len());
+        for (note_id, reversal) in by_note {
+            let note = match self.notes.get_note(note_id, user_id).await {
+                Ok(note) => note,
+                Err(DomainError::NoteNotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+
+            // Trashed notes cannot be edited, so restore before and trash after
+            if reversal.trash && !forward {
+                self.notes.restore_note(note_id, user_id).await?;
+            }
+
+            let tags = if reversal.add_tags.is_empty() && reversal.remove_tags.is_empty() {
+                None
+            } else {
+                let mut names: Vec<String> = note
+                    .tags
+                    .iter()
+                    .map(|t| t.name.as_ref().to_string())
+                    .filter(|name| !reversal.remove_tags.contains(name))
+                    .collect();
+                for name in reversal.add_tags {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                Some(
+                    names
+                        .into_iter()
+                        .map(TagName::try_from)
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            };
+            if reversal.archive || tags.is_some() {
+                self.notes
+                    .update_note(UpdateNoteRequest {
+                        id: note_id,
+                        user_id,
+                        title: None,
+                        content: None,
+                        is_pinned: None,
+                        is_archived: reversal.archive.then_some(forward),
+                        color: None,
+                        tags,
+                        location: None,
+                        place_name: None,
+                        remind_at: None,
+                    })
+                    .await?;
+            }
+
+            if reversal.trash && forward {
+                self.notes.delete_note(note_id, user_id).await?;
+            }
+
+            notes.push(self.notes.get_note(note_id, user_id).await?);
+        }
+
+        Ok(notes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::tests::MockNoteRepository;
+    use crate::services::CreateNoteRequest;
+    use crate::services::tests::*;
+
+    fn create_undo_service() -> (UndoService, Arc<NoteService>) {
+        let event_log = Arc::new(MockEventLogRepository::default());
+        let events = Arc::new(EventDispatcher::new().with_event_log(event_log.clone()));
+        let notes = Arc::new(
+            NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            )
+            .with_event_dispatcher(events.clone()),
+        );
+        let service = UndoService::new(notes.clone(), event_log).with_event_dispatcher(events);
+        (service, notes)
+    }
+
+    async fn create_tagged_note(notes: &NoteService, user_id: Uuid, tag: &str) -> Note {
+        notes
+            .create_note(CreateNoteRequest {
+                user_id,
+                title: None,
+                content: "content".to_string(),
+                tags: vec![TagName::try_from(tag).unwrap()],
+                color: None,
+                is_pinned: false,
+                location: None,
+                place_name: None,
+                remind_at: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    fn tag_names(note: &Note) -> Vec<&str> {
+        note.tags.iter().map(|t| t.name.as_ref()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_undo_restores_bulk_trashed_notes_and_redo_trashes_them_again() {
+        let (service, notes) = create_undo_service();
+        let user_id = Uuid::new_v4();
+        let first = create_tagged_note(&notes, user_id, "work").await;
+        let second = create_tagged_note(&notes, user_id, "work").await;
+        notes.delete_note(first.id, user_id).await.unwrap();
+        notes.delete_note(second.id, user_id).await.unwrap();
+
+        let restored = service.undo(user_id).await.unwrap();
+        assert_eq!(restored.len(), 2);
+        assert!(restored.iter().all(|note| !note.is_trashed()));
+        assert!(matches!(
+            service.undo(user_id).await,
+            Err(DomainError::NothingToUndo)
+        ));
+
+        let trashed = service.redo(user_id).await.unwrap();
+        assert!(trashed.iter().all(|note| note.is_trashed()));
+        assert!(matches!(
+            service.redo(user_id).await,
+            Err(DomainError::NothingToRedo)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_undo_reverts_archive_and_tag_change() {
+        let (service, notes) = create_undo_service();
+        let user_id = Uuid::new_v4();
+        let note = create_tagged_note(&notes, user_id, "work").await;
+        notes
+            .update_note(UpdateNoteRequest {
+                id: note.id,
+                user_id,
+                title: None,
+                content: None,
+                is_pinned: None,
+                is_archived: Some(true),
+                color: None,
+                tags: Some(vec![TagName::try_from("ideas").unwrap()]),
+                location: None,
+                place_name: None,
+                remind_at: None,
+            })
+            .await
+            .unwrap();
+
+        let restored = service.undo(user_id).await.unwrap();
+
+        assert_eq!(restored.len(), 1);
+        assert!(!restored[0].is_archived);
+        assert_eq!(tag_names(&restored[0]), vec!["work"]);
+    }
+
+    #[tokio::test]
+    async fn test_undo_without_destructive_change_fails() {
+        let (service, notes) = create_undo_service();
+        let user_id = Uuid::new_v4();
+        create_tagged_note(&notes, user_id, "work").await;
+
+        assert!(matches!(
+            service.undo(user_id).await,
+            Err(DomainError::NothingToUndo)
+        ));
+    }
+}
@@ -38,6 +38,18 @@ pub enum ValidationError {
 
     #[error("Secret too short: minimum {min} bytes required, got {actual}")]
     SecretTooShort { min: usize, actual: usize },
+
+    #[error("Latitude must be between -90 and 90, got {0}")]
+    InvalidLatitude(String),
+
+    #[error("Longitude must be between -180 and 180, got {0}")]
+    InvalidLongitude(String),
+
+    #[error("Place name cannot exceed {max} characters, got {actual}")]
+    PlaceNameTooLong { max: usize, actual: usize },
+
+    #[error("Unknown language code: {0}")]
+    UnknownLanguage(String),
 }
 
 // ============================================================================
@@ -349,6 +361,141 @@ impl<'de> Deserialize<'de> for NoteTitle {
     }
 }
 
+// ============================================================================
+// Geolocation
+// ============================================================================
+
+/// A latitude in degrees, between -90 (south) and 90 (north).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Latitude(f64);
+
+// Valid values are never NaN, so equality is total
+impl Eq for Latitude {}
+
+impl Latitude {
+    pub fn new(value: f64) -> Result<Self, ValidationError> {
+        if !(-90.0..=90.0).contains(&value) {
+            return Err(ValidationError::InvalidLatitude(value.to_string()));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn degrees(self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for Latitude {
+    type Error = ValidationError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Latitude> for f64 {
+    fn from(val: Latitude) -> Self {
+        val.0
+    }
+}
+
+/// A longitude in degrees, between -180 (west) and 180 (east).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Longitude(f64);
+
+// Valid values are never NaN, so equality is total
+impl Eq for Longitude {}
+
+impl Longitude {
+    pub fn new(value: f64) -> Result<Self, ValidationError> {
+        if !(-180.0..=180.0).contains(&value) {
+            return Err(ValidationError::InvalidLongitude(value.to_string()));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn degrees(self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for Longitude {
+    type Error = ValidationError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Longitude> for f64 {
+    fn from(val: Longitude) -> Self {
+        val.0
+    }
+}
+
+/// A human-readable name for where a note was written, e.g. "Lisbon".
+///
+/// Enforces: 1-200 characters after trimming.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PlaceName(String);
+
+/// Maximum place name length
+pub const MAX_PLACE_NAME_LENGTH: usize = 200;
+
+impl PlaceName {
+    pub fn new(value: impl Into<String>) -> Result<Self, ValidationError> {
+        let trimmed = value.into().trim().to_string();
+        if trimmed.is_empty() {
+            return Err(ValidationError::Empty("place_name".to_string()));
+        }
+        let actual = trimmed.chars().count();
+        if actual > MAX_PLACE_NAME_LENGTH {
+            return Err(ValidationError::PlaceNameTooLong {
+                max: MAX_PLACE_NAME_LENGTH,
+                actual,
+            });
+        }
+        Ok(Self(trimmed))
+    }
+}
+
+impl AsRef<str> for PlaceName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PlaceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<String> for PlaceName {
+    type Error = ValidationError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<&str> for PlaceName {
+    type Error = ValidationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<PlaceName> for String {
+    fn from(val: PlaceName) -> Self {
+        val.0
+    }
+}
+
 // ============================================================================
 // OIDC Configuration Newtypes
 // ============================================================================
@@ -927,6 +1074,34 @@ mod tests {
         }
     }
 
+    mod geolocation_tests {
+        use super::*;
+
+        #[test]
+        fn test_coordinates_must_be_in_range() {
+            assert!(Latitude::new(90.0).is_ok());
+            assert!(Latitude::new(-90.5).is_err());
+            assert!(Latitude::new(f64::NAN).is_err());
+            assert!(Longitude::new(-180.0).is_ok());
+            assert!(Longitude::new(180.1).is_err());
+            assert!(Longitude::new(f64::INFINITY).is_err());
+        }
+
+        #[test]
+        fn test_coordinates_deserialize_with_validation() {
+            let lat: Latitude = serde_json::from_str("38.72").unwrap();
+            assert_eq!(lat.degrees(), 38.72);
+            assert!(serde_json::from_str::<Latitude>("91").is_err());
+        }
+
+        #[test]
+        fn test_place_name_trims_and_limits_length() {
+            assert_eq!(PlaceName::new("  Lisbon ").unwrap().as_ref(), "Lisbon");
+            assert!(PlaceName::new("   ").is_err());
+            assert!(PlaceName::new("a".repeat(MAX_PLACE_NAME_LENGTH + 1)).is_err());
+        }
+    }
+
     mod oidc_tests {
         use super::*;
 
@@ -0,0 +1,165 @@
+//! Wiki-link parsing for K-Notes
+//!
+//! Notes can reference each other with `[[Target]]` or `[[Target|label]]`.
+//! The target is matched against note titles (case-insensitive) or note IDs.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::entities::Note;
+
+/// A `[[wiki-link]]` found in note content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    /// Title or ID of the referenced note
+    pub target: String,
+    /// Optional display label (`[[target|label]]`)
+    pub label: Option<String>,
+}
+
+impl WikiLink {
+    /// Text to display for the link (label if present, otherwise the target)
+    pub fn display_text(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.target)
+    }
+}
+
+/// Lookup of wiki-link targets (lowercased title or note ID) to note IDs
+pub struct LinkTargets {
+    targets: HashMap<String, Uuid>,
+}
+
+impl LinkTargets {
+    pub fn new(notes: &[Note]) -> Self {
+        let mut targets = HashMap::new();
+        for note in notes {
+            targets.insert(note.id.to_string(), note.id);
+            if !note.title_str().is_empty() {
+                // First note with a given title wins, matching list order
+                targets
+                    .entry(note.title_str().to_lowercase())
+                    .or_insert(note.id);
+            }
+        }
+        Self { targets }
+    }
+
+    /// The note a link target refers to, if any
+    pub fn resolve(&self, target: &str) -> Option<Uuid> {
+        self.targets.get(&target.to_lowercase()).copied()
+    }
+}
+
+/// Parse a single link body (the text between `[[` and `]]`)
+fn parse_link_body(body: &str) -> Option<WikiLink> {
+    if body.contains('\n') || body.contains('[') || body.contains(']') {
+        return None;
+    }
+
+    let (target, label) = match body.split_once('|') {
+        Some((target, label)) => (target.trim(), Some(label.trim())),
+        None => (body.trim(), None),
+    };
+
+    if target.is_empty() {
+        return None;
+    }
+
+    Some(WikiLink {
+        target: target.to_string(),
+        label: label.filter(|l| !l.is_empty()).map(str::to_string),
+    })
+}
+
+/// Extract all wiki-links from note content, in order of appearance
+pub fn extract_wiki_links(content: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    replace_wiki_links(content, |link| {
+        links.push(link.clone());
+        String::new()
+    });
+    links
+}
+
+/// Replace every wiki-link in the content with the output of `replace`.
+///
+/// Text that merely looks like a link but is malformed (e.g. spans lines)
+/// is left untouched.
+pub fn replace_wiki_links(content: &str, mut replace: impl FnMut(&WikiLink) -> String) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("[[") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        match after_open.find("]]") {
+            Some(end) => match parse_link_body(&after_open[..end]) {
+                Some(link) => {
+                    output.push_str(&replace(&link));
+                    rest = &after_open[end + 2..];
+                }
+                None => {
+                    output.push_str("[[");
+                    rest = after_open;
+                }
+            },
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_simple_and_labelled_links() {
+        let links = extract_wiki_links("See [[Shopping List]] and [[Meeting Notes|the meeting]].");
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].target, "Shopping List");
+        assert_eq!(links[0].label, None);
+        assert_eq!(links[1].target, "Meeting Notes");
+        assert_eq!(links[1].display_text(), "the meeting");
+    }
+
+    #[test]
+    fn test_malformed_links_are_ignored() {
+        assert!(extract_wiki_links("[[]] [[ | label]] [[open").is_empty());
+        assert!(extract_wiki_links("[[multi\nline]]").is_empty());
+    }
+
+    #[test]
+    fn test_replace_wiki_links_preserves_surrounding_text() {
+        let output = replace_wiki_links("a [[B]] c [[broken", |link| format!("<{}>", link.target));
+        assert_eq!(output, "a <B> c [[broken");
+    }
+
+    #[test]
+    fn test_link_targets_resolve_titles_case_insensitively_and_ids() {
+        let note = Note::new(
+            Uuid::nil(),
+            crate::value_objects::NoteTitle::try_from("Meeting Notes").ok(),
+            "",
+        );
+        let targets = LinkTargets::new(std::slice::from_ref(&note));
+
+        assert_eq!(targets.resolve("meeting notes"), Some(note.id));
+        assert_eq!(targets.resolve(&note.id.to_string()), Some(note.id));
+        assert_eq!(targets.resolve("Other"), None);
+    }
+
+    #[test]
+    fn test_nested_brackets_fall_back_to_plain_text() {
+        let output = replace_wiki_links("[[a [[b]] c]]", |link| format!("<{}>", link.target));
+        assert_eq!(output, "[[a <b> c]]");
+    }
+}
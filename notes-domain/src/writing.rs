@@ -0,0 +1,308 @@
+//! Writing statistics and proofreading of notes
+//!
+//! Every note can be measured: words, sentences, paragraphs and how long it
+//! takes to read. Instances with a [`Proofreader`]
+//! also point out spelling and grammar mistakes, with suggested
+//! replacements. Suggestions are kept per content, so asking again about an
+//! unchanged note does not reach the proofreader.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::Note;
+use crate::errors::{DomainError, DomainResult};
+use crate::language::Language;
+use crate::ports::{Proofreader, content_hash};
+use crate::services::NoteService;
+
+/// Longest note content proofread, in characters
+pub const MAX_PROOFREAD_LENGTH: usize = 20_000;
+
+/// Distinct contents whose suggestions are kept
+pub const PROOFREAD_CACHE_ENTRIES: usize = 256;
+
+/// Words read per minute, for reading times
+const READING_WORDS_PER_MINUTE: usize = 200;
+
+/// Measures of a note's content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WritingStats {
+    pub words: usize,
+    /// Characters other than whitespace
+    pub characters: usize,
+    pub sentences: usize,
+    /// Blocks of text separated by blank lines
+    pub paragraphs: usize,
+    /// Minutes it takes to read, rounded up
+    pub reading_minutes: usize,
+}
+
+impl WritingStats {
+    /// Measure `content`; Markdown markers like `#` and `-` are not words
+    pub fn of(content: &str) -> Self {
+        let words: Vec<&str> = content
+            .split_whitespace()
+            .filter(|w| w.chars().any(char::is_alphanumeric))
+            .collect();
+        let ends_sentence = |w: &&str| {
+            w.trim_end_matches(['"', '\'', ')'])
+                .ends_with(['.', '!', '?'])
+        };
+        let sentences = words.iter().filter(|w| ends_sentence(w)).count()
+            + usize::from(words.last().is_some_and(|w| !ends_sentence(w)));
+        let paragraphs = content
+            .split("\n\n")
+            .filter(|block| block.chars().any(char::is_alphanumeric))
+            .count();
+
+        Self {
+            words: words.len(),
+            characters: content.chars().filter(|c| !c.is_whitespace()).count(),
+            sentences,
+            paragraphs,
+            reading_minutes: words.len().div_ceil(READING_WORDS_PER_MINUTE),
+        }
+    }
+}
+
+/// What kind of mistake a suggestion is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    Spelling,
+    Grammar,
+    Style,
+    Other,
+}
+
+/// A possible mistake in a note's content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofreadSuggestion {
+    /// Position of the flagged text in the content, in characters
+    pub offset: usize,
+    /// Length of the flagged text, in characters
+    pub length: usize,
+    pub message: String,
+    pub kind: SuggestionKind,
+    /// Replacements for the flagged text, best first
+    pub replacements: Vec<String>,
+    /// Proofreader rule that flagged the text, like `MORFOLOGIK_RULE_EN_US`
+    pub rule: String,
+}
+
+/// Statistics and suggestions for a note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proofreading {
+    pub language: Option<Language>,
+    pub stats: WritingStats,
+    /// `None` when the instance has no proofreader
+    pub suggestions: Option<Vec<ProofreadSuggestion>>,
+}
+
+/// Service measuring notes and pointing out their mistakes
+pub struct ProofreadService {
+    notes: Arc<NoteService>,
+    proofreader: Option<Arc<dyn Proofreader>>,
+    /// Suggestions by language and content hash, emptied when full
+    suggestions: Mutex<HashMap<String, Vec<ProofreadSuggestion>>>,
+}
+
+impl ProofreadService {
+    pub fn new(notes: Arc<NoteService>) -> Self {
+        Self {
+            notes,
+            proofreader: None,
+            suggestions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Builder method to suggest corrections with a proofreader
+    pub fn with_proofreader(mut self, proofreader: Arc<dyn Proofreader>) -> Self {
+        self.proofreader = Some(proofreader);
+        self
+    }
+
+    /// Statistics of a note, and suggestions when there is a proofreader
+    pub async fn proofread(&self, note_id: Uuid, user_id: Uuid) -> DomainResult<Proofreading> {
+        let note = self.notes.get_note(note_id, user_id).await?;
+        let suggestions = match self.proofreader {
+            Some(ref proofreader) => Some(self.suggestions_for(proofreader.as_ref(), &note).await?),
+            None => None,
+        };
+
+        Ok(Proofreading {
+            stats: WritingStats::of(&note.content),
+            language: note.language,
+            suggestions,
+        })
+    }
+
+    async fn suggestions_for(
+        &self,
+        proofreader: &dyn Proofreader,
+        note: &Note,
+    ) -> DomainResult<Vec<ProofreadSuggestion>> {
+        if note.content.chars().count() > MAX_PROOFREAD_LENGTH {
+            return Err(DomainError::validation(format!(
+                "Notes longer than {} characters cannot be proofread",
+                MAX_PROOFREAD_LENGTH
+            )));
+        }
+
+        let language = note.language.as_ref();
+        let key = format!(
+            "{}:{}",
+            language.map_or("auto", |l| l.code()),
+            content_hash(&note.content)
+        );
+        if let Some(cached) = self.suggestions.lock().unwrap().get(&key) {
+            return Ok(cached.clone());
+        }
+
+        let suggestions = proofreader.check(&note.content, language).await?;
+        let mut cache = self.suggestions.lock().unwrap();
+        if cache.len() >= PROOFREAD_CACHE_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(key, suggestions.clone());
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_count_words_sentences_and_paragraphs() {
+        let stats = WritingStats::of("# Trip\n\nWe leave at 9. Bring snacks!\n\n- tent\n- map");
+
+        assert_eq!(stats.words, 9);
+        assert_eq!(stats.sentences, 3);
+        assert_eq!(stats.paragraphs, 3);
+        assert_eq!(stats.reading_minutes, 1);
+        assert_eq!(WritingStats::of(" \n\n "), WritingStats::default());
+    }
+
+    #[test]
+    fn test_reading_time_rounds_up() {
+        let content = "word ".repeat(READING_WORDS_PER_MINUTE + 1);
+        assert_eq!(WritingStats::of(&content).reading_minutes, 2);
+    }
+
+    mod proofread_service_tests {
+        use super::*;
+        use crate::repositories::tests::MockNoteRepository;
+        use crate::services::tests::*;
+        use crate::services::{CreateNoteRequest, UpdateNoteRequest};
+        use crate::writing::SuggestionKind;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Flags every `teh`, counting its checks
+        #[derive(Default)]
+        struct TypoProofreader {
+            checks: AtomicUsize,
+        }
+
+        #[async_trait::async_trait]
+        impl Proofreader for TypoProofreader {
+            async fn check(
+                &self,
+                text: &str,
+                _language: Option<&Language>,
+            ) -> DomainResult<Vec<ProofreadSuggestion>> {
+                self.checks.fetch_add(1, Ordering::SeqCst);
+                Ok(text
+                    .match_indices("teh")
+                    .map(|(offset, typo)| ProofreadSuggestion {
+                        offset,
+                        length: typo.len(),
+                        message: "Possible spelling mistake".to_string(),
+                        kind: SuggestionKind::Spelling,
+                        replacements: vec!["the".to_string()],
+                        rule: "TYPO".to_string(),
+                    })
+                    .collect())
+            }
+        }
+
+        async fn create_note(notes: &NoteService, user_id: Uuid, content: &str) -> Note {
+            notes
+                .create_note(CreateNoteRequest {
+                    user_id,
+                    title: None,
+                    content: content.to_string(),
+                    tags: vec![],
+                    color: None,
+                    is_pinned: false,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_suggestions_are_cached_until_content_changes() {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let proofreader = Arc::new(TypoProofreader::default());
+            let service =
+                ProofreadService::new(notes.clone()).with_proofreader(proofreader.clone());
+            let user_id = Uuid::new_v4();
+            let note = create_note(&notes, user_id, "Feed teh cat.").await;
+
+            let first = service.proofread(note.id, user_id).await.unwrap();
+            let again = service.proofread(note.id, user_id).await.unwrap();
+            assert_eq!(first, again);
+            assert_eq!(first.stats.words, 3);
+            let suggestions = first.suggestions.unwrap();
+            assert_eq!(suggestions.len(), 1);
+            assert_eq!(suggestions[0].offset, 5);
+            assert_eq!(proofreader.checks.load(Ordering::SeqCst), 1);
+
+            notes
+                .update_note(UpdateNoteRequest {
+                    id: note.id,
+                    user_id,
+                    title: None,
+                    content: Some("Feed the cat.".to_string()),
+                    is_pinned: None,
+                    is_archived: None,
+                    color: None,
+                    tags: None,
+                    location: None,
+                    place_name: None,
+                    remind_at: None,
+                })
+                .await
+                .unwrap();
+            let edited = service.proofread(note.id, user_id).await.unwrap();
+            assert_eq!(edited.suggestions, Some(Vec::new()));
+            assert_eq!(proofreader.checks.load(Ordering::SeqCst), 2);
+
+            assert!(service.proofread(note.id, Uuid::new_v4()).await.is_err());
+        }
+
+        #[tokio::test]
+        async fn test_stats_without_a_proofreader() {
+            let notes = Arc::new(NoteService::new(
+                Arc::new(MockNoteRepository::new()),
+                Arc::new(MockTagRepository::new()),
+            ));
+            let service = ProofreadService::new(notes.clone());
+            let user_id = Uuid::new_v4();
+            let note = create_note(&notes, user_id, "Feed teh cat.").await;
+
+            let proofreading = service.proofread(note.id, user_id).await.unwrap();
+            assert_eq!(proofreading.suggestions, None);
+            assert_eq!(proofreading.stats.sentences, 1);
+        }
+    }
+}
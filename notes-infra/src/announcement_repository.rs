@@ -0,0 +1,260 @@
+//! SQLite implementation of AnnouncementRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{AnnouncementRepository, DomainResult, announcements::Announcement};
+
+/// SQLite adapter for AnnouncementRepository
+pub struct SqliteAnnouncementRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteAnnouncementRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct AnnouncementRow {
+    id: String,
+    title: String,
+    body: String,
+    level: String,
+    starts_at: Option<String>,
+    ends_at: Option<String>,
+    created_by: String,
+    created_at: String,
+    updated_at: String,
+}
+
+fn parse_datetime(s: &str) -> DomainResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))
+}
+
+impl AnnouncementRow {
+    fn try_into_announcement(self) -> DomainResult<Announcement> {
+        let parse_uuid =
+            |s: &str| Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)));
+
+        Ok(Announcement {
+            id: parse_uuid(&self.id)?,
+            title: self.title,
+            body: self.body,
+            level: self
+                .level
+                .parse()
+                .map_err(|e| decode_error(format!("{}", e)))?,
+            starts_at: self.starts_at.as_deref().map(parse_datetime).transpose()?,
+            ends_at: self.ends_at.as_deref().map(parse_datetime).transpose()?,
+            created_by: parse_uuid(&self.created_by)?,
+            created_at: parse_datetime(&self.created_at)?,
+            updated_at: parse_datetime(&self.updated_at)?,
+        })
+    }
+}
+
+#[async_trait]
+impl AnnouncementRepository for SqliteAnnouncementRepository {
+    async fn save(&self, announcement: &Announcement) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO announcements (id, title, body, level, starts_at, ends_at,
+                                           created_by, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    title = excluded.title,
+                    body = excluded.body,
+                    level = excluded.level,
+                    starts_at = excluded.starts_at,
+                    ends_at = excluded.ends_at,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(announcement.id.to_string())
+            .bind(&announcement.title)
+            .bind(&announcement.body)
+            .bind(announcement.level.as_str())
+            .bind(announcement.starts_at.map(|t| t.to_rfc3339()))
+            .bind(announcement.ends_at.map(|t| t.to_rfc3339()))
+            .bind(announcement.created_by.to_string())
+            .bind(announcement.created_at.to_rfc3339())
+            .bind(announcement.updated_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Announcement>> {
+        let row: Option<AnnouncementRow> =
+            sqlx::query_as("SELECT * FROM announcements WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        row.map(AnnouncementRow::try_into_announcement).transpose()
+    }
+
+    async fn find_all(&self) -> DomainResult<Vec<Announcement>> {
+        let rows: Vec<AnnouncementRow> =
+            sqlx::query_as("SELECT * FROM announcements ORDER BY created_at DESC")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(AnnouncementRow::try_into_announcement)
+            .collect()
+    }
+
+    async fn find_active_for_user(
+        &self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> DomainResult<Vec<Announcement>> {
+        // RFC 3339 timestamps in UTC compare correctly as strings
+        let now = now.to_rfc3339();
+        let rows: Vec<AnnouncementRow> = sqlx::query_as(
+            r#"
+            SELECT a.* FROM announcements a
+            WHERE (a.starts_at IS NULL OR a.starts_at <= ?)
+              AND (a.ends_at IS NULL OR a.ends_at > ?)
+              AND NOT EXISTS (
+                  SELECT 1 FROM announcement_dismissals d
+                  WHERE d.announcement_id = a.id AND d.user_id = ?
+              )
+            ORDER BY a.created_at DESC
+            "#,
+        )
+        .bind(&now)
+        .bind(&now)
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(AnnouncementRow::try_into_announcement)
+            .collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query("DELETE FROM announcements WHERE id = ?")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn dismiss(&self, id: Uuid, user_id: Uuid) -> DomainResult<()> {
+        let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO announcement_dismissals (announcement_id, user_id, dismissed_at)
+                VALUES (?, ?, ?)
+                ON CONFLICT(announcement_id, user_id) DO NOTHING
+                "#,
+            )
+            .bind(id.to_string())
+            .bind(user_id.to_string())
+            .bind(now)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use chrono::Duration;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool, subject: &str, email: &str) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(subject, Email::try_from(email).unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_active_announcements_respect_schedule_and_dismissals() {
+        let pool = setup_test_db().await;
+        let admin = create_test_user(&pool, "test|admin", "admin@example.com").await;
+        let user = create_test_user(&pool, "test|user", "user@example.com").await;
+        let repo = SqliteAnnouncementRepository::new(pool);
+        let now = Utc::now();
+
+        let current = Announcement::new(admin.id, "Upgrade", "Tonight");
+        let mut scheduled = Announcement::new(admin.id, "Later", "");
+        scheduled.starts_at = Some(now + Duration::days(1));
+        let mut expired = Announcement::new(admin.id, "Past", "");
+        expired.ends_at = Some(now - Duration::days(1));
+        for announcement in [&current, &scheduled, &expired] {
+            repo.save(announcement).await.unwrap();
+        }
+
+        assert_eq!(repo.find_all().await.unwrap().len(), 3);
+        assert_eq!(
+            repo.find_by_id(current.id).await.unwrap(),
+            Some(current.clone())
+        );
+        let active = repo.find_active_for_user(user.id, now).await.unwrap();
+        assert_eq!(
+            active.iter().map(|a| a.id).collect::<Vec<_>>(),
+            vec![current.id]
+        );
+
+        repo.dismiss(current.id, user.id).await.unwrap();
+        repo.dismiss(current.id, user.id).await.unwrap();
+        assert!(
+            repo.find_active_for_user(user.id, now)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            repo.find_active_for_user(admin.id, now)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        repo.delete(current.id).await.unwrap();
+        assert_eq!(repo.find_by_id(current.id).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! SQLite implementation of AudioUploadRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{AudioUploadRepository, DomainResult, transcription::AudioUpload};
+
+/// SQLite adapter for AudioUploadRepository
+pub struct SqliteAudioUploadRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteAudioUploadRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct AudioUploadRow {
+    id: String,
+    note_id: String,
+    user_id: String,
+    content_type: String,
+    audio: Vec<u8>,
+    attempts: i64,
+    created_at: String,
+}
+
+impl AudioUploadRow {
+    fn try_into_upload(self) -> DomainResult<AudioUpload> {
+        let parse_uuid =
+            |s: &str| Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)));
+
+        Ok(AudioUpload {
+            id: parse_uuid(&self.id)?,
+            note_id: parse_uuid(&self.note_id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            content_type: self.content_type,
+            audio: self.audio,
+            attempts: u32::try_from(self.attempts)
+                .map_err(|e| decode_error(format!("Invalid attempt count: {}", e)))?,
+            created_at: DateTime::parse_from_rfc3339(&self.created_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?,
+        })
+    }
+}
+
+#[async_trait]
+impl AudioUploadRepository for SqliteAudioUploadRepository {
+    async fn save(&self, upload: &AudioUpload) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO audio_uploads (id, note_id, user_id, content_type, audio, attempts, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(upload.id.to_string())
+            .bind(upload.note_id.to_string())
+            .bind(upload.user_id.to_string())
+            .bind(&upload.content_type)
+            .bind(&upload.audio)
+            .bind(i64::from(upload.attempts))
+            .bind(upload.created_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_pending(&self, limit: usize) -> DomainResult<Vec<AudioUpload>> {
+        let rows: Vec<AudioUploadRow> = sqlx::query_as(
+            r#"
+            SELECT id, note_id, user_id, content_type, audio, attempts, created_at
+            FROM audio_uploads
+            ORDER BY created_at
+            LIMIT ?
+            "#,
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(AudioUploadRow::try_into_upload)
+            .collect()
+    }
+
+    async fn record_failure(&self, id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query("UPDATE audio_uploads SET attempts = attempts + 1 WHERE id = ?")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query("DELETE FROM audio_uploads WHERE id = ?")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::note_repository::SqliteNoteRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, Note, NoteRepository, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_uploads_round_trip_and_count_failures() {
+        let pool = setup_test_db().await;
+        let user = User::new("test|user", Email::try_from("user@example.com").unwrap());
+        SqliteUserRepository::new(pool.clone())
+            .save(&user)
+            .await
+            .unwrap();
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let note = Note::new(user.id, None, "Shopping");
+        note_repo.save(&note).await.unwrap();
+        let repo = SqliteAudioUploadRepository::new(pool);
+
+        let upload = AudioUpload::new(note.id, user.id, "audio/ogg".to_string(), vec![1, 2, 3]);
+        repo.save(&upload).await.unwrap();
+        repo.record_failure(upload.id).await.unwrap();
+
+        let pending = repo.find_pending(10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].audio, vec![1, 2, 3]);
+        assert_eq!(pending[0].content_type, "audio/ogg");
+        assert_eq!(pending[0].attempts, 1);
+
+        repo.delete(upload.id).await.unwrap();
+        assert!(repo.find_pending(10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_uploads_go_with_their_note() {
+        let pool = setup_test_db().await;
+        let user = User::new("test|user", Email::try_from("user@example.com").unwrap());
+        SqliteUserRepository::new(pool.clone())
+            .save(&user)
+            .await
+            .unwrap();
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let note = Note::new(user.id, None, "Shopping");
+        note_repo.save(&note).await.unwrap();
+        let repo = SqliteAudioUploadRepository::new(pool);
+
+        repo.save(&AudioUpload::new(
+            note.id,
+            user.id,
+            "audio/ogg".to_string(),
+            vec![1],
+        ))
+        .await
+        .unwrap();
+        note_repo.delete(note.id).await.unwrap();
+
+        assert!(repo.find_pending(10).await.unwrap().is_empty());
+    }
+}
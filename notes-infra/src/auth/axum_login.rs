@@ -1,12 +1,11 @@
 use std::sync::Arc;
 
 use axum_login::{AuthnBackend, UserId};
-use password_auth::verify_password;
 use serde::{Deserialize, Serialize};
 use tower_sessions::SessionManagerLayer;
 use uuid::Uuid;
 
-use notes_domain::{User, UserRepository};
+use notes_domain::{DomainError, User, UserService};
 
 use crate::session_store::InfraSessionStore;
 
@@ -33,12 +32,12 @@ impl axum_login::AuthUser for AuthUser {
 
 #[derive(Clone)]
 pub struct AuthBackend {
-    pub user_repo: Arc<dyn UserRepository>,
+    pub user_service: Arc<UserService>,
 }
 
 impl AuthBackend {
-    pub fn new(user_repo: Arc<dyn UserRepository>) -> Self {
-        Self { user_repo }
+    pub fn new(user_service: Arc<UserService>) -> Self {
+        Self { user_service }
     }
 }
 
@@ -63,32 +62,22 @@ impl AuthnBackend for AuthBackend {
         &self,
         creds: Self::Credentials,
     ) -> Result<Option<Self::User>, Self::Error> {
+        // Verifies the password and upgrades outdated hashes
         let user = self
-            .user_repo
-            .find_by_email(creds.email.as_ref())
+            .user_service
+            .authenticate(creds.email.as_ref(), creds.password.as_ref())
             .await
             .map_err(|e| AuthError::Anyhow(anyhow::anyhow!(e)))?;
 
-        if let Some(user) = user {
-            if let Some(hash) = &user.password_hash {
-                // Verify password
-                if verify_password(creds.password.as_ref(), hash).is_ok() {
-                    return Ok(Some(AuthUser(user)));
-                }
-            }
-        }
-
-        Ok(None)
+        Ok(user.map(AuthUser))
     }
 
     async fn get_user(&self, user_id: &UserId<Self>) -> Result<Option<Self::User>, Self::Error> {
-        let user = self
-            .user_repo
-            .find_by_id(*user_id)
-            .await
-            .map_err(|e| AuthError::Anyhow(anyhow::anyhow!(e)))?;
-
-        Ok(user.map(AuthUser))
+        match self.user_service.find_by_id(*user_id).await {
+            Ok(user) => Ok(Some(AuthUser(user))),
+            Err(DomainError::UserNotFound(_)) => Ok(None),
+            Err(e) => Err(AuthError::Anyhow(anyhow::anyhow!(e))),
+        }
     }
 }
 
@@ -97,14 +86,10 @@ pub type AuthManagerLayer = axum_login::AuthManagerLayer<AuthBackend, InfraSessi
 
 pub async fn setup_auth_layer(
     session_layer: SessionManagerLayer<InfraSessionStore>,
-    user_repo: Arc<dyn UserRepository>,
+    user_service: Arc<UserService>,
 ) -> Result<AuthManagerLayer, AuthError> {
-    let backend = AuthBackend::new(user_repo);
+    let backend = AuthBackend::new(user_service);
 
     let auth_layer = axum_login::AuthManagerLayerBuilder::new(backend, session_layer).build();
     Ok(auth_layer)
 }
-
-pub fn hash_password(password: &str) -> String {
-    password_auth::generate_hash(password)
-}
@@ -5,6 +5,7 @@
 
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use notes_domain::User;
+use notes_domain::scopes::Scopes;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -79,6 +80,19 @@ pub struct JwtClaims {
     /// Audience
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aud: Option<String>,
+    /// Space-separated scopes; tokens without them have full access
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl JwtClaims {
+    /// The scopes the token grants
+    pub fn scopes(&self) -> Result<Scopes, JwtError> {
+        match self.scope {
+            Some(ref scope) => Scopes::parse(scope).map_err(|_| JwtError::InvalidFormat),
+            None => Ok(Scopes::Full),
+        }
+    }
 }
 
 /// JWT-related errors
@@ -143,6 +157,11 @@ impl JwtValidator {
 
     /// Create a JWT token for the given user
     pub fn create_token(&self, user: &User) -> Result<String, JwtError> {
+        self.create_scoped_token(user, &Scopes::Full)
+    }
+
+    /// Create a JWT token for the given user that only grants `scopes`
+    pub fn create_scoped_token(&self, user: &User, scopes: &Scopes) -> Result<String, JwtError> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
@@ -157,6 +176,10 @@ impl JwtValidator {
             iat: now,
             iss: self.config.issuer.clone(),
             aud: self.config.audience.clone(),
+            scope: match scopes {
+                Scopes::Full => None,
+                Scopes::Limited(_) => Some(scopes.to_string()),
+            },
         };
 
         let header = Header::new(Algorithm::HS256);
@@ -225,6 +248,24 @@ mod tests {
         assert_eq!(claims.email, "test@example.com");
     }
 
+    #[test]
+    fn test_scoped_token_carries_its_scopes() {
+        let config = JwtConfig::new_unchecked("test-secret-key-that-is-long-enough".to_string());
+        let validator = JwtValidator::new(config);
+        let user = create_test_user();
+        let scopes = Scopes::parse("notes:read").unwrap();
+
+        let token = validator.create_scoped_token(&user, &scopes).unwrap();
+        let claims = validator.validate_token(&token).unwrap();
+        assert_eq!(claims.scope.as_deref(), Some("notes:read"));
+        assert_eq!(claims.scopes().unwrap(), scopes);
+
+        let token = validator.create_token(&user).unwrap();
+        let claims = validator.validate_token(&token).unwrap();
+        assert_eq!(claims.scope, None);
+        assert!(claims.scopes().unwrap().is_full());
+    }
+
     #[test]
     fn test_weak_secret_rejected_in_production() {
         let result = JwtConfig::new(
@@ -0,0 +1,270 @@
+//! SQLite implementation of BoardRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::boards::{Board, BoardColumn, ColumnSource};
+use notes_domain::{BoardRepository, DomainResult, TagName};
+
+/// SQLite adapter for BoardRepository
+pub struct SqliteBoardRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteBoardRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn load_columns(&self, board_id: &str) -> DomainResult<Vec<BoardColumn>> {
+        let rows: Vec<BoardColumnRow> = sqlx::query_as(
+            "SELECT id, name, kind, value FROM board_columns WHERE board_id = ? ORDER BY position",
+        )
+        .bind(board_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(BoardColumnRow::try_into_column)
+            .collect()
+    }
+
+    async fn with_columns(&self, row: BoardRow) -> DomainResult<Board> {
+        let columns = self.load_columns(&row.id).await?;
+        row.try_into_board(columns)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct BoardRow {
+    id: String,
+    user_id: String,
+    name: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, FromRow)]
+struct BoardColumnRow {
+    id: String,
+    name: String,
+    kind: String,
+    value: String,
+}
+
+fn parse_datetime(s: &str) -> DomainResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))
+}
+
+fn parse_uuid(s: &str) -> DomainResult<Uuid> {
+    Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))
+}
+
+/// Stored `kind` and `value` of a column source
+fn source_parts(source: &ColumnSource) -> (&'static str, &str) {
+    match source {
+        ColumnSource::Tag(tag) => ("tag", tag.as_ref()),
+        ColumnSource::Status(status) => ("status", status.as_str()),
+    }
+}
+
+impl BoardColumnRow {
+    fn try_into_column(self) -> DomainResult<BoardColumn> {
+        let source = match self.kind.as_str() {
+            "tag" => ColumnSource::Tag(
+                TagName::try_from(self.value)
+                    .map_err(|e| decode_error(format!("Invalid tag name in DB: {}", e)))?,
+            ),
+            "status" => ColumnSource::Status(
+                self.value
+                    .parse()
+                    .map_err(|e| decode_error(format!("{}", e)))?,
+            ),
+            other => return Err(decode_error(format!("Unknown column kind: {}", other))),
+        };
+
+        Ok(BoardColumn {
+            id: parse_uuid(&self.id)?,
+            name: self.name,
+            source,
+        })
+    }
+}
+
+impl BoardRow {
+    fn try_into_board(self, columns: Vec<BoardColumn>) -> DomainResult<Board> {
+        Ok(Board {
+            id: parse_uuid(&self.id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            name: self.name,
+            columns,
+            created_at: parse_datetime(&self.created_at)?,
+            updated_at: parse_datetime(&self.updated_at)?,
+        })
+    }
+}
+
+#[async_trait]
+impl BoardRepository for SqliteBoardRepository {
+    async fn save(&self, board: &Board) -> DomainResult<()> {
+        write(move || async move {
+            let id_str = board.id.to_string();
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO boards (id, user_id, name, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&id_str)
+            .bind(board.user_id.to_string())
+            .bind(&board.name)
+            .bind(board.created_at.to_rfc3339())
+            .bind(board.updated_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            // Columns are replaced as a whole so removed ones disappear
+            sqlx::query("DELETE FROM board_columns WHERE board_id = ?")
+                .bind(&id_str)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_sqlx_error)?;
+            for (position, column) in board.columns.iter().enumerate() {
+                let (kind, value) = source_parts(&column.source);
+                sqlx::query(
+                    r#"
+                    INSERT INTO board_columns (id, board_id, position, name, kind, value)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(column.id.to_string())
+                .bind(&id_str)
+                .bind(position as i64)
+                .bind(&column.name)
+                .bind(kind)
+                .bind(value)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_sqlx_error)?;
+            }
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Board>> {
+        let row: Option<BoardRow> = sqlx::query_as("SELECT * FROM boards WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        match row {
+            Some(row) => Ok(Some(self.with_columns(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Board>> {
+        let rows: Vec<BoardRow> =
+            sqlx::query_as("SELECT * FROM boards WHERE user_id = ? ORDER BY created_at")
+                .bind(user_id.to_string())
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        let mut boards = Vec::with_capacity(rows.len());
+        for row in rows {
+            boards.push(self.with_columns(row).await?);
+        }
+        Ok(boards)
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query("DELETE FROM boards WHERE id = ?")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::boards::NoteStatus;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new("test|board", Email::try_from("board@example.com").unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_save_replaces_columns_in_order() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteBoardRepository::new(pool);
+
+        let mut board = Board::new(
+            user.id,
+            "Tasks",
+            vec![
+                BoardColumn::new(
+                    "To do",
+                    ColumnSource::Tag(TagName::try_from("todo").unwrap()),
+                ),
+                BoardColumn::new("Done", ColumnSource::Status(NoteStatus::Archived)),
+            ],
+        );
+        repo.save(&board).await.unwrap();
+        assert_eq!(
+            repo.find_by_id(board.id).await.unwrap(),
+            Some(board.clone())
+        );
+
+        board.columns.reverse();
+        board.columns.pop();
+        board.name = "Renamed".to_string();
+        repo.save(&board).await.unwrap();
+        assert_eq!(
+            repo.find_by_user(user.id).await.unwrap(),
+            vec![board.clone()]
+        );
+
+        repo.delete(board.id).await.unwrap();
+        assert_eq!(repo.find_by_id(board.id).await.unwrap(), None);
+    }
+}
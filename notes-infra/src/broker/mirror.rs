@@ -0,0 +1,87 @@
+//! Publishing events to a second broker
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use notes_domain::events::DomainEvent;
+use notes_domain::{DomainResult, MessageBroker};
+
+/// Publishes to a primary broker and copies every event to a mirror.
+///
+/// Mirror failures are logged rather than returned, so an unavailable
+/// integration never fails the write that produced the event.
+/// Subscriptions come from the primary broker only.
+pub struct MirroredMessageBroker {
+    primary: Arc<dyn MessageBroker>,
+    mirror: Arc<dyn MessageBroker>,
+}
+
+impl MirroredMessageBroker {
+    pub fn new(primary: Arc<dyn MessageBroker>, mirror: Arc<dyn MessageBroker>) -> Self {
+        Self { primary, mirror }
+    }
+}
+
+#[async_trait]
+impl MessageBroker for MirroredMessageBroker {
+    async fn publish(&self, event: &DomainEvent) -> DomainResult<()> {
+        let published = self.primary.publish(event).await;
+        if let Err(e) = self.mirror.publish(event).await {
+            tracing::warn!(event_id = %event.id, "Failed to mirror event: {}", e);
+        }
+        published
+    }
+
+    async fn subscribe(
+        &self,
+    ) -> DomainResult<Pin<Box<dyn futures_core::Stream<Item = DomainEvent> + Send>>> {
+        self.primary.subscribe().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notes_domain::{DomainError, Note};
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct RecordingBroker {
+        fail: bool,
+        events: Mutex<Vec<Uuid>>,
+    }
+
+    #[async_trait]
+    impl MessageBroker for RecordingBroker {
+        async fn publish(&self, event: &DomainEvent) -> DomainResult<()> {
+            if self.fail {
+                return Err(DomainError::InfrastructureError("down".into()));
+            }
+            self.events.lock().unwrap().push(event.id);
+            Ok(())
+        }
+
+        async fn subscribe(
+            &self,
+        ) -> DomainResult<Pin<Box<dyn futures_core::Stream<Item = DomainEvent> + Send>>> {
+            Err(DomainError::InfrastructureError("unsupported".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mirror_failures_do_not_fail_publishing() {
+        let primary = Arc::new(RecordingBroker::default());
+        let mirror = Arc::new(RecordingBroker {
+            fail: true,
+            ..Default::default()
+        });
+        let broker = MirroredMessageBroker::new(primary.clone(), mirror);
+
+        let event = DomainEvent::note_updated(Note::new(Uuid::new_v4(), None, "content"));
+        broker.publish(&event).await.unwrap();
+
+        assert_eq!(*primary.events.lock().unwrap(), vec![event.id]);
+    }
+}
@@ -3,5 +3,8 @@
 //! This module provides implementations of the `MessageBroker` port
 //! for different messaging backends.
 
+pub mod mirror;
+#[cfg(feature = "broker-mqtt")]
+pub mod mqtt;
 #[cfg(feature = "broker-nats")]
 pub mod nats;
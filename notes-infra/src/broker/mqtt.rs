@@ -0,0 +1,138 @@
+//! MQTT event publisher
+//!
+//! Mirrors domain events to an MQTT broker for home-automation systems
+//! such as Home Assistant. Each event is published as its JSON envelope on
+//! `{prefix}/{user_id}/{subject}`, with the subject's dots turned into topic
+//! levels, e.g. `knotes/<user>/notes/updated`.
+//!
+//! Publishing only; the worker consumes events from the primary broker.
+
+use std::pin::Pin;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use notes_domain::events::DomainEvent;
+use notes_domain::{DomainError, DomainResult, MessageBroker};
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+
+/// Delay before polling again after the connection failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Requests queued while the connection is down before publishing waits
+const REQUEST_QUEUE_CAPACITY: usize = 100;
+
+/// Connection settings for the MQTT publisher
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    /// First topic level of every published event
+    pub topic_prefix: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "k-notes".to_string(),
+            topic_prefix: "knotes".to_string(),
+            username: None,
+            password: None,
+        }
+    }
+}
+
+/// MessageBroker adapter that publishes events to MQTT topics
+pub struct MqttEventPublisher {
+    client: AsyncClient,
+    topic_prefix: String,
+}
+
+impl MqttEventPublisher {
+    /// Create the client and keep its connection alive in a background task.
+    ///
+    /// Connecting happens in that task, so an unreachable broker is logged
+    /// and retried instead of failing startup.
+    pub fn connect(config: &MqttConfig) -> Self {
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(ref username) = config.username {
+            options.set_credentials(username, config.password.as_deref().unwrap_or_default());
+        }
+
+        let (client, mut event_loop) = AsyncClient::new(options, REQUEST_QUEUE_CAPACITY);
+        let host = format!("{}:{}", config.host, config.port);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = event_loop.poll().await {
+                    tracing::warn!("MQTT connection to {} failed: {}", host, e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        });
+
+        Self {
+            client,
+            topic_prefix: config.topic_prefix.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+/// Topic an event is published on
+fn topic_for(prefix: &str, event: &DomainEvent) -> String {
+    format!(
+        "{}/{}/{}",
+        prefix,
+        event.user_id,
+        event.subject().replace('.', "/")
+    )
+}
+
+#[async_trait]
+impl MessageBroker for MqttEventPublisher {
+    async fn publish(&self, event: &DomainEvent) -> DomainResult<()> {
+        let payload = event.encode().map_err(|e| {
+            DomainError::InfrastructureError(format!("Failed to serialize event: {}", e))
+        })?;
+
+        self.client
+            .publish(
+                topic_for(&self.topic_prefix, event),
+                QoS::AtLeastOnce,
+                false,
+                payload,
+            )
+            .await
+            .map_err(|e| DomainError::InfrastructureError(format!("MQTT publish error: {}", e)))
+    }
+
+    async fn subscribe(
+        &self,
+    ) -> DomainResult<Pin<Box<dyn futures_core::Stream<Item = DomainEvent> + Send>>> {
+        Err(DomainError::InfrastructureError(
+            "The MQTT publisher does not support subscriptions".into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notes_domain::Note;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_topics_are_scoped_by_user_and_subject() {
+        let note = Note::new(Uuid::new_v4(), None, "milk, eggs");
+        let event = DomainEvent::note_updated(note.clone());
+
+        assert_eq!(
+            topic_for("knotes", &event),
+            format!("knotes/{}/notes/updated", note.user_id)
+        );
+    }
+}
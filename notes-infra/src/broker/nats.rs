@@ -7,7 +7,8 @@ use std::pin::Pin;
 use async_trait::async_trait;
 use futures_util::StreamExt;
 use k_core::broker::{MessageBroker as CoreBroker, nats::NatsBroker};
-use notes_domain::{DomainError, DomainResult, MessageBroker, Note};
+use notes_domain::events::DomainEvent;
+use notes_domain::{DomainError, DomainResult, MessageBroker};
 
 pub struct NatsMessageBroker {
     inner: NatsBroker,
@@ -21,38 +22,43 @@ impl NatsMessageBroker {
 
 #[async_trait]
 impl MessageBroker for NatsMessageBroker {
-    async fn publish_note_updated(&self, note: &Note) -> DomainResult<()> {
-        let payload = serde_json::to_vec(note).map_err(|e| {
-            DomainError::RepositoryError(format!("Failed to serialize note: {}", e))
+    async fn publish(&self, event: &DomainEvent) -> DomainResult<()> {
+        let payload = event.encode().map_err(|e| {
+            DomainError::InfrastructureError(format!("Failed to serialize event: {}", e))
         })?;
 
         self.inner
-            .publish("notes.updated", payload.into())
+            .publish(event.subject(), payload.into())
             .await
-            .map_err(|e| DomainError::RepositoryError(format!("Failed to publish event: {}", e)))?;
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Failed to publish event: {}", e))
+            })?;
 
         Ok(())
     }
 
-    async fn subscribe_note_updates(
+    async fn subscribe(
         &self,
-    ) -> DomainResult<Pin<Box<dyn futures_core::Stream<Item = Note> + Send>>> {
-        let stream =
-            self.inner.subscribe("notes.updated").await.map_err(|e| {
-                DomainError::RepositoryError(format!("Broker subscribe error: {}", e))
-            })?;
+    ) -> DomainResult<Pin<Box<dyn futures_core::Stream<Item = DomainEvent> + Send>>> {
+        let mut streams = Vec::with_capacity(DomainEvent::SUBJECTS.len());
+        for subject in DomainEvent::SUBJECTS {
+            streams.push(self.inner.subscribe(subject).await.map_err(|e| {
+                DomainError::InfrastructureError(format!("Broker subscribe error: {}", e))
+            })?);
+        }
 
-        // Map generic bytes back to Domain Note
-        let note_stream = stream.filter_map(|bytes| async move {
-            match serde_json::from_slice::<Note>(&bytes) {
-                Ok(note) => Some(note),
-                Err(e) => {
-                    tracing::warn!("Failed to deserialize note from message: {}", e);
-                    None
+        // Map generic bytes back to domain events
+        let event_stream =
+            futures_util::stream::select_all(streams).filter_map(|bytes| async move {
+                match DomainEvent::decode(&bytes) {
+                    Ok(event) => Some(event),
+                    Err(e) => {
+                        tracing::warn!("Skipping undecodable event message: {}", e);
+                        None
+                    }
                 }
-            }
-        });
+            });
 
-        Ok(Box::pin(note_stream))
+        Ok(Box::pin(event_stream))
     }
 }
@@ -0,0 +1,455 @@
+//! Read-through caching for hot repository reads.
+//!
+//! [`CachedNoteRepository`] and [`CachedTagRepository`] wrap other
+//! repositories and cache `find_by_id` lookups and per-user tag lists in a
+//! [`Cache`] backend, invalidating entries whenever they write. Writes made
+//! through a [`UnitOfWork`] must go through [`CachedUnitOfWork`] for the same
+//! reason.
+//!
+//! The in-process moka backend only sees writes from its own process; use the
+//! Redis backend when several processes (API replicas, the worker) write.
+//! Entries also expire after a TTL, which bounds staleness from writes that
+//! bypass the cache (e.g. cascading deletes of a user's data).
+
+#[cfg(feature = "cache-moka")]
+pub mod moka;
+#[cfg(feature = "cache-redis")]
+pub mod redis;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use notes_domain::{
+    DomainResult, Note, NoteCounts, NoteFilter, NoteRepository, NoteVersion, Tag, TagRepository,
+    UnitOfWork, bookmarks::LinkPreview, query::NoteQuery, search::TitleSuggestion,
+};
+
+/// Key/value store holding serialized entities.
+///
+/// Backends swallow their own errors: a failed read is a miss and a failed
+/// write is logged, so an unavailable cache degrades to direct reads.
+#[async_trait]
+pub trait Cache: Send + Sync {
+    async fn get(&self, key: &str) -> Option<String>;
+
+    async fn set(&self, key: &str, value: String);
+
+    async fn remove(&self, keys: &[String]);
+
+    /// Remove every entry whose key starts with `prefix`
+    async fn remove_prefix(&self, prefix: &str);
+}
+
+const NOTE_PREFIX: &str = "note:";
+
+fn note_key(id: Uuid) -> String {
+    format!("{}{}", NOTE_PREFIX, id)
+}
+
+fn user_tags_key(user_id: Uuid) -> String {
+    format!("tags:{}", user_id)
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(cache: &dyn Cache, key: &str) -> Option<T> {
+    let value = cache.get(key).await?;
+    match serde_json::from_str(&value) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!(key, "Discarding undecodable cache entry: {}", e);
+            None
+        }
+    }
+}
+
+async fn set_json<T: serde::Serialize>(cache: &dyn Cache, key: &str, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(json) => cache.set(key, json).await,
+        Err(e) => tracing::warn!(key, "Failed to serialize cache entry: {}", e),
+    }
+}
+
+/// NoteRepository decorator caching notes by ID
+pub struct CachedNoteRepository {
+    inner: Arc<dyn NoteRepository>,
+    cache: Arc<dyn Cache>,
+}
+
+impl CachedNoteRepository {
+    pub fn new(inner: Arc<dyn NoteRepository>, cache: Arc<dyn Cache>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[async_trait]
+impl NoteRepository for CachedNoteRepository {
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Note>> {
+        let key = note_key(id);
+        if let Some(note) = get_json(self.cache.as_ref(), &key).await {
+            return Ok(Some(note));
+        }
+
+        let note = self.inner.find_by_id(id).await?;
+        if let Some(ref note) = note {
+            set_json(self.cache.as_ref(), &key, note).await;
+        }
+        Ok(note)
+    }
+
+    async fn find_by_user(&self, user_id: Uuid, filter: NoteFilter) -> DomainResult<Vec<Note>> {
+        self.inner.find_by_user(user_id, filter).await
+    }
+
+    async fn count_by_user(&self, user_id: Uuid) -> DomainResult<NoteCounts> {
+        self.inner.count_by_user(user_id).await
+    }
+
+    async fn save(&self, note: &Note) -> DomainResult<()> {
+        let result = self.inner.save(note).await;
+        self.cache.remove(&[note_key(note.id)]).await;
+        result
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<u64> {
+        let result = self.inner.delete(id).await;
+        self.cache.remove(&[note_key(id)]).await;
+        result
+    }
+
+    async fn find_trashed_before(&self, cutoff: DateTime<Utc>) -> DomainResult<Vec<Note>> {
+        self.inner.find_trashed_before(cutoff).await
+    }
+
+    async fn search(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+    ) -> DomainResult<Vec<Note>> {
+        self.inner.search(user_id, query, include_archived).await
+    }
+
+    async fn search_versions(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+        limit: usize,
+    ) -> DomainResult<Vec<NoteVersion>> {
+        self.inner
+            .search_versions(user_id, query, include_archived, limit)
+            .await
+    }
+
+    async fn suggest_titles(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<TitleSuggestion>> {
+        self.inner.suggest_titles(user_id, prefix, limit).await
+    }
+
+    async fn query(&self, user_id: Uuid, query: &NoteQuery) -> DomainResult<Vec<Note>> {
+        self.inner.query(user_id, query).await
+    }
+
+    async fn save_version(&self, version: &NoteVersion) -> DomainResult<()> {
+        self.inner.save_version(version).await
+    }
+
+    async fn find_versions_by_note_id(&self, note_id: Uuid) -> DomainResult<Vec<NoteVersion>> {
+        self.inner.find_versions_by_note_id(note_id).await
+    }
+
+    async fn find_latest_version(&self, note_id: Uuid) -> DomainResult<Option<NoteVersion>> {
+        self.inner.find_latest_version(note_id).await
+    }
+
+    async fn reorder_pins(&self, user_id: Uuid, note_ids: &[Uuid]) -> DomainResult<()> {
+        let result = self.inner.reorder_pins(user_id, note_ids).await;
+        let keys: Vec<String> = note_ids.iter().copied().map(note_key).collect();
+        self.cache.remove(&keys).await;
+        result
+    }
+
+    async fn find_stale_link_previews(&self, limit: usize) -> DomainResult<Vec<Note>> {
+        self.inner.find_stale_link_previews(limit).await
+    }
+
+    async fn save_link_previews(
+        &self,
+        note_id: Uuid,
+        previews: &[LinkPreview],
+        as_of: DateTime<Utc>,
+    ) -> DomainResult<()> {
+        let result = self
+            .inner
+            .save_link_previews(note_id, previews, as_of)
+            .await;
+        self.cache.remove(&[note_key(note_id)]).await;
+        result
+    }
+}
+
+/// TagRepository decorator caching each user's tag list.
+///
+/// Notes embed their tags, so writes that change associations drop the
+/// affected note, and renaming or deleting a tag drops all cached notes.
+pub struct CachedTagRepository {
+    inner: Arc<dyn TagRepository>,
+    cache: Arc<dyn Cache>,
+}
+
+impl CachedTagRepository {
+    pub fn new(inner: Arc<dyn TagRepository>, cache: Arc<dyn Cache>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[async_trait]
+impl TagRepository for CachedTagRepository {
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Tag>> {
+        self.inner.find_by_id(id).await
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        let key = user_tags_key(user_id);
+        if let Some(tags) = get_json(self.cache.as_ref(), &key).await {
+            return Ok(tags);
+        }
+
+        let tags = self.inner.find_by_user(user_id).await?;
+        set_json(self.cache.as_ref(), &key, &tags).await;
+        Ok(tags)
+    }
+
+    async fn find_by_prefix(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<Tag>> {
+        self.inner.find_by_prefix(user_id, prefix, limit).await
+    }
+
+    async fn find_by_name(&self, user_id: Uuid, name: &str) -> DomainResult<Option<Tag>> {
+        self.inner.find_by_name(user_id, name).await
+    }
+
+    async fn save(&self, tag: &Tag) -> DomainResult<()> {
+        let result = self.inner.save(tag).await;
+        self.cache.remove(&[user_tags_key(tag.user_id)]).await;
+        self.cache.remove_prefix(NOTE_PREFIX).await;
+        result
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        let tag = self.inner.find_by_id(id).await?;
+        let result = self.inner.delete(id).await;
+        if let Some(tag) = tag {
+            self.cache.remove(&[user_tags_key(tag.user_id)]).await;
+        }
+        self.cache.remove_prefix(NOTE_PREFIX).await;
+        result
+    }
+
+    async fn add_to_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
+        let result = self.inner.add_to_note(tag_id, note_id).await;
+        self.cache.remove(&[note_key(note_id)]).await;
+        result
+    }
+
+    async fn add_many_to_note(&self, tag_ids: &[Uuid], note_id: Uuid) -> DomainResult<()> {
+        let result = self.inner.add_many_to_note(tag_ids, note_id).await;
+        self.cache.remove(&[note_key(note_id)]).await;
+        result
+    }
+
+    async fn remove_from_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
+        let result = self.inner.remove_from_note(tag_id, note_id).await;
+        self.cache.remove(&[note_key(note_id)]).await;
+        result
+    }
+
+    async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<Tag>> {
+        self.inner.find_by_note(note_id).await
+    }
+
+    async fn merge(&self, source_id: Uuid, target_id: Uuid) -> DomainResult<()> {
+        let tag = self.inner.find_by_id(source_id).await?;
+        let result = self.inner.merge(source_id, target_id).await;
+        if let Some(tag) = tag {
+            self.cache.remove(&[user_tags_key(tag.user_id)]).await;
+        }
+        self.cache.remove_prefix(NOTE_PREFIX).await;
+        result
+    }
+
+    async fn find_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        self.inner.find_unused(user_id).await
+    }
+
+    // Unused tags are on no note, so cached notes stay valid
+    async fn delete_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        let result = self.inner.delete_unused(user_id).await;
+        self.cache.remove(&[user_tags_key(user_id)]).await;
+        result
+    }
+
+    async fn reorder(&self, user_id: Uuid, tag_ids: &[Uuid]) -> DomainResult<()> {
+        let result = self.inner.reorder(user_id, tag_ids).await;
+        self.cache.remove(&[user_tags_key(user_id)]).await;
+        self.cache.remove_prefix(NOTE_PREFIX).await;
+        result
+    }
+}
+
+/// UnitOfWork decorator dropping the cached copy of committed notes
+pub struct CachedUnitOfWork {
+    inner: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn Cache>,
+}
+
+impl CachedUnitOfWork {
+    pub fn new(inner: Arc<dyn UnitOfWork>, cache: Arc<dyn Cache>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for CachedUnitOfWork {
+    async fn commit_note(&self, note: &Note, version: Option<&NoteVersion>) -> DomainResult<()> {
+        let result = self.inner.commit_note(note, version).await;
+        self.cache.remove(&[note_key(note.id)]).await;
+        result
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::{SqliteNoteRepository, SqliteTagRepository, SqliteUserRepository};
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, NoteTitle, TagName, User, UserRepository};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryCache {
+        entries: Mutex<HashMap<String, String>>,
+    }
+
+    #[async_trait]
+    impl Cache for MemoryCache {
+        async fn get(&self, key: &str) -> Option<String> {
+            self.entries.lock().unwrap().get(key).cloned()
+        }
+
+        async fn set(&self, key: &str, value: String) {
+            self.entries.lock().unwrap().insert(key.to_string(), value);
+        }
+
+        async fn remove(&self, keys: &[String]) {
+            let mut entries = self.entries.lock().unwrap();
+            for key in keys {
+                entries.remove(key);
+            }
+        }
+
+        async fn remove_prefix(&self, prefix: &str) {
+            self.entries
+                .lock()
+                .unwrap()
+                .retain(|key, _| !key.starts_with(prefix));
+        }
+    }
+
+    async fn setup() -> (Arc<SqliteNoteRepository>, Arc<SqliteTagRepository>, User) {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        let pool = pool.sqlite_pool().unwrap().clone();
+
+        let user = User::new("test|cache", Email::try_from("cache@example.com").unwrap());
+        SqliteUserRepository::new(pool.clone())
+            .save(&user)
+            .await
+            .unwrap();
+        (
+            Arc::new(SqliteNoteRepository::new(pool.clone())),
+            Arc::new(SqliteTagRepository::new(pool)),
+            user,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_note_reads_are_cached_until_written() {
+        let (inner, _, user) = setup().await;
+        let cache = Arc::new(MemoryCache::default());
+        let repo = CachedNoteRepository::new(inner.clone(), cache);
+
+        let mut note = Note::new(user.id, NoteTitle::try_from("Cached").ok(), "v1");
+        repo.save(&note).await.unwrap();
+        assert_eq!(
+            repo.find_by_id(note.id).await.unwrap().unwrap().content,
+            "v1"
+        );
+
+        // A write behind the decorator's back is not seen until invalidated
+        note.content = "v2".to_string();
+        inner.save(&note).await.unwrap();
+        assert_eq!(
+            repo.find_by_id(note.id).await.unwrap().unwrap().content,
+            "v1"
+        );
+
+        note.content = "v3".to_string();
+        repo.save(&note).await.unwrap();
+        assert_eq!(
+            repo.find_by_id(note.id).await.unwrap().unwrap().content,
+            "v3"
+        );
+
+        repo.delete(note.id).await.unwrap();
+        assert!(repo.find_by_id(note.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tag_writes_invalidate_tag_lists_and_notes() {
+        let (inner_notes, inner_tags, user) = setup().await;
+        let cache: Arc<dyn Cache> = Arc::new(MemoryCache::default());
+        let notes = CachedNoteRepository::new(inner_notes, cache.clone());
+        let tags = CachedTagRepository::new(inner_tags, cache);
+
+        let note = Note::new(user.id, None, "content");
+        notes.save(&note).await.unwrap();
+        assert!(tags.find_by_user(user.id).await.unwrap().is_empty());
+        assert!(
+            notes
+                .find_by_id(note.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .tags
+                .is_empty()
+        );
+
+        let mut tag = Tag::new(TagName::try_from("work").unwrap(), user.id);
+        tags.save(&tag).await.unwrap();
+        tags.add_to_note(tag.id, note.id).await.unwrap();
+        assert_eq!(tags.find_by_user(user.id).await.unwrap().len(), 1);
+        assert_eq!(
+            notes.find_by_id(note.id).await.unwrap().unwrap().tags,
+            vec![tag.clone()]
+        );
+
+        tag.name = TagName::try_from("office").unwrap();
+        tags.save(&tag).await.unwrap();
+        let cached = notes.find_by_id(note.id).await.unwrap().unwrap();
+        assert_eq!(cached.tags[0].name_str(), "office");
+    }
+}
@@ -0,0 +1,50 @@
+//! In-process cache backed by moka
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::Cache;
+
+/// Bounded in-process cache with a time-to-live per entry
+pub struct MokaCache {
+    inner: moka::future::Cache<String, String>,
+}
+
+impl MokaCache {
+    pub fn new(max_entries: u64, ttl: Duration) -> Self {
+        let inner = moka::future::Cache::builder()
+            .max_capacity(max_entries)
+            .time_to_live(ttl)
+            .support_invalidation_closures()
+            .build();
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl Cache for MokaCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.inner.get(key).await
+    }
+
+    async fn set(&self, key: &str, value: String) {
+        self.inner.insert(key.to_string(), value).await;
+    }
+
+    async fn remove(&self, keys: &[String]) {
+        for key in keys {
+            self.inner.invalidate(key).await;
+        }
+    }
+
+    async fn remove_prefix(&self, prefix: &str) {
+        let prefix = prefix.to_string();
+        if let Err(e) = self
+            .inner
+            .invalidate_entries_if(move |key, _| key.starts_with(&prefix))
+        {
+            tracing::warn!("Failed to invalidate cache entries: {}", e);
+        }
+    }
+}
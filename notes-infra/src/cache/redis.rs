@@ -0,0 +1,92 @@
+//! Shared cache backed by Redis
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+
+use super::Cache;
+
+/// Namespace keeping K-Notes entries apart from other users of the server
+const KEY_PREFIX: &str = "k-notes:";
+
+/// Cache shared by every process connected to the same Redis server
+pub struct RedisCache {
+    conn: ConnectionManager,
+    ttl: Duration,
+}
+
+impl RedisCache {
+    pub async fn connect(url: &str, ttl: Duration) -> redis::RedisResult<Self> {
+        let client = redis::Client::open(url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(Self { conn, ttl })
+    }
+
+    fn key(key: &str) -> String {
+        format!("{}{}", KEY_PREFIX, key)
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut conn = self.conn.clone();
+        match conn.get(Self::key(key)).await {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!(key, "Cache read failed: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: &str, value: String) {
+        let mut conn = self.conn.clone();
+        let result: redis::RedisResult<()> = conn
+            .set_ex(Self::key(key), value, self.ttl.as_secs().max(1))
+            .await;
+        if let Err(e) = result {
+            tracing::warn!(key, "Cache write failed: {}", e);
+        }
+    }
+
+    async fn remove(&self, keys: &[String]) {
+        if keys.is_empty() {
+            return;
+        }
+        let keys: Vec<String> = keys.iter().map(|k| Self::key(k)).collect();
+        let mut conn = self.conn.clone();
+        let result: redis::RedisResult<()> = conn.del(&keys).await;
+        if let Err(e) = result {
+            tracing::warn!("Cache invalidation failed: {}", e);
+        }
+    }
+
+    async fn remove_prefix(&self, prefix: &str) {
+        let mut conn = self.conn.clone();
+        let pattern = format!("{}*", Self::key(prefix));
+        let keys: Vec<String> = match conn.scan_match(&pattern).await {
+            Ok(mut iter) => {
+                let mut keys = Vec::new();
+                while let Some(key) = iter.next_item().await {
+                    keys.push(key);
+                }
+                keys
+            }
+            Err(e) => {
+                tracing::warn!("Cache invalidation failed: {}", e);
+                return;
+            }
+        };
+
+        if keys.is_empty() {
+            return;
+        }
+        let result: redis::RedisResult<()> = conn.del(&keys).await;
+        if let Err(e) = result {
+            tracing::warn!("Cache invalidation failed: {}", e);
+        }
+    }
+}
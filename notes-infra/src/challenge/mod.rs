@@ -0,0 +1,7 @@
+//! Challenge verifier adapters.
+//!
+//! This module provides implementations of the `ChallengeVerifier` port.
+
+#[cfg(feature = "challenge-captcha")]
+pub mod captcha;
+pub mod pow;
@@ -0,0 +1,176 @@
+//! Proof-of-work challenge adapter
+//!
+//! A self-hosted alternative to captchas that needs no third party. The
+//! server issues a signed challenge and the client searches for a suffix
+//! whose SHA-256 hash starts with enough zero bits. Solving takes the browser
+//! a moment but makes bulk signups expensive.
+//!
+//! Challenges are stateless and signed with HMAC, so any instance holding the
+//! same secret can verify them. Solved challenges are remembered until they
+//! expire, so each one is accepted only once per process.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+
+use notes_domain::{ChallengeTicket, ChallengeVerifier, DomainResult, Signer};
+
+use crate::signing::HmacSigner;
+
+/// Leading zero bits required when none are configured; takes a browser
+/// about a second
+pub const DEFAULT_POW_DIFFICULTY: u8 = 18;
+
+/// How long an issued challenge can be solved and submitted
+const CHALLENGE_TTL_MINUTES: i64 = 10;
+
+pub struct ProofOfWorkVerifier {
+    signer: HmacSigner,
+    difficulty: u8,
+    /// Solved challenges and when they expire
+    spent: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl ProofOfWorkVerifier {
+    /// Sign challenges with `secret` as [`HmacSigner::new`] does
+    pub fn new(secret: Option<&str>, difficulty: u8) -> Self {
+        Self {
+            signer: HmacSigner::new(secret),
+            difficulty: difficulty.min(32),
+            spent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Expiry and difficulty of a challenge issued with this key, or `None`
+    /// if it was not
+    fn open(&self, challenge: &str) -> Option<(DateTime<Utc>, u8)> {
+        // <expires>.<difficulty>.<nonce>.<signature>
+        let (payload, signature) = challenge.rsplit_once('.')?;
+        if !self.signer.verify(payload, signature) {
+            return None;
+        }
+
+        let mut parts = payload.split('.');
+        let expires = DateTime::from_timestamp(parts.next()?.parse().ok()?, 0)?;
+        let difficulty = parts.next()?.parse().ok()?;
+        Some((expires, difficulty))
+    }
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        if *byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
+
+#[async_trait]
+impl ChallengeVerifier for ProofOfWorkVerifier {
+    fn provider(&self) -> &'static str {
+        "pow"
+    }
+
+    async fn issue(&self) -> DomainResult<Option<ChallengeTicket>> {
+        let expires_at = Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES);
+        let payload = format!(
+            "{}.{}.{}",
+            expires_at.timestamp(),
+            self.difficulty,
+            hex::encode(rand::random::<[u8; 16]>())
+        );
+        let signature = self.signer.sign(&payload);
+
+        Ok(Some(ChallengeTicket {
+            challenge: format!("{}.{}", payload, signature),
+            difficulty: self.difficulty,
+            expires_at,
+        }))
+    }
+
+    /// Expects `<challenge>:<solution>`, where the SHA-256 hash of that whole
+    /// string has the challenge's number of leading zero bits
+    async fn verify(&self, response: &str) -> DomainResult<bool> {
+        let Some((challenge, _solution)) = response.rsplit_once(':') else {
+            return Ok(false);
+        };
+        // The difficulty is signed, so challenges issued before the
+        // configured difficulty changed stay valid until they expire
+        let Some((expires_at, difficulty)) = self.open(challenge) else {
+            return Ok(false);
+        };
+        let now = Utc::now();
+        if expires_at <= now {
+            return Ok(false);
+        }
+
+        if leading_zero_bits(&Sha256::digest(response.as_bytes())) < u32::from(difficulty) {
+            return Ok(false);
+        }
+
+        let mut spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        spent.retain(|_, expires| *expires > now);
+        Ok(spent.insert(challenge.to_string(), expires_at).is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(challenge: &str, difficulty: u8) -> String {
+        (0u64..)
+            .map(|n| format!("{}:{}", challenge, n))
+            .find(|response| {
+                leading_zero_bits(&Sha256::digest(response.as_bytes())) >= u32::from(difficulty)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0xff]), 0);
+        assert_eq!(leading_zero_bits(&[0x00, 0x10]), 11);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00]), 16);
+    }
+
+    #[tokio::test]
+    async fn test_solved_challenge_is_accepted_once() {
+        let verifier = ProofOfWorkVerifier::new(Some("secret"), 8);
+        let ticket = verifier.issue().await.unwrap().unwrap();
+        let response = solve(&ticket.challenge, ticket.difficulty);
+
+        assert!(verifier.verify(&response).await.unwrap());
+        assert!(!verifier.verify(&response).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unsolved_and_forged_challenges() {
+        let verifier = ProofOfWorkVerifier::new(Some("secret"), 8);
+        let ticket = verifier.issue().await.unwrap().unwrap();
+
+        assert!(!verifier.verify(&ticket.challenge).await.unwrap());
+        let unsolved = (0u64..)
+            .map(|n| format!("{}:{}", ticket.challenge, n))
+            .find(|response| leading_zero_bits(&Sha256::digest(response.as_bytes())) < 8)
+            .unwrap();
+        assert!(!verifier.verify(&unsolved).await.unwrap());
+
+        // Lowering the difficulty invalidates the signature
+        let forged = ticket.challenge.replacen(".8.", ".0.", 1);
+        assert!(!verifier.verify(&format!("{}:0", forged)).await.unwrap());
+
+        // Challenges from another key are not accepted
+        let other = ProofOfWorkVerifier::new(Some("other"), 8);
+        let response = solve(&ticket.challenge, ticket.difficulty);
+        assert!(!other.verify(&response).await.unwrap());
+    }
+}
@@ -1,6 +1,171 @@
-//! Database connection pool management
+//! Database connection pool management and error mapping
 
-use k_core::db::DatabasePool;
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use k_core::db::{DatabaseConfig, DatabasePool};
+use notes_domain::{DomainError, DomainResult, RepositoryError};
+use sqlx::error::ErrorKind;
+use tokio::sync::Semaphore;
+
+/// How long SQLite itself waits for a lock before reporting SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Attempts after the first for a write that keeps failing with SQLITE_BUSY
+const MAX_BUSY_RETRIES: u32 = 4;
+
+/// Backoff before the first retry, doubled on every further attempt
+const BUSY_BACKOFF: Duration = Duration::from_millis(25);
+
+/// SQLite allows one writer at a time; queueing writers here instead of in
+/// SQLite's busy handler keeps in-process writes from failing each other
+static WRITE_GATE: LazyLock<Semaphore> = LazyLock::new(|| Semaphore::new(1));
+
+/// Connect to the database, tuning SQLite for concurrent access.
+///
+/// File databases use WAL (readers do not block the writer),
+/// `synchronous = NORMAL` and a busy timeout. Other databases, including
+/// in-memory SQLite, are connected unchanged.
+pub async fn connect(config: &DatabaseConfig) -> Result<DatabasePool, sqlx::Error> {
+    #[cfg(feature = "sqlite")]
+    if config.url.starts_with("sqlite:") && !config.url.contains(":memory:") {
+        use sqlx::sqlite::{
+            SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous,
+        };
+        use std::str::FromStr;
+
+        let options = SqliteConnectOptions::from_str(&config.url)?
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(BUSY_TIMEOUT)
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(config.acquire_timeout)
+            .connect_with(options)
+            .await?;
+        return Ok(DatabasePool::Sqlite(pool));
+    }
+
+    k_core::db::connect(config).await
+}
+
+/// Connect to a read-only replica of the database.
+///
+/// SQLite replicas (e.g. LiteFS replica nodes) are opened read-only and left
+/// in whatever journal mode the primary chose; migrations are never run on
+/// them. Other databases are connected unchanged.
+pub async fn connect_replica(config: &DatabaseConfig) -> Result<DatabasePool, sqlx::Error> {
+    #[cfg(feature = "sqlite")]
+    if config.url.starts_with("sqlite:") && !config.url.contains(":memory:") {
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+        use std::str::FromStr;
+
+        let options = SqliteConnectOptions::from_str(&config.url)?
+            .read_only(true)
+            .busy_timeout(BUSY_TIMEOUT);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(config.acquire_timeout)
+            .connect_with(options)
+            .await?;
+        return Ok(DatabasePool::Sqlite(pool));
+    }
+
+    k_core::db::connect(config).await
+}
+
+/// Run a write with the process-wide writer permit held, retrying with
+/// jittered exponential backoff while the database reports it is busy.
+///
+/// `op` is called once per attempt, so transactions must be begun inside it.
+pub(crate) async fn write<T, F, Fut>(mut op: F) -> DomainResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = DomainResult<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        let result = {
+            let _permit = WRITE_GATE
+                .acquire()
+                .await
+                .expect("write gate is never closed");
+            op().await
+        };
+
+        match result {
+            Err(DomainError::RepositoryError(ref e))
+                if e.is_retryable() && attempt < MAX_BUSY_RETRIES =>
+            {
+                let backoff = BUSY_BACKOFF * 2u32.pow(attempt);
+                // Up to 50% jitter so competing writers do not retry in lockstep
+                let jitter = backoff.mul_f64(rand::random::<f64>() / 2.0);
+                attempt += 1;
+                tracing::debug!(attempt, "Database busy, retrying write: {}", e);
+                tokio::time::sleep(backoff + jitter).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether the database reported SQLITE_BUSY or SQLITE_LOCKED
+fn is_busy(error: &dyn sqlx::error::DatabaseError) -> bool {
+    // Extended result codes keep the primary code in the low byte
+    error
+        .code()
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| matches!(code & 0xff, 5 | 6))
+}
+
+/// Classify a sqlx error so callers can tell conflicts from outages
+pub(crate) fn map_sqlx_error(error: sqlx::Error) -> DomainError {
+    let message = error.to_string();
+    let kind = match &error {
+        sqlx::Error::Database(db) => match db.kind() {
+            ErrorKind::UniqueViolation | ErrorKind::ForeignKeyViolation => {
+                RepositoryError::Conflict(message)
+            }
+            _ if is_busy(db.as_ref()) => RepositoryError::Connection(message),
+            _ => RepositoryError::Other(message),
+        },
+        sqlx::Error::RowNotFound => RepositoryError::NotFound(message),
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => RepositoryError::Connection(message),
+        sqlx::Error::ColumnDecode { .. }
+        | sqlx::Error::ColumnNotFound(_)
+        | sqlx::Error::Decode(_)
+        | sqlx::Error::Encode(_)
+        | sqlx::Error::TypeNotFound { .. } => RepositoryError::Serialization(message),
+        _ => RepositoryError::Other(message),
+    };
+    DomainError::RepositoryError(kind)
+}
+
+/// Error for stored data that does not decode into a valid domain value
+pub(crate) fn decode_error(message: impl Into<String>) -> DomainError {
+    DomainError::RepositoryError(RepositoryError::Serialization(message.into()))
+}
+
+/// Escape `%`, `_` and backslashes for a `LIKE ... ESCAPE` pattern
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Exclusive upper bound of the strings starting with `prefix`, for
+/// index-friendly `col >= prefix AND col < bound` range scans
+pub(crate) fn prefix_upper_bound(prefix: &str) -> String {
+    format!("{}{}", prefix, char::MAX)
+}
 
 /// Run database migrations
 pub async fn run_migrations(pool: &DatabasePool) -> Result<(), sqlx::Error> {
@@ -32,6 +197,62 @@ mod tests {
         assert!(pool.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_write_retries_busy_errors() {
+        let mut calls = 0;
+        let result = write(|| {
+            calls += 1;
+            let busy = calls < 3;
+            async move {
+                if busy {
+                    return Err(DomainError::RepositoryError(RepositoryError::Connection(
+                        "database is locked".to_string(),
+                    )));
+                }
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_write_does_not_retry_conflicts() {
+        let mut calls = 0;
+        let result: DomainResult<()> = write(|| {
+            calls += 1;
+            async {
+                Err(DomainError::RepositoryError(RepositoryError::Conflict(
+                    "UNIQUE constraint failed".to_string(),
+                )))
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_enables_wal_for_file_databases() {
+        let path = std::env::temp_dir().join(format!("k-notes-{}.db", uuid::Uuid::new_v4()));
+        let config = DatabaseConfig::new(format!("sqlite:{}?mode=rwc", path.display()));
+        let pool = connect(&config).await.unwrap();
+
+        let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(pool.sqlite_pool().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        pool.sqlite_pool().unwrap().close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
     #[tokio::test]
     async fn test_run_migrations() {
         let config = k_core::db::DatabaseConfig::in_memory();
@@ -0,0 +1,78 @@
+//! Adapter for Kroki servers
+//!
+//! Works with a self-hosted Kroki server (with its Mermaid companion for
+//! Mermaid diagrams) as well as the public `https://kroki.io`, by posting
+//! diagram sources to `/{language}/svg`.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use notes_domain::diagrams::DiagramKind;
+use notes_domain::{DiagramRenderer, DomainError, DomainResult};
+
+/// Draws diagrams with a Kroki server
+pub struct KrokiDiagramRenderer {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl KrokiDiagramRenderer {
+    /// `base_url` is the server root, such as `http://localhost:8000`
+    pub fn new(base_url: &str, timeout: Duration) -> DomainResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+            })?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn svg_url(&self, kind: DiagramKind) -> String {
+        format!("{}/{}/svg", self.base_url, kind.name())
+    }
+}
+
+#[async_trait]
+impl DiagramRenderer for KrokiDiagramRenderer {
+    async fn render_svg(&self, kind: DiagramKind, source: &str) -> DomainResult<String> {
+        self.client
+            .post(self.svg_url(kind))
+            .header(reqwest::header::CONTENT_TYPE, "text/plain")
+            .body(source.to_string())
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Diagram rendering failed: {}", e))
+            })?
+            .text()
+            .await
+            .map_err(|e| DomainError::InfrastructureError(format!("Invalid diagram: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagrams_are_posted_by_language() {
+        let renderer =
+            KrokiDiagramRenderer::new("http://kroki:8000/", Duration::from_secs(5)).unwrap();
+
+        assert_eq!(
+            renderer.svg_url(DiagramKind::Mermaid),
+            "http://kroki:8000/mermaid/svg"
+        );
+        assert_eq!(
+            renderer.svg_url(DiagramKind::PlantUml),
+            "http://kroki:8000/plantuml/svg"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! Diagram rendering adapters
+//!
+//! This module provides implementations of the `DiagramRenderer` port.
+
+pub mod kroki;
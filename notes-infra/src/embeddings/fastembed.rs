@@ -1,29 +1,104 @@
 use async_trait::async_trait;
-use k_core::ai::embeddings::FastEmbedAdapter as CoreFastEmbed;
+use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use notes_domain::errors::{DomainError, DomainResult};
 use notes_domain::ports::EmbeddingGenerator;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, TryLockError};
+use tokio::sync::Semaphore;
 
+/// Texts embedded per inference call; larger batches are split
+const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// Recorded with stored vectors; change it when switching models
+const MODEL_NAME: &str = "fastembed/all-MiniLM-L6-v2";
+
+/// Local ONNX embeddings using fastembed directly, since k-core's adapter
+/// embeds one text per call.
+///
+/// Holds a pool of loaded model instances so that several embedding calls
+/// run at once, each on a blocking thread, instead of queueing behind one
+/// model while a long document embeds.
 pub struct FastEmbedAdapter {
-    inner: Arc<CoreFastEmbed>,
+    models: Arc<[Mutex<TextEmbedding>]>,
+    /// One permit per model, so a holder is guaranteed a free instance
+    permits: Arc<Semaphore>,
+    batch_size: usize,
 }
 
 impl FastEmbedAdapter {
-    pub fn new(inner: CoreFastEmbed) -> Self {
-        Self {
-            inner: Arc::new(inner),
-        }
+    /// Load `pool_size` instances of the model (at least one)
+    pub fn new(pool_size: usize) -> DomainResult<Self> {
+        let pool_size = pool_size.max(1);
+        let models = (0..pool_size)
+            .map(|_| {
+                TextEmbedding::try_new(InitOptions::new(EmbeddingModel::AllMiniLML6V2))
+                    .map(Mutex::new)
+                    .map_err(|e| {
+                        DomainError::InfrastructureError(format!(
+                            "Failed to load embedding model: {}",
+                            e
+                        ))
+                    })
+            })
+            .collect::<DomainResult<Vec<_>>>()?;
+
+        Ok(Self {
+            models: models.into(),
+            permits: Arc::new(Semaphore::new(pool_size)),
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    /// Run inference on a free model off the async runtime; the ONNX session
+    /// is CPU bound
+    async fn embed(&self, texts: Vec<String>) -> DomainResult<Vec<Vec<f32>>> {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("model pool semaphore is never closed");
+        let models = self.models.clone();
+        let batch_size = self.batch_size;
+
+        tokio::task::spawn_blocking(move || {
+            // Held until inference ends, even if the caller stops waiting
+            let _permit = permit;
+            let mut model = models
+                .iter()
+                .find_map(|model| match model.try_lock() {
+                    Ok(guard) => Some(guard),
+                    // A panic mid-inference leaves the session usable
+                    Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+                    Err(TryLockError::WouldBlock) => None,
+                })
+                .expect("a permit guarantees a free model");
+            model.embed(texts, Some(batch_size))
+        })
+        .await
+        .map_err(|e| DomainError::InfrastructureError(format!("Embedding task failed: {}", e)))?
+        .map_err(|e| {
+            DomainError::InfrastructureError(format!("Embedding generation failed: {}", e))
+        })
     }
 }
 
 #[async_trait]
 impl EmbeddingGenerator for FastEmbedAdapter {
     async fn generate_embedding(&self, text: &str) -> DomainResult<Vec<f32>> {
-        self.inner
-            .generate_embedding_async(text)
-            .await
-            .map_err(|e| {
-                DomainError::InfrastructureError(format!("Embedding generation failed: {}", e))
-            })
+        self.embed(vec![text.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| DomainError::InfrastructureError("Model returned no embedding".into()))
+    }
+
+    async fn generate_embeddings(&self, texts: &[String]) -> DomainResult<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.embed(texts.to_vec()).await
+    }
+
+    fn model_name(&self) -> &str {
+        MODEL_NAME
     }
 }
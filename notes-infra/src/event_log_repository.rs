@@ -0,0 +1,176 @@
+//! SQLite implementation of EventLogRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{
+    DomainResult, EventLogRepository,
+    event_log::{LoggedEvent, LoggedEventKind},
+};
+
+/// SQLite adapter for EventLogRepository
+pub struct SqliteEventLogRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteEventLogRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct EventRow {
+    id: String,
+    user_id: String,
+    data: String,
+    occurred_at: String,
+}
+
+fn parse_uuid(s: &str) -> DomainResult<Uuid> {
+    Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))
+}
+
+impl EventRow {
+    fn try_into_event(self) -> DomainResult<LoggedEvent> {
+        let kind: LoggedEventKind = serde_json::from_str(&self.data)
+            .map_err(|e| decode_error(format!("Invalid event data: {}", e)))?;
+
+        Ok(LoggedEvent {
+            id: parse_uuid(&self.id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            occurred_at: DateTime::parse_from_rfc3339(&self.occurred_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?,
+            kind,
+        })
+    }
+}
+
+#[async_trait]
+impl EventLogRepository for SqliteEventLogRepository {
+    async fn append(&self, event: &LoggedEvent) -> DomainResult<()> {
+        let data = serde_json::to_string(&event.kind)
+            .map_err(|e| decode_error(format!("Failed to encode event: {}", e)))?;
+        let data = data.as_str();
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO event_log (id, user_id, event_type, note_id, data, occurred_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(event.id.to_string())
+            .bind(event.user_id.to_string())
+            .bind(event.kind.as_str())
+            .bind(event.kind.note_id().map(|id| id.to_string()))
+            .bind(data)
+            .bind(event.occurred_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        before: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> DomainResult<Vec<LoggedEvent>> {
+        // RFC 3339 timestamps in UTC compare correctly as strings
+        let rows: Vec<EventRow> = sqlx::query_as(
+            r#"
+            SELECT id, user_id, data, occurred_at FROM event_log
+            WHERE user_id = ? AND (? IS NULL OR occurred_at < ?)
+            ORDER BY occurred_at DESC, id
+            LIMIT ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(before.map(|t| t.to_rfc3339()))
+        .bind(before.map(|t| t.to_rfc3339()))
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(EventRow::try_into_event).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use chrono::Duration;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool, subject: &str, email: &str) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(subject, Email::try_from(email).unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_find_by_user_pages_newest_first() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool, "test|user", "user@example.com").await;
+        let other = create_test_user(&pool, "test|other", "other@example.com").await;
+        let repo = SqliteEventLogRepository::new(pool);
+
+        let note_id = Uuid::new_v4();
+        let start = Utc::now() - Duration::minutes(10);
+        let mut events = Vec::new();
+        for (i, kind) in [
+            LoggedEventKind::NoteCreated {
+                note_id,
+                title: Some("Plan".to_string()),
+            },
+            LoggedEventKind::NoteTagged {
+                note_id,
+                tag: "work".to_string(),
+            },
+            LoggedEventKind::NoteArchived { note_id },
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut event = LoggedEvent::new(user.id, kind);
+            event.occurred_at = start + Duration::minutes(i as i64);
+            repo.append(&event).await.unwrap();
+            events.push(event);
+        }
+        repo.append(&LoggedEvent::new(
+            other.id,
+            LoggedEventKind::NotePinned { note_id },
+        ))
+        .await
+        .unwrap();
+
+        let page = repo.find_by_user(user.id, None, 2).await.unwrap();
+        assert_eq!(page, vec![events[2].clone(), events[1].clone()]);
+
+        let rest = repo
+            .find_by_user(user.id, Some(page[1].occurred_at), 2)
+            .await
+            .unwrap();
+        assert_eq!(rest, vec![events[0].clone()]);
+    }
+}
@@ -1,19 +1,34 @@
 use std::sync::Arc;
 
+use crate::cache::{Cache, CachedNoteRepository, CachedTagRepository, CachedUnitOfWork};
+use crate::replica::{ReplicatedNoteRepository, ReplicatedTagRepository, ReplicatedUserRepository};
 #[cfg(feature = "sqlite")]
-use crate::{SqliteNoteRepository, SqliteTagRepository, SqliteUserRepository};
+use crate::{
+    SqliteAnnouncementRepository, SqliteAudioUploadRepository, SqliteBoardRepository,
+    SqliteEventLogRepository, SqliteHousekeepingRepository, SqliteImpersonationRepository,
+    SqliteInstanceSettingsRepository, SqliteInvitationRepository, SqliteJobRepository,
+    SqliteLegalRepository, SqliteNoteIssueRepository, SqliteNoteRelationRepository,
+    SqliteNoteRepository, SqliteSearchHistoryRepository, SqliteTagAliasRepository,
+    SqliteTagRepository, SqliteUnitOfWork, SqliteUserRepository,
+};
 use k_core::db::DatabasePool;
 use k_core::session::store::InfraSessionStore;
-use notes_domain::{NoteRepository, TagRepository, UserRepository};
+use notes_domain::{
+    AnnouncementRepository, AudioUploadRepository, BoardRepository, EventLogRepository,
+    HousekeepingRepository, ImpersonationRepository, InstanceSettingsRepository,
+    InvitationRepository, JobRepository, LegalRepository, NoteIssueRepository,
+    NoteRelationRepository, NoteRepository, SearchHistoryRepository, TagAliasRepository,
+    TagRepository, UnitOfWork, UserRepository,
+};
 
+#[cfg(feature = "broker-mqtt")]
+pub use crate::broker::mqtt::MqttConfig;
 #[cfg(feature = "smart-features")]
 use crate::embeddings::fastembed::FastEmbedAdapter;
 #[cfg(feature = "smart-features")]
 use crate::vector::qdrant::QdrantVectorAdapter;
 #[cfg(feature = "smart-features")]
-use k_core::ai::{
-    embeddings::FastEmbedAdapter as CoreFastEmbed, qdrant::QdrantAdapter as CoreQdrant,
-};
+pub use crate::vector::qdrant::{QdrantConfig, VectorDistance};
 #[cfg(feature = "smart-features")]
 use k_core::broker::nats::NatsBroker;
 
@@ -32,26 +47,47 @@ pub type FactoryResult<T> = anyhow::Result<T>;
 #[cfg(feature = "smart-features")]
 #[derive(Debug, Clone)]
 pub enum EmbeddingProvider {
-    FastEmbed,
+    /// Local fastembed model, with `pool_size` instances loaded so that
+    /// several texts embed concurrently
+    FastEmbed { pool_size: usize },
     // Ollama(String), // Url
     // OpenAI(String), // ApiKey
 }
 
+#[cfg(feature = "smart-features")]
+impl EmbeddingProvider {
+    /// How many embedding calls the provider can serve at once
+    pub fn concurrency(&self) -> usize {
+        match self {
+            EmbeddingProvider::FastEmbed { pool_size } => (*pool_size).max(1),
+        }
+    }
+}
+
 #[cfg(feature = "smart-features")]
 #[derive(Debug, Clone)]
 pub enum VectorProvider {
-    Qdrant { url: String, collection: String },
+    Qdrant(QdrantConfig),
     // InMemory,
 }
 
+#[cfg(feature = "smart-features")]
+impl VectorProvider {
+    /// Dimension of the stored vectors, which embeddings must match
+    pub fn dimension(&self) -> u64 {
+        match self {
+            VectorProvider::Qdrant(config) => config.vector_size,
+        }
+    }
+}
+
 #[cfg(feature = "smart-features")]
 pub async fn build_embedding_generator(
     provider: &EmbeddingProvider,
 ) -> FactoryResult<Arc<dyn notes_domain::ports::EmbeddingGenerator>> {
     match provider {
-        EmbeddingProvider::FastEmbed => {
-            let core_embed = CoreFastEmbed::new()?;
-            Ok(Arc::new(FastEmbedAdapter::new(core_embed)))
+        EmbeddingProvider::FastEmbed { pool_size } => {
+            Ok(Arc::new(FastEmbedAdapter::new(*pool_size)?))
         }
     }
 }
@@ -61,9 +97,8 @@ pub async fn build_vector_store(
     provider: &VectorProvider,
 ) -> FactoryResult<Arc<dyn notes_domain::ports::VectorStore>> {
     match provider {
-        VectorProvider::Qdrant { url, collection } => {
-            let core_qdrant = CoreQdrant::new(url, collection)?;
-            let adapter = QdrantVectorAdapter::new(core_qdrant);
+        VectorProvider::Qdrant(config) => {
+            let adapter = QdrantVectorAdapter::new(config.clone())?;
             adapter.init().await.map_err(|e| anyhow::anyhow!(e))?;
             Ok(Arc::new(adapter))
         }
@@ -76,6 +111,9 @@ pub enum BrokerProvider {
     /// NATS message broker (requires `broker-nats` feature).
     #[cfg(feature = "broker-nats")]
     Nats { url: String },
+    /// Publish-only MQTT mirror for home automation (requires `broker-mqtt` feature).
+    #[cfg(feature = "broker-mqtt")]
+    Mqtt(MqttConfig),
     /// No message broker (messaging disabled).
     None,
 }
@@ -92,10 +130,559 @@ pub async fn build_message_broker(
             let adapter = crate::broker::nats::NatsMessageBroker::new(core_broker);
             Ok(Some(Arc::new(adapter)))
         }
+        #[cfg(feature = "broker-mqtt")]
+        BrokerProvider::Mqtt(config) => Ok(Some(Arc::new(
+            crate::broker::mqtt::MqttEventPublisher::connect(config),
+        ))),
         BrokerProvider::None => Ok(None),
     }
 }
 
+/// Copy events published to `primary` to `mirror` as well; either may be
+/// absent.
+pub fn mirror_message_broker(
+    primary: Option<Arc<dyn notes_domain::MessageBroker>>,
+    mirror: Option<Arc<dyn notes_domain::MessageBroker>>,
+) -> Option<Arc<dyn notes_domain::MessageBroker>> {
+    match (primary, mirror) {
+        #[cfg(any(feature = "broker-nats", feature = "broker-mqtt"))]
+        (Some(primary), Some(mirror)) => Some(Arc::new(
+            crate::broker::mirror::MirroredMessageBroker::new(primary, mirror),
+        )),
+        (primary, mirror) => primary.or(mirror),
+    }
+}
+
+/// Configuration for read caching providers.
+#[derive(Debug, Clone)]
+pub enum CacheProvider {
+    /// In-process cache (requires `cache-moka` feature).
+    #[cfg(feature = "cache-moka")]
+    Moka {
+        max_entries: u64,
+        ttl: std::time::Duration,
+    },
+    /// Redis cache shared between processes (requires `cache-redis` feature).
+    #[cfg(feature = "cache-redis")]
+    Redis {
+        url: String,
+        ttl: std::time::Duration,
+    },
+    /// Caching disabled.
+    None,
+}
+
+/// Build a cache based on the provider configuration.
+/// Returns `None` if `CacheProvider::None` is specified.
+pub async fn build_cache(provider: &CacheProvider) -> FactoryResult<Option<Arc<dyn Cache>>> {
+    match provider {
+        #[cfg(feature = "cache-moka")]
+        CacheProvider::Moka { max_entries, ttl } => Ok(Some(Arc::new(
+            crate::cache::moka::MokaCache::new(*max_entries, *ttl),
+        ))),
+        #[cfg(feature = "cache-redis")]
+        CacheProvider::Redis { url, ttl } => Ok(Some(Arc::new(
+            crate::cache::redis::RedisCache::connect(url, *ttl).await?,
+        ))),
+        CacheProvider::None => Ok(None),
+    }
+}
+
+/// Repositories whose writes must invalidate cached reads
+pub struct CacheableRepositories {
+    pub note_repo: Arc<dyn NoteRepository>,
+    pub tag_repo: Arc<dyn TagRepository>,
+    pub unit_of_work: Arc<dyn UnitOfWork>,
+}
+
+impl CacheableRepositories {
+    /// Wrap the repositories in caching decorators sharing `cache`
+    pub fn with_cache(self, cache: Option<Arc<dyn Cache>>) -> Self {
+        let Some(cache) = cache else {
+            return self;
+        };
+        Self {
+            note_repo: Arc::new(CachedNoteRepository::new(self.note_repo, cache.clone())),
+            tag_repo: Arc::new(CachedTagRepository::new(self.tag_repo, cache.clone())),
+            unit_of_work: Arc::new(CachedUnitOfWork::new(self.unit_of_work, cache)),
+        }
+    }
+}
+
+/// Repositories whose reads can be served by a read replica
+pub struct ReplicableRepositories {
+    pub note_repo: Arc<dyn NoteRepository>,
+    pub tag_repo: Arc<dyn TagRepository>,
+    pub user_repo: Arc<dyn UserRepository>,
+}
+
+impl ReplicableRepositories {
+    /// Send reads to repositories backed by `replica`, keeping writes on the
+    /// current ones. Apply before [`CacheableRepositories::with_cache`] so
+    /// cache misses are the reads that reach the replica.
+    pub async fn with_replica(self, replica: Option<&DatabasePool>) -> FactoryResult<Self> {
+        let Some(replica) = replica else {
+            return Ok(self);
+        };
+        Ok(Self {
+            note_repo: Arc::new(ReplicatedNoteRepository::new(
+                self.note_repo,
+                build_note_repository(replica).await?,
+            )),
+            tag_repo: Arc::new(ReplicatedTagRepository::new(
+                self.tag_repo,
+                build_tag_repository(replica).await?,
+            )),
+            user_repo: Arc::new(ReplicatedUserRepository::new(
+                self.user_repo,
+                build_user_repository(replica).await?,
+            )),
+        })
+    }
+}
+
+/// Configuration for PDF rendering providers.
+#[derive(Debug, Clone)]
+pub enum PdfProvider {
+    /// Headless Chromium/Chrome binary used to print HTML to PDF, rendered
+    /// with `render`.
+    Chromium {
+        binary: String,
+        render: crate::render::html::RenderOptions,
+    },
+    /// PDF export disabled.
+    None,
+}
+
+/// Build a PDF renderer based on the provider configuration.
+/// Returns `None` if `PdfProvider::None` is specified.
+pub async fn build_pdf_renderer(
+    provider: &PdfProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::PdfRenderer>>> {
+    match provider {
+        PdfProvider::Chromium { binary, render } => Ok(Some(Arc::new(
+            crate::pdf::chromium::ChromiumPdfRenderer::new(binary.clone(), render.clone()),
+        ))),
+        PdfProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for email delivery providers.
+#[derive(Debug, Clone)]
+pub enum MailProvider {
+    /// SMTP relay with STARTTLS (requires `mail-smtp` feature).
+    #[cfg(feature = "mail-smtp")]
+    Smtp {
+        host: String,
+        port: u16,
+        credentials: Option<(String, String)>,
+        from: String,
+    },
+    /// Write emails to the log instead of sending them.
+    Log,
+}
+
+/// Build an email sender based on the provider configuration.
+pub async fn build_email_sender(
+    provider: &MailProvider,
+) -> FactoryResult<Arc<dyn notes_domain::EmailSender>> {
+    match provider {
+        #[cfg(feature = "mail-smtp")]
+        MailProvider::Smtp {
+            host,
+            port,
+            credentials,
+            from,
+        } => Ok(Arc::new(crate::mail::smtp::SmtpEmailSender::new(
+            host,
+            *port,
+            credentials.clone(),
+            from,
+        )?)),
+        MailProvider::Log => Ok(Arc::new(crate::mail::log::LogEmailSender::new())),
+    }
+}
+
+/// Configuration for registration challenges.
+#[derive(Debug, Clone)]
+pub enum ChallengeProvider {
+    /// hCaptcha widget (requires `challenge-captcha` feature).
+    #[cfg(feature = "challenge-captcha")]
+    HCaptcha { site_key: String, secret: String },
+    /// Cloudflare Turnstile widget (requires `challenge-captcha` feature).
+    #[cfg(feature = "challenge-captcha")]
+    Turnstile { site_key: String, secret: String },
+    /// Proof-of-work puzzle solved by the browser, without third parties.
+    /// Signs challenges with `secret`, or a random per-process key.
+    ProofOfWork {
+        secret: Option<String>,
+        difficulty: u8,
+    },
+    /// No challenge required.
+    None,
+}
+
+/// Build a challenge verifier based on the provider configuration.
+/// Returns `None` if `ChallengeProvider::None` is specified.
+pub async fn build_challenge_verifier(
+    provider: &ChallengeProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::ChallengeVerifier>>> {
+    #[cfg(feature = "challenge-captcha")]
+    use crate::challenge::captcha::{CaptchaService, CaptchaVerifier};
+
+    match provider {
+        #[cfg(feature = "challenge-captcha")]
+        ChallengeProvider::HCaptcha { site_key, secret } => Ok(Some(Arc::new(
+            CaptchaVerifier::new(CaptchaService::HCaptcha, site_key.clone(), secret.clone())?,
+        ))),
+        #[cfg(feature = "challenge-captcha")]
+        ChallengeProvider::Turnstile { site_key, secret } => Ok(Some(Arc::new(
+            CaptchaVerifier::new(CaptchaService::Turnstile, site_key.clone(), secret.clone())?,
+        ))),
+        ChallengeProvider::ProofOfWork { secret, difficulty } => Ok(Some(Arc::new(
+            crate::challenge::pow::ProofOfWorkVerifier::new(secret.as_deref(), *difficulty),
+        ))),
+        ChallengeProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for checking external links found in notes.
+#[derive(Debug, Clone)]
+pub enum LinkCheckProvider {
+    /// Request each URL over HTTP (requires `web-fetch` feature).
+    #[cfg(feature = "web-fetch")]
+    Http { timeout: std::time::Duration },
+    /// Only wiki-links are checked.
+    None,
+}
+
+/// Build a URL checker based on the provider configuration.
+/// Returns `None` if `LinkCheckProvider::None` is specified.
+pub async fn build_url_checker(
+    provider: &LinkCheckProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::UrlChecker>>> {
+    match provider {
+        #[cfg(feature = "web-fetch")]
+        LinkCheckProvider::Http { timeout } => Ok(Some(Arc::new(
+            crate::web::link_checker::HttpUrlChecker::new(*timeout)?,
+        ))),
+        LinkCheckProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for fetching previews of bookmarked links.
+#[derive(Debug, Clone)]
+pub enum LinkPreviewProvider {
+    /// Read page metadata over HTTP (requires `web-fetch` feature).
+    #[cfg(feature = "web-fetch")]
+    Http { timeout: std::time::Duration },
+    /// Bookmarks stay plain links.
+    None,
+}
+
+/// Build a link preview fetcher based on the provider configuration.
+/// Returns `None` if `LinkPreviewProvider::None` is specified.
+pub async fn build_link_preview_fetcher(
+    provider: &LinkPreviewProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::LinkPreviewFetcher>>> {
+    match provider {
+        #[cfg(feature = "web-fetch")]
+        LinkPreviewProvider::Http { timeout } => Ok(Some(Arc::new(
+            crate::web::preview::HttpLinkPreviewFetcher::new(*timeout)?,
+        ))),
+        LinkPreviewProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for loading external images of notes for readers.
+#[derive(Debug, Clone)]
+pub enum ImageProxyProvider {
+    /// Load images over HTTP (requires `web-fetch` feature); proxy URLs are
+    /// signed with `secret`, or a random key when `None`.
+    #[cfg(feature = "web-fetch")]
+    Http {
+        timeout: std::time::Duration,
+        secret: Option<String>,
+    },
+    /// Images are loaded from their hosts by readers.
+    None,
+}
+
+/// Build an image proxy's fetcher and URL signer based on the provider
+/// configuration. Returns `None` if `ImageProxyProvider::None` is specified.
+#[allow(clippy::type_complexity)]
+pub async fn build_image_proxy(
+    provider: &ImageProxyProvider,
+) -> FactoryResult<
+    Option<(
+        Arc<dyn notes_domain::ImageFetcher>,
+        Arc<dyn notes_domain::Signer>,
+    )>,
+> {
+    match provider {
+        #[cfg(feature = "web-fetch")]
+        ImageProxyProvider::Http { timeout, secret } => Ok(Some((
+            Arc::new(crate::web::image::HttpImageFetcher::new(*timeout)?),
+            Arc::new(crate::signing::HmacSigner::new(secret.as_deref())),
+        ))),
+        ImageProxyProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for generating text, such as titles of untitled notes.
+#[derive(Debug, Clone)]
+pub enum TextGeneratorProvider {
+    /// Chat model behind an OpenAI-compatible API, like OpenAI or Ollama
+    /// (requires `text-generation` feature).
+    #[cfg(feature = "text-generation")]
+    OpenAi {
+        base_url: String,
+        model: String,
+        api_key: Option<String>,
+        timeout: std::time::Duration,
+    },
+    /// Text is derived from note content without a model.
+    None,
+}
+
+/// Build a text generator based on the provider configuration.
+/// Returns `None` if `TextGeneratorProvider::None` is specified.
+pub async fn build_text_generator(
+    provider: &TextGeneratorProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::TextGenerator>>> {
+    match provider {
+        #[cfg(feature = "text-generation")]
+        TextGeneratorProvider::OpenAi {
+            base_url,
+            model,
+            api_key,
+            timeout,
+        } => Ok(Some(Arc::new(
+            crate::text::openai::OpenAiTextGenerator::new(
+                base_url,
+                model.clone(),
+                api_key.clone(),
+                *timeout,
+            )?,
+        ))),
+        TextGeneratorProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for proofreading notes.
+#[derive(Debug, Clone)]
+pub enum ProofreaderProvider {
+    /// LanguageTool server, self-hosted or public (requires `proofreading` feature).
+    #[cfg(feature = "proofreading")]
+    LanguageTool {
+        base_url: String,
+        timeout: std::time::Duration,
+    },
+    /// Proofreading disabled; notes only get writing statistics.
+    None,
+}
+
+/// Build a proofreader based on the provider configuration.
+/// Returns `None` if `ProofreaderProvider::None` is specified.
+pub async fn build_proofreader(
+    provider: &ProofreaderProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::Proofreader>>> {
+    match provider {
+        #[cfg(feature = "proofreading")]
+        ProofreaderProvider::LanguageTool { base_url, timeout } => Ok(Some(Arc::new(
+            crate::proofread::languagetool::LanguageToolProofreader::new(base_url, *timeout)?,
+        ))),
+        ProofreaderProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for reading notes aloud.
+#[derive(Debug, Clone)]
+pub enum SpeechProvider {
+    /// Local `piper` binary, with `ffmpeg` to encode its output (requires
+    /// `speech` feature). `voices` maps ISO 639-1 codes to voices read
+    /// instead of `voice`.
+    #[cfg(feature = "speech")]
+    Piper {
+        binary: String,
+        ffmpeg: String,
+        voice: std::path::PathBuf,
+        voices: std::collections::HashMap<String, std::path::PathBuf>,
+    },
+    /// Voice behind an OpenAI-compatible speech API (requires `speech` feature).
+    #[cfg(feature = "speech")]
+    OpenAi {
+        base_url: String,
+        model: String,
+        voice: String,
+        api_key: Option<String>,
+        timeout: std::time::Duration,
+    },
+    /// Reading notes aloud disabled.
+    None,
+}
+
+/// Build a speech synthesizer based on the provider configuration.
+/// Returns `None` if `SpeechProvider::None` is specified.
+pub async fn build_speech_synthesizer(
+    provider: &SpeechProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::SpeechSynthesizer>>> {
+    match provider {
+        #[cfg(feature = "speech")]
+        SpeechProvider::Piper {
+            binary,
+            ffmpeg,
+            voice,
+            voices,
+        } => Ok(Some(Arc::new(
+            crate::speech::piper::PiperSpeechSynthesizer::new(
+                binary.clone(),
+                ffmpeg.clone(),
+                voice.clone(),
+                voices.clone(),
+            ),
+        ))),
+        #[cfg(feature = "speech")]
+        SpeechProvider::OpenAi {
+            base_url,
+            model,
+            voice,
+            api_key,
+            timeout,
+        } => Ok(Some(Arc::new(
+            crate::speech::openai::OpenAiSpeechSynthesizer::new(
+                base_url,
+                model.clone(),
+                voice.clone(),
+                api_key.clone(),
+                *timeout,
+            )?,
+        ))),
+        SpeechProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for transcribing voice memos.
+#[derive(Debug, Clone)]
+pub enum TranscriberProvider {
+    /// Local whisper.cpp `whisper-cli` binary and GGML model, with `ffmpeg`
+    /// to decode uploads (requires `transcription` feature).
+    #[cfg(feature = "transcription")]
+    WhisperCpp {
+        binary: String,
+        ffmpeg: String,
+        model: std::path::PathBuf,
+    },
+    /// Model behind an OpenAI-compatible transcription API (requires
+    /// `transcription` feature).
+    #[cfg(feature = "transcription")]
+    OpenAi {
+        base_url: String,
+        model: String,
+        api_key: Option<String>,
+        timeout: std::time::Duration,
+    },
+    /// Voice memos are not transcribed.
+    None,
+}
+
+/// Build a transcriber based on the provider configuration.
+/// Returns `None` if `TranscriberProvider::None` is specified.
+pub async fn build_transcriber(
+    provider: &TranscriberProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::Transcriber>>> {
+    match provider {
+        #[cfg(feature = "transcription")]
+        TranscriberProvider::WhisperCpp {
+            binary,
+            ffmpeg,
+            model,
+        } => Ok(Some(Arc::new(
+            crate::transcription::whisper::WhisperCppTranscriber::new(
+                binary.clone(),
+                ffmpeg.clone(),
+                model.clone(),
+            ),
+        ))),
+        #[cfg(feature = "transcription")]
+        TranscriberProvider::OpenAi {
+            base_url,
+            model,
+            api_key,
+            timeout,
+        } => Ok(Some(Arc::new(
+            crate::transcription::openai::OpenAiTranscriber::new(
+                base_url,
+                model.clone(),
+                api_key.clone(),
+                *timeout,
+            )?,
+        ))),
+        TranscriberProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for drawing diagrams in rendered notes.
+#[derive(Debug, Clone)]
+pub enum DiagramProvider {
+    /// Kroki server, which draws Mermaid and PlantUML among others
+    /// (requires `diagrams` feature).
+    #[cfg(feature = "diagrams")]
+    Kroki {
+        base_url: String,
+        timeout: std::time::Duration,
+    },
+    /// Diagrams stay code blocks.
+    None,
+}
+
+/// Build a diagram renderer based on the provider configuration.
+/// Returns `None` if `DiagramProvider::None` is specified.
+pub async fn build_diagram_renderer(
+    provider: &DiagramProvider,
+) -> FactoryResult<Option<Arc<dyn notes_domain::DiagramRenderer>>> {
+    match provider {
+        #[cfg(feature = "diagrams")]
+        DiagramProvider::Kroki { base_url, timeout } => Ok(Some(Arc::new(
+            crate::diagram::kroki::KrokiDiagramRenderer::new(base_url, *timeout)?,
+        ))),
+        DiagramProvider::None => Ok(None),
+    }
+}
+
+/// Configuration for password hashing.
+#[derive(Debug, Clone, Default)]
+pub struct PasswordHashConfig {
+    /// Argon2id cost parameters used for new hashes.
+    pub argon2: crate::password::argon2id::Argon2Config,
+    /// Also verify bcrypt hashes (requires `password-bcrypt` feature).
+    pub bcrypt_compat: bool,
+}
+
+/// Build the password hasher used for local accounts.
+/// Hashes that use other parameters or algorithms are flagged for rehashing.
+pub fn build_password_hasher(
+    config: &PasswordHashConfig,
+) -> FactoryResult<Arc<dyn notes_domain::PasswordHasher>> {
+    use crate::password::{argon2id::Argon2PasswordHasher, compat::CompatPasswordHasher};
+
+    let hasher = CompatPasswordHasher::new(Argon2PasswordHasher::new(config.argon2)?);
+
+    if config.bcrypt_compat {
+        #[cfg(feature = "password-bcrypt")]
+        return Ok(Arc::new(hasher.with_bcrypt(
+            crate::password::bcrypt_compat::BcryptPasswordHasher::default(),
+        )));
+        #[cfg(not(feature = "password-bcrypt"))]
+        return Err(FactoryError::NotImplemented(
+            "bcrypt compatibility requires the password-bcrypt feature".to_string(),
+        )
+        .into());
+    }
+
+    Ok(Arc::new(hasher))
+}
+
 #[cfg(feature = "sqlite")]
 pub async fn build_link_repository(
     pool: &DatabasePool,
@@ -129,6 +716,200 @@ pub async fn build_tag_repository(pool: &DatabasePool) -> FactoryResult<Arc<dyn
     }
 }
 
+pub async fn build_unit_of_work(pool: &DatabasePool) -> FactoryResult<Arc<dyn UnitOfWork>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteUnitOfWork::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres UnitOfWork not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_search_history_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn SearchHistoryRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => {
+            Ok(Arc::new(SqliteSearchHistoryRepository::new(pool.clone())))
+        }
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => {
+            anyhow::bail!("Postgres SearchHistoryRepository not implemented")
+        }
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_job_repository(pool: &DatabasePool) -> FactoryResult<Arc<dyn JobRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteJobRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres JobRepository not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_announcement_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn AnnouncementRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteAnnouncementRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => {
+            anyhow::bail!("Postgres AnnouncementRepository not implemented")
+        }
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_invitation_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn InvitationRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteInvitationRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres InvitationRepository not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_impersonation_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn ImpersonationRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => {
+            Ok(Arc::new(SqliteImpersonationRepository::new(pool.clone())))
+        }
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => {
+            anyhow::bail!("Postgres ImpersonationRepository not implemented")
+        }
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_legal_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn LegalRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteLegalRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres LegalRepository not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_event_log_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn EventLogRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteEventLogRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres EventLogRepository not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_note_issue_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn NoteIssueRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteNoteIssueRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres NoteIssueRepository not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_audio_upload_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn AudioUploadRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteAudioUploadRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => {
+            anyhow::bail!("Postgres AudioUploadRepository not implemented")
+        }
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_board_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn BoardRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteBoardRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres BoardRepository not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_note_relation_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn NoteRelationRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteNoteRelationRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => {
+            anyhow::bail!("Postgres NoteRelationRepository not implemented")
+        }
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_housekeeping_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn HousekeepingRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteHousekeepingRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => {
+            anyhow::bail!("Postgres HousekeepingRepository not implemented")
+        }
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
+pub async fn build_tag_alias_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn TagAliasRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteTagAliasRepository::new(pool.clone()))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => anyhow::bail!("Postgres TagAliasRepository not implemented"),
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
 pub async fn build_user_repository(pool: &DatabasePool) -> FactoryResult<Arc<dyn UserRepository>> {
     match pool {
         #[cfg(feature = "sqlite")]
@@ -140,6 +921,23 @@ pub async fn build_user_repository(pool: &DatabasePool) -> FactoryResult<Arc<dyn
     }
 }
 
+pub async fn build_instance_settings_repository(
+    pool: &DatabasePool,
+) -> FactoryResult<Arc<dyn InstanceSettingsRepository>> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => Ok(Arc::new(SqliteInstanceSettingsRepository::new(
+            pool.clone(),
+        ))),
+        #[cfg(feature = "postgres")]
+        DatabasePool::Postgres(_) => {
+            anyhow::bail!("Postgres InstanceSettingsRepository not implemented")
+        }
+        #[allow(unreachable_patterns)]
+        _ => anyhow::bail!("No database feature enabled"),
+    }
+}
+
 pub async fn build_session_store(pool: &DatabasePool) -> Result<InfraSessionStore, sqlx::Error> {
     Ok(match pool {
         #[cfg(feature = "sqlite")]
@@ -0,0 +1,118 @@
+//! K-Notes backups
+//!
+//! The JSON document `GET /api/v1/export` downloads. Restoring it keeps the
+//! IDs of notes and tags, so importing a backup twice updates the same notes.
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use notes_domain::{DomainError, DomainResult, Note, Tag};
+
+use super::{Exporter, ImportOptions, Importer};
+use crate::import::ImportedData;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupData {
+    pub notes: Vec<Note>,
+    pub tags: Vec<Tag>,
+}
+
+impl BackupData {
+    /// A backup of `notes` with the tags they use
+    pub fn of_notes(notes: Vec<Note>) -> Self {
+        let mut tags: Vec<Tag> = notes.iter().flat_map(|n| n.tags.clone()).collect();
+        tags.sort_by_key(|tag| tag.id);
+        tags.dedup_by_key(|tag| tag.id);
+        Self { notes, tags }
+    }
+}
+
+/// Restores a K-Notes backup
+pub struct KNotesImporter;
+
+impl Importer for KNotesImporter {
+    fn name(&self) -> &'static str {
+        "k_notes"
+    }
+
+    fn content_types(&self) -> &'static [&'static str] {
+        &["application/json"]
+    }
+
+    fn restores_backup(&self) -> bool {
+        true
+    }
+
+    fn import(
+        &self,
+        body: &[u8],
+        _user_id: Uuid,
+        _options: &ImportOptions,
+    ) -> DomainResult<ImportedData> {
+        let backup: BackupData = serde_json::from_slice(body)
+            .map_err(|e| DomainError::validation(format!("Not a valid backup: {}", e)))?;
+        Ok(ImportedData {
+            notes: backup.notes,
+            tags: backup.tags,
+            skipped: Vec::new(),
+        })
+    }
+}
+
+/// Writes notes as a K-Notes backup
+pub struct JsonExporter;
+
+#[async_trait]
+impl Exporter for JsonExporter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    async fn export(&self, _title: &str, notes: Vec<Note>, _tz: Tz) -> DomainResult<Vec<u8>> {
+        serde_json::to_vec(&BackupData::of_notes(notes))
+            .map_err(|e| DomainError::InfrastructureError(format!("Failed to write backup: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notes_domain::{NoteTitle, TagName};
+
+    #[tokio::test]
+    async fn test_exported_backup_is_restored() {
+        let tag = Tag::new(TagName::try_from("work").unwrap(), Uuid::nil());
+        let mut first = Note::new(Uuid::nil(), NoteTitle::try_from("One").ok(), "a");
+        first.tags.push(tag.clone());
+        let mut second = Note::new(Uuid::nil(), None, "b");
+        second.tags.push(tag.clone());
+
+        let json = JsonExporter
+            .export("K-Notes", vec![first.clone(), second], Tz::UTC)
+            .await
+            .unwrap();
+        let data = KNotesImporter
+            .import(&json, Uuid::nil(), &ImportOptions::new())
+            .unwrap();
+
+        assert_eq!(data.notes.len(), 2);
+        assert_eq!(data.notes[0].id, first.id);
+        assert_eq!(data.notes[0].tags[0].id, tag.id);
+        assert_eq!(data.tags.len(), 1);
+        assert!(
+            KNotesImporter
+                .import(b"{}", Uuid::nil(), &ImportOptions::new())
+                .is_err()
+        );
+    }
+}
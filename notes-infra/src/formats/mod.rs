@@ -0,0 +1,248 @@
+//! Import and export formats, looked up by name.
+//!
+//! Each format lives in its own module and implements [`Importer`],
+//! [`Exporter`] or both. The API picks formats from a [`FormatRegistry`] by
+//! the name given in the request, so adding a format means writing its module
+//! and registering it in [`FormatRegistry::builtin`].
+
+pub mod k_notes;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+use notes_domain::{DomainResult, Note, PdfRenderer};
+
+use crate::import::ImportedData;
+use crate::import::csv::CsvImporter;
+use crate::import::html::HtmlImporter;
+use crate::import::markdown::MarkdownImporter;
+use crate::import::simplenote::SimplenoteImporter;
+use crate::import::standard_notes::StandardNotesImporter;
+use crate::pdf::PdfExporter;
+use crate::render::csv::CsvExporter;
+use crate::render::html::RenderOptions;
+use crate::render::markdown::MarkdownExporter;
+use crate::render::site::SiteExporter;
+use k_notes::{JsonExporter, KNotesImporter};
+
+/// Format-specific import options by name, like the CSV column mapping
+pub type ImportOptions = HashMap<String, String>;
+
+/// Reads an export into notes and tags of the importing user
+pub trait Importer: Send + Sync {
+    /// Name requests pick the format by, like `simplenote`
+    fn name(&self) -> &'static str;
+
+    /// Content types the format is picked for when a request names none
+    fn content_types(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Whether the export is a K-Notes backup, whose tags are restored as
+    /// they are; tags of other exports join the user's tags of the same name
+    fn restores_backup(&self) -> bool {
+        false
+    }
+
+    fn import(
+        &self,
+        body: &[u8],
+        user_id: Uuid,
+        options: &ImportOptions,
+    ) -> DomainResult<ImportedData>;
+}
+
+/// Writes notes into a downloadable file
+#[async_trait]
+pub trait Exporter: Send + Sync {
+    /// Name requests pick the format by, like `csv`
+    fn name(&self) -> &'static str;
+
+    fn content_type(&self) -> &'static str;
+
+    /// Extension of the downloaded file, like `zip`
+    fn extension(&self) -> &'static str;
+
+    /// Whether exporting all notes includes archived ones
+    fn includes_archived(&self) -> bool {
+        true
+    }
+
+    /// Whether notes are rendered for reading, with their `{{include:…}}`
+    /// directives expanded; formats meant to be imported again keep them
+    fn renders_content(&self) -> bool {
+        false
+    }
+
+    /// Export `notes` under `title`, with timestamps shown in `tz`
+    async fn export(&self, title: &str, notes: Vec<Note>, tz: Tz) -> DomainResult<Vec<u8>>;
+}
+
+/// The import and export formats an instance offers
+#[derive(Clone, Default)]
+pub struct FormatRegistry {
+    importers: Vec<Arc<dyn Importer>>,
+    exporters: Vec<Arc<dyn Exporter>>,
+}
+
+impl FormatRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The formats K-Notes ships with; PDF export needs a renderer, and
+    /// `render` applies to the formats that render Markdown
+    pub fn builtin(pdf_renderer: Option<Arc<dyn PdfRenderer>>, render: RenderOptions) -> Self {
+        let registry = Self::new()
+            .with_importer(KNotesImporter)
+            .with_importer(StandardNotesImporter)
+            .with_importer(SimplenoteImporter)
+            .with_importer(HtmlImporter)
+            .with_importer(MarkdownImporter)
+            .with_importer(CsvImporter)
+            .with_exporter(JsonExporter)
+            .with_exporter(MarkdownExporter)
+            .with_exporter(SiteExporter::new(render))
+            .with_exporter(CsvExporter);
+
+        match pdf_renderer {
+            Some(renderer) => registry.with_exporter(PdfExporter::new(renderer)),
+            None => registry,
+        }
+    }
+
+    /// Add an importer, replacing the one of the same name
+    pub fn with_importer(mut self, importer: impl Importer + 'static) -> Self {
+        self.importers.retain(|i| i.name() != importer.name());
+        self.importers.push(Arc::new(importer));
+        self
+    }
+
+    /// Add an exporter, replacing the one of the same name
+    pub fn with_exporter(mut self, exporter: impl Exporter + 'static) -> Self {
+        self.exporters.retain(|e| e.name() != exporter.name());
+        self.exporters.push(Arc::new(exporter));
+        self
+    }
+
+    pub fn importer(&self, name: &str) -> Option<Arc<dyn Importer>> {
+        self.importers.iter().find(|i| i.name() == name).cloned()
+    }
+
+    pub fn exporter(&self, name: &str) -> Option<Arc<dyn Exporter>> {
+        self.exporters.iter().find(|e| e.name() == name).cloned()
+    }
+
+    /// The importer claiming `content_type`, or else the first registered
+    pub fn importer_for_content_type(&self, content_type: &str) -> Option<Arc<dyn Importer>> {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        self.importers
+            .iter()
+            .find(|i| i.content_types().contains(&essence.as_str()))
+            .or_else(|| self.importers.first())
+            .cloned()
+    }
+
+    /// Importer names in registration order
+    pub fn importer_names(&self) -> Vec<&'static str> {
+        self.importers.iter().map(|i| i.name()).collect()
+    }
+
+    /// Exporter names in registration order
+    pub fn exporter_names(&self) -> Vec<&'static str> {
+        self.exporters.iter().map(|e| e.name()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Plain(&'static str);
+
+    impl Importer for Plain {
+        fn name(&self) -> &'static str {
+            "plain"
+        }
+
+        fn content_types(&self) -> &'static [&'static str] {
+            &["text/plain"]
+        }
+
+        fn import(
+            &self,
+            body: &[u8],
+            user_id: Uuid,
+            _options: &ImportOptions,
+        ) -> DomainResult<ImportedData> {
+            let content = format!("{}{}", self.0, String::from_utf8_lossy(body));
+            Ok(ImportedData {
+                notes: vec![Note::new(user_id, None, content)],
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_builtin_formats_are_found_by_name() {
+        let registry = FormatRegistry::builtin(None, RenderOptions::default());
+        assert_eq!(
+            registry.importer_names(),
+            vec![
+                "k_notes",
+                "standard_notes",
+                "simplenote",
+                "html",
+                "markdown",
+                "csv"
+            ]
+        );
+        assert_eq!(
+            registry.exporter_names(),
+            vec!["json", "markdown", "site", "csv"]
+        );
+        assert!(registry.exporter("pdf").is_none());
+        assert!(!registry.exporter("site").unwrap().includes_archived());
+        assert!(registry.importer("k_notes").unwrap().restores_backup());
+    }
+
+    #[test]
+    fn test_importer_is_picked_by_content_type() {
+        let registry =
+            FormatRegistry::builtin(None, RenderOptions::default()).with_importer(Plain(""));
+        let name = |content_type| {
+            registry
+                .importer_for_content_type(content_type)
+                .unwrap()
+                .name()
+        };
+        assert_eq!(name("application/zip"), "simplenote");
+        assert_eq!(name("text/csv; charset=utf-8"), "csv");
+        assert_eq!(name("Text/Plain"), "plain");
+        assert_eq!(name(""), "k_notes");
+    }
+
+    #[test]
+    fn test_registering_a_name_again_replaces_the_format() {
+        let registry = FormatRegistry::new()
+            .with_importer(Plain("old: "))
+            .with_importer(Plain("new: "));
+        assert_eq!(registry.importer_names(), vec!["plain"]);
+
+        let data = registry
+            .importer("plain")
+            .unwrap()
+            .import(b"hi", Uuid::nil(), &ImportOptions::new())
+            .unwrap();
+        assert_eq!(data.notes[0].content, "new: hi");
+    }
+}
@@ -0,0 +1,400 @@
+//! SQLite implementation of HousekeepingRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use crate::search_index::FTS_TABLES;
+use notes_domain::housekeeping::StoredNote;
+use notes_domain::jobs::JobStatus;
+use notes_domain::{DomainResult, HousekeepingRepository};
+
+/// Table of the SQL session store, created when the server starts
+const SESSIONS_TABLE: &str = "tower_sessions";
+
+const ORPHANED_NOTE_TAGS: &str = "note_id NOT IN (SELECT id FROM notes) \
+                                  OR tag_id NOT IN (SELECT id FROM tags)";
+
+/// SQLite adapter for HousekeepingRepository
+pub struct SqliteHousekeepingRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteHousekeepingRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn count(&self, sql: &str) -> DomainResult<u64> {
+        let count: i64 = sqlx::query_scalar(sql)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(count as u64)
+    }
+
+    /// Whether the session store has created its table yet
+    async fn has_sessions(&self) -> DomainResult<bool> {
+        let table: Option<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(SESSIONS_TABLE)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+        Ok(table.is_some())
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct StoredNoteRow {
+    id: String,
+    user_id: String,
+    is_trashed: bool,
+}
+
+fn parse_uuid(s: &str) -> DomainResult<Uuid> {
+    Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))
+}
+
+impl StoredNoteRow {
+    fn try_into_note(self) -> DomainResult<StoredNote> {
+        Ok(StoredNote {
+            id: parse_uuid(&self.id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            is_trashed: self.is_trashed,
+        })
+    }
+}
+
+#[async_trait]
+impl HousekeepingRepository for SqliteHousekeepingRepository {
+    async fn count_orphaned_note_tags(&self) -> DomainResult<u64> {
+        self.count(&format!(
+            "SELECT COUNT(*) FROM note_tags WHERE {}",
+            ORPHANED_NOTE_TAGS
+        ))
+        .await
+    }
+
+    async fn delete_orphaned_note_tags(&self) -> DomainResult<u64> {
+        write(move || async move {
+            let result = sqlx::query(&format!(
+                "DELETE FROM note_tags WHERE {}",
+                ORPHANED_NOTE_TAGS
+            ))
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+
+    async fn count_fts_drift(&self) -> DomainResult<u64> {
+        // Every indexed row has an entry in the index's docsize table,
+        // keyed by the row's rowid
+        let mut drift = 0;
+        for (fts, table) in FTS_TABLES {
+            drift += self
+                .count(&format!(
+                    "SELECT \
+                     (SELECT COUNT(*) FROM {table} WHERE rowid NOT IN (SELECT id FROM {fts}_docsize)) + \
+                     (SELECT COUNT(*) FROM {fts}_docsize WHERE id NOT IN (SELECT rowid FROM {table}))"
+                ))
+                .await?;
+        }
+        Ok(drift)
+    }
+
+    async fn rebuild_fts(&self) -> DomainResult<()> {
+        write(move || async move {
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+            for (fts, _) in FTS_TABLES {
+                sqlx::query(&format!("INSERT INTO {fts}({fts}) VALUES('rebuild')"))
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(map_sqlx_error)?;
+            }
+            tx.commit().await.map_err(map_sqlx_error)
+        })
+        .await
+    }
+
+    async fn find_stored_notes(&self) -> DomainResult<Vec<StoredNote>> {
+        let rows: Vec<StoredNoteRow> =
+            sqlx::query_as("SELECT id, user_id, deleted_at IS NOT NULL AS is_trashed FROM notes")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(StoredNoteRow::try_into_note).collect()
+    }
+
+    async fn count_expired_sessions(&self, now: DateTime<Utc>) -> DomainResult<u64> {
+        if !self.has_sessions().await? {
+            return Ok(0);
+        }
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {} WHERE expiry_date < ?",
+            SESSIONS_TABLE
+        ))
+        .bind(now.timestamp())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        Ok(count as u64)
+    }
+
+    async fn delete_expired_sessions(&self, now: DateTime<Utc>) -> DomainResult<u64> {
+        if !self.has_sessions().await? {
+            return Ok(0);
+        }
+        write(move || async move {
+            let result = sqlx::query(&format!(
+                "DELETE FROM {} WHERE expiry_date < ?",
+                SESSIONS_TABLE
+            ))
+            .bind(now.timestamp())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+
+    async fn count_stale_jobs(&self, before: DateTime<Utc>) -> DomainResult<u64> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE status = ? AND updated_at < ?")
+                .bind(JobStatus::Running.as_str())
+                .bind(before.to_rfc3339())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+        Ok(count as u64)
+    }
+
+    async fn fail_stale_jobs(&self, before: DateTime<Utc>, error: &str) -> DomainResult<u64> {
+        let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
+        let before = before.to_rfc3339();
+        let before = before.as_str();
+        write(move || async move {
+            let result = sqlx::query(
+                r#"
+                UPDATE jobs
+                SET status = ?, error = ?, updated_at = ?, finished_at = ?
+                WHERE status = ? AND updated_at < ?
+                "#,
+            )
+            .bind(JobStatus::Failed.as_str())
+            .bind(error)
+            .bind(now)
+            .bind(now)
+            .bind(JobStatus::Running.as_str())
+            .bind(before)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::job_repository::SqliteJobRepository;
+    use crate::note_repository::SqliteNoteRepository;
+    use crate::tag_repository::SqliteTagRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::jobs::{Job, JobKind};
+    use notes_domain::{
+        Email, JobRepository, Note, NoteRepository, Tag, TagName, TagRepository, User,
+        UserRepository,
+    };
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(
+            "test|housekeeping",
+            Email::try_from("housekeeping@example.com").unwrap(),
+        );
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_note_tags_are_counted_and_deleted() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let tag_repo = SqliteTagRepository::new(pool.clone());
+        let repo = SqliteHousekeepingRepository::new(pool.clone());
+
+        let note = Note::new(user.id, None, "Tagged");
+        let tag = Tag::new(TagName::try_from("kept").unwrap(), user.id);
+        note_repo.save(&note).await.unwrap();
+        tag_repo.save(&tag).await.unwrap();
+        tag_repo.add_to_note(tag.id, note.id).await.unwrap();
+
+        // Left behind by a delete made with foreign keys off
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)")
+            .bind(Uuid::new_v4().to_string())
+            .bind(tag.id.to_string())
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        assert_eq!(repo.count_orphaned_note_tags().await.unwrap(), 1);
+        assert_eq!(repo.delete_orphaned_note_tags().await.unwrap(), 1);
+        assert_eq!(repo.count_orphaned_note_tags().await.unwrap(), 0);
+        assert_eq!(tag_repo.find_by_note(note.id).await.unwrap(), vec![tag]);
+    }
+
+    #[tokio::test]
+    async fn test_rebuilding_fixes_fts_drift() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let repo = SqliteHousekeepingRepository::new(pool.clone());
+
+        note_repo
+            .save(&Note::new(user.id, None, "Lost from the index"))
+            .await
+            .unwrap();
+        assert_eq!(repo.count_fts_drift().await.unwrap(), 0);
+
+        sqlx::query("INSERT INTO notes_fts(notes_fts) VALUES('delete-all')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(repo.count_fts_drift().await.unwrap(), 1);
+        assert!(
+            note_repo
+                .search(user.id, "index", false)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        repo.rebuild_fts().await.unwrap();
+        assert_eq!(repo.count_fts_drift().await.unwrap(), 0);
+        assert_eq!(
+            note_repo
+                .search(user.id, "index", false)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stored_notes_include_trashed_ones() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let repo = SqliteHousekeepingRepository::new(pool);
+
+        let note = Note::new(user.id, None, "Live");
+        let mut trashed = Note::new(user.id, None, "Trashed");
+        trashed.deleted_at = Some(Utc::now());
+        note_repo.save(&note).await.unwrap();
+        note_repo.save(&trashed).await.unwrap();
+
+        let mut stored = repo.find_stored_notes().await.unwrap();
+        stored.sort_by_key(|n| n.is_trashed);
+        assert_eq!(
+            stored,
+            vec![
+                StoredNote {
+                    id: note.id,
+                    user_id: user.id,
+                    is_trashed: false,
+                },
+                StoredNote {
+                    id: trashed.id,
+                    user_id: user.id,
+                    is_trashed: true,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_stale_running_jobs_are_failed() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let job_repo = SqliteJobRepository::new(pool.clone());
+        let repo = SqliteHousekeepingRepository::new(pool);
+
+        let mut stale = Job::new(user.id, JobKind::Import, None);
+        stale.updated_at -= chrono::Duration::hours(2);
+        let active = Job::new(user.id, JobKind::Import, None);
+        job_repo.save(&stale).await.unwrap();
+        job_repo.save(&active).await.unwrap();
+
+        let before = Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(repo.count_stale_jobs(before).await.unwrap(), 1);
+        assert_eq!(repo.fail_stale_jobs(before, "stuck").await.unwrap(), 1);
+
+        let stale = job_repo.find_by_id(stale.id).await.unwrap().unwrap();
+        assert_eq!(stale.status, JobStatus::Failed);
+        assert_eq!(stale.error.as_deref(), Some("stuck"));
+        let active = job_repo.find_by_id(active.id).await.unwrap().unwrap();
+        assert_eq!(active.status, JobStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_deleted() {
+        let pool = setup_test_db().await;
+        let repo = SqliteHousekeepingRepository::new(pool.clone());
+        // Without a session store yet, there is nothing to clean up
+        assert_eq!(repo.count_expired_sessions(Utc::now()).await.unwrap(), 0);
+
+        tower_sessions_sqlx_store::SqliteStore::new(pool.clone())
+            .migrate()
+            .await
+            .unwrap();
+        let now = Utc::now().timestamp();
+        for expiry_date in [now - 3600, now + 3600] {
+            sqlx::query("INSERT INTO tower_sessions (id, data, expiry_date) VALUES (?, ?, ?)")
+                .bind(Uuid::new_v4().to_string())
+                .bind(Vec::<u8>::new())
+                .bind(expiry_date)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(repo.count_expired_sessions(Utc::now()).await.unwrap(), 1);
+        assert_eq!(repo.delete_expired_sessions(Utc::now()).await.unwrap(), 1);
+        assert_eq!(repo.count_expired_sessions(Utc::now()).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,99 @@
+//! Helpers for reading HTML without a full parser
+//!
+//! Shared by the link preview fetcher and the HTML importer, which only need
+//! tag names, attributes and text.
+
+/// `name="value"` pairs of a tag, names lowercased
+pub(crate) fn parse_attributes(mut s: &str) -> Vec<(String, &str)> {
+    let mut attrs = Vec::new();
+    loop {
+        s = s.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        let name_end = s
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(s.len());
+        if name_end == 0 {
+            return attrs;
+        }
+        let name = s[..name_end].to_ascii_lowercase();
+        s = s[name_end..].trim_start();
+
+        let Some(rest) = s.strip_prefix('=') else {
+            attrs.push((name, ""));
+            continue;
+        };
+        let rest = rest.trim_start();
+        let (value, after) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let inner = &rest[1..];
+                let end = inner.find(quote).unwrap_or(inner.len());
+                (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        attrs.push((name, value));
+        s = after;
+    }
+}
+
+pub(crate) fn attr<'a>(attrs: &[(String, &'a str)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+}
+
+/// Decode the character references titles commonly contain
+pub(crate) fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_entities_leaves_unknown_references() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#x27;c&#39; &bogus; & d"),
+            "a <b> 'c' &bogus; & d"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! SQLite implementation of ImpersonationRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{DomainResult, ImpersonationRepository, impersonation::Impersonation};
+
+/// SQLite adapter for ImpersonationRepository
+pub struct SqliteImpersonationRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteImpersonationRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct ImpersonationRow {
+    id: String,
+    admin_id: String,
+    user_id: String,
+    reason: String,
+    started_at: String,
+    expires_at: String,
+    ended_at: Option<String>,
+}
+
+fn parse_datetime(s: &str) -> DomainResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))
+}
+
+impl ImpersonationRow {
+    fn try_into_impersonation(self) -> DomainResult<Impersonation> {
+        let parse_uuid =
+            |s: &str| Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)));
+
+        Ok(Impersonation {
+            id: parse_uuid(&self.id)?,
+            admin_id: parse_uuid(&self.admin_id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            reason: self.reason,
+            started_at: parse_datetime(&self.started_at)?,
+            expires_at: parse_datetime(&self.expires_at)?,
+            ended_at: self.ended_at.as_deref().map(parse_datetime).transpose()?,
+        })
+    }
+}
+
+#[async_trait]
+impl ImpersonationRepository for SqliteImpersonationRepository {
+    async fn save(&self, impersonation: &Impersonation) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO impersonations (id, admin_id, user_id, reason, started_at,
+                                            expires_at, ended_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    expires_at = excluded.expires_at,
+                    ended_at = excluded.ended_at
+                "#,
+            )
+            .bind(impersonation.id.to_string())
+            .bind(impersonation.admin_id.to_string())
+            .bind(impersonation.user_id.to_string())
+            .bind(&impersonation.reason)
+            .bind(impersonation.started_at.to_rfc3339())
+            .bind(impersonation.expires_at.to_rfc3339())
+            .bind(impersonation.ended_at.map(|t| t.to_rfc3339()))
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Impersonation>> {
+        let row: Option<ImpersonationRow> =
+            sqlx::query_as("SELECT * FROM impersonations WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        row.map(ImpersonationRow::try_into_impersonation)
+            .transpose()
+    }
+
+    async fn find_active(
+        &self,
+        admin_id: Uuid,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> DomainResult<Option<Impersonation>> {
+        // RFC 3339 timestamps in UTC compare correctly as strings
+        let row: Option<ImpersonationRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM impersonations
+            WHERE admin_id = ? AND user_id = ? AND ended_at IS NULL AND expires_at > ?
+            ORDER BY started_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(admin_id.to_string())
+        .bind(user_id.to_string())
+        .bind(now.to_rfc3339())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        row.map(ImpersonationRow::try_into_impersonation)
+            .transpose()
+    }
+
+    async fn find_recent(&self, limit: usize) -> DomainResult<Vec<Impersonation>> {
+        let rows: Vec<ImpersonationRow> =
+            sqlx::query_as("SELECT * FROM impersonations ORDER BY started_at DESC LIMIT ?")
+                .bind(limit as i64)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(ImpersonationRow::try_into_impersonation)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use chrono::Duration;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool, subject: &str, email: &str) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(subject, Email::try_from(email).unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_only_unexpired_impersonations_are_active() {
+        let pool = setup_test_db().await;
+        let admin = create_test_user(&pool, "test|admin", "admin@example.com").await;
+        let user = create_test_user(&pool, "test|user", "user@example.com").await;
+        let repo = SqliteImpersonationRepository::new(pool);
+        let now = Utc::now();
+
+        let mut expired = Impersonation::new(admin.id, user.id, "Earlier", Some(5)).unwrap();
+        expired.started_at = now - Duration::hours(1);
+        expired.expires_at = now - Duration::minutes(55);
+        let current = Impersonation::new(admin.id, user.id, "Missing notes", None).unwrap();
+        repo.save(&expired).await.unwrap();
+        repo.save(&current).await.unwrap();
+
+        assert_eq!(
+            repo.find_active(admin.id, user.id, now).await.unwrap(),
+            Some(current.clone())
+        );
+        assert_eq!(
+            repo.find_active(user.id, admin.id, now).await.unwrap(),
+            None
+        );
+
+        let mut ended = current.clone();
+        ended.ended_at = Some(now);
+        repo.save(&ended).await.unwrap();
+        assert_eq!(
+            repo.find_active(admin.id, user.id, now).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            repo.find_by_id(current.id).await.unwrap(),
+            Some(ended.clone())
+        );
+        assert_eq!(repo.find_recent(10).await.unwrap(), vec![ended, expired]);
+        assert_eq!(repo.find_recent(1).await.unwrap().len(), 1);
+    }
+}
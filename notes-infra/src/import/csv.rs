@@ -0,0 +1,300 @@
+//! CSV import
+//!
+//! Reads one note per row from a spreadsheet export. The first row names the
+//! columns; which ones hold the title, content, tags and timestamps can be
+//! set, and defaults to the columns the CSV export writes. Other columns are
+//! ignored.
+//!
+//! Import options name the columns: `title_column`, `content_column`,
+//! `tags_column`, `created_column`, `updated_column` and `tag_separator`.
+
+use serde::Deserialize;
+use uuid::Uuid;
+
+use notes_domain::{DomainError, DomainResult, Note};
+
+use super::{ImportedData, SkippedItem, format_error, note_title, parse_date};
+use crate::formats::{ImportOptions, Importer};
+
+/// Which columns hold what, by header name (case-insensitive)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CsvColumns {
+    pub title: String,
+    pub content: String,
+    pub tags: String,
+    pub created: String,
+    pub updated: String,
+    /// Separates the tags within the tags column
+    pub tag_separator: char,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        Self {
+            title: "title".to_string(),
+            content: "content".to_string(),
+            tags: "tags".to_string(),
+            created: "created_at".to_string(),
+            updated: "updated_at".to_string(),
+            tag_separator: ',',
+        }
+    }
+}
+
+impl CsvColumns {
+    /// The columns named in import options, defaulting to those the CSV
+    /// export writes
+    pub fn from_options(options: &ImportOptions) -> DomainResult<Self> {
+        let defaults = Self::default();
+        let column = |key: &str, default: String| {
+            options
+                .get(key)
+                .filter(|c| !c.trim().is_empty())
+                .cloned()
+                .unwrap_or(default)
+        };
+        let tag_separator = match options.get("tag_separator") {
+            None => defaults.tag_separator,
+            Some(separator) => {
+                let mut chars = separator.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => {
+                        return Err(DomainError::validation(
+                            "tag_separator must be a single character",
+                        ));
+                    }
+                }
+            }
+        };
+        Ok(Self {
+            title: column("title_column", defaults.title),
+            content: column("content_column", defaults.content),
+            tags: column("tags_column", defaults.tags),
+            created: column("created_column", defaults.created),
+            updated: column("updated_column", defaults.updated),
+            tag_separator,
+        })
+    }
+}
+
+/// Split CSV text into records of fields, following RFC 4180 quoting
+fn parse_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Read CSV rows into notes and tags of `user_id`
+pub fn parse_csv(csv: &[u8], user_id: Uuid, columns: &CsvColumns) -> DomainResult<ImportedData> {
+    let text = String::from_utf8_lossy(csv);
+    // Spreadsheets often start UTF-8 files with a byte order mark
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    let mut records = parse_records(text).into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| format_error("CSV", "it is empty"))?;
+
+    let find = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+    };
+    let content_column = find(&columns.content)
+        .ok_or_else(|| format_error("CSV", format!("it has no {:?} column", columns.content)))?;
+    let title_column = find(&columns.title);
+    let tags_column = find(&columns.tags);
+    let created_column = find(&columns.created);
+    let updated_column = find(&columns.updated);
+    let pinned_column = find("pinned");
+    let archived_column = find("archived");
+
+    let mut data = ImportedData::new();
+    for (index, record) in records.enumerate() {
+        // Blank lines are not notes
+        if record.len() == 1 && record[0].is_empty() {
+            continue;
+        }
+        // Row numbers as spreadsheets show them, counting the header
+        let row = format!("row {}", index + 2);
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|i| record.get(i))
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let flag = |column| cell(column).is_some_and(|v| v.eq_ignore_ascii_case("true"));
+
+        let title = cell(title_column);
+        let content = record
+            .get(content_column)
+            .map(String::as_str)
+            .unwrap_or_default();
+        if title.is_none() && content.trim().is_empty() {
+            data.skipped
+                .push(SkippedItem::new(row, "no title or content"));
+            continue;
+        }
+
+        let mut note = Note::new(user_id, title.and_then(note_title), content);
+        if let Some(created_at) = cell(created_column).and_then(parse_date) {
+            note.created_at = created_at;
+        }
+        note.updated_at = cell(updated_column)
+            .and_then(parse_date)
+            .unwrap_or(note.created_at);
+        note.is_pinned = flag(pinned_column);
+        note.is_archived = flag(archived_column);
+
+        let names = cell(tags_column)
+            .unwrap_or_default()
+            .split(columns.tag_separator);
+        for name in names.map(str::trim).filter(|name| !name.is_empty()) {
+            match data.tag(user_id, name) {
+                Ok(tag) if !note.tags.iter().any(|t| t.id == tag.id) => note.tags.push(tag),
+                Ok(_) => {}
+                Err(e) => data.skipped.push(SkippedItem::new(
+                    row.clone(),
+                    format!("tag {:?}: {}", name, e),
+                )),
+            }
+        }
+        data.notes.push(note);
+    }
+
+    Ok(data)
+}
+
+/// Imports one note per CSV row
+pub struct CsvImporter;
+
+impl Importer for CsvImporter {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn content_types(&self) -> &'static [&'static str] {
+        &["text/csv"]
+    }
+
+    fn import(
+        &self,
+        body: &[u8],
+        user_id: Uuid,
+        options: &ImportOptions,
+    ) -> DomainResult<ImportedData> {
+        parse_csv(body, user_id, &CsvColumns::from_options(options)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::csv::build_csv;
+    use notes_domain::{NoteTitle, Tag, TagName};
+
+    #[test]
+    fn test_parse_reads_the_csv_export() {
+        let mut note = Note::new(
+            Uuid::nil(),
+            NoteTitle::try_from("Plan, \"v2\"").ok(),
+            "- one\n- two",
+        );
+        note.tags
+            .push(Tag::new(TagName::try_from("work").unwrap(), Uuid::nil()));
+        note.is_archived = true;
+
+        let data = parse_csv(
+            build_csv(std::slice::from_ref(&note)).as_bytes(),
+            Uuid::nil(),
+            &CsvColumns::default(),
+        )
+        .unwrap();
+        let imported = &data.notes[0];
+        assert_eq!(imported.title, note.title);
+        assert_eq!(imported.content, note.content);
+        assert_eq!(imported.tags[0].name_str(), "work");
+        assert!(imported.is_archived && !imported.is_pinned);
+        assert_eq!(imported.created_at.timestamp(), note.created_at.timestamp());
+    }
+
+    #[test]
+    fn test_parse_uses_the_column_mapping() {
+        let csv = "\u{feff}Name,Body,Labels,Date\r\nGroceries,milk,home; errands,2024-02-01\n\n,,,\nTodo,,,\n";
+        let columns = CsvColumns {
+            title: "name".to_string(),
+            content: "Body".to_string(),
+            tags: "labels".to_string(),
+            created: "date".to_string(),
+            tag_separator: ';',
+            ..CsvColumns::default()
+        };
+
+        let data = parse_csv(csv.as_bytes(), Uuid::nil(), &columns).unwrap();
+        assert_eq!(data.notes.len(), 2);
+        let groceries = &data.notes[0];
+        assert_eq!(groceries.title_str(), "Groceries");
+        assert_eq!(groceries.content, "milk");
+        let tags: Vec<&str> = groceries.tags.iter().map(|t| t.name_str()).collect();
+        assert_eq!(tags, vec!["home", "errands"]);
+        assert_eq!(
+            groceries.created_at.to_rfc3339(),
+            "2024-02-01T00:00:00+00:00"
+        );
+        assert_eq!(data.notes[1].title_str(), "Todo");
+        assert_eq!(
+            data.skipped,
+            vec![SkippedItem::new("row 4", "no title or content")]
+        );
+
+        assert!(parse_csv(b"title\nx\n", Uuid::nil(), &CsvColumns::default()).is_err());
+    }
+
+    #[test]
+    fn test_columns_are_read_from_import_options() {
+        let options = ImportOptions::from([
+            ("title_column".to_string(), "Name".to_string()),
+            ("tags_column".to_string(), " ".to_string()),
+            ("tag_separator".to_string(), ";".to_string()),
+        ]);
+        let columns = CsvColumns::from_options(&options).unwrap();
+        assert_eq!(columns.title, "Name");
+        assert_eq!(columns.tags, "tags");
+        assert_eq!(columns.tag_separator, ';');
+
+        let options = ImportOptions::from([("tag_separator".to_string(), ";;".to_string())]);
+        assert!(CsvColumns::from_options(&options).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Zipped folders of notes, shared by the HTML and markdown importers
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use zip::ZipArchive;
+
+use notes_domain::{DomainResult, Note};
+
+use super::{ImportedData, SkippedItem, format_error, read_entry, unpack_budget};
+
+/// A file of the export
+pub(super) struct ExportFile {
+    pub contents: Vec<u8>,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// Zip entry times carry no time zone; they are read as UTC
+fn zip_time(time: zip::DateTime) -> Option<DateTime<Utc>> {
+    NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+        .and_hms_opt(
+            time.hour().into(),
+            time.minute().into(),
+            time.second().into(),
+        )
+        .map(|naive| naive.and_utc())
+}
+
+/// Resolve `src` relative to the directory `dir`, decoding `%20` and friends
+fn resolve_path(dir: &str, src: &str) -> String {
+    let mut bytes = Vec::with_capacity(src.len());
+    let mut rest = src.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(decoded) if b == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    let src = String::from_utf8_lossy(&bytes);
+
+    let mut segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in src.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn image_type(path: &str) -> Option<&'static str> {
+    let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "heic" => "image/heic",
+        _ => return None,
+    })
+}
+
+/// Directory and file name stem of a path, like `("a/b", "Note")`
+pub(super) fn split_path(path: &str) -> (&str, &str) {
+    let (dir, file_name) = path.rsplit_once('/').unwrap_or(("", path));
+    let stem = file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+    (dir, stem)
+}
+
+/// Every file of a zipped folder, by path
+pub(super) struct ExportFolder {
+    files: HashMap<String, ExportFile>,
+    /// The directory all files share, like `Notes/` when the folder itself
+    /// was zipped; it is not one of the user's folders
+    root: String,
+}
+
+impl ExportFolder {
+    pub fn read(zip: &[u8], app: &str) -> DomainResult<Self> {
+        let to_error = |e: &dyn std::fmt::Display| format_error(app, e);
+        let mut archive = ZipArchive::new(Cursor::new(zip)).map_err(|e| to_error(&e))?;
+        let mut files = HashMap::new();
+        let mut budget = unpack_budget(zip);
+
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).map_err(|e| to_error(&e))?;
+            // Skip directories and entries escaping the archive, like `../x`
+            let Some(path) = entry.enclosed_name().filter(|_| entry.is_file()) else {
+                continue;
+            };
+            let path = path.to_string_lossy().replace('\\', "/");
+            if path.starts_with("__MACOSX/") {
+                continue;
+            }
+            let contents = read_entry(&mut entry, &mut budget).map_err(|e| to_error(&e))?;
+            let modified = entry.last_modified().and_then(zip_time);
+            files.insert(path, ExportFile { contents, modified });
+        }
+
+        let mut root = match files.keys().next().and_then(|p| p.split_once('/')) {
+            Some((first, _)) => format!("{}/", first),
+            None => String::new(),
+        };
+        if !files.keys().all(|p| p.starts_with(&root)) {
+            root.clear();
+        }
+        Ok(Self { files, root })
+    }
+
+    /// Paths of the files with one of these extensions, sorted
+    pub fn paths_with_extension(&self, extensions: &[&str]) -> Vec<&str> {
+        let mut paths: Vec<&str> = self
+            .files
+            .keys()
+            .map(String::as_str)
+            .filter(|path| {
+                let lower = path.to_ascii_lowercase();
+                extensions
+                    .iter()
+                    .any(|ext| lower.ends_with(&format!(".{}", ext)))
+            })
+            .collect();
+        paths.sort_unstable();
+        paths
+    }
+
+    pub fn file(&self, path: &str) -> &ExportFile {
+        &self.files[path]
+    }
+
+    /// A path without the shared root directory
+    pub fn relative<'a>(&self, path: &'a str) -> &'a str {
+        path.strip_prefix(self.root.as_str()).unwrap_or(path)
+    }
+
+    /// The image at `src` relative to `dir` as a data URI, since notes have
+    /// no attachments; data URIs, remote and missing images are kept as is.
+    /// Like Obsidian, a bare file name also finds the image in any folder.
+    pub fn image_url(&self, dir: &str, src: &str) -> String {
+        if src.contains(':') {
+            return src.to_string();
+        }
+        let mut path = resolve_path(dir, src);
+        if !self.files.contains_key(&path) && !src.contains('/') {
+            // `path` is decoded and resolved against `dir`; only its file
+            // name is looked for
+            let name = path.rsplit('/').next().unwrap_or_default();
+            let suffix = format!("/{}", name);
+            // The shallowest match, like Obsidian picks
+            let found = self
+                .files
+                .keys()
+                .filter(|p| *p == name || p.ends_with(&suffix))
+                .min_by(|a, b| {
+                    let depth = |p: &str| p.matches('/').count();
+                    depth(a).cmp(&depth(b)).then_with(|| a.cmp(b))
+                });
+            if let Some(found) = found {
+                path = found.clone();
+            }
+        }
+        match (self.files.get(&path), image_type(&path)) {
+            (Some(image), Some(mime)) => {
+                format!("data:{};base64,{}", mime, STANDARD.encode(&image.contents))
+            }
+            _ => src.to_string(),
+        }
+    }
+}
+
+impl ImportedData {
+    /// Tag the note with the folder it was in; nested folders keep their
+    /// whole path, e.g. `work/projects`, when that fits a tag name and
+    /// otherwise just the innermost folder
+    pub(super) fn tag_folder(&mut self, user_id: Uuid, note: &mut Note, folder: &str, path: &str) {
+        let folder = folder.trim_matches('/');
+        if folder.is_empty() {
+            return;
+        }
+        let innermost = folder.rsplit('/').next().unwrap_or_default();
+        match self
+            .tag(user_id, folder)
+            .or_else(|_| self.tag(user_id, innermost))
+        {
+            Ok(tag) if !note.tags.iter().any(|t| t.id == tag.id) => note.tags.push(tag),
+            Ok(_) => {}
+            Err(e) => self.skipped.push(SkippedItem::new(
+                path,
+                format!("folder {:?}: {}", folder, e),
+            )),
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! HTML folder import
+//!
+//! Reads a zip of a folder of HTML files, as written by the tools that
+//! export Apple Notes, and turns each HTML file into a markdown note. Images
+//! next to the notes are inlined as data URIs, since notes have no
+//! attachments, and the folder a note was in becomes its tag, since tags are
+//! how K-Notes groups notes.
+
+use uuid::Uuid;
+
+use notes_domain::{DomainResult, Note};
+
+use super::folder::{ExportFolder, split_path};
+use super::{ImportedData, format_error, note_title};
+use crate::formats::{ImportOptions, Importer};
+use crate::html_scan::{attr, decode_entities, parse_attributes};
+
+/// Tags whose content is never note text
+const SKIPPED_ELEMENTS: [&str; 3] = ["script", "style", "title"];
+
+/// Markdown being written from HTML
+#[derive(Default)]
+struct MarkdownWriter {
+    out: String,
+    /// Whitespace seen since the last word, written before the next one
+    space: bool,
+    /// Open lists, with the next number of ordered ones
+    lists: Vec<Option<usize>>,
+    /// Targets of open links; `None` for anchors without `href`
+    links: Vec<Option<String>>,
+    in_pre: bool,
+}
+
+impl MarkdownWriter {
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with(['\n', ' '])
+    }
+
+    fn push_inline(&mut self, s: &str) {
+        if self.space && !self.at_line_start() {
+            self.out.push(' ');
+        }
+        self.space = false;
+        self.out.push_str(s);
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.in_pre {
+            self.out.push_str(text);
+            return;
+        }
+        for (i, word) in text.split(char::is_whitespace).enumerate() {
+            self.space |= i > 0;
+            if !word.is_empty() {
+                self.push_inline(word);
+            }
+        }
+    }
+
+    fn trim_line_end(&mut self) {
+        let len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(len);
+        self.space = false;
+    }
+
+    fn newline(&mut self) {
+        self.trim_line_end();
+        self.out.push('\n');
+    }
+
+    /// Start a new line unless already at one
+    fn line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.newline();
+        }
+    }
+
+    fn paragraph(&mut self) {
+        self.trim_line_end();
+        if self.out.is_empty() {
+            return;
+        }
+        while !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn open(
+        &mut self,
+        name: &str,
+        attrs: &[(String, &str)],
+        image: &mut impl FnMut(&str) -> String,
+    ) {
+        match name {
+            "p" | "blockquote" | "table" => self.paragraph(),
+            "div" | "tr" => self.line(),
+            "br" => self.newline(),
+            "hr" => {
+                self.paragraph();
+                self.out.push_str("---");
+                self.paragraph();
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph();
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            "b" | "strong" => self.push_inline("**"),
+            "i" | "em" => self.push_inline("*"),
+            "s" | "strike" | "del" => self.push_inline("~~"),
+            "code" | "tt" if !self.in_pre => self.push_inline("`"),
+            "pre" => {
+                self.paragraph();
+                self.out.push_str("```\n");
+                self.in_pre = true;
+            }
+            "ul" => self.lists.push(None),
+            "ol" => self.lists.push(Some(1)),
+            "li" => {
+                self.line();
+                let depth = self.lists.len().saturating_sub(1);
+                self.out.push_str(&"  ".repeat(depth));
+                match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        self.out.push_str(&format!("{}. ", number));
+                        *number += 1;
+                    }
+                    _ => self.out.push_str("- "),
+                }
+            }
+            "a" => {
+                let href = attr(attrs, "href").map(decode_entities);
+                if href.is_some() {
+                    self.push_inline("[");
+                }
+                self.links.push(href);
+            }
+            "img" => {
+                if let Some(src) = attr(attrs, "src") {
+                    let alt = attr(attrs, "alt").map(decode_entities).unwrap_or_default();
+                    let src = image(&decode_entities(src));
+                    self.push_inline(&format!("![{}]({})", alt, src));
+                }
+            }
+            "td" | "th" => self.space = true,
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "p" | "blockquote" | "table" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph()
+            }
+            "div" | "tr" | "li" => self.line(),
+            "b" | "strong" => self.out.push_str("**"),
+            "i" | "em" => self.out.push_str("*"),
+            "s" | "strike" | "del" => self.out.push_str("~~"),
+            "code" | "tt" if !self.in_pre => self.out.push_str("`"),
+            "pre" => {
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.out.push_str("```");
+                self.in_pre = false;
+                self.paragraph();
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.paragraph();
+                }
+            }
+            "a" => {
+                if let Some(Some(href)) = self.links.pop() {
+                    self.out.push_str(&format!("]({})", href));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut markdown = String::with_capacity(self.out.len());
+        let mut blank_lines = 0;
+        for line in self.out.trim().lines() {
+            let line = line.trim_end();
+            blank_lines = if line.is_empty() { blank_lines + 1 } else { 0 };
+            if blank_lines < 2 {
+                markdown.push_str(line);
+                markdown.push('\n');
+            }
+        }
+        markdown.trim_end().to_string()
+    }
+}
+
+/// Convert an HTML note to markdown, returning its `<title>` as well;
+/// `image` maps each image `src` to the URL to write
+pub fn html_to_markdown(
+    html: &str,
+    mut image: impl FnMut(&str) -> String,
+) -> (Option<String>, String) {
+    // ASCII lowercasing keeps byte offsets, so text is sliced from `html`
+    let lower = html.to_ascii_lowercase();
+    let mut writer = MarkdownWriter::default();
+    let mut title = None;
+
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find('<') {
+        let text_end = pos + offset;
+        writer.push_text(&decode_entities(&html[pos..text_end]));
+
+        if lower[text_end..].starts_with("<!--") {
+            pos = lower[text_end..]
+                .find("-->")
+                .map_or(lower.len(), |i| text_end + i + 3);
+            continue;
+        }
+        let start = text_end + 1;
+        let closing = lower[start..].starts_with('/');
+        let name_start = start + usize::from(closing);
+        let name_end = lower[name_start..]
+            .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+            .map_or(lower.len(), |i| name_start + i);
+        let Some(tag_end) = lower[start..].find('>').map(|i| start + i) else {
+            pos = lower.len();
+            break;
+        };
+        pos = tag_end + 1;
+
+        let name = &lower[name_start..name_end];
+        if closing {
+            writer.close(name);
+        } else if SKIPPED_ELEMENTS.contains(&name) {
+            let end = lower[pos..]
+                .find(&format!("</{}", name))
+                .map_or(lower.len(), |i| pos + i);
+            if name == "title" && title.is_none() {
+                title = Some(decode_entities(html[pos..end].trim()));
+            }
+            pos = end;
+        } else {
+            writer.open(
+                name,
+                &parse_attributes(&html[name_end..tag_end]),
+                &mut image,
+            );
+        }
+    }
+    writer.push_text(&decode_entities(&html[pos..]));
+
+    (title.filter(|t| !t.is_empty()), writer.finish())
+}
+
+/// Read a zipped folder of HTML notes into notes and tags of `user_id`
+pub fn parse_html_folder(zip: &[u8], user_id: Uuid) -> DomainResult<ImportedData> {
+    let folder = ExportFolder::read(zip, "HTML folder")?;
+    let pages = folder.paths_with_extension(&["html", "htm"]);
+    if pages.is_empty() {
+        return Err(format_error("HTML folder", "it holds no HTML files"));
+    }
+    let mut data = ImportedData::new();
+
+    for path in pages {
+        let file = folder.file(path);
+        let (dir, stem) = split_path(path);
+        let html = String::from_utf8_lossy(&file.contents);
+        let (title, content) = html_to_markdown(&html, |src| folder.image_url(dir, src));
+
+        let mut note = Note::new(
+            user_id,
+            note_title(title.as_deref().unwrap_or(stem)),
+            content,
+        );
+        if let Some(modified) = file.modified {
+            note.created_at = modified;
+            note.updated_at = modified;
+        }
+        data.tag_folder(
+            user_id,
+            &mut note,
+            split_path(folder.relative(path)).0,
+            path,
+        );
+        data.notes.push(note);
+    }
+
+    Ok(data)
+}
+
+/// Imports a zipped folder of HTML notes
+pub struct HtmlImporter;
+
+impl Importer for HtmlImporter {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn import(
+        &self,
+        body: &[u8],
+        user_id: Uuid,
+        _options: &ImportOptions,
+    ) -> DomainResult<ImportedData> {
+        parse_html_folder(body, user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn folder_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, contents) in files {
+            writer
+                .start_file(*path, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_html_to_markdown_converts_apple_notes_markup() {
+        let html = r#"<html><head><title>Trip &amp; plans</title><style>b {}</style></head>
+            <body><div><h1>Trip</h1></div>
+            <div>Pack <b>light</b>, see <a href="https://example.com/?a=1&amp;b=2">the map</a></div>
+            <div><br></div>
+            <ul><li>Passport</li><li>Charger<ol><li>USB-C</li></ol></li></ul>
+            <!-- exported -->
+            <div><img src="Attachments/map%20one.png" alt="Map"></div></body></html>"#;
+
+        let (title, markdown) = html_to_markdown(html, |src| format!("<{}>", src));
+        assert_eq!(title.as_deref(), Some("Trip & plans"));
+        assert_eq!(
+            markdown,
+            "# Trip\n\nPack **light**, see [the map](https://example.com/?a=1&b=2)\n\n\
+             - Passport\n- Charger\n  1. USB-C\n\n![Map](<Attachments/map%20one.png>)"
+        );
+    }
+
+    #[test]
+    fn test_parse_inlines_images_and_tags_folders() {
+        let zip = folder_zip(&[
+            (
+                "Notes/Work/Projects/Launch.html",
+                b"<div>Ship it</div><img src=\"../Attachments/chart.png\">",
+            ),
+            ("Notes/Work/Attachments/chart.png", b"PNG"),
+            ("Notes/Loose.htm", b"<p>Loose note</p>"),
+            ("__MACOSX/Notes/._Loose.htm", b"junk"),
+        ]);
+
+        let data = parse_html_folder(&zip, Uuid::nil()).unwrap();
+        assert_eq!(data.notes.len(), 2);
+
+        let loose = &data.notes[0];
+        assert_eq!(loose.title_str(), "Loose");
+        assert_eq!(loose.content, "Loose note");
+        assert!(loose.tags.is_empty());
+
+        let launch = &data.notes[1];
+        assert_eq!(launch.title_str(), "Launch");
+        assert_eq!(launch.content, "Ship it\n![](data:image/png;base64,UE5H)");
+        assert_eq!(launch.tags[0].name_str(), "work/projects");
+        assert_eq!(data.tags.len(), 1);
+        assert!(data.skipped.is_empty());
+
+        assert!(parse_html_folder(&folder_zip(&[("a.txt", b"hi")]), Uuid::nil()).is_err());
+    }
+}
@@ -0,0 +1,443 @@
+//! Markdown folder import
+//!
+//! Reads a zip of a folder of markdown files, like an Obsidian vault, a Bear
+//! export or the K-Notes markdown export. Front matter sets a note's title,
+//! tags, timestamps and flags, and `#tags` in the text become tags as well.
+//! Notes are imported in two passes so `[[wiki-links]]` between the files
+//! can be pointed at the IDs of the notes they refer to. As with the HTML
+//! import, folders become tags and images are inlined.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use notes_domain::wiki_links::{WikiLink, replace_wiki_links};
+use notes_domain::{DomainResult, Note};
+
+use super::folder::{ExportFolder, split_path};
+use super::{ImportedData, SkippedItem, format_error, note_title, parse_date};
+use crate::formats::{ImportOptions, Importer};
+
+/// What a note's front matter says about it
+#[derive(Debug, Default, PartialEq, Eq)]
+struct FrontMatter {
+    title: Option<String>,
+    tags: Vec<String>,
+    /// Other names wiki-links may use for the note (Obsidian)
+    aliases: Vec<String>,
+    created: Option<DateTime<Utc>>,
+    updated: Option<DateTime<Utc>>,
+    pinned: bool,
+    archived: bool,
+    /// Lines of keys K-Notes has no use for, kept in the note
+    other: Vec<String>,
+}
+
+/// Split `---` delimited front matter from the rest of the file
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (None, text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, text)
+}
+
+/// A YAML scalar; double-quoted ones use the same escapes as JSON
+fn scalar(value: &str) -> String {
+    let value = value.trim();
+    if value.starts_with('"')
+        && let Ok(s) = serde_json::from_str::<String>(value)
+    {
+        return s;
+    }
+    value
+        .strip_prefix('\'')
+        .and_then(|v| v.strip_suffix('\''))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// A flow list (`[a, "b"]`) or, as Obsidian allows for tags, a comma or
+/// space separated string
+fn inline_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let items = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        Some(inner) => inner.split(',').map(scalar).collect(),
+        None if value.contains(',') => value.split(',').map(scalar).collect(),
+        None => value.split_whitespace().map(scalar).collect(),
+    };
+    clean_list(items)
+}
+
+fn clean_list(items: Vec<String>) -> Vec<String> {
+    items
+        .into_iter()
+        .map(|item| item.trim().trim_start_matches('#').to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+impl FrontMatter {
+    /// Read the keys K-Notes understands; this is not a full YAML parser,
+    /// only the subset note apps write
+    fn parse(yaml: &str) -> Self {
+        let mut front_matter = Self::default();
+        let lines: Vec<&str> = yaml.lines().collect();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+            i += 1;
+            let Some((key, value)) = line
+                .split_once(':')
+                .filter(|_| !line.starts_with([' ', '-']))
+            else {
+                continue;
+            };
+            // Block lists and nested maps follow on indented lines
+            let start = i;
+            while i < lines.len() && lines[i].starts_with([' ', '\t', '-']) {
+                i += 1;
+            }
+            let block = &lines[start..i];
+            let list = || match value.trim() {
+                "" => clean_list(
+                    block
+                        .iter()
+                        .filter_map(|l| l.trim_start().strip_prefix('-'))
+                        .map(scalar)
+                        .collect(),
+                ),
+                value => inline_list(value),
+            };
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "title" => front_matter.title = Some(scalar(value)).filter(|t| !t.is_empty()),
+                "tags" | "tag" => front_matter.tags.extend(list()),
+                "aliases" | "alias" => front_matter.aliases.extend(list()),
+                "created" | "date" | "created_at" => {
+                    front_matter.created = parse_date(&scalar(value))
+                }
+                "updated" | "modified" | "updated_at" => {
+                    front_matter.updated = parse_date(&scalar(value))
+                }
+                "pinned" => front_matter.pinned = scalar(value) == "true",
+                "archived" => front_matter.archived = scalar(value) == "true",
+                _ => {
+                    front_matter.other.push(line.to_string());
+                    front_matter
+                        .other
+                        .extend(block.iter().map(|l| l.to_string()));
+                }
+            }
+        }
+        front_matter
+    }
+}
+
+/// `#tags` written in the text, outside code blocks; headings need a space
+/// after the `#`, so they are not tags
+fn inline_tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_code = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let mut previous = ' ';
+        for (i, c) in line.char_indices() {
+            if c == '#' && previous.is_whitespace() {
+                let tag: String = line[i + 1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                    .collect();
+                let tag = tag.trim_end_matches('/');
+                if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                    tags.push(tag.to_string());
+                }
+            }
+            previous = c;
+        }
+    }
+    tags
+}
+
+/// Drop a leading `# Title` heading that only repeats the title, as Bear
+/// writes one into every file
+fn strip_title_heading<'a>(body: &'a str, title: &str) -> &'a str {
+    let trimmed = body.trim_start();
+    let (first, rest) = trimmed.split_once('\n').unwrap_or((trimmed, ""));
+    match first.strip_prefix("# ") {
+        Some(heading) if heading.trim().eq_ignore_ascii_case(title.trim()) => rest,
+        _ => body,
+    }
+}
+
+/// Inline the images of `![alt](src)` and Obsidian's `![[image.png]]`
+fn inline_images(content: &str, image: impl Fn(&str) -> String) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("![") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        if let Some(embed) = after.strip_prefix('[')
+            && let Some(end) = embed.find("]]")
+        {
+            // `|300` sets the display width, which markdown cannot
+            let target = embed[..end].split('|').next().unwrap_or_default().trim();
+            let url = image(target);
+            if url != target {
+                output.push_str(&format!("![]({})", url));
+                rest = &embed[end + 2..];
+                continue;
+            }
+        } else if let Some((alt, tail)) = after.split_once("](")
+            && !alt.contains('\n')
+            && let Some(end) = tail.find(')')
+        {
+            // Keep any `"title"` after the URL
+            let target = &tail[..end];
+            let (src, title) = target.split_once(' ').unwrap_or((target, ""));
+            let src = src.trim_start_matches('<').trim_end_matches('>');
+            let url = image(src);
+            let separator = if title.is_empty() { "" } else { " " };
+            output.push_str(&format!("![{}]({}{}{})", alt, url, separator, title));
+            rest = &tail[end + 1..];
+            continue;
+        }
+        output.push_str("![");
+        rest = after;
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Lowercased target of a wiki-link, without heading, block or `.md`
+fn link_key(target: &str) -> String {
+    let target = target.split(['#', '^']).next().unwrap_or_default().trim();
+    let target = target.strip_suffix(".md").unwrap_or(target);
+    target.to_lowercase()
+}
+
+/// Read a zipped folder of markdown notes into notes and tags of `user_id`
+pub fn parse_markdown_folder(zip: &[u8], user_id: Uuid) -> DomainResult<ImportedData> {
+    let folder = ExportFolder::read(zip, "markdown folder")?;
+    let paths = folder.paths_with_extension(&["md", "markdown"]);
+    if paths.is_empty() {
+        return Err(format_error(
+            "markdown folder",
+            "it holds no markdown files",
+        ));
+    }
+    let mut data = ImportedData::new();
+    // Names wiki-links may use -> imported note; the first note wins
+    let mut link_targets: HashMap<String, Uuid> = HashMap::new();
+
+    // First pass: create every note, so links can point at any of them
+    for path in paths {
+        let file = folder.file(path);
+        let relative = folder.relative(path);
+        let (dir, stem) = split_path(path);
+        let text = String::from_utf8_lossy(&file.contents);
+        let (yaml, body) = split_front_matter(&text);
+        let front_matter = yaml.map(FrontMatter::parse).unwrap_or_default();
+
+        let title = front_matter.title.as_deref().unwrap_or(stem);
+        let body = inline_images(strip_title_heading(body, title), |src| {
+            folder.image_url(dir, src)
+        });
+        let mut content = String::new();
+        if !front_matter.other.is_empty() {
+            content.push_str(&format!("---\n{}\n---\n\n", front_matter.other.join("\n")));
+        }
+        content.push_str(body.trim());
+
+        let mut note = Note::new(user_id, note_title(title), content);
+        if let Some(created) = front_matter.created.or(file.modified) {
+            note.created_at = created;
+        }
+        note.updated_at = front_matter
+            .updated
+            .or(file.modified)
+            .unwrap_or(note.created_at)
+            .max(note.created_at);
+        note.is_pinned = front_matter.pinned;
+        note.is_archived = front_matter.archived;
+
+        let mut tag_names = front_matter.tags.clone();
+        tag_names.extend(inline_tags(&note.content));
+        for name in tag_names {
+            match data.tag(user_id, &name) {
+                Ok(tag) if !note.tags.iter().any(|t| t.id == tag.id) => note.tags.push(tag),
+                Ok(_) => {}
+                Err(e) => data
+                    .skipped
+                    .push(SkippedItem::new(path, format!("tag {:?}: {}", name, e))),
+            }
+        }
+        data.tag_folder(user_id, &mut note, split_path(relative).0, path);
+
+        let names = [stem, title]
+            .into_iter()
+            .map(str::to_string)
+            .chain([relative
+                .rsplit_once('.')
+                .map_or(relative, |(path, _)| path)
+                .to_string()])
+            .chain(front_matter.aliases);
+        for name in names {
+            link_targets.entry(link_key(&name)).or_insert(note.id);
+        }
+        data.notes.push(note);
+    }
+
+    // Second pass: point wiki-links at the imported notes by ID
+    for note in &mut data.notes {
+        note.content = replace_wiki_links(&note.content, |link| {
+            match link_targets.get(&link_key(&link.target)) {
+                Some(id) => format!("[[{}|{}]]", id, link.display_text()),
+                None => unresolved(link),
+            }
+        });
+    }
+
+    Ok(data)
+}
+
+/// A link to a note outside the export, written back unchanged
+fn unresolved(link: &WikiLink) -> String {
+    match &link.label {
+        Some(label) => format!("[[{}|{}]]", link.target, label),
+        None => format!("[[{}]]", link.target),
+    }
+}
+
+/// Imports a zipped folder of markdown notes
+pub struct MarkdownImporter;
+
+impl Importer for MarkdownImporter {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn import(
+        &self,
+        body: &[u8],
+        user_id: Uuid,
+        _options: &ImportOptions,
+    ) -> DomainResult<ImportedData> {
+        parse_markdown_folder(body, user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn folder_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, contents) in files {
+            writer
+                .start_file(*path, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_front_matter_reads_lists_dates_and_keeps_other_keys() {
+        let (yaml, body) = split_front_matter(
+            "---\ntitle: \"Plan \\\"B\\\"\"\ntags:\n  - work\n  - \"#q3\"\naliases: [Backup plan]\ncreated: 2023-04-01\ncssclass: wide\npinned: true\n---\nBody\n",
+        );
+        let front_matter = FrontMatter::parse(yaml.unwrap());
+        assert_eq!(body, "Body\n");
+        assert_eq!(front_matter.title.as_deref(), Some("Plan \"B\""));
+        assert_eq!(front_matter.tags, vec!["work", "q3"]);
+        assert_eq!(front_matter.aliases, vec!["Backup plan"]);
+        assert_eq!(
+            front_matter.created.unwrap().to_rfc3339(),
+            "2023-04-01T00:00:00+00:00"
+        );
+        assert!(front_matter.pinned);
+        assert_eq!(front_matter.other, vec!["cssclass: wide"]);
+
+        assert_eq!(inline_list("a, b"), vec!["a", "b"]);
+        assert_eq!(inline_list("#a #b"), vec!["a", "b"]);
+        assert_eq!(
+            split_front_matter("No front matter"),
+            (None, "No front matter")
+        );
+    }
+
+    #[test]
+    fn test_inline_tags_skip_headings_code_and_numbers() {
+        let content = "# Heading\nBuy #groceries and #home/kitchen stuff, issue #42\n```\n#not-a-tag\n```\nurl.com/#anchor";
+        assert_eq!(inline_tags(content), vec!["groceries", "home/kitchen"]);
+    }
+
+    #[test]
+    fn test_unpacked_size_is_shared_by_all_files() {
+        let half = vec![b'a'; super::super::MIN_UNPACKED_BYTES as usize / 2 + 1];
+        let zip = folder_zip(&[("a.md", &half), ("b.md", &half)]);
+
+        assert!(parse_markdown_folder(&zip, Uuid::new_v4()).is_err());
+        let zip = folder_zip(&[("a.md", &half)]);
+        assert!(parse_markdown_folder(&zip, Uuid::new_v4()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_resolves_wiki_links_between_files() {
+        let zip = folder_zip(&[
+            (
+                "Vault/Projects/Launch.md",
+                b"---\ntags: [work]\n---\n# Launch\nSee [[Checklist#Day one|the checklist]], [[Missing]] and ![[chart.png]]",
+            ),
+            ("Vault/Checklist.md", b"Back to [[Projects/Launch]] #todo"),
+            ("Vault/attachments/chart.png", b"PNG"),
+        ]);
+
+        let data = parse_markdown_folder(&zip, Uuid::nil()).unwrap();
+        assert_eq!(data.notes.len(), 2);
+        let checklist = &data.notes[0];
+        let launch = &data.notes[1];
+
+        assert_eq!(launch.title_str(), "Launch");
+        assert_eq!(
+            launch.content,
+            format!(
+                "See [[{}|the checklist]], [[Missing]] and ![](data:image/png;base64,UE5H)",
+                checklist.id
+            )
+        );
+        let tags: Vec<&str> = launch.tags.iter().map(|t| t.name_str()).collect();
+        assert_eq!(tags, vec!["work", "projects"]);
+
+        assert_eq!(
+            checklist.content,
+            format!("Back to [[{}|Projects/Launch]] #todo", launch.id)
+        );
+        assert_eq!(checklist.tags[0].name_str(), "todo");
+        assert!(data.skipped.is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! Importers for other note-taking apps.
+//!
+//! Each importer turns an app's export into notes and tags owned by the
+//! importing user, ready to be saved like a K-Notes backup. Items that cannot
+//! be imported, such as encrypted Standard Notes items, are reported instead
+//! of failing the whole import.
+
+pub mod csv;
+mod folder;
+pub mod html;
+pub mod markdown;
+pub mod simplenote;
+pub mod standard_notes;
+
+use std::io::Read;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use notes_domain::{
+    DomainError, DomainResult, MAX_NOTE_TITLE_LENGTH, Note, NoteTitle, Tag, TagName,
+};
+
+/// Zipped exports may unpack to this many times their own size...
+const MAX_UNPACKED_RATIO: u64 = 20;
+
+/// ...or this many bytes, whichever is more, so a small zip bomb cannot
+/// exhaust memory
+const MIN_UNPACKED_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Bytes the entries of `zip` may unpack to, together
+fn unpack_budget(zip: &[u8]) -> u64 {
+    (zip.len() as u64)
+        .saturating_mul(MAX_UNPACKED_RATIO)
+        .max(MIN_UNPACKED_BYTES)
+}
+
+/// Read a zip entry, taking its size from the `budget` left for the whole
+/// archive and failing once it is spent
+fn read_entry(entry: impl Read, budget: &mut u64) -> std::io::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    entry.take(*budget + 1).read_to_end(&mut contents)?;
+    let size = contents.len() as u64;
+    if size > *budget {
+        return Err(std::io::Error::other(
+            "it unpacks to more data than an import may hold",
+        ));
+    }
+    *budget -= size;
+    Ok(contents)
+}
+
+/// Notes and tags read from another app's export
+#[derive(Debug, Clone, Default)]
+pub struct ImportedData {
+    pub notes: Vec<Note>,
+    /// Every tag used by the notes, plus tags no note uses
+    pub tags: Vec<Tag>,
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// An item of the export that was left out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedItem {
+    /// The item's ID in the exporting app
+    pub id: String,
+    pub reason: String,
+}
+
+impl SkippedItem {
+    fn new(id: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl ImportedData {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// The user's tag with this name, created on first use; names that differ
+    /// only in case or surrounding spaces share a tag
+    fn tag(&mut self, user_id: Uuid, name: &str) -> DomainResult<Tag> {
+        let name = TagName::try_from(name)?;
+        if let Some(tag) = self.tags.iter().find(|tag| tag.name == name) {
+            return Ok(tag.clone());
+        }
+        let tag = Tag::new(name, user_id);
+        self.tags.push(tag.clone());
+        Ok(tag)
+    }
+}
+
+/// A title that fits, or none for blank ones; other apps allow longer titles
+fn note_title(raw: &str) -> Option<NoteTitle> {
+    let mut title = raw.trim();
+    if title.len() > MAX_NOTE_TITLE_LENGTH {
+        let end = (0..=MAX_NOTE_TITLE_LENGTH)
+            .rev()
+            .find(|&i| title.is_char_boundary(i))
+            .unwrap_or(0);
+        title = title[..end].trim_end();
+    }
+    NoteTitle::from_optional(Some(title.to_string()))
+        .ok()
+        .flatten()
+}
+
+fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// An RFC 3339 timestamp, or a date and time without a zone read as UTC,
+/// the way spreadsheets and note apps write them
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    parse_timestamp(value)
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                .map(|naive| naive.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|naive| naive.and_utc())
+        })
+}
+
+fn format_error(app: &str, e: impl std::fmt::Display) -> DomainError {
+    DomainError::validation(format!("Not a valid {} export: {}", app, e))
+}
@@ -0,0 +1,203 @@
+//! SimpleNote export import
+//!
+//! Reads the zip SimpleNote offers under "Export notes". Its
+//! `source/notes.json` holds every note with its tags and timestamps; the
+//! plain text files next to it are ignored. SimpleNote has no separate
+//! titles, so the first line of a note becomes its title.
+
+use std::io::Cursor;
+
+use serde::Deserialize;
+use uuid::Uuid;
+use zip::ZipArchive;
+
+use notes_domain::{DomainResult, Note};
+
+use super::{
+    ImportedData, SkippedItem, format_error, note_title, parse_timestamp, read_entry, unpack_budget,
+};
+use crate::formats::{ImportOptions, Importer};
+
+/// Archive entry holding the notes
+const NOTES_FILE: &str = "notes.json";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Export {
+    #[serde(default)]
+    active_notes: Vec<ExportedNote>,
+    #[serde(default)]
+    trashed_notes: Vec<ExportedNote>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedNote {
+    id: String,
+    content: String,
+    creation_date: Option<String>,
+    last_modified: Option<String>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Split a SimpleNote note into its first line and the rest
+fn split_title(content: &str) -> (&str, &str) {
+    let content = content.trim_start();
+    match content.split_once('\n') {
+        Some((title, body)) => (title.trim_end(), body.trim_start_matches(['\r', '\n'])),
+        None => (content, ""),
+    }
+}
+
+fn read_notes_file(zip: &[u8]) -> DomainResult<Vec<u8>> {
+    let to_error = |e: &dyn std::fmt::Display| format_error("SimpleNote", e);
+    let mut archive = ZipArchive::new(Cursor::new(zip)).map_err(|e| to_error(&e))?;
+    let name = archive
+        .file_names()
+        .find(|name| *name == NOTES_FILE || name.ends_with(&format!("/{}", NOTES_FILE)))
+        .map(str::to_string)
+        .ok_or_else(|| to_error(&format!("{} is missing", NOTES_FILE)))?;
+
+    let entry = archive.by_name(&name).map_err(|e| to_error(&e))?;
+    read_entry(entry, &mut unpack_budget(zip)).map_err(|e| to_error(&e))
+}
+
+/// Read a SimpleNote export zip into notes and tags of `user_id`
+pub fn parse_simplenote(zip: &[u8], user_id: Uuid) -> DomainResult<ImportedData> {
+    let json = read_notes_file(zip)?;
+    let export: Export =
+        serde_json::from_slice(&json).map_err(|e| format_error("SimpleNote", e))?;
+    let mut data = ImportedData::new();
+
+    for exported in export.trashed_notes {
+        data.skipped
+            .push(SkippedItem::new(exported.id, "in the trash"));
+    }
+
+    for exported in export.active_notes {
+        let (title, body) = split_title(&exported.content);
+        let mut note = Note::new(user_id, note_title(title), body);
+        if let Some(created_at) = exported.creation_date.as_deref().and_then(parse_timestamp) {
+            note.created_at = created_at;
+        }
+        note.updated_at = exported
+            .last_modified
+            .as_deref()
+            .and_then(parse_timestamp)
+            .unwrap_or(note.created_at);
+        note.is_pinned = exported.pinned;
+
+        for name in &exported.tags {
+            match data.tag(user_id, name) {
+                Ok(tag) if !note.tags.iter().any(|t| t.id == tag.id) => note.tags.push(tag),
+                Ok(_) => {}
+                Err(e) => data.skipped.push(SkippedItem::new(
+                    exported.id.clone(),
+                    format!("tag {:?}: {}", name, e),
+                )),
+            }
+        }
+        data.notes.push(note);
+    }
+
+    Ok(data)
+}
+
+/// Imports a SimpleNote export zip
+pub struct SimplenoteImporter;
+
+impl Importer for SimplenoteImporter {
+    fn name(&self) -> &'static str {
+        "simplenote"
+    }
+
+    fn content_types(&self) -> &'static [&'static str] {
+        &["application/zip"]
+    }
+
+    fn import(
+        &self,
+        body: &[u8],
+        user_id: Uuid,
+        _options: &ImportOptions,
+    ) -> DomainResult<ImportedData> {
+        parse_simplenote(body, user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn export_zip(json: &str) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("source/notes.json", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(json.as_bytes()).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_parse_uses_first_line_as_title_and_skips_trash() {
+        let zip = export_zip(
+            r#"{
+                "activeNotes": [
+                    {
+                        "id": "a1",
+                        "content": "Trip ideas\r\n\r\n- Lisbon",
+                        "creationDate": "2020-05-01T08:00:00.000Z",
+                        "lastModified": "2020-06-01T08:00:00.000Z",
+                        "pinned": true,
+                        "tags": ["Travel", "travel", "someday"]
+                    },
+                    {"id": "a2", "content": "Call mom", "tags": []}
+                ],
+                "trashedNotes": [{"id": "t1", "content": "Old", "tags": []}]
+            }"#,
+        );
+
+        let data = parse_simplenote(&zip, Uuid::nil()).unwrap();
+        assert_eq!(data.notes.len(), 2);
+        let trip = &data.notes[0];
+        assert_eq!(trip.title_str(), "Trip ideas");
+        assert_eq!(trip.content, "- Lisbon");
+        assert!(trip.is_pinned);
+        assert_eq!(trip.created_at.to_rfc3339(), "2020-05-01T08:00:00+00:00");
+        assert_eq!(trip.updated_at.to_rfc3339(), "2020-06-01T08:00:00+00:00");
+        let tags: Vec<&str> = trip.tags.iter().map(|t| t.name_str()).collect();
+        assert_eq!(tags, vec!["travel", "someday"]);
+        assert_eq!(data.tags.len(), 2);
+
+        assert_eq!(data.notes[1].title_str(), "Call mom");
+        assert_eq!(data.notes[1].content, "");
+        assert_eq!(data.skipped, vec![SkippedItem::new("t1", "in the trash")]);
+
+        assert!(parse_simplenote(b"not a zip", Uuid::nil()).is_err());
+    }
+
+    #[test]
+    fn test_exports_unpacking_to_too_much_are_refused() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("source/notes.json", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .write_all(&vec![b' '; super::super::MIN_UNPACKED_BYTES as usize + 1])
+            .unwrap();
+        let zip = writer.finish().unwrap().into_inner();
+
+        let error = parse_simplenote(&zip, Uuid::new_v4()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("more data than an import may hold")
+        );
+    }
+}
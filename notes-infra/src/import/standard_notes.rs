@@ -0,0 +1,224 @@
+//! Standard Notes backup import
+//!
+//! Reads the decrypted backup file Standard Notes writes from its backup
+//! settings. Items of an encrypted backup cannot be read without the
+//! account's keys, so they are reported as skipped; so are notes in the
+//! trash. Tags are separate items referring to their notes.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use notes_domain::{DomainResult, Note};
+
+use super::{ImportedData, SkippedItem, format_error, note_title, parse_timestamp};
+use crate::formats::{ImportOptions, Importer};
+
+/// Key under which Standard Notes keeps its own per-item flags
+const APP_DATA_KEY: &str = "org.standardnotes.sn";
+
+#[derive(Debug, Deserialize)]
+struct Backup {
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    uuid: String,
+    content_type: String,
+    /// An object in decrypted backups, an encrypted string otherwise
+    #[serde(default)]
+    content: serde_json::Value,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ItemContent {
+    title: String,
+    text: String,
+    references: Vec<Reference>,
+    trashed: bool,
+    app_data: HashMap<String, AppData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reference {
+    uuid: String,
+    content_type: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AppData {
+    pinned: bool,
+    archived: bool,
+    /// When the user last edited the item; `updated_at` is when it synced
+    client_updated_at: Option<String>,
+}
+
+fn timestamp(raw: Option<&str>) -> Option<DateTime<Utc>> {
+    raw.and_then(parse_timestamp)
+}
+
+/// Read a Standard Notes backup into notes and tags of `user_id`
+pub fn parse_standard_notes(json: &[u8], user_id: Uuid) -> DomainResult<ImportedData> {
+    let backup: Backup =
+        serde_json::from_slice(json).map_err(|e| format_error("Standard Notes", e))?;
+    let mut data = ImportedData::new();
+    // Standard Notes item ID -> index of the imported note
+    let mut notes_by_item = HashMap::new();
+    let mut tag_items = Vec::new();
+
+    for item in backup.items {
+        let is_note = item.content_type == "Note";
+        if item.deleted || !(is_note || item.content_type == "Tag") {
+            continue;
+        }
+        if !item.content.is_object() {
+            data.skipped.push(SkippedItem::new(item.uuid, "encrypted"));
+            continue;
+        }
+        let content: ItemContent = match serde_json::from_value(item.content) {
+            Ok(content) => content,
+            Err(e) => {
+                data.skipped
+                    .push(SkippedItem::new(item.uuid, format!("unreadable: {}", e)));
+                continue;
+            }
+        };
+
+        if !is_note {
+            tag_items.push((item.uuid, content));
+            continue;
+        }
+        if content.trashed {
+            data.skipped
+                .push(SkippedItem::new(item.uuid, "in the trash"));
+            continue;
+        }
+
+        let flags = content.app_data.get(APP_DATA_KEY);
+        let mut note = Note::new(user_id, note_title(&content.title), content.text);
+        if let Some(created_at) = timestamp(item.created_at.as_deref()) {
+            note.created_at = created_at;
+        }
+        let updated_at = flags
+            .and_then(|flags| timestamp(flags.client_updated_at.as_deref()))
+            .or_else(|| timestamp(item.updated_at.as_deref()));
+        note.updated_at = updated_at.unwrap_or(note.created_at);
+        note.is_pinned = flags.is_some_and(|flags| flags.pinned);
+        note.is_archived = flags.is_some_and(|flags| flags.archived);
+
+        notes_by_item.insert(item.uuid, data.notes.len());
+        data.notes.push(note);
+    }
+
+    for (uuid, content) in tag_items {
+        let tag = match data.tag(user_id, &content.title) {
+            Ok(tag) => tag,
+            Err(e) => {
+                data.skipped.push(SkippedItem::new(uuid, e.to_string()));
+                continue;
+            }
+        };
+        for reference in content.references {
+            if reference.content_type != "Note" {
+                continue;
+            }
+            if let Some(&index) = notes_by_item.get(&reference.uuid) {
+                let note = &mut data.notes[index];
+                if !note.tags.iter().any(|t| t.id == tag.id) {
+                    note.tags.push(tag.clone());
+                }
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+/// Imports a decrypted Standard Notes backup
+pub struct StandardNotesImporter;
+
+impl Importer for StandardNotesImporter {
+    fn name(&self) -> &'static str {
+        "standard_notes"
+    }
+
+    fn import(
+        &self,
+        body: &[u8],
+        user_id: Uuid,
+        _options: &ImportOptions,
+    ) -> DomainResult<ImportedData> {
+        parse_standard_notes(body, user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_maps_notes_tags_and_skips_encrypted_items() {
+        let backup = r#"{
+            "version": "004",
+            "items": [
+                {
+                    "uuid": "n1", "content_type": "Note",
+                    "created_at": "2021-03-01T10:00:00.000Z",
+                    "updated_at": "2021-03-05T10:00:00.000Z",
+                    "content": {
+                        "title": "Groceries", "text": "- milk", "references": [],
+                        "appData": {"org.standardnotes.sn": {
+                            "pinned": true,
+                            "client_updated_at": "2021-03-04T09:00:00.000Z"
+                        }}
+                    }
+                },
+                {
+                    "uuid": "n2", "content_type": "Note",
+                    "created_at": "2021-03-02T10:00:00.000Z",
+                    "content": {"title": "", "text": "old", "trashed": true}
+                },
+                {
+                    "uuid": "t1", "content_type": "Tag",
+                    "content": {"title": "Home", "references": [
+                        {"uuid": "n1", "content_type": "Note"}
+                    ]}
+                },
+                {"uuid": "e1", "content_type": "Note", "content": "004:abc:def", "enc_item_key": "004:xyz"},
+                {"uuid": "k1", "content_type": "SN|ItemsKey", "content": "004:abc"},
+                {"uuid": "d1", "content_type": "Note", "deleted": true}
+            ]
+        }"#;
+
+        let data = parse_standard_notes(backup.as_bytes(), Uuid::nil()).unwrap();
+        assert_eq!(data.notes.len(), 1);
+        let note = &data.notes[0];
+        assert_eq!(note.title_str(), "Groceries");
+        assert_eq!(note.content, "- milk");
+        assert!(note.is_pinned);
+        assert!(!note.is_archived);
+        assert_eq!(note.created_at.to_rfc3339(), "2021-03-01T10:00:00+00:00");
+        assert_eq!(note.updated_at.to_rfc3339(), "2021-03-04T09:00:00+00:00");
+        assert_eq!(note.tags.len(), 1);
+        assert_eq!(note.tags[0].name_str(), "home");
+        assert_eq!(data.tags.len(), 1);
+
+        assert_eq!(
+            data.skipped,
+            vec![
+                SkippedItem::new("n2", "in the trash"),
+                SkippedItem::new("e1", "encrypted"),
+            ]
+        );
+        assert!(parse_standard_notes(b"[]", Uuid::nil()).is_err());
+    }
+}
@@ -0,0 +1,452 @@
+//! SQLite implementation of InstanceSettingsRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::SqlitePool;
+
+use crate::db::{decode_error, map_sqlx_error};
+use notes_domain::{
+    DomainResult, InstanceSettingsRepository,
+    instance::InstanceSettingsUpdate,
+    jobs::JobStatus,
+    overview::{DailyCount, DatabaseMetrics, JobCounts, UserCounts},
+    trash::{StorageStats, TrashPurgeReport},
+};
+
+const READ_ONLY_KEY: &str = "read_only";
+const PURGED_NOTES_KEY: &str = "trash_purged_notes";
+const RECLAIMED_BYTES_KEY: &str = "trash_reclaimed_bytes";
+const LAST_PURGED_AT_KEY: &str = "trash_last_purged_at";
+const ALLOW_REGISTRATION_KEY: &str = "allow_registration";
+const MAX_PINNED_NOTES_KEY: &str = "max_pinned_notes";
+const SMART_FEATURES_KEY: &str = "smart_features_enabled";
+const PRINT_LOGO_URL_KEY: &str = "print_logo_url";
+const PRINT_ACCENT_COLOR_KEY: &str = "print_accent_color";
+const NOTE_COLORS_KEY: &str = "note_colors";
+const SHOW_ARCHIVED_KEY: &str = "show_archived";
+const MAX_NOTE_LENGTH_KEY: &str = "max_note_length";
+const ATTACHMENT_TYPES_KEY: &str = "attachment_types";
+const MARKDOWN_KEY: &str = "markdown";
+
+/// SQLite adapter for instance settings, stored as key/value rows
+pub struct SqliteInstanceSettingsRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteInstanceSettingsRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn get(&self, key: &str) -> DomainResult<Option<String>> {
+        sqlx::query_scalar("SELECT value FROM instance_settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_sqlx_error)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> DomainResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO instance_settings (key, value, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn count(&self, sql: &str, since: Option<&str>) -> DomainResult<u64> {
+        let mut query = sqlx::query_scalar::<_, i64>(sql);
+        if let Some(since) = since {
+            query = query.bind(since);
+        }
+        let count = query.fetch_one(&self.pool).await.map_err(map_sqlx_error)?;
+        Ok(count.max(0) as u64)
+    }
+
+    /// Stored value of `key`, ignoring values that no longer parse
+    async fn get_parsed<T: std::str::FromStr>(&self, key: &str) -> DomainResult<Option<T>> {
+        Ok(self.get(key).await?.and_then(|value| value.parse().ok()))
+    }
+
+    /// Stored JSON value of `key`, ignoring values that no longer deserialize
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, key: &str) -> DomainResult<Option<T>> {
+        Ok(self
+            .get(key)
+            .await?
+            .and_then(|value| serde_json::from_str(&value).ok()))
+    }
+
+    async fn set_json<T: serde::Serialize>(&self, key: &str, value: &T) -> DomainResult<()> {
+        let value = serde_json::to_string(value)
+            .map_err(|e| decode_error(format!("Failed to encode {}: {}", key, e)))?;
+        self.set(key, &value).await
+    }
+
+    async fn get_counter(&self, key: &str) -> DomainResult<u64> {
+        Ok(self
+            .get(key)
+            .await?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0))
+    }
+}
+
+#[async_trait]
+impl InstanceSettingsRepository for SqliteInstanceSettingsRepository {
+    async fn is_read_only(&self) -> DomainResult<bool> {
+        Ok(self.get(READ_ONLY_KEY).await?.as_deref() == Some("true"))
+    }
+
+    async fn set_read_only(&self, read_only: bool) -> DomainResult<()> {
+        self.set(READ_ONLY_KEY, if read_only { "true" } else { "false" })
+            .await
+    }
+
+    async fn record_trash_purge(&self, report: &TrashPurgeReport) -> DomainResult<()> {
+        let purged = self.get_counter(PURGED_NOTES_KEY).await? + report.note_ids.len() as u64;
+        let reclaimed = self.get_counter(RECLAIMED_BYTES_KEY).await? + report.reclaimed_bytes;
+
+        self.set(PURGED_NOTES_KEY, &purged.to_string()).await?;
+        self.set(RECLAIMED_BYTES_KEY, &reclaimed.to_string())
+            .await?;
+        self.set(LAST_PURGED_AT_KEY, &Utc::now().to_rfc3339()).await
+    }
+
+    async fn storage_stats(&self) -> DomainResult<StorageStats> {
+        let last_purged_at = self
+            .get(LAST_PURGED_AT_KEY)
+            .await?
+            .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+
+        Ok(StorageStats {
+            purged_notes: self.get_counter(PURGED_NOTES_KEY).await?,
+            reclaimed_bytes: self.get_counter(RECLAIMED_BYTES_KEY).await?,
+            last_purged_at,
+        })
+    }
+
+    async fn database_metrics(&self, since: DateTime<Utc>) -> DomainResult<DatabaseMetrics> {
+        let since = since.to_rfc3339();
+        let since = Some(since.as_str());
+
+        let users = UserCounts {
+            total: self.count("SELECT COUNT(*) FROM users", None).await?,
+            new: self
+                .count("SELECT COUNT(*) FROM users WHERE created_at >= ?", since)
+                .await?,
+            active: self
+                .count(
+                    "SELECT COUNT(DISTINCT user_id) FROM notes WHERE updated_at >= ?",
+                    since,
+                )
+                .await?,
+        };
+
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT substr(created_at, 1, 10) AS day, COUNT(*)
+            FROM notes
+            WHERE created_at >= ?
+            GROUP BY day
+            ORDER BY day
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        let notes_created = rows
+            .into_iter()
+            .map(|(day, count)| {
+                let date = NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                    .map_err(|e| decode_error(format!("Invalid date: {}", e)))?;
+                Ok(DailyCount {
+                    date,
+                    count: count.max(0) as u64,
+                })
+            })
+            .collect::<DomainResult<_>>()?;
+
+        let content_bytes = self
+            .count(
+                r#"
+                SELECT
+                    (SELECT COALESCE(SUM(LENGTH(CAST(COALESCE(title, '') AS BLOB))
+                                         + LENGTH(CAST(content AS BLOB))), 0) FROM notes)
+                  + (SELECT COALESCE(SUM(LENGTH(CAST(COALESCE(title, '') AS BLOB))
+                                         + LENGTH(CAST(content AS BLOB))), 0) FROM note_versions)
+                "#,
+                None,
+            )
+            .await?;
+
+        let failed_jobs = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM jobs WHERE status = ? AND created_at >= ?",
+        )
+        .bind(JobStatus::Failed.as_str())
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+        let running_jobs =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM jobs WHERE status = ?")
+                .bind(JobStatus::Running.as_str())
+                .fetch_one(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        Ok(DatabaseMetrics {
+            users,
+            notes: self
+                .count("SELECT COUNT(*) FROM notes WHERE deleted_at IS NULL", None)
+                .await?,
+            trashed_notes: self
+                .count(
+                    "SELECT COUNT(*) FROM notes WHERE deleted_at IS NOT NULL",
+                    None,
+                )
+                .await?,
+            notes_created,
+            database_bytes: self
+                .count(
+                    "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                    None,
+                )
+                .await?,
+            content_bytes,
+            jobs: JobCounts {
+                running: running_jobs.max(0) as u64,
+                failed: failed_jobs.max(0) as u64,
+            },
+        })
+    }
+
+    async fn settings_overrides(&self) -> DomainResult<InstanceSettingsUpdate> {
+        Ok(InstanceSettingsUpdate {
+            allow_registration: self.get_parsed(ALLOW_REGISTRATION_KEY).await?,
+            max_pinned_notes: self.get_parsed(MAX_PINNED_NOTES_KEY).await?,
+            smart_features_enabled: self.get_parsed(SMART_FEATURES_KEY).await?,
+            print_logo_url: self.get(PRINT_LOGO_URL_KEY).await?,
+            print_accent_color: self.get(PRINT_ACCENT_COLOR_KEY).await?,
+            note_colors: self.get_json(NOTE_COLORS_KEY).await?,
+            show_archived: self.get_parsed(SHOW_ARCHIVED_KEY).await?,
+            max_note_length: self.get_parsed(MAX_NOTE_LENGTH_KEY).await?,
+            attachment_types: self.get_json(ATTACHMENT_TYPES_KEY).await?,
+            markdown: self.get_json(MARKDOWN_KEY).await?,
+        })
+    }
+
+    async fn update_settings(&self, update: &InstanceSettingsUpdate) -> DomainResult<()> {
+        if let Some(allow) = update.allow_registration {
+            self.set(ALLOW_REGISTRATION_KEY, &allow.to_string()).await?;
+        }
+        if let Some(max) = update.max_pinned_notes {
+            self.set(MAX_PINNED_NOTES_KEY, &max.to_string()).await?;
+        }
+        if let Some(enabled) = update.smart_features_enabled {
+            self.set(SMART_FEATURES_KEY, &enabled.to_string()).await?;
+        }
+        // Empty strings are stored so a cleared value keeps overriding the default
+        if let Some(url) = &update.print_logo_url {
+            self.set(PRINT_LOGO_URL_KEY, url).await?;
+        }
+        if let Some(color) = &update.print_accent_color {
+            self.set(PRINT_ACCENT_COLOR_KEY, color).await?;
+        }
+        if let Some(colors) = &update.note_colors {
+            self.set_json(NOTE_COLORS_KEY, colors).await?;
+        }
+        if let Some(show) = update.show_archived {
+            self.set(SHOW_ARCHIVED_KEY, &show.to_string()).await?;
+        }
+        // `0` is stored so a lifted limit keeps overriding the default
+        if let Some(max) = update.max_note_length {
+            self.set(MAX_NOTE_LENGTH_KEY, &max.to_string()).await?;
+        }
+        if let Some(types) = &update.attachment_types {
+            self.set_json(ATTACHMENT_TYPES_KEY, types).await?;
+        }
+        if let Some(markdown) = &update.markdown {
+            self.set_json(MARKDOWN_KEY, markdown).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k_core::db::DatabaseConfig;
+
+    use super::*;
+    use crate::db::run_migrations;
+    use notes_domain::instance::MarkdownOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_read_only_defaults_to_false_and_toggles() {
+        let repo = SqliteInstanceSettingsRepository::new(setup_test_db().await);
+
+        assert!(!repo.is_read_only().await.unwrap());
+
+        repo.set_read_only(true).await.unwrap();
+        assert!(repo.is_read_only().await.unwrap());
+
+        repo.set_read_only(false).await.unwrap();
+        assert!(!repo.is_read_only().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_settings_overrides_keep_unchanged_values() {
+        let repo = SqliteInstanceSettingsRepository::new(setup_test_db().await);
+        assert_eq!(
+            repo.settings_overrides().await.unwrap(),
+            InstanceSettingsUpdate::default()
+        );
+
+        repo.update_settings(&InstanceSettingsUpdate {
+            allow_registration: Some(false),
+            max_pinned_notes: Some(3),
+            smart_features_enabled: None,
+            print_logo_url: None,
+            print_accent_color: Some("#123456".to_string()),
+            note_colors: Some(vec!["DEFAULT".to_string(), "#ffcc00".to_string()]),
+            show_archived: None,
+            max_note_length: Some(0),
+            attachment_types: Some(Vec::new()),
+            markdown: None,
+        })
+        .await
+        .unwrap();
+        repo.update_settings(&InstanceSettingsUpdate {
+            max_pinned_notes: Some(4),
+            markdown: Some(MarkdownOptions {
+                hard_breaks: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            repo.settings_overrides().await.unwrap(),
+            InstanceSettingsUpdate {
+                allow_registration: Some(false),
+                max_pinned_notes: Some(4),
+                smart_features_enabled: None,
+                print_logo_url: None,
+                print_accent_color: Some("#123456".to_string()),
+                note_colors: Some(vec!["DEFAULT".to_string(), "#ffcc00".to_string()]),
+                show_archived: None,
+                max_note_length: Some(0),
+                attachment_types: Some(Vec::new()),
+                markdown: Some(MarkdownOptions {
+                    hard_breaks: true,
+                    ..Default::default()
+                }),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trash_purges_accumulate_in_storage_stats() {
+        let repo = SqliteInstanceSettingsRepository::new(setup_test_db().await);
+        assert_eq!(repo.storage_stats().await.unwrap(), StorageStats::default());
+
+        let report = TrashPurgeReport {
+            note_ids: vec![uuid::Uuid::new_v4(), uuid::Uuid::new_v4()],
+            reclaimed_bytes: 100,
+        };
+        repo.record_trash_purge(&report).await.unwrap();
+        repo.record_trash_purge(&report).await.unwrap();
+
+        let stats = repo.storage_stats().await.unwrap();
+        assert_eq!(stats.purged_notes, 4);
+        assert_eq!(stats.reclaimed_bytes, 200);
+        assert!(stats.last_purged_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_database_metrics_count_users_notes_and_jobs() {
+        use crate::job_repository::SqliteJobRepository;
+        use crate::note_repository::SqliteNoteRepository;
+        use crate::user_repository::SqliteUserRepository;
+        use notes_domain::jobs::{Job, JobKind};
+        use notes_domain::{Email, JobRepository, Note, NoteRepository, User, UserRepository};
+
+        let pool = setup_test_db().await;
+        let user = User::new(
+            "test|metrics",
+            Email::try_from("metrics@example.com").unwrap(),
+        );
+        SqliteUserRepository::new(pool.clone())
+            .save(&user)
+            .await
+            .unwrap();
+
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        note_repo
+            .save(&Note::new(user.id, None, "héllo"))
+            .await
+            .unwrap();
+        let mut trashed = Note::new(user.id, None, "gone");
+        trashed.move_to_trash();
+        note_repo.save(&trashed).await.unwrap();
+
+        let mut failed = Job::new(user.id, JobKind::Import, None);
+        failed.status = JobStatus::Failed;
+        SqliteJobRepository::new(pool.clone())
+            .save(&failed)
+            .await
+            .unwrap();
+
+        let repo = SqliteInstanceSettingsRepository::new(pool);
+        let metrics = repo
+            .database_metrics(Utc::now() - chrono::Duration::days(1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            metrics.users,
+            UserCounts {
+                total: 1,
+                new: 1,
+                active: 1
+            }
+        );
+        assert_eq!(metrics.notes, 1);
+        assert_eq!(metrics.trashed_notes, 1);
+        assert_eq!(
+            metrics.notes_created.iter().map(|d| d.count).sum::<u64>(),
+            2
+        );
+        assert_eq!(metrics.content_bytes, 10);
+        assert!(metrics.database_bytes > 0);
+        assert_eq!(
+            metrics.jobs,
+            JobCounts {
+                running: 0,
+                failed: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! SQLite implementation of InvitationRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{DomainResult, InvitationRepository, invitations::Invitation};
+
+/// SQLite adapter for InvitationRepository
+pub struct SqliteInvitationRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteInvitationRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct InvitationRow {
+    id: String,
+    code: String,
+    created_by: String,
+    max_uses: Option<i64>,
+    uses: i64,
+    expires_at: Option<String>,
+    created_at: String,
+}
+
+fn parse_datetime(s: &str) -> DomainResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))
+}
+
+fn parse_uuid(s: &str) -> DomainResult<Uuid> {
+    Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))
+}
+
+fn parse_count(n: i64) -> DomainResult<u32> {
+    u32::try_from(n).map_err(|e| decode_error(format!("Invalid invitation count: {}", e)))
+}
+
+impl InvitationRow {
+    fn try_into_invitation(self) -> DomainResult<Invitation> {
+        Ok(Invitation {
+            id: parse_uuid(&self.id)?,
+            code: self.code,
+            created_by: parse_uuid(&self.created_by)?,
+            max_uses: self.max_uses.map(parse_count).transpose()?,
+            uses: parse_count(self.uses)?,
+            expires_at: self.expires_at.as_deref().map(parse_datetime).transpose()?,
+            created_at: parse_datetime(&self.created_at)?,
+        })
+    }
+}
+
+#[async_trait]
+impl InvitationRepository for SqliteInvitationRepository {
+    async fn save(&self, invitation: &Invitation) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO invitations (id, code, created_by, max_uses, uses, expires_at, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(invitation.id.to_string())
+            .bind(&invitation.code)
+            .bind(invitation.created_by.to_string())
+            .bind(invitation.max_uses.map(i64::from))
+            .bind(i64::from(invitation.uses))
+            .bind(invitation.expires_at.map(|t| t.to_rfc3339()))
+            .bind(invitation.created_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Invitation>> {
+        let row: Option<InvitationRow> = sqlx::query_as("SELECT * FROM invitations WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        row.map(InvitationRow::try_into_invitation).transpose()
+    }
+
+    async fn find_by_code(&self, code: &str) -> DomainResult<Option<Invitation>> {
+        let row: Option<InvitationRow> = sqlx::query_as("SELECT * FROM invitations WHERE code = ?")
+            .bind(code)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        row.map(InvitationRow::try_into_invitation).transpose()
+    }
+
+    async fn find_all(&self) -> DomainResult<Vec<Invitation>> {
+        let rows: Vec<InvitationRow> =
+            sqlx::query_as("SELECT * FROM invitations ORDER BY created_at DESC")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(InvitationRow::try_into_invitation)
+            .collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query("DELETE FROM invitations WHERE id = ?")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn redeem(&self, id: Uuid, user_id: Uuid, now: DateTime<Utc>) -> DomainResult<bool> {
+        let now = now.to_rfc3339();
+        let now = now.as_str();
+        write(move || async move {
+            let id_str = id.to_string();
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            // The usability check and the increment are one statement, so two
+            // registrations cannot both take the last use
+            let claimed = sqlx::query(
+                r#"
+                UPDATE invitations
+                SET uses = uses + 1
+                WHERE id = ?
+                  AND (max_uses IS NULL OR uses < max_uses)
+                  AND (expires_at IS NULL OR expires_at > ?)
+                "#,
+            )
+            .bind(&id_str)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_sqlx_error)?
+            .rows_affected()
+                == 1;
+            if !claimed {
+                return Ok(false);
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO invitation_redemptions (invitation_id, user_id, redeemed_at)
+                VALUES (?, ?, ?)
+                "#,
+            )
+            .bind(&id_str)
+            .bind(user_id.to_string())
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn find_invited_users(&self, id: Uuid) -> DomainResult<Vec<Uuid>> {
+        let user_ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT user_id FROM invitation_redemptions
+            WHERE invitation_id = ?
+            ORDER BY redeemed_at
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        user_ids.iter().map(|id| parse_uuid(id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool, subject: &str, email: &str) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(subject, Email::try_from(email).unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_redeem_counts_uses_up_to_the_limit() {
+        let pool = setup_test_db().await;
+        let admin = create_test_user(&pool, "test|admin", "admin@example.com").await;
+        let first = create_test_user(&pool, "test|first", "first@example.com").await;
+        let second = create_test_user(&pool, "test|second", "second@example.com").await;
+        let repo = SqliteInvitationRepository::new(pool);
+
+        let invitation = Invitation::new(admin.id, Some(1), None).unwrap();
+        repo.save(&invitation).await.unwrap();
+        assert_eq!(
+            repo.find_by_code(&invitation.code).await.unwrap(),
+            Some(invitation.clone())
+        );
+
+        let now = Utc::now();
+        assert!(repo.redeem(invitation.id, first.id, now).await.unwrap());
+        assert!(!repo.redeem(invitation.id, second.id, now).await.unwrap());
+
+        let stored = repo.find_by_id(invitation.id).await.unwrap().unwrap();
+        assert_eq!(stored.uses, 1);
+        assert_eq!(
+            repo.find_invited_users(invitation.id).await.unwrap(),
+            vec![first.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_deleted_invitations_cannot_be_redeemed() {
+        let pool = setup_test_db().await;
+        let admin = create_test_user(&pool, "test|admin", "admin@example.com").await;
+        let repo = SqliteInvitationRepository::new(pool);
+
+        let invitation = Invitation::new(
+            admin.id,
+            None,
+            Some(Utc::now() + chrono::Duration::hours(1)),
+        )
+        .unwrap();
+        repo.save(&invitation).await.unwrap();
+
+        let later = Utc::now() + chrono::Duration::hours(2);
+        assert!(!repo.redeem(invitation.id, admin.id, later).await.unwrap());
+
+        repo.delete(invitation.id).await.unwrap();
+        assert!(repo.find_all().await.unwrap().is_empty());
+        assert!(
+            !repo
+                .redeem(invitation.id, admin.id, Utc::now())
+                .await
+                .unwrap()
+        );
+    }
+}
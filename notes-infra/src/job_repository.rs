@@ -0,0 +1,259 @@
+//! SQLite implementation of JobRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{
+    DomainResult, JobRepository,
+    jobs::{Job, JobStatus},
+};
+
+/// SQLite adapter for JobRepository
+pub struct SqliteJobRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteJobRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct JobRow {
+    id: String,
+    user_id: String,
+    kind: String,
+    status: String,
+    processed: i64,
+    total: Option<i64>,
+    error: Option<String>,
+    result: Option<String>,
+    created_at: String,
+    updated_at: String,
+    finished_at: Option<String>,
+}
+
+fn parse_datetime(s: &str) -> DomainResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))
+}
+
+fn parse_count(n: i64) -> DomainResult<u64> {
+    u64::try_from(n).map_err(|e| decode_error(format!("Invalid job count: {}", e)))
+}
+
+impl JobRow {
+    fn try_into_job(self) -> DomainResult<Job> {
+        let parse_uuid =
+            |s: &str| Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)));
+
+        Ok(Job {
+            id: parse_uuid(&self.id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            kind: self
+                .kind
+                .parse()
+                .map_err(|e| decode_error(format!("{}", e)))?,
+            status: self
+                .status
+                .parse()
+                .map_err(|e| decode_error(format!("{}", e)))?,
+            processed: parse_count(self.processed)?,
+            total: self.total.map(parse_count).transpose()?,
+            error: self.error,
+            result: self
+                .result
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(|e| decode_error(format!("Invalid job result: {}", e)))?,
+            created_at: parse_datetime(&self.created_at)?,
+            updated_at: parse_datetime(&self.updated_at)?,
+            finished_at: self
+                .finished_at
+                .as_deref()
+                .map(parse_datetime)
+                .transpose()?,
+        })
+    }
+}
+
+#[async_trait]
+impl JobRepository for SqliteJobRepository {
+    async fn save(&self, job: &Job) -> DomainResult<()> {
+        let result = job
+            .result
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| decode_error(e.to_string()))?;
+        let result = result.as_deref();
+
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO jobs (id, user_id, kind, status, processed, total, error, result,
+                                  created_at, updated_at, finished_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    status = excluded.status,
+                    processed = excluded.processed,
+                    total = excluded.total,
+                    error = excluded.error,
+                    result = excluded.result,
+                    updated_at = excluded.updated_at,
+                    finished_at = excluded.finished_at
+                "#,
+            )
+            .bind(job.id.to_string())
+            .bind(job.user_id.to_string())
+            .bind(job.kind.as_str())
+            .bind(job.status.as_str())
+            .bind(job.processed as i64)
+            .bind(job.total.map(|t| t as i64))
+            .bind(&job.error)
+            .bind(result)
+            .bind(job.created_at.to_rfc3339())
+            .bind(job.updated_at.to_rfc3339())
+            .bind(job.finished_at.map(|t| t.to_rfc3339()))
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Job>> {
+        let row: Option<JobRow> = sqlx::query_as("SELECT * FROM jobs WHERE id = ?")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        row.map(JobRow::try_into_job).transpose()
+    }
+
+    async fn find_by_user(&self, user_id: Uuid, limit: usize) -> DomainResult<Vec<Job>> {
+        let rows: Vec<JobRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM jobs
+            WHERE user_id = ?
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(JobRow::try_into_job).collect()
+    }
+
+    async fn fail_running(&self, error: &str) -> DomainResult<u64> {
+        let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
+        write(move || async move {
+            let result = sqlx::query(
+                r#"
+                UPDATE jobs
+                SET status = ?, error = ?, updated_at = ?, finished_at = ?
+                WHERE status = ?
+                "#,
+            )
+            .bind(JobStatus::Failed.as_str())
+            .bind(error)
+            .bind(now)
+            .bind(now)
+            .bind(JobStatus::Running.as_str())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::jobs::JobKind;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new("test|jobs", Email::try_from("jobs@example.com").unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_save_updates_and_lists_newest_first() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteJobRepository::new(pool);
+
+        let mut older = Job::new(user.id, JobKind::Import, Some(10));
+        older.created_at -= chrono::Duration::minutes(1);
+        repo.save(&older).await.unwrap();
+        older.processed = 10;
+        older.result = Some(serde_json::json!({ "notes": 10 }));
+        repo.save(&older).await.unwrap();
+
+        let newer = Job::new(user.id, JobKind::SitePublish, None);
+        repo.save(&newer).await.unwrap();
+
+        assert_eq!(
+            repo.find_by_id(older.id).await.unwrap(),
+            Some(older.clone())
+        );
+        let jobs = repo.find_by_user(user.id, 10).await.unwrap();
+        assert_eq!(
+            jobs.iter().map(|j| j.id).collect::<Vec<_>>(),
+            vec![newer.id, older.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fail_running_only_touches_running_jobs() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteJobRepository::new(pool);
+
+        let running = Job::new(user.id, JobKind::Import, None);
+        let mut failed = Job::new(user.id, JobKind::Import, None);
+        failed.status = JobStatus::Failed;
+        failed.error = Some("bad input".to_string());
+        repo.save(&running).await.unwrap();
+        repo.save(&failed).await.unwrap();
+
+        assert_eq!(repo.fail_running("restarted").await.unwrap(), 1);
+
+        let running = repo.find_by_id(running.id).await.unwrap().unwrap();
+        assert_eq!(running.status, JobStatus::Failed);
+        assert_eq!(running.error.as_deref(), Some("restarted"));
+        assert!(running.finished_at.is_some());
+        let failed = repo.find_by_id(failed.id).await.unwrap().unwrap();
+        assert_eq!(failed.error.as_deref(), Some("bad input"));
+    }
+}
@@ -0,0 +1,216 @@
+//! SQLite implementation of LegalRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{
+    DomainResult, LegalRepository,
+    legal::{LegalAcceptance, LegalDocument, LegalDocumentKind},
+};
+
+/// SQLite adapter for LegalRepository
+pub struct SqliteLegalRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteLegalRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct LegalDocumentRow {
+    kind: String,
+    version: i64,
+    content: String,
+    published_at: String,
+}
+
+fn parse_datetime(s: &str) -> DomainResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))
+}
+
+impl LegalDocumentRow {
+    fn try_into_document(self) -> DomainResult<LegalDocument> {
+        Ok(LegalDocument {
+            kind: self
+                .kind
+                .parse()
+                .map_err(|e| decode_error(format!("Invalid document kind: {}", e)))?,
+            version: u32::try_from(self.version)
+                .map_err(|e| decode_error(format!("Invalid version: {}", e)))?,
+            content: self.content,
+            published_at: parse_datetime(&self.published_at)?,
+        })
+    }
+}
+
+/// Latest version of each document
+const CURRENT_DOCUMENTS: &str = r#"
+    SELECT d.* FROM legal_documents d
+    WHERE d.version = (SELECT MAX(version) FROM legal_documents WHERE kind = d.kind)
+"#;
+
+#[async_trait]
+impl LegalRepository for SqliteLegalRepository {
+    async fn save_document(&self, document: &LegalDocument) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO legal_documents (kind, version, content, published_at)
+                VALUES (?, ?, ?, ?)
+                "#,
+            )
+            .bind(document.kind.as_str())
+            .bind(i64::from(document.version))
+            .bind(&document.content)
+            .bind(document.published_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_current(&self, kind: LegalDocumentKind) -> DomainResult<Option<LegalDocument>> {
+        let row: Option<LegalDocumentRow> = sqlx::query_as(
+            "SELECT * FROM legal_documents WHERE kind = ? ORDER BY version DESC LIMIT 1",
+        )
+        .bind(kind.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        row.map(LegalDocumentRow::try_into_document).transpose()
+    }
+
+    async fn find_all_current(&self) -> DomainResult<Vec<LegalDocument>> {
+        let rows: Vec<LegalDocumentRow> =
+            sqlx::query_as(&format!("{} ORDER BY d.kind", CURRENT_DOCUMENTS))
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(LegalDocumentRow::try_into_document)
+            .collect()
+    }
+
+    async fn save_acceptance(&self, acceptance: &LegalAcceptance) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO legal_acceptances (user_id, kind, version, accepted_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(user_id, kind, version) DO NOTHING
+                "#,
+            )
+            .bind(acceptance.user_id.to_string())
+            .bind(acceptance.kind.as_str())
+            .bind(i64::from(acceptance.version))
+            .bind(acceptance.accepted_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_unaccepted(&self, user_id: Uuid) -> DomainResult<Vec<LegalDocument>> {
+        let rows: Vec<LegalDocumentRow> = sqlx::query_as(&format!(
+            r#"
+            {}
+            AND NOT EXISTS (
+                SELECT 1 FROM legal_acceptances a
+                WHERE a.user_id = ? AND a.kind = d.kind AND a.version = d.version
+            )
+            ORDER BY d.kind
+            "#,
+            CURRENT_DOCUMENTS
+        ))
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(LegalDocumentRow::try_into_document)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool, subject: &str, email: &str) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(subject, Email::try_from(email).unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_only_current_versions_count_as_accepted() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool, "test|user", "user@example.com").await;
+        let repo = SqliteLegalRepository::new(pool);
+
+        let terms = LegalDocument::publish(LegalDocumentKind::Terms, "# Terms", None).unwrap();
+        let privacy =
+            LegalDocument::publish(LegalDocumentKind::Privacy, "# Privacy", None).unwrap();
+        repo.save_document(&terms).await.unwrap();
+        repo.save_document(&privacy).await.unwrap();
+        repo.save_acceptance(&LegalAcceptance::new(user.id, LegalDocumentKind::Terms, 1))
+            .await
+            .unwrap();
+        // Accepting twice is a no-op
+        repo.save_acceptance(&LegalAcceptance::new(user.id, LegalDocumentKind::Terms, 1))
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.find_unaccepted(user.id).await.unwrap(),
+            vec![privacy.clone()]
+        );
+
+        let revised =
+            LegalDocument::publish(LegalDocumentKind::Terms, "# Terms, revised", Some(&terms))
+                .unwrap();
+        repo.save_document(&revised).await.unwrap();
+        assert_eq!(
+            repo.find_current(LegalDocumentKind::Terms).await.unwrap(),
+            Some(revised.clone())
+        );
+        assert_eq!(
+            repo.find_all_current().await.unwrap(),
+            vec![privacy.clone(), revised.clone()]
+        );
+        assert_eq!(
+            repo.find_unaccepted(user.id).await.unwrap(),
+            vec![privacy, revised]
+        );
+        // Publishing the same version twice is a conflict
+        assert!(repo.save_document(&terms).await.is_err());
+    }
+}
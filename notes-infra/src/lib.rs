@@ -8,37 +8,154 @@
 //! - [`SqliteNoteRepository`] - SQLite adapter for notes with FTS5 search
 //! - [`SqliteUserRepository`] - SQLite adapter for users (OIDC-ready)
 //! - [`SqliteTagRepository`] - SQLite adapter for tags
+//! - [`SqliteSearchHistoryRepository`] - SQLite adapter for per-user search history
+//! - [`SqliteJobRepository`] - SQLite adapter for background job progress
+//! - [`SqliteEventLogRepository`] - SQLite adapter for the per-user event log
+//! - [`SqliteNoteIssueRepository`] - SQLite adapter for issues found by the lint job
+//! - [`SqliteBoardRepository`] - SQLite adapter for kanban boards and their columns
+//! - [`SqliteAudioUploadRepository`] - SQLite adapter for voice memos waiting to be transcribed
+//! - [`SqliteNoteRelationRepository`] - SQLite adapter for typed relations between notes
+//! - [`SqliteTagAliasRepository`] - SQLite adapter for alternative tag names
+//! - [`SqliteImpersonationRepository`] - SQLite adapter for administrators' impersonations of users
+//! - [`SqliteLegalRepository`] - SQLite adapter for legal documents and users' acceptance of them
+//! - [`SqliteInstanceSettingsRepository`] - SQLite adapter for instance settings (maintenance mode)
+//! - [`SqliteHousekeepingRepository`] - SQLite adapter for finding and fixing leftovers in the database
+//! - [`SqliteUnitOfWork`] - SQLite transaction adapter for atomic note writes
+//! - [`cache::CachedNoteRepository`] / [`cache::CachedTagRepository`] - Caching decorators (moka or Redis)
+//! - [`replica::ReplicatedNoteRepository`] and friends - Route reads to a read replica, writes to the primary
+//! - [`pdf::chromium::ChromiumPdfRenderer`] - Headless Chromium adapter for PDF export
+//! - [`mail::log::LogEmailSender`] - Email adapter that logs instead of sending
+//! - [`challenge::pow::ProofOfWorkVerifier`] - Self-hosted proof-of-work challenge for registration
+//! - [`web::link_checker::HttpUrlChecker`] - Link checker that refuses private network addresses
+//! - [`web::preview::HttpLinkPreviewFetcher`] - Bookmark previews from page metadata
+//! - [`web::image::HttpImageFetcher`] - External images of notes loaded for the image proxy
+//! - [`signing::HmacSigner`] - HMAC signatures of image proxy URLs and registration challenges
+//! - [`text::openai::OpenAiTextGenerator`] - Text generation with a chat model behind an OpenAI-compatible API
+//! - [`proofread::languagetool::LanguageToolProofreader`] - Spelling and grammar suggestions from a LanguageTool server
+//! - [`speech::piper::PiperSpeechSynthesizer`] / [`speech::openai::OpenAiSpeechSynthesizer`] - Notes read aloud locally or through an OpenAI-compatible API
+//! - [`transcription::whisper::WhisperCppTranscriber`] / [`transcription::openai::OpenAiTranscriber`] - Voice memos transcribed locally or through an OpenAI-compatible API
+//! - [`diagram::kroki::KrokiDiagramRenderer`] - Mermaid and PlantUML diagrams drawn by a Kroki server
+//! - [`password::compat::CompatPasswordHasher`] - Argon2id hasher that also verifies legacy bcrypt hashes
+//! - [`formats::FormatRegistry`] - Import and export formats by name
 //!
 //! ## Database
 //!
+//! - [`db::connect`] - Connect with SQLite tuned for concurrent access (WAL, busy timeout)
+//! - [`db::connect_replica`] - Connect read-only to a replica
 //! - [`db::run_migrations`] - Run database migrations
+//! - [`search_index::ensure_search_tokenizer`] - Rebuild the search index for the configured tokenizer
 
+#[cfg(feature = "sqlite")]
+pub mod announcement_repository;
+#[cfg(feature = "sqlite")]
+pub mod audio_upload_repository;
 pub mod auth;
-#[cfg(feature = "broker-nats")]
+#[cfg(feature = "sqlite")]
+pub mod board_repository;
+#[cfg(any(feature = "broker-nats", feature = "broker-mqtt"))]
 pub mod broker;
+pub mod cache;
+pub mod challenge;
 pub mod db;
+#[cfg(feature = "diagrams")]
+pub mod diagram;
 #[cfg(feature = "smart-features")]
 pub mod embeddings;
+#[cfg(feature = "sqlite")]
+pub mod event_log_repository;
 pub mod factory;
+pub mod formats;
+#[cfg(feature = "sqlite")]
+pub mod housekeeping_repository;
+mod html_scan;
+#[cfg(feature = "sqlite")]
+pub mod impersonation_repository;
+pub mod import;
+#[cfg(feature = "sqlite")]
+pub mod instance_settings_repository;
+#[cfg(feature = "sqlite")]
+pub mod invitation_repository;
+#[cfg(feature = "sqlite")]
+pub mod job_repository;
+#[cfg(feature = "sqlite")]
+pub mod legal_repository;
 #[cfg(feature = "sqlite")]
 pub mod link_repository;
+pub mod mail;
+#[cfg(feature = "sqlite")]
+pub mod note_issue_repository;
+#[cfg(feature = "sqlite")]
+pub mod note_relation_repository;
 #[cfg(feature = "sqlite")]
 pub mod note_repository;
+pub mod password;
+pub mod pdf;
+#[cfg(feature = "proofreading")]
+pub mod proofread;
+pub mod render;
+pub mod replica;
+#[cfg(feature = "sqlite")]
+pub mod search_history_repository;
+#[cfg(feature = "sqlite")]
+pub mod search_index;
 pub mod session_store;
+pub mod signing;
+#[cfg(feature = "speech")]
+pub mod speech;
+#[cfg(feature = "sqlite")]
+pub mod tag_alias_repository;
 #[cfg(feature = "sqlite")]
 pub mod tag_repository;
+#[cfg(feature = "text-generation")]
+pub mod text;
+#[cfg(feature = "transcription")]
+pub mod transcription;
+#[cfg(feature = "sqlite")]
+pub mod unit_of_work;
 #[cfg(feature = "sqlite")]
 pub mod user_repository;
 #[cfg(feature = "smart-features")]
 pub mod vector;
+#[cfg(feature = "web-fetch")]
+pub mod web;
 
 // Re-export for convenience
+#[cfg(feature = "sqlite")]
+pub use announcement_repository::SqliteAnnouncementRepository;
+#[cfg(feature = "sqlite")]
+pub use audio_upload_repository::SqliteAudioUploadRepository;
+#[cfg(feature = "sqlite")]
+pub use board_repository::SqliteBoardRepository;
 pub use db::run_migrations;
 #[cfg(feature = "sqlite")]
+pub use event_log_repository::SqliteEventLogRepository;
+#[cfg(feature = "sqlite")]
+pub use housekeeping_repository::SqliteHousekeepingRepository;
+#[cfg(feature = "sqlite")]
+pub use impersonation_repository::SqliteImpersonationRepository;
+#[cfg(feature = "sqlite")]
+pub use instance_settings_repository::SqliteInstanceSettingsRepository;
+#[cfg(feature = "sqlite")]
+pub use invitation_repository::SqliteInvitationRepository;
+#[cfg(feature = "sqlite")]
+pub use job_repository::SqliteJobRepository;
+#[cfg(feature = "sqlite")]
+pub use legal_repository::SqliteLegalRepository;
+#[cfg(feature = "sqlite")]
 pub use link_repository::SqliteLinkRepository;
 #[cfg(feature = "sqlite")]
+pub use note_issue_repository::SqliteNoteIssueRepository;
+#[cfg(feature = "sqlite")]
+pub use note_relation_repository::SqliteNoteRelationRepository;
+#[cfg(feature = "sqlite")]
 pub use note_repository::SqliteNoteRepository;
 #[cfg(feature = "sqlite")]
+pub use search_history_repository::SqliteSearchHistoryRepository;
+#[cfg(feature = "sqlite")]
+pub use tag_alias_repository::SqliteTagAliasRepository;
+#[cfg(feature = "sqlite")]
 pub use tag_repository::SqliteTagRepository;
 #[cfg(feature = "sqlite")]
+pub use unit_of_work::SqliteUnitOfWork;
+#[cfg(feature = "sqlite")]
 pub use user_repository::SqliteUserRepository;
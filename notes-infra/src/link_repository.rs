@@ -2,8 +2,9 @@ use async_trait::async_trait;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use notes_domain::entities::NoteLink;
-use notes_domain::errors::{DomainError, DomainResult};
+use crate::db::{map_sqlx_error, write};
+use notes_domain::entities::{NoteLink, RELATED_NOTE_SNIPPET_LENGTH, RelatedNote};
+use notes_domain::errors::DomainResult;
 use notes_domain::ports::LinkRepository;
 
 pub struct SqliteLinkRepository {
@@ -19,71 +20,110 @@ impl SqliteLinkRepository {
 #[async_trait]
 impl LinkRepository for SqliteLinkRepository {
     async fn save_links(&self, links: &[NoteLink]) -> DomainResult<()> {
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
-
-        for link in links {
-            let source = link.source_note_id.to_string();
-            let target = link.target_note_id.to_string();
-            let created_at = link.created_at.to_rfc3339();
-
-            sqlx::query(
-                r#"
-                INSERT INTO note_links (source_note_id, target_note_id, score, created_at)
-                VALUES (?, ?, ?, ?)
-                ON CONFLICT(source_note_id, target_note_id) DO UPDATE SET
-                    score = excluded.score,
-                    created_at = excluded.created_at
-                "#,
-            )
-            .bind(source)
-            .bind(target)
-            .bind(link.score)
-            .bind(created_at)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
-        }
+        write(move || async move {
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
 
-        tx.commit()
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+            for link in links {
+                let source = link.source_note_id.to_string();
+                let target = link.target_note_id.to_string();
+                let created_at = link.created_at.to_rfc3339();
 
-        Ok(())
+                sqlx::query(
+                    r#"
+                    INSERT INTO note_links (source_note_id, target_note_id, score, created_at)
+                    VALUES (?, ?, ?, ?)
+                    ON CONFLICT(source_note_id, target_note_id) DO UPDATE SET
+                        score = excluded.score,
+                        created_at = excluded.created_at
+                    "#,
+                )
+                .bind(source)
+                .bind(target)
+                .bind(link.score)
+                .bind(created_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_sqlx_error)?;
+            }
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
     }
 
     async fn delete_links_for_source(&self, source_note_id: Uuid) -> DomainResult<()> {
-        let source_str = source_note_id.to_string();
-        sqlx::query("DELETE FROM note_links WHERE source_note_id = ?")
-            .bind(source_str)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        write(move || async move {
+            let source_str = source_note_id.to_string();
+            sqlx::query("DELETE FROM note_links WHERE source_note_id = ?")
+                .bind(source_str)
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
-    async fn get_links_for_note(&self, source_note_id: Uuid) -> DomainResult<Vec<NoteLink>> {
+    async fn get_links_for_note(
+        &self,
+        source_note_id: Uuid,
+        limit: usize,
+        min_score: f32,
+    ) -> DomainResult<Vec<RelatedNote>> {
         let source_str = source_note_id.to_string();
 
-        // We select links where the note is the source
-        // TODO: Should we also include links where the note is the target?
-        // For now, let's stick to outgoing links as defined by the service logic.
-        // Actually, semantic similarity is symmetric, but we only save (A -> B) if we process A.
-        // Ideally we should look for both directions or enforce symmetry.
-        // Given current implementation saves A->B when A is processed, if B is processed it saves B->A.
-        // So just querying source_note_id is fine if we assume all notes are processed.
+        // Similarity is symmetric, but a link is only stored in the direction of
+        // the note that was processed. Read both directions and keep the best
+        // score per related note, so results don't depend on processing order.
+        // Joining the target also hides notes that are in the trash.
+        let rows = sqlx::query_as::<_, SqliteRelatedNote>(
+            r#"
+            SELECT ?1 AS source_note_id, r.other AS target_note_id,
+                   MAX(r.score) AS score, MAX(r.created_at) AS created_at,
+                   n.title, substr(n.content, 1, ?2) AS snippet
+            FROM (
+                SELECT target_note_id AS other, score, created_at
+                FROM note_links WHERE source_note_id = ?1
+                UNION ALL
+                SELECT source_note_id AS other, score, created_at
+                FROM note_links WHERE target_note_id = ?1
+            ) r
+            JOIN notes n ON n.id = r.other
+            WHERE r.other != ?1 AND n.deleted_at IS NULL
+            GROUP BY r.other
+            HAVING MAX(r.score) >= ?3
+            ORDER BY score DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(source_str)
+        .bind(RELATED_NOTE_SNIPPET_LENGTH as i64)
+        .bind(min_score)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(rows.into_iter().map(RelatedNote::from).collect())
+    }
 
+    async fn get_links_for_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteLink>> {
         let links = sqlx::query_as::<_, SqliteNoteLink>(
-            "SELECT * FROM note_links WHERE source_note_id = ? ORDER BY score DESC",
+            r#"
+            SELECT l.source_note_id, l.target_note_id, l.score, l.created_at
+            FROM note_links l
+            JOIN notes s ON s.id = l.source_note_id
+            JOIN notes t ON t.id = l.target_note_id
+            WHERE s.user_id = ? AND t.user_id = s.user_id
+            "#,
         )
-        .bind(source_str)
+        .bind(user_id.to_string())
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         Ok(links.into_iter().map(NoteLink::from).collect())
     }
@@ -97,6 +137,24 @@ struct SqliteNoteLink {
     created_at: String, // Stored as ISO string
 }
 
+#[derive(sqlx::FromRow)]
+struct SqliteRelatedNote {
+    #[sqlx(flatten)]
+    link: SqliteNoteLink,
+    title: Option<String>,
+    snippet: String,
+}
+
+impl From<SqliteRelatedNote> for RelatedNote {
+    fn from(row: SqliteRelatedNote) -> Self {
+        Self {
+            link: NoteLink::from(row.link),
+            title: row.title.unwrap_or_default(),
+            snippet: row.snippet,
+        }
+    }
+}
+
 impl From<SqliteNoteLink> for NoteLink {
     fn from(row: SqliteNoteLink) -> Self {
         Self {
@@ -109,3 +167,99 @@ impl From<SqliteNoteLink> for NoteLink {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::note_repository::SqliteNoteRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, Note, NoteRepository, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_notes(pool: &SqlitePool, count: usize) -> Vec<Note> {
+        let user = User::new("test|user", Email::try_from("test@example.com").unwrap());
+        SqliteUserRepository::new(pool.clone())
+            .save(&user)
+            .await
+            .unwrap();
+
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let mut notes = Vec::new();
+        for _ in 0..count {
+            let note = Note::new(user.id, None, "content");
+            note_repo.save(&note).await.unwrap();
+            notes.push(note);
+        }
+        notes
+    }
+
+    #[tokio::test]
+    async fn test_links_are_returned_in_both_directions() {
+        let pool = setup_test_db().await;
+        let notes = create_notes(&pool, 3).await;
+        let (a, b, c) = (notes[0].id, notes[1].id, notes[2].id);
+        let repo = SqliteLinkRepository::new(pool);
+
+        // Only A and C were processed: A -> B and C -> A
+        repo.save_links(&[NoteLink::new(a, b, 0.9), NoteLink::new(c, a, 0.7)])
+            .await
+            .unwrap();
+
+        let from_b = repo.get_links_for_note(b, 10, 0.0).await.unwrap();
+        assert_eq!(from_b.len(), 1);
+        assert_eq!(from_b[0].link.source_note_id, b);
+        assert_eq!(from_b[0].link.target_note_id, a);
+
+        let from_a = repo.get_links_for_note(a, 10, 0.0).await.unwrap();
+        let targets: Vec<Uuid> = from_a.iter().map(|r| r.link.target_note_id).collect();
+        assert_eq!(targets, vec![b, c]);
+    }
+
+    #[tokio::test]
+    async fn test_links_stored_both_ways_are_deduplicated() {
+        let pool = setup_test_db().await;
+        let notes = create_notes(&pool, 2).await;
+        let (a, b) = (notes[0].id, notes[1].id);
+        let repo = SqliteLinkRepository::new(pool);
+
+        repo.save_links(&[NoteLink::new(a, b, 0.6), NoteLink::new(b, a, 0.8)])
+            .await
+            .unwrap();
+
+        let links = repo.get_links_for_note(a, 10, 0.0).await.unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].link.score, 0.8);
+    }
+
+    #[tokio::test]
+    async fn test_related_notes_are_filtered_limited_and_previewed() {
+        let pool = setup_test_db().await;
+        let notes = create_notes(&pool, 4).await;
+        let (a, b, c, d) = (notes[0].id, notes[1].id, notes[2].id, notes[3].id);
+        let repo = SqliteLinkRepository::new(pool);
+
+        repo.save_links(&[
+            NoteLink::new(a, b, 0.9),
+            NoteLink::new(a, c, 0.8),
+            NoteLink::new(a, d, 0.2),
+        ])
+        .await
+        .unwrap();
+
+        let related = repo.get_links_for_note(a, 1, 0.5).await.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].link.target_note_id, b);
+        assert_eq!(related[0].snippet, "content");
+
+        let related = repo.get_links_for_note(a, 10, 0.5).await.unwrap();
+        assert_eq!(related.len(), 2);
+    }
+}
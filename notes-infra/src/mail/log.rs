@@ -0,0 +1,30 @@
+//! Logging email adapter
+//!
+//! Writes emails to the application log instead of delivering them.
+//! Used when no mail server is configured, e.g. in development.
+
+use async_trait::async_trait;
+
+use notes_domain::{DomainResult, EmailMessage, EmailSender};
+
+#[derive(Debug, Default)]
+pub struct LogEmailSender;
+
+impl LogEmailSender {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl EmailSender for LogEmailSender {
+    async fn send(&self, message: &EmailMessage) -> DomainResult<()> {
+        tracing::info!(
+            to = %message.to,
+            subject = %message.subject,
+            "Email delivery not configured, logging message:\n{}",
+            message.body
+        );
+        Ok(())
+    }
+}
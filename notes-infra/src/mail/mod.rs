@@ -0,0 +1,7 @@
+//! Email sender adapters.
+//!
+//! This module provides implementations of the `EmailSender` port.
+
+pub mod log;
+#[cfg(feature = "mail-smtp")]
+pub mod smtp;
@@ -0,0 +1,65 @@
+//! SMTP email adapter
+
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+
+use notes_domain::{DomainError, DomainResult, EmailMessage, EmailSender};
+
+pub struct SmtpEmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpEmailSender {
+    /// Connect to an SMTP relay using STARTTLS
+    pub fn new(
+        host: &str,
+        port: u16,
+        credentials: Option<(String, String)>,
+        from: &str,
+    ) -> DomainResult<Self> {
+        let from = from
+            .parse::<Mailbox>()
+            .map_err(|e| DomainError::InfrastructureError(format!("Invalid SMTP sender: {}", e)))?;
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|e| DomainError::InfrastructureError(format!("Invalid SMTP host: {}", e)))?
+            .port(port);
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl EmailSender for SmtpEmailSender {
+    async fn send(&self, message: &EmailMessage) -> DomainResult<()> {
+        let to =
+            message.to.as_ref().parse::<Mailbox>().map_err(|e| {
+                DomainError::InfrastructureError(format!("Invalid recipient: {}", e))
+            })?;
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&message.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body.clone())
+            .map_err(|e| DomainError::InfrastructureError(format!("Invalid email: {}", e)))?;
+
+        self.transport.send(email).await.map_err(|e| {
+            DomainError::InfrastructureError(format!("SMTP delivery failed: {}", e))
+        })?;
+
+        Ok(())
+    }
+}
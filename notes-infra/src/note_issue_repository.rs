@@ -0,0 +1,179 @@
+//! SQLite implementation of NoteIssueRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::{DomainResult, NoteIssueRepository, lint::NoteIssue};
+
+/// SQLite adapter for NoteIssueRepository
+pub struct SqliteNoteIssueRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteNoteIssueRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct NoteIssueRow {
+    note_id: String,
+    user_id: String,
+    kind: String,
+    target: String,
+    detail: Option<String>,
+    found_at: String,
+}
+
+impl NoteIssueRow {
+    fn try_into_issue(self) -> DomainResult<NoteIssue> {
+        let parse_uuid =
+            |s: &str| Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)));
+
+        Ok(NoteIssue {
+            note_id: parse_uuid(&self.note_id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            kind: self
+                .kind
+                .parse()
+                .map_err(|e| decode_error(format!("{}", e)))?,
+            target: self.target,
+            detail: self.detail,
+            found_at: DateTime::parse_from_rfc3339(&self.found_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?,
+        })
+    }
+}
+
+#[async_trait]
+impl NoteIssueRepository for SqliteNoteIssueRepository {
+    async fn replace_for_note(&self, note_id: Uuid, issues: &[NoteIssue]) -> DomainResult<()> {
+        write(move || async move {
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            sqlx::query("DELETE FROM note_issues WHERE note_id = ?")
+                .bind(note_id.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            for issue in issues {
+                sqlx::query(
+                    r#"
+                    INSERT INTO note_issues (note_id, user_id, kind, target, detail, found_at)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(note_id.to_string())
+                .bind(issue.user_id.to_string())
+                .bind(issue.kind.as_str())
+                .bind(&issue.target)
+                .bind(&issue.detail)
+                .bind(issue.found_at.to_rfc3339())
+                .execute(&mut *tx)
+                .await
+                .map_err(map_sqlx_error)?;
+            }
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<NoteIssue>> {
+        let rows: Vec<NoteIssueRow> = sqlx::query_as(
+            r#"
+            SELECT note_id, user_id, kind, target, detail, found_at FROM note_issues
+            WHERE note_id = ?
+            ORDER BY id
+            "#,
+        )
+        .bind(note_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(NoteIssueRow::try_into_issue).collect()
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteIssue>> {
+        // Trashed notes keep their rows until purged but are not reported
+        let rows: Vec<NoteIssueRow> = sqlx::query_as(
+            r#"
+            SELECT i.note_id, i.user_id, i.kind, i.target, i.detail, i.found_at
+            FROM note_issues i
+            JOIN notes n ON n.id = i.note_id
+            WHERE i.user_id = ? AND n.deleted_at IS NULL
+            ORDER BY i.id
+            "#,
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(NoteIssueRow::try_into_issue).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::note_repository::SqliteNoteRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::lint::NoteIssueKind;
+    use notes_domain::{Email, Note, NoteRepository, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool, subject: &str, email: &str) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(subject, Email::try_from(email).unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_replace_for_note_overwrites_previous_run() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool, "test|user", "user@example.com").await;
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let mut note = Note::new(user.id, None, "[[Missing]] https://gone.example");
+        note_repo.save(&note).await.unwrap();
+        let repo = SqliteNoteIssueRepository::new(pool);
+
+        let broken = NoteIssue::new(&note, NoteIssueKind::BrokenUrl, "https://gone.example")
+            .with_detail("HTTP 404");
+        let dangling = NoteIssue::new(&note, NoteIssueKind::DanglingWikiLink, "Missing");
+        repo.replace_for_note(note.id, &[broken.clone(), dangling.clone()])
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.find_by_note(note.id).await.unwrap(),
+            vec![broken, dangling.clone()]
+        );
+
+        repo.replace_for_note(note.id, std::slice::from_ref(&dangling))
+            .await
+            .unwrap();
+        assert_eq!(repo.find_by_user(user.id).await.unwrap(), vec![dangling]);
+
+        note.move_to_trash();
+        note_repo.save(&note).await.unwrap();
+        assert!(repo.find_by_user(user.id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+//! SQLite implementation of NoteRelationRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::relations::NoteRelation;
+use notes_domain::{DomainResult, NoteRelationRepository};
+
+/// SQLite adapter for NoteRelationRepository
+pub struct SqliteNoteRelationRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteNoteRelationRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct NoteRelationRow {
+    id: String,
+    user_id: String,
+    source_note_id: String,
+    target_note_id: String,
+    kind: String,
+    created_at: String,
+}
+
+fn parse_uuid(s: &str) -> DomainResult<Uuid> {
+    Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))
+}
+
+impl NoteRelationRow {
+    fn try_into_relation(self) -> DomainResult<NoteRelation> {
+        Ok(NoteRelation {
+            id: parse_uuid(&self.id)?,
+            user_id: parse_uuid(&self.user_id)?,
+            source_id: parse_uuid(&self.source_note_id)?,
+            target_id: parse_uuid(&self.target_note_id)?,
+            kind: self
+                .kind
+                .parse()
+                .map_err(|e| decode_error(format!("{}", e)))?,
+            created_at: DateTime::parse_from_rfc3339(&self.created_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?,
+        })
+    }
+}
+
+fn into_relations(rows: Vec<NoteRelationRow>) -> DomainResult<Vec<NoteRelation>> {
+    rows.into_iter()
+        .map(NoteRelationRow::try_into_relation)
+        .collect()
+}
+
+#[async_trait]
+impl NoteRelationRepository for SqliteNoteRelationRepository {
+    async fn save(&self, relation: &NoteRelation) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO note_relations
+                    (id, user_id, source_note_id, target_note_id, kind, created_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(relation.id.to_string())
+            .bind(relation.user_id.to_string())
+            .bind(relation.source_id.to_string())
+            .bind(relation.target_id.to_string())
+            .bind(relation.kind.as_str())
+            .bind(relation.created_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<NoteRelation>> {
+        let row: Option<NoteRelationRow> =
+            sqlx::query_as("SELECT * FROM note_relations WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        row.map(NoteRelationRow::try_into_relation).transpose()
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<NoteRelation>> {
+        let rows: Vec<NoteRelationRow> =
+            sqlx::query_as("SELECT * FROM note_relations WHERE user_id = ? ORDER BY created_at")
+                .bind(user_id.to_string())
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        into_relations(rows)
+    }
+
+    async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<NoteRelation>> {
+        let rows: Vec<NoteRelationRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM note_relations
+            WHERE source_note_id = ?1 OR target_note_id = ?1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(note_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        into_relations(rows)
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query("DELETE FROM note_relations WHERE id = ?")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::note_repository::SqliteNoteRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::relations::RelationKind;
+    use notes_domain::{Email, Note, NoteRepository, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(
+            "test|relation",
+            Email::try_from("relation@example.com").unwrap(),
+        );
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_relations_are_removed_with_their_notes() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let repo = SqliteNoteRelationRepository::new(pool);
+
+        let (a, b, c) = (
+            Note::new(user.id, None, "a"),
+            Note::new(user.id, None, "b"),
+            Note::new(user.id, None, "c"),
+        );
+        for note in [&a, &b, &c] {
+            note_repo.save(note).await.unwrap();
+        }
+        let parent = NoteRelation::new(user.id, a.id, RelationKind::ParentOf, b.id);
+        let blocked = NoteRelation::new(user.id, c.id, RelationKind::BlockedBy, a.id);
+        repo.save(&parent).await.unwrap();
+        repo.save(&blocked).await.unwrap();
+        assert!(repo.save(&parent).await.is_err());
+
+        assert_eq!(
+            repo.find_by_id(parent.id).await.unwrap(),
+            Some(parent.clone())
+        );
+        assert_eq!(repo.find_by_note(a.id).await.unwrap().len(), 2);
+        assert_eq!(repo.find_by_note(b.id).await.unwrap(), vec![parent.clone()]);
+
+        note_repo.delete(c.id).await.unwrap();
+        assert_eq!(
+            repo.find_by_user(user.id).await.unwrap(),
+            vec![parent.clone()]
+        );
+
+        repo.delete(parent.id).await.unwrap();
+        assert!(repo.find_by_user(user.id).await.unwrap().is_empty());
+    }
+}
@@ -2,12 +2,18 @@
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool};
+use sqlx::{Executor, FromRow, QueryBuilder, Sqlite, SqlitePool};
 use uuid::Uuid;
 
+use crate::db::{decode_error, escape_like, map_sqlx_error, prefix_upper_bound, write};
+use notes_domain::bookmarks::LinkPreview;
+use notes_domain::geo::GeoPoint;
+use notes_domain::language::Language;
+use notes_domain::query::{NotePredicate, NoteQuery, normalize_tag};
+use notes_domain::search::TitleSuggestion;
 use notes_domain::{
-    DomainError, DomainResult, Note, NoteFilter, NoteRepository, NoteTitle, NoteVersion, Tag,
-    TagName,
+    DomainError, DomainResult, Latitude, Longitude, Note, NoteCounts, NoteFilter, NoteRepository,
+    NoteSortOrder, NoteTitle, NoteVersion, PlaceName, Tag, TagName,
 };
 
 /// SQLite adapter for NoteRepository
@@ -30,9 +36,18 @@ struct NoteRowWithTags {
     content: String,
     color: String,
     is_pinned: i32,
+    pin_order: Option<i32>,
     is_archived: i32,
+    is_locked: i32,
     created_at: String,
     updated_at: String,
+    deleted_at: Option<String>,
+    link_previews: String,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    place_name: Option<String>,
+    remind_at: Option<String>,
+    language: Option<String>,
     tags_json: String,
 }
 
@@ -43,14 +58,14 @@ fn parse_datetime(s: &str) -> Result<DateTime<Utc>, DomainError> {
         .or_else(|_| {
             chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").map(|dt| dt.and_utc())
         })
-        .map_err(|e| DomainError::RepositoryError(format!("Invalid datetime: {}", e)))
+        .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))
 }
 
 /// Helper to parse tags from JSON array
 fn parse_tags_json(tags_json: &str) -> Result<Vec<Tag>, DomainError> {
     // SQLite returns [null] for LEFT JOIN with no matches
     let parsed: Vec<serde_json::Value> = serde_json::from_str(tags_json)
-        .map_err(|e| DomainError::RepositoryError(format!("Failed to parse tags JSON: {}", e)))?;
+        .map_err(|e| decode_error(format!("Failed to parse tags JSON: {}", e)))?;
 
     parsed
         .into_iter()
@@ -58,45 +73,73 @@ fn parse_tags_json(tags_json: &str) -> Result<Vec<Tag>, DomainError> {
         .map(|v| {
             let id_str = v["id"]
                 .as_str()
-                .ok_or_else(|| DomainError::RepositoryError("Missing tag id".to_string()))?;
+                .ok_or_else(|| decode_error("Missing tag id"))?;
             let name = v["name"]
                 .as_str()
-                .ok_or_else(|| DomainError::RepositoryError("Missing tag name".to_string()))?;
+                .ok_or_else(|| decode_error("Missing tag name"))?;
             let user_id_str = v["user_id"]
                 .as_str()
-                .ok_or_else(|| DomainError::RepositoryError("Missing tag user_id".to_string()))?;
+                .ok_or_else(|| decode_error("Missing tag user_id"))?;
 
             let id = Uuid::parse_str(id_str)
-                .map_err(|e| DomainError::RepositoryError(format!("Invalid tag UUID: {}", e)))?;
+                .map_err(|e| decode_error(format!("Invalid tag UUID: {}", e)))?;
             let user_id = Uuid::parse_str(user_id_str)
-                .map_err(|e| DomainError::RepositoryError(format!("Invalid tag user_id: {}", e)))?;
+                .map_err(|e| decode_error(format!("Invalid tag user_id: {}", e)))?;
 
             // Parse TagName from stored string
-            let tag_name = TagName::try_from(name.to_string()).map_err(|e| {
-                DomainError::RepositoryError(format!("Invalid tag name in DB: {}", e))
-            })?;
+            let tag_name = TagName::try_from(name.to_string())
+                .map_err(|e| decode_error(format!("Invalid tag name in DB: {}", e)))?;
 
-            Ok(Tag::with_id(id, tag_name, user_id))
+            Ok(Tag {
+                is_pinned: v["is_pinned"].as_i64().is_some_and(|pinned| pinned != 0),
+                sort_order: v["sort_order"].as_i64().map(|order| order as i32),
+                ..Tag::with_id(id, tag_name, user_id)
+            })
         })
         .collect()
 }
 
 impl NoteRowWithTags {
     fn try_into_note(self) -> Result<Note, DomainError> {
-        let id = Uuid::parse_str(&self.id)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid UUID: {}", e)))?;
+        let id =
+            Uuid::parse_str(&self.id).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
         let user_id = Uuid::parse_str(&self.user_id)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid UUID: {}", e)))?;
+            .map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
 
         let created_at = parse_datetime(&self.created_at)?;
         let updated_at = parse_datetime(&self.updated_at)?;
+        let deleted_at = self.deleted_at.as_deref().map(parse_datetime).transpose()?;
+        let remind_at = self.remind_at.as_deref().map(parse_datetime).transpose()?;
         let tags = parse_tags_json(&self.tags_json)?;
+        let link_previews = serde_json::from_str(&self.link_previews)
+            .map_err(|e| decode_error(format!("Failed to parse link previews: {}", e)))?;
+
+        let location = match (self.latitude, self.longitude) {
+            (Some(latitude), Some(longitude)) => Some(GeoPoint::new(
+                Latitude::new(latitude)
+                    .map_err(|e| decode_error(format!("Invalid latitude in DB: {}", e)))?,
+                Longitude::new(longitude)
+                    .map_err(|e| decode_error(format!("Invalid longitude in DB: {}", e)))?,
+            )),
+            _ => None,
+        };
+        let place_name = self
+            .place_name
+            .map(PlaceName::try_from)
+            .transpose()
+            .map_err(|e| decode_error(format!("Invalid place name in DB: {}", e)))?;
+        let language = self
+            .language
+            .map(Language::try_from)
+            .transpose()
+            .map_err(|e| decode_error(format!("Invalid language in DB: {}", e)))?;
 
         // Parse optional title - empty string or NULL maps to None
         let title: Option<NoteTitle> = match self.title {
-            Some(t) if !t.trim().is_empty() => Some(NoteTitle::try_from(t).map_err(|e| {
-                DomainError::RepositoryError(format!("Invalid title in DB: {}", e))
-            })?),
+            Some(t) if !t.trim().is_empty() => Some(
+                NoteTitle::try_from(t)
+                    .map_err(|e| decode_error(format!("Invalid title in DB: {}", e)))?,
+            ),
             _ => None,
         };
 
@@ -107,10 +150,18 @@ impl NoteRowWithTags {
             content: self.content,
             color: self.color,
             is_pinned: self.is_pinned != 0,
+            pin_order: self.pin_order,
             is_archived: self.is_archived != 0,
+            is_locked: self.is_locked != 0,
             created_at,
             updated_at,
+            deleted_at,
             tags,
+            link_previews,
+            location,
+            place_name,
+            remind_at,
+            language,
         })
     }
 }
@@ -122,14 +173,15 @@ struct NoteVersionRow {
     title: Option<String>, // Title can be NULL
     content: String,
     created_at: String,
+    created_by: Option<String>,
 }
 
 impl NoteVersionRow {
     fn try_into_version(self) -> Result<NoteVersion, DomainError> {
-        let id = Uuid::parse_str(&self.id)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid UUID: {}", e)))?;
+        let id =
+            Uuid::parse_str(&self.id).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
         let note_id = Uuid::parse_str(&self.note_id)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid UUID: {}", e)))?;
+            .map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
 
         let created_at = DateTime::parse_from_rfc3339(&self.created_at)
             .map(|dt| dt.with_timezone(&Utc))
@@ -137,7 +189,13 @@ impl NoteVersionRow {
                 chrono::NaiveDateTime::parse_from_str(&self.created_at, "%Y-%m-%d %H:%M:%S")
                     .map(|dt| dt.and_utc())
             })
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid datetime: {}", e)))?;
+            .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?;
+        let created_by = self
+            .created_by
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
 
         Ok(NoteVersion {
             id,
@@ -145,21 +203,189 @@ impl NoteVersionRow {
             title: self.title, // Already Option<String>
             content: self.content,
             created_at,
+            created_by,
         })
     }
 }
 
+/// Insert or update a note row; shared with the unit of work
+pub(crate) async fn upsert_note<'e, E>(executor: E, note: &Note) -> DomainResult<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let id = note.id.to_string();
+    let user_id = note.user_id.to_string();
+    let is_pinned: i32 = if note.is_pinned { 1 } else { 0 };
+    let is_archived: i32 = if note.is_archived { 1 } else { 0 };
+    let is_locked: i32 = if note.is_locked { 1 } else { 0 };
+    let created_at = note.created_at.to_rfc3339();
+    let updated_at = note.updated_at.to_rfc3339();
+    let deleted_at = note.deleted_at.map(|dt| dt.to_rfc3339());
+    // Convert Option<NoteTitle> to Option<&str> for binding
+    let title_str: Option<&str> = note.title.as_ref().map(|t| t.as_ref());
+    let latitude = note.location.map(|l| l.latitude.degrees());
+    let longitude = note.location.map(|l| l.longitude.degrees());
+    let place_name: Option<&str> = note.place_name.as_ref().map(|p| p.as_ref());
+    let remind_at = note.remind_at.map(|dt| dt.to_rfc3339());
+    let language = note.language.as_ref().map(|l| l.code());
+
+    sqlx::query(
+        r#"
+        INSERT INTO notes (id, user_id, title, content, color, is_pinned, pin_order, is_archived, is_locked, created_at, updated_at, deleted_at,
+                           latitude, longitude, place_name, remind_at, language)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            title = excluded.title,
+            content = excluded.content,
+            color = excluded.color,
+            is_pinned = excluded.is_pinned,
+            pin_order = excluded.pin_order,
+            is_archived = excluded.is_archived,
+            is_locked = excluded.is_locked,
+            updated_at = excluded.updated_at,
+            deleted_at = excluded.deleted_at,
+            latitude = excluded.latitude,
+            longitude = excluded.longitude,
+            place_name = excluded.place_name,
+            remind_at = excluded.remind_at,
+            language = excluded.language
+        "#
+    )
+    .bind(&id)
+    .bind(&user_id)
+    .bind(title_str)
+    .bind(&note.content)
+    .bind(&note.color)
+    .bind(is_pinned)
+    .bind(note.pin_order)
+    .bind(is_archived)
+    .bind(is_locked)
+    .bind(&created_at)
+    .bind(&updated_at)
+    .bind(&deleted_at)
+    .bind(latitude)
+    .bind(longitude)
+    .bind(place_name)
+    .bind(&remind_at)
+    .bind(language)
+    .execute(executor)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(())
+}
+
+/// Insert a note version row; shared with the unit of work
+pub(crate) async fn insert_version<'e, E>(executor: E, version: &NoteVersion) -> DomainResult<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let id = version.id.to_string();
+    let note_id = version.note_id.to_string();
+    let created_at = version.created_at.to_rfc3339();
+    let created_by = version.created_by.map(|id| id.to_string());
+
+    sqlx::query(
+        r#"
+        INSERT INTO note_versions (id, note_id, title, content, created_at, created_by)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&note_id)
+    .bind(&version.title)
+    .bind(&version.content)
+    .bind(&created_at)
+    .bind(&created_by)
+    .execute(executor)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(())
+}
+
+/// Append the SQL condition for `predicate`; every value is bound, never inlined
+fn push_predicate(qb: &mut QueryBuilder<'_, Sqlite>, predicate: &NotePredicate) {
+    match predicate {
+        NotePredicate::And(predicates) => push_group(qb, predicates, " AND ", "1 = 1"),
+        NotePredicate::Or(predicates) => push_group(qb, predicates, " OR ", "1 = 0"),
+        NotePredicate::Not(predicate) => {
+            qb.push("NOT (");
+            push_predicate(qb, predicate);
+            qb.push(")");
+        }
+        NotePredicate::Tag(name) => {
+            qb.push(
+                "EXISTS (SELECT 1 FROM note_tags qnt JOIN tags qt ON qnt.tag_id = qt.id \
+                 WHERE qnt.note_id = n.id AND qt.name = ",
+            )
+            .push_bind(normalize_tag(name))
+            .push(")");
+        }
+        NotePredicate::Color(color) => {
+            qb.push("n.color = ").push_bind(color.clone());
+        }
+        NotePredicate::Pinned(pinned) => {
+            qb.push("n.is_pinned = ").push_bind(i32::from(*pinned));
+        }
+        NotePredicate::Archived(archived) => {
+            qb.push("n.is_archived = ").push_bind(i32::from(*archived));
+        }
+        NotePredicate::Text(text) => {
+            let pattern = format!("%{}%", escape_like(text));
+            qb.push("(COALESCE(n.title, '') LIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR n.content LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+        NotePredicate::CreatedAfter(at) => push_date(qb, "n.created_at", ">", at),
+        NotePredicate::CreatedBefore(at) => push_date(qb, "n.created_at", "<", at),
+        NotePredicate::UpdatedAfter(at) => push_date(qb, "n.updated_at", ">", at),
+        NotePredicate::UpdatedBefore(at) => push_date(qb, "n.updated_at", "<", at),
+    }
+}
+
+fn push_group(
+    qb: &mut QueryBuilder<'_, Sqlite>,
+    predicates: &[NotePredicate],
+    separator: &str,
+    when_empty: &str,
+) {
+    if predicates.is_empty() {
+        qb.push(when_empty);
+        return;
+    }
+    qb.push("(");
+    for (i, predicate) in predicates.iter().enumerate() {
+        if i > 0 {
+            qb.push(separator);
+        }
+        push_predicate(qb, predicate);
+    }
+    qb.push(")");
+}
+
+/// Compare through julianday() so legacy "YYYY-MM-DD HH:MM:SS" values order correctly
+fn push_date(qb: &mut QueryBuilder<'_, Sqlite>, column: &str, op: &str, at: &DateTime<Utc>) {
+    qb.push(format!("julianday({}) {} julianday(", column, op))
+        .push_bind(at.to_rfc3339())
+        .push(")");
+}
+
 #[async_trait]
 impl NoteRepository for SqliteNoteRepository {
     async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Note>> {
         let id_str = id.to_string();
         let row: Option<NoteRowWithTags> = sqlx::query_as(
             r#"
-            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.is_archived, 
-                   n.created_at, n.updated_at,
+            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.pin_order, n.is_archived, n.is_locked, 
+                   n.created_at, n.updated_at, n.deleted_at, n.link_previews,
+                   n.latitude, n.longitude, n.place_name, n.remind_at, n.language,
                    json_group_array(
                        CASE WHEN t.id IS NOT NULL 
-                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id)
+                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id,
+                                        'is_pinned', t.is_pinned, 'sort_order', t.sort_order)
                        ELSE NULL END
                    ) as tags_json
             FROM notes n
@@ -172,7 +398,7 @@ impl NoteRepository for SqliteNoteRepository {
         .bind(&id_str)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         match row {
             Some(row) => Ok(Some(row.try_into_note()?)),
@@ -186,11 +412,13 @@ impl NoteRepository for SqliteNoteRepository {
         // Build dynamic query using QueryBuilder for safety
         let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
             r#"
-            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.is_archived,
-                   n.created_at, n.updated_at,
+            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.pin_order, n.is_archived, n.is_locked,
+                   n.created_at, n.updated_at, n.deleted_at, n.link_previews,
+                   n.latitude, n.longitude, n.place_name, n.remind_at, n.language,
                    json_group_array(
                        CASE WHEN t.id IS NOT NULL
-                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id)
+                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id,
+                                        'is_pinned', t.is_pinned, 'sort_order', t.sort_order)
                        ELSE NULL END
                    ) as tags_json
             FROM notes n
@@ -213,6 +441,12 @@ impl NoteRepository for SqliteNoteRepository {
                 .push_bind(if archived { 1i32 } else { 0i32 });
         }
 
+        query_builder.push(if filter.trashed {
+            " AND n.deleted_at IS NOT NULL"
+        } else {
+            " AND n.deleted_at IS NULL"
+        });
+
         if let Some(tag_id) = filter.tag_id {
             query_builder
                 .push(" AND n.id IN (SELECT note_id FROM note_tags WHERE tag_id = ")
@@ -220,85 +454,152 @@ impl NoteRepository for SqliteNoteRepository {
                 .push(")");
         }
 
-        query_builder.push(" GROUP BY n.id ORDER BY n.is_pinned DESC, n.updated_at DESC");
+        if let Some(bbox) = filter.within {
+            // A box crossing the antimeridian covers both ends of the longitude range
+            let longitudes = if bbox.crosses_antimeridian() {
+                " OR "
+            } else {
+                " AND "
+            };
+            query_builder
+                .push(" AND n.rowid IN (SELECT id FROM note_locations WHERE min_lat >= ")
+                .push_bind(bbox.min_latitude)
+                .push(" AND max_lat <= ")
+                .push_bind(bbox.max_latitude)
+                .push(" AND (min_lon >= ")
+                .push_bind(bbox.min_longitude)
+                .push(longitudes)
+                .push("max_lon <= ")
+                .push_bind(bbox.max_longitude)
+                .push("))");
+        }
+
+        query_builder
+            .push(" GROUP BY n.id ORDER BY n.is_pinned DESC, n.pin_order ASC, n.updated_at DESC");
 
         let rows: Vec<NoteRowWithTags> = query_builder
             .build_query_as()
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+            .map_err(map_sqlx_error)?;
 
         rows.into_iter().map(|row| row.try_into_note()).collect()
     }
 
     async fn save(&self, note: &Note) -> DomainResult<()> {
-        let id = note.id.to_string();
-        let user_id = note.user_id.to_string();
-        let is_pinned: i32 = if note.is_pinned { 1 } else { 0 };
-        let is_archived: i32 = if note.is_archived { 1 } else { 0 };
-        let created_at = note.created_at.to_rfc3339();
-        let updated_at = note.updated_at.to_rfc3339();
-        // Convert Option<NoteTitle> to Option<&str> for binding
-        let title_str: Option<&str> = note.title.as_ref().map(|t| t.as_ref());
-
-        sqlx::query(
+        write(move || upsert_note(&self.pool, note)).await
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<u64> {
+        write(move || async move {
+            let id_str = id.to_string();
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            // Measured before deleting; BLOB casts count bytes rather than characters
+            let reclaimed: i64 = sqlx::query_scalar(
+                r#"
+                SELECT
+                    (SELECT COALESCE(SUM(LENGTH(CAST(COALESCE(title, '') AS BLOB)) + LENGTH(CAST(content AS BLOB))), 0)
+                     FROM notes WHERE id = ?1)
+                  + (SELECT COALESCE(SUM(LENGTH(CAST(COALESCE(title, '') AS BLOB)) + LENGTH(CAST(content AS BLOB))), 0)
+                     FROM note_versions WHERE note_id = ?1)
+                "#,
+            )
+            .bind(&id_str)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            // Versions, tags, links, issues and relations cascade
+            sqlx::query("DELETE FROM notes WHERE id = ?")
+                .bind(&id_str)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(reclaimed as u64)
+        })
+        .await
+    }
+
+    async fn count_by_user(&self, user_id: Uuid) -> DomainResult<NoteCounts> {
+        let (total, pinned, archived): (i64, i64, i64) = sqlx::query_as(
             r#"
-            INSERT INTO notes (id, user_id, title, content, color, is_pinned, is_archived, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(id) DO UPDATE SET
-                title = excluded.title,
-                content = excluded.content,
-                color = excluded.color,
-                is_pinned = excluded.is_pinned,
-                is_archived = excluded.is_archived,
-                updated_at = excluded.updated_at
-            "#
+            SELECT COUNT(*),
+                   COALESCE(SUM(is_pinned AND NOT is_archived), 0),
+                   COALESCE(SUM(is_archived), 0)
+            FROM notes
+            WHERE user_id = ? AND deleted_at IS NULL
+            "#,
         )
-        .bind(&id)
-        .bind(&user_id)
-        .bind(title_str)
-        .bind(&note.content)
-        .bind(&note.color)
-        .bind(is_pinned)
-        .bind(is_archived)
-        .bind(&created_at)
-        .bind(&updated_at)
-        .execute(&self.pool)
+        .bind(user_id.to_string())
+        .fetch_one(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
-        Ok(())
+        Ok(NoteCounts {
+            total: total as usize,
+            pinned: pinned as usize,
+            archived: archived as usize,
+        })
     }
 
-    async fn delete(&self, id: Uuid) -> DomainResult<()> {
-        let id_str = id.to_string();
-        sqlx::query("DELETE FROM notes WHERE id = ?")
-            .bind(&id_str)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+    async fn find_trashed_before(&self, cutoff: DateTime<Utc>) -> DomainResult<Vec<Note>> {
+        let rows: Vec<NoteRowWithTags> = sqlx::query_as(
+            r#"
+            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.pin_order, n.is_archived, n.is_locked,
+                   n.created_at, n.updated_at, n.deleted_at, n.link_previews,
+                   n.latitude, n.longitude, n.place_name, n.remind_at, n.language,
+                   json_group_array(
+                       CASE WHEN t.id IS NOT NULL
+                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id,
+                                        'is_pinned', t.is_pinned, 'sort_order', t.sort_order)
+                       ELSE NULL END
+                   ) as tags_json
+            FROM notes n
+            LEFT JOIN note_tags nt ON n.id = nt.note_id
+            LEFT JOIN tags t ON nt.tag_id = t.id
+            WHERE n.deleted_at IS NOT NULL AND n.deleted_at < ?
+            GROUP BY n.id
+            ORDER BY n.deleted_at ASC
+            "#,
+        )
+        .bind(cutoff.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
 
-        Ok(())
+        rows.into_iter().map(|row| row.try_into_note()).collect()
     }
 
-    async fn search(&self, user_id: Uuid, query: &str) -> DomainResult<Vec<Note>> {
+    async fn search(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+    ) -> DomainResult<Vec<Note>> {
         let user_id_str = user_id.to_string();
         let like_query = format!("%{}%", query);
 
         // Use FTS5 for full-text search OR tag name match, with JSON-aggregated tags
         let rows: Vec<NoteRowWithTags> = sqlx::query_as(
             r#"
-            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.is_archived,
-                   n.created_at, n.updated_at,
+            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.pin_order, n.is_archived, n.is_locked,
+                   n.created_at, n.updated_at, n.deleted_at, n.link_previews,
+                   n.latitude, n.longitude, n.place_name, n.remind_at, n.language,
                    json_group_array(
                        CASE WHEN t.id IS NOT NULL
-                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id)
+                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id,
+                                        'is_pinned', t.is_pinned, 'sort_order', t.sort_order)
                        ELSE NULL END
                    ) as tags_json
             FROM notes n
             LEFT JOIN note_tags nt ON n.id = nt.note_id
             LEFT JOIN tags t ON nt.tag_id = t.id
-            WHERE n.user_id = ? 
+            WHERE n.user_id = ? AND n.deleted_at IS NULL
+            AND (? OR n.is_archived = 0)
             AND (
                 n.rowid IN (SELECT rowid FROM notes_fts WHERE notes_fts MATCH ?)
                 OR
@@ -313,36 +614,131 @@ impl NoteRepository for SqliteNoteRepository {
             "#,
         )
         .bind(&user_id_str)
+        .bind(include_archived)
         .bind(query)
         .bind(like_query)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         rows.into_iter().map(|row| row.try_into_note()).collect()
     }
 
-    async fn save_version(&self, version: &NoteVersion) -> DomainResult<()> {
-        let id = version.id.to_string();
-        let note_id = version.note_id.to_string();
-        let created_at = version.created_at.to_rfc3339();
+    async fn search_versions(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+        limit: usize,
+    ) -> DomainResult<Vec<NoteVersion>> {
+        let rows: Vec<NoteVersionRow> = sqlx::query_as(
+            r#"
+            SELECT v.id, v.note_id, v.title, v.content, v.created_at, v.created_by
+            FROM note_versions_fts
+            JOIN note_versions v ON v.rowid = note_versions_fts.rowid
+            JOIN notes n ON n.id = v.note_id
+            WHERE note_versions_fts MATCH ?
+            AND n.user_id = ? AND n.deleted_at IS NULL
+            AND (? OR n.is_archived = 0)
+            ORDER BY bm25(note_versions_fts)
+            LIMIT ?
+            "#,
+        )
+        .bind(query)
+        .bind(user_id.to_string())
+        .bind(include_archived)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(NoteVersionRow::try_into_version)
+            .collect()
+    }
 
-        sqlx::query(
+    async fn suggest_titles(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<TitleSuggestion>> {
+        // A range scan on idx_notes_user_title; NOCASE folds ASCII only
+        let rows: Vec<(String, String)> = sqlx::query_as(
             r#"
-            INSERT INTO note_versions (id, note_id, title, content, created_at)
-            VALUES (?, ?, ?, ?, ?)
+            SELECT id, title FROM notes
+            WHERE user_id = ? AND deleted_at IS NULL
+            AND title >= ? COLLATE NOCASE AND title < ? COLLATE NOCASE
+            ORDER BY title COLLATE NOCASE
+            LIMIT ?
             "#,
         )
-        .bind(&id)
-        .bind(&note_id)
-        .bind(&version.title)
-        .bind(&version.content)
-        .bind(&created_at)
-        .execute(&self.pool)
+        .bind(user_id.to_string())
+        .bind(prefix)
+        .bind(prefix_upper_bound(prefix))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(|(id, title)| {
+                let note_id = Uuid::parse_str(&id)
+                    .map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
+                Ok(TitleSuggestion { note_id, title })
+            })
+            .collect()
+    }
+
+    async fn query(&self, user_id: Uuid, query: &NoteQuery) -> DomainResult<Vec<Note>> {
+        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+            r#"
+            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.pin_order, n.is_archived, n.is_locked,
+                   n.created_at, n.updated_at, n.deleted_at, n.link_previews,
+                   n.latitude, n.longitude, n.place_name, n.remind_at, n.language,
+                   json_group_array(
+                       CASE WHEN t.id IS NOT NULL
+                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id,
+                                        'is_pinned', t.is_pinned, 'sort_order', t.sort_order)
+                       ELSE NULL END
+                   ) as tags_json
+            FROM notes n
+            LEFT JOIN note_tags nt ON n.id = nt.note_id
+            LEFT JOIN tags t ON nt.tag_id = t.id
+            WHERE n.deleted_at IS NULL AND n.user_id = 
+            "#,
+        );
+        query_builder.push_bind(user_id.to_string());
 
-        Ok(())
+        if let Some(ref filter) = query.filter {
+            query_builder.push(" AND ");
+            push_predicate(&mut query_builder, filter);
+        }
+
+        query_builder.push(match query.sort {
+            NoteSortOrder::UpdatedDesc => " GROUP BY n.id ORDER BY n.updated_at DESC, n.id",
+            NoteSortOrder::CreatedDesc => " GROUP BY n.id ORDER BY n.created_at DESC, n.id",
+            NoteSortOrder::TitleAsc => {
+                " GROUP BY n.id ORDER BY COALESCE(n.title, '') COLLATE NOCASE ASC, n.id"
+            }
+        });
+        query_builder
+            .push(" LIMIT ")
+            .push_bind(query.limit() as i64)
+            .push(" OFFSET ")
+            .push_bind(query.offset as i64);
+
+        let rows: Vec<NoteRowWithTags> = query_builder
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(|row| row.try_into_note()).collect()
+    }
+
+    async fn save_version(&self, version: &NoteVersion) -> DomainResult<()> {
+        write(move || insert_version(&self.pool, version)).await
     }
 
     async fn find_versions_by_note_id(&self, note_id: Uuid) -> DomainResult<Vec<NoteVersion>> {
@@ -350,7 +746,7 @@ impl NoteRepository for SqliteNoteRepository {
 
         let rows: Vec<NoteVersionRow> = sqlx::query_as(
             r#"
-            SELECT id, note_id, title, content, created_at
+            SELECT id, note_id, title, content, created_at, created_by
             FROM note_versions
             WHERE note_id = ?
             ORDER BY created_at DESC
@@ -359,7 +755,7 @@ impl NoteRepository for SqliteNoteRepository {
         .bind(&note_id_str)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         let mut versions = Vec::with_capacity(rows.len());
         for row in rows {
@@ -368,4 +764,402 @@ impl NoteRepository for SqliteNoteRepository {
 
         Ok(versions)
     }
+
+    async fn find_latest_version(&self, note_id: Uuid) -> DomainResult<Option<NoteVersion>> {
+        let row: Option<NoteVersionRow> = sqlx::query_as(
+            r#"
+            SELECT id, note_id, title, content, created_at, created_by
+            FROM note_versions
+            WHERE note_id = ?
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(note_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        row.map(NoteVersionRow::try_into_version).transpose()
+    }
+
+    async fn reorder_pins(&self, user_id: Uuid, note_ids: &[Uuid]) -> DomainResult<()> {
+        write(move || async move {
+            let user_id_str = user_id.to_string();
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            for (position, id) in note_ids.iter().enumerate() {
+                sqlx::query("UPDATE notes SET pin_order = ? WHERE id = ? AND user_id = ?")
+                    .bind(position as i32)
+                    .bind(id.to_string())
+                    .bind(&user_id_str)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(map_sqlx_error)?;
+            }
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_stale_link_previews(&self, limit: usize) -> DomainResult<Vec<Note>> {
+        // Notes without "http" have no bookmarks, unless they lost them in an edit
+        let rows: Vec<NoteRowWithTags> = sqlx::query_as(
+            r#"
+            SELECT n.id, n.user_id, n.title, n.content, n.color, n.is_pinned, n.pin_order, n.is_archived, n.is_locked,
+                   n.created_at, n.updated_at, n.deleted_at, n.link_previews,
+                   n.latitude, n.longitude, n.place_name, n.remind_at, n.language,
+                   json_group_array(
+                       CASE WHEN t.id IS NOT NULL
+                       THEN json_object('id', t.id, 'name', t.name, 'user_id', t.user_id,
+                                        'is_pinned', t.is_pinned, 'sort_order', t.sort_order)
+                       ELSE NULL END
+                   ) as tags_json
+            FROM notes n
+            LEFT JOIN note_tags nt ON n.id = nt.note_id
+            LEFT JOIN tags t ON nt.tag_id = t.id
+            WHERE n.deleted_at IS NULL
+              AND (n.content LIKE '%http%' OR n.link_previews != '[]')
+              AND (n.link_previews_at IS NULL
+                   OR julianday(n.updated_at) > julianday(n.link_previews_at))
+            GROUP BY n.id
+            ORDER BY julianday(n.updated_at), n.id
+            LIMIT ?
+            "#,
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(|row| row.try_into_note()).collect()
+    }
+
+    async fn save_link_previews(
+        &self,
+        note_id: Uuid,
+        previews: &[LinkPreview],
+        as_of: DateTime<Utc>,
+    ) -> DomainResult<()> {
+        let previews = serde_json::to_string(previews)
+            .map_err(|e| decode_error(format!("Failed to encode link previews: {}", e)))?;
+        let previews = previews.as_str();
+        let as_of = as_of.to_rfc3339();
+        let as_of = as_of.as_str();
+        write(move || async move {
+            sqlx::query("UPDATE notes SET link_previews = ?, link_previews_at = ? WHERE id = ?")
+                .bind(previews)
+                .bind(as_of)
+                .bind(note_id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::tag_repository::SqliteTagRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, TagRepository, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new("test|user", Email::try_from("test@example.com").unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_query_combines_predicates() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let tag_repo = SqliteTagRepository::new(pool.clone());
+        let repo = SqliteNoteRepository::new(pool);
+
+        let work = Tag::new(TagName::try_from("work").unwrap(), user.id);
+        tag_repo.save(&work).await.unwrap();
+
+        let mut tagged = Note::new(user.id, NoteTitle::try_from("Plan").ok(), "100% done");
+        tagged.tags.push(work.clone());
+        repo.save(&tagged).await.unwrap();
+        tag_repo.add_to_note(work.id, tagged.id).await.unwrap();
+
+        let mut pinned = Note::new(user.id, None, "100% done");
+        pinned.is_pinned = true;
+        repo.save(&pinned).await.unwrap();
+        repo.save(&Note::new(user.id, None, "1000 done"))
+            .await
+            .unwrap();
+
+        // "%" must match literally rather than as a wildcard
+        let query = NoteQuery {
+            filter: Some(NotePredicate::And(vec![
+                NotePredicate::Text("100%".to_string()),
+                NotePredicate::Or(vec![
+                    NotePredicate::Tag("Work".to_string()),
+                    NotePredicate::Not(Box::new(NotePredicate::Pinned(true))),
+                ]),
+            ])),
+            ..Default::default()
+        };
+        let notes = repo.query(user.id, &query).await.unwrap();
+
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].id, tagged.id);
+        assert_eq!(notes[0].tags.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_detected_language_is_stored_and_follows_content() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteNoteRepository::new(pool);
+
+        let mut note = Note::new(
+            user.id,
+            None,
+            "Jutro rano idziemy na spacer do parku, a potem zjemy obiad u babci. Wieczorem czytamy książki i pijemy herbatę z cytryną.",
+        );
+        repo.save(&note).await.unwrap();
+        let found = repo.find_by_id(note.id).await.unwrap().unwrap();
+        assert_eq!(found.language, Language::new("pl").ok());
+
+        note.set_content("ok");
+        repo.save(&note).await.unwrap();
+        let found = repo.find_by_id(note.id).await.unwrap().unwrap();
+        assert_eq!(found.language, None);
+    }
+
+    #[tokio::test]
+    async fn test_count_by_user_skips_trashed_notes() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteNoteRepository::new(pool);
+
+        let mut pinned = Note::new(user.id, None, "Pinned");
+        pinned.is_pinned = true;
+        let mut archived = Note::new(user.id, None, "Archived and pinned");
+        archived.is_pinned = true;
+        archived.is_archived = true;
+        let mut trashed = Note::new(user.id, None, "Trashed");
+        trashed.deleted_at = Some(Utc::now());
+        for note in [
+            &pinned,
+            &archived,
+            &trashed,
+            &Note::new(user.id, None, "Plain"),
+        ] {
+            repo.save(note).await.unwrap();
+        }
+
+        assert_eq!(
+            repo.count_by_user(user.id).await.unwrap(),
+            NoteCounts {
+                total: 3,
+                pinned: 1,
+                archived: 1,
+            }
+        );
+        assert_eq!(
+            repo.count_by_user(Uuid::new_v4()).await.unwrap(),
+            NoteCounts::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_versions_finds_history_of_live_notes() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteNoteRepository::new(pool);
+
+        let note = Note::new(user.id, None, "current text");
+        repo.save(&note).await.unwrap();
+        let version = NoteVersion::new(note.id, None, "draft about zebras".to_string());
+        repo.save_version(&version).await.unwrap();
+
+        let hits = repo
+            .search_versions(user.id, "zebras", false, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, version.id);
+        assert_eq!(hits[0].title, None);
+
+        let mut trashed = note.clone();
+        trashed.move_to_trash();
+        repo.save(&trashed).await.unwrap();
+        let hits = repo
+            .search_versions(user.id, "zebras", false, 10)
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_paginates_in_sort_order() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteNoteRepository::new(pool);
+
+        for title in ["b", "a", "c"] {
+            repo.save(&Note::new(user.id, NoteTitle::try_from(title).ok(), ""))
+                .await
+                .unwrap();
+        }
+
+        let query = NoteQuery {
+            sort: NoteSortOrder::TitleAsc,
+            limit: Some(2),
+            offset: 1,
+            ..Default::default()
+        };
+        let titles: Vec<String> = repo
+            .query(user.id, &query)
+            .await
+            .unwrap()
+            .iter()
+            .map(|n| n.title_str().to_string())
+            .collect();
+
+        assert_eq!(titles, vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_titles_matches_prefix_case_insensitively() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteNoteRepository::new(pool);
+
+        for title in ["Recipes", "reading list", "Groceries"] {
+            repo.save(&Note::new(user.id, NoteTitle::try_from(title).ok(), ""))
+                .await
+                .unwrap();
+        }
+        repo.save(&Note::new(user.id, None, "untitled"))
+            .await
+            .unwrap();
+
+        let titles: Vec<String> = repo
+            .suggest_titles(user.id, "RE", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["reading list", "Recipes"]);
+        assert_eq!(
+            repo.suggest_titles(user.id, "re", 1).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_link_previews_survive_saves_until_content_changes() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteNoteRepository::new(pool);
+
+        let mut bookmarked = Note::new(user.id, None, "https://example.com");
+        repo.save(&bookmarked).await.unwrap();
+        repo.save(&Note::new(user.id, None, "no links"))
+            .await
+            .unwrap();
+
+        let stale = repo.find_stale_link_previews(10).await.unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].id, bookmarked.id);
+
+        let mut preview = LinkPreview::new("https://example.com");
+        preview.title = Some("Example Domain".to_string());
+        repo.save_link_previews(
+            bookmarked.id,
+            std::slice::from_ref(&preview),
+            bookmarked.updated_at,
+        )
+        .await
+        .unwrap();
+        assert!(repo.find_stale_link_previews(10).await.unwrap().is_empty());
+
+        // Regular saves neither clear the previews nor mark them fresh
+        bookmarked.content = "https://example.com\nhttps://example.org".to_string();
+        bookmarked.updated_at = Utc::now() + chrono::Duration::seconds(1);
+        repo.save(&bookmarked).await.unwrap();
+
+        let stored = repo.find_by_id(bookmarked.id).await.unwrap().unwrap();
+        assert_eq!(stored.link_previews, vec![preview]);
+        assert_eq!(repo.find_stale_link_previews(10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_find_by_user_within_bounding_box_uses_location_index() {
+        use notes_domain::geo::BoundingBox;
+
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteNoteRepository::new(pool);
+        let point = |lat: f64, lon: f64| {
+            Some(GeoPoint::new(
+                Latitude::new(lat).unwrap(),
+                Longitude::new(lon).unwrap(),
+            ))
+        };
+
+        let mut lisbon = Note::new(user.id, None, "Lisbon");
+        lisbon.location = point(38.7223, -9.1393);
+        lisbon.place_name = PlaceName::try_from("Lisbon").ok();
+        let mut fiji = Note::new(user.id, None, "Fiji");
+        fiji.location = point(-17.7, 179.9);
+        for note in [&lisbon, &fiji, &Note::new(user.id, None, "nowhere")] {
+            repo.save(note).await.unwrap();
+        }
+
+        let within = |center: &Note| {
+            NoteFilter::new().within(BoundingBox::around(
+                center.location.as_ref().unwrap(),
+                100.0,
+            ))
+        };
+        let found = repo.find_by_user(user.id, within(&lisbon)).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].location, lisbon.location);
+        assert_eq!(found[0].place_name, lisbon.place_name);
+
+        let mut across = fiji.clone();
+        across.location = point(-17.7, -179.8);
+        let found = repo.find_by_user(user.id, within(&across)).await.unwrap();
+        assert_eq!(
+            found.iter().map(|n| n.id).collect::<Vec<_>>(),
+            vec![fiji.id]
+        );
+
+        // Moving a note updates the index
+        lisbon.location = point(40.4168, -3.7038);
+        repo.save(&lisbon).await.unwrap();
+        let mut old = lisbon.clone();
+        old.location = point(38.7223, -9.1393);
+        assert!(
+            repo.find_by_user(user.id, within(&old))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -0,0 +1,171 @@
+//! Argon2id password hasher
+//!
+//! Produces PHC strings (`$argon2id$v=19$m=...,t=...,p=...$salt$hash`), so
+//! hashes created with earlier parameters keep verifying and can be
+//! detected for rehashing.
+
+use argon2::{
+    Algorithm, Argon2, Params, Version,
+    password_hash::{
+        self, PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString, rand_core::OsRng,
+    },
+};
+
+use async_trait::async_trait;
+use notes_domain::{DomainError, DomainResult, PasswordHasher};
+
+use super::blocking;
+
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Config {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Config {
+    /// OWASP recommended minimum, also the `argon2` crate default
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Argon2PasswordHasher {
+    params: Params,
+}
+
+impl Argon2PasswordHasher {
+    pub fn new(config: Argon2Config) -> DomainResult<Self> {
+        let params = Params::new(
+            config.memory_kib,
+            config.iterations,
+            config.parallelism,
+            None,
+        )
+        .map_err(|e| {
+            DomainError::InfrastructureError(format!("Invalid Argon2 parameters: {}", e))
+        })?;
+
+        Ok(Self { params })
+    }
+
+    fn argon2(&self) -> Argon2<'_> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+
+    fn hash_now(&self, password: &str) -> DomainResult<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        self.argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Password hashing failed: {}", e))
+            })
+    }
+
+    fn verify_now(&self, password: &str, hash: &str) -> DomainResult<bool> {
+        let parsed = match PasswordHash::new(hash) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                // Fails the login like a wrong password would
+                tracing::warn!("Stored password hash is not a PHC string: {}", e);
+                return Ok(false);
+            }
+        };
+
+        // Parameters are read from the hash itself, not from `self.params`
+        match self.argon2().verify_password(password.as_bytes(), &parsed) {
+            Ok(()) => Ok(true),
+            Err(password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(DomainError::InfrastructureError(format!(
+                "Password verification failed: {}",
+                e
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl PasswordHasher for Argon2PasswordHasher {
+    async fn hash(&self, password: &str) -> DomainResult<String> {
+        let hasher = self.clone();
+        let password = password.to_string();
+        blocking(move || hasher.hash_now(&password)).await
+    }
+
+    async fn verify(&self, password: &str, hash: &str) -> DomainResult<bool> {
+        let hasher = self.clone();
+        let (password, hash) = (password.to_string(), hash.to_string());
+        blocking(move || hasher.verify_now(&password, &hash)).await
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return true;
+        };
+
+        if parsed.algorithm != Algorithm::Argon2id.ident() || parsed.version != Some(0x13) {
+            return true;
+        }
+
+        match Params::try_from(&parsed) {
+            Ok(params) => {
+                params.m_cost() != self.params.m_cost()
+                    || params.t_cost() != self.params.t_cost()
+                    || params.p_cost() != self.params.p_cost()
+            }
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap parameters to keep tests fast
+    fn config(memory_kib: u32) -> Argon2Config {
+        Argon2Config {
+            memory_kib,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_and_verify() {
+        let hasher = Argon2PasswordHasher::new(config(1024)).unwrap();
+        let hash = hasher.hash("secret").await.unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(hasher.verify("secret", &hash).await.unwrap());
+        assert!(!hasher.verify("wrong", &hash).await.unwrap());
+        assert!(!hasher.needs_rehash(&hash));
+    }
+
+    #[tokio::test]
+    async fn test_changed_parameters_require_rehash() {
+        let old = Argon2PasswordHasher::new(config(1024)).unwrap();
+        let new = Argon2PasswordHasher::new(config(2048)).unwrap();
+        let hash = old.hash("secret").await.unwrap();
+
+        // Old hashes keep verifying but are flagged for an upgrade
+        assert!(new.verify("secret", &hash).await.unwrap());
+        assert!(new.needs_rehash(&hash));
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_hash_does_not_verify() {
+        let hasher = Argon2PasswordHasher::new(config(1024)).unwrap();
+        assert!(!hasher.verify("secret", "not-a-hash").await.unwrap());
+    }
+}
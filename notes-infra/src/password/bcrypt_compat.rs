@@ -0,0 +1,77 @@
+//! bcrypt password hasher
+//!
+//! Verifies `$2a$`/`$2b$`/`$2y$` hashes of users imported from other
+//! applications. New hashes should use Argon2id; see [`super::compat`].
+
+use async_trait::async_trait;
+use notes_domain::{DomainError, DomainResult, PasswordHasher};
+
+use super::blocking;
+
+pub struct BcryptPasswordHasher {
+    cost: u32,
+}
+
+impl BcryptPasswordHasher {
+    pub fn new(cost: u32) -> Self {
+        Self { cost }
+    }
+
+    /// Whether a stored hash is in bcrypt's modular crypt format
+    pub fn is_bcrypt_hash(hash: &str) -> bool {
+        ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|prefix| hash.starts_with(prefix))
+    }
+}
+
+impl Default for BcryptPasswordHasher {
+    fn default() -> Self {
+        Self::new(bcrypt::DEFAULT_COST)
+    }
+}
+
+#[async_trait]
+impl PasswordHasher for BcryptPasswordHasher {
+    async fn hash(&self, password: &str) -> DomainResult<String> {
+        let (password, cost) = (password.to_string(), self.cost);
+        blocking(move || {
+            bcrypt::hash(password, cost).map_err(|e| {
+                DomainError::InfrastructureError(format!("Password hashing failed: {}", e))
+            })
+        })
+        .await
+    }
+
+    async fn verify(&self, password: &str, hash: &str) -> DomainResult<bool> {
+        let (password, hash) = (password.to_string(), hash.to_string());
+        blocking(move || {
+            bcrypt::verify(password, &hash).map_err(|e| {
+                DomainError::InfrastructureError(format!("Password verification failed: {}", e))
+            })
+        })
+        .await
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        hash.parse::<bcrypt::HashParts>()
+            .map(|parts| parts.get_cost() < self.cost)
+            .unwrap_or(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_verifies_bcrypt_hashes() {
+        let hasher = BcryptPasswordHasher::new(4);
+        let hash = hasher.hash("secret").await.unwrap();
+
+        assert!(BcryptPasswordHasher::is_bcrypt_hash(&hash));
+        assert!(hasher.verify("secret", &hash).await.unwrap());
+        assert!(!hasher.verify("wrong", &hash).await.unwrap());
+        assert!(BcryptPasswordHasher::new(5).needs_rehash(&hash));
+    }
+}
@@ -0,0 +1,98 @@
+//! Password hasher with legacy-format support
+//!
+//! Always hashes with Argon2id, but verifies hashes in older formats
+//! (bcrypt from imported users) and flags them for rehashing, so accounts
+//! migrate to the current scheme on their next successful login.
+
+use async_trait::async_trait;
+use notes_domain::{DomainResult, PasswordHasher};
+
+use super::argon2id::Argon2PasswordHasher;
+#[cfg(feature = "password-bcrypt")]
+use super::bcrypt_compat::BcryptPasswordHasher;
+
+pub struct CompatPasswordHasher {
+    primary: Argon2PasswordHasher,
+    #[cfg(feature = "password-bcrypt")]
+    bcrypt: Option<BcryptPasswordHasher>,
+}
+
+impl CompatPasswordHasher {
+    pub fn new(primary: Argon2PasswordHasher) -> Self {
+        Self {
+            primary,
+            #[cfg(feature = "password-bcrypt")]
+            bcrypt: None,
+        }
+    }
+
+    /// Also accept bcrypt hashes (e.g. from imported users)
+    #[cfg(feature = "password-bcrypt")]
+    pub fn with_bcrypt(mut self, bcrypt: BcryptPasswordHasher) -> Self {
+        self.bcrypt = Some(bcrypt);
+        self
+    }
+
+    fn is_legacy(hash: &str) -> bool {
+        hash.starts_with("$2")
+    }
+}
+
+#[async_trait]
+impl PasswordHasher for CompatPasswordHasher {
+    async fn hash(&self, password: &str) -> DomainResult<String> {
+        self.primary.hash(password).await
+    }
+
+    async fn verify(&self, password: &str, hash: &str) -> DomainResult<bool> {
+        if !Self::is_legacy(hash) {
+            return self.primary.verify(password, hash).await;
+        }
+
+        #[cfg(feature = "password-bcrypt")]
+        if let Some(ref bcrypt) = self.bcrypt {
+            return bcrypt.verify(password, hash).await;
+        }
+
+        tracing::warn!("Stored password hash is bcrypt, which is not enabled on this instance");
+        Ok(false)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        Self::is_legacy(hash) || self.primary.needs_rehash(hash)
+    }
+}
+
+#[cfg(all(test, feature = "password-bcrypt"))]
+mod tests {
+    use super::*;
+    use crate::password::argon2id::Argon2Config;
+
+    fn hasher() -> CompatPasswordHasher {
+        let primary = Argon2PasswordHasher::new(Argon2Config {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        })
+        .unwrap();
+        CompatPasswordHasher::new(primary).with_bcrypt(BcryptPasswordHasher::new(4))
+    }
+
+    #[tokio::test]
+    async fn test_bcrypt_hashes_verify_and_need_rehash() {
+        let hasher = hasher();
+        let legacy = BcryptPasswordHasher::new(4).hash("secret").await.unwrap();
+
+        assert!(hasher.verify("secret", &legacy).await.unwrap());
+        assert!(hasher.needs_rehash(&legacy));
+    }
+
+    #[tokio::test]
+    async fn test_new_hashes_use_argon2id() {
+        let hasher = hasher();
+        let hash = hasher.hash("secret").await.unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(!hasher.needs_rehash(&hash));
+    }
+}
@@ -0,0 +1,19 @@
+//! Password hasher adapters.
+//!
+//! This module provides implementations of the `PasswordHasher` port.
+
+pub mod argon2id;
+#[cfg(feature = "password-bcrypt")]
+pub mod bcrypt_compat;
+pub mod compat;
+
+use notes_domain::{DomainError, DomainResult};
+
+/// Run hashing work off the async workers, since it is slow on purpose
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> DomainResult<T> + Send + 'static,
+) -> DomainResult<T> {
+    tokio::task::spawn_blocking(work).await.map_err(|e| {
+        DomainError::InfrastructureError(format!("Password hashing task failed: {}", e))
+    })?
+}
@@ -0,0 +1,131 @@
+//! Headless Chromium PDF adapter
+//!
+//! Renders notes to a styled HTML document and prints it with
+//! `chromium --headless --print-to-pdf`.
+//!
+//! Chromium runs sandboxed, and every request the document makes goes to a
+//! proxy that does not exist, so a note cannot make the renderer reach
+//! internal hosts. The only host it may load from is the KaTeX one when
+//! math is typeset; remote images are left out of PDFs.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use notes_domain::{DomainError, DomainResult, Note, PdfRenderer};
+
+use crate::render::html::{RenderOptions, render_notes_document};
+
+/// Where Chromium's requests are sent; nothing listens there
+const DEAD_PROXY: &str = "127.0.0.1:9";
+
+pub struct ChromiumPdfRenderer {
+    binary: String,
+    render: RenderOptions,
+}
+
+impl ChromiumPdfRenderer {
+    pub fn new(binary: impl Into<String>, render: RenderOptions) -> Self {
+        Self {
+            binary: binary.into(),
+            render,
+        }
+    }
+
+    /// Proxy bypass rules: loopback addresses go through the proxy too, and
+    /// only the KaTeX host skips it
+    fn proxy_bypass_list(&self) -> String {
+        let mut rules = String::from("<-loopback>");
+        if self.render.math
+            && let Some(host) = url_authority(&self.render.katex_url)
+        {
+            rules.push(';');
+            rules.push_str(host);
+        }
+        rules
+    }
+
+    async fn print_to_pdf(&self, input: &Path, output: &Path) -> std::io::Result<Vec<u8>> {
+        let result = Command::new(&self.binary)
+            .arg("--headless")
+            .arg("--disable-gpu")
+            .arg("--disable-extensions")
+            .arg(format!("--proxy-server={}", DEAD_PROXY))
+            .arg(format!("--proxy-bypass-list={}", self.proxy_bypass_list()))
+            .arg("--no-pdf-header-footer")
+            .arg(format!("--print-to-pdf={}", output.display()))
+            .arg(format!("file://{}", input.display()))
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        if !result.status.success() {
+            return Err(std::io::Error::other(
+                String::from_utf8_lossy(&result.stderr).to_string(),
+            ));
+        }
+
+        tokio::fs::read(output).await
+    }
+}
+
+/// The `host[:port]` of an `http(s)` URL
+fn url_authority(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !matches!(scheme, "http" | "https") {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    // Credentials are not part of the host
+    let host = authority.rsplit('@').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+#[async_trait]
+impl PdfRenderer for ChromiumPdfRenderer {
+    async fn render_pdf(&self, title: &str, notes: &[Note], tz: Tz) -> DomainResult<Vec<u8>> {
+        let html = render_notes_document(title, notes, tz, &self.render);
+
+        let work_dir: PathBuf =
+            std::env::temp_dir().join(format!("k-notes-pdf-{}", Uuid::new_v4()));
+        let input = work_dir.join("document.html");
+        let output = work_dir.join("document.pdf");
+
+        let result = async {
+            tokio::fs::create_dir_all(&work_dir).await?;
+            tokio::fs::write(&input, html).await?;
+            self.print_to_pdf(&input, &output).await
+        }
+        .await;
+
+        // Always clean up the scratch directory, even if printing failed
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+
+        result.map_err(|e| DomainError::InfrastructureError(format!("PDF rendering failed: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_katex_host_bypasses_the_proxy() {
+        let plain = ChromiumPdfRenderer::new("chromium", RenderOptions::default());
+        assert_eq!(plain.proxy_bypass_list(), "<-loopback>");
+
+        let math = ChromiumPdfRenderer::new(
+            "chromium",
+            RenderOptions {
+                math: true,
+                katex_url: "https://user@katex.example:8443/dist".to_string(),
+                ..RenderOptions::default()
+            },
+        );
+        assert_eq!(math.proxy_bypass_list(), "<-loopback>;katex.example:8443");
+        assert_eq!(url_authority("file:///etc/katex"), None);
+    }
+}
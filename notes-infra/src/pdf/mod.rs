@@ -0,0 +1,49 @@
+//! PDF renderer adapters.
+//!
+//! This module provides implementations of the `PdfRenderer` port, and the
+//! PDF export format built on them.
+
+pub mod chromium;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+
+use notes_domain::{DomainResult, Note, PdfRenderer};
+
+use crate::formats::Exporter;
+
+/// Exports notes as one PDF through the configured renderer
+pub struct PdfExporter {
+    renderer: Arc<dyn PdfRenderer>,
+}
+
+impl PdfExporter {
+    pub fn new(renderer: Arc<dyn PdfRenderer>) -> Self {
+        Self { renderer }
+    }
+}
+
+#[async_trait]
+impl Exporter for PdfExporter {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/pdf"
+    }
+
+    fn extension(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn renders_content(&self) -> bool {
+        true
+    }
+
+    async fn export(&self, title: &str, notes: Vec<Note>, tz: Tz) -> DomainResult<Vec<u8>> {
+        self.renderer.render_pdf(title, &notes, tz).await
+    }
+}
@@ -0,0 +1,191 @@
+//! Adapter for LanguageTool servers
+//!
+//! Works with a self-hosted LanguageTool server as well as the public
+//! `https://api.languagetool.org` API, through their `/v2/check` endpoint.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use notes_domain::language::Language;
+use notes_domain::writing::{ProofreadSuggestion, SuggestionKind};
+use notes_domain::{DomainError, DomainResult, Proofreader};
+
+/// Replacements kept per suggestion; common words get dozens
+const MAX_REPLACEMENTS: usize = 5;
+
+/// Variants LanguageTool needs for spelling checks of these languages
+const LANGUAGE_VARIANTS: &[(&str, &str)] = &[
+    ("de", "de-DE"),
+    ("en", "en-US"),
+    ("nl", "nl-NL"),
+    ("pt", "pt-PT"),
+];
+
+#[derive(Debug, Deserialize)]
+struct CheckResponse {
+    matches: Vec<CheckMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckMatch {
+    message: String,
+    /// UTF-16 code units, as counted by Java
+    offset: usize,
+    length: usize,
+    #[serde(default)]
+    replacements: Vec<Replacement>,
+    rule: Rule,
+}
+
+#[derive(Debug, Deserialize)]
+struct Replacement {
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    #[serde(default)]
+    issue_type: String,
+}
+
+/// Checks text with a LanguageTool server
+pub struct LanguageToolProofreader {
+    client: reqwest::Client,
+    check_url: String,
+}
+
+impl LanguageToolProofreader {
+    /// `base_url` is the server root, such as `http://localhost:8081`
+    pub fn new(base_url: &str, timeout: Duration) -> DomainResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+            })?;
+
+        Ok(Self {
+            client,
+            check_url: format!("{}/v2/check", base_url.trim_end_matches('/')),
+        })
+    }
+}
+
+/// The language parameter LanguageTool expects for `language`
+fn language_param(language: Option<&Language>) -> &'static str {
+    let Some(language) = language else {
+        return "auto";
+    };
+    LANGUAGE_VARIANTS
+        .iter()
+        .find(|(code, _)| *code == language.code())
+        .map_or(language.code(), |(_, variant)| *variant)
+}
+
+/// Character offsets in `text` of each UTF-16 offset, so suggestions point
+/// at the same text in clients counting characters
+fn char_offsets(text: &str) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    for (index, c) in text.chars().enumerate() {
+        offsets.extend(std::iter::repeat_n(index, c.len_utf16()));
+    }
+    offsets.push(text.chars().count());
+    offsets
+}
+
+fn suggestions(text: &str, response: CheckResponse) -> Vec<ProofreadSuggestion> {
+    let offsets = char_offsets(text);
+    let at = |utf16: usize| offsets.get(utf16).copied();
+
+    response
+        .matches
+        .into_iter()
+        .filter_map(|m| {
+            let offset = at(m.offset)?;
+            let end = at(m.offset + m.length)?;
+            let kind = match m.rule.issue_type.as_str() {
+                "misspelling" => SuggestionKind::Spelling,
+                "grammar" => SuggestionKind::Grammar,
+                "style" | "typographical" | "whitespace" => SuggestionKind::Style,
+                _ => SuggestionKind::Other,
+            };
+            Some(ProofreadSuggestion {
+                offset,
+                length: end - offset,
+                message: m.message,
+                kind,
+                replacements: m
+                    .replacements
+                    .into_iter()
+                    .take(MAX_REPLACEMENTS)
+                    .map(|r| r.value)
+                    .collect(),
+                rule: m.rule.id,
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Proofreader for LanguageToolProofreader {
+    async fn check(
+        &self,
+        text: &str,
+        language: Option<&Language>,
+    ) -> DomainResult<Vec<ProofreadSuggestion>> {
+        let response: CheckResponse = self
+            .client
+            .post(&self.check_url)
+            .form(&[("text", text), ("language", language_param(language))])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| DomainError::InfrastructureError(format!("Proofreading failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Invalid proofreader response: {}", e))
+            })?;
+
+        Ok(suggestions(text, response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offsets_are_converted_to_characters() {
+        // The emoji takes two UTF-16 code units
+        let text = "😀 Feed teh cat";
+        let response: CheckResponse = serde_json::from_str(
+            r#"{"matches":[{"message":"Possible spelling mistake found.","offset":8,"length":3,
+                "replacements":[{"value":"the"},{"value":"tech"}],
+                "rule":{"id":"MORFOLOGIK_RULE_EN_US","issueType":"misspelling"}}]}"#,
+        )
+        .unwrap();
+
+        let found = suggestions(text, response);
+        assert_eq!(found.len(), 1);
+        let flagged: String = text
+            .chars()
+            .skip(found[0].offset)
+            .take(found[0].length)
+            .collect();
+        assert_eq!(flagged, "teh");
+        assert_eq!(found[0].kind, SuggestionKind::Spelling);
+        assert_eq!(found[0].replacements, vec!["the", "tech"]);
+    }
+
+    #[test]
+    fn test_language_param_adds_variants() {
+        assert_eq!(language_param(None), "auto");
+        assert_eq!(language_param(Language::new("en").ok().as_ref()), "en-US");
+        assert_eq!(language_param(Language::new("pl").ok().as_ref()), "pl");
+    }
+}
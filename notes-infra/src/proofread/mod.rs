@@ -0,0 +1,5 @@
+//! Proofreading adapters
+//!
+//! This module provides implementations of the `Proofreader` port.
+
+pub mod languagetool;
@@ -0,0 +1,124 @@
+//! CSV export
+//!
+//! One row per note with its title, content, tags, flags and timestamps,
+//! for spreadsheets. The CSV import reads these columns by default.
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+
+use notes_domain::{DomainResult, Note};
+
+use crate::formats::Exporter;
+
+/// Columns written, in order
+pub const CSV_HEADERS: [&str; 7] = [
+    "title",
+    "content",
+    "tags",
+    "pinned",
+    "archived",
+    "created_at",
+    "updated_at",
+];
+
+/// Quote a field when it holds a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) || value.trim() != value {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn push_row<'a>(csv: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    let row: Vec<String> = fields.into_iter().map(csv_field).collect();
+    csv.push_str(&row.join(","));
+    csv.push_str("\r\n");
+}
+
+/// A CSV document of the notes; tags are comma-separated in one column
+pub fn build_csv(notes: &[Note]) -> String {
+    let mut csv = String::new();
+    push_row(&mut csv, CSV_HEADERS);
+
+    for note in notes {
+        let tags: Vec<&str> = note.tags.iter().map(|tag| tag.name_str()).collect();
+        let tags = tags.join(",");
+        let created_at = note.created_at.to_rfc3339();
+        let updated_at = note.updated_at.to_rfc3339();
+        push_row(
+            &mut csv,
+            [
+                note.title_str(),
+                &note.content,
+                &tags,
+                if note.is_pinned { "true" } else { "false" },
+                if note.is_archived { "true" } else { "false" },
+                &created_at,
+                &updated_at,
+            ],
+        );
+    }
+    csv
+}
+
+/// Writes one CSV row per note
+pub struct CsvExporter;
+
+#[async_trait]
+impl Exporter for CsvExporter {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "text/csv; charset=utf-8"
+    }
+
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    async fn export(&self, _title: &str, notes: Vec<Note>, _tz: Tz) -> DomainResult<Vec<u8>> {
+        Ok(build_csv(&notes).into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notes_domain::{NoteTitle, Tag, TagName};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_build_csv_quotes_fields_that_need_it() {
+        let mut note = Note::new(
+            Uuid::nil(),
+            NoteTitle::try_from("Plan, \"v2\"").ok(),
+            "- one\n- two",
+        );
+        note.tags
+            .push(Tag::new(TagName::try_from("work").unwrap(), Uuid::nil()));
+        note.tags
+            .push(Tag::new(TagName::try_from("q3").unwrap(), Uuid::nil()));
+        note.is_pinned = true;
+
+        let csv = build_csv(&[note.clone()]);
+        let mut lines = csv.split("\r\n");
+        assert_eq!(
+            lines.next(),
+            Some("title,content,tags,pinned,archived,created_at,updated_at")
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                format!(
+                    "\"Plan, \"\"v2\"\"\",\"- one\n- two\",\"work,q3\",true,false,{},{}",
+                    note.created_at.to_rfc3339(),
+                    note.updated_at.to_rfc3339()
+                )
+                .as_str()
+            )
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Syntax highlighting of code blocks
+//!
+//! Code blocks are highlighted with syntect's bundled grammars and themes,
+//! with the theme's colours inlined on every span, so exported pages and
+//! PDFs look the same without a stylesheet. A block's language comes from
+//! its fence (` ```rust `); blocks naming none are recognised by their
+//! first line, such as a shebang or `<?xml`, and the rest stay plain.
+
+use std::sync::LazyLock;
+
+use pulldown_cmark::{CodeBlockKind, Event, Tag, TagEnd};
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+use super::html::DEFAULT_CODE_THEME;
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Names of the themes code can be highlighted with
+pub fn theme_names() -> Vec<&'static str> {
+    THEMES.themes.keys().map(String::as_str).collect()
+}
+
+/// The theme called `name`, or the default theme for names it does not know
+fn theme(name: &str) -> Option<&'static Theme> {
+    THEMES
+        .themes
+        .get(name)
+        .or_else(|| THEMES.themes.get(DEFAULT_CODE_THEME))
+}
+
+/// The grammar for a code block with fence info `info`
+fn syntax(info: &str, code: &str) -> Option<&'static SyntaxReference> {
+    // Fences like ` ```rust,ignore ` or ` ```python title="x" ` carry more
+    // than the language
+    let language = info.split([',', ' ', '\t']).next().unwrap_or("").trim();
+    if !language.is_empty() {
+        return SYNTAXES.find_syntax_by_token(language);
+    }
+    SYNTAXES.find_syntax_by_first_line(code.lines().next()?)
+}
+
+/// `events` with the code blocks syntect has a grammar for replaced by
+/// highlighted HTML in `theme_name`
+pub fn highlight_code<'a>(
+    events: impl Iterator<Item = Event<'a>>,
+    theme_name: &str,
+) -> Vec<Event<'a>> {
+    let Some(theme) = theme(theme_name) else {
+        return events.collect();
+    };
+
+    let mut output = Vec::new();
+    // The opening event, fence info and code of the block being read
+    let mut block: Option<(Event<'a>, String, String)> = None;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let info = match &kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                block = Some((Event::Start(Tag::CodeBlock(kind)), info, String::new()));
+            }
+            Event::Text(text) => match &mut block {
+                Some((_, _, code)) => code.push_str(&text),
+                None => output.push(Event::Text(text)),
+            },
+            Event::End(TagEnd::CodeBlock) => {
+                let Some((start, info, code)) = block.take() else {
+                    output.push(Event::End(TagEnd::CodeBlock));
+                    continue;
+                };
+                let highlighted = syntax(&info, &code).and_then(|syntax| {
+                    highlighted_html_for_string(&code, &SYNTAXES, syntax, theme).ok()
+                });
+                match highlighted {
+                    Some(html) => output.push(Event::Html(html.into())),
+                    None => output.extend([
+                        start,
+                        Event::Text(code.into()),
+                        Event::End(TagEnd::CodeBlock),
+                    ]),
+                }
+            }
+            event => output.push(event),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Parser, html};
+
+    fn render(markdown: &str, theme: &str) -> String {
+        let mut output = String::new();
+        html::push_html(
+            &mut output,
+            highlight_code(Parser::new(markdown), theme).into_iter(),
+        );
+        output
+    }
+
+    #[test]
+    fn test_fenced_code_is_highlighted_with_inline_styles() {
+        let html = render("```rust\nfn main() {}\n```\n", DEFAULT_CODE_THEME);
+
+        assert!(html.starts_with("<pre style=\""));
+        assert!(html.contains("<span style=\"color:"));
+        assert!(!html.contains("<code"));
+    }
+
+    #[test]
+    fn test_language_is_detected_from_the_first_line() {
+        let html = render("```\n#!/bin/bash\necho hi\n```\n", DEFAULT_CODE_THEME);
+        assert!(html.contains("<span style=\"color:"));
+
+        let plain = render("```\njust words\n```\n", DEFAULT_CODE_THEME);
+        assert_eq!(plain, "<pre><code>just words\n</code></pre>\n");
+    }
+
+    #[test]
+    fn test_unknown_languages_stay_plain_and_escaped() {
+        let html = render("```nope\n<b>bold</b>\n```\n", DEFAULT_CODE_THEME);
+        assert_eq!(
+            html,
+            "<pre><code class=\"language-nope\">&lt;b&gt;bold&lt;/b&gt;\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_themes_fall_back_to_the_default() {
+        let markdown = "```python\nprint(1)\n```\n";
+        assert_eq!(
+            render(markdown, "No Such Theme"),
+            render(markdown, DEFAULT_CODE_THEME)
+        );
+        assert!(theme_names().contains(&DEFAULT_CODE_THEME));
+    }
+}
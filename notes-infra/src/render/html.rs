@@ -0,0 +1,425 @@
+//! Markdown to HTML rendering
+//!
+//! Rendered notes are opened by other readers (print view, static site) and
+//! by the PDF renderer, so note content only ever becomes Markdown's own
+//! markup: raw HTML typed in a note is shown as text, and links and images
+//! keep only URLs that cannot run scripts or read local files.
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, html};
+
+use notes_domain::Note;
+
+/// Base stylesheet embedded into standalone documents
+const DOCUMENT_CSS: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, Helvetica, Arial, sans-serif; line-height: 1.6; color: #1f2328; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; }
+h1, h2, h3 { line-height: 1.25; }
+pre { background: #f6f8fa; padding: 0.75rem; border-radius: 6px; overflow-x: auto; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.9em; }
+blockquote { border-left: 4px solid #d0d7de; margin: 0; padding-left: 1rem; color: #57606a; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d0d7de; padding: 0.25rem 0.5rem; }
+img { max-width: 100%; height: auto; }
+img[src^="data:image/svg+xml"] { display: block; margin: 1rem auto; }
+.note { page-break-after: always; }
+.note:last-child { page-break-after: auto; }
+.note-meta { color: #57606a; font-size: 0.85em; }
+.tag { display: inline-block; background: #eaeef2; border-radius: 999px; padding: 0 0.5rem; margin-right: 0.25rem; }
+"#;
+
+/// Extra styles for printable note views
+const PRINT_CSS: &str = r#"
+@page { margin: 2cm; }
+@media print { body { margin: 0; max-width: none; } }
+.print-header { padding-bottom: 0.5rem; margin-bottom: 1.5rem; border-bottom: 2px solid #d0d7de; }
+.print-header img { max-height: 3rem; }
+"#;
+
+/// KaTeX release math is typeset with, unless the instance serves its own
+pub const DEFAULT_KATEX_URL: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist";
+
+/// Theme code is highlighted with, unless the instance picks another
+pub const DEFAULT_CODE_THEME: &str = "InspiredGitHub";
+
+/// Typesets the math spans pulldown-cmark writes once KaTeX has loaded
+const KATEX_SCRIPT: &str = r#"document.addEventListener("DOMContentLoaded", function () {
+  document.querySelectorAll(".math").forEach(function (el) {
+    katex.render(el.textContent, el, { displayMode: el.classList.contains("math-display"), throwOnError: false });
+  });
+});"#;
+
+/// URL schemes links may point to; URLs without a scheme are relative
+const LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// URL schemes images may be loaded from, including drawn diagrams
+const IMAGE_SCHEMES: &[&str] = &["http", "https", "data"];
+
+/// How an instance renders Markdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Render `$…$` and `$$…$$` as math, typeset with KaTeX by the browser;
+    /// otherwise dollar signs are plain text
+    pub math: bool,
+    /// Where `katex.min.js` and `katex.min.css` are loaded from
+    pub katex_url: String,
+    /// syntect theme code blocks are highlighted with, or `None` to leave
+    /// them plain; without the `highlighting` feature code is always plain
+    pub code_theme: Option<String>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            math: false,
+            katex_url: DEFAULT_KATEX_URL.to_string(),
+            code_theme: Some(DEFAULT_CODE_THEME.to_string()),
+        }
+    }
+}
+
+/// Branding of printable note views
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrintTheme {
+    pub logo_url: Option<String>,
+    /// CSS colour, validated by the instance settings
+    pub accent_color: Option<String>,
+}
+
+/// Render Markdown to an HTML fragment
+pub fn markdown_to_html(markdown: &str, render: &RenderOptions) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    if render.math {
+        options.insert(Options::ENABLE_MATH);
+    }
+
+    let parser = safe_events(Parser::new_ext(markdown, options));
+    let mut output = String::with_capacity(markdown.len() * 3 / 2);
+    #[cfg(feature = "highlighting")]
+    if let Some(theme) = &render.code_theme {
+        let events = super::highlight::highlight_code(parser, theme);
+        html::push_html(&mut output, events.into_iter());
+        return output;
+    }
+    html::push_html(&mut output, parser);
+    output
+}
+
+/// `events` with raw HTML turned into text and the URLs of links and
+/// images outside [`LINK_SCHEMES`] and [`IMAGE_SCHEMES`] emptied
+fn safe_events<'a>(events: impl Iterator<Item = Event<'a>>) -> impl Iterator<Item = Event<'a>> {
+    events.map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url, LINK_SCHEMES),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url, IMAGE_SCHEMES),
+            title,
+            id,
+        }),
+        event => event,
+    })
+}
+
+/// `url`, or nothing when it has a scheme outside `schemes`
+fn safe_url<'a>(url: CowStr<'a>, schemes: &[&str]) -> CowStr<'a> {
+    // Browsers skip leading spaces and controls and drop tabs and newlines
+    // anywhere, so `java\tscript:` is still a script
+    let cleaned: String = url
+        .trim_start_matches(|c: char| c <= ' ')
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    let scheme = cleaned
+        .find([':', '/', '?', '#'])
+        .filter(|&end| cleaned[end..].starts_with(':'))
+        .map(|end| &cleaned[..end]);
+
+    match scheme {
+        Some(scheme) if !schemes.iter().any(|s| scheme.eq_ignore_ascii_case(s)) => {
+            CowStr::Borrowed("")
+        }
+        _ => url,
+    }
+}
+
+/// Escape text for safe inclusion in HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format a timestamp for readers in `tz`, e.g. `2026-03-14 19:30 CET`
+pub fn format_timestamp(at: DateTime<Utc>, tz: Tz) -> String {
+    at.with_timezone(&tz)
+        .format("%Y-%m-%d %H:%M %Z")
+        .to_string()
+}
+
+/// Render a single note as an `<article>` fragment, with times shown in `tz`
+pub fn render_note_article(note: &Note, tz: Tz, render: &RenderOptions) -> String {
+    let mut article = String::from("<article class=\"note\">\n");
+
+    if !note.title_str().is_empty() {
+        article.push_str(&format!("<h1>{}</h1>\n", escape_html(note.title_str())));
+    }
+
+    article.push_str(&format!(
+        "<p class=\"note-meta\">Updated {}</p>\n",
+        format_timestamp(note.updated_at, tz)
+    ));
+
+    if !note.tags.is_empty() {
+        article.push_str("<p class=\"note-tags\">");
+        for tag in &note.tags {
+            article.push_str(&format!(
+                "<span class=\"tag\">#{}</span>",
+                escape_html(tag.name_str())
+            ));
+        }
+        article.push_str("</p>\n");
+    }
+
+    article.push_str(&markdown_to_html(&note.content, render));
+    article.push_str("</article>\n");
+    article
+}
+
+/// Wrap body HTML into a standalone, styled document
+pub fn render_document(title: &str, body: &str, render: &RenderOptions) -> String {
+    render_styled_document(title, "", body, render)
+}
+
+/// KaTeX for documents with math in them
+fn math_head(body: &str, render: &RenderOptions) -> String {
+    if !render.math || !body.contains("<span class=\"math math-") {
+        return String::new();
+    }
+    let url = escape_html(render.katex_url.trim_end_matches('/'));
+    format!(
+        "<link rel=\"stylesheet\" href=\"{0}/katex.min.css\">\n<script src=\"{0}/katex.min.js\"></script>\n<script>{1}</script>\n",
+        url, KATEX_SCRIPT
+    )
+}
+
+fn render_styled_document(
+    title: &str,
+    extra_css: &str,
+    body: &str,
+    render: &RenderOptions,
+) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}{}</style>\n{}</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        DOCUMENT_CSS,
+        extra_css,
+        math_head(body, render),
+        body
+    )
+}
+
+/// Render a note as a standalone page meant for printing
+pub fn render_print_view(
+    note: &Note,
+    theme: &PrintTheme,
+    tz: Tz,
+    render: &RenderOptions,
+) -> String {
+    let mut css = String::from(PRINT_CSS);
+    if let Some(color) = &theme.accent_color {
+        css.push_str(&format!(
+            "h1, h2, h3 {{ color: {0}; }}\n.print-header {{ border-bottom-color: {0}; }}\n",
+            color
+        ));
+    }
+
+    let mut body = String::new();
+    if let Some(logo_url) = &theme.logo_url {
+        body.push_str(&format!(
+            "<header class=\"print-header\"><img src=\"{}\" alt=\"\"></header>\n",
+            escape_html(logo_url)
+        ));
+    }
+    body.push_str(&render_note_article(note, tz, render));
+
+    render_styled_document(note.title_str(), &css, &body, render)
+}
+
+/// Render a list of notes into one standalone document
+pub fn render_notes_document(
+    title: &str,
+    notes: &[Note],
+    tz: Tz,
+    render: &RenderOptions,
+) -> String {
+    let body: String = notes
+        .iter()
+        .map(|note| render_note_article(note, tz, render))
+        .collect();
+    render_document(title, &body, render)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notes_domain::NoteTitle;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_markdown_to_html_renders_headings_and_tables() {
+        let html = markdown_to_html(
+            "# Title\n\n| a | b |\n|---|---|\n| 1 | 2 |",
+            &RenderOptions::default(),
+        );
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<table>"));
+    }
+
+    #[test]
+    fn test_note_title_is_escaped() {
+        let title = NoteTitle::try_from("<script>alert(1)</script>").ok();
+        let note = Note::new(Uuid::new_v4(), title, "content");
+
+        let html = render_note_article(&note, Tz::UTC, &RenderOptions::default());
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_raw_html_is_shown_as_text() {
+        let html = markdown_to_html(
+            "<iframe src=\"file:///etc/passwd\"></iframe>\n\nA <img src=\"http://169.254.169.254/\"> b",
+            &RenderOptions::default(),
+        );
+        assert!(!html.contains("<iframe"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;iframe src="));
+    }
+
+    #[test]
+    fn test_only_proxied_images_load() {
+        let content = notes_domain::images::replace_image_urls(
+            "![a](https://tracker.example/a.png) <img src=\"https://tracker.example/b.png\">",
+            |url| Some(format!("/proxy?url={}", url)),
+        );
+        let html = markdown_to_html(&content, &RenderOptions::default());
+
+        assert_eq!(html.matches("<img").count(), 1);
+        assert!(html.contains("<img src=\"/proxy?url=https://tracker.example/a.png\""));
+    }
+
+    #[test]
+    fn test_only_safe_url_schemes_are_kept() {
+        let html = markdown_to_html(
+            "[a](javascript:alert(1)) [b](<java\tscript:x>) ![c](file:///etc/hosts) \
+             [d](https://example.com) [e](mailto:a@example.com) [f](other.html#top) \
+             ![g](data:image/png;base64,AA) [h](data:text/html,x)",
+            &RenderOptions::default(),
+        );
+        assert!(!html.contains("javascript"));
+        assert!(!html.contains("script:x"));
+        assert!(!html.contains("file:"));
+        assert!(!html.contains("data:text/html"));
+        assert!(html.contains("href=\"https://example.com\""));
+        assert!(html.contains("href=\"mailto:a@example.com\""));
+        assert!(html.contains("href=\"other.html#top\""));
+        assert!(html.contains("src=\"data:image/png;base64,AA\""));
+    }
+
+    #[test]
+    fn test_print_view_applies_theme() {
+        let note = Note::new(Uuid::new_v4(), NoteTitle::try_from("Minutes").ok(), "text");
+        let theme = PrintTheme {
+            logo_url: Some("https://example.com/logo.png?a=1&b=\"2\"".to_string()),
+            accent_color: Some("#1f6feb".to_string()),
+        };
+
+        let html = render_print_view(&note, &theme, Tz::UTC, &RenderOptions::default());
+        assert!(html.contains("<title>Minutes</title>"));
+        assert!(html.contains("src=\"https://example.com/logo.png?a=1&amp;b=&quot;2&quot;\""));
+        assert!(html.contains("color: #1f6feb"));
+
+        let plain = render_print_view(
+            &note,
+            &PrintTheme::default(),
+            Tz::UTC,
+            &RenderOptions::default(),
+        );
+        assert!(!plain.contains("print-header\"><img"));
+    }
+
+    #[test]
+    fn test_timestamps_are_shown_in_the_readers_timezone() {
+        use chrono::TimeZone;
+
+        let at = Utc.with_ymd_and_hms(2026, 7, 1, 22, 30, 0).unwrap();
+        assert_eq!(format_timestamp(at, Tz::UTC), "2026-07-01 22:30 UTC");
+        assert_eq!(
+            format_timestamp(at, Tz::Europe__Warsaw),
+            "2026-07-02 00:30 CEST"
+        );
+    }
+
+    #[test]
+    fn test_untitled_note_has_no_heading() {
+        let note = Note::new(Uuid::new_v4(), None, "just text");
+        let html = render_note_article(&note, Tz::UTC, &RenderOptions::default());
+        assert!(!html.contains("<h1>"));
+        assert!(html.contains("just text"));
+    }
+
+    #[test]
+    fn test_math_is_passed_to_katex_when_enabled() {
+        let note = Note::new(Uuid::new_v4(), None, "Costs $5 or $a^2$.\n\n$$\\sum x$$");
+        let math = RenderOptions {
+            math: true,
+            ..RenderOptions::default()
+        };
+
+        let typeset = render_notes_document("Math", std::slice::from_ref(&note), Tz::UTC, &math);
+        assert!(typeset.contains("katex.min.js"));
+        assert!(typeset.contains("<span class=\"math math-display\">"));
+
+        let plain = render_notes_document(
+            "Math",
+            std::slice::from_ref(&note),
+            Tz::UTC,
+            &RenderOptions::default(),
+        );
+        assert!(!plain.contains("katex"));
+        assert!(plain.contains("$a^2$"));
+
+        let no_math = Note::new(Uuid::new_v4(), None, "No formulas");
+        let untouched = render_notes_document("Plain", &[no_math], Tz::UTC, &math);
+        assert!(!untouched.contains("katex"));
+    }
+}
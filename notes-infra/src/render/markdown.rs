@@ -0,0 +1,143 @@
+//! Markdown export
+//!
+//! Writes each note as a markdown file whose front matter holds its title,
+//! tags and timestamps, so the notes can be opened in other editors.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+
+use notes_domain::{DomainResult, Note};
+
+use super::site::{SiteFile, zip_blocking};
+use crate::formats::Exporter;
+
+/// File name stem from the note title, or its ID when untitled
+fn file_stem(note: &Note) -> String {
+    let mut slug = String::new();
+    for c in note.title_str().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        note.id.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// JSON strings and arrays are valid YAML, which spares escaping rules
+fn yaml_value<T: serde::Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn render_note(note: &Note) -> String {
+    let tags: Vec<&str> = note.tags.iter().map(|tag| tag.name_str()).collect();
+
+    let mut markdown = String::from("---\n");
+    if !note.title_str().is_empty() {
+        markdown.push_str(&format!("title: {}\n", yaml_value(note.title_str())));
+    }
+    markdown.push_str(&format!("tags: {}\n", yaml_value(&tags)));
+    markdown.push_str(&format!("created: {}\n", note.created_at.to_rfc3339()));
+    markdown.push_str(&format!("updated: {}\n", note.updated_at.to_rfc3339()));
+    if note.is_pinned {
+        markdown.push_str("pinned: true\n");
+    }
+    if note.is_archived {
+        markdown.push_str("archived: true\n");
+    }
+    markdown.push_str("---\n\n");
+    markdown.push_str(&note.content);
+    if !note.content.ends_with('\n') {
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// One markdown file per note, named after its title; notes sharing a
+/// title get numbered names
+pub fn build_markdown(notes: &[Note]) -> Vec<SiteFile> {
+    let mut taken = HashSet::new();
+    notes
+        .iter()
+        .map(|note| {
+            let stem = file_stem(note);
+            let mut path = format!("{}.md", stem);
+            let mut n = 2;
+            while !taken.insert(path.clone()) {
+                path = format!("{}-{}.md", stem, n);
+                n += 1;
+            }
+            SiteFile {
+                path,
+                contents: render_note(note),
+            }
+        })
+        .collect()
+}
+
+/// Zips one markdown file per note
+pub struct MarkdownExporter;
+
+#[async_trait]
+impl Exporter for MarkdownExporter {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/zip"
+    }
+
+    fn extension(&self) -> &'static str {
+        "zip"
+    }
+
+    async fn export(&self, _title: &str, notes: Vec<Note>, _tz: Tz) -> DomainResult<Vec<u8>> {
+        zip_blocking(move || build_markdown(&notes)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notes_domain::{NoteTitle, Tag, TagName};
+    use uuid::Uuid;
+
+    fn note(title: &str, content: &str) -> Note {
+        Note::new(Uuid::nil(), NoteTitle::try_from(title).ok(), content)
+    }
+
+    #[test]
+    fn test_build_markdown_writes_front_matter_and_unique_names() {
+        let mut plan = note("Project: \"Plan\"", "- ship it");
+        plan.tags
+            .push(Tag::new(TagName::try_from("work").unwrap(), Uuid::nil()));
+        plan.is_pinned = true;
+        let untitled = note("", "scratch");
+
+        let files = build_markdown(&[plan.clone(), note("Project Plan", "v2"), untitled.clone()]);
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "project-plan.md".to_string(),
+                "project-plan-2.md".to_string(),
+                format!("{}.md", untitled.id),
+            ]
+        );
+
+        let contents = &files[0].contents;
+        assert!(contents.starts_with("---\ntitle: \"Project: \\\"Plan\\\"\"\ntags: [\"work\"]\n"));
+        assert!(contents.contains("pinned: true\n"));
+        assert!(contents.ends_with("---\n\n- ship it\n"));
+        assert!(!files[2].contents.contains("title:"));
+    }
+}
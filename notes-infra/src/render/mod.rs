@@ -0,0 +1,11 @@
+//! Server-side rendering of note content.
+//!
+//! Shared by the exporters (PDF, static site, markdown) so every output
+//! format renders Markdown the same way.
+
+pub mod csv;
+#[cfg(feature = "highlighting")]
+pub mod highlight;
+pub mod html;
+pub mod markdown;
+pub mod site;
@@ -0,0 +1,372 @@
+//! Static HTML site export
+//!
+//! Turns a set of notes into a small browsable wiki: an index page, one page
+//! per tag and one page per note, with `[[wiki-links]]` resolved to relative
+//! links between note pages.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use uuid::Uuid;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use notes_domain::{
+    DomainError, DomainResult, Note, Tag,
+    wiki_links::{LinkTargets, replace_wiki_links},
+};
+
+use super::html::{
+    RenderOptions, escape_html, format_timestamp, markdown_to_html, render_document,
+};
+use crate::formats::Exporter;
+
+/// A single generated file, with a path relative to the site root
+#[derive(Debug, Clone)]
+pub struct SiteFile {
+    pub path: String,
+    pub contents: String,
+}
+
+fn note_path(id: Uuid) -> String {
+    format!("notes/{}.html", id)
+}
+
+fn tag_path(id: Uuid) -> String {
+    format!("tags/{}.html", id)
+}
+
+fn note_label(note: &Note) -> &str {
+    if note.title_str().is_empty() {
+        "Untitled"
+    } else {
+        note.title_str()
+    }
+}
+
+fn note_list(notes: &[&Note], prefix: &str) -> String {
+    let mut list = String::from("<ul>\n");
+    for note in notes {
+        list.push_str(&format!(
+            "<li><a href=\"{}{}\">{}</a></li>\n",
+            prefix,
+            note_path(note.id),
+            escape_html(note_label(note))
+        ));
+    }
+    list.push_str("</ul>\n");
+    list
+}
+
+fn render_index(
+    site_title: &str,
+    notes: &[Note],
+    tags: &[(&Tag, Vec<&Note>)],
+    render: &RenderOptions,
+) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape_html(site_title));
+
+    if !tags.is_empty() {
+        body.push_str("<h2>Tags</h2>\n<p class=\"note-tags\">");
+        for (tag, tagged) in tags {
+            body.push_str(&format!(
+                "<a class=\"tag\" href=\"{}\">#{} ({})</a>",
+                tag_path(tag.id),
+                escape_html(tag.name_str()),
+                tagged.len()
+            ));
+        }
+        body.push_str("</p>\n");
+    }
+
+    body.push_str("<h2>Notes</h2>\n");
+    let all: Vec<&Note> = notes.iter().collect();
+    body.push_str(&note_list(&all, ""));
+
+    render_document(site_title, &body, render)
+}
+
+fn render_tag_page(site_title: &str, tag: &Tag, notes: &[&Note], render: &RenderOptions) -> String {
+    let mut body = String::from("<nav><a href=\"../index.html\">Index</a></nav>\n");
+    body.push_str(&format!("<h1>#{}</h1>\n", escape_html(tag.name_str())));
+    body.push_str(&note_list(notes, "../"));
+
+    render_document(
+        &format!("#{} - {}", tag.name_str(), site_title),
+        &body,
+        render,
+    )
+}
+
+fn render_note_page(
+    site_title: &str,
+    note: &Note,
+    links: &LinkTargets,
+    tz: Tz,
+    render: &RenderOptions,
+) -> String {
+    // Note pages live next to each other, so resolved links are bare file
+    // names; links to notes not on the site are left as their text
+    let content = replace_wiki_links(&note.content, |link| match links.resolve(&link.target) {
+        Some(id) => format!("[{}]({}.html)", escape_markdown(link.display_text()), id),
+        None => escape_markdown(link.display_text()),
+    });
+
+    let mut body = String::from("<nav><a href=\"../index.html\">Index</a></nav>\n");
+    body.push_str("<article>\n");
+    body.push_str(&format!("<h1>{}</h1>\n", escape_html(note_label(note))));
+    body.push_str(&format!(
+        "<p class=\"note-meta\">Updated {}</p>\n",
+        format_timestamp(note.updated_at, tz)
+    ));
+
+    if !note.tags.is_empty() {
+        body.push_str("<p class=\"note-tags\">");
+        for tag in &note.tags {
+            body.push_str(&format!(
+                "<a class=\"tag\" href=\"../{}\">#{}</a>",
+                tag_path(tag.id),
+                escape_html(tag.name_str())
+            ));
+        }
+        body.push_str("</p>\n");
+    }
+
+    body.push_str(&markdown_to_html(&content, render));
+    body.push_str("</article>\n");
+
+    render_document(
+        &format!("{} - {}", note_label(note), site_title),
+        &body,
+        render,
+    )
+}
+
+/// Render notes into the files of a static site, with times shown in `tz`
+pub fn build_site(
+    site_title: &str,
+    notes: &[Note],
+    tz: Tz,
+    render: &RenderOptions,
+) -> Vec<SiteFile> {
+    let links = LinkTargets::new(notes);
+
+    // Group notes by tag; BTreeMap keeps tag pages in a stable order
+    let mut by_tag: BTreeMap<String, (&Tag, Vec<&Note>)> = BTreeMap::new();
+    for note in notes {
+        for tag in &note.tags {
+            by_tag
+                .entry(tag.name_str().to_lowercase())
+                .or_insert_with(|| (tag, Vec::new()))
+                .1
+                .push(note);
+        }
+    }
+    let tags: Vec<(&Tag, Vec<&Note>)> = by_tag.into_values().collect();
+
+    let mut files = Vec::with_capacity(notes.len() + tags.len() + 1);
+    files.push(SiteFile {
+        path: "index.html".to_string(),
+        contents: render_index(site_title, notes, &tags, render),
+    });
+
+    for (tag, tagged) in &tags {
+        files.push(SiteFile {
+            path: tag_path(tag.id),
+            contents: render_tag_page(site_title, tag, tagged, render),
+        });
+    }
+
+    for note in notes {
+        files.push(SiteFile {
+            path: note_path(note.id),
+            contents: render_note_page(site_title, note, &links, tz, render),
+        });
+    }
+
+    files
+}
+
+/// Escape text so Markdown shows it as typed
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn archive_error(e: impl std::fmt::Display) -> DomainError {
+    DomainError::InfrastructureError(format!("Failed to build site archive: {}", e))
+}
+
+/// Package site files into a zip archive
+pub fn write_zip(files: &[SiteFile]) -> DomainResult<Vec<u8>> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for file in files {
+        writer
+            .start_file(file.path.as_str(), options)
+            .map_err(archive_error)?;
+        writer
+            .write_all(file.contents.as_bytes())
+            .map_err(archive_error)?;
+    }
+
+    let cursor = writer.finish().map_err(archive_error)?;
+    Ok(cursor.into_inner())
+}
+
+/// Build files and package them into a zip archive off the async workers,
+/// since rendering and compression are CPU-bound
+pub(crate) async fn zip_blocking(
+    build: impl FnOnce() -> Vec<SiteFile> + Send + 'static,
+) -> DomainResult<Vec<u8>> {
+    tokio::task::spawn_blocking(move || write_zip(&build()))
+        .await
+        .map_err(archive_error)?
+}
+
+/// Write site files below `root`, replacing any previously published copy
+pub async fn write_to_directory(files: &[SiteFile], root: &Path) -> DomainResult<()> {
+    let to_error = |e: std::io::Error| {
+        DomainError::InfrastructureError(format!("Failed to publish site: {}", e))
+    };
+
+    if tokio::fs::try_exists(root).await.map_err(to_error)? {
+        tokio::fs::remove_dir_all(root).await.map_err(to_error)?;
+    }
+
+    for file in files {
+        let path = root.join(&file.path);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(to_error)?;
+        }
+        tokio::fs::write(&path, &file.contents)
+            .await
+            .map_err(to_error)?;
+    }
+
+    Ok(())
+}
+
+/// Zips the static site of non-archived notes
+pub struct SiteExporter {
+    render: RenderOptions,
+}
+
+impl SiteExporter {
+    pub fn new(render: RenderOptions) -> Self {
+        Self { render }
+    }
+}
+
+#[async_trait]
+impl Exporter for SiteExporter {
+    fn name(&self) -> &'static str {
+        "site"
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/zip"
+    }
+
+    fn extension(&self) -> &'static str {
+        "zip"
+    }
+
+    fn includes_archived(&self) -> bool {
+        false
+    }
+
+    fn renders_content(&self) -> bool {
+        true
+    }
+
+    async fn export(&self, title: &str, notes: Vec<Note>, tz: Tz) -> DomainResult<Vec<u8>> {
+        let title = title.to_string();
+        let render = self.render.clone();
+        zip_blocking(move || build_site(&title, &notes, tz, &render)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notes_domain::{NoteTitle, TagName};
+
+    fn note(title: &str, content: &str) -> Note {
+        Note::new(Uuid::nil(), NoteTitle::try_from(title).ok(), content)
+    }
+
+    #[test]
+    fn test_build_site_generates_index_tag_and_note_pages() {
+        let mut first = note("First", "hello");
+        first
+            .tags
+            .push(Tag::new(TagName::try_from("work").unwrap(), Uuid::nil()));
+        let second = note("Second", "world");
+
+        let files = build_site(
+            "Site",
+            &[first.clone(), second.clone()],
+            Tz::UTC,
+            &RenderOptions::default(),
+        );
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+
+        assert!(paths.contains(&"index.html"));
+        assert!(paths.contains(&tag_path(first.tags[0].id).as_str()));
+        assert!(paths.contains(&note_path(first.id).as_str()));
+        assert!(paths.contains(&note_path(second.id).as_str()));
+    }
+
+    #[test]
+    fn test_wiki_links_resolve_to_relative_note_pages() {
+        let target = note("Target Note", "target");
+        let source = note(
+            "Source",
+            "see [[target note|the target]] and [[Missing]] or [[<b>Bold</b>]]",
+        );
+
+        let files = build_site(
+            "Site",
+            &[source.clone(), target.clone()],
+            Tz::UTC,
+            &RenderOptions::default(),
+        );
+        let page = files
+            .iter()
+            .find(|f| f.path == note_path(source.id))
+            .unwrap();
+
+        assert!(
+            page.contents
+                .contains(&format!("<a href=\"{}.html\">the target</a>", target.id))
+        );
+        assert!(
+            page.contents
+                .contains("and Missing or &lt;b&gt;Bold&lt;/b&gt;")
+        );
+    }
+
+    #[test]
+    fn test_write_zip_produces_archive() {
+        let files = build_site(
+            "Site",
+            &[note("Only", "content")],
+            Tz::UTC,
+            &RenderOptions::default(),
+        );
+        let archive = write_zip(&files).unwrap();
+
+        // Zip local file header signature
+        assert_eq!(&archive[..4], b"PK\x03\x04");
+    }
+}
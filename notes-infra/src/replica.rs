@@ -0,0 +1,454 @@
+//! Read/write splitting across a primary database and a read replica.
+//!
+//! [`ReplicatedNoteRepository`], [`ReplicatedTagRepository`] and
+//! [`ReplicatedUserRepository`] send list and search reads to repositories
+//! backed by a read-only replica (e.g. a LiteFS replica node or a Postgres
+//! standby) and everything else to ones backed by the primary.
+//!
+//! Lookups by key go to the primary too. Services read a note, tag or user
+//! by key before changing it, to check ownership, locks and versions, and a
+//! lagging replica would let those checks pass against a stale row.
+//!
+//! A replica that cannot be reached (a connection or busy error) is skipped
+//! for that read, so replica outages do not fail requests. List and search
+//! results may still miss the latest writes until the replica catches up.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use notes_domain::{
+    DomainError, DomainResult, EmailChange, Note, NoteCounts, NoteFilter, NoteRepository,
+    NoteVersion, Tag, TagRepository, User, UserRepository, UserSettings, bookmarks::LinkPreview,
+    query::NoteQuery, search::TitleSuggestion,
+};
+
+/// Replica result, or the primary's when the replica was unreachable
+async fn or_primary<T>(
+    replica: DomainResult<T>,
+    primary: impl Future<Output = DomainResult<T>>,
+) -> DomainResult<T> {
+    match replica {
+        Err(DomainError::RepositoryError(ref e)) if e.is_retryable() => {
+            tracing::warn!("Read replica unavailable, reading from primary: {}", e);
+            primary.await
+        }
+        result => result,
+    }
+}
+
+/// NoteRepository listing and searching on a replica, everything else on the primary
+pub struct ReplicatedNoteRepository {
+    primary: Arc<dyn NoteRepository>,
+    replica: Arc<dyn NoteRepository>,
+}
+
+impl ReplicatedNoteRepository {
+    pub fn new(primary: Arc<dyn NoteRepository>, replica: Arc<dyn NoteRepository>) -> Self {
+        Self { primary, replica }
+    }
+}
+
+#[async_trait]
+impl NoteRepository for ReplicatedNoteRepository {
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Note>> {
+        self.primary.find_by_id(id).await
+    }
+
+    async fn find_by_user(&self, user_id: Uuid, filter: NoteFilter) -> DomainResult<Vec<Note>> {
+        or_primary(
+            self.replica.find_by_user(user_id, filter.clone()).await,
+            self.primary.find_by_user(user_id, filter),
+        )
+        .await
+    }
+
+    async fn count_by_user(&self, user_id: Uuid) -> DomainResult<NoteCounts> {
+        or_primary(
+            self.replica.count_by_user(user_id).await,
+            self.primary.count_by_user(user_id),
+        )
+        .await
+    }
+
+    async fn save(&self, note: &Note) -> DomainResult<()> {
+        self.primary.save(note).await
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<u64> {
+        self.primary.delete(id).await
+    }
+
+    async fn find_trashed_before(&self, cutoff: DateTime<Utc>) -> DomainResult<Vec<Note>> {
+        // Lists notes to purge; one restored a moment ago must not be
+        self.primary.find_trashed_before(cutoff).await
+    }
+
+    async fn search(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+    ) -> DomainResult<Vec<Note>> {
+        or_primary(
+            self.replica.search(user_id, query, include_archived).await,
+            self.primary.search(user_id, query, include_archived),
+        )
+        .await
+    }
+
+    async fn search_versions(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_archived: bool,
+        limit: usize,
+    ) -> DomainResult<Vec<NoteVersion>> {
+        or_primary(
+            self.replica
+                .search_versions(user_id, query, include_archived, limit)
+                .await,
+            self.primary
+                .search_versions(user_id, query, include_archived, limit),
+        )
+        .await
+    }
+
+    async fn suggest_titles(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<TitleSuggestion>> {
+        or_primary(
+            self.replica.suggest_titles(user_id, prefix, limit).await,
+            self.primary.suggest_titles(user_id, prefix, limit),
+        )
+        .await
+    }
+
+    async fn query(&self, user_id: Uuid, query: &NoteQuery) -> DomainResult<Vec<Note>> {
+        or_primary(
+            self.replica.query(user_id, query).await,
+            self.primary.query(user_id, query),
+        )
+        .await
+    }
+
+    async fn save_version(&self, version: &NoteVersion) -> DomainResult<()> {
+        self.primary.save_version(version).await
+    }
+
+    async fn find_versions_by_note_id(&self, note_id: Uuid) -> DomainResult<Vec<NoteVersion>> {
+        self.primary.find_versions_by_note_id(note_id).await
+    }
+
+    async fn find_latest_version(&self, note_id: Uuid) -> DomainResult<Option<NoteVersion>> {
+        // Decides whether a save snapshots a new version, so it must see
+        // the versions the primary has
+        self.primary.find_latest_version(note_id).await
+    }
+
+    async fn reorder_pins(&self, user_id: Uuid, note_ids: &[Uuid]) -> DomainResult<()> {
+        self.primary.reorder_pins(user_id, note_ids).await
+    }
+
+    async fn find_stale_link_previews(&self, limit: usize) -> DomainResult<Vec<Note>> {
+        // A lagging replica would list notes whose previews were just saved
+        self.primary.find_stale_link_previews(limit).await
+    }
+
+    async fn save_link_previews(
+        &self,
+        note_id: Uuid,
+        previews: &[LinkPreview],
+        as_of: DateTime<Utc>,
+    ) -> DomainResult<()> {
+        self.primary
+            .save_link_previews(note_id, previews, as_of)
+            .await
+    }
+}
+
+/// TagRepository listing on a replica, everything else on the primary
+pub struct ReplicatedTagRepository {
+    primary: Arc<dyn TagRepository>,
+    replica: Arc<dyn TagRepository>,
+}
+
+impl ReplicatedTagRepository {
+    pub fn new(primary: Arc<dyn TagRepository>, replica: Arc<dyn TagRepository>) -> Self {
+        Self { primary, replica }
+    }
+}
+
+#[async_trait]
+impl TagRepository for ReplicatedTagRepository {
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Tag>> {
+        self.primary.find_by_id(id).await
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        or_primary(
+            self.replica.find_by_user(user_id).await,
+            self.primary.find_by_user(user_id),
+        )
+        .await
+    }
+
+    async fn find_by_prefix(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<Tag>> {
+        or_primary(
+            self.replica.find_by_prefix(user_id, prefix, limit).await,
+            self.primary.find_by_prefix(user_id, prefix, limit),
+        )
+        .await
+    }
+
+    async fn find_by_name(&self, user_id: Uuid, name: &str) -> DomainResult<Option<Tag>> {
+        self.primary.find_by_name(user_id, name).await
+    }
+
+    async fn save(&self, tag: &Tag) -> DomainResult<()> {
+        self.primary.save(tag).await
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        self.primary.delete(id).await
+    }
+
+    async fn add_to_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
+        self.primary.add_to_note(tag_id, note_id).await
+    }
+
+    async fn add_many_to_note(&self, tag_ids: &[Uuid], note_id: Uuid) -> DomainResult<()> {
+        self.primary.add_many_to_note(tag_ids, note_id).await
+    }
+
+    async fn remove_from_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
+        self.primary.remove_from_note(tag_id, note_id).await
+    }
+
+    async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<Tag>> {
+        self.primary.find_by_note(note_id).await
+    }
+
+    async fn merge(&self, source_id: Uuid, target_id: Uuid) -> DomainResult<()> {
+        self.primary.merge(source_id, target_id).await
+    }
+
+    async fn find_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        or_primary(
+            self.replica.find_unused(user_id).await,
+            self.primary.find_unused(user_id),
+        )
+        .await
+    }
+
+    async fn delete_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        self.primary.delete_unused(user_id).await
+    }
+
+    async fn reorder(&self, user_id: Uuid, tag_ids: &[Uuid]) -> DomainResult<()> {
+        self.primary.reorder(user_id, tag_ids).await
+    }
+}
+
+/// UserRepository reading from a replica and writing to the primary.
+///
+/// Only the user id lists of the scheduled jobs are read from the replica;
+/// users, settings and pending email changes are read back right after
+/// being written.
+pub struct ReplicatedUserRepository {
+    primary: Arc<dyn UserRepository>,
+    replica: Arc<dyn UserRepository>,
+}
+
+impl ReplicatedUserRepository {
+    pub fn new(primary: Arc<dyn UserRepository>, replica: Arc<dyn UserRepository>) -> Self {
+        Self { primary, replica }
+    }
+}
+
+#[async_trait]
+impl UserRepository for ReplicatedUserRepository {
+    async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<User>> {
+        self.primary.find_by_id(id).await
+    }
+
+    async fn find_by_subject(&self, subject: &str) -> DomainResult<Option<User>> {
+        self.primary.find_by_subject(subject).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> DomainResult<Option<User>> {
+        self.primary.find_by_email(email).await
+    }
+
+    async fn save(&self, user: &User) -> DomainResult<()> {
+        self.primary.save(user).await
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        self.primary.delete(id).await
+    }
+
+    async fn find_settings(&self, user_id: Uuid) -> DomainResult<UserSettings> {
+        self.primary.find_settings(user_id).await
+    }
+
+    async fn save_settings(&self, user_id: Uuid, settings: &UserSettings) -> DomainResult<()> {
+        self.primary.save_settings(user_id, settings).await
+    }
+
+    async fn save_email_change(&self, change: &EmailChange) -> DomainResult<()> {
+        self.primary.save_email_change(change).await
+    }
+
+    async fn find_email_change(&self, token: &str) -> DomainResult<Option<EmailChange>> {
+        self.primary.find_email_change(token).await
+    }
+
+    async fn delete_email_change(&self, user_id: Uuid) -> DomainResult<()> {
+        self.primary.delete_email_change(user_id).await
+    }
+
+    async fn find_ids_with_auto_archive(&self) -> DomainResult<Vec<Uuid>> {
+        or_primary(
+            self.replica.find_ids_with_auto_archive().await,
+            self.primary.find_ids_with_auto_archive(),
+        )
+        .await
+    }
+
+    async fn find_ids_with_auto_title(&self) -> DomainResult<Vec<Uuid>> {
+        or_primary(
+            self.replica.find_ids_with_auto_title().await,
+            self.primary.find_ids_with_auto_title(),
+        )
+        .await
+    }
+
+    async fn find_all_ids(&self) -> DomainResult<Vec<Uuid>> {
+        or_primary(
+            self.replica.find_all_ids().await,
+            self.primary.find_all_ids(),
+        )
+        .await
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::{SqliteNoteRepository, SqliteTagRepository, SqliteUserRepository};
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, NoteTitle, RepositoryError, TagName};
+    use sqlx::SqlitePool;
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    /// Two unrelated databases, so every write is invisible to the
+    /// "replica" as if it had not been replicated yet
+    async fn setup() -> (SqlitePool, SqlitePool, User) {
+        let primary = setup_test_db().await;
+        let replica = setup_test_db().await;
+        let user = User::new(
+            "test|replica",
+            Email::try_from("replica@example.com").unwrap(),
+        );
+        SqliteUserRepository::new(primary.clone())
+            .save(&user)
+            .await
+            .unwrap();
+        (primary, replica, user)
+    }
+
+    #[tokio::test]
+    async fn test_lookups_read_from_primary_before_replicated() {
+        let (primary, replica, user) = setup().await;
+        let notes = ReplicatedNoteRepository::new(
+            Arc::new(SqliteNoteRepository::new(primary.clone())),
+            Arc::new(SqliteNoteRepository::new(replica.clone())),
+        );
+        let tags = ReplicatedTagRepository::new(
+            Arc::new(SqliteTagRepository::new(primary.clone())),
+            Arc::new(SqliteTagRepository::new(replica)),
+        );
+
+        let note = Note::new(user.id, NoteTitle::try_from("Fresh").ok(), "content");
+        notes.save(&note).await.unwrap();
+        let tag = Tag::new(TagName::try_from("work").unwrap(), user.id);
+        tags.save(&tag).await.unwrap();
+
+        // Writes went to the primary only
+        assert!(
+            SqliteNoteRepository::new(primary)
+                .find_by_id(note.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        // Keyed lookups, as done before updates, read their own writes
+        assert!(notes.find_by_id(note.id).await.unwrap().is_some());
+        assert!(tags.find_by_name(user.id, "work").await.unwrap().is_some());
+        // Lists are served by the replica, which has not caught up
+        assert!(
+            notes
+                .find_by_user(user.id, NoteFilter::new())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(tags.find_by_user(user.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_replica_falls_back_to_primary() {
+        let (primary, replica, user) = setup().await;
+        let notes = ReplicatedNoteRepository::new(
+            Arc::new(SqliteNoteRepository::new(primary)),
+            Arc::new(SqliteNoteRepository::new(replica.clone())),
+        );
+        notes
+            .save(&Note::new(user.id, None, "content"))
+            .await
+            .unwrap();
+
+        replica.close().await;
+
+        assert_eq!(
+            notes
+                .find_by_user(user.id, NoteFilter::new())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replica_errors_other_than_outages_are_not_masked() {
+        let result: DomainResult<Vec<Note>> = or_primary(
+            Err(DomainError::RepositoryError(RepositoryError::Other(
+                "syntax error".to_string(),
+            ))),
+            async { Ok(Vec::new()) },
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+}
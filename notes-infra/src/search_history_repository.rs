@@ -0,0 +1,231 @@
+//! SQLite implementation of SearchHistoryRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, escape_like, map_sqlx_error, write};
+use notes_domain::{DomainResult, SearchHistoryRepository, search::SearchHistoryEntry};
+
+/// SQLite adapter for SearchHistoryRepository, one row per distinct query
+pub struct SqliteSearchHistoryRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteSearchHistoryRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct SearchHistoryRow {
+    query: String,
+    last_searched_at: String,
+    search_count: i64,
+}
+
+impl SearchHistoryRow {
+    fn try_into_entry(self) -> DomainResult<SearchHistoryEntry> {
+        let last_searched_at = DateTime::parse_from_rfc3339(&self.last_searched_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?;
+        Ok(SearchHistoryEntry {
+            query: self.query,
+            last_searched_at,
+            search_count: u32::try_from(self.search_count)
+                .map_err(|e| decode_error(format!("Invalid search count: {}", e)))?,
+        })
+    }
+}
+
+#[async_trait]
+impl SearchHistoryRepository for SqliteSearchHistoryRepository {
+    async fn record(&self, user_id: Uuid, query: &str, at: DateTime<Utc>) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO search_queries (user_id, query, last_searched_at)
+                VALUES (?, ?, ?)
+                ON CONFLICT(user_id, query) DO UPDATE SET
+                    last_searched_at = excluded.last_searched_at,
+                    search_count = search_count + 1
+                "#,
+            )
+            .bind(user_id.to_string())
+            .bind(query)
+            .bind(at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        limit: usize,
+    ) -> DomainResult<Vec<SearchHistoryEntry>> {
+        let rows: Vec<SearchHistoryRow> = sqlx::query_as(
+            r#"
+            SELECT query, last_searched_at, search_count FROM search_queries
+            WHERE user_id = ?
+            ORDER BY last_searched_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter()
+            .map(SearchHistoryRow::try_into_entry)
+            .collect()
+    }
+
+    async fn find_recent(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<String>> {
+        // LIKE ignores ASCII case; idx_search_queries_recent serves the ordering
+        sqlx::query_scalar(
+            r#"
+            SELECT query FROM search_queries
+            WHERE user_id = ? AND query LIKE ? ESCAPE '\'
+            ORDER BY last_searched_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(format!("{}%", escape_like(prefix)))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn find_frequent(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<String>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT query FROM search_queries
+            WHERE user_id = ? AND search_count > 1 AND query LIKE ? ESCAPE '\'
+            ORDER BY search_count DESC, last_searched_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(format!("{}%", escape_like(prefix)))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)
+    }
+
+    async fn delete_by_user(&self, user_id: Uuid) -> DomainResult<u64> {
+        write(move || async move {
+            let result = sqlx::query("DELETE FROM search_queries WHERE user_id = ?")
+                .bind(user_id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(result.rows_affected())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::user_repository::SqliteUserRepository;
+    use chrono::Duration;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(
+            "test|history",
+            Email::try_from("history@example.com").unwrap(),
+        );
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_find_recent_matches_prefix_newest_first() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteSearchHistoryRepository::new(pool);
+        let now = Utc::now();
+
+        repo.record(user.id, "rust traits", now - Duration::minutes(3))
+            .await
+            .unwrap();
+        repo.record(user.id, "recipes", now - Duration::minutes(2))
+            .await
+            .unwrap();
+        repo.record(user.id, "groceries", now - Duration::minutes(1))
+            .await
+            .unwrap();
+        // Searching again moves the query to the front
+        repo.record(user.id, "rust traits", now).await.unwrap();
+
+        assert_eq!(
+            repo.find_recent(user.id, "R", 10).await.unwrap(),
+            vec!["rust traits", "recipes"]
+        );
+        assert_eq!(repo.find_recent(user.id, "r", 1).await.unwrap().len(), 1);
+        assert!(repo.find_recent(user.id, "%", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_queries_count_and_clear() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteSearchHistoryRepository::new(pool);
+        let now = Utc::now();
+
+        repo.record(user.id, "rust", now - Duration::minutes(2))
+            .await
+            .unwrap();
+        repo.record(user.id, "rust", now - Duration::minutes(1))
+            .await
+            .unwrap();
+        repo.record(user.id, "ruby", now).await.unwrap();
+
+        let history = repo.find_by_user(user.id, 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].query, "ruby");
+        assert_eq!(history[1].search_count, 2);
+        assert_eq!(
+            repo.find_frequent(user.id, "ru", 10).await.unwrap(),
+            vec!["rust"]
+        );
+
+        assert_eq!(repo.delete_by_user(user.id).await.unwrap(), 2);
+        assert!(repo.find_by_user(user.id, 10).await.unwrap().is_empty());
+    }
+}
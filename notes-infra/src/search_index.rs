@@ -0,0 +1,265 @@
+//! Full-text search index setup
+//!
+//! The FTS5 tables default to the `unicode61` tokenizer with diacritics
+//! folded. Instances whose users write in languages without spaces between
+//! words (Chinese, Japanese, ...) can switch to the `trigram` tokenizer,
+//! which matches any substring of three or more characters, and English
+//! instances to `porter`, which also matches other forms of a word.
+//!
+//! `auto` picks one from the dominant language of the instance's notes each
+//! time the server starts.
+
+use std::str::FromStr;
+
+use k_core::db::DatabasePool;
+use notes_domain::language::Language;
+
+/// FTS tables and the content tables they index
+pub(crate) const FTS_TABLES: &[(&str, &str)] = &[
+    ("notes_fts", "notes"),
+    ("note_versions_fts", "note_versions"),
+];
+
+/// Tokenizer used by the full-text search index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchTokenizer {
+    /// Word-based, case and diacritic insensitive
+    #[default]
+    Unicode61,
+    /// Substring matching, suited to CJK text
+    Trigram,
+    /// Word-based with English stemming, so `running` matches `run`
+    Porter,
+    /// Whichever suits the dominant language of the notes
+    Auto,
+}
+
+impl SearchTokenizer {
+    /// The tokenizer suiting notes mostly written in `language`
+    pub fn for_language(language: Option<&Language>) -> Self {
+        match language {
+            Some(language) if language.is_unspaced() => Self::Trigram,
+            Some(language) if language.code() == "en" => Self::Porter,
+            _ => Self::Unicode61,
+        }
+    }
+
+    /// FTS5 `tokenize` option
+    fn definition(self) -> &'static str {
+        match self {
+            Self::Unicode61 | Self::Auto => "unicode61 remove_diacritics 2",
+            Self::Trigram => "trigram",
+            Self::Porter => "porter unicode61 remove_diacritics 2",
+        }
+    }
+}
+
+impl FromStr for SearchTokenizer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unicode61" => Ok(Self::Unicode61),
+            "trigram" => Ok(Self::Trigram),
+            "porter" => Ok(Self::Porter),
+            "auto" => Ok(Self::Auto),
+            other => Err(format!("Unknown search tokenizer: {}", other)),
+        }
+    }
+}
+
+/// Make the search index use `tokenizer`, rebuilding it if it uses another.
+///
+/// Runs after migrations; returns whether the index was rebuilt. With
+/// [`SearchTokenizer::Auto`] the tokenizer follows the language most live
+/// notes are written in.
+pub async fn ensure_search_tokenizer(
+    pool: &DatabasePool,
+    tokenizer: SearchTokenizer,
+) -> Result<bool, sqlx::Error> {
+    match pool {
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => {
+            let tokenizer = match tokenizer {
+                SearchTokenizer::Auto => {
+                    let dominant: Option<String> = sqlx::query_scalar(
+                        "SELECT language FROM notes \
+                         WHERE language IS NOT NULL AND deleted_at IS NULL \
+                         GROUP BY language ORDER BY COUNT(*) DESC LIMIT 1",
+                    )
+                    .fetch_optional(pool)
+                    .await?;
+                    let dominant = dominant.and_then(|code| Language::new(&code).ok());
+                    SearchTokenizer::for_language(dominant.as_ref())
+                }
+                tokenizer => tokenizer,
+            };
+            let current: Option<String> =
+                sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE name = 'notes_fts'")
+                    .fetch_optional(pool)
+                    .await?;
+            let expected = format!("tokenize='{}'", tokenizer.definition());
+            if current.is_some_and(|sql| sql.contains(&expected)) {
+                return Ok(false);
+            }
+
+            tracing::info!("Rebuilding search index with the {:?} tokenizer", tokenizer);
+            let mut tx = pool.begin().await?;
+            for (fts, table) in FTS_TABLES {
+                sqlx::query(&format!("DROP TABLE IF EXISTS {}", fts))
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(&format!(
+                    "CREATE VIRTUAL TABLE {fts} USING fts5(title, content, \
+                     content='{table}', content_rowid='rowid', {expected})"
+                ))
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query(&format!("INSERT INTO {fts}({fts}) VALUES('rebuild')"))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+            Ok(true)
+        }
+        #[allow(unreachable_patterns)]
+        _ => Ok(false),
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::note_repository::SqliteNoteRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, Note, NoteRepository, User, UserRepository};
+
+    async fn setup_test_db() -> DatabasePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn save_note(pool: &DatabasePool, content: &str) -> (SqliteNoteRepository, User) {
+        let pool = pool.sqlite_pool().unwrap().clone();
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new(
+            "test|search",
+            Email::try_from("search@example.com").unwrap(),
+        );
+        user_repo.save(&user).await.unwrap();
+
+        let note_repo = SqliteNoteRepository::new(pool);
+        note_repo
+            .save(&Note::new(user.id, None, content))
+            .await
+            .unwrap();
+        (note_repo, user)
+    }
+
+    #[tokio::test]
+    async fn test_switching_tokenizer_rebuilds_once() {
+        let pool = setup_test_db().await;
+
+        // Migrations already set up unicode61
+        assert!(
+            !ensure_search_tokenizer(&pool, SearchTokenizer::Unicode61)
+                .await
+                .unwrap()
+        );
+        assert!(
+            ensure_search_tokenizer(&pool, SearchTokenizer::Trigram)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !ensure_search_tokenizer(&pool, SearchTokenizer::Trigram)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unicode61_folds_diacritics() {
+        let pool = setup_test_db().await;
+        let (note_repo, user) = save_note(&pool, "Meet at the café").await;
+
+        assert_eq!(
+            note_repo
+                .search(user.id, "cafe", false)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_follows_the_dominant_language() {
+        let pool = setup_test_db().await;
+        let (note_repo, user) = save_note(
+            &pool,
+            "We are running late for the meeting, so start without us please.",
+        )
+        .await;
+        assert!(
+            note_repo
+                .search(user.id, "run", false)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(
+            ensure_search_tokenizer(&pool, SearchTokenizer::Auto)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !ensure_search_tokenizer(&pool, SearchTokenizer::Porter)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            note_repo.search(user.id, "run", false).await.unwrap().len(),
+            1
+        );
+        assert_eq!(
+            SearchTokenizer::for_language(Language::new("ja").ok().as_ref()),
+            SearchTokenizer::Trigram
+        );
+        assert_eq!(
+            SearchTokenizer::for_language(None),
+            SearchTokenizer::Unicode61
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trigram_reindexes_existing_notes_for_substrings() {
+        let pool = setup_test_db().await;
+        let (note_repo, user) = save_note(&pool, "東京都の天気予報").await;
+        assert!(
+            note_repo
+                .search(user.id, "天気予", false)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        ensure_search_tokenizer(&pool, SearchTokenizer::Trigram)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            note_repo
+                .search(user.id, "天気予", false)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
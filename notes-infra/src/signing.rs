@@ -0,0 +1,63 @@
+//! HMAC adapter for the `Signer` port
+//!
+//! Signatures are hex-encoded HMAC-SHA256 digests, so any instance holding
+//! the same secret can check the values another one signed.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use notes_domain::Signer;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct HmacSigner {
+    key: Vec<u8>,
+}
+
+impl HmacSigner {
+    /// Sign with `secret`, or a random key when `None`
+    ///
+    /// A random key invalidates handed out signatures on restart and is not
+    /// shared between instances.
+    pub fn new(secret: Option<&str>) -> Self {
+        let key = match secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => rand::random::<[u8; 32]>().to_vec(),
+        };
+        Self { key }
+    }
+
+    fn mac(&self, value: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(value.as_bytes());
+        mac
+    }
+}
+
+impl Signer for HmacSigner {
+    fn sign(&self, value: &str) -> String {
+        hex::encode(self.mac(value).finalize().into_bytes())
+    }
+
+    fn verify(&self, value: &str, signature: &str) -> bool {
+        hex::decode(signature)
+            .is_ok_and(|signature| self.mac(value).verify_slice(&signature).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signatures_verify_only_with_the_same_secret() {
+        let signer = HmacSigner::new(Some("secret"));
+        let signature = signer.sign("https://a.example/a.png");
+
+        assert!(signer.verify("https://a.example/a.png", &signature));
+        assert!(!signer.verify("https://a.example/b.png", &signature));
+        assert!(!signer.verify("https://a.example/a.png", "not hex"));
+        assert!(!HmacSigner::new(Some("other")).verify("https://a.example/a.png", &signature));
+        assert!(!HmacSigner::new(None).verify("https://a.example/a.png", &signature));
+    }
+}
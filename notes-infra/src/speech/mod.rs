@@ -0,0 +1,6 @@
+//! Speech synthesis adapters
+//!
+//! This module provides implementations of the `SpeechSynthesizer` port.
+
+pub mod openai;
+pub mod piper;
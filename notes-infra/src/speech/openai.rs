@@ -0,0 +1,168 @@
+//! Adapter for OpenAI-compatible speech APIs
+//!
+//! Posts text to `/v1/audio/speech`, as served by OpenAI and by local
+//! servers such as openedai-speech or Kokoro-FastAPI. The endpoint takes a
+//! few thousand characters at a time, so longer notes are read in chunks
+//! ending on sentences, and the encoded chunks are joined: MP3 frames follow
+//! one another, and Ogg streams may be chained.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use notes_domain::language::Language;
+use notes_domain::speech::AudioFormat;
+use notes_domain::{DomainError, DomainResult, SpeechSynthesizer};
+
+/// Characters the endpoint reads per request
+const MAX_INPUT_LENGTH: usize = 4096;
+
+#[derive(Debug, Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: &'a str,
+}
+
+/// Reads text with a voice behind an OpenAI-compatible API
+pub struct OpenAiSpeechSynthesizer {
+    client: reqwest::Client,
+    speech_url: String,
+    model: String,
+    voice: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiSpeechSynthesizer {
+    /// `base_url` is the API root, such as `https://api.openai.com/v1`
+    pub fn new(
+        base_url: &str,
+        model: String,
+        voice: String,
+        api_key: Option<String>,
+        timeout: Duration,
+    ) -> DomainResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+            })?;
+
+        Ok(Self {
+            client,
+            speech_url: format!("{}/audio/speech", base_url.trim_end_matches('/')),
+            model,
+            voice,
+            api_key: api_key.filter(|key| !key.is_empty()),
+        })
+    }
+
+    async fn speak(&self, input: &str, format: AudioFormat) -> DomainResult<Vec<u8>> {
+        let request = SpeechRequest {
+            model: &self.model,
+            input,
+            voice: &self.voice,
+            response_format: match format {
+                AudioFormat::Mp3 => "mp3",
+                AudioFormat::Ogg => "opus",
+            },
+        };
+
+        let mut builder = self.client.post(&self.speech_url).json(&request);
+        if let Some(ref api_key) = self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let audio = builder
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Speech synthesis failed: {}", e))
+            })?
+            .bytes()
+            .await
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Invalid speech response: {}", e))
+            })?;
+
+        Ok(audio.to_vec())
+    }
+}
+
+/// `text` in pieces of at most `max` characters, split after sentences
+/// where they fit and between words where a sentence alone is too long
+fn chunks(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    let mut length = 0;
+    let mut push = |piece: &str, chunk: &mut String, length: &mut usize| {
+        let piece_length = piece.chars().count();
+        if *length + piece_length > max && !chunk.trim().is_empty() {
+            chunks.push(chunk.trim().to_string());
+            chunk.clear();
+            *length = 0;
+        }
+        chunk.push_str(piece);
+        *length += piece_length;
+    };
+
+    for sentence in text.split_inclusive(['.', '!', '?', '\n']) {
+        if sentence.chars().count() <= max {
+            push(sentence, &mut chunk, &mut length);
+            continue;
+        }
+        for word in sentence.split_inclusive(char::is_whitespace) {
+            // Words are never this long in prose; cut them anyway
+            let word: String = word.chars().take(max).collect();
+            push(&word, &mut chunk, &mut length);
+        }
+    }
+    if !chunk.trim().is_empty() {
+        chunks.push(chunk.trim().to_string());
+    }
+    chunks
+}
+
+#[async_trait]
+impl SpeechSynthesizer for OpenAiSpeechSynthesizer {
+    async fn synthesize(
+        &self,
+        text: &str,
+        _language: Option<&Language>,
+        format: AudioFormat,
+    ) -> DomainResult<Vec<u8>> {
+        // Voices of these APIs read whatever language they are given
+        let mut audio = Vec::new();
+        for chunk in chunks(text, MAX_INPUT_LENGTH) {
+            audio.extend(self.speak(&chunk, format).await?);
+        }
+        Ok(audio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_end_on_sentences() {
+        let text = "First sentence here. Second one! Third?";
+        assert_eq!(
+            chunks(text, 25),
+            vec!["First sentence here.", "Second one! Third?"]
+        );
+        assert_eq!(chunks(text, 100), vec![text]);
+    }
+
+    #[test]
+    fn test_long_sentences_are_split_between_words() {
+        let text = "one two three four five six seven eight nine ten";
+        let pieces = chunks(text, 12);
+
+        assert!(pieces.iter().all(|p| p.chars().count() <= 12));
+        assert_eq!(pieces.join(" "), text);
+    }
+}
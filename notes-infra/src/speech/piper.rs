@@ -0,0 +1,171 @@
+//! Local Piper speech adapter
+//!
+//! Reads text with the `piper` binary and an ONNX voice, then encodes the
+//! WAV it writes with `ffmpeg`. Nothing leaves the instance.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use notes_domain::language::Language;
+use notes_domain::speech::AudioFormat;
+use notes_domain::{DomainError, DomainResult, SpeechSynthesizer};
+
+pub struct PiperSpeechSynthesizer {
+    binary: String,
+    ffmpeg: String,
+    default_voice: PathBuf,
+    /// Voices by ISO 639-1 code, read instead of the default voice
+    voices: HashMap<String, PathBuf>,
+}
+
+impl PiperSpeechSynthesizer {
+    pub fn new(
+        binary: impl Into<String>,
+        ffmpeg: impl Into<String>,
+        default_voice: PathBuf,
+        voices: HashMap<String, PathBuf>,
+    ) -> Self {
+        Self {
+            binary: binary.into(),
+            ffmpeg: ffmpeg.into(),
+            default_voice,
+            voices,
+        }
+    }
+
+    fn voice(&self, language: Option<&Language>) -> &Path {
+        language
+            .and_then(|l| self.voices.get(l.code()))
+            .unwrap_or(&self.default_voice)
+    }
+
+    async fn speak_to_wav(&self, text: &str, voice: &Path, output: &Path) -> std::io::Result<()> {
+        let mut child = Command::new(&self.binary)
+            .arg("--model")
+            .arg(voice)
+            .arg("--output_file")
+            .arg(output)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| std::io::Error::other("piper has no input"))?;
+        // Written while piper runs, so its log output cannot fill up and
+        // stall both sides; piper finishes once its input is closed
+        let write = async move {
+            let written = stdin.write_all(text.as_bytes()).await;
+            drop(stdin);
+            written
+        };
+        let (written, result) = tokio::join!(write, child.wait_with_output());
+
+        let result = result?;
+        if !result.status.success() {
+            return Err(std::io::Error::other(
+                String::from_utf8_lossy(&result.stderr).to_string(),
+            ));
+        }
+        written
+    }
+
+    async fn encode(
+        &self,
+        input: &Path,
+        output: &Path,
+        format: AudioFormat,
+    ) -> std::io::Result<()> {
+        let codec: &[&str] = match format {
+            AudioFormat::Mp3 => &["-codec:a", "libmp3lame", "-q:a", "4"],
+            AudioFormat::Ogg => &["-codec:a", "libopus", "-b:a", "48k"],
+        };
+        let result = Command::new(&self.ffmpeg)
+            .args(["-y", "-loglevel", "error", "-i"])
+            .arg(input)
+            .args(codec)
+            .arg(output)
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        if !result.status.success() {
+            return Err(std::io::Error::other(
+                String::from_utf8_lossy(&result.stderr).to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SpeechSynthesizer for PiperSpeechSynthesizer {
+    async fn synthesize(
+        &self,
+        text: &str,
+        language: Option<&Language>,
+        format: AudioFormat,
+    ) -> DomainResult<Vec<u8>> {
+        let work_dir: PathBuf =
+            std::env::temp_dir().join(format!("k-notes-speech-{}", Uuid::new_v4()));
+        let wav = work_dir.join("speech.wav");
+        let output = work_dir.join(format!("speech.{}", format.extension()));
+
+        let result = async {
+            tokio::fs::create_dir_all(&work_dir).await?;
+            self.speak_to_wav(text, self.voice(language), &wav).await?;
+            self.encode(&wav, &output, format).await?;
+            tokio::fs::read(&output).await
+        }
+        .await;
+
+        // Always clean up the scratch directory, even if synthesis failed
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+
+        result.map_err(|e| {
+            DomainError::InfrastructureError(format!("Speech synthesis failed: {}", e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_follows_the_language() {
+        let synthesizer = PiperSpeechSynthesizer::new(
+            "piper",
+            "ffmpeg",
+            PathBuf::from("/voices/en_US-lessac-medium.onnx"),
+            HashMap::from([(
+                "pl".to_string(),
+                PathBuf::from("/voices/pl_PL-gosia-medium.onnx"),
+            )]),
+        );
+
+        let polish = Language::new("pl").unwrap();
+        let german = Language::new("de").unwrap();
+        assert_eq!(
+            synthesizer.voice(Some(&polish)),
+            Path::new("/voices/pl_PL-gosia-medium.onnx")
+        );
+        assert_eq!(
+            synthesizer.voice(Some(&german)),
+            Path::new("/voices/en_US-lessac-medium.onnx")
+        );
+        assert_eq!(
+            synthesizer.voice(None),
+            Path::new("/voices/en_US-lessac-medium.onnx")
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! SQLite implementation of TagAliasRepository
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::db::{decode_error, map_sqlx_error, write};
+use notes_domain::tag_aliases::TagAlias;
+use notes_domain::{DomainResult, TagAliasRepository, TagName};
+
+/// SQLite adapter for TagAliasRepository
+pub struct SqliteTagAliasRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteTagAliasRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct TagAliasRow {
+    user_id: String,
+    alias: String,
+    tag_id: String,
+    created_at: String,
+}
+
+fn parse_uuid(s: &str) -> DomainResult<Uuid> {
+    Uuid::parse_str(s).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))
+}
+
+impl TagAliasRow {
+    fn try_into_alias(self) -> DomainResult<TagAlias> {
+        Ok(TagAlias {
+            user_id: parse_uuid(&self.user_id)?,
+            alias: TagName::try_from(self.alias)
+                .map_err(|e| decode_error(format!("Invalid tag alias in DB: {}", e)))?,
+            tag_id: parse_uuid(&self.tag_id)?,
+            created_at: DateTime::parse_from_rfc3339(&self.created_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?,
+        })
+    }
+}
+
+#[async_trait]
+impl TagAliasRepository for SqliteTagAliasRepository {
+    async fn save(&self, alias: &TagAlias) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query(
+                r#"
+                INSERT INTO tag_aliases (user_id, alias, tag_id, created_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(user_id, alias) DO UPDATE SET
+                    tag_id = excluded.tag_id,
+                    created_at = excluded.created_at
+                "#,
+            )
+            .bind(alias.user_id.to_string())
+            .bind(alias.alias.as_ref())
+            .bind(alias.tag_id.to_string())
+            .bind(alias.created_at.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_alias(&self, user_id: Uuid, alias: &str) -> DomainResult<Option<TagAlias>> {
+        let row: Option<TagAliasRow> =
+            sqlx::query_as("SELECT * FROM tag_aliases WHERE user_id = ? AND alias = ?")
+                .bind(user_id.to_string())
+                .bind(alias)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        row.map(TagAliasRow::try_into_alias).transpose()
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<TagAlias>> {
+        let rows: Vec<TagAliasRow> =
+            sqlx::query_as("SELECT * FROM tag_aliases WHERE user_id = ? ORDER BY alias")
+                .bind(user_id.to_string())
+                .fetch_all(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(TagAliasRow::try_into_alias).collect()
+    }
+
+    async fn delete(&self, user_id: Uuid, alias: &str) -> DomainResult<()> {
+        write(move || async move {
+            sqlx::query("DELETE FROM tag_aliases WHERE user_id = ? AND alias = ?")
+                .bind(user_id.to_string())
+                .bind(alias)
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::tag_repository::SqliteTagRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, Tag, TagRepository, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new("test|alias", Email::try_from("alias@example.com").unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_aliases_follow_their_tag() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let tag_repo = SqliteTagRepository::new(pool.clone());
+        let repo = SqliteTagAliasRepository::new(pool);
+
+        let javascript = Tag::new(TagName::try_from("javascript").unwrap(), user.id);
+        let ecmascript = Tag::new(TagName::try_from("ecmascript").unwrap(), user.id);
+        tag_repo.save(&javascript).await.unwrap();
+        tag_repo.save(&ecmascript).await.unwrap();
+
+        let js = TagAlias::new(user.id, TagName::try_from("js").unwrap(), ecmascript.id);
+        repo.save(&js).await.unwrap();
+        let moved = TagAlias {
+            tag_id: javascript.id,
+            ..js.clone()
+        };
+        repo.save(&moved).await.unwrap();
+        assert_eq!(
+            repo.find_by_alias(user.id, "js").await.unwrap(),
+            Some(moved.clone())
+        );
+
+        // Merging a tag carries its aliases along; deleting drops them
+        let es = TagAlias::new(user.id, TagName::try_from("es").unwrap(), ecmascript.id);
+        repo.save(&es).await.unwrap();
+        tag_repo.merge(ecmascript.id, javascript.id).await.unwrap();
+        let aliases = repo.find_by_user(user.id).await.unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert!(aliases.iter().all(|a| a.tag_id == javascript.id));
+
+        tag_repo.delete(javascript.id).await.unwrap();
+        assert!(repo.find_by_user(user.id).await.unwrap().is_empty());
+
+        repo.delete(user.id, "missing").await.unwrap();
+    }
+}
@@ -1,11 +1,16 @@
 //! SQLite implementation of TagRepository
 
 use async_trait::async_trait;
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
+use crate::db::{decode_error, map_sqlx_error, prefix_upper_bound, write};
 use notes_domain::{DomainError, DomainResult, Tag, TagName, TagRepository};
 
+/// Condition selecting the tags of user `?1` that no note uses
+const UNUSED_TAGS: &str = "user_id = ?1 \
+     AND NOT EXISTS (SELECT 1 FROM note_tags WHERE note_tags.tag_id = tags.id)";
+
 /// SQLite adapter for TagRepository
 pub struct SqliteTagRepository {
     pool: SqlitePool,
@@ -22,46 +27,118 @@ struct TagRow {
     id: String,
     name: String,
     user_id: String,
+    is_pinned: i32,
+    sort_order: Option<i32>,
 }
 
 impl TryFrom<TagRow> for Tag {
     type Error = DomainError;
 
     fn try_from(row: TagRow) -> Result<Self, Self::Error> {
-        let id = Uuid::parse_str(&row.id)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid UUID: {}", e)))?;
+        let id =
+            Uuid::parse_str(&row.id).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
         let user_id = Uuid::parse_str(&row.user_id)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid UUID: {}", e)))?;
+            .map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
 
         // Parse TagName from stored string - was validated when originally stored
         let name = TagName::try_from(row.name)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid tag name in DB: {}", e)))?;
+            .map_err(|e| decode_error(format!("Invalid tag name in DB: {}", e)))?;
+
+        Ok(Tag {
+            is_pinned: row.is_pinned != 0,
+            sort_order: row.sort_order,
+            ..Tag::with_id(id, name, user_id)
+        })
+    }
+}
+
+/// Replace all tag associations of a note; shared with the unit of work
+pub(crate) async fn replace_note_tags(
+    conn: &mut SqliteConnection,
+    note_id: Uuid,
+    tag_ids: &[Uuid],
+) -> DomainResult<()> {
+    let note_id_str = note_id.to_string();
+
+    sqlx::query("DELETE FROM note_tags WHERE note_id = ?")
+        .bind(&note_id_str)
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
 
-        Ok(Tag::with_id(id, name, user_id))
+    if tag_ids.is_empty() {
+        return Ok(());
     }
+
+    let mut query_builder: QueryBuilder<Sqlite> =
+        QueryBuilder::new("INSERT OR IGNORE INTO note_tags (note_id, tag_id) ");
+    query_builder.push_values(tag_ids, |mut row, tag_id| {
+        row.push_bind(note_id_str.clone())
+            .push_bind(tag_id.to_string());
+    });
+
+    query_builder
+        .build()
+        .execute(&mut *conn)
+        .await
+        .map_err(map_sqlx_error)?;
+
+    Ok(())
 }
 
 #[async_trait]
 impl TagRepository for SqliteTagRepository {
     async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<Tag>> {
         let id_str = id.to_string();
-        let row: Option<TagRow> = sqlx::query_as("SELECT id, name, user_id FROM tags WHERE id = ?")
-            .bind(&id_str)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        let row: Option<TagRow> = sqlx::query_as(
+            "SELECT id, name, user_id, is_pinned, sort_order FROM tags WHERE id = ?",
+        )
+        .bind(&id_str)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
 
         row.map(Tag::try_from).transpose()
     }
 
     async fn find_by_user(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
         let user_id_str = user_id.to_string();
-        let rows: Vec<TagRow> =
-            sqlx::query_as("SELECT id, name, user_id FROM tags WHERE user_id = ? ORDER BY name")
-                .bind(&user_id_str)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        let rows: Vec<TagRow> = sqlx::query_as(
+            r#"
+                SELECT id, name, user_id, is_pinned, sort_order FROM tags
+                WHERE user_id = ?
+                ORDER BY is_pinned DESC, sort_order IS NULL, sort_order, name
+                "#,
+        )
+        .bind(&user_id_str)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(Tag::try_from).collect()
+    }
+
+    async fn find_by_prefix(
+        &self,
+        user_id: Uuid,
+        prefix: &str,
+        limit: usize,
+    ) -> DomainResult<Vec<Tag>> {
+        let rows: Vec<TagRow> = sqlx::query_as(
+            r#"
+            SELECT id, name, user_id, is_pinned, sort_order FROM tags
+            WHERE user_id = ? AND name >= ? AND name < ?
+            ORDER BY name
+            LIMIT ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(prefix)
+        .bind(prefix_upper_bound(prefix))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
 
         rows.into_iter().map(Tag::try_from).collect()
     }
@@ -69,81 +146,122 @@ impl TagRepository for SqliteTagRepository {
     async fn find_by_name(&self, user_id: Uuid, name: &str) -> DomainResult<Option<Tag>> {
         let user_id_str = user_id.to_string();
         let row: Option<TagRow> =
-            sqlx::query_as("SELECT id, name, user_id FROM tags WHERE user_id = ? AND name = ?")
+            sqlx::query_as("SELECT id, name, user_id, is_pinned, sort_order FROM tags WHERE user_id = ? AND name = ?")
                 .bind(&user_id_str)
                 .bind(name)
                 .fetch_optional(&self.pool)
                 .await
-                .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+                .map_err(map_sqlx_error)?;
 
         row.map(Tag::try_from).transpose()
     }
 
     async fn save(&self, tag: &Tag) -> DomainResult<()> {
-        let id = tag.id.to_string();
-        let user_id = tag.user_id.to_string();
+        write(move || async move {
+            let id = tag.id.to_string();
+            let user_id = tag.user_id.to_string();
+
+            sqlx::query(
+                r#"
+                INSERT INTO tags (id, name, user_id, is_pinned, sort_order)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    is_pinned = excluded.is_pinned,
+                    sort_order = excluded.sort_order
+                "#,
+            )
+            .bind(&id)
+            .bind(tag.name.as_ref()) // Use .as_ref() to get the inner &str
+            .bind(&user_id)
+            .bind(tag.is_pinned as i32)
+            .bind(tag.sort_order)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO tags (id, name, user_id)
-            VALUES (?, ?, ?)
-            ON CONFLICT(id) DO UPDATE SET name = excluded.name
-            "#,
-        )
-        .bind(&id)
-        .bind(tag.name.as_ref()) // Use .as_ref() to get the inner &str
-        .bind(&user_id)
-        .execute(&self.pool)
+            Ok(())
+        })
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
-
-        Ok(())
     }
 
     async fn delete(&self, id: Uuid) -> DomainResult<()> {
-        let id_str = id.to_string();
-        sqlx::query("DELETE FROM tags WHERE id = ?")
-            .bind(&id_str)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        write(move || async move {
+            sqlx::query("DELETE FROM tags WHERE id = ?")
+                .bind(id.to_string())
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     async fn add_to_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
-        let tag_id_str = tag_id.to_string();
-        let note_id_str = note_id.to_string();
+        write(move || async move {
+            let tag_id_str = tag_id.to_string();
+            let note_id_str = note_id.to_string();
+
+            sqlx::query("INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?, ?)")
+                .bind(&note_id_str)
+                .bind(&tag_id_str)
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
 
-        sqlx::query("INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?, ?)")
-            .bind(&note_id_str)
-            .bind(&tag_id_str)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn add_many_to_note(&self, tag_ids: &[Uuid], note_id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            if tag_ids.is_empty() {
+                return Ok(());
+            }
+
+            let note_id_str = note_id.to_string();
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("INSERT OR IGNORE INTO note_tags (note_id, tag_id) ");
+            query_builder.push_values(tag_ids, |mut row, tag_id| {
+                row.push_bind(note_id_str.clone())
+                    .push_bind(tag_id.to_string());
+            });
+
+            query_builder
+                .build()
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     async fn remove_from_note(&self, tag_id: Uuid, note_id: Uuid) -> DomainResult<()> {
-        let tag_id_str = tag_id.to_string();
-        let note_id_str = note_id.to_string();
-
-        sqlx::query("DELETE FROM note_tags WHERE note_id = ? AND tag_id = ?")
-            .bind(&note_id_str)
-            .bind(&tag_id_str)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        write(move || async move {
+            let tag_id_str = tag_id.to_string();
+            let note_id_str = note_id.to_string();
+
+            sqlx::query("DELETE FROM note_tags WHERE note_id = ? AND tag_id = ?")
+                .bind(&note_id_str)
+                .bind(&tag_id_str)
+                .execute(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     async fn find_by_note(&self, note_id: Uuid) -> DomainResult<Vec<Tag>> {
         let note_id_str = note_id.to_string();
         let rows: Vec<TagRow> = sqlx::query_as(
             r#"
-            SELECT t.id, t.name, t.user_id
+            SELECT t.id, t.name, t.user_id, t.is_pinned, t.sort_order
             FROM tags t
             INNER JOIN note_tags nt ON t.id = nt.tag_id
             WHERE nt.note_id = ?
@@ -153,10 +271,101 @@ impl TagRepository for SqliteTagRepository {
         .bind(&note_id_str)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
+
+        rows.into_iter().map(Tag::try_from).collect()
+    }
+
+    async fn merge(&self, source_id: Uuid, target_id: Uuid) -> DomainResult<()> {
+        write(move || async move {
+            let source = source_id.to_string();
+            let target = target_id.to_string();
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            for statement in [
+                // Notes already tagged with the target keep a single association
+                "INSERT OR IGNORE INTO note_tags (note_id, tag_id) \
+                 SELECT note_id, ?2 FROM note_tags WHERE tag_id = ?1",
+                "DELETE FROM note_tags WHERE tag_id = ?1",
+                "UPDATE tag_aliases SET tag_id = ?2 WHERE tag_id = ?1",
+                "DELETE FROM tags WHERE id = ?1",
+            ] {
+                sqlx::query(statement)
+                    .bind(&source)
+                    .bind(&target)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(map_sqlx_error)?;
+            }
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        let rows: Vec<TagRow> = sqlx::query_as(&format!(
+            "SELECT id, name, user_id, is_pinned, sort_order FROM tags WHERE {} ORDER BY name",
+            UNUSED_TAGS
+        ))
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
 
         rows.into_iter().map(Tag::try_from).collect()
     }
+
+    async fn delete_unused(&self, user_id: Uuid) -> DomainResult<Vec<Tag>> {
+        write(move || async move {
+            let user_id_str = user_id.to_string();
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            let rows: Vec<TagRow> = sqlx::query_as(&format!(
+                "SELECT id, name, user_id, is_pinned, sort_order FROM tags WHERE {} ORDER BY name",
+                UNUSED_TAGS
+            ))
+            .bind(&user_id_str)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+            sqlx::query(&format!("DELETE FROM tags WHERE {}", UNUSED_TAGS))
+                .bind(&user_id_str)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_sqlx_error)?;
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            rows.into_iter().map(Tag::try_from).collect()
+        })
+        .await
+    }
+
+    async fn reorder(&self, user_id: Uuid, tag_ids: &[Uuid]) -> DomainResult<()> {
+        write(move || async move {
+            let user_id_str = user_id.to_string();
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            for (position, id) in tag_ids.iter().enumerate() {
+                sqlx::query("UPDATE tags SET sort_order = ? WHERE id = ? AND user_id = ?")
+                    .bind(position as i32)
+                    .bind(id.to_string())
+                    .bind(&user_id_str)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(map_sqlx_error)?;
+            }
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+
+            Ok(())
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -212,6 +421,22 @@ mod tests {
         assert_eq!(found.unwrap().id, tag.id);
     }
 
+    #[tokio::test]
+    async fn test_duplicate_name_is_conflict() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteTagRepository::new(pool);
+
+        let name = TagName::try_from("work").unwrap();
+        repo.save(&Tag::new(name.clone(), user.id)).await.unwrap();
+
+        let err = repo.save(&Tag::new(name, user.id)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            DomainError::RepositoryError(notes_domain::RepositoryError::Conflict(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_find_by_user() {
         let pool = setup_test_db().await;
@@ -229,4 +454,116 @@ mod tests {
         assert_eq!(tags[0].name_str(), "alpha");
         assert_eq!(tags[1].name_str(), "beta");
     }
+
+    #[tokio::test]
+    async fn test_add_many_to_note() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = crate::note_repository::SqliteNoteRepository::new(pool.clone());
+        let repo = SqliteTagRepository::new(pool);
+
+        let note = notes_domain::Note::new(user.id, None, "content");
+        notes_domain::NoteRepository::save(&note_repo, &note)
+            .await
+            .unwrap();
+
+        let work = Tag::new(TagName::try_from("work").unwrap(), user.id);
+        let home = Tag::new(TagName::try_from("home").unwrap(), user.id);
+        repo.save(&work).await.unwrap();
+        repo.save(&home).await.unwrap();
+
+        repo.add_many_to_note(&[work.id, home.id], note.id)
+            .await
+            .unwrap();
+        // Existing associations are ignored
+        repo.add_many_to_note(&[work.id], note.id).await.unwrap();
+
+        let tags = repo.find_by_note(note.id).await.unwrap();
+        assert_eq!(tags.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_moves_notes_to_the_target() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = crate::note_repository::SqliteNoteRepository::new(pool.clone());
+        let repo = SqliteTagRepository::new(pool);
+
+        let js = Tag::new(TagName::try_from("js").unwrap(), user.id);
+        let javascript = Tag::new(TagName::try_from("javascript").unwrap(), user.id);
+        repo.save(&js).await.unwrap();
+        repo.save(&javascript).await.unwrap();
+        let (both, only_js) = (
+            notes_domain::Note::new(user.id, None, "both"),
+            notes_domain::Note::new(user.id, None, "only js"),
+        );
+        for note in [&both, &only_js] {
+            notes_domain::NoteRepository::save(&note_repo, note)
+                .await
+                .unwrap();
+            repo.add_to_note(js.id, note.id).await.unwrap();
+        }
+        repo.add_to_note(javascript.id, both.id).await.unwrap();
+
+        repo.merge(js.id, javascript.id).await.unwrap();
+
+        assert!(repo.find_by_id(js.id).await.unwrap().is_none());
+        for note in [&both, &only_js] {
+            assert_eq!(
+                repo.find_by_note(note.id).await.unwrap(),
+                vec![javascript.clone()]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_unused_keeps_tags_on_notes() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = crate::note_repository::SqliteNoteRepository::new(pool.clone());
+        let repo = SqliteTagRepository::new(pool);
+
+        let used = Tag::new(TagName::try_from("work").unwrap(), user.id);
+        let unused = Tag::new(TagName::try_from("old").unwrap(), user.id);
+        repo.save(&used).await.unwrap();
+        repo.save(&unused).await.unwrap();
+        let note = notes_domain::Note::new(user.id, None, "content");
+        notes_domain::NoteRepository::save(&note_repo, &note)
+            .await
+            .unwrap();
+        repo.add_to_note(used.id, note.id).await.unwrap();
+
+        assert_eq!(
+            repo.find_unused(user.id).await.unwrap(),
+            vec![unused.clone()]
+        );
+        assert_eq!(repo.delete_unused(user.id).await.unwrap(), vec![unused]);
+        assert_eq!(repo.find_by_user(user.id).await.unwrap(), vec![used]);
+        assert!(repo.delete_unused(user.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_by_user_lists_pinned_then_reordered_tags() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let repo = SqliteTagRepository::new(pool);
+
+        let [alpha, beta, gamma, delta] = ["alpha", "beta", "gamma", "delta"]
+            .map(|name| Tag::new(TagName::try_from(name).unwrap(), user.id));
+        for tag in [&alpha, &beta, &gamma, &delta] {
+            repo.save(tag).await.unwrap();
+        }
+        repo.reorder(user.id, &[gamma.id, beta.id]).await.unwrap();
+        let pinned = Tag {
+            is_pinned: true,
+            ..repo.find_by_id(delta.id).await.unwrap().unwrap()
+        };
+        repo.save(&pinned).await.unwrap();
+
+        let tags = repo.find_by_user(user.id).await.unwrap();
+        let names: Vec<&str> = tags.iter().map(Tag::name_str).collect();
+        assert_eq!(names, vec!["delta", "gamma", "beta", "alpha"]);
+        assert!(tags[0].is_pinned);
+        assert_eq!(tags[1].sort_order, Some(0));
+    }
 }
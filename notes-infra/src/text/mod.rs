@@ -0,0 +1,5 @@
+//! Text generation adapters
+//!
+//! This module provides implementations of the `TextGenerator` port.
+
+pub mod openai;
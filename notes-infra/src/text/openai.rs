@@ -0,0 +1,161 @@
+//! Adapter for OpenAI-compatible chat completion APIs
+//!
+//! Besides OpenAI itself, Ollama, llama.cpp and vLLM serve the same
+//! `/v1/chat/completions` endpoint, so a local model works as well.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use notes_domain::{DomainError, DomainResult, TextGenerator};
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 1],
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatAnswer,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatAnswer {
+    #[serde(default)]
+    content: String,
+}
+
+/// Generates text with a chat model behind an OpenAI-compatible API
+pub struct OpenAiTextGenerator {
+    client: reqwest::Client,
+    completions_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiTextGenerator {
+    /// `base_url` is the API root, such as `https://api.openai.com/v1` or
+    /// `http://localhost:11434/v1` for Ollama
+    pub fn new(
+        base_url: &str,
+        model: String,
+        api_key: Option<String>,
+        timeout: Duration,
+    ) -> DomainResult<Self> {
+        if model.is_empty() {
+            return Err(DomainError::InfrastructureError(
+                "Text generation needs a model".to_string(),
+            ));
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+            })?;
+
+        Ok(Self {
+            client,
+            completions_url: format!("{}/chat/completions", base_url.trim_end_matches('/')),
+            model,
+            api_key: api_key.filter(|key| !key.is_empty()),
+        })
+    }
+}
+
+/// The answer of the first choice in a chat completion
+fn first_answer(response: ChatResponse) -> DomainResult<String> {
+    response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content)
+        .ok_or_else(|| {
+            DomainError::InfrastructureError("Text generator returned no answer".to_string())
+        })
+}
+
+#[async_trait]
+impl TextGenerator for OpenAiTextGenerator {
+    async fn generate(&self, prompt: &str) -> DomainResult<String> {
+        let request = ChatRequest {
+            model: &self.model,
+            messages: [ChatMessage {
+                role: "user",
+                content: prompt,
+            }],
+            stream: false,
+        };
+
+        let mut builder = self.client.post(&self.completions_url).json(&request);
+        if let Some(ref api_key) = self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let response: ChatResponse = builder
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Text generation failed: {}", e))
+            })?
+            .json()
+            .await
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Invalid text generator response: {}", e))
+            })?;
+
+        first_answer(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_answer_is_read_from_the_completion() {
+        let response: ChatResponse = serde_json::from_str(
+            r#"{"id":"x","choices":[{"index":0,"message":{"role":"assistant","content":"Trip plan"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(first_answer(response).unwrap(), "Trip plan");
+
+        let empty: ChatResponse = serde_json::from_str(r#"{"choices":[]}"#).unwrap();
+        assert!(first_answer(empty).is_err());
+    }
+
+    #[test]
+    fn test_completions_url_is_under_the_api_root() {
+        let generator = OpenAiTextGenerator::new(
+            "http://localhost:11434/v1/",
+            "llama3.2".to_string(),
+            Some(String::new()),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(
+            generator.completions_url,
+            "http://localhost:11434/v1/chat/completions"
+        );
+        assert!(generator.api_key.is_none());
+        assert!(
+            OpenAiTextGenerator::new("http://x", String::new(), None, Duration::from_secs(5))
+                .is_err()
+        );
+    }
+}
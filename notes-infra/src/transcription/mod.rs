@@ -0,0 +1,6 @@
+//! Transcription adapters
+//!
+//! This module provides implementations of the `Transcriber` port.
+
+pub mod openai;
+pub mod whisper;
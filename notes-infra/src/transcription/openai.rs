@@ -0,0 +1,115 @@
+//! Adapter for OpenAI-compatible transcription APIs
+//!
+//! Posts audio to `/v1/audio/transcriptions`, as served by OpenAI and by
+//! local servers such as faster-whisper-server or whisper.cpp's own server.
+//! The endpoint tells formats apart by file name, so uploads are named
+//! after their content type.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+
+use notes_domain::language::Language;
+use notes_domain::{DomainError, DomainResult, Transcriber};
+
+#[derive(Debug, Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+/// Transcribes audio with a model behind an OpenAI-compatible API
+pub struct OpenAiTranscriber {
+    client: reqwest::Client,
+    transcriptions_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiTranscriber {
+    /// `base_url` is the API root, such as `https://api.openai.com/v1`
+    pub fn new(
+        base_url: &str,
+        model: String,
+        api_key: Option<String>,
+        timeout: Duration,
+    ) -> DomainResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+            })?;
+
+        Ok(Self {
+            client,
+            transcriptions_url: format!("{}/audio/transcriptions", base_url.trim_end_matches('/')),
+            model,
+            api_key: api_key.filter(|key| !key.is_empty()),
+        })
+    }
+}
+
+/// File name extension of audio encoded as `content_type`
+fn extension(content_type: &str) -> &'static str {
+    match content_type {
+        "audio/flac" => "flac",
+        "audio/mp4" | "audio/x-m4a" => "m4a",
+        "audio/ogg" => "ogg",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/webm" => "webm",
+        _ => "mp3",
+    }
+}
+
+#[async_trait]
+impl Transcriber for OpenAiTranscriber {
+    async fn transcribe(
+        &self,
+        audio: &[u8],
+        content_type: &str,
+        language: Option<&Language>,
+    ) -> DomainResult<String> {
+        let file = Part::bytes(audio.to_vec())
+            .file_name(format!("audio.{}", extension(content_type)))
+            .mime_str(content_type)
+            .map_err(|e| DomainError::InfrastructureError(format!("Invalid audio type: {}", e)))?;
+        let mut form = Form::new()
+            .part("file", file)
+            .text("model", self.model.clone())
+            .text("response_format", "json");
+        if let Some(language) = language {
+            form = form.text("language", language.code().to_string());
+        }
+
+        let mut builder = self.client.post(&self.transcriptions_url).multipart(form);
+        if let Some(ref api_key) = self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let response: TranscriptionResponse = builder
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| DomainError::InfrastructureError(format!("Transcription failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Invalid transcription response: {}", e))
+            })?;
+
+        Ok(response.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uploads_are_named_after_their_content_type() {
+        assert_eq!(extension("audio/x-m4a"), "m4a");
+        assert_eq!(extension("audio/webm"), "webm");
+        assert_eq!(extension("audio/mpeg"), "mp3");
+    }
+}
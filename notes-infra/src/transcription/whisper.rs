@@ -0,0 +1,127 @@
+//! Local whisper.cpp transcription adapter
+//!
+//! Decodes uploads into the 16 kHz mono WAV whisper.cpp reads with
+//! `ffmpeg`, then transcribes them with the `whisper-cli` binary and a GGML
+//! model. Nothing leaves the instance.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use notes_domain::language::Language;
+use notes_domain::{DomainError, DomainResult, Transcriber};
+
+pub struct WhisperCppTranscriber {
+    binary: String,
+    ffmpeg: String,
+    model: PathBuf,
+}
+
+impl WhisperCppTranscriber {
+    pub fn new(binary: impl Into<String>, ffmpeg: impl Into<String>, model: PathBuf) -> Self {
+        Self {
+            binary: binary.into(),
+            ffmpeg: ffmpeg.into(),
+            model,
+        }
+    }
+
+    async fn decode(&self, input: &Path, output: &Path) -> std::io::Result<()> {
+        let result = Command::new(&self.ffmpeg)
+            .args(["-y", "-loglevel", "error", "-i"])
+            .arg(input)
+            .args(["-ar", "16000", "-ac", "1", "-codec:a", "pcm_s16le"])
+            .arg(output)
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        if !result.status.success() {
+            return Err(std::io::Error::other(
+                String::from_utf8_lossy(&result.stderr).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Transcribe `wav` into `<output>.txt`
+    async fn transcribe_wav(
+        &self,
+        wav: &Path,
+        output: &Path,
+        language: Option<&Language>,
+    ) -> std::io::Result<()> {
+        let result = Command::new(&self.binary)
+            .arg("--model")
+            .arg(&self.model)
+            .arg("--file")
+            .arg(wav)
+            .args(["--language", language_arg(language)])
+            .args(["--no-prints", "--output-txt", "--output-file"])
+            .arg(output)
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        if !result.status.success() {
+            return Err(std::io::Error::other(
+                String::from_utf8_lossy(&result.stderr).to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The `--language` whisper.cpp is given; it detects the language itself
+/// from `auto`
+fn language_arg(language: Option<&Language>) -> &str {
+    language.map_or("auto", |l| l.code())
+}
+
+#[async_trait]
+impl Transcriber for WhisperCppTranscriber {
+    async fn transcribe(
+        &self,
+        audio: &[u8],
+        _content_type: &str,
+        language: Option<&Language>,
+    ) -> DomainResult<String> {
+        // ffmpeg finds the format from the audio itself
+        let work_dir: PathBuf =
+            std::env::temp_dir().join(format!("k-notes-transcription-{}", Uuid::new_v4()));
+        let input = work_dir.join("upload");
+        let wav = work_dir.join("audio.wav");
+        let output = work_dir.join("transcript");
+
+        let result = async {
+            tokio::fs::create_dir_all(&work_dir).await?;
+            tokio::fs::write(&input, audio).await?;
+            self.decode(&input, &wav).await?;
+            self.transcribe_wav(&wav, &output, language).await?;
+            tokio::fs::read_to_string(output.with_extension("txt")).await
+        }
+        .await;
+
+        // Always clean up the scratch directory, even if transcription failed
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+
+        // Segments are written one per line
+        result
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .map_err(|e| DomainError::InfrastructureError(format!("Transcription failed: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_is_detected_without_a_hint() {
+        let polish = Language::new("pl").unwrap();
+        assert_eq!(language_arg(Some(&polish)), "pl");
+        assert_eq!(language_arg(None), "auto");
+    }
+}
@@ -0,0 +1,116 @@
+//! SQLite implementation of UnitOfWork
+
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::{map_sqlx_error, write};
+use crate::note_repository::{insert_version, upsert_note};
+use crate::tag_repository::replace_note_tags;
+use notes_domain::{DomainResult, Note, NoteVersion, UnitOfWork};
+
+/// SQLite adapter for UnitOfWork, backed by a sqlx transaction
+pub struct SqliteUnitOfWork {
+    pool: SqlitePool,
+}
+
+impl SqliteUnitOfWork {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for SqliteUnitOfWork {
+    async fn commit_note(&self, note: &Note, version: Option<&NoteVersion>) -> DomainResult<()> {
+        write(move || async move {
+            // Dropping the transaction on an early return rolls it back
+            let mut tx = self.pool.begin().await.map_err(map_sqlx_error)?;
+
+            upsert_note(&mut *tx, note).await?;
+            let tag_ids: Vec<Uuid> = note.tags.iter().map(|t| t.id).collect();
+            replace_note_tags(&mut tx, note.id, &tag_ids).await?;
+            if let Some(version) = version {
+                insert_version(&mut *tx, version).await?;
+            }
+
+            tx.commit().await.map_err(map_sqlx_error)?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::run_migrations;
+    use crate::note_repository::SqliteNoteRepository;
+    use crate::tag_repository::SqliteTagRepository;
+    use crate::user_repository::SqliteUserRepository;
+    use k_core::db::DatabaseConfig;
+    use notes_domain::{Email, NoteRepository, Tag, TagName, TagRepository, User, UserRepository};
+
+    async fn setup_test_db() -> SqlitePool {
+        let config = DatabaseConfig::in_memory();
+        let pool = k_core::db::connect(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool.sqlite_pool().unwrap().clone()
+    }
+
+    async fn create_test_user(pool: &SqlitePool) -> User {
+        let user_repo = SqliteUserRepository::new(pool.clone());
+        let user = User::new("test|uow", Email::try_from("uow@example.com").unwrap());
+        user_repo.save(&user).await.unwrap();
+        user
+    }
+
+    #[tokio::test]
+    async fn test_commit_note_writes_note_tags_and_version() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let tag_repo = SqliteTagRepository::new(pool.clone());
+        let uow = SqliteUnitOfWork::new(pool);
+
+        let tag = Tag::new(TagName::try_from("work").unwrap(), user.id);
+        tag_repo.save(&tag).await.unwrap();
+
+        let mut note = Note::new(user.id, None, "content");
+        note.tags.push(tag);
+        let version = NoteVersion::new(note.id, None, "old content".to_string());
+        uow.commit_note(&note, Some(&version)).await.unwrap();
+
+        assert!(note_repo.find_by_id(note.id).await.unwrap().is_some());
+        assert_eq!(tag_repo.find_by_note(note.id).await.unwrap().len(), 1);
+        assert_eq!(
+            note_repo
+                .find_versions_by_note_id(note.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Committing without tags drops the association
+        note.tags.clear();
+        uow.commit_note(&note, None).await.unwrap();
+        assert!(tag_repo.find_by_note(note.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_commit_writes_nothing() {
+        let pool = setup_test_db().await;
+        let user = create_test_user(&pool).await;
+        let note_repo = SqliteNoteRepository::new(pool.clone());
+        let uow = SqliteUnitOfWork::new(pool);
+
+        // The tag was never saved, so the association violates its foreign key
+        let mut note = Note::new(user.id, None, "content");
+        note.tags
+            .push(Tag::new(TagName::try_from("missing").unwrap(), user.id));
+
+        assert!(uow.commit_note(&note, None).await.is_err());
+        assert!(note_repo.find_by_id(note.id).await.unwrap().is_none());
+    }
+}
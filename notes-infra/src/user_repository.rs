@@ -5,7 +5,10 @@ use chrono::{DateTime, Utc};
 use sqlx::{FromRow, SqlitePool};
 use uuid::Uuid;
 
-use notes_domain::{DomainError, DomainResult, Email, User, UserRepository};
+use crate::db::{decode_error, map_sqlx_error};
+use notes_domain::{
+    DomainError, DomainResult, Email, EmailChange, User, UserRepository, UserSettings,
+};
 
 /// SQLite adapter for UserRepository
 pub struct SqliteUserRepository {
@@ -25,6 +28,8 @@ struct UserRow {
     subject: String,
     email: String,
     password_hash: Option<String>,
+    display_name: Option<String>,
+    avatar_url: Option<String>,
     created_at: String,
 }
 
@@ -32,8 +37,8 @@ impl TryFrom<UserRow> for User {
     type Error = DomainError;
 
     fn try_from(row: UserRow) -> Result<Self, Self::Error> {
-        let id = Uuid::parse_str(&row.id)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid UUID: {}", e)))?;
+        let id =
+            Uuid::parse_str(&row.id).map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
         let created_at = DateTime::parse_from_rfc3339(&row.created_at)
             .map(|dt| dt.with_timezone(&Utc))
             .or_else(|_| {
@@ -41,19 +46,46 @@ impl TryFrom<UserRow> for User {
                 chrono::NaiveDateTime::parse_from_str(&row.created_at, "%Y-%m-%d %H:%M:%S")
                     .map(|dt| dt.and_utc())
             })
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid datetime: {}", e)))?;
+            .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?;
 
         // Parse email from string - it was validated when originally stored
         let email = Email::try_from(row.email)
-            .map_err(|e| DomainError::RepositoryError(format!("Invalid email in DB: {}", e)))?;
+            .map_err(|e| decode_error(format!("Invalid email in DB: {}", e)))?;
 
-        Ok(User::with_id(
-            id,
-            row.subject,
-            email,
-            row.password_hash,
-            created_at,
-        ))
+        let mut user = User::with_id(id, row.subject, email, row.password_hash, created_at);
+        user.display_name = row.display_name;
+        user.avatar_url = row.avatar_url;
+        Ok(user)
+    }
+}
+
+/// Row type for pending email changes
+#[derive(Debug, FromRow)]
+struct EmailChangeRow {
+    user_id: String,
+    new_email: String,
+    token: String,
+    expires_at: String,
+}
+
+impl TryFrom<EmailChangeRow> for EmailChange {
+    type Error = DomainError;
+
+    fn try_from(row: EmailChangeRow) -> Result<Self, Self::Error> {
+        let user_id = Uuid::parse_str(&row.user_id)
+            .map_err(|e| decode_error(format!("Invalid UUID: {}", e)))?;
+        let new_email = Email::try_from(row.new_email)
+            .map_err(|e| decode_error(format!("Invalid email in DB: {}", e)))?;
+        let expires_at = DateTime::parse_from_rfc3339(&row.expires_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| decode_error(format!("Invalid datetime: {}", e)))?;
+
+        Ok(EmailChange {
+            user_id,
+            new_email,
+            token: row.token,
+            expires_at,
+        })
     }
 }
 
@@ -62,36 +94,36 @@ impl UserRepository for SqliteUserRepository {
     async fn find_by_id(&self, id: Uuid) -> DomainResult<Option<User>> {
         let id_str = id.to_string();
         let row: Option<UserRow> = sqlx::query_as(
-            "SELECT id, subject, email, password_hash, created_at FROM users WHERE id = ?",
+            "SELECT id, subject, email, password_hash, display_name, avatar_url, created_at FROM users WHERE id = ?",
         )
         .bind(&id_str)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         row.map(User::try_from).transpose()
     }
 
     async fn find_by_subject(&self, subject: &str) -> DomainResult<Option<User>> {
         let row: Option<UserRow> = sqlx::query_as(
-            "SELECT id, subject, email, password_hash, created_at FROM users WHERE subject = ?",
+            "SELECT id, subject, email, password_hash, display_name, avatar_url, created_at FROM users WHERE subject = ?",
         )
         .bind(subject)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         row.map(User::try_from).transpose()
     }
 
     async fn find_by_email(&self, email: &str) -> DomainResult<Option<User>> {
         let row: Option<UserRow> = sqlx::query_as(
-            "SELECT id, subject, email, password_hash, created_at FROM users WHERE email = ?",
+            "SELECT id, subject, email, password_hash, display_name, avatar_url, created_at FROM users WHERE email = ?",
         )
         .bind(email)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         row.map(User::try_from).transpose()
     }
@@ -102,22 +134,26 @@ impl UserRepository for SqliteUserRepository {
 
         sqlx::query(
             r#"
-            INSERT INTO users (id, subject, email, password_hash, created_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO users (id, subject, email, password_hash, display_name, avatar_url, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(id) DO UPDATE SET
                 subject = excluded.subject,
                 email = excluded.email,
-                password_hash = excluded.password_hash
+                password_hash = excluded.password_hash,
+                display_name = excluded.display_name,
+                avatar_url = excluded.avatar_url
             "#,
         )
         .bind(&id)
         .bind(&user.subject)
         .bind(user.email.as_ref()) // Use .as_ref() to get the inner &str
         .bind(&user.password_hash)
+        .bind(&user.display_name)
+        .bind(&user.avatar_url)
         .bind(&created_at)
         .execute(&self.pool)
         .await
-        .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+        .map_err(map_sqlx_error)?;
 
         Ok(())
     }
@@ -128,10 +164,140 @@ impl UserRepository for SqliteUserRepository {
             .bind(&id_str)
             .execute(&self.pool)
             .await
-            .map_err(|e| DomainError::RepositoryError(e.to_string()))?;
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn find_settings(&self, user_id: Uuid) -> DomainResult<UserSettings> {
+        let id_str = user_id.to_string();
+        let settings: Option<String> =
+            sqlx::query_scalar("SELECT settings FROM users WHERE id = ?")
+                .bind(&id_str)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        let Some(settings) = settings else {
+            return Ok(UserSettings::default());
+        };
+
+        // A corrupt settings document should not lock the user out
+        Ok(serde_json::from_str(&settings).unwrap_or_else(|e| {
+            tracing::warn!(%user_id, "Invalid settings JSON, using defaults: {}", e);
+            UserSettings::default()
+        }))
+    }
+
+    async fn save_settings(&self, user_id: Uuid, settings: &UserSettings) -> DomainResult<()> {
+        let id_str = user_id.to_string();
+        let json = serde_json::to_string(settings).map_err(|e| decode_error(e.to_string()))?;
+
+        let result = sqlx::query("UPDATE users SET settings = ? WHERE id = ?")
+            .bind(&json)
+            .bind(&id_str)
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DomainError::UserNotFound(user_id));
+        }
+
+        Ok(())
+    }
+
+    async fn save_email_change(&self, change: &EmailChange) -> DomainResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO email_changes (user_id, new_email, token, expires_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                new_email = excluded.new_email,
+                token = excluded.token,
+                expires_at = excluded.expires_at
+            "#,
+        )
+        .bind(change.user_id.to_string())
+        .bind(change.new_email.as_ref())
+        .bind(&change.token)
+        .bind(change.expires_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn find_email_change(&self, token: &str) -> DomainResult<Option<EmailChange>> {
+        let row: Option<EmailChangeRow> = sqlx::query_as(
+            "SELECT user_id, new_email, token, expires_at FROM email_changes WHERE token = ?",
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        row.map(EmailChange::try_from).transpose()
+    }
+
+    async fn delete_email_change(&self, user_id: Uuid) -> DomainResult<()> {
+        sqlx::query("DELETE FROM email_changes WHERE user_id = ?")
+            .bind(user_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
 
         Ok(())
     }
+
+    async fn find_all_ids(&self) -> DomainResult<Vec<Uuid>> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM users")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).map_err(|e| decode_error(format!("Invalid UUID: {}", e))))
+            .collect()
+    }
+
+    async fn find_ids_with_auto_archive(&self) -> DomainResult<Vec<Uuid>> {
+        // Rows with malformed settings fall back to defaults (opted out)
+        let ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM users
+            WHERE CASE WHEN json_valid(settings)
+                THEN json_extract(settings, '$.auto_archive_after_days')
+            END IS NOT NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).map_err(|e| decode_error(format!("Invalid UUID: {}", e))))
+            .collect()
+    }
+
+    async fn find_ids_with_auto_title(&self) -> DomainResult<Vec<Uuid>> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM users
+            WHERE CASE WHEN json_valid(settings)
+                THEN json_extract(settings, '$.auto_title_enabled')
+            END = 1
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).map_err(|e| decode_error(format!("Invalid UUID: {}", e))))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +374,116 @@ mod tests {
         let found = repo.find_by_id(user.id).await.unwrap();
         assert!(found.is_none());
     }
+
+    #[tokio::test]
+    async fn test_settings_default_and_round_trip() {
+        let pool = setup_test_db().await;
+        let repo = SqliteUserRepository::new(pool);
+
+        let email = Email::try_from("settings@test.com").unwrap();
+        let user = User::new("test|settings", email);
+        repo.save(&user).await.unwrap();
+
+        assert_eq!(
+            repo.find_settings(user.id).await.unwrap(),
+            UserSettings::default()
+        );
+
+        let settings = UserSettings {
+            locale: "pl-PL".to_string(),
+            smart_features_enabled: false,
+            ..UserSettings::default()
+        };
+        repo.save_settings(user.id, &settings).await.unwrap();
+
+        assert_eq!(repo.find_settings(user.id).await.unwrap(), settings);
+
+        // Saving the user itself must not clobber settings
+        repo.save(&user).await.unwrap();
+        assert_eq!(repo.find_settings(user.id).await.unwrap(), settings);
+    }
+
+    #[tokio::test]
+    async fn test_find_ids_with_auto_archive() {
+        let pool = setup_test_db().await;
+        let repo = SqliteUserRepository::new(pool);
+
+        let opted_in = User::new_local(Email::try_from("in@test.com").unwrap(), "hash");
+        let opted_out = User::new_local(Email::try_from("out@test.com").unwrap(), "hash");
+        repo.save(&opted_in).await.unwrap();
+        repo.save(&opted_out).await.unwrap();
+
+        let settings = UserSettings {
+            auto_archive_after_days: Some(30),
+            ..UserSettings::default()
+        };
+        repo.save_settings(opted_in.id, &settings).await.unwrap();
+        repo.save_settings(opted_out.id, &UserSettings::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repo.find_ids_with_auto_archive().await.unwrap(),
+            vec![opted_in.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_ids_with_auto_title() {
+        let pool = setup_test_db().await;
+        let repo = SqliteUserRepository::new(pool);
+
+        let opted_in = User::new_local(Email::try_from("in@test.com").unwrap(), "hash");
+        let opted_out = User::new_local(Email::try_from("out@test.com").unwrap(), "hash");
+        repo.save(&opted_in).await.unwrap();
+        repo.save(&opted_out).await.unwrap();
+
+        let settings = UserSettings {
+            auto_title_enabled: true,
+            ..UserSettings::default()
+        };
+        repo.save_settings(opted_in.id, &settings).await.unwrap();
+        repo.save_settings(opted_out.id, &UserSettings::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repo.find_ids_with_auto_title().await.unwrap(),
+            vec![opted_in.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_profile_fields_and_email_change_round_trip() {
+        let pool = setup_test_db().await;
+        let repo = SqliteUserRepository::new(pool);
+
+        let email = Email::try_from("profile@test.com").unwrap();
+        let mut user = User::new_local(email, "hash");
+        user.display_name = Some("Profile User".to_string());
+        repo.save(&user).await.unwrap();
+
+        let found = repo.find_by_id(user.id).await.unwrap().unwrap();
+        assert_eq!(found.display_name.as_deref(), Some("Profile User"));
+        assert!(found.avatar_url.is_none());
+
+        let change = EmailChange::new(user.id, Email::try_from("changed@test.com").unwrap());
+        repo.save_email_change(&change).await.unwrap();
+
+        let found = repo
+            .find_email_change(&change.token)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.user_id, user.id);
+        assert_eq!(found.new_email.as_ref(), "changed@test.com");
+
+        repo.delete_email_change(user.id).await.unwrap();
+        assert!(
+            repo.find_email_change(&change.token)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
 }
@@ -1,45 +1,516 @@
 use async_trait::async_trait;
-use k_core::ai::qdrant::QdrantAdapter as CoreQdrant;
+use chrono::{DateTime, Utc};
 use notes_domain::errors::{DomainError, DomainResult};
-use notes_domain::ports::VectorStore;
+use notes_domain::language::Language;
+use notes_domain::ports::{VectorFilter, VectorPayload, VectorStore};
+use qdrant_client::qdrant::point_id::PointIdOptions;
+use qdrant_client::qdrant::value::Kind;
+use qdrant_client::qdrant::vectors_config::Config as VectorsConfigKind;
+use qdrant_client::qdrant::{
+    Condition, CountPointsBuilder, CreateCollectionBuilder, CreateFieldIndexCollectionBuilder,
+    DeletePointsBuilder, Distance, FieldType, Filter, GetPointsBuilder, PointId, PointStruct,
+    PointsIdsList, ScrollPointsBuilder, SearchPointsBuilder, UpsertPointsBuilder, Value,
+    VectorParams, VectorParamsBuilder, VectorsConfigBuilder,
+};
+use qdrant_client::{Payload, Qdrant};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::time::Duration;
 use uuid::Uuid;
 
-pub struct QdrantVectorAdapter {
-    inner: Arc<CoreQdrant>,
+/// Points read per request when listing every vector
+const SCROLL_PAGE_SIZE: u32 = 1000;
+
+/// Similarity metric of the collection's vectors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VectorDistance {
+    #[default]
+    Cosine,
+    Dot,
+    Euclid,
+    Manhattan,
 }
 
-impl QdrantVectorAdapter {
-    pub fn new(inner: CoreQdrant) -> Self {
+impl VectorDistance {
+    fn to_qdrant(self) -> Distance {
+        match self {
+            Self::Cosine => Distance::Cosine,
+            Self::Dot => Distance::Dot,
+            Self::Euclid => Distance::Euclid,
+            Self::Manhattan => Distance::Manhattan,
+        }
+    }
+}
+
+impl FromStr for VectorDistance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cosine" => Ok(Self::Cosine),
+            "dot" => Ok(Self::Dot),
+            "euclid" => Ok(Self::Euclid),
+            "manhattan" => Ok(Self::Manhattan),
+            other => Err(format!("Unknown vector distance: {}", other)),
+        }
+    }
+}
+
+/// Connection and collection settings for Qdrant
+#[derive(Debug, Clone)]
+pub struct QdrantConfig {
+    pub url: String,
+    pub collection: String,
+    /// Sent with every request; required by Qdrant Cloud
+    pub api_key: Option<String>,
+    /// Refuse to connect over plain `http://`
+    pub require_tls: bool,
+    /// Per-request timeout
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Store vectors under this name instead of the collection's default vector
+    pub vector_name: Option<String>,
+    /// Vector dimension; must match the embedding model
+    pub vector_size: u64,
+    pub distance: VectorDistance,
+}
+
+impl Default for QdrantConfig {
+    fn default() -> Self {
         Self {
-            inner: Arc::new(inner),
+            url: "http://localhost:6334".to_string(),
+            collection: "notes".to_string(),
+            api_key: None,
+            require_tls: false,
+            timeout: Duration::from_secs(10),
+            connect_timeout: Duration::from_secs(5),
+            vector_name: None,
+            // all-MiniLM-L6-v2
+            vector_size: 384,
+            distance: VectorDistance::Cosine,
+        }
+    }
+}
+
+impl QdrantConfig {
+    /// Reject settings that cannot work before anything connects
+    pub fn validate(&self) -> DomainResult<()> {
+        let invalid = |message: String| {
+            DomainError::InfrastructureError(format!("Invalid Qdrant configuration: {}", message))
+        };
+
+        let is_tls = self.url.starts_with("https://");
+        if !is_tls && !self.url.starts_with("http://") {
+            return Err(invalid(format!(
+                "URL must start with http:// or https://, got {}",
+                self.url
+            )));
+        }
+        if self.require_tls && !is_tls {
+            return Err(invalid(
+                "TLS is required but the URL is not https://".into(),
+            ));
+        }
+        if self.api_key.as_deref().is_some_and(|k| k.trim().is_empty()) {
+            return Err(invalid("API key is empty".into()));
+        }
+        if self.collection.trim().is_empty() {
+            return Err(invalid("collection name is empty".into()));
+        }
+        if self
+            .vector_name
+            .as_deref()
+            .is_some_and(|n| n.trim().is_empty())
+        {
+            return Err(invalid("vector name is empty".into()));
+        }
+        if self.vector_size == 0 {
+            return Err(invalid("vector size must be positive".into()));
         }
+        if self.timeout.is_zero() || self.connect_timeout.is_zero() {
+            return Err(invalid("timeouts must be positive".into()));
+        }
+
+        if self.api_key.is_some() && !is_tls {
+            tracing::warn!("Qdrant API key is sent over plain HTTP to {}", self.url);
+        }
+        Ok(())
+    }
+}
+
+pub struct QdrantVectorAdapter {
+    client: Qdrant,
+    config: QdrantConfig,
+}
+
+impl QdrantVectorAdapter {
+    pub fn new(config: QdrantConfig) -> DomainResult<Self> {
+        config.validate()?;
+
+        let client = Qdrant::from_url(&config.url)
+            .api_key(config.api_key.clone())
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .build()
+            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant client error: {}", e)))?;
+
+        Ok(Self { client, config })
     }
 
+    /// Create the collection if missing, or check that the existing one
+    /// stores vectors of the configured size and distance
     pub async fn init(&self) -> DomainResult<()> {
-        self.inner
-            .create_collection_if_not_exists(384)
+        let collection = &self.config.collection;
+        let exists = self
+            .client
+            .collection_exists(collection)
             .await
-            .map_err(|e| DomainError::InfrastructureError(e.to_string()))
+            .map_err(|e| {
+                DomainError::InfrastructureError(format!("Qdrant connection error: {}", e))
+            })?;
+
+        if exists {
+            self.check_collection().await?;
+        } else {
+            let params =
+                VectorParamsBuilder::new(self.config.vector_size, self.config.distance.to_qdrant());
+            let mut vectors = VectorsConfigBuilder::default();
+            match self.config.vector_name {
+                Some(ref name) => vectors.add_named_vector_params(name, params),
+                None => vectors.add_vector_params(params),
+            };
+            self.client
+                .create_collection(CreateCollectionBuilder::new(collection).vectors_config(vectors))
+                .await
+                .map_err(|e| {
+                    DomainError::InfrastructureError(format!("Qdrant collection error: {}", e))
+                })?;
+        }
+
+        // Every search filters by owner; creating an existing index is a no-op
+        self.client
+            .create_field_index(
+                CreateFieldIndexCollectionBuilder::new(collection, "user_id", FieldType::Keyword)
+                    .wait(true),
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant index error: {}", e)))
+    }
+
+    async fn check_collection(&self) -> DomainResult<()> {
+        let mismatch = |message: String| {
+            DomainError::InfrastructureError(format!(
+                "Qdrant collection {} does not match the configuration: {}",
+                self.config.collection, message
+            ))
+        };
+
+        let info = self
+            .client
+            .collection_info(&self.config.collection)
+            .await
+            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant info error: {}", e)))?;
+        let kind = info
+            .result
+            .and_then(|info| info.config)
+            .and_then(|config| config.params)
+            .and_then(|params| params.vectors_config)
+            .and_then(|vectors| vectors.config)
+            .ok_or_else(|| mismatch("it has no vector configuration".into()))?;
+
+        let params: VectorParams = match (kind, &self.config.vector_name) {
+            (VectorsConfigKind::Params(params), None) => params,
+            (VectorsConfigKind::ParamsMap(mut map), Some(name)) => map
+                .map
+                .remove(name)
+                .ok_or_else(|| mismatch(format!("it has no vector named {}", name)))?,
+            (VectorsConfigKind::Params(_), Some(name)) => {
+                return Err(mismatch(format!(
+                    "it has an unnamed vector, not one named {}",
+                    name
+                )));
+            }
+            (VectorsConfigKind::ParamsMap(_), None) => {
+                return Err(mismatch("it has named vectors; set the vector name".into()));
+            }
+        };
+
+        if params.size != self.config.vector_size {
+            return Err(mismatch(format!(
+                "vector size is {}, expected {}",
+                params.size, self.config.vector_size
+            )));
+        }
+        if params.distance != self.config.distance.to_qdrant() as i32 {
+            return Err(mismatch(format!(
+                "distance is not {:?}",
+                self.config.distance
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn to_payload(payload: &VectorPayload) -> DomainResult<Payload> {
+    Payload::try_from(serde_json::json!({
+        "user_id": payload.user_id.to_string(),
+        "note_id": payload.note_id.to_string(),
+        "chunk_index": payload.chunk_index,
+        "updated_at": payload.updated_at.to_rfc3339(),
+        "model": payload.model,
+        "content_hash": payload.content_hash,
+        "language": payload.language.as_ref().map(|l| l.code()),
+    }))
+    .map_err(|e| DomainError::InfrastructureError(format!("Qdrant payload error: {}", e)))
+}
+
+/// Read back a payload written by [`to_payload`]
+fn from_payload(payload: &HashMap<String, Value>) -> Option<VectorPayload> {
+    let string = |key: &str| match payload.get(key)?.kind.as_ref()? {
+        Kind::StringValue(value) => Some(value.as_str()),
+        _ => None,
+    };
+    let chunk_index = match payload.get("chunk_index")?.kind.as_ref()? {
+        Kind::IntegerValue(value) => u32::try_from(*value).ok()?,
+        _ => return None,
+    };
+
+    Some(VectorPayload {
+        user_id: Uuid::parse_str(string("user_id")?).ok()?,
+        note_id: Uuid::parse_str(string("note_id")?).ok()?,
+        chunk_index,
+        updated_at: DateTime::parse_from_rfc3339(string("updated_at")?)
+            .ok()?
+            .with_timezone(&Utc),
+        model: string("model")?.to_string(),
+        content_hash: string("content_hash")?.to_string(),
+        // Vectors stored before languages were detected have none
+        language: string("language").and_then(|code| Language::new(code).ok()),
+    })
+}
+
+fn point_uuid(id: Option<PointId>) -> Option<Uuid> {
+    match id?.point_id_options? {
+        PointIdOptions::Uuid(id) => Uuid::parse_str(&id).ok(),
+        PointIdOptions::Num(_) => None,
+    }
+}
+
+fn to_filter(filter: &VectorFilter) -> Filter {
+    Filter {
+        must: filter
+            .user_id
+            .map(|id| Condition::matches("user_id", id.to_string()))
+            .into_iter()
+            .collect(),
+        must_not: filter
+            .exclude_note_id
+            .map(|id| Condition::matches("note_id", id.to_string()))
+            .into_iter()
+            .collect(),
+        ..Default::default()
     }
 }
 
 #[async_trait]
 impl VectorStore for QdrantVectorAdapter {
-    async fn upsert(&self, id: Uuid, vector: &[f32]) -> DomainResult<()> {
-        let payload = HashMap::new();
+    async fn upsert(&self, id: Uuid, vector: &[f32], payload: &VectorPayload) -> DomainResult<()> {
+        let payload = to_payload(payload)?;
+        let point = match self.config.vector_name {
+            Some(ref name) => PointStruct::new(
+                id.to_string(),
+                HashMap::from([(name.clone(), vector.to_vec())]),
+                payload,
+            ),
+            None => PointStruct::new(id.to_string(), vector.to_vec(), payload),
+        };
 
-        self.inner
-            .upsert(id, vector.to_vec(), payload)
+        self.client
+            .upsert_points(
+                UpsertPointsBuilder::new(&self.config.collection, vec![point]).wait(true),
+            )
             .await
+            .map(|_| ())
             .map_err(|e| DomainError::InfrastructureError(format!("Qdrant upsert error: {}", e)))
     }
 
-    async fn find_similar(&self, vector: &[f32], limit: usize) -> DomainResult<Vec<(Uuid, f32)>> {
-        self.inner
-            .search(vector.to_vec(), limit as u64)
+    async fn get_payloads(&self, ids: &[Uuid]) -> DomainResult<HashMap<Uuid, VectorPayload>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let ids: Vec<PointId> = ids.iter().map(|id| id.to_string().into()).collect();
+
+        let response = self
+            .client
+            .get_points(
+                GetPointsBuilder::new(&self.config.collection, ids)
+                    .with_payload(true)
+                    .with_vectors(false),
+            )
+            .await
+            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant get error: {}", e)))?;
+
+        Ok(response
+            .result
+            .into_iter()
+            .filter_map(|point| Some((point_uuid(point.id)?, from_payload(&point.payload)?)))
+            .collect())
+    }
+
+    async fn find_similar(
+        &self,
+        vector: &[f32],
+        limit: usize,
+        filter: &VectorFilter,
+    ) -> DomainResult<Vec<(Uuid, f32)>> {
+        let mut search =
+            SearchPointsBuilder::new(&self.config.collection, vector.to_vec(), limit as u64)
+                .filter(to_filter(filter));
+        if let Some(ref name) = self.config.vector_name {
+            search = search.vector_name(name);
+        }
+
+        let response =
+            self.client.search_points(search).await.map_err(|e| {
+                DomainError::InfrastructureError(format!("Qdrant search error: {}", e))
+            })?;
+
+        Ok(response
+            .result
+            .into_iter()
+            .filter_map(|point| Some((point_uuid(point.id)?, point.score)))
+            .collect())
+    }
+
+    async fn delete(&self, id: Uuid) -> DomainResult<()> {
+        self.client
+            .delete_points(
+                DeletePointsBuilder::new(&self.config.collection)
+                    .points(PointsIdsList {
+                        ids: vec![id.to_string().into()],
+                    })
+                    .wait(true),
+            )
             .await
-            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant search error: {}", e)))
+            .map(|_| ())
+            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant delete error: {}", e)))
+    }
+
+    async fn count(&self) -> DomainResult<u64> {
+        self.client
+            .count(CountPointsBuilder::new(&self.config.collection).exact(false))
+            .await
+            .map(|response| response.result.map_or(0, |r| r.count))
+            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant count error: {}", e)))
+    }
+
+    async fn ids(&self) -> DomainResult<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        let mut offset: Option<PointId> = None;
+        loop {
+            let mut request = ScrollPointsBuilder::new(&self.config.collection)
+                .limit(SCROLL_PAGE_SIZE)
+                .with_payload(false)
+                .with_vectors(false);
+            if let Some(offset) = offset {
+                request = request.offset(offset);
+            }
+            let response = self.client.scroll(request).await.map_err(|e| {
+                DomainError::InfrastructureError(format!("Qdrant scroll error: {}", e))
+            })?;
+
+            ids.extend(response.result.into_iter().filter_map(|p| point_uuid(p.id)));
+            match response.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => return Ok(ids),
+            }
+        }
+    }
+
+    async fn delete_by_user(&self, user_id: Uuid) -> DomainResult<()> {
+        self.client
+            .delete_points(
+                DeletePointsBuilder::new(&self.config.collection)
+                    .points(Filter::must([Condition::matches(
+                        "user_id",
+                        user_id.to_string(),
+                    )]))
+                    .wait(true),
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| DomainError::InfrastructureError(format!("Qdrant delete error: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(QdrantConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_payload_round_trips_with_and_without_language() {
+        let note = notes_domain::Note::new(
+            Uuid::new_v4(),
+            None,
+            "Jutro rano idziemy na spacer do parku, a potem zjemy obiad u babci. Wieczorem czytamy książki i pijemy herbatę z cytryną.",
+        );
+        let mut payload = VectorPayload::for_note(&note, "test-model");
+        assert_eq!(payload.language, Language::new("pl").ok());
+
+        for _ in 0..2 {
+            let stored: HashMap<String, Value> = to_payload(&payload).unwrap().into();
+            assert_eq!(from_payload(&stored), Some(payload.clone()));
+            payload.language = None;
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_unusable_settings() {
+        let invalid = [
+            QdrantConfig {
+                url: "localhost:6334".to_string(),
+                ..Default::default()
+            },
+            QdrantConfig {
+                require_tls: true,
+                ..Default::default()
+            },
+            QdrantConfig {
+                api_key: Some(" ".to_string()),
+                ..Default::default()
+            },
+            QdrantConfig {
+                vector_size: 0,
+                ..Default::default()
+            },
+            QdrantConfig {
+                timeout: Duration::ZERO,
+                ..Default::default()
+            },
+        ];
+
+        for config in invalid {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+
+        let tls = QdrantConfig {
+            url: "https://qdrant.example.com:6334".to_string(),
+            require_tls: true,
+            api_key: Some("secret".to_string()),
+            ..Default::default()
+        };
+        assert!(tls.validate().is_ok());
+    }
+
+    #[test]
+    fn test_distance_parsing() {
+        assert_eq!("Dot".parse::<VectorDistance>(), Ok(VectorDistance::Dot));
+        assert!("cosinus".parse::<VectorDistance>().is_err());
     }
 }
@@ -0,0 +1,191 @@
+//! Guards for requests to URLs taken from user content
+//!
+//! Note content can name any URL, including ones on the server's own network.
+//! Clients built here only connect to public addresses: names resolving to
+//! loopback, private, link-local or otherwise reserved addresses are refused
+//! at connect time, which also covers redirects and DNS rebinding.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Url, redirect};
+
+/// Redirects followed before giving up
+const MAX_REDIRECTS: usize = 5;
+
+/// Whether the address is reachable on the public internet
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space of carrier-grade NAT
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking
+        || (a == 198 && (b == 18 || b == 19))
+        // Reserved
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // NAT64 gateways forward to the embedded IPv4 address
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_public_v4(Ipv4Addr::new(a, b, c, d));
+    }
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link-local
+        || (segments[0] & 0xffc0) == 0xfe80
+        // Documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+}
+
+/// The URL's host as an IP address, if it is written as one
+fn host_ip(url: &Url) -> Option<IpAddr> {
+    url.host_str()?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// Whether a client may follow `url`: `http(s)` and not an IP literal of a
+/// non-public address. Host names are checked when they are resolved.
+fn is_allowed_url(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https")
+        && url.host_str().is_some()
+        && host_ip(url).is_none_or(is_public_ip)
+}
+
+/// Whether `url` points at the public internet, resolving its host name.
+///
+/// Fails when the name does not resolve at all.
+pub async fn is_public_url(url: &Url) -> std::io::Result<bool> {
+    if !is_allowed_url(url) {
+        return Ok(false);
+    }
+    if host_ip(url).is_some() {
+        return Ok(true);
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let mut addrs = tokio::net::lookup_host((host, port)).await?.peekable();
+    if addrs.peek().is_none() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{} has no addresses", host),
+        ));
+    }
+    Ok(addrs.all(|addr| is_public_ip(addr.ip())))
+}
+
+/// Resolves names with the system resolver, dropping non-public addresses
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Build an HTTP client that only connects to public addresses
+pub fn public_client(timeout: Duration) -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("k-notes/", env!("CARGO_PKG_VERSION")))
+        .dns_resolver(Arc::new(PublicResolver))
+        // A proxy would resolve names itself, past the resolver
+        .no_proxy()
+        .redirect(redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if !is_allowed_url(attempt.url()) {
+                attempt.stop()
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_public_addresses_are_rejected() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(
+                !is_public_ip(ip.parse().unwrap()),
+                "{} should be blocked",
+                ip
+            );
+        }
+        for ip in ["93.184.215.14", "2606:4700::1111", "64:ff9b::5db8:d70e"] {
+            assert!(
+                is_public_ip(ip.parse().unwrap()),
+                "{} should be allowed",
+                ip
+            );
+        }
+    }
+
+    #[test]
+    fn test_only_http_urls_without_private_literals_are_allowed() {
+        let allowed = |url: &str| is_allowed_url(&Url::parse(url).unwrap());
+
+        assert!(allowed("https://example.com/page"));
+        assert!(allowed("http://93.184.215.14/"));
+        assert!(!allowed("http://127.0.0.1:8080/admin"));
+        assert!(!allowed("http://[::1]/"));
+        assert!(!allowed("file:///etc/passwd"));
+        assert!(!allowed("ftp://example.com/"));
+    }
+}
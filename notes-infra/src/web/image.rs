@@ -0,0 +1,100 @@
+//! HTTP adapter for the `ImageFetcher` port
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Url, header};
+
+use notes_domain::images::{FetchedImage, PROXIED_IMAGE_TYPES};
+use notes_domain::{DomainError, DomainResult, ImageFetcher};
+
+use super::guard::{is_public_url, public_client};
+
+/// Loads images of notes for readers, from public addresses only
+pub struct HttpImageFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpImageFetcher {
+    pub fn new(timeout: Duration) -> DomainResult<Self> {
+        let client = public_client(timeout).map_err(|e| {
+            DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+        })?;
+        Ok(Self { client })
+    }
+}
+
+/// The proxied type a `Content-Type` header names, without its parameters
+fn image_type(content_type: &str) -> Option<&'static str> {
+    let essence = content_type.split(';').next()?.trim();
+    PROXIED_IMAGE_TYPES
+        .iter()
+        .copied()
+        .find(|t| t.eq_ignore_ascii_case(essence))
+}
+
+#[async_trait]
+impl ImageFetcher for HttpImageFetcher {
+    async fn fetch_image(&self, url: &str, max_bytes: usize) -> DomainResult<Option<FetchedImage>> {
+        let Ok(url) = Url::parse(url) else {
+            return Ok(None);
+        };
+        if !is_public_url(&url).await.unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let Ok(mut response) = self
+            .client
+            .get(url)
+            .header(header::ACCEPT, PROXIED_IMAGE_TYPES.join(","))
+            .send()
+            .await
+        else {
+            return Ok(None);
+        };
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(image_type);
+        let Some(content_type) = content_type.filter(|_| response.status().is_success()) else {
+            return Ok(None);
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_bytes as u64)
+        {
+            return Ok(None);
+        }
+
+        // The length may be missing or wrong, so it is counted as it arrives
+        let mut bytes = Vec::new();
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) if bytes.len() + chunk.len() <= max_bytes => {
+                    bytes.extend_from_slice(&chunk)
+                }
+                Ok(Some(_)) | Err(_) => return Ok(None),
+                Ok(None) => break,
+            }
+        }
+
+        Ok(Some(FetchedImage {
+            bytes,
+            content_type: content_type.to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_raster_images_are_served() {
+        assert_eq!(image_type("image/png"), Some("image/png"));
+        assert_eq!(image_type("IMAGE/JPEG; charset=binary"), Some("image/jpeg"));
+        assert_eq!(image_type("image/svg+xml"), None);
+        assert_eq!(image_type("text/html"), None);
+    }
+}
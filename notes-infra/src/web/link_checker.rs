@@ -0,0 +1,70 @@
+//! HTTP adapter for the `UrlChecker` port
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{StatusCode, Url};
+
+use notes_domain::{DomainError, DomainResult, UrlChecker};
+
+use super::guard::{is_public_url, public_client};
+
+/// Checks links with a `HEAD` request, falling back to `GET`
+pub struct HttpUrlChecker {
+    client: reqwest::Client,
+}
+
+impl HttpUrlChecker {
+    pub fn new(timeout: Duration) -> DomainResult<Self> {
+        let client = public_client(timeout).map_err(|e| {
+            DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+        })?;
+        Ok(Self { client })
+    }
+}
+
+/// Statuses that say more about the checker than the link: login walls,
+/// bot protection and rate limits
+fn is_inconclusive(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    )
+}
+
+#[async_trait]
+impl UrlChecker for HttpUrlChecker {
+    async fn check(&self, url: &str) -> DomainResult<Option<String>> {
+        let Ok(url) = Url::parse(url) else {
+            return Ok(Some("Invalid URL".to_string()));
+        };
+        match is_public_url(&url).await {
+            Ok(true) => {}
+            // Links into private networks may well work for the reader
+            Ok(false) => return Ok(None),
+            Err(_) => return Ok(Some("Host not found".to_string())),
+        }
+
+        let mut response = self.client.head(url.clone()).send().await;
+        // Some servers do not implement HEAD
+        if let Ok(ref head) = response
+            && matches!(
+                head.status(),
+                StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+            )
+        {
+            response = self.client.get(url).send().await;
+        }
+
+        Ok(match response {
+            Ok(response) => {
+                let status = response.status();
+                let failed = status.is_client_error() || status.is_server_error();
+                (failed && !is_inconclusive(status)).then(|| format!("HTTP {}", status.as_u16()))
+            }
+            Err(e) if e.is_timeout() => Some("Timed out".to_string()),
+            Err(e) if e.is_redirect() => Some("Too many redirects".to_string()),
+            Err(_) => Some("Connection failed".to_string()),
+        })
+    }
+}
@@ -0,0 +1,10 @@
+//! Outbound HTTP adapters for URLs found in note content.
+//!
+//! This module provides implementations of the `UrlChecker`,
+//! `LinkPreviewFetcher` and `ImageFetcher` ports, built on clients that
+//! refuse to reach the server's own network.
+
+pub mod guard;
+pub mod image;
+pub mod link_checker;
+pub mod preview;
@@ -0,0 +1,227 @@
+//! HTTP adapter for the `LinkPreviewFetcher` port
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Url, header};
+
+use notes_domain::{
+    DomainError, DomainResult, LinkPreviewFetcher,
+    bookmarks::{LinkPreview, clean_preview_text},
+};
+
+use super::guard::{is_public_url, public_client};
+use crate::html_scan::{attr, decode_entities, parse_attributes};
+
+/// Most of a page read looking for its `<head>`
+const MAX_PAGE_BYTES: usize = 512 * 1024;
+
+/// Reads Open Graph tags, the `<title>` and the icon link of bookmarked pages
+pub struct HttpLinkPreviewFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpLinkPreviewFetcher {
+    pub fn new(timeout: Duration) -> DomainResult<Self> {
+        let client = public_client(timeout).map_err(|e| {
+            DomainError::InfrastructureError(format!("Failed to build HTTP client: {}", e))
+        })?;
+        Ok(Self { client })
+    }
+
+    /// Start of the page body, or `None` if it is not an HTML page
+    async fn fetch_html(&self, url: Url) -> Option<(Url, String)> {
+        let mut response = self
+            .client
+            .get(url)
+            .header(header::ACCEPT, "text/html,application/xhtml+xml")
+            .send()
+            .await
+            .ok()?;
+        let is_html = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("html"));
+        if !response.status().is_success() || !is_html {
+            return None;
+        }
+
+        // Redirects change the base relative icon links resolve against
+        let base = response.url().clone();
+        let mut body = Vec::new();
+        while body.len() < MAX_PAGE_BYTES {
+            match response.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                Ok(None) => break,
+                // Keep what arrived; the head usually came first
+                Err(_) if !body.is_empty() => break,
+                Err(_) => return None,
+            }
+        }
+        body.truncate(MAX_PAGE_BYTES);
+
+        Some((base, String::from_utf8_lossy(&body).into_owned()))
+    }
+}
+
+#[async_trait]
+impl LinkPreviewFetcher for HttpLinkPreviewFetcher {
+    async fn fetch(&self, url: &str) -> DomainResult<Option<LinkPreview>> {
+        let Ok(parsed) = Url::parse(url) else {
+            return Ok(None);
+        };
+        if !is_public_url(&parsed).await.unwrap_or(false) {
+            return Ok(None);
+        }
+        let Some((base, html)) = self.fetch_html(parsed).await else {
+            return Ok(None);
+        };
+
+        let head = PageHead::parse(&html);
+        let icon = head.icon.as_deref().unwrap_or("/favicon.ico");
+        let absolute = |href: &str| {
+            base.join(href)
+                .ok()
+                .filter(|u| matches!(u.scheme(), "http" | "https"))
+                .map(String::from)
+        };
+        let mut preview = LinkPreview::new(url);
+        preview.title = head.title.as_deref().and_then(clean_preview_text);
+        preview.description = head.description.as_deref().and_then(clean_preview_text);
+        preview.favicon_url = absolute(icon);
+        preview.image_url = head.image.as_deref().and_then(absolute);
+        preview.site_name = head.site_name.as_deref().and_then(clean_preview_text);
+
+        Ok(Some(preview))
+    }
+}
+
+/// What a page's `<head>` says about it
+#[derive(Debug, Default, PartialEq, Eq)]
+struct PageHead {
+    title: Option<String>,
+    description: Option<String>,
+    /// `href` of the icon link as written
+    icon: Option<String>,
+    /// `og:image` as written
+    image: Option<String>,
+    site_name: Option<String>,
+}
+
+impl PageHead {
+    /// Scan the tags before `<body>`; Open Graph values win over plain ones
+    fn parse(html: &str) -> Self {
+        // ASCII lowercasing keeps byte offsets, so values are sliced from `html`
+        let lower = html.to_ascii_lowercase();
+        let mut og_title = None;
+        let mut title = None;
+        let mut og_description = None;
+        let mut description = None;
+        let mut icon = None;
+        let mut image = None;
+        let mut site_name = None;
+
+        let mut pos = 0;
+        while let Some(offset) = lower[pos..].find('<') {
+            let start = pos + offset + 1;
+            // Closing tags keep their slash, e.g. `/head`
+            let name_start = start + usize::from(lower[start..].starts_with('/'));
+            let name_end = lower[name_start..]
+                .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+                .map_or(lower.len(), |i| name_start + i);
+            let Some(tag_end) = lower[start..].find('>').map(|i| start + i) else {
+                break;
+            };
+            pos = tag_end + 1;
+
+            let read_attrs = || parse_attributes(&html[name_end..tag_end]);
+            match &lower[start..name_end] {
+                "body" | "/head" => break,
+                "title" if title.is_none() => {
+                    let end = lower[pos..]
+                        .find("</title")
+                        .map_or(lower.len(), |i| pos + i);
+                    title = Some(decode_entities(&html[pos..end]));
+                    pos = end;
+                }
+                "meta" => {
+                    let attrs = read_attrs();
+                    let key = attr(&attrs, "property").or_else(|| attr(&attrs, "name"));
+                    let content = attr(&attrs, "content").map(decode_entities);
+                    match key.map(str::to_ascii_lowercase).as_deref() {
+                        Some("og:title") => og_title = og_title.or(content),
+                        Some("og:description") => og_description = og_description.or(content),
+                        Some("description") => description = description.or(content),
+                        Some("og:image") => image = image.or(content),
+                        Some("og:site_name") => site_name = site_name.or(content),
+                        _ => {}
+                    }
+                }
+                "link" if icon.is_none() => {
+                    let attrs = read_attrs();
+                    let is_icon = attr(&attrs, "rel").is_some_and(|rel| {
+                        rel.split_ascii_whitespace()
+                            .any(|r| r.eq_ignore_ascii_case("icon"))
+                    });
+                    if is_icon {
+                        icon = attr(&attrs, "href").map(decode_entities);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            title: og_title.or(title),
+            description: og_description.or(description),
+            icon,
+            image,
+            site_name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_prefers_open_graph_values() {
+        let html = r#"<!DOCTYPE html>
+            <HTML><Head>
+            <meta charset="utf-8">
+            <title>Plain &amp; simple</title>
+            <meta name="description" content="Plain description">
+            <META property='og:title' content="Rust &#8212; Blog">
+            <meta property="og:image" content="/images/card.png">
+            <meta property="og:site_name" content="Rust">
+            <link rel="stylesheet" href="/style.css">
+            <link rel="shortcut icon" href=/static/icon.png>
+            </head>
+            <body><meta property="og:description" content="Too late"></body>"#;
+
+        assert_eq!(
+            PageHead::parse(html),
+            PageHead {
+                title: Some("Rust — Blog".to_string()),
+                description: Some("Plain description".to_string()),
+                icon: Some("/static/icon.png".to_string()),
+                image: Some("/images/card.png".to_string()),
+                site_name: Some("Rust".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_head_of_minimal_page() {
+        assert_eq!(
+            PageHead::parse("<title>Only a title"),
+            PageHead {
+                title: Some("Only a title".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(PageHead::parse("no markup & stray <"), PageHead::default());
+    }
+}
@@ -0,0 +1,50 @@
+//! Scheduled note titling job
+//!
+//! Periodically titles the untitled notes of users who opted in, once the
+//! notes have not been edited for a while.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use notes_domain::{DomainResult, InstanceSettingsRepository, NoteService, UserRepository};
+
+/// Run the job every `interval` until the process exits
+pub async fn run(
+    note_service: Arc<NoteService>,
+    user_repo: Arc<dyn UserRepository>,
+    instance_settings: Arc<dyn InstanceSettingsRepository>,
+    interval: Duration,
+) {
+    tracing::info!("Note titling job scheduled every {:?}", interval);
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if instance_settings.is_read_only().await.unwrap_or(false) {
+            tracing::info!("Read-only maintenance mode enabled, skipping note titling");
+            continue;
+        }
+
+        if let Err(e) = run_once(&note_service, user_repo.as_ref()).await {
+            tracing::error!("Note titling run failed: {}", e);
+        }
+    }
+}
+
+async fn run_once(note_service: &NoteService, user_repo: &dyn UserRepository) -> DomainResult<()> {
+    let now = Utc::now();
+
+    let user_ids = user_repo.find_ids_with_auto_title().await?;
+    let results = crate::for_each_user(user_ids, "Note titling", |user_id| {
+        note_service.run_auto_title(user_id, now)
+    })
+    .await;
+
+    for (user_id, count) in results.into_iter().filter(|(_, count)| *count > 0) {
+        tracing::info!(%user_id, "Titled {} notes", count);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! Scheduled link preview job
+//!
+//! Periodically fetches previews for URLs pasted on a line of their own in
+//! notes that changed since their previews were last saved.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use notes_domain::{DomainResult, InstanceSettingsRepository, LinkPreviewService};
+
+/// Notes refreshed per batch
+const BATCH_SIZE: usize = 50;
+
+/// Run the job every `interval` until the process exits
+pub async fn run(
+    preview_service: Arc<LinkPreviewService>,
+    instance_settings: Arc<dyn InstanceSettingsRepository>,
+    interval: Duration,
+) {
+    tracing::info!("Link preview job scheduled every {:?}", interval);
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if instance_settings.is_read_only().await.unwrap_or(false) {
+            tracing::info!("Read-only maintenance mode enabled, skipping link previews");
+            continue;
+        }
+
+        if let Err(e) = run_once(&preview_service).await {
+            tracing::error!("Link preview run failed: {}", e);
+        }
+    }
+}
+
+async fn run_once(preview_service: &LinkPreviewService) -> DomainResult<()> {
+    let mut total = 0;
+    loop {
+        let refreshed = preview_service.refresh_stale(BATCH_SIZE).await?;
+        total += refreshed;
+        if refreshed < BATCH_SIZE {
+            break;
+        }
+    }
+
+    if total > 0 {
+        tracing::info!("Refreshed link previews of {} notes", total);
+    }
+    Ok(())
+}
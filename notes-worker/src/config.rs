@@ -1,28 +1,101 @@
 #[cfg(feature = "smart-features")]
-use notes_infra::factory::{EmbeddingProvider, VectorProvider};
+use notes_infra::factory::{EmbeddingProvider, QdrantConfig, VectorProvider};
+
+use notes_domain::tag_cleanup::UnusedTagPolicy;
+use notes_domain::trash::{DEFAULT_TRASH_RETENTION_DAYS, TrashRetention};
+use notes_infra::factory::{
+    CacheProvider, LinkCheckProvider, LinkPreviewProvider, TextGeneratorProvider,
+    TranscriberProvider,
+};
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub broker_url: String,
     pub database_url: String,
+    /// How often the auto-archive job runs (`None` = disabled)
+    pub auto_archive_interval: Option<Duration>,
+    /// How often the trash purge job runs (`None` = disabled)
+    pub trash_purge_interval: Option<Duration>,
+    /// How long trashed notes are kept before being purged
+    pub trash_retention: TrashRetention,
+    /// How often the note lint job runs (`None` = disabled)
+    pub lint_interval: Option<Duration>,
+    /// How the lint job checks external links
+    pub link_check_provider: LinkCheckProvider,
+    /// How often unused tags are cleaned up (`None` = disabled)
+    pub tag_cleanup_interval: Option<Duration>,
+    /// Whether the tag cleanup job flags or deletes unused tags
+    pub unused_tag_policy: UnusedTagPolicy,
+    /// How often notes are scanned for new bookmarks (`None` = disabled)
+    pub link_preview_interval: Option<Duration>,
+    /// How bookmark previews are fetched
+    pub link_preview_provider: LinkPreviewProvider,
+    /// How often untitled notes of opted-in users are titled (`None` = disabled)
+    pub auto_title_interval: Option<Duration>,
+    /// What titles untitled notes; without a model, their first line does
+    pub text_generator_provider: TextGeneratorProvider,
+    /// How often voice memos are transcribed
+    pub transcription_interval: Duration,
+    /// What transcribes voice memos (`None` = the job does not run)
+    pub transcriber_provider: TranscriberProvider,
+    /// Shared cache the API reads from, so job writes invalidate its entries
+    pub cache_provider: CacheProvider,
     #[cfg(feature = "smart-features")]
     pub embedding_provider: EmbeddingProvider,
     #[cfg(feature = "smart-features")]
     pub vector_provider: VectorProvider,
+    /// Most queued note updates embedded together in one model call
+    #[cfg(feature = "smart-features")]
+    pub embedding_batch_size: usize,
+    /// Updates of a note within this window are coalesced into the latest
+    #[cfg(feature = "smart-features")]
+    pub note_debounce: Duration,
 }
 
 impl Default for Config {
     fn default() -> Self {
+        // Only a shared cache can see the worker's writes
+        let cache_provider = match std::env::var("CACHE_PROVIDER").unwrap_or_default().as_str() {
+            #[cfg(feature = "cache-redis")]
+            "redis" => CacheProvider::Redis {
+                url: std::env::var("REDIS_URL")
+                    .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
+                ttl: Duration::from_secs(
+                    std::env::var("CACHE_TTL_SECS")
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(60),
+                ),
+            },
+            _ => CacheProvider::None,
+        };
+
         Self {
             broker_url: "nats://localhost:4222".to_string(),
             database_url: "sqlite::memory:".to_string(),
+            auto_archive_interval: None,
+            trash_purge_interval: Some(Duration::from_secs(3600)),
+            trash_retention: TrashRetention::default(),
+            lint_interval: Some(Duration::from_secs(86400)),
+            link_check_provider: LinkCheckProvider::None,
+            tag_cleanup_interval: Some(Duration::from_secs(86400)),
+            unused_tag_policy: UnusedTagPolicy::default(),
+            link_preview_interval: Some(Duration::from_secs(60)),
+            link_preview_provider: LinkPreviewProvider::None,
+            auto_title_interval: Some(Duration::from_secs(60)),
+            text_generator_provider: TextGeneratorProvider::None,
+            transcription_interval: Duration::from_secs(30),
+            transcriber_provider: TranscriberProvider::None,
+            cache_provider: CacheProvider::None,
             #[cfg(feature = "smart-features")]
-            embedding_provider: EmbeddingProvider::FastEmbed,
+            embedding_provider: EmbeddingProvider::FastEmbed { pool_size: 2 },
             #[cfg(feature = "smart-features")]
-            vector_provider: VectorProvider::Qdrant {
-                url: "http://localhost:6334".to_string(),
-                collection: "notes".to_string(),
-            },
+            vector_provider: VectorProvider::Qdrant(QdrantConfig::default()),
+            #[cfg(feature = "smart-features")]
+            embedding_batch_size: 16,
+            #[cfg(feature = "smart-features")]
+            note_debounce: Duration::from_secs(5),
         }
     }
 }
@@ -36,7 +109,12 @@ impl Config {
             .unwrap_or_default()
             .as_str()
         {
-            _ => EmbeddingProvider::FastEmbed,
+            _ => EmbeddingProvider::FastEmbed {
+                pool_size: std::env::var("EMBEDDING_POOL_SIZE")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(2),
+            },
         };
 
         #[cfg(feature = "smart-features")]
@@ -44,21 +122,226 @@ impl Config {
             .unwrap_or_default()
             .as_str()
         {
-            _ => VectorProvider::Qdrant {
-                url: std::env::var("QDRANT_URL")
-                    .unwrap_or_else(|_| "http://localhost:6334".to_string()),
-                collection: std::env::var("QDRANT_COLLECTION")
-                    .unwrap_or_else(|_| "notes".to_string()),
+            _ => {
+                let defaults = QdrantConfig::default();
+                let secs = |name: &str, default: std::time::Duration| {
+                    std::env::var(name)
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .map(std::time::Duration::from_secs)
+                        .unwrap_or(default)
+                };
+                VectorProvider::Qdrant(QdrantConfig {
+                    url: std::env::var("QDRANT_URL").unwrap_or(defaults.url.clone()),
+                    collection: std::env::var("QDRANT_COLLECTION")
+                        .unwrap_or(defaults.collection.clone()),
+                    api_key: std::env::var("QDRANT_API_KEY").ok(),
+                    require_tls: std::env::var("QDRANT_REQUIRE_TLS")
+                        .map(|v| v == "1" || v.to_lowercase() == "true")
+                        .unwrap_or(false),
+                    timeout: secs("QDRANT_TIMEOUT_SECS", defaults.timeout),
+                    connect_timeout: secs("QDRANT_CONNECT_TIMEOUT_SECS", defaults.connect_timeout),
+                    vector_name: std::env::var("QDRANT_VECTOR_NAME").ok(),
+                    vector_size: std::env::var("QDRANT_VECTOR_SIZE")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.vector_size),
+                    distance: std::env::var("QDRANT_DISTANCE")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.distance),
+                })
+            }
+        };
+
+        #[cfg(feature = "smart-features")]
+        let embedding_batch_size = std::env::var("EMBEDDING_BATCH_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&size: &usize| size > 0)
+            .unwrap_or(16);
+
+        // 0 processes every update as soon as it arrives
+        #[cfg(feature = "smart-features")]
+        let note_debounce = Duration::from_secs(
+            std::env::var("NOTE_DEBOUNCE_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+        );
+
+        // Off unless an interval is set; 0 keeps it off
+        let auto_archive_interval = std::env::var("AUTO_ARCHIVE_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|secs: &u64| *secs > 0)
+            .map(Duration::from_secs);
+
+        // 0 disables the job
+        let trash_purge_interval = std::env::var("TRASH_PURGE_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map_or(Some(3600), |secs: u64| (secs > 0).then_some(secs))
+            .map(Duration::from_secs);
+
+        let trash_retention = TrashRetention::new(
+            std::env::var("TRASH_RETENTION_DAYS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS),
+        );
+
+        // 0 disables the job
+        let lint_interval = std::env::var("LINT_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map_or(Some(86400), |secs: u64| (secs > 0).then_some(secs))
+            .map(Duration::from_secs);
+
+        // Requesting every link in every note reveals what users read, so
+        // outbound checks are opt-in
+        let check_urls = std::env::var("LINT_CHECK_URLS")
+            .map(|v| v == "1" || v.to_lowercase() == "true")
+            .unwrap_or(false);
+        let link_check_provider = match check_urls {
+            #[cfg(feature = "web-fetch")]
+            true => LinkCheckProvider::Http {
+                timeout: Duration::from_secs(
+                    std::env::var("LINT_URL_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(10),
+                ),
             },
+            _ => LinkCheckProvider::None,
+        };
+
+        // 0 disables the job
+        let tag_cleanup_interval = std::env::var("TAG_CLEANUP_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map_or(Some(86400), |secs: u64| (secs > 0).then_some(secs))
+            .map(Duration::from_secs);
+
+        // Deleting is opt-in; by default unused tags are only reported
+        let unused_tag_policy = std::env::var("UNUSED_TAG_POLICY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
+
+        // 0 disables the job
+        let link_preview_interval = std::env::var("LINK_PREVIEW_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map_or(Some(60), |secs: u64| (secs > 0).then_some(secs))
+            .map(Duration::from_secs);
+
+        #[cfg(feature = "web-fetch")]
+        let link_preview_provider = LinkPreviewProvider::Http {
+            timeout: Duration::from_secs(
+                std::env::var("LINK_PREVIEW_TIMEOUT_SECS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(10),
+            ),
+        };
+        #[cfg(not(feature = "web-fetch"))]
+        let link_preview_provider = LinkPreviewProvider::None;
+
+        // 0 disables the job
+        let auto_title_interval = std::env::var("AUTO_TITLE_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map_or(Some(60), |secs: u64| (secs > 0).then_some(secs))
+            .map(Duration::from_secs);
+
+        // Note content leaves the instance, so a model is opt-in
+        let text_generator_provider = match std::env::var("TEXT_GENERATOR_PROVIDER")
+            .unwrap_or_default()
+            .as_str()
+        {
+            #[cfg(feature = "text-generation")]
+            "openai" => TextGeneratorProvider::OpenAi {
+                base_url: std::env::var("TEXT_GENERATOR_URL")
+                    .unwrap_or_else(|_| "https://api.openai.com/v1".to_string()),
+                model: std::env::var("TEXT_GENERATOR_MODEL").unwrap_or_default(),
+                api_key: std::env::var("TEXT_GENERATOR_API_KEY").ok(),
+                timeout: Duration::from_secs(
+                    std::env::var("TEXT_GENERATOR_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(30),
+                ),
+            },
+            _ => TextGeneratorProvider::None,
+        };
+
+        let transcription_interval = Duration::from_secs(
+            std::env::var("TRANSCRIPTION_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|&secs: &u64| secs > 0)
+                .unwrap_or(30),
+        );
+
+        // The API accepts voice memos for the same setting
+        let transcriber_provider = match std::env::var("TRANSCRIBER_PROVIDER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            // A model has to be downloaded first, so there is no default
+            #[cfg(feature = "transcription")]
+            "whisper" if std::env::var("WHISPER_MODEL").is_ok() => {
+                TranscriberProvider::WhisperCpp {
+                    binary: std::env::var("WHISPER_BINARY")
+                        .unwrap_or_else(|_| "whisper-cli".to_string()),
+                    ffmpeg: std::env::var("FFMPEG_BINARY").unwrap_or_else(|_| "ffmpeg".to_string()),
+                    model: std::env::var("WHISPER_MODEL").unwrap_or_default().into(),
+                }
+            }
+            #[cfg(feature = "transcription")]
+            "openai" => TranscriberProvider::OpenAi {
+                base_url: std::env::var("TRANSCRIBER_URL")
+                    .unwrap_or_else(|_| "https://api.openai.com/v1".to_string()),
+                model: std::env::var("TRANSCRIBER_MODEL")
+                    .unwrap_or_else(|_| "whisper-1".to_string()),
+                api_key: std::env::var("TRANSCRIBER_API_KEY").ok(),
+                timeout: Duration::from_secs(
+                    std::env::var("TRANSCRIBER_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(300),
+                ),
+            },
+            _ => TranscriberProvider::None,
         };
 
         Self {
             broker_url: std::env::var("BROKER_URL").unwrap_or("nats://localhost:4222".to_string()),
             database_url: std::env::var("DATABASE_URL").unwrap_or("sqlite::memory:".to_string()),
+            auto_archive_interval,
+            trash_purge_interval,
+            trash_retention,
+            lint_interval,
+            link_check_provider,
+            tag_cleanup_interval,
+            unused_tag_policy,
+            link_preview_interval,
+            link_preview_provider,
+            auto_title_interval,
+            text_generator_provider,
+            transcription_interval,
+            transcriber_provider,
+            cache_provider,
             #[cfg(feature = "smart-features")]
             embedding_provider,
             #[cfg(feature = "smart-features")]
             vector_provider,
+            #[cfg(feature = "smart-features")]
+            embedding_batch_size,
+            #[cfg(feature = "smart-features")]
+            note_debounce,
         }
     }
 }
@@ -0,0 +1,159 @@
+//! Coalescing of rapid note updates
+//!
+//! Autosaving editors publish an update every few seconds while the user
+//! types. The first update of a note opens a window; later updates within it
+//! replace the pending version, and only the latest one is emitted when the
+//! window closes. Windows are not extended by further updates, so a note
+//! that is edited continuously is still processed once per window.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use futures_util::{FutureExt, Stream, StreamExt, stream};
+use notes_domain::Note;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+struct Debouncer<S> {
+    updates: S,
+    window: Duration,
+    max_batch: usize,
+    pending: HashMap<Uuid, Note>,
+    /// Window deadlines in the order the windows opened
+    deadlines: VecDeque<(Instant, Uuid)>,
+    closed: bool,
+}
+
+impl<S: Stream<Item = Note> + Unpin> Debouncer<S> {
+    fn push(&mut self, note: Note) {
+        let id = note.id;
+        if self.pending.insert(id, note).is_none() {
+            self.deadlines.push_back((Instant::now() + self.window, id));
+        }
+    }
+
+    /// Take updates that already arrived without waiting, so that with no
+    /// window the queued ones are still batched together
+    fn drain_ready(&mut self) {
+        for _ in 0..self.max_batch {
+            match self.updates.next().now_or_never() {
+                Some(Some(note)) => self.push(note),
+                Some(None) => {
+                    self.closed = true;
+                    break;
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Pending notes whose window closed at `now`, oldest window first
+    fn take_due(&mut self, now: Instant) -> Vec<Note> {
+        let mut batch = Vec::new();
+        while batch.len() < self.max_batch {
+            match self.deadlines.front() {
+                Some(&(deadline, id)) if deadline <= now => {
+                    self.deadlines.pop_front();
+                    batch.extend(self.pending.remove(&id));
+                }
+                _ => break,
+            }
+        }
+        batch
+    }
+
+    async fn next_batch(&mut self) -> Option<Vec<Note>> {
+        loop {
+            if !self.closed {
+                self.drain_ready();
+            }
+
+            // Once the subscription ends there is nothing left to wait for
+            let now = if self.closed {
+                Instant::now() + self.window
+            } else {
+                Instant::now()
+            };
+            let batch = self.take_due(now);
+            if !batch.is_empty() {
+                return Some(batch);
+            }
+            if self.closed {
+                return None;
+            }
+
+            let next_deadline = self.deadlines.front().map(|&(deadline, _)| deadline);
+            tokio::select! {
+                update = self.updates.next() => match update {
+                    Some(note) => self.push(note),
+                    None => self.closed = true,
+                },
+                _ = async {
+                    match next_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline).await,
+                        None => std::future::pending().await,
+                    }
+                } => {}
+            }
+        }
+    }
+}
+
+/// Coalesce `updates` per note over `window`, emitting batches of at most
+/// `max_batch` notes whose window has closed
+pub fn debounce<S>(updates: S, window: Duration, max_batch: usize) -> impl Stream<Item = Vec<Note>>
+where
+    S: Stream<Item = Note> + Unpin,
+{
+    let debouncer = Debouncer {
+        updates,
+        window,
+        max_batch: max_batch.max(1),
+        pending: HashMap::new(),
+        deadlines: VecDeque::new(),
+        closed: false,
+    };
+    stream::unfold(debouncer, |mut debouncer| async move {
+        let batch = debouncer.next_batch().await?;
+        Some((batch, debouncer))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_emits_latest_version_once_per_window() {
+        let user_id = Uuid::new_v4();
+        let mut note = Note::new(user_id, None, "draft 1");
+        let other = Note::new(user_id, None, "other");
+
+        let mut updates = vec![note.clone(), other.clone()];
+        for draft in ["draft 2", "draft 3"] {
+            note.content = draft.to_string();
+            updates.push(note.clone());
+        }
+        // Keep the subscription open so only the window can release notes
+        let updates = stream::iter(updates).chain(stream::pending());
+
+        let started = Instant::now();
+        let mut batches = Box::pin(debounce(updates, Duration::from_millis(50), 10));
+        let batch = batches.next().await.unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].content, "draft 3");
+        assert_eq!(batch[1].id, other.id);
+    }
+
+    #[tokio::test]
+    async fn test_flushes_pending_notes_when_updates_end() {
+        let note = Note::new(Uuid::new_v4(), None, "content");
+        let batches: Vec<Vec<Note>> = debounce(stream::iter([note]), Duration::from_secs(3600), 10)
+            .collect()
+            .await;
+
+        assert_eq!(batches.len(), 1);
+    }
+}
@@ -0,0 +1,50 @@
+//! Scheduled note lint job
+//!
+//! Periodically checks every user's notes for dangling wiki-links and, when
+//! link checking is enabled, for external links that no longer load.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use notes_domain::{DomainResult, InstanceSettingsRepository, NoteLintService, UserRepository};
+
+/// Run the job every `interval` until the process exits
+pub async fn run(
+    lint_service: Arc<NoteLintService>,
+    user_repo: Arc<dyn UserRepository>,
+    instance_settings: Arc<dyn InstanceSettingsRepository>,
+    interval: Duration,
+) {
+    tracing::info!("Note lint job scheduled every {:?}", interval);
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if instance_settings.is_read_only().await.unwrap_or(false) {
+            tracing::info!("Read-only maintenance mode enabled, skipping note lint run");
+            continue;
+        }
+
+        if let Err(e) = run_once(&lint_service, user_repo.as_ref()).await {
+            tracing::error!("Note lint run failed: {}", e);
+        }
+    }
+}
+
+async fn run_once(
+    lint_service: &NoteLintService,
+    user_repo: &dyn UserRepository,
+) -> DomainResult<()> {
+    let user_ids = user_repo.find_all_ids().await?;
+    let results = crate::for_each_user(user_ids, "Note lint", |user_id| {
+        lint_service.lint_user(user_id)
+    })
+    .await;
+
+    for (user_id, count) in results.into_iter().filter(|(_, count)| *count > 0) {
+        tracing::info!(%user_id, "Found {} issues in notes", count);
+    }
+
+    Ok(())
+}
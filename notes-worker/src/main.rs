@@ -1,15 +1,154 @@
+use std::sync::Arc;
+
+#[cfg(feature = "smart-features")]
 use futures_util::StreamExt;
+use k_core::db::DatabaseConfig;
+#[cfg(feature = "smart-features")]
+use notes_domain::Note;
+#[cfg(feature = "smart-features")]
+use notes_domain::events::{DomainEvent, DomainEventKind};
 #[cfg(feature = "smart-features")]
 use notes_domain::services::SmartNoteService;
+use notes_domain::{
+    EventDispatcher, LinkPreviewService, NoteLintService, NoteService, TagService,
+    TranscriptionService,
+};
 #[cfg(feature = "smart-features")]
 use notes_infra::factory::{
     BrokerProvider, build_embedding_generator, build_link_repository, build_message_broker,
     build_vector_store,
 };
+use notes_infra::factory::{
+    CacheableRepositories, build_audio_upload_repository, build_cache, build_event_log_repository,
+    build_instance_settings_repository, build_link_preview_fetcher, build_note_issue_repository,
+    build_note_repository, build_tag_repository, build_text_generator, build_transcriber,
+    build_unit_of_work, build_url_checker, build_user_repository,
+};
 
 use crate::config::Config;
 
+mod auto_archive;
+mod auto_title;
+mod bookmarks;
 mod config;
+#[cfg(feature = "smart-features")]
+mod debounce;
+mod lint;
+mod tag_cleanup;
+mod transcription;
+mod trash_purge;
+
+/// How often the maintenance flag is checked while paused
+#[cfg(feature = "smart-features")]
+const MAINTENANCE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Block while the instance is in read-only maintenance mode.
+/// Pending events stay queued in the subscription until consumption resumes.
+#[cfg(feature = "smart-features")]
+async fn wait_while_read_only(settings: &dyn notes_domain::InstanceSettingsRepository) {
+    let mut paused = false;
+    loop {
+        match settings.is_read_only().await {
+            Ok(false) => break,
+            Ok(true) => {
+                if !paused {
+                    tracing::warn!("Read-only maintenance mode enabled, pausing consumption");
+                    paused = true;
+                }
+            }
+            // Keep going rather than stalling on a flaky settings lookup
+            Err(e) => {
+                tracing::warn!("Failed to check maintenance mode: {}", e);
+                break;
+            }
+        }
+        tokio::time::sleep(MAINTENANCE_POLL_INTERVAL).await;
+    }
+
+    if paused {
+        tracing::info!("Maintenance mode disabled, resuming consumption");
+    }
+}
+
+/// Clean up after events that do not need an embedding.
+/// Note updates are embedded in batches instead and never reach this.
+#[cfg(feature = "smart-features")]
+async fn handle_event(smart_service: &SmartNoteService, event: DomainEvent) {
+    let result = match event.kind {
+        DomainEventKind::NoteUpdated(_) => Ok(()),
+        // Links of the note were removed together with it
+        DomainEventKind::NoteDeleted { note_id } => smart_service.forget_note(note_id).await,
+        // Embeddings and links only depend on note content
+        DomainEventKind::TagUpdated(tag) => {
+            tracing::debug!(tag_id = %tag.id, "Tag updated, nothing to invalidate");
+            Ok(())
+        }
+        DomainEventKind::UserDeleted => smart_service.forget_user(event.user_id).await,
+    };
+
+    if let Err(e) = result {
+        tracing::error!(event_id = %event.id, "Failed to handle {} event: {}", event.subject(), e);
+    }
+}
+
+/// Whether administrators left smart features on for the whole instance
+#[cfg(feature = "smart-features")]
+async fn instance_smart_features(settings: &dyn notes_domain::InstanceSettingsRepository) -> bool {
+    match settings.settings_overrides().await {
+        Ok(overrides) => overrides.smart_features_enabled.unwrap_or(true),
+        // Like per-user settings, a failed lookup should not drop the batch
+        Err(e) => {
+            tracing::warn!("Failed to load instance settings: {}", e);
+            true
+        }
+    }
+}
+
+/// Drop notes whose owners turned smart features off
+#[cfg(feature = "smart-features")]
+async fn with_smart_features(
+    user_repo: &dyn notes_domain::UserRepository,
+    mut notes: Vec<Note>,
+) -> Vec<Note> {
+    let mut enabled = std::collections::HashMap::new();
+    for note in &notes {
+        if !enabled.contains_key(&note.user_id) {
+            let settings = match user_repo.find_settings(note.user_id).await {
+                Ok(settings) => settings.smart_features_enabled,
+                // Processing a note is safer than silently dropping it
+                Err(e) => {
+                    tracing::warn!(user_id = %note.user_id, "Failed to load settings: {}", e);
+                    true
+                }
+            };
+            enabled.insert(note.user_id, settings);
+        }
+    }
+
+    notes.retain(|note| enabled[&note.user_id]);
+    notes
+}
+
+/// Run a job's step for each user, returning the results of those it
+/// succeeded for. Failures are logged as `"<job> failed"`; one failing user
+/// must not block the others.
+async fn for_each_user<T, Fut>(
+    user_ids: Vec<uuid::Uuid>,
+    job: &str,
+    mut step: impl FnMut(uuid::Uuid) -> Fut,
+) -> Vec<(uuid::Uuid, T)>
+where
+    Fut: std::future::Future<Output = notes_domain::DomainResult<T>>,
+{
+    let mut results = Vec::with_capacity(user_ids.len());
+    for user_id in user_ids {
+        match step(user_id).await {
+            Ok(result) => results.push((user_id, result)),
+            Err(e) => tracing::warn!(%user_id, "{} failed: {}", job, e),
+        }
+    }
+    results
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -17,25 +156,28 @@ async fn main() -> anyhow::Result<()> {
 
     let config = Config::from_env();
 
-    #[cfg(feature = "smart-features")]
-    {
-        // Connect to message broker via factory
-
-        use k_core::db::DatabaseConfig;
-        tracing::info!("Connecting to message broker: {}", config.broker_url);
-        let broker_provider = BrokerProvider::Nats {
-            url: config.broker_url.clone(),
-        };
-        let broker = build_message_broker(&broker_provider)
-            .await?
-            .expect("Message broker required for worker");
-
-        let db_config = DatabaseConfig::new(config.database_url.clone());
-        let db_pool = k_core::db::connect(&db_config).await?;
+    let db_config = DatabaseConfig::new(config.database_url.clone());
+    let db_pool = notes_infra::db::connect(&db_config).await?;
+    let instance_settings = build_instance_settings_repository(&db_pool).await?;
 
+    #[cfg(feature = "smart-features")]
+    let smart_service = {
         // Initialize smart feature adapters
         let embedding_generator = build_embedding_generator(&config.embedding_provider).await?;
         let vector_store = build_vector_store(&config.vector_provider).await?;
+
+        // Fail now rather than on the first upsert if the model and the
+        // collection disagree on the vector size
+        let probe = embedding_generator
+            .generate_embedding("dimension probe")
+            .await?;
+        anyhow::ensure!(
+            probe.len() as u64 == config.vector_provider.dimension(),
+            "Embedding model {} produces {}-dimensional vectors, but the vector store expects {}",
+            embedding_generator.model_name(),
+            probe.len(),
+            config.vector_provider.dimension()
+        );
         let link_repo = build_link_repository(&db_pool).await?;
 
         // Create the service
@@ -44,23 +186,195 @@ async fn main() -> anyhow::Result<()> {
             "SmartNoteService initialized successfully with {:?}",
             config.embedding_provider
         );
+        Arc::new(smart_service)
+    };
+
+    let user_repo = build_user_repository(&db_pool).await?;
+    let repos = CacheableRepositories {
+        note_repo: build_note_repository(&db_pool).await?,
+        tag_repo: build_tag_repository(&db_pool).await?,
+        unit_of_work: build_unit_of_work(&db_pool).await?,
+    }
+    .with_cache(build_cache(&config.cache_provider).await?);
+    // Scheduled jobs archive and purge notes on the owners' behalf
+    let events = Arc::new(
+        EventDispatcher::new().with_event_log(build_event_log_repository(&db_pool).await?),
+    );
+    let mut lint_service = NoteLintService::new(
+        repos.note_repo.clone(),
+        build_note_issue_repository(&db_pool).await?,
+    );
+    if let Some(url_checker) = build_url_checker(&config.link_check_provider).await? {
+        lint_service = lint_service.with_url_checker(url_checker);
+    }
+    let preview_service = build_link_preview_fetcher(&config.link_preview_provider)
+        .await?
+        .map(|fetcher| Arc::new(LinkPreviewService::new(repos.note_repo.clone(), fetcher)));
+    let tag_service =
+        Arc::new(TagService::new(repos.tag_repo.clone()).with_event_dispatcher(events.clone()));
+    let mut note_service = NoteService::new(repos.note_repo, repos.tag_repo)
+        .with_user_repository(user_repo.clone())
+        .with_unit_of_work(repos.unit_of_work)
+        .with_event_dispatcher(events);
+    if let Some(text_generator) = build_text_generator(&config.text_generator_provider).await? {
+        note_service = note_service.with_text_generator(text_generator);
+    }
+    let note_service = Arc::new(note_service);
+
+    // Scheduled jobs
+    let mut jobs = Vec::new();
+    if let Some(interval) = config.auto_archive_interval {
+        jobs.push(tokio::spawn(auto_archive::run(
+            note_service.clone(),
+            user_repo.clone(),
+            instance_settings.clone(),
+            interval,
+        )));
+    }
+    if let Some(interval) = config.auto_title_interval {
+        jobs.push(tokio::spawn(auto_title::run(
+            note_service.clone(),
+            user_repo.clone(),
+            instance_settings.clone(),
+            interval,
+        )));
+    }
+    if let Some(interval) = config.trash_purge_interval {
+        jobs.push(tokio::spawn(trash_purge::run(
+            note_service.clone(),
+            #[cfg(feature = "smart-features")]
+            smart_service.clone(),
+            instance_settings.clone(),
+            config.trash_retention,
+            interval,
+        )));
+    }
+    if let (Some(interval), Some(preview_service)) = (config.link_preview_interval, preview_service)
+    {
+        jobs.push(tokio::spawn(bookmarks::run(
+            preview_service,
+            instance_settings.clone(),
+            interval,
+        )));
+    }
+    if let Some(interval) = config.tag_cleanup_interval {
+        jobs.push(tokio::spawn(tag_cleanup::run(
+            tag_service,
+            user_repo.clone(),
+            instance_settings.clone(),
+            config.unused_tag_policy,
+            interval,
+        )));
+    }
+    if let Some(transcriber) = build_transcriber(&config.transcriber_provider).await? {
+        let transcription_service = TranscriptionService::new(
+            note_service.clone(),
+            build_audio_upload_repository(&db_pool).await?,
+        )
+        .with_transcriber(transcriber);
+        jobs.push(tokio::spawn(transcription::run(
+            Arc::new(transcription_service),
+            #[cfg(feature = "smart-features")]
+            smart_service.clone(),
+            #[cfg(feature = "smart-features")]
+            user_repo.clone(),
+            instance_settings.clone(),
+            config.transcription_interval,
+        )));
+    }
+    if let Some(interval) = config.lint_interval {
+        jobs.push(tokio::spawn(lint::run(
+            Arc::new(lint_service),
+            user_repo.clone(),
+            instance_settings.clone(),
+            interval,
+        )));
+    }
 
-        // Subscribe to note update events via the broker's stream API
-        let mut note_stream = broker.subscribe_note_updates().await?;
-        tracing::info!("Worker listening on 'notes.updated'...");
+    #[cfg(feature = "smart-features")]
+    {
+        // Connect to message broker via factory
+        tracing::info!("Connecting to message broker: {}", config.broker_url);
+        let broker_provider = BrokerProvider::Nats {
+            url: config.broker_url.clone(),
+        };
+        let broker = build_message_broker(&broker_provider)
+            .await?
+            .expect("Message broker required for worker");
 
-        while let Some(note) = note_stream.next().await {
-            tracing::info!("Processing smart features for note: {}", note.id);
-            match smart_service.process_note(&note).await {
-                Ok(_) => tracing::info!("Successfully processed note {}", note.id),
-                Err(e) => tracing::error!("Failed to process note {}: {}", note.id, e),
+        // Subscribe to domain events via the broker's stream API. Deletions
+        // are handled as they arrive; rapid edits of a note are coalesced
+        // into its latest version, and notes due together are embedded in
+        // one model call.
+        let event_service = smart_service.clone();
+        let note_updates = broker.subscribe().await?.filter_map(move |event| {
+            let smart_service = event_service.clone();
+            async move {
+                match event.kind {
+                    DomainEventKind::NoteUpdated(note) => Some(note),
+                    _ => {
+                        handle_event(&smart_service, event).await;
+                        None
+                    }
+                }
             }
+        });
+        let note_stream = debounce::debounce(
+            Box::pin(note_updates),
+            config.note_debounce,
+            config.embedding_batch_size,
+        );
+        tracing::info!("Worker listening on {:?}...", DomainEvent::SUBJECTS);
+
+        // Batches embed concurrently, one per loaded model instance
+        let concurrency = config.embedding_provider.concurrency();
+        note_stream
+            .for_each_concurrent(concurrency, |notes| {
+                let smart_service = smart_service.clone();
+                let instance_settings = instance_settings.clone();
+                let user_repo = user_repo.clone();
+                async move {
+                    wait_while_read_only(instance_settings.as_ref()).await;
+
+                    if !instance_smart_features(instance_settings.as_ref()).await {
+                        tracing::debug!(
+                            "Smart features are off for the instance, skipping {} notes",
+                            notes.len()
+                        );
+                        return;
+                    }
+                    let notes = with_smart_features(user_repo.as_ref(), notes).await;
+                    if notes.is_empty() {
+                        return;
+                    }
+
+                    tracing::info!("Processing smart features for {} notes", notes.len());
+                    match smart_service.process_notes(&notes).await {
+                        Ok(_) => tracing::info!("Successfully processed {} notes", notes.len()),
+                        Err(e) => {
+                            tracing::error!("Failed to process {} notes: {}", notes.len(), e)
+                        }
+                    }
+                }
+            })
+            .await;
+
+        // The subscription closed; stop scheduled jobs and exit
+        for job in jobs {
+            job.abort();
         }
     }
 
     #[cfg(not(feature = "smart-features"))]
     {
-        tracing::info!("Smart features are disabled. Worker will exit.");
+        if jobs.is_empty() {
+            tracing::info!("Smart features are disabled. Worker will exit.");
+        } else {
+            tracing::info!("Smart features are disabled. Worker will only run scheduled jobs.");
+            for job in jobs {
+                job.await?;
+            }
+        }
     }
 
     Ok(())
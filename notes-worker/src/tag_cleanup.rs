@@ -0,0 +1,68 @@
+//! Scheduled unused tag cleanup job
+//!
+//! Periodically applies the unused tag policy to every user's tags: flagged
+//! tags are only logged, deleted ones are recorded in the owner's activity
+//! feed.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use notes_domain::{
+    DomainResult, InstanceSettingsRepository, TagService, UserRepository,
+    tag_cleanup::UnusedTagPolicy,
+};
+
+/// Run the job every `interval` until the process exits
+pub async fn run(
+    tag_service: Arc<TagService>,
+    user_repo: Arc<dyn UserRepository>,
+    instance_settings: Arc<dyn InstanceSettingsRepository>,
+    policy: UnusedTagPolicy,
+    interval: Duration,
+) {
+    tracing::info!(
+        "Tag cleanup job scheduled every {:?} (policy: {})",
+        interval,
+        policy
+    );
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if instance_settings.is_read_only().await.unwrap_or(false) {
+            tracing::info!("Read-only maintenance mode enabled, skipping tag cleanup");
+            continue;
+        }
+
+        if let Err(e) = run_once(&tag_service, user_repo.as_ref(), policy).await {
+            tracing::error!("Tag cleanup failed: {}", e);
+        }
+    }
+}
+
+async fn run_once(
+    tag_service: &TagService,
+    user_repo: &dyn UserRepository,
+    policy: UnusedTagPolicy,
+) -> DomainResult<()> {
+    let user_ids = user_repo.find_all_ids().await?;
+    let results = crate::for_each_user(user_ids, "Tag cleanup", |user_id| {
+        tag_service.clean_up_unused_tags(user_id, policy)
+    })
+    .await;
+
+    for (user_id, tags) in results {
+        if tags.is_empty() {
+            continue;
+        }
+        match policy {
+            UnusedTagPolicy::Flag => tracing::info!(%user_id, "Found {} unused tags", tags.len()),
+            UnusedTagPolicy::Delete => {
+                tracing::info!(%user_id, "Deleted {} unused tags", tags.len())
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Scheduled voice memo transcription job
+//!
+//! Transcribes audio uploaded to notes and appends the transcripts to them.
+//! The worker's own edits never reach the broker it listens on, so the
+//! changed notes are embedded here.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "smart-features")]
+use notes_domain::services::SmartNoteService;
+#[cfg(feature = "smart-features")]
+use notes_domain::{DomainResult, Note, UserRepository};
+use notes_domain::{InstanceSettingsRepository, TranscriptionService};
+
+/// Uploads transcribed per run; each one can take a while
+const BATCH_SIZE: usize = 5;
+
+/// Run the job every `interval` until the process exits
+pub async fn run(
+    transcription_service: Arc<TranscriptionService>,
+    #[cfg(feature = "smart-features")] smart_service: Arc<SmartNoteService>,
+    #[cfg(feature = "smart-features")] user_repo: Arc<dyn UserRepository>,
+    instance_settings: Arc<dyn InstanceSettingsRepository>,
+    interval: Duration,
+) {
+    tracing::info!("Transcription job scheduled every {:?}", interval);
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if instance_settings.is_read_only().await.unwrap_or(false) {
+            tracing::info!("Read-only maintenance mode enabled, skipping transcription");
+            continue;
+        }
+
+        let notes = match transcription_service.transcribe_pending(BATCH_SIZE).await {
+            Ok(notes) => notes,
+            Err(e) => {
+                tracing::error!("Transcription run failed: {}", e);
+                continue;
+            }
+        };
+        if notes.is_empty() {
+            continue;
+        }
+        tracing::info!("Transcribed voice memos into {} notes", notes.len());
+
+        #[cfg(feature = "smart-features")]
+        if let Err(e) = index(
+            &smart_service,
+            user_repo.as_ref(),
+            instance_settings.as_ref(),
+            notes,
+        )
+        .await
+        {
+            tracing::error!("Failed to index transcribed notes: {}", e);
+        }
+    }
+}
+
+/// Embed the transcribed notes of users with smart features on
+#[cfg(feature = "smart-features")]
+async fn index(
+    smart_service: &SmartNoteService,
+    user_repo: &dyn UserRepository,
+    instance_settings: &dyn InstanceSettingsRepository,
+    notes: Vec<Note>,
+) -> DomainResult<()> {
+    if !crate::instance_smart_features(instance_settings).await {
+        return Ok(());
+    }
+    let notes = crate::with_smart_features(user_repo, notes).await;
+    if notes.is_empty() {
+        return Ok(());
+    }
+    smart_service.process_notes(&notes).await
+}
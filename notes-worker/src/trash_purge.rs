@@ -0,0 +1,79 @@
+//! Scheduled trash purge job
+//!
+//! Permanently deletes notes that have been in the trash for longer than the
+//! retention window and records the reclaimed storage.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+#[cfg(feature = "smart-features")]
+use notes_domain::services::SmartNoteService;
+use notes_domain::{DomainResult, InstanceSettingsRepository, NoteService, trash::TrashRetention};
+
+/// Run the job every `interval` until the process exits
+pub async fn run(
+    note_service: Arc<NoteService>,
+    #[cfg(feature = "smart-features")] smart_service: Arc<SmartNoteService>,
+    instance_settings: Arc<dyn InstanceSettingsRepository>,
+    retention: TrashRetention,
+    interval: Duration,
+) {
+    tracing::info!(
+        "Trash purge job scheduled every {:?} (retention: {} days)",
+        interval,
+        retention.days
+    );
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        if instance_settings.is_read_only().await.unwrap_or(false) {
+            tracing::info!("Read-only maintenance mode enabled, skipping trash purge");
+            continue;
+        }
+
+        let result = run_once(
+            &note_service,
+            #[cfg(feature = "smart-features")]
+            &smart_service,
+            instance_settings.as_ref(),
+            retention,
+        )
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!("Trash purge failed: {}", e);
+        }
+    }
+}
+
+async fn run_once(
+    note_service: &NoteService,
+    #[cfg(feature = "smart-features")] smart_service: &SmartNoteService,
+    instance_settings: &dyn InstanceSettingsRepository,
+    retention: TrashRetention,
+) -> DomainResult<()> {
+    let report = note_service
+        .purge_trash(retention.cutoff(Utc::now()))
+        .await?;
+    if report.is_empty() {
+        return Ok(());
+    }
+
+    // The notes are gone from the database already; stale vectors only waste space
+    #[cfg(feature = "smart-features")]
+    for note_id in &report.note_ids {
+        if let Err(e) = smart_service.forget_note(*note_id).await {
+            tracing::warn!(%note_id, "Failed to drop embedding of purged note: {}", e);
+        }
+    }
+
+    tracing::info!(
+        "Purged {} notes from the trash, reclaiming {} bytes",
+        report.note_ids.len(),
+        report.reclaimed_bytes
+    );
+    instance_settings.record_trash_purge(&report).await
+}